@@ -0,0 +1,193 @@
+//! Seeded fault injection for the e2e harness.
+//!
+//! Every simulated actor draws its faults from its own random number generator,
+//! derived from the seed and the name of the actor, so that a seed always yields
+//! the same sequence of faults for each actor regardless of thread scheduling.
+
+use phase1_coordinator::storage::ContributionLocator;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// The probability of each fault occurring at every opportunity for it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Faults {
+    /// The seed all fault decisions are derived from.
+    pub seed: u64,
+    /// A contributor loses the upload of its response and has to redo it.
+    pub drop_upload: f64,
+    /// A contributor uploads a response which fails verification.
+    pub corrupt_response: f64,
+    /// The verifier crashes while verifying a contribution, before reporting the outcome.
+    pub crash_verifier: f64,
+    /// The coordinator restarts from storage, checked on every coordinator update.
+    pub restart_coordinator: f64,
+}
+
+impl Faults {
+    /// Returns a configuration which never injects faults.
+    pub fn none() -> Self {
+        Self {
+            seed: 0,
+            drop_upload: 0.0,
+            corrupt_response: 0.0,
+            crash_verifier: 0.0,
+            restart_coordinator: 0.0,
+        }
+    }
+
+    /// Returns the fault generator of the actor with the given name.
+    pub(crate) fn generator(&self, actor: &str) -> FaultGenerator {
+        // FNV-1a, which unlike the standard hasher is stable across toolchains
+        let actor_hash = actor.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        FaultGenerator {
+            faults: *self,
+            rng: StdRng::seed_from_u64(self.seed ^ actor_hash),
+        }
+    }
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// The fault decisions of a single actor.
+pub(crate) struct FaultGenerator {
+    faults: Faults,
+    rng: StdRng,
+}
+
+impl FaultGenerator {
+    pub(crate) fn drop_upload(&mut self) -> bool {
+        self.rng.gen_bool(self.faults.drop_upload)
+    }
+
+    pub(crate) fn corrupt_response(&mut self) -> bool {
+        self.rng.gen_bool(self.faults.corrupt_response)
+    }
+
+    pub(crate) fn crash_verifier(&mut self) -> bool {
+        self.rng.gen_bool(self.faults.crash_verifier)
+    }
+
+    pub(crate) fn restart_coordinator(&mut self) -> bool {
+        self.rng.gen_bool(self.faults.restart_coordinator)
+    }
+}
+
+/// The faults which were injected during a harness run.
+#[derive(Debug, Default)]
+pub struct FaultLog {
+    dropped_uploads: AtomicUsize,
+    corrupted_responses: AtomicUsize,
+    verifier_crashes: AtomicUsize,
+    coordinator_restarts: AtomicUsize,
+    /// The corrupted responses which failed verification.
+    rejected_responses: AtomicUsize,
+    /// The responses which were corrupted on purpose, and are expected to fail verification.
+    corrupted: Mutex<HashSet<ContributionLocator>>,
+}
+
+impl FaultLog {
+    pub fn dropped_uploads(&self) -> usize {
+        self.dropped_uploads.load(Ordering::SeqCst)
+    }
+
+    pub fn corrupted_responses(&self) -> usize {
+        self.corrupted_responses.load(Ordering::SeqCst)
+    }
+
+    pub fn verifier_crashes(&self) -> usize {
+        self.verifier_crashes.load(Ordering::SeqCst)
+    }
+
+    pub fn coordinator_restarts(&self) -> usize {
+        self.coordinator_restarts.load(Ordering::SeqCst)
+    }
+
+    pub fn rejected_responses(&self) -> usize {
+        self.rejected_responses.load(Ordering::SeqCst)
+    }
+
+    /// Returns the total number of injected faults.
+    pub fn total(&self) -> usize {
+        self.dropped_uploads() + self.corrupted_responses() + self.verifier_crashes() + self.coordinator_restarts()
+    }
+
+    pub(crate) fn record_dropped_upload(&self) {
+        self.dropped_uploads.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_corrupted_response(&self, response: ContributionLocator) {
+        self.corrupted_responses.fetch_add(1, Ordering::SeqCst);
+        self.corrupted.lock().unwrap().insert(response);
+    }
+
+    pub(crate) fn record_rejected_response(&self) {
+        self.rejected_responses.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_verifier_crash(&self) {
+        self.verifier_crashes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn record_coordinator_restart(&self) {
+        self.coordinator_restarts.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the given response was corrupted on purpose.
+    pub(crate) fn is_corrupted(&self, response: &ContributionLocator) -> bool {
+        self.corrupted.lock().unwrap().contains(response)
+    }
+}
+
+impl fmt::Display for FaultLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} dropped uploads, {} corrupted responses ({} rejected), {} verifier crashes, {} coordinator restarts",
+            self.dropped_uploads(),
+            self.corrupted_responses(),
+            self.rejected_responses(),
+            self.verifier_crashes(),
+            self.coordinator_restarts()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let faults = Faults {
+            seed: 42,
+            drop_upload: 0.5,
+            ..Faults::none()
+        };
+        let draw = |actor| {
+            let mut generator = faults.generator(actor);
+            (0..64).map(|_| generator.drop_upload()).collect::<Vec<_>>()
+        };
+        assert_eq!(draw("contributor-1"), draw("contributor-1"));
+        assert_ne!(draw("contributor-1"), draw("contributor-2"));
+    }
+
+    #[test]
+    fn test_no_faults() {
+        let mut generator = Faults::none().generator("contributor-1");
+        assert!((0..64).all(|_| !generator.drop_upload() && !generator.restart_coordinator()));
+    }
+}
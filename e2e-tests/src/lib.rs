@@ -0,0 +1,477 @@
+//! An in-process harness which runs a small ceremony end-to-end.
+//!
+//! The [Harness] launches a coordinator, a number of simulated contributors
+//! and a verifier worker on their own threads, drives the coordinator until
+//! the round is aggregated, and checks that the resulting transcript is valid.
+//!
+//! Faults can be injected into a run with [Faults], see the [faults] module.
+//!
+//! The coordinator only supports disk storage, so every harness keeps its
+//! transcript in a fresh temporary directory which is removed when it is dropped.
+
+pub mod faults;
+pub use faults::{FaultLog, Faults};
+
+use faults::FaultGenerator;
+use phase1::{helpers::CurveKind, ContributionMode, Phase1, Phase1Parameters, ProvingSystem};
+use phase1_coordinator::{
+    authentication::Dummy,
+    environment::{Environment, Parameters, Settings, Testing},
+    objects::Task,
+    storage::{ContributionLocator, Locator},
+    Coordinator,
+    CoordinatorError,
+    Participant,
+    Seed,
+    SigningKey,
+    SEED_LENGTH,
+};
+use setup_utils::{CheckForCorrectness, UseCompression};
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
+
+use anyhow::{anyhow, bail};
+use rand::RngCore;
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+        RwLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+
+/// How long the participants and the coordinator wait between polls.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Distinguishes the storage directories of harnesses within the same process.
+static HARNESS_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The configuration of a harness run.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The number of contributors taking part in the round.
+    pub contributors: usize,
+    /// The ceremony parameters, which should be kept tiny.
+    pub settings: Settings,
+    /// The time after which the round is considered stuck.
+    pub timeout: Duration,
+    /// The faults to inject while running a round.
+    pub faults: Faults,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            contributors: 3,
+            settings: Settings::new(
+                ContributionMode::Chunked,
+                ProvingSystem::Groth16,
+                CurveKind::Bls12_377,
+                7,  /* power */
+                32, /* batch_size */
+                32, /* chunk_size */
+            ),
+            timeout: Duration::from_secs(600),
+            faults: Faults::none(),
+        }
+    }
+}
+
+/// A simulated contributor client.
+#[derive(Debug, Clone)]
+pub struct Contributor {
+    pub participant: Participant,
+    pub signing_key: SigningKey,
+    pub seed: Seed,
+}
+
+impl Contributor {
+    fn new(participant: Participant) -> Self {
+        let mut seed: Seed = [0; SEED_LENGTH];
+        rand::thread_rng().fill_bytes(&mut seed[..]);
+
+        Self {
+            participant,
+            signing_key: "secret_key".to_string(),
+            seed,
+        }
+    }
+}
+
+/// A simulated verifier worker.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    pub participant: Participant,
+    pub signing_key: SigningKey,
+}
+
+/// A coordinator together with the simulated participants of a ceremony.
+pub struct Harness {
+    coordinator: Arc<RwLock<Coordinator>>,
+    contributors: Vec<Contributor>,
+    /// The contributors managed by the coordinator, which replace dropped contributors.
+    coordinator_contributors: Vec<Contributor>,
+    verifier: Verifier,
+    options: Options,
+    fault_log: Arc<FaultLog>,
+    base_directory: PathBuf,
+}
+
+impl Harness {
+    /// Starts a coordinator with fresh storage and initializes the ceremony to round 0.
+    pub fn new(options: Options) -> anyhow::Result<Self> {
+        let base_directory = std::env::temp_dir().join(format!(
+            "e2e-tests-{}-{}",
+            std::process::id(),
+            HARNESS_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        if base_directory.exists() {
+            fs::remove_dir_all(&base_directory)?;
+        }
+        let local_base_directory = base_directory
+            .to_str()
+            .ok_or_else(|| anyhow!("{:?} is not valid UTF-8", base_directory))?;
+
+        let environment: Environment = Testing::from(Parameters::Custom(options.settings.clone()))
+            .maximum_contributors_per_round(options.contributors)
+            .local_base_directory(local_base_directory)
+            .into();
+
+        let coordinator_contributors = environment
+            .coordinator_contributors()
+            .iter()
+            .cloned()
+            .map(Contributor::new)
+            .collect();
+        let coordinator = start_coordinator(environment)?;
+
+        Ok(Self {
+            coordinator: Arc::new(RwLock::new(coordinator)),
+            contributors: (0..options.contributors)
+                .map(|id| Contributor::new(Participant::new_contributor(&format!("e2e-contributor-{}", id))))
+                .collect(),
+            coordinator_contributors,
+            verifier: Verifier {
+                participant: Participant::new_verifier("e2e-verifier"),
+                signing_key: "secret_key".to_string(),
+            },
+            options,
+            fault_log: Arc::new(FaultLog::default()),
+            base_directory,
+        })
+    }
+
+    /// Returns the coordinator shared by all participants.
+    pub fn coordinator(&self) -> Arc<RwLock<Coordinator>> {
+        self.coordinator.clone()
+    }
+
+    /// Returns the simulated contributors.
+    pub fn contributors(&self) -> &[Contributor] {
+        &self.contributors
+    }
+
+    /// Returns the faults injected so far.
+    pub fn fault_log(&self) -> &FaultLog {
+        &self.fault_log
+    }
+
+    /// Queues every contributor, runs the next round to completion and
+    /// returns the height of the aggregated round.
+    pub fn run_round(&self) -> anyhow::Result<u64> {
+        let round_height = {
+            let mut coordinator = write(&self.coordinator)?;
+            for contributor in &self.contributors {
+                let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+                coordinator.add_to_queue(contributor.participant.clone(), Some(ip), 10)?;
+            }
+            let before = coordinator.current_round_height()?;
+            coordinator.update()?;
+            let round_height = coordinator.current_round_height()?;
+            if round_height != before + 1 {
+                bail!("the coordinator did not start round {}", before + 1);
+            }
+            round_height
+        };
+        info!(
+            "Started round {} with {} contributors",
+            round_height,
+            self.contributors.len()
+        );
+
+        // The coordinator contributors are trusted, so no faults are injected into them.
+        let done = Arc::new(AtomicBool::new(false));
+        let contributors: Vec<_> = self
+            .contributors
+            .iter()
+            .map(|contributor| (contributor, self.options.faults))
+            .chain(
+                self.coordinator_contributors
+                    .iter()
+                    .map(|contributor| (contributor, Faults::none())),
+            )
+            .map(|(contributor, faults)| {
+                let worker = Worker {
+                    coordinator: self.coordinator.clone(),
+                    faults: faults.generator(&contributor.participant.to_string()),
+                    fault_log: self.fault_log.clone(),
+                    done: done.clone(),
+                };
+                let contributor = contributor.clone();
+                thread::spawn(move || worker.run_contributor(&contributor))
+            })
+            .collect();
+        let verifier = {
+            let worker = Worker {
+                coordinator: self.coordinator.clone(),
+                faults: self.options.faults.generator(&self.verifier.participant.to_string()),
+                fault_log: self.fault_log.clone(),
+                done: done.clone(),
+            };
+            let verifier = self.verifier.clone();
+            thread::spawn(move || worker.run_verifier(&verifier))
+        };
+
+        let result = self.drive(round_height);
+        done.store(true, Ordering::SeqCst);
+
+        for contributor in contributors {
+            join(contributor)?;
+        }
+        join(verifier)?;
+        result?;
+
+        info!("Round {} is aggregated", round_height);
+        Ok(round_height)
+    }
+
+    /// Updates the coordinator until the given round is aggregated.
+    fn drive(&self, round_height: u64) -> anyhow::Result<()> {
+        let mut faults = self.options.faults.generator("coordinator");
+        let deadline = Instant::now() + self.options.timeout;
+        loop {
+            {
+                let mut coordinator = write(&self.coordinator)?;
+                if faults.restart_coordinator() {
+                    warn!("Injecting a coordinator restart in round {}", round_height);
+                    self.fault_log.record_coordinator_restart();
+                    let environment = coordinator.environment().clone();
+                    *coordinator = start_coordinator(environment)?;
+                }
+                coordinator.update()?;
+                let state = coordinator.state();
+                if coordinator.current_round_height()? != round_height || state.is_current_round_aggregated() {
+                    return Ok(());
+                }
+            }
+            if Instant::now() > deadline {
+                bail!(
+                    "round {} did not finish within {:?}",
+                    round_height,
+                    self.options.timeout
+                );
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Checks the aggregated transcript of the given round, independently of the
+    /// verification the coordinator ran during aggregation.
+    pub fn verify_transcript(&self, round_height: u64) -> anyhow::Result<()> {
+        let path = read(&self.coordinator)?.locator_to_path(Locator::RoundFile { round_height })?;
+        let transcript = fs::read(path)?;
+
+        let settings = &self.options.settings;
+        let input = (&transcript[..], UseCompression::No, CheckForCorrectness::Full);
+        match settings.curve() {
+            CurveKind::Bls12_377 => Phase1::aggregate_verification(
+                input,
+                &Phase1Parameters::<Bls12_377>::new_full(
+                    settings.proving_system(),
+                    settings.power(),
+                    settings.batch_size(),
+                ),
+            )?,
+            CurveKind::BW6 => Phase1::aggregate_verification(
+                input,
+                &Phase1Parameters::<BW6_761>::new_full(
+                    settings.proving_system(),
+                    settings.power(),
+                    settings.batch_size(),
+                ),
+            )?,
+        };
+        Ok(())
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.base_directory);
+    }
+}
+
+/// Starts a coordinator from the given environment, resuming the ceremony found in its storage.
+fn start_coordinator(environment: Environment) -> anyhow::Result<Coordinator> {
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    Ok(coordinator)
+}
+
+/// The state shared by the thread of a simulated participant.
+struct Worker {
+    coordinator: Arc<RwLock<Coordinator>>,
+    faults: FaultGenerator,
+    fault_log: Arc<FaultLog>,
+    done: Arc<AtomicBool>,
+}
+
+impl Worker {
+    fn is_done(&self) -> bool {
+        self.done.load(Ordering::SeqCst)
+    }
+
+    /// Contributes to every assigned chunk until the round is done, waiting whenever
+    /// the contributor has no chunk which is ready to be contributed to.
+    fn run_contributor(mut self, contributor: &Contributor) -> anyhow::Result<()> {
+        let coordinator = self.coordinator.clone();
+        while !self.is_done() {
+            let result = self.contribute(&mut *write(&coordinator)?, contributor);
+            match result {
+                Ok(()) => debug!("{} contributed a chunk", contributor.participant),
+                // The previous contribution to the chunk is still in flight.
+                Err(CoordinatorError::PreviousContributionMissing { .. })
+                | Err(CoordinatorError::ChunkLockAlreadyAcquired)
+                | Err(CoordinatorError::ChunkMissingVerification)
+                // The contributor has no tasks, was dropped, or the round is over.
+                | Err(CoordinatorError::ParticipantHasNoRemainingTasks)
+                | Err(CoordinatorError::ParticipantAlreadyFinished)
+                | Err(CoordinatorError::ParticipantUnauthorized)
+                | Err(CoordinatorError::CurrentRoundFinished)
+                | Err(CoordinatorError::CurrentRoundAggregating)
+                | Err(CoordinatorError::CurrentRoundAggregated) => thread::sleep(POLL_INTERVAL),
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes and uploads the contribution to the next chunk of the contributor.
+    fn contribute(&mut self, coordinator: &mut Coordinator, contributor: &Contributor) -> Result<(), CoordinatorError> {
+        let (chunk_id, locked_locators) = coordinator.try_lock(&contributor.participant)?;
+        let response = locked_locators.next_contribution();
+        loop {
+            coordinator.run_computation(
+                response.round_height(),
+                chunk_id,
+                response.contribution_id(),
+                &contributor.participant,
+                &contributor.signing_key,
+                &contributor.seed,
+            )?;
+            if !self.faults.drop_upload() {
+                break;
+            }
+            // The response never reached the coordinator, so the contributor retries.
+            warn!("Injecting a dropped upload for {}", contributor.participant);
+            self.fault_log.record_dropped_upload();
+        }
+
+        if self.faults.corrupt_response() {
+            warn!("Injecting a corrupted response for {}", contributor.participant);
+            let path = coordinator.locator_to_path(Locator::ContributionFile(response))?;
+            let mut bytes = fs::read(&path)?;
+            let middle = bytes.len() / 2;
+            bytes[middle] ^= 0xff;
+            fs::write(&path, bytes)?;
+            self.fault_log.record_corrupted_response(response);
+        }
+
+        coordinator.try_contribute(&contributor.participant, chunk_id)?;
+        Ok(())
+    }
+
+    /// Verifies pending contributions until the round is done. Contributors whose
+    /// responses were corrupted on purpose are dropped from the round.
+    fn run_verifier(mut self, verifier: &Verifier) -> anyhow::Result<()> {
+        while !self.is_done() {
+            let mut coordinator = write(&self.coordinator)?;
+            let task = match coordinator.prioritized_pending_verifications().first().map(|(task, _)| *task) {
+                Some(task) => task,
+                None => {
+                    drop(coordinator);
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+            if self.faults.crash_verifier() {
+                // The verifier crashes before reporting the outcome of the verification,
+                // and has to verify the contribution again after restarting.
+                warn!("Injecting a verifier crash on {}", task);
+                let round_height = coordinator.current_round_height()?;
+                let _ = coordinator.run_verification(round_height, &task, &verifier.participant, &verifier.signing_key);
+                self.fault_log.record_verifier_crash();
+                continue;
+            }
+
+            let response = response_locator(&coordinator, &task)?;
+            match coordinator.verify(&verifier.participant, &verifier.signing_key, &task) {
+                Ok(()) if self.fault_log.is_corrupted(&response) => {
+                    bail!("the corrupted response to {} passed verification", task)
+                }
+                Ok(()) => {}
+                Err(error) if !self.fault_log.is_corrupted(&response) => return Err(error),
+                Err(_) => {
+                    self.fault_log.record_rejected_response();
+                    let contributor = contributor_of(&coordinator, &task)?;
+                    info!("Dropping {} for the corrupted response to {}", contributor, task);
+                    coordinator.drop_participant(&contributor)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the locator of the unverified response to the given task in the current round.
+fn response_locator(coordinator: &Coordinator, task: &Task) -> anyhow::Result<ContributionLocator> {
+    Ok(ContributionLocator::new(
+        coordinator.current_round_height()?,
+        task.chunk_id(),
+        task.contribution_id(),
+        false,
+    ))
+}
+
+/// Returns the contributor of the response to the given task in the current round.
+fn contributor_of(coordinator: &Coordinator, task: &Task) -> anyhow::Result<Participant> {
+    let round = coordinator.current_round()?;
+    round
+        .chunk(task.chunk_id())?
+        .get_contribution(task.contribution_id())?
+        .get_contributor()
+        .clone()
+        .ok_or_else(|| anyhow!("{} has no contributor", task))
+}
+
+fn read(coordinator: &RwLock<Coordinator>) -> anyhow::Result<std::sync::RwLockReadGuard<Coordinator>> {
+    coordinator
+        .read()
+        .map_err(|_| anyhow!("the coordinator lock is poisoned"))
+}
+
+fn write(coordinator: &RwLock<Coordinator>) -> anyhow::Result<std::sync::RwLockWriteGuard<Coordinator>> {
+    coordinator
+        .write()
+        .map_err(|_| anyhow!("the coordinator lock is poisoned"))
+}
+
+fn join(handle: thread::JoinHandle<anyhow::Result<()>>) -> anyhow::Result<()> {
+    handle.join().map_err(|_| anyhow!("a participant thread panicked"))?
+}
@@ -0,0 +1,27 @@
+use e2e_tests::{Harness, Options};
+
+#[test]
+fn tiny_ceremony_produces_a_valid_transcript() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let harness = Harness::new(Options::default()).unwrap();
+    let round_height = harness.run_round().unwrap();
+    assert_eq!(round_height, 1);
+    harness.verify_transcript(round_height).unwrap();
+}
+
+#[test]
+fn consecutive_rounds_produce_valid_transcripts() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let harness = Harness::new(Options {
+        contributors: 2,
+        ..Default::default()
+    })
+    .unwrap();
+    for expected in 1..=2 {
+        let round_height = harness.run_round().unwrap();
+        assert_eq!(round_height, expected);
+        harness.verify_transcript(round_height).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use e2e_tests::{FaultLog, Faults, Harness, Options};
+
+use tracing::info;
+
+/// Runs a round with the given faults, checks that its transcript is valid, and
+/// checks the injected faults with the given assertion.
+fn run_with_faults(faults: Faults, check: impl Fn(&FaultLog)) {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let harness = Harness::new(Options {
+        faults,
+        ..Default::default()
+    })
+    .unwrap();
+    let round_height = harness.run_round().unwrap();
+    harness.verify_transcript(round_height).unwrap();
+
+    let fault_log = harness.fault_log();
+    info!("seed {}: {}", faults.seed, fault_log);
+    check(fault_log);
+}
+
+#[test]
+fn converges_with_dropped_uploads() {
+    run_with_faults(
+        Faults {
+            seed: 1,
+            drop_upload: 0.3,
+            ..Faults::none()
+        },
+        |fault_log| {
+            assert!(fault_log.dropped_uploads() > 0);
+            assert_eq!(fault_log.total(), fault_log.dropped_uploads());
+        },
+    );
+}
+
+#[test]
+fn converges_with_corrupted_responses() {
+    run_with_faults(
+        Faults {
+            seed: 2,
+            corrupt_response: 0.1,
+            ..Faults::none()
+        },
+        |fault_log| {
+            assert!(fault_log.corrupted_responses() > 0);
+            // Every corrupted response which was verified was rejected, or the run would have failed,
+            // and the round only finished once the contributors of the corrupted responses were dropped.
+            assert!(fault_log.rejected_responses() > 0);
+            assert!(fault_log.rejected_responses() <= fault_log.corrupted_responses());
+            assert_eq!(fault_log.total(), fault_log.corrupted_responses());
+        },
+    );
+}
+
+#[test]
+fn converges_with_verifier_crashes() {
+    run_with_faults(
+        Faults {
+            seed: 3,
+            crash_verifier: 0.3,
+            ..Faults::none()
+        },
+        |fault_log| {
+            assert!(fault_log.verifier_crashes() > 0);
+            assert_eq!(fault_log.total(), fault_log.verifier_crashes());
+        },
+    );
+}
+
+#[test]
+fn converges_with_coordinator_restarts() {
+    run_with_faults(
+        Faults {
+            seed: 4,
+            restart_coordinator: 0.05,
+            ..Faults::none()
+        },
+        |fault_log| {
+            assert!(fault_log.coordinator_restarts() > 0);
+            assert_eq!(fault_log.total(), fault_log.coordinator_restarts());
+        },
+    );
+}
+
+#[test]
+fn converges_with_all_faults() {
+    for seed in 0..3 {
+        run_with_faults(
+            Faults {
+                seed,
+                drop_upload: 0.1,
+                corrupt_response: 0.05,
+                crash_verifier: 0.1,
+                restart_coordinator: 0.02,
+            },
+            |fault_log| {
+                assert!(fault_log.total() > 0);
+                assert!(fault_log.rejected_responses() <= fault_log.corrupted_responses());
+            },
+        );
+    }
+}
+
+#[test]
+fn converges_without_faults() {
+    run_with_faults(Faults::none(), |fault_log| assert_eq!(fault_log.total(), 0));
+}
@@ -1,6 +1,6 @@
 #!/bin/bash -e
 
-rm -f challenge* response* new_challenge* processed*
+rm -f challenge* response* new_challenge* processed* ceremony.json
 
 POWER=19
 BATCH=10000
@@ -25,7 +25,7 @@ $phase2 --response-fname response --phase2-fname processed --phase2-size $POWER
 
 ###### Phase 2
 
-$snark new --phase1 processed --output initial_ceremony --phase1-size $POWER --is-inner
+$snark new --phase1 processed --output initial_ceremony --phase1-size $POWER --circuit inner
 
 cp initial_ceremony contribution1
 yes | $snark contribute --data contribution1 --is-inner
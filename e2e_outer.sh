@@ -1,6 +1,6 @@
 #!/bin/bash -e
 
-rm -f challenge* response* new_challenge* processed*
+rm -f challenge* response* new_challenge* processed* ceremony.json
 
 POWER=20
 BATCH=10000
@@ -25,7 +25,7 @@ $phase2 --response-fname response --phase2-fname processed --phase2-size $POWER
 
 ###### Phase 2
 
-$snark new --phase1 processed --output initial_ceremony --phase1-size $POWER
+$snark new --phase1 processed --output initial_ceremony --phase1-size $POWER --circuit outer
 
 cp initial_ceremony contribution1
 yes | $snark contribute --data contribution1
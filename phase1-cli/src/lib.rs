@@ -11,7 +11,11 @@ mod new_challenge;
 pub use new_challenge::new_challenge;
 
 mod transform_pok_and_correctness;
-pub use transform_pok_and_correctness::transform_pok_and_correctness;
+pub use transform_pok_and_correctness::{
+    batch_transform_pok_and_correctness,
+    transform_pok_and_correctness,
+    BatchedContribution,
+};
 
 mod transform_ratios;
 pub use transform_ratios::transform_ratios;
@@ -1,5 +1,13 @@
-use phase1::{Phase1, Phase1Parameters, PublicKey};
-use setup_utils::{calculate_hash, print_hash, CheckForCorrectness, UseCompression};
+use phase1::{Phase1, Phase1Parameters, PublicKey, VerificationMode};
+use setup_utils::{
+    calculate_hash,
+    print_hash,
+    CheckForCorrectness,
+    GenericArray,
+    RatioCheckBatch,
+    UseCompression,
+    U64,
+};
 
 use snarkvm_curves::PairingEngine as Engine;
 
@@ -23,6 +31,185 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
         parameters.total_size_in_log2
     );
 
+    let (challenge_readable_map, response_readable_map) = load_contribution(
+        challenge_is_compressed,
+        challenge_filename,
+        contribution_is_compressed,
+        response_filename,
+        parameters,
+    );
+
+    // Check the hash chain - a new response must be based on the previous challenge!
+    let (current_accumulator_hash, response_hash) = check_hash_chain(&challenge_readable_map, &response_readable_map)
+        .expect("Hash chain failure. This is not the right response.");
+
+    // get the contributor's public key
+    let public_key = PublicKey::read(&response_readable_map, contribution_is_compressed, &parameters)
+        .expect("wasn't able to deserialize the response file's public key");
+
+    // check that it follows the protocol
+
+    println!("Verifying a contribution to contain proper powers and correspond to the public key...");
+
+    let res = Phase1::verification(
+        &challenge_readable_map,
+        &response_readable_map,
+        &public_key,
+        current_accumulator_hash.as_slice(),
+        challenge_is_compressed,
+        contribution_is_compressed,
+        CheckForCorrectness::No,
+        CheckForCorrectness::Full,
+        &parameters,
+    );
+
+    if let Err(e) = res {
+        println!("Verification failed: {}", e);
+        panic!("INVALID CONTRIBUTION!!!");
+    } else {
+        println!("Verification succeeded!");
+    }
+
+    write_new_challenge(
+        challenge_filename,
+        contribution_is_compressed,
+        &response_readable_map,
+        response_hash.as_slice(),
+        compress_new_challenge,
+        new_challenge_filename,
+        parameters,
+    );
+}
+
+/// The files and parameters of a single contribution verified by `batch_transform_pok_and_correctness`.
+pub struct BatchedContribution<'a, T: Engine> {
+    pub challenge_filename: &'a str,
+    pub response_filename: &'a str,
+    pub new_challenge_filename: &'a str,
+    pub parameters: &'a Phase1Parameters<T>,
+}
+
+/// Verifies several contributions, which may be for different chunks, and generates the
+/// new challenge of every valid one. Unlike `transform_pok_and_correctness`, the ratio checks
+/// of all contributions are batched into a single multi-pairing, and invalid contributions
+/// are reported instead of panicking.
+///
+/// Returns whether each contribution is valid. If the batch is invalid, the ratio checks
+/// of each contribution are verified separately to find the invalid ones.
+pub fn batch_transform_pok_and_correctness<T: Engine + Sync>(
+    challenge_is_compressed: UseCompression,
+    contribution_is_compressed: UseCompression,
+    compress_new_challenge: UseCompression,
+    contributions: &[BatchedContribution<T>],
+) -> Vec<bool> {
+    println!(
+        "Will verify and decompress {} contributions with batched ratio checks",
+        contributions.len()
+    );
+
+    // Verify everything but the ratio checks, which are collected in a batch per contribution.
+    let mut verified: Vec<Option<(Mmap, GenericArray<u8, U64>, RatioCheckBatch<T>)>> = contributions
+        .iter()
+        .enumerate()
+        .map(|(i, contribution)| {
+            let (challenge_readable_map, response_readable_map) = load_contribution(
+                challenge_is_compressed,
+                contribution.challenge_filename,
+                contribution_is_compressed,
+                contribution.response_filename,
+                contribution.parameters,
+            );
+
+            let (current_accumulator_hash, response_hash) =
+                match check_hash_chain(&challenge_readable_map, &response_readable_map) {
+                    Some(hashes) => hashes,
+                    None => {
+                        println!("Hash chain failure in contribution {}", i);
+                        return None;
+                    }
+                };
+
+            let public_key = match PublicKey::read(
+                &response_readable_map,
+                contribution_is_compressed,
+                contribution.parameters,
+            ) {
+                Ok(public_key) => public_key,
+                Err(e) => {
+                    println!("Invalid public key in contribution {}: {}", i, e);
+                    return None;
+                }
+            };
+
+            let mut batch = RatioCheckBatch::new();
+            if let Err(e) = Phase1::verification_with_batch(
+                &challenge_readable_map,
+                &response_readable_map,
+                &public_key,
+                current_accumulator_hash.as_slice(),
+                challenge_is_compressed,
+                contribution_is_compressed,
+                CheckForCorrectness::No,
+                CheckForCorrectness::Full,
+                VerificationMode::Full,
+                Some(&mut batch),
+                contribution.parameters,
+            ) {
+                println!("Verification of contribution {} failed: {}", i, e);
+                return None;
+            }
+
+            Some((response_readable_map, response_hash, batch))
+        })
+        .collect();
+
+    // Verify the ratio checks of all contributions at once.
+    let rng = &mut rand::thread_rng();
+    let mut combined = RatioCheckBatch::new();
+    for (_, _, batch) in verified.iter().flatten() {
+        combined.append(&mut batch.clone());
+    }
+    println!("Verifying {} batched ratio checks...", combined.len());
+    if combined.verify(rng).is_err() {
+        for (i, contribution) in verified.iter_mut().enumerate() {
+            if let Some((_, _, batch)) = contribution {
+                if let Err(e) = batch.verify(rng) {
+                    println!("Verification of contribution {} failed: {}", i, e);
+                    *contribution = None;
+                }
+            }
+        }
+    }
+
+    contributions
+        .iter()
+        .zip(verified)
+        .map(|(contribution, verified)| match verified {
+            Some((response_readable_map, response_hash, _)) => {
+                write_new_challenge(
+                    contribution.challenge_filename,
+                    contribution_is_compressed,
+                    &response_readable_map,
+                    response_hash.as_slice(),
+                    compress_new_challenge,
+                    contribution.new_challenge_filename,
+                    contribution.parameters,
+                );
+                true
+            }
+            None => false,
+        })
+        .collect()
+}
+
+/// Loads the challenge and response files of a contribution, checking their sizes.
+fn load_contribution<T: Engine>(
+    challenge_is_compressed: UseCompression,
+    challenge_filename: &str,
+    contribution_is_compressed: UseCompression,
+    response_filename: &str,
+    parameters: &Phase1Parameters<T>,
+) -> (Mmap, Mmap) {
     // Try to load challenge file from disk.
     let challenge_reader = OpenOptions::new()
         .read(true)
@@ -81,11 +268,20 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
             .expect("unable to create a memory map for input")
     };
 
+    (challenge_readable_map, response_readable_map)
+}
+
+/// Checks that the response is based on the challenge, and returns the hashes of both,
+/// or `None` if the hash chain is broken.
+fn check_hash_chain(
+    challenge_readable_map: &[u8],
+    response_readable_map: &[u8],
+) -> Option<(GenericArray<u8, U64>, GenericArray<u8, U64>)> {
     println!("Calculating previous challenge hash...");
 
     // Check that contribution is correct
 
-    let current_accumulator_hash = calculate_hash(&challenge_readable_map);
+    let current_accumulator_hash = calculate_hash(challenge_readable_map);
 
     println!("Hash of the `challenge` file for verification:");
     print_hash(&current_accumulator_hash);
@@ -104,42 +300,28 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
         print_hash(&response_challenge_hash);
 
         if &response_challenge_hash[..] != current_accumulator_hash.as_slice() {
-            panic!("Hash chain failure. This is not the right response.");
+            return None;
         }
     }
 
-    let response_hash = calculate_hash(&response_readable_map);
+    let response_hash = calculate_hash(response_readable_map);
 
     println!("Hash of the response file for verification:");
     print_hash(&response_hash);
 
-    // get the contributor's public key
-    let public_key = PublicKey::read(&response_readable_map, contribution_is_compressed, &parameters)
-        .expect("wasn't able to deserialize the response file's public key");
-
-    // check that it follows the protocol
-
-    println!("Verifying a contribution to contain proper powers and correspond to the public key...");
-
-    let res = Phase1::verification(
-        &challenge_readable_map,
-        &response_readable_map,
-        &public_key,
-        current_accumulator_hash.as_slice(),
-        challenge_is_compressed,
-        contribution_is_compressed,
-        CheckForCorrectness::No,
-        CheckForCorrectness::Full,
-        &parameters,
-    );
-
-    if let Err(e) = res {
-        println!("Verification failed: {}", e);
-        panic!("INVALID CONTRIBUTION!!!");
-    } else {
-        println!("Verification succeeded!");
-    }
+    Some((current_accumulator_hash, response_hash))
+}
 
+/// Writes the new challenge file of a verified contribution.
+fn write_new_challenge<T: Engine + Sync>(
+    challenge_filename: &str,
+    contribution_is_compressed: UseCompression,
+    response_readable_map: &[u8],
+    response_hash: &[u8],
+    compress_new_challenge: UseCompression,
+    new_challenge_filename: &str,
+    parameters: &Phase1Parameters<T>,
+) {
     if compress_new_challenge == contribution_is_compressed {
         println!("Don't need to recompress the contribution, copying the file without the public key...");
         fs::copy(challenge_filename, new_challenge_filename)
@@ -189,7 +371,7 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
 
         {
             (&mut writable_map[0..])
-                .write_all(response_hash.as_slice())
+                .write_all(response_hash)
                 .expect("unable to write a default hash to mmap");
 
             writable_map
@@ -198,7 +380,7 @@ pub fn transform_pok_and_correctness<T: Engine + Sync>(
         }
 
         Phase1::decompress(
-            &response_readable_map,
+            response_readable_map,
             &mut writable_map,
             CheckForCorrectness::No,
             &parameters,
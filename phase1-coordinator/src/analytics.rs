@@ -0,0 +1,226 @@
+//! Per-chunk sizes and timings of a ceremony, read from its event log, so that
+//! organizers can tune the number and size of chunks for the next ceremony
+//! from the data of the last one.
+//!
+//! The timings are derived from the timestamps of the events of each
+//! contribution: the assignment of the chunk, the start of the upload of the
+//! response, its addition to the round, the start of the upload of the next
+//! challenge by the verifier, and the verification. Event logs written before
+//! upload starts were recorded leave the sizes and upload times empty.
+
+use crate::{
+    events::{CeremonyEvent, EventKind},
+    objects::Participant,
+};
+
+use serde::Serialize;
+use std::{collections::HashMap, io::Write};
+use time::OffsetDateTime;
+
+/// The sizes and timings of a contribution to a chunk, one row of the exported CSV.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChunkTiming {
+    pub round_height: u64,
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    pub contributor: String,
+    pub verifier: Option<String>,
+    /// The size of the response file, as declared when it was uploaded.
+    pub response_bytes: Option<u64>,
+    /// The size of the next challenge file, as declared when it was uploaded.
+    pub next_challenge_bytes: Option<u64>,
+    /// The seconds from the assignment of the chunk to the start of the upload of the
+    /// response, which covers downloading the challenge and computing the contribution.
+    pub contribution_secs: Option<i64>,
+    /// The seconds the upload of the response took.
+    pub upload_secs: Option<i64>,
+    /// The seconds from the addition of the contribution to the start of the upload of
+    /// the next challenge, including the time the contribution waited for a verifier.
+    pub verification_secs: Option<i64>,
+    /// The seconds the upload of the next challenge took.
+    pub verification_upload_secs: Option<i64>,
+}
+
+fn secs(from: Option<OffsetDateTime>, to: Option<OffsetDateTime>) -> Option<i64> {
+    Some((to? - from?).whole_seconds())
+}
+
+///
+/// Returns the sizes and timings of the contributions added to the rounds in the given
+/// events, in the order they were added, or only of those to the given round.
+///
+/// A contribution which was assigned more than once, e.g. after its contributor was
+/// dropped, is timed from its last assignment. An upload is matched with the last upload
+/// of the chunk its participant started before it.
+///
+pub fn chunk_timings(events: &[CeremonyEvent], round_height: Option<u64>) -> Vec<ChunkTiming> {
+    let mut assigned = HashMap::new();
+    let mut upload_started: HashMap<(u64, &Participant, u64), (OffsetDateTime, u64)> = HashMap::new();
+    let mut rows: Vec<ChunkTiming> = vec![];
+    let mut uploaded_at = vec![];
+    let mut positions = HashMap::new();
+
+    for event in events {
+        if round_height.map_or(false, |round_height| round_height != event.round_height) {
+            continue;
+        }
+        let round = event.round_height;
+        match &event.kind {
+            EventKind::ChunkAssigned {
+                chunk_id,
+                contribution_id,
+                ..
+            } => {
+                assigned.insert((round, *chunk_id, *contribution_id), event.timestamp);
+            }
+            EventKind::UploadStarted {
+                participant,
+                chunk_id,
+                size,
+            } => {
+                upload_started.insert((round, participant, *chunk_id), (event.timestamp, *size));
+            }
+            EventKind::ContributionUploaded {
+                participant,
+                chunk_id,
+                contribution_id,
+            } => {
+                let upload = upload_started.remove(&(round, participant, *chunk_id));
+                let started_at = upload.map(|(started_at, _)| started_at);
+                let assigned_at = assigned.get(&(round, *chunk_id, *contribution_id)).copied();
+                positions.insert((round, *chunk_id, *contribution_id), rows.len());
+                uploaded_at.push(event.timestamp);
+                rows.push(ChunkTiming {
+                    round_height: round,
+                    chunk_id: *chunk_id,
+                    contribution_id: *contribution_id,
+                    contributor: participant.to_string(),
+                    verifier: None,
+                    response_bytes: upload.map(|(_, size)| size),
+                    next_challenge_bytes: None,
+                    // Without the start of the upload, the contribution is timed to its addition.
+                    contribution_secs: secs(assigned_at, started_at.or(Some(event.timestamp))),
+                    upload_secs: secs(started_at, Some(event.timestamp)),
+                    verification_secs: None,
+                    verification_upload_secs: None,
+                });
+            }
+            EventKind::ContributionVerified {
+                participant,
+                chunk_id,
+                contribution_id,
+            } => {
+                let position = match positions.get(&(round, *chunk_id, *contribution_id)) {
+                    Some(position) => *position,
+                    None => continue,
+                };
+                let upload = upload_started.remove(&(round, participant, *chunk_id));
+                let started_at = upload.map(|(started_at, _)| started_at);
+                let row = &mut rows[position];
+                row.verifier = Some(participant.to_string());
+                row.next_challenge_bytes = upload.map(|(_, size)| size);
+                row.verification_secs = secs(Some(uploaded_at[position]), started_at.or(Some(event.timestamp)));
+                row.verification_upload_secs = secs(started_at, Some(event.timestamp));
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+/// Writes the given rows as CSV with a header, leaving the fields which are unknown empty.
+pub fn write_csv<W: Write>(rows: &[ChunkTiming], writer: W) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    #[test]
+    fn test_chunk_timings() {
+        let contributor = Participant::new_contributor("test-contributor");
+        let verifier = Participant::new_verifier("test-verifier");
+        let at = |minutes: i64| datetime!(2021-11-01 12:00 UTC) + time::Duration::minutes(minutes);
+        let events = vec![
+            CeremonyEvent::new(at(0), 1, EventKind::ChunkAssigned {
+                participant: contributor.clone(),
+                chunk_id: 0,
+                contribution_id: 1,
+            }),
+            CeremonyEvent::new(at(10), 1, EventKind::UploadStarted {
+                participant: contributor.clone(),
+                chunk_id: 0,
+                size: 1000,
+            }),
+            CeremonyEvent::new(at(12), 1, EventKind::ContributionUploaded {
+                participant: contributor.clone(),
+                chunk_id: 0,
+                contribution_id: 1,
+            }),
+            CeremonyEvent::new(at(15), 1, EventKind::UploadStarted {
+                participant: verifier.clone(),
+                chunk_id: 0,
+                size: 500,
+            }),
+            CeremonyEvent::new(at(16), 1, EventKind::ContributionVerified {
+                participant: verifier.clone(),
+                chunk_id: 0,
+                contribution_id: 1,
+            }),
+            // A contribution recorded before upload starts were, which is not verified yet.
+            CeremonyEvent::new(at(20), 2, EventKind::ChunkAssigned {
+                participant: contributor.clone(),
+                chunk_id: 1,
+                contribution_id: 1,
+            }),
+            CeremonyEvent::new(at(25), 2, EventKind::ContributionUploaded {
+                participant: contributor.clone(),
+                chunk_id: 1,
+                contribution_id: 1,
+            }),
+        ];
+
+        let rows = chunk_timings(&events, None);
+        assert_eq!(2, rows.len());
+        assert_eq!(
+            ChunkTiming {
+                round_height: 1,
+                chunk_id: 0,
+                contribution_id: 1,
+                contributor: "test-contributor.contributor".to_string(),
+                verifier: Some("test-verifier.verifier".to_string()),
+                response_bytes: Some(1000),
+                next_challenge_bytes: Some(500),
+                contribution_secs: Some(600),
+                upload_secs: Some(120),
+                verification_secs: Some(180),
+                verification_upload_secs: Some(60),
+            },
+            rows[0]
+        );
+        assert_eq!(Some(300), rows[1].contribution_secs);
+        assert_eq!(None, rows[1].upload_secs);
+        assert_eq!(None, rows[1].verifier);
+
+        assert_eq!(vec![rows[1].clone()], chunk_timings(&events, Some(2)));
+
+        let mut csv = vec![];
+        write_csv(&rows, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            "round_height,chunk_id,contribution_id,contributor,verifier,response_bytes,next_challenge_bytes,\
+             contribution_secs,upload_secs,verification_secs,verification_upload_secs",
+            lines[0]
+        );
+        assert_eq!("2,1,1,test-contributor.contributor,,,,300,,,", lines[2]);
+    }
+}
@@ -0,0 +1,165 @@
+//! The audit log of the ceremony. Every mutation of the ceremony by an
+//! administrator is made under an operation ID chosen by the administrator,
+//! and is appended to the audit log with the identity of the administrator,
+//! signed by the coordinator, one JSON object per line, before it is applied.
+//!
+//! Retrying an operation under the same operation ID does not apply it
+//! again, so an administrator can safely retry an operation whose outcome
+//! they did not see, e.g. after a connection dropped during an incident.
+//! An operation which failed is retried under a new operation ID.
+
+use crate::{objects::Participant, CoordinatorError};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// A mutation of the ceremony by an administrator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum AdminOperation {
+    /// Bans a participant from the current and all future rounds.
+    BanParticipant { participant: Participant },
+    /// Allows a banned participant to join the queue again.
+    UnbanParticipant { participant: Participant },
+    /// Drops a participant from the current round.
+    DropParticipant { participant: Participant },
+    /// Resets the current round, with a rollback to the end of the previous round.
+    ResetRound,
+    /// Holds the coordinator from transitioning to the next round.
+    EnableManualLock,
+    /// Lets the coordinator transition to the next round again.
+    DisableManualLock,
+    /// Marks the verification of a contribution as urgent.
+    PrioritizeVerification { chunk_id: u64, contribution_id: u64 },
+    /// Opens the ceremony to the public before the schedule of the trusted cohort.
+    OpenPublicPhase,
+}
+
+/// An operation of an administrator, as recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// The ID the administrator chose for the operation, unique within the ceremony.
+    pub operation_id: String,
+    /// The identity of the administrator who made the operation.
+    pub admin: String,
+    pub timestamp: OffsetDateTime,
+    /// The height of the current round when the operation was recorded.
+    pub round_height: u64,
+    #[serde(flatten)]
+    pub operation: AdminOperation,
+}
+
+/// An [AuditRecord] signed by the coordinator over its canonical JSON encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedAuditRecord {
+    pub record: AuditRecord,
+    /// The name of the signature scheme of the coordinator.
+    pub signature_scheme: String,
+    pub signature: String,
+}
+
+/// The outcome of an operation of an administrator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminOutcome {
+    /// The operation was appended to the audit log and applied.
+    Applied(SignedAuditRecord),
+    /// The operation was recorded before under the same operation ID, so the
+    /// retry changed nothing. Holds the record of the original operation.
+    AlreadyApplied(SignedAuditRecord),
+}
+
+impl AdminOutcome {
+    /// Returns the record of the operation in the audit log.
+    pub fn record(&self) -> &SignedAuditRecord {
+        match self {
+            AdminOutcome::Applied(record) | AdminOutcome::AlreadyApplied(record) => record,
+        }
+    }
+}
+
+///
+/// Returns the record of the operation made under the given operation ID, if any,
+/// so that a retry of the operation is not applied again.
+///
+/// Returns [CoordinatorError::AdminOperationIdReused] if the operation ID was used
+/// for a different operation, or by a different administrator.
+///
+pub fn find_operation<'a>(
+    records: &'a [SignedAuditRecord],
+    operation_id: &str,
+    admin: &str,
+    operation: &AdminOperation,
+) -> Result<Option<&'a SignedAuditRecord>, CoordinatorError> {
+    match records.iter().find(|signed| signed.record.operation_id == operation_id) {
+        Some(signed) if signed.record.admin == admin && signed.record.operation == *operation => Ok(Some(signed)),
+        Some(_) => Err(CoordinatorError::AdminOperationIdReused {
+            operation_id: operation_id.to_string(),
+        }),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    fn signed(operation_id: &str, admin: &str, operation: AdminOperation) -> SignedAuditRecord {
+        SignedAuditRecord {
+            record: AuditRecord {
+                operation_id: operation_id.to_string(),
+                admin: admin.to_string(),
+                timestamp: datetime!(2021-11-01 12:00 UTC),
+                round_height: 3,
+                operation,
+            },
+            signature_scheme: "dummy".to_string(),
+            signature: "signature".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_audit_record_schema() {
+        let record = signed("incident-7-ban", "alice", AdminOperation::BanParticipant {
+            participant: Participant::new_contributor("test-contributor"),
+        });
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!("incident-7-ban", json["record"]["operation_id"]);
+        assert_eq!("alice", json["record"]["admin"]);
+        assert_eq!("ban_participant", json["record"]["operation"]);
+        assert_eq!("test-contributor.contributor", json["record"]["participant"]);
+
+        assert_eq!(record, serde_json::from_value(json).unwrap());
+    }
+
+    #[test]
+    fn test_find_operation() {
+        let ban = AdminOperation::BanParticipant {
+            participant: Participant::new_contributor("test-contributor"),
+        };
+        let records = vec![
+            signed("lock", "alice", AdminOperation::EnableManualLock),
+            signed("ban", "alice", ban.clone()),
+        ];
+
+        // A retry finds the original operation.
+        let found = find_operation(&records, "ban", "alice", &ban).unwrap();
+        assert_eq!(Some(&records[1]), found);
+        assert_eq!(
+            None,
+            find_operation(&records, "reset", "alice", &AdminOperation::ResetRound).unwrap()
+        );
+
+        // An operation ID can not be reused for another operation, or by another administrator.
+        assert!(matches!(
+            find_operation(&records, "lock", "alice", &AdminOperation::DisableManualLock),
+            Err(CoordinatorError::AdminOperationIdReused { .. })
+        ));
+        assert!(matches!(
+            find_operation(&records, "ban", "bob", &ban),
+            Err(CoordinatorError::AdminOperationIdReused { .. })
+        ));
+    }
+}
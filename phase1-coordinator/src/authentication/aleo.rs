@@ -0,0 +1,62 @@
+use crate::authentication::Signature;
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, ViewKey};
+use snarkvm_utilities::{FromBytes, ToBytes};
+
+use std::str::FromStr;
+
+/// An authentication for contributions with Aleo accounts.
+///
+/// The signing key is an Aleo view key, and the public key is the Aleo address of the account.
+pub struct AleoSignature;
+
+impl Signature for AleoSignature {
+    /// Returns the name of the signature scheme.
+    fn name(&self) -> String {
+        "AleoSignatureScheme".to_string()
+    }
+
+    /// Returns `true` if the signature scheme is safe for use in production.
+    fn is_secure(&self) -> bool {
+        true
+    }
+
+    /// Signs the given message using the given signing key,
+    /// and returns the signature as a string.
+    fn sign(&self, signing_key: &str, message: &str) -> anyhow::Result<String> {
+        let view_key = ViewKey::<Testnet2Parameters>::from_str(signing_key)?;
+        let signature = view_key.sign(message.as_bytes(), &mut rand::thread_rng())?;
+        Ok(hex::encode(signature.to_bytes_le()?))
+    }
+
+    /// Verifies the given signature for the given message and public key,
+    /// and returns `true` if the signature is valid.
+    fn verify(&self, public_key: &str, message: &str, signature: &str) -> bool {
+        let verify = || -> anyhow::Result<bool> {
+            let address = Address::<Testnet2Parameters>::from_str(public_key)?;
+            let signature = FromBytes::from_bytes_le(&hex::decode(signature)?)?;
+            Ok(address.verify_signature(message.as_bytes(), &signature)?)
+        };
+        verify().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snarkvm_dpc::PrivateKey;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_aleo_signature() {
+        let private_key = PrivateKey::<Testnet2Parameters>::new(&mut rand::thread_rng());
+        let view_key = ViewKey::try_from(&private_key).unwrap();
+        let address = Address::try_from(&private_key).unwrap();
+        let message = "message";
+
+        let signature = AleoSignature.sign(&view_key.to_string(), message).unwrap();
+        assert!(AleoSignature.verify(&address.to_string(), message, &signature));
+        assert!(!AleoSignature.verify(&address.to_string(), "other message", &signature));
+        assert!(!AleoSignature.verify("public", message, &signature));
+    }
+}
@@ -1,3 +1,6 @@
+pub mod aleo;
+pub use aleo::*;
+
 pub mod dummy;
 pub use dummy::*;
 
@@ -0,0 +1,96 @@
+//! Bootstrapping of a brand-new coordinator deployment from a set of
+//! pre-generated round 0 challenges, see [Coordinator::bootstrap].
+
+use crate::{environment::Environment, objects::Participant, CoordinatorError};
+
+#[cfg(doc)]
+use crate::Coordinator;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// A challenge listed in a [BootstrapManifest].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapChallenge {
+    pub chunk_id: u64,
+    /// The path of the challenge file, relative to the manifest.
+    pub path: PathBuf,
+    /// The hex-encoded hash of the challenge file.
+    pub hash: String,
+}
+
+/// Describes the challenges generated for round 0 of a ceremony.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapManifest {
+    pub challenges: Vec<BootstrapChallenge>,
+}
+
+impl BootstrapManifest {
+    /// Reads the manifest at the given path.
+    pub fn load(path: &Path) -> Result<Self, CoordinatorError> {
+        let manifest = fs_err::read(path)?;
+        Ok(serde_json::from_slice(&manifest)?)
+    }
+
+    ///
+    /// Returns the challenge for each chunk ID in the given environment,
+    /// in the order of their chunk IDs.
+    ///
+    /// Returns a `CoordinatorError` if the manifest has a challenge for a chunk ID
+    /// that does not exist, lists a chunk ID more than once, or does not have a
+    /// challenge for every chunk ID.
+    ///
+    pub fn challenges(&self, environment: &Environment) -> Result<Vec<&BootstrapChallenge>, CoordinatorError> {
+        let mut chunk_ids = HashSet::new();
+        for challenge in &self.challenges {
+            if challenge.chunk_id >= environment.number_of_chunks() {
+                return Err(CoordinatorError::ChunkIdInvalid);
+            }
+            if !chunk_ids.insert(challenge.chunk_id) {
+                return Err(CoordinatorError::ChunkIdAlreadyAdded);
+            }
+        }
+
+        (0..environment.number_of_chunks())
+            .map(|chunk_id| {
+                self.challenges
+                    .iter()
+                    .find(|challenge| challenge.chunk_id == chunk_id)
+                    .ok_or(CoordinatorError::ChunkMissing)
+            })
+            .collect()
+    }
+}
+
+/// The onboarding information of a bootstrapped ceremony.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapSummary {
+    pub round_height: u64,
+    pub number_of_chunks: u64,
+    pub verifiers: Vec<Participant>,
+    /// The hex-encoded challenge hash of each chunk, in order of chunk ID.
+    pub challenge_hashes: Vec<String>,
+}
+
+impl fmt::Display for BootstrapSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Ceremony bootstrapped at round {}", self.round_height)?;
+        writeln!(f, "Number of chunks: {}", self.number_of_chunks)?;
+        writeln!(f, "Verifiers:")?;
+        for verifier in &self.verifiers {
+            writeln!(f, "  {}", verifier)?;
+        }
+        writeln!(f, "Challenge hashes:")?;
+        for (chunk_id, hash) in self.challenge_hashes.iter().enumerate() {
+            writeln!(f, "  chunk {}: {}", chunk_id, hash)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+//! The configuration of a ceremony whose first rounds are restricted to a
+//! fixed roster of trusted contributors, before it opens to the public.
+
+use crate::objects::Participant;
+
+use serde::{Deserialize, Serialize};
+use setup1_shared::protocol::v1::CeremonyPhase;
+use std::collections::BTreeSet;
+use time::OffsetDateTime;
+
+/// Restricts the rounds up to `last_round` to the contributors on the roster.
+///
+/// The ceremony opens to the public once the current round is `last_round`,
+/// so that the public is scheduled for the rounds after it, or at `opens_at`
+/// if that comes first. An administrator may open it earlier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CohortPhase {
+    /// The addresses of the contributors of the trusted cohort.
+    pub roster: BTreeSet<String>,
+    /// The last round which is restricted to the roster.
+    pub last_round: u64,
+    /// The time the ceremony opens to the public at the latest.
+    #[serde(default)]
+    pub opens_at: Option<OffsetDateTime>,
+}
+
+impl CohortPhase {
+    ///
+    /// Returns `true` if the ceremony is due to open to the public,
+    /// given the height of the current round and the time now.
+    ///
+    pub fn is_public_due(&self, current_round_height: u64, now: OffsetDateTime) -> bool {
+        current_round_height >= self.last_round || self.opens_at.map_or(false, |opens_at| now >= opens_at)
+    }
+
+    ///
+    /// Returns `true` if the given participant may join the queue in the given phase.
+    ///
+    pub fn is_eligible(&self, participant: &Participant, phase: CeremonyPhase) -> bool {
+        match phase {
+            CeremonyPhase::TrustedCohort => self.roster.contains(&participant.address()),
+            CeremonyPhase::Public => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cohort_phase() {
+        let member = Participant::new_contributor("aleo1member");
+        let stranger = Participant::new_contributor("aleo1stranger");
+        let now = OffsetDateTime::from_unix_timestamp(1_000_000).unwrap();
+        let mut cohort = CohortPhase {
+            roster: vec![member.address()].into_iter().collect(),
+            last_round: 3,
+            opens_at: None,
+        };
+
+        assert!(cohort.is_eligible(&member, CeremonyPhase::TrustedCohort));
+        assert!(!cohort.is_eligible(&stranger, CeremonyPhase::TrustedCohort));
+        assert!(cohort.is_eligible(&stranger, CeremonyPhase::Public));
+
+        // The public is scheduled for the round after the last round of the cohort.
+        assert!(!cohort.is_public_due(2, now));
+        assert!(cohort.is_public_due(3, now));
+
+        // A scheduled opening comes first if it is earlier.
+        cohort.opens_at = Some(now + time::Duration::hours(1));
+        assert!(!cohort.is_public_due(1, now));
+        assert!(cohort.is_public_due(1, now + time::Duration::hours(1)));
+    }
+}
@@ -74,6 +74,69 @@ impl Initialization {
         Ok(hash)
     }
 
+    ///
+    /// Imports a pre-generated challenge for a given environment, round height, and chunk ID.
+    ///
+    /// The challenge is checked against the expected challenge size and the given
+    /// challenge hash before it is written to storage for this round and the next.
+    ///
+    #[inline]
+    pub(crate) fn import(
+        environment: &Environment,
+        storage: &mut Disk,
+        round_height: u64,
+        chunk_id: u64,
+        challenge: &[u8],
+        challenge_hash: &[u8],
+    ) -> anyhow::Result<Vec<u8>> {
+        info!("Starting import on round {} chunk {}", round_height, chunk_id);
+        let start = Instant::now();
+
+        // Check that the challenge matches the expected challenge size.
+        let expected_challenge_size = Object::contribution_file_size(environment, chunk_id, true);
+        trace!("Expected challenge file size is {}", expected_challenge_size);
+        if challenge.len() as u64 != expected_challenge_size {
+            error!(
+                "Challenge for chunk {} has size {}, expected {}",
+                chunk_id,
+                challenge.len(),
+                expected_challenge_size
+            );
+            return Err(CoordinatorError::ContributionFileSizeMismatch.into());
+        }
+
+        // Check that the challenge matches the given challenge hash.
+        if calculate_hash(challenge).as_slice() != challenge_hash {
+            error!("Challenge for chunk {} does not match its challenge hash", chunk_id);
+            return Err(CoordinatorError::ContributionHashMismatch.into());
+        }
+
+        // Initialize the contribution locator and write the challenge to it.
+        let contribution_locator = Locator::ContributionFile(ContributionLocator::new(round_height, chunk_id, 0, true));
+        storage.initialize(contribution_locator.clone(), expected_challenge_size as u64)?;
+        {
+            use crate::storage::ObjectWriter;
+
+            let mut writer = storage.writer(&contribution_locator)?;
+            writer.as_mut().copy_from_slice(challenge);
+            writer.flush()?;
+        }
+
+        // Copy the current transcript to the next transcript.
+        // This operation will *overwrite* the contents of `next_transcript`.
+        let next_contribution_locator =
+            Locator::ContributionFile(ContributionLocator::new(round_height + 1, chunk_id, 0, true));
+        storage.copy(&contribution_locator, &next_contribution_locator)?;
+
+        // Check that the current and next contribution hash match.
+        let hash = Self::check_hash(storage, &contribution_locator, &next_contribution_locator)?;
+        debug!("The challenge hash of Chunk {} is {}", chunk_id, pretty_hash!(&hash));
+
+        let elapsed = Instant::now().duration_since(start);
+        info!("Completed import on chunk {} in {:?}", chunk_id, elapsed);
+        Ok(hash)
+    }
+
     /// Runs Phase 1 initialization on the given parameters.
     #[inline]
     fn initialization<T: Engine + Sync>(
@@ -9,9 +9,7 @@ pub(crate) use computation::*;
 pub(crate) mod initialization;
 pub(crate) use initialization::*;
 
-#[cfg(any(test, feature = "operator"))]
 pub(crate) mod verification;
-#[cfg(any(test, feature = "operator"))]
 pub(crate) use verification::*;
 
 #[cfg(any(test, feature = "operator"))]
@@ -79,11 +77,8 @@ pub(crate) fn write_contribution_file_signature(
     // Construct the contribution state.
     let contribution_state = ContributionState::new(challenge_hash, response_hash, next_challenge_hash)?;
 
-    // Generate the contribution signature.
-    let contribution_signature = signature.sign(signing_key, &contribution_state.signature_message()?)?;
-
-    // Construct the contribution file signature.
-    let contribution_file_signature = ContributionFileSignature::new(contribution_signature, contribution_state)?;
+    // Sign the contribution state and construct the contribution file signature.
+    let contribution_file_signature = ContributionFileSignature::sign(&*signature, signing_key, contribution_state)?;
     let contribution_file_signature_bytes = serde_json::to_vec_pretty(&contribution_file_signature)?;
 
     // Write the contribution file signature.
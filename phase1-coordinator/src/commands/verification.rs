@@ -1,25 +1,24 @@
 use crate::{
-    authentication::Signature,
-    commands::SigningKey,
     environment::Environment,
-    storage::{
-        ContributionLocator,
-        ContributionSignatureLocator,
-        Disk,
-        Locator,
-        Object,
-        StorageLocator,
-        StorageObject,
-    },
+    storage::{ContributionLocator, Disk, Locator, StorageObject},
     CoordinatorError,
 };
-use phase1::{helpers::CurveKind, Phase1, Phase1Parameters, PublicKey};
+use phase1::{helpers::CurveKind, Phase1, Phase1Parameters, PublicKey, VerificationMode};
 use setup_utils::{calculate_hash, CheckForCorrectness, GenericArray, U64};
 use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine as Engine};
 
-use std::{io::Write, sync::Arc, time::Instant};
+use std::time::Instant;
 use tracing::{debug, error, info, trace};
 
+#[cfg(any(test, feature = "operator"))]
+use crate::{
+    authentication::Signature,
+    commands::SigningKey,
+    storage::{ContributionSignatureLocator, Object, StorageLocator},
+};
+#[cfg(any(test, feature = "operator"))]
+use std::{io::Write, sync::Arc};
+
 pub(crate) struct Verification;
 
 impl Verification {
@@ -28,6 +27,7 @@ impl Verification {
     /// round height, chunk ID, and contribution ID of the
     /// unverified response file.
     ///
+    #[cfg(any(test, feature = "operator"))]
     #[inline]
     pub(crate) fn run(
         environment: &Environment,
@@ -51,7 +51,7 @@ impl Verification {
         }
 
         // Check that the chunk ID is valid.
-        if chunk_id > environment.number_of_chunks() {
+        if chunk_id >= environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -153,6 +153,115 @@ impl Verification {
         Ok(())
     }
 
+    ///
+    /// Runs a full verification of the response file for a given environment,
+    /// storage, round height, chunk ID, and contribution ID, which was previously
+    /// accepted by a sampled verification.
+    ///
+    /// Unlike `Verification::run`, this does not write any files to storage.
+    ///
+    #[inline]
+    pub(crate) fn run_full(
+        environment: &Environment,
+        storage: &Disk,
+        round_height: u64,
+        chunk_id: u64,
+        current_contribution_id: u64,
+    ) -> Result<(), CoordinatorError> {
+        info!(
+            "Starting full verification of round {} chunk {} contribution {}",
+            round_height, chunk_id, current_contribution_id
+        );
+        let start = Instant::now();
+
+        // Check that this is not the initial contribution.
+        if current_contribution_id == 0 {
+            return Err(CoordinatorError::VerificationOnContributionIdZero);
+        }
+
+        // Fetch the locators for `Verification`.
+        let challenge_locator = Locator::ContributionFile(ContributionLocator::new(
+            round_height,
+            chunk_id,
+            current_contribution_id - 1,
+            true,
+        ));
+        let response_locator = Locator::ContributionFile(ContributionLocator::new(
+            round_height,
+            chunk_id,
+            current_contribution_id,
+            false,
+        ));
+
+        // Check that the previous and current locators exist in storage.
+        if !storage.exists(&challenge_locator) || !storage.exists(&response_locator) {
+            return Err(CoordinatorError::ContributionLocatorMissing);
+        }
+
+        let settings = environment.parameters();
+        let result = match settings.curve() {
+            CurveKind::Bls12_377 => Self::transform_pok_and_correctness(
+                environment,
+                storage.reader(&challenge_locator)?.as_ref(),
+                storage.reader(&response_locator)?.as_ref(),
+                VerificationMode::Full,
+                &phase1_chunked_parameters!(Bls12_377, settings, chunk_id),
+            ),
+            CurveKind::BW6 => Self::transform_pok_and_correctness(
+                environment,
+                storage.reader(&challenge_locator)?.as_ref(),
+                storage.reader(&response_locator)?.as_ref(),
+                VerificationMode::Full,
+                &phase1_chunked_parameters!(BW6_761, settings, chunk_id),
+            ),
+        };
+        if let Err(error) = result {
+            error!("Full verification failed with {}", error);
+            return Err(CoordinatorError::VerificationFailed);
+        }
+
+        let elapsed = Instant::now().duration_since(start);
+        info!(
+            "Completed full verification of round {} chunk {} contribution {} in {:?}",
+            round_height, chunk_id, current_contribution_id, elapsed
+        );
+        Ok(())
+    }
+
+    ///
+    /// Runs a full verification of the given response file against the given
+    /// challenge file for a chunk ID, to spot check a contribution.
+    ///
+    /// Unlike `Verification::run`, this does not read or write any files in storage.
+    /// Returns the hash of the response file.
+    ///
+    #[inline]
+    pub(crate) fn run_spot_check(
+        environment: &Environment,
+        chunk_id: u64,
+        challenge: &[u8],
+        response: &[u8],
+    ) -> Result<GenericArray<u8, U64>, CoordinatorError> {
+        let settings = environment.parameters();
+        match settings.curve() {
+            CurveKind::Bls12_377 => Self::transform_pok_and_correctness(
+                environment,
+                challenge,
+                response,
+                VerificationMode::Full,
+                &phase1_chunked_parameters!(Bls12_377, settings, chunk_id),
+            ),
+            CurveKind::BW6 => Self::transform_pok_and_correctness(
+                environment,
+                challenge,
+                response,
+                VerificationMode::Full,
+                &phase1_chunked_parameters!(BW6_761, settings, chunk_id),
+            ),
+        }
+    }
+
+    #[cfg(any(test, feature = "operator"))]
     #[inline]
     fn verification(
         environment: &Environment,
@@ -174,12 +283,14 @@ impl Verification {
                 environment,
                 storage.reader(&challenge_locator)?.as_ref(),
                 storage.reader(&response_locator)?.as_ref(),
+                environment.verification_mode(),
                 &phase1_chunked_parameters!(Bls12_377, settings, chunk_id),
             ),
             CurveKind::BW6 => Self::transform_pok_and_correctness(
                 environment,
                 storage.reader(&challenge_locator)?.as_ref(),
                 storage.reader(&response_locator)?.as_ref(),
+                environment.verification_mode(),
                 &phase1_chunked_parameters!(BW6_761, settings, chunk_id),
             ),
         };
@@ -262,6 +373,7 @@ impl Verification {
         environment: &Environment,
         challenge_reader: &[u8],
         response_reader: &[u8],
+        mode: VerificationMode,
         parameters: &Phase1Parameters<T>,
     ) -> Result<GenericArray<u8, U64>, CoordinatorError> {
         debug!("Verifying 2^{} powers of tau", parameters.total_size_in_log2);
@@ -300,7 +412,7 @@ impl Verification {
         // trace!("Public key of the contributor is {:#?}", public_key);
 
         trace!("Starting verification");
-        Phase1::verification(
+        Phase1::verification_with_mode(
             challenge_reader,
             response_reader,
             &public_key,
@@ -309,6 +421,7 @@ impl Verification {
             compressed_response,
             CheckForCorrectness::No,
             CheckForCorrectness::Full,
+            mode,
             &parameters,
         )?;
         trace!("Completed verification");
@@ -316,6 +429,7 @@ impl Verification {
         Ok(response_hash)
     }
 
+    #[cfg(any(test, feature = "operator"))]
     #[inline]
     fn decompress<'a, T: Engine + Sync>(
         response_reader: &[u8],
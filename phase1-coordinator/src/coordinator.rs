@@ -3,8 +3,11 @@
 //! [CoordinatorState] object.
 
 use crate::{
+    analytics::{chunk_timings, ChunkTiming},
+    audit::{find_operation, AdminOperation, AdminOutcome, AuditRecord, SignedAuditRecord},
     authentication::Signature,
-    commands::{Aggregation, Initialization},
+    bootstrap::{BootstrapManifest, BootstrapSummary},
+    commands::{Aggregation, Initialization, SigningKey, Verification},
     coordinator_state::{
         CeremonyStorageAction,
         CoordinatorState,
@@ -14,7 +17,22 @@ use crate::{
         RoundMetrics,
     },
     environment::{Deployment, Environment},
+    events::{contribution_page, CeremonyEvent, EventKind},
+    health::{
+        available_disk_space,
+        check_disk_headroom,
+        check_locks,
+        check_storage,
+        check_verification_backlog,
+        HealthReport,
+        HealthThresholds,
+    },
     objects::{participant::*, task::TaskInitializationError, ContributionFileSignature, LockedLocators, Round, Task},
+    outboard::{challenge_outboard, ChallengeOutboard},
+    policy::{ContributorOrigin, DiversityMeasure},
+    quarantine::{ForensicBundle, Rejection},
+    runbook::{write_atomically, AdvanceRoundReport, AdvanceRoundStep},
+    spot_check::spot_check,
     storage::{
         ContributionLocator,
         ContributionSignatureLocator,
@@ -26,23 +44,50 @@ use crate::{
         StorageLocator,
         StorageObject,
         UpdateAction,
+        WalEntry,
+        WalObject,
     },
 };
-use setup_utils::calculate_hash;
+use phase1::VerificationMode;
+use setup1_shared::ids::{ChunkId, ContributionId};
+use setup1_shared::protocol::v1::{
+    ChallengeDigest,
+    ContributionPage,
+    ContributionsQuery,
+    ContributorEligibility,
+    ErrorCode,
+    LatestIndex,
+    MachineAttestation,
+    RejectedContributionReport,
+    SeedCommitment,
+    SignedLatestIndex,
+    SignedSeedCommitmentReceipt,
+    SignedSpotCheckVerdict,
+    SlotReservation,
+    SlotReservationRequest,
+    SpotCheckRequest,
+    TranscriptDigest,
+    VerificationPriority,
+};
+use setup1_shared::structures::{ArtifactReplica, LockResponse};
+use setup_utils::{calculate_hash, to_canonical_json};
 
 use std::{
+    collections::{HashMap, VecDeque},
     fmt,
     net::IpAddr,
+    path::Path,
     sync::{Arc, RwLock},
 };
 use time::OffsetDateTime;
 use tracing::*;
 
-#[cfg(any(test, feature = "operator"))]
-use std::collections::HashMap;
-
 #[derive(Debug)]
 pub enum CoordinatorError {
+    AdminOperationIdMissing,
+    AdminOperationIdReused {
+        operation_id: String,
+    },
     AggregateContributionFileSizeMismatch,
     ChallengeHashSizeInvalid,
     ChunkAlreadyComplete,
@@ -57,6 +102,8 @@ pub enum CoordinatorError {
     ChunkMissingVerification,
     ChunkCannotLockZeroContributions { chunk_id: u64 },
     ChunkNotLockedOrByWrongParticipant,
+    ChunkPinningDisabled,
+    CohortPhaseNotConfigured,
     ComputationFailed,
     CompressedContributionHashingUnsupported,
     ContributorPendingTasksCannotBeEmpty(Participant),
@@ -95,6 +142,7 @@ pub enum CoordinatorError {
     ExpectedContributor,
     ExpectedVerifier,
     Error(anyhow::Error),
+    IdempotencyKeyReused,
     InitializationFailed,
     InitializationTranscriptsDiffer,
     Integer(std::num::ParseIntError),
@@ -102,6 +150,7 @@ pub enum CoordinatorError {
     Hex(hex::FromHexError),
     JsonError(serde_json::Error),
     JustificationInvalid,
+    KeyUsagePolicyViolated { participant: Participant, chunk_id: u64 },
     LocatorDeserializationFailed,
     LocatorFileAlreadyExists,
     LocatorFileAlreadyExistsAndOpen,
@@ -110,6 +159,7 @@ pub enum CoordinatorError {
     LocatorFileNotOpen,
     LocatorFileShouldBeOpen,
     LocatorSerializationFailed,
+    ManualLockEnabled,
     NextChallengeHashAlreadyExists,
     NextChallengeHashSizeInvalid,
     NextChallengeHashMissing,
@@ -117,6 +167,7 @@ pub enum CoordinatorError {
     NextRoundShouldBeEmpty,
     NumberOfChunksInvalid,
     NumberOfContributionsDiffer,
+    PageCursorInvalid,
     ParticipantAlreadyAdded,
     ParticipantAlreadyAddedChunk,
     ParticipantAlreadyBanned,
@@ -143,6 +194,7 @@ pub enum CoordinatorError {
     ParticipantMissingDisposingTask,
     ParticipantMissingPendingTask { pending_task: Task },
     ParticipantNotFound(Participant),
+    ParticipantNotInCohort,
     ParticipantNotReady,
     ParticipantRoundHeightInvalid,
     ParticipantRoundHeightMissing,
@@ -159,7 +211,12 @@ pub enum CoordinatorError {
     Phase1Setup(setup_utils::Error),
     QueueIsEmpty,
     QueueWaitTimeIncomplete,
+    ReservationMissing,
+    ReservationOverlapping,
+    ReservationSlotsFull,
+    ReservationWindowInvalid,
     ResponseHashSizeInvalid,
+    RoundAdmissionContributorsInsufficient { queued: usize, minimum: usize },
     RoundAggregationFailed,
     RoundAlreadyInitialized,
     RoundAlreadyAggregated,
@@ -171,6 +228,9 @@ pub enum CoordinatorError {
     RoundDoesNotExist,
     RoundFileMissing,
     RoundFileSizeMismatch,
+    RoundFinalizationContributionsInsufficient { finished: usize, minimum: usize },
+    RoundFinalizationDiversityInsufficient { measure: DiversityMeasure, count: usize, minimum: usize },
+    RoundFinalizationTooEarly { not_before: OffsetDateTime },
     RoundHeightIsZero,
     RoundHeightMismatch,
     RoundHeightNotSet,
@@ -179,6 +239,7 @@ pub enum CoordinatorError {
     RoundNotAggregated,
     RoundNotComplete,
     RoundNotReady,
+    RoundNotVerified { pending_chunks: Vec<u64>, pending_verifications: usize },
     RoundNumberOfContributorsUnauthorized,
     RoundNumberOfVerifiersUnauthorized,
     RoundShouldNotExist,
@@ -187,8 +248,18 @@ pub enum CoordinatorError {
     RoundUpdateCorruptedStateOfVerifiers,
     RoundVerifiersMissing,
     RoundVerifiersNotUnique,
+    SeedCommitmentInvalid,
+    SeedCommitmentMismatch,
+    SeedCommittedAfterLock,
     SignatureSchemeIsInsecure,
     StorageCopyFailed,
+    StorageDecryptionFailed,
+    StorageEncryptionFailed,
+    StorageEncryptionKeyInvalid,
+    StorageEncryptionKeyMissing {
+        key_id: String,
+    },
+    StorageEncryptionWithoutColdStorage,
     StorageFailed,
     StorageInitializationFailed,
     StorageLocatorAlreadyExists,
@@ -205,6 +276,8 @@ pub enum CoordinatorError {
     TryFromSliceError(std::array::TryFromSliceError),
     UnauthorizedChunkContributor,
     UnauthorizedChunkVerifier,
+    UploadRateLimited,
+    UploadSizeMismatch { expected: u64, declared: u64 },
     VerificationFailed,
     VerificationOnContributionIdZero,
     VerifierMissing,
@@ -267,6 +340,32 @@ impl fmt::Display for CoordinatorError {
     }
 }
 
+impl CoordinatorError {
+    ///
+    /// Returns the category of this error for the participant who made the request
+    /// which failed with it, so that their client can tell them what to do about it.
+    ///
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            CoordinatorError::CoordinatorStateNotInitialized
+            | CoordinatorError::CurrentRoundAggregating
+            | CoordinatorError::CurrentRoundAggregated
+            | CoordinatorError::CurrentRoundFinished
+            | CoordinatorError::RoundNotReady => ErrorCode::CeremonyPaused,
+            CoordinatorError::ChunkNotLockedOrByWrongParticipant
+            | CoordinatorError::ParticipantDidntLockChunkId
+            | CoordinatorError::ParticipantUnauthorizedForChunkId { .. }
+            | CoordinatorError::ParticipantWasDropped
+            | CoordinatorError::UnauthorizedChunkContributor
+            | CoordinatorError::UnauthorizedChunkVerifier => ErrorCode::ChunkReassigned,
+            CoordinatorError::ParticipantNotInCohort => ErrorCode::NotEligible,
+            CoordinatorError::UploadRateLimited => ErrorCode::TooManyRequests,
+            CoordinatorError::UploadSizeMismatch { .. } => ErrorCode::UploadSizeMismatch,
+            _ => ErrorCode::Other,
+        }
+    }
+}
+
 impl From<CoordinatorError> for anyhow::Error {
     fn from(error: CoordinatorError) -> Self {
         error!("{}", error);
@@ -333,6 +432,16 @@ impl TimeSource for MockTimeSource {
     }
 }
 
+/// The current round with a contribution or a verification added, which is
+/// yet to be written to storage along with the coordinator state.
+struct PreparedRound {
+    round: Round,
+    /// The ID of the contribution which was added or verified.
+    contribution_id: u64,
+    /// The files the round refers to for the contribution or the verification.
+    files: Vec<Locator>,
+}
+
 /// A core structure for operating the Phase 1 ceremony. This struct
 /// is designed to be [Send] + [Sync]. The state of the ceremony is
 /// stored in a [CoordinatorState] object.
@@ -349,6 +458,8 @@ pub struct Coordinator {
     time: Arc<dyn TimeSource>,
     /// Callback to call after aggregation is done
     aggregation_callback: Arc<dyn Fn(Vec<Participant>) -> () + Send + Sync>,
+    /// The times of the uploads checked in the last minute, by participant.
+    upload_attempts: HashMap<Participant, VecDeque<OffsetDateTime>>,
 }
 
 impl Coordinator {
@@ -372,7 +483,33 @@ impl Coordinator {
         time: Arc<dyn TimeSource>,
     ) -> Result<Self, CoordinatorError> {
         // Load an instance of storage.
-        let storage = environment.storage()?;
+        let mut storage = environment.storage()?;
+
+        // Recover the mutations a crash interrupted, before any state is read.
+        for recovery in storage.recover()? {
+            warn!("Recovered storage - {}", recovery);
+        }
+
+        Self::with_storage(environment, signature, storage, time)
+    }
+
+    ///
+    /// Opens the coordinator on the storage of an existing ceremony without
+    /// modifying the storage, for operator tools which inspect the ceremony.
+    ///
+    /// Unlike `new`, this fails if the ceremony has no coordinator state yet.
+    ///
+    pub fn open_read_only(environment: Environment, signature: Arc<dyn Signature>) -> Result<Self, CoordinatorError> {
+        let storage = Disk::open_read_only(&environment)?;
+        Self::with_storage(environment, signature, storage, Arc::new(SystemTimeSource::new()))
+    }
+
+    fn with_storage(
+        environment: Environment,
+        signature: Arc<dyn Signature>,
+        storage: Disk,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, CoordinatorError> {
         // Load an instance of coordinator self.
         let state = match storage.get(&Locator::CoordinatorState)? {
             Object::CoordinatorState(state) => state,
@@ -386,6 +523,7 @@ impl Coordinator {
             state,
             time,
             aggregation_callback: Arc::new(|_| ()),
+            upload_attempts: Default::default(),
         })
     }
 
@@ -438,16 +576,154 @@ impl Coordinator {
         Ok(())
     }
 
+    ///
+    /// Bootstraps a new ceremony from a set of pre-generated round 0 challenges.
+    ///
+    /// Instead of running initialization for round 0, this function imports
+    /// the challenges listed in the given manifest into storage, checking each
+    /// of them against its expected size and hash, and initializes the
+    /// coordinator state to round 0. Challenge paths in the manifest are
+    /// resolved relative to the given `challenges_directory`.
+    ///
+    /// On success, returns the onboarding information of the new ceremony.
+    ///
+    /// On failure, this function returns a `CoordinatorError`.
+    ///
+    pub fn bootstrap(
+        &mut self,
+        manifest: &BootstrapManifest,
+        challenges_directory: &Path,
+    ) -> Result<BootstrapSummary, CoordinatorError> {
+        // Check if the deployment is in production, that the signature scheme is secure.
+        if *self.environment.deployment() == Deployment::Production && !self.signature.is_secure() {
+            return Err(CoordinatorError::SignatureSchemeIsInsecure);
+        }
+
+        // Check that the ceremony has not begun yet.
+        if Self::load_current_round_height(&self.storage).is_ok() {
+            return Err(CoordinatorError::RoundAlreadyInitialized);
+        }
+
+        // Check that the manifest has a challenge for every chunk.
+        let challenges = manifest.challenges(&self.environment)?;
+
+        info!("Bootstrapping ceremony from {}", challenges_directory.display());
+        let round_height =
+            self.run_initialization_with(self.time.now_utc(), |environment, storage, round_height, chunk_id| {
+                let challenge = &challenges[chunk_id as usize];
+                let challenge_file = fs_err::read(challenges_directory.join(&challenge.path))?;
+                let challenge_hash = hex::decode(&challenge.hash)?;
+                Initialization::import(
+                    environment,
+                    storage,
+                    round_height,
+                    chunk_id,
+                    &challenge_file,
+                    &challenge_hash,
+                )
+            })?;
+        info!("Bootstrapped ceremony");
+
+        // Initialize the coordinator state to round 0.
+        self.state.initialize(round_height);
+        self.save_state()?;
+
+        Ok(BootstrapSummary {
+            round_height,
+            number_of_chunks: self.environment.number_of_chunks(),
+            verifiers: self.environment.coordinator_verifiers().clone(),
+            challenge_hashes: challenges.iter().map(|challenge| challenge.hash.clone()).collect(),
+        })
+    }
+
     /// Save the current state of the coordinator to storage.
     pub fn save_state(&mut self) -> Result<(), CoordinatorError> {
         self.state.save(&mut self.storage)
     }
 
+    ///
+    /// Saves the given round and the current state of the coordinator to storage,
+    /// along with the rest of the given mutation, through the write-ahead log, so
+    /// that a crash cannot leave the round and the coordinator state disagreeing.
+    ///
+    fn save_round_and_state(&mut self, entry: WalEntry, round: Round) -> Result<(), CoordinatorError> {
+        let round_locator = Locator::RoundState {
+            round_height: round.round_height(),
+        };
+        let entry = entry
+            .writes(&self.storage, round_locator, WalObject::RoundState(round))?
+            .writes(
+                &self.storage,
+                Locator::CoordinatorState,
+                WalObject::CoordinatorState(self.state.clone()),
+            )?;
+        self.storage.write_ahead(entry)
+    }
+
+    ///
+    /// Returns the events in the event log which occurred at or after the given time,
+    /// or all of the events if no time is given.
+    ///
+    pub fn events(&self, since: Option<OffsetDateTime>) -> Result<Vec<CeremonyEvent>, CoordinatorError> {
+        let events = self.storage.events()?;
+        Ok(match since {
+            Some(since) => events.into_iter().filter(|event| event.timestamp >= since).collect(),
+            None => events,
+        })
+    }
+
+    ///
+    /// Returns the page of the contribution history which matches the given query,
+    /// as recorded in the event log.
+    ///
+    pub fn contributions(&self, query: &ContributionsQuery) -> Result<ContributionPage, CoordinatorError> {
+        contribution_page(&self.storage.events()?, query)
+    }
+
+    ///
+    /// Returns the sizes and timings of the contributions recorded in the event log,
+    /// or only of those to the given round.
+    ///
+    pub fn chunk_timings(&self, round_height: Option<u64>) -> Result<Vec<ChunkTiming>, CoordinatorError> {
+        Ok(chunk_timings(&self.storage.events()?, round_height))
+    }
+
+    ///
+    /// Appends an event of the given kind in the current round to the event log.
+    ///
+    /// The event log is only used for analysis, so a failure to append to it
+    /// is logged and does not fail the operation which caused the event.
+    ///
+    fn record_event(&mut self, kind: EventKind) {
+        let event = CeremonyEvent::new(self.time.now_utc(), self.state.current_round_height(), kind);
+        if let Err(error) = self.storage.append_event(&event) {
+            warn!("Failed to append {:?} to the event log: {}", event, error);
+        }
+    }
+
+    ///
+    /// Copies the given verified contribution to the artifact replicas, as the challenge
+    /// of the next contribution.
+    ///
+    /// Contributors fall back to the coordinator for a challenge which is missing from a
+    /// replica, so a failure to replicate is logged and does not fail the operation.
+    ///
+    fn replicate_challenge(&self, locator: ContributionLocator) {
+        if let Err(error) = self.storage.replicate_challenge(&locator) {
+            warn!("Failed to replicate the challenge {:?}: {}", locator, error);
+        }
+    }
+
     ///
     /// Runs a set of operations to update the coordinator state to reflect
     /// newly finished, dropped, or banned participants.
     ///
     pub fn update(&mut self) -> Result<(), CoordinatorError> {
+        // Open the ceremony to the public once the rounds of the cohort are scheduled.
+        if self.state.is_public_phase_due(self.time.as_ref()) {
+            self.open_public_phase_with(true)?;
+        }
+
         // Process ceremony updates for the current round and queue.
         let (is_current_round_finished, is_current_round_aggregated) = {
             // Acquire the state write lock.
@@ -458,17 +734,26 @@ impl Coordinator {
             self.save_state()?;
 
             // Update the state of the queue.
-            self.state.update_queue()?;
+            self.state.update_queue(self.time.as_ref())?;
             self.save_state()?;
 
             // Update the state of current round contributors.
             self.state.update_current_contributors(self.time.as_ref())?;
             self.save_state()?;
 
+            // Forget the uploads which no longer count towards the rate limit.
+            self.evict_upload_attempts();
+
             // Drop disconnected participants from the current round.
             for drop in self.state.update_dropped_participants(self.time.as_ref())? {
                 // Update the round to reflect the coordinator state changes.
                 self.drop_participant_from_storage(&drop)?;
+
+                let participant = match &drop {
+                    DropParticipant::DropCurrent(data) => data.participant.clone(),
+                    DropParticipant::DropQueue(data) => data.participant.clone(),
+                };
+                self.record_event(EventKind::ParticipantTimedOut { participant });
             }
             self.save_state()?;
 
@@ -476,7 +761,9 @@ impl Coordinator {
             self.save_state()?;
 
             // Ban any participants who meet the coordinator criteria.
-            self.state.update_banned_participants()?;
+            for participant in self.state.update_banned_participants()? {
+                self.record_event(EventKind::ParticipantBanned { participant });
+            }
             self.save_state()?;
 
             // Check if the current round is finished and if the current round is aggregated.
@@ -491,19 +778,35 @@ impl Coordinator {
         let (is_current_round_aggregated, is_precommit_next_round_ready) = {
             // Check if the coordinator should aggregate, and attempt aggregation.
             if is_current_round_finished && !is_current_round_aggregated {
-                // Aggregate the current round.
-                self.try_aggregate()?;
-
-                // Update the metrics for the current round and participants.
-                self.state.update_round_metrics();
-                self.save_state()?;
-
-                match self.state.current_round_finished_contributors() {
-                    Ok(contributors) => {
-                        (self.aggregation_callback)(contributors);
+                // Wait until the finalization lock of the current round, if any, is released.
+                match self.check_finalization_policies() {
+                    // Contributions are never added to a finished round, so the lock can not be released.
+                    Err(CoordinatorError::RoundFinalizationContributionsInsufficient { finished, minimum }) => {
+                        error!(
+                            "Current round is finished with {} of the {} contributors its finalization lock \
+                            requires, and cannot be finalized until an administrator resets the round",
+                            finished, minimum
+                        );
+                    }
+                    Err(error) => {
+                        info!("Current round is finished but cannot be finalized yet: {:?}", error);
                     }
-                    Err(e) => {
-                        tracing::error!("Failed to get current round finished contributors: {}", e);
+                    Ok(()) => {
+                        // Aggregate the current round.
+                        self.try_aggregate()?;
+
+                        // Update the metrics for the current round and participants.
+                        self.state.update_round_metrics();
+                        self.save_state()?;
+
+                        match self.state.current_round_finished_contributors() {
+                            Ok(contributors) => {
+                                (self.aggregation_callback)(contributors);
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to get current round finished contributors: {}", e);
+                            }
+                        }
                     }
                 }
             }
@@ -630,6 +933,36 @@ impl Coordinator {
         Ok(())
     }
 
+    ///
+    /// Returns whether the given participant may join the queue in the
+    /// current phase of the ceremony, and when it opens to the public.
+    ///
+    #[inline]
+    pub fn eligibility(&self, participant: &Participant) -> ContributorEligibility {
+        self.state.eligibility(participant)
+    }
+
+    ///
+    /// Opens the ceremony to the public before its scheduled time, ending
+    /// the rounds which are restricted to the trusted cohort.
+    ///
+    #[inline]
+    pub fn open_public_phase(&mut self) -> Result<(), CoordinatorError> {
+        self.open_public_phase_with(false)
+    }
+
+    fn open_public_phase_with(&mut self, scheduled: bool) -> Result<(), CoordinatorError> {
+        if self.state.open_public_phase(self.time.as_ref())? {
+            info!("Opened the ceremony to the public");
+            self.record_event(EventKind::PublicPhaseOpened { scheduled });
+
+            // Save the coordinator state in storage.
+            self.save_state()?;
+        }
+
+        Ok(())
+    }
+
     ///
     /// Removes the given participant from the queue if they are in the queue.
     ///
@@ -644,6 +977,109 @@ impl Coordinator {
         Ok(())
     }
 
+    ///
+    /// Reserves the requested time window for the given contributor, in which it is
+    /// scheduled for a round ahead of the contributors in the queue without a reservation.
+    ///
+    /// The contributor still has to join the queue to contribute, and is scheduled
+    /// for the next round which starts while its window is open.
+    ///
+    #[tracing::instrument(
+        level = "error",
+        skip(self, request),
+        fields(participant = %participant),
+        err
+    )]
+    pub fn reserve_slot(
+        &mut self,
+        participant: &Participant,
+        request: &SlotReservationRequest,
+    ) -> Result<SlotReservation, CoordinatorError> {
+        let timestamp = |timestamp| {
+            OffsetDateTime::from_unix_timestamp(timestamp).map_err(|_| CoordinatorError::ReservationWindowInvalid)
+        };
+        let reservation = self.state.reserve_slot(
+            participant,
+            timestamp(request.starts_at)?,
+            timestamp(request.ends_at)?,
+            self.time.as_ref(),
+        )?;
+        info!(
+            "Reserved the time window from {} to {} for {}",
+            reservation.starts_at, reservation.ends_at, participant
+        );
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(reservation.to_response())
+    }
+
+    ///
+    /// Records the commitment of the given contributor to its seed, before it locks any
+    /// chunk of the current round, and returns the receipt signed by the coordinator.
+    ///
+    /// Committing again to the same seed returns a receipt with the time of the first
+    /// commitment, so that a contributor can retry a commitment whose receipt it did not get.
+    ///
+    pub fn commit_seed(
+        &mut self,
+        signing_key: &SigningKey,
+        participant: &Participant,
+        request: &SeedCommitment,
+    ) -> anyhow::Result<SignedSeedCommitmentReceipt> {
+        if !participant.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor.into());
+        }
+        let is_retry = self.state.seed_commitment(participant).is_some();
+        let receipt = self
+            .state
+            .commit_seed(participant, &request.commitment, self.time.as_ref())?;
+
+        if !is_retry {
+            info!("{} committed to its seed for round {}", participant, receipt.round_height);
+            self.record_event(EventKind::SeedCommitted {
+                participant: participant.clone(),
+                commitment: receipt.commitment.clone(),
+            });
+
+            // Save the coordinator state in storage.
+            self.save_state()?;
+        }
+
+        let signature = self.signature.sign(signing_key, &to_canonical_json(&receipt)?)?;
+        Ok(SignedSeedCommitmentReceipt {
+            receipt,
+            signature_scheme: self.signature.name(),
+            signature,
+        })
+    }
+
+    ///
+    /// Cancels the time window with the given ID the given contributor reserved.
+    ///
+    #[inline]
+    pub fn cancel_reservation(&mut self, participant: &Participant, id: u64) -> Result<(), CoordinatorError> {
+        self.state.cancel_reservation(participant, id)?;
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(())
+    }
+
+    ///
+    /// Returns the time windows the given contributor reserved, which have not ended yet.
+    ///
+    pub fn reservations(&self, participant: &Participant) -> Vec<SlotReservation> {
+        self.state
+            .reservations()
+            .iter()
+            .filter(|reservation| reservation.participant == *participant)
+            .map(|reservation| reservation.to_response())
+            .collect()
+    }
+
     ///
     /// Drops the given participant from the ceremony.
     ///
@@ -655,11 +1091,12 @@ impl Coordinator {
         // Drop the participant from the ceremony.
         let drop = self.state.drop_participant(participant, self.time.as_ref())?;
 
-        // Update the round to reflect the coordinator state change.
+        // Update the round to reflect the coordinator state change, and save both in storage.
         self.drop_participant_from_storage(&drop)?;
 
-        // Save the coordinator state in storage.
-        self.save_state()?;
+        self.record_event(EventKind::ParticipantDropped {
+            participant: participant.clone(),
+        });
 
         Ok(())
     }
@@ -672,11 +1109,12 @@ impl Coordinator {
         // Ban the participant from the ceremony.
         let drop = self.state.ban_participant(participant, self.time.as_ref())?;
 
-        // Update the round on disk to reflect the coordinator state change.
+        // Update the round on disk to reflect the coordinator state change, and save both in storage.
         self.drop_participant_from_storage(&drop)?;
 
-        // Save the coordinator state in storage.
-        self.save_state()?;
+        self.record_event(EventKind::ParticipantBanned {
+            participant: participant.clone(),
+        });
 
         Ok(())
     }
@@ -695,6 +1133,85 @@ impl Coordinator {
         Ok(())
     }
 
+    ///
+    /// Appends the given operation of an administrator under the given operation ID
+    /// with the identity of the administrator to the audit log, signed with the given
+    /// signing key, and then applies it.
+    ///
+    /// The record is appended before the operation is applied, so that no operation is
+    /// applied without being in the audit log. If the operation was already recorded
+    /// under this operation ID, this function returns the original record without
+    /// applying the operation again, even if applying it failed the first time, so a
+    /// failed operation is retried under a new operation ID. Reusing an operation ID
+    /// for another operation returns [CoordinatorError::AdminOperationIdReused].
+    ///
+    pub fn administer(
+        &mut self,
+        signing_key: &SigningKey,
+        admin: &str,
+        operation_id: &str,
+        operation: AdminOperation,
+    ) -> anyhow::Result<AdminOutcome> {
+        if operation_id.is_empty() {
+            return Err(CoordinatorError::AdminOperationIdMissing.into());
+        }
+        let records = self.storage.audit_records()?;
+        if let Some(signed) = find_operation(&records, operation_id, admin, &operation)? {
+            debug!("Acknowledging a retried operation {}", operation_id);
+            return Ok(AdminOutcome::AlreadyApplied(signed.clone()));
+        }
+
+        // Unlike the event log, the audit log is the record of the operation,
+        // so a failure to append to it fails the operation before it is applied.
+        let record = AuditRecord {
+            operation_id: operation_id.to_string(),
+            admin: admin.to_string(),
+            timestamp: self.time.now_utc(),
+            round_height: self.state.current_round_height(),
+            operation,
+        };
+        let signature = self.signature.sign(signing_key, &to_canonical_json(&record)?)?;
+        let signed = SignedAuditRecord {
+            record,
+            signature_scheme: self.signature.name(),
+            signature,
+        };
+        self.storage.append_audit_record(&signed)?;
+
+        match &signed.record.operation {
+            AdminOperation::BanParticipant { participant } => self.ban_participant(participant)?,
+            AdminOperation::UnbanParticipant { participant } => self.unban_participant(participant)?,
+            AdminOperation::DropParticipant { participant } => self.drop_participant(participant)?,
+            AdminOperation::ResetRound => self.reset_round()?,
+            AdminOperation::EnableManualLock => self.enable_manual_lock()?,
+            AdminOperation::DisableManualLock => self.disable_manual_lock()?,
+            AdminOperation::PrioritizeVerification {
+                chunk_id,
+                contribution_id,
+            } => self.mark_verification_urgent(&Task::new(*chunk_id, *contribution_id))?,
+            AdminOperation::OpenPublicPhase => self.open_public_phase()?,
+        }
+
+        Ok(AdminOutcome::Applied(signed))
+    }
+
+    ///
+    /// Returns the records in the audit log, in the order the operations were applied.
+    ///
+    pub fn audit_log(&self) -> Result<Vec<SignedAuditRecord>, CoordinatorError> {
+        self.storage.audit_records()
+    }
+
+    ///
+    /// Wraps the data keys of the archived contribution files with the
+    /// active key of the storage encryption, after a key rotation.
+    ///
+    /// Returns the number of archived contribution files which were rewrapped.
+    ///
+    pub fn rewrap_archives(&self) -> Result<usize, CoordinatorError> {
+        self.storage.rewrap_archives()
+    }
+
     ///
     /// Returns `true` if the manual lock for transitioning to the next round is enabled.
     ///
@@ -832,6 +1349,40 @@ impl Coordinator {
         Self::load_current_round(&self.storage)
     }
 
+    ///
+    /// Returns an error if the finalization lock of the current round, or the
+    /// diversity policy of the environment, does not yet permit the current
+    /// round to be finalized.
+    ///
+    fn check_finalization_policies(&self) -> Result<(), CoordinatorError> {
+        self.state.check_finalization_lock(self.time.as_ref())?;
+
+        if let Some(policy) = self.environment.diversity_policy() {
+            let round = self.current_round()?;
+            let started_at = round.started_at().ok_or(CoordinatorError::RoundNotReady)?;
+            self.state
+                .check_diversity_policy(policy, started_at, self.time.as_ref())?;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Checks the transcript of every round up to the current round against the
+    /// key usage policy, so that no contributor identity contributed more often
+    /// in a round than the policy permits.
+    ///
+    /// If a round of the transcript violates the policy, returns a `CoordinatorError`.
+    ///
+    pub fn verify_key_usage(&self) -> Result<(), CoordinatorError> {
+        let current_round_height = Self::load_current_round_height(&self.storage)?;
+        for round_height in 1..=current_round_height {
+            let round = Self::load_round(&self.storage, round_height)?;
+            round.verify_key_usage(self.environment.key_usage_policy())?;
+        }
+        Ok(())
+    }
+
     ///
     /// Returns the round state corresponding to the given height from storage.
     ///
@@ -906,16 +1457,34 @@ impl Coordinator {
             return Err(CoordinatorError::PreviousContributionMissing { current_task });
         }
 
+        // Check that the contributor may still use its key in this round.
+        if let Err(error) = round.check_key_usage(
+            participant,
+            current_task.chunk_id(),
+            self.environment.key_usage_policy(),
+        ) {
+            self.state
+                .rollback_pending_task(participant, current_task, &*self.time)?;
+            return Err(error);
+        }
+
         debug!("Locking chunk {} for {}", current_task.chunk_id(), participant);
-        match self.try_lock_chunk(current_task.chunk_id(), participant) {
+        match self.prepare_lock_chunk(current_task.chunk_id(), participant) {
             // Case 1 - Participant acquired lock, return the locator.
-            Ok(locked_locators) => {
+            Ok((round, locked_locators)) => {
                 trace!("Incrementing the number of locks held by {}", participant);
                 self.state
                     .acquired_lock(participant, current_task.chunk_id(), self.time.as_ref())?;
 
-                // Save the coordinator state in storage.
-                self.save_state()?;
+                // Save the updated round and the coordinator state in storage.
+                let entry = WalEntry::new(format!("lock chunk {} for {}", current_task.chunk_id(), participant));
+                self.save_round_and_state(entry, round)?;
+
+                self.record_event(EventKind::ChunkAssigned {
+                    participant: participant.clone(),
+                    chunk_id: current_task.chunk_id(),
+                    contribution_id: current_task.contribution_id(),
+                });
 
                 info!("Acquired lock on chunk {} for {}", current_task.chunk_id(), participant);
                 Ok((current_task.chunk_id(), locked_locators))
@@ -937,23 +1506,187 @@ impl Coordinator {
         }
     }
 
-    /// Returns previous contribution, current contribution and next contribution paths
-    pub fn get_chunk_locators_for_verifier(
-        &self,
-        participant: &Participant,
-        chunk_id: u64,
-        contribution_id: u64,
-    ) -> Result<LockedLocators, CoordinatorError> {
-        if !participant.is_verifier() {
-            return Err(CoordinatorError::ExpectedVerifier);
+    ///
+    /// Pins the given chunk IDs for the given contributor, who then locks them before the
+    /// rest of its assigned chunks, so contributors with large hardware can take the chunks
+    /// they have resources for first.
+    ///
+    /// Whether pinning is honored is up to the coordinator, which pins at most
+    /// [Environment::maximum_pinned_chunks] chunks.
+    ///
+    /// On success, this function returns the chunk IDs which were pinned.
+    ///
+    /// On failure, it returns a `CoordinatorError`.
+    ///
+    #[tracing::instrument(
+        level = "error",
+        skip(self, chunk_ids),
+        fields(participant = %participant),
+        err
+    )]
+    pub fn pin_chunks(&mut self, participant: &Participant, chunk_ids: &[u64]) -> Result<Vec<u64>, CoordinatorError> {
+        if !participant.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor);
         }
-        let round = Self::load_current_round(&self.storage)?;
-        round.get_chunk_locators_for_verifier(&self.storage, participant, chunk_id, contribution_id)
-    }
 
-    /// Initialize the files for the next challenge
-    pub fn initialize_verifier_response_files(
-        &mut self,
+        // Check that the participant is in the current round, and has not been dropped or finished.
+        if !self.state.is_current_contributor(participant) {
+            return Err(CoordinatorError::ParticipantUnauthorized);
+        }
+
+        let pinned = self.state.pin_chunks(participant, chunk_ids)?;
+        info!("Pinned chunks {:?} for {}", pinned, participant);
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(pinned)
+    }
+
+    ///
+    /// Records the class of hardware the given contributor computes on, which the
+    /// coordinator estimates the compute time of its contributions from.
+    ///
+    /// On failure, it returns a `CoordinatorError`.
+    ///
+    #[tracing::instrument(
+        level = "error",
+        skip(self),
+        fields(participant = %participant),
+        err
+    )]
+    pub fn report_hardware_class(
+        &mut self,
+        participant: &Participant,
+        hardware_class: &str,
+    ) -> Result<(), CoordinatorError> {
+        if !participant.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor);
+        }
+
+        self.state.set_hardware_class(participant, hardware_class);
+        info!("Recorded hardware class {} for {}", hardware_class, participant);
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(())
+    }
+
+    ///
+    /// Records the machine the given contributor runs on non-interactively, which
+    /// lets auditors tie its contributions to the run which made them.
+    ///
+    /// On failure, it returns a `CoordinatorError`.
+    ///
+    #[tracing::instrument(
+        level = "error",
+        skip(self, machine),
+        fields(participant = %participant),
+        err
+    )]
+    pub fn report_machine(
+        &mut self,
+        participant: &Participant,
+        machine: MachineAttestation,
+    ) -> Result<(), CoordinatorError> {
+        if !participant.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor);
+        }
+
+        info!("Recorded machine {:?} for {}", machine, participant);
+        self.state.set_machine_attestation(participant, machine);
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(())
+    }
+
+    ///
+    /// Records where the given contributor is located on the network, as reported
+    /// by the contributor or derived from its IP address, which counts towards the
+    /// diversity of the contributors of a round.
+    ///
+    /// On failure, it returns a `CoordinatorError`.
+    ///
+    #[tracing::instrument(
+        level = "error",
+        skip(self, origin),
+        fields(participant = %participant),
+        err
+    )]
+    pub fn report_contributor_origin(
+        &mut self,
+        participant: &Participant,
+        origin: ContributorOrigin,
+    ) -> Result<(), CoordinatorError> {
+        if !participant.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor);
+        }
+
+        debug!("Recorded origin {:?} for {}", origin, participant);
+        self.state.set_contributor_origin(participant, origin);
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(())
+    }
+
+    ///
+    /// Returns the estimated time the given contributor takes to compute a contribution,
+    /// calibrated from the contributions computed on the class of hardware it reported.
+    ///
+    /// Returns `None` if the contributor did not report its hardware class, or if no
+    /// contribution was computed on that class of hardware yet.
+    ///
+    pub fn estimated_compute_time(&self, participant: &Participant) -> Option<time::Duration> {
+        let hardware_class = self.state.hardware_class(participant)?;
+        let seconds = self.state.estimated_compute_seconds(hardware_class)?;
+        Some(time::Duration::seconds(seconds as i64))
+    }
+
+    ///
+    /// Fills in the estimated compute time of the given contributor and the lease of
+    /// the lock it holds on the chunk of the given lock response, so that it can tell
+    /// whether it is likely to contribute to the chunk in time.
+    ///
+    /// The lease ends once the lock was held for the participant lock timeout, as
+    /// measured on the clock of the coordinator. The lease is cleared if the
+    /// contributor does not hold a lock on the chunk.
+    ///
+    pub fn fill_lock_lease(&self, participant: &Participant, response: &mut LockResponse) {
+        response.estimated_seconds = self
+            .estimated_compute_time(participant)
+            .map(|estimate| estimate.whole_seconds() as u64);
+
+        let lock = self
+            .state
+            .current_participant_info(participant)
+            .and_then(|info| info.locked_chunks().get(&response.chunk_id));
+        let expires_at = lock.map(|lock| *lock.lock_time() + self.environment.participant_lock_timeout());
+        response.lease_seconds = expires_at.map(|expires_at| (expires_at - self.time.now_utc()).whole_seconds().max(0) as u64);
+        response.lease_expires_at = expires_at.map(|expires_at| expires_at.unix_timestamp());
+    }
+
+    /// Returns previous contribution, current contribution and next contribution paths
+    pub fn get_chunk_locators_for_verifier(
+        &self,
+        participant: &Participant,
+        chunk_id: u64,
+        contribution_id: u64,
+    ) -> Result<LockedLocators, CoordinatorError> {
+        if !participant.is_verifier() {
+            return Err(CoordinatorError::ExpectedVerifier);
+        }
+        let round = Self::load_current_round(&self.storage)?;
+        round.get_chunk_locators_for_verifier(&self.storage, participant, chunk_id, contribution_id)
+    }
+
+    /// Initialize the files for the next challenge
+    pub fn initialize_verifier_response_files(
+        &mut self,
         participant: &Participant,
         chunk_id: u64,
         locators: &LockedLocators,
@@ -995,7 +1728,7 @@ impl Coordinator {
         }
 
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1030,13 +1763,6 @@ impl Coordinator {
             // Move the task to the disposed tasks of the contributor.
             self.state.disposed_task(participant, &task, self.time.as_ref())?;
 
-            // Remove the response file from storage.
-            let response = ContributionLocator::new(round_height, chunk_id, contribution_id, false);
-            self.storage.remove(&Locator::ContributionFile(response.clone()))?;
-
-            // Save the coordinator state in storage.
-            self.save_state()?;
-
             debug!("Removing lock for disposed task {} {}", chunk_id, contribution_id);
 
             // Fetch the current round from storage.
@@ -1046,54 +1772,628 @@ impl Coordinator {
             // Release the lock on this chunk from the contributor.
             round.chunk_mut(chunk_id)?.set_lock_holder_unsafe(None);
 
-            // Save the updated round to storage.
-            self.storage.update(
-                &Locator::RoundState {
-                    round_height: round.round_height(),
-                },
-                Object::RoundState(round),
-            )?;
+            // Remove the response file, and save the updated round and the coordinator state in storage.
+            let response = ContributionLocator::new(round_height, chunk_id, contribution_id, false);
+            let entry = WalEntry::new(format!(
+                "dispose of the response of chunk {} contribution {}",
+                chunk_id, contribution_id
+            ))
+            .removes(Locator::ContributionFile(response));
+            self.save_round_and_state(entry, round)?;
 
             return Ok(response);
         }
 
-        // Check if the participant has this chunk ID in a pending task.
-        if let Some(task) = self.state.lookup_pending_task(participant, chunk_id)?.cloned() {
-            debug!("Adding contribution for chunk");
-
-            match self.add_contribution(chunk_id, participant) {
-                // Case 1 - Participant added contribution, return the response file locator.
-                Ok((locator, contribution_id)) => {
-                    trace!("Release the lock on chunk");
-                    let completed_task = Task::new(chunk_id, contribution_id);
-                    self.state
-                        .completed_task(participant, &completed_task, self.time.as_ref())?;
+        // Check if the participant has this chunk ID in a pending task.
+        if let Some(task) = self.state.lookup_pending_task(participant, chunk_id)?.cloned() {
+            debug!("Adding contribution for chunk");
+
+            match self.prepare_contribution(chunk_id, participant) {
+                // Case 1 - Participant added contribution, return the response file locator.
+                Ok(prepared) => {
+                    trace!("Release the lock on chunk");
+                    let contribution_id = prepared.contribution_id;
+                    let locator = ContributionLocator::new(round_height, chunk_id, contribution_id, false);
+                    let completed_task = Task::new(chunk_id, contribution_id);
+                    self.state
+                        .completed_task(participant, &completed_task, self.time.as_ref())?;
+
+                    // Save the updated round and the coordinator state in storage.
+                    let entry = WalEntry::new(format!(
+                        "add the contribution of {} to chunk {} contribution {}",
+                        participant, chunk_id, contribution_id
+                    ))
+                    .requires(prepared.files);
+                    self.save_round_and_state(entry, prepared.round)?;
+
+                    self.record_event(EventKind::ContributionUploaded {
+                        participant: participant.clone(),
+                        chunk_id,
+                        contribution_id,
+                    });
+
+                    info!("Added contribution");
+                    return Ok(locator);
+                }
+                // Case 2 - Participant failed to add their contribution, remove the contribution file.
+                Err(error) => {
+                    info!("Failed to add a contribution and removing the contribution file");
+                    // Remove the invalid response file from storage.
+                    let response = Locator::ContributionFile(ContributionLocator::new(
+                        round_height,
+                        chunk_id,
+                        task.contribution_id(),
+                        false,
+                    ));
+                    self.storage.remove(&response)?;
+
+                    error!("{}", error);
+                    return Err(error);
+                }
+            }
+        }
+
+        Err(CoordinatorError::ContributionFailed)
+    }
+
+    ///
+    /// Returns the hex encoded hash of the challenge file for the given chunk ID and
+    /// contribution ID in the current round, which is advertised to the contributor
+    /// locking the chunk, so it can check the challenge it downloads.
+    ///
+    /// The hash is taken from the signature of the verifier which produced the challenge,
+    /// if there is one, and computed from the challenge file otherwise.
+    ///
+    pub fn challenge_hash(
+        &self,
+        chunk_id: ChunkId,
+        contribution_id: ContributionId,
+    ) -> Result<String, CoordinatorError> {
+        let (chunk_id, contribution_id) = (chunk_id.0, contribution_id.0);
+        let round_height = Self::load_current_round_height(&self.storage)?;
+
+        let signature_locator = Locator::ContributionFileSignature(ContributionSignatureLocator::new(
+            round_height,
+            chunk_id,
+            contribution_id,
+            true,
+        ));
+        if self.storage.exists(&signature_locator) {
+            let signature: ContributionFileSignature =
+                serde_json::from_slice(&*self.storage.reader(&signature_locator)?)?;
+            if let Some(next_challenge_hash) = signature.get_next_challenge_hash() {
+                return Ok(next_challenge_hash.clone());
+            }
+        }
+
+        let challenge_locator =
+            Locator::ContributionFile(ContributionLocator::new(round_height, chunk_id, contribution_id, true));
+        let challenge_reader = self.storage.reader(&challenge_locator)?;
+        Ok(hex::encode(calculate_hash(challenge_reader.as_ref())))
+    }
+
+    ///
+    /// Returns the BLAKE3 root hash and the bao outboard tree of the challenge file for
+    /// the given chunk ID and contribution ID in the current round. The root hash is
+    /// advertised to the contributor locking the chunk, and the outboard is served to
+    /// it, so it can verify the challenge block by block while downloading it.
+    ///
+    /// The outboard is encoded the first time it is requested, to the `outboards`
+    /// directory of the local base directory.
+    ///
+    pub fn challenge_outboard(
+        &self,
+        chunk_id: ChunkId,
+        contribution_id: ContributionId,
+    ) -> Result<ChallengeOutboard, CoordinatorError> {
+        let challenge_hash = self.challenge_hash(chunk_id, contribution_id)?;
+        let round_height = Self::load_current_round_height(&self.storage)?;
+
+        let challenge_locator =
+            Locator::ContributionFile(ContributionLocator::new(round_height, chunk_id.0, contribution_id.0, true));
+        let challenge_reader = self.storage.reader(&challenge_locator)?;
+        let directory = Path::new(self.environment.local_base_directory()).join("outboards");
+        challenge_outboard(&directory, &challenge_hash, challenge_reader.as_ref())
+    }
+
+    ///
+    /// Returns the digest of the challenges of the current round, which lists the
+    /// hash of every verified contribution of each chunk, so that it can be published
+    /// independently of the coordinator.
+    ///
+    pub fn transcript_digest(&self) -> Result<TranscriptDigest, CoordinatorError> {
+        let round = self.current_round()?;
+
+        let mut challenges = vec![];
+        for chunk in round.chunks() {
+            for (contribution_id, contribution) in chunk.get_contributions() {
+                if contribution.is_verified() {
+                    challenges.push(ChallengeDigest {
+                        chunk_id: chunk.chunk_id(),
+                        contribution_id: *contribution_id,
+                        hash: self.challenge_hash(ChunkId(chunk.chunk_id()), ContributionId(*contribution_id))?,
+                    });
+                }
+            }
+        }
+
+        Ok(TranscriptDigest {
+            round_height: round.round_height(),
+            challenges,
+        })
+    }
+
+    ///
+    /// Returns the latest index of the ceremony, pointing to the digest of the current
+    /// round and the given artifact URLs, signed with the given signing key.
+    ///
+    pub fn latest_index(
+        &self,
+        signing_key: &SigningKey,
+        artifact_urls: Vec<String>,
+    ) -> anyhow::Result<SignedLatestIndex> {
+        let transcript_digest = self.transcript_digest()?;
+        let index = LatestIndex {
+            round_height: transcript_digest.round_height,
+            transcript_digest,
+            artifact_urls,
+            published_at: self.time.now_utc().unix_timestamp(),
+        };
+        let signature = self.signature.sign(signing_key, &to_canonical_json(&index)?)?;
+
+        Ok(SignedLatestIndex {
+            index,
+            signature_scheme: self.signature.name(),
+            signature,
+        })
+    }
+
+    ///
+    /// Advances the ceremony from the given round, or else the current round, to the next
+    /// round: checks that every chunk of the round is verified, aggregates the round,
+    /// generates the challenges of the next round, publishes the signed index of the next
+    /// round to `index_path`, and notifies the contributors.
+    ///
+    /// Each step is skipped if an earlier run already took it, so the operation can be
+    /// retried after a failure. Once the given round was advanced, a retry only publishes
+    /// the index again.
+    ///
+    pub fn advance_round(
+        &mut self,
+        round_height: Option<u64>,
+        signing_key: &SigningKey,
+        artifact_urls: Vec<String>,
+        index_path: &Path,
+    ) -> anyhow::Result<AdvanceRoundReport> {
+        let current_round_height = self.current_round_height()?;
+        let from_round = round_height.unwrap_or(current_round_height);
+        let mut report = AdvanceRoundReport::new(from_round);
+
+        if from_round + 1 == current_round_height {
+            let detail = format!("round {} was already advanced", from_round);
+            report.skipped(AdvanceRoundStep::CheckVerified, detail.clone());
+            report.skipped(AdvanceRoundStep::Aggregate, detail.clone());
+            report.skipped(AdvanceRoundStep::NextRound, detail);
+        } else if from_round != current_round_height {
+            return Err(CoordinatorError::RoundHeightMismatch.into());
+        } else {
+            let round = self.current_round()?;
+            let expected_contributions = round.expected_number_of_contributions();
+            let pending_chunks: Vec<u64> = round
+                .chunks()
+                .iter()
+                .filter(|chunk| !chunk.is_complete(expected_contributions))
+                .map(|chunk| chunk.chunk_id())
+                .collect();
+            if !self.state.is_current_round_finished() || !pending_chunks.is_empty() {
+                return Err(CoordinatorError::RoundNotVerified {
+                    pending_chunks,
+                    pending_verifications: self.state.get_pending_verifications().len(),
+                }
+                .into());
+            }
+            report.taken(
+                AdvanceRoundStep::CheckVerified,
+                format!("all {} chunks of round {} are verified", round.chunks().len(), from_round),
+            );
+
+            // The operator holds the transition with the manual lock, which this operation must respect
+            // too, before the round is aggregated.
+            if self.state.is_manual_lock_enabled() {
+                return Err(CoordinatorError::ManualLockEnabled.into());
+            }
+
+            if self.state.is_current_round_aggregated() {
+                report.skipped(
+                    AdvanceRoundStep::Aggregate,
+                    format!("round {} was already aggregated", from_round),
+                );
+            } else {
+                self.try_aggregate()?;
+                self.state.update_round_metrics();
+                self.save_state()?;
+                match self.state.current_round_finished_contributors() {
+                    Ok(contributors) => (self.aggregation_callback)(contributors),
+                    Err(e) => tracing::error!("Failed to get current round finished contributors: {}", e),
+                }
+                report.taken(AdvanceRoundStep::Aggregate, format!("aggregated round {}", from_round));
+            }
+
+            let next_round_height = self.try_advance(self.time.now_utc())?;
+            report.taken(
+                AdvanceRoundStep::NextRound,
+                format!(
+                    "generated the {} challenges of round {}",
+                    self.current_round()?.chunks().len(),
+                    next_round_height
+                ),
+            );
+        }
+
+        let latest = self.latest_index(signing_key, artifact_urls)?;
+        write_atomically(index_path, &serde_json::to_vec_pretty(&latest)?)?;
+        report.taken(
+            AdvanceRoundStep::PublishIndex,
+            format!(
+                "published the index of round {} to {}",
+                latest.index.round_height,
+                index_path.display()
+            ),
+        );
+
+        let to_round = report.to_round;
+        let notified = self
+            .storage
+            .events()?
+            .iter()
+            .any(|event| event.round_height == to_round && matches!(event.kind, EventKind::RoundAdvanced { .. }));
+        if notified {
+            report.skipped(
+                AdvanceRoundStep::NotifyContributors,
+                format!("the contributors were already notified of round {}", to_round),
+            );
+        } else {
+            let contributors = self.current_round()?.contributors().clone();
+            let detail = format!("notified the {} contributors of round {}", contributors.len(), to_round);
+            self.record_event(EventKind::RoundAdvanced { contributors });
+            report.taken(AdvanceRoundStep::NotifyContributors, detail);
+        }
+
+        Ok(report)
+    }
+
+    ///
+    /// Verifies the given response file against the challenge it was computed on in
+    /// storage, for anyone who wants to spot check a contribution, and returns the
+    /// verdict signed with the given signing key.
+    ///
+    /// Contributions of earlier rounds can be spot checked as long as their round
+    /// is kept in storage. See [spot_check] for the errors of the request. As anyone
+    /// may rely on the verdict, it is only signed with a secure signature scheme.
+    ///
+    pub fn spot_check(
+        &self,
+        signing_key: &SigningKey,
+        request: &SpotCheckRequest,
+        response: &[u8],
+    ) -> anyhow::Result<SignedSpotCheckVerdict> {
+        if !self.signature.is_secure() {
+            return Err(CoordinatorError::SignatureSchemeIsInsecure.into());
+        }
+        if request.contribution_id == 0 {
+            return Err(CoordinatorError::VerificationOnContributionIdZero.into());
+        }
+
+        let challenge_locator = Locator::ContributionFile(ContributionLocator::new(
+            request.round_height,
+            request.chunk_id,
+            request.contribution_id - 1,
+            true,
+        ));
+        if !self.storage.exists(&challenge_locator) {
+            return Err(CoordinatorError::ContributionLocatorMissing.into());
+        }
+        let challenge = self.storage.reader(&challenge_locator)?;
+
+        let verdict = spot_check(
+            &self.environment,
+            request,
+            challenge.as_ref(),
+            response,
+            self.time.now_utc().unix_timestamp(),
+        )?;
+        let signature = self.signature.sign(signing_key, &to_canonical_json(&verdict)?)?;
+
+        Ok(SignedSpotCheckVerdict {
+            verdict,
+            verifier: None,
+            signature_scheme: self.signature.name(),
+            signature,
+        })
+    }
+
+    ///
+    /// Checks the invariants of the ceremony against the given thresholds: that no
+    /// chunk is locked for longer than the lease and its grace period, that the
+    /// verification backlog is small enough, that the coordinator state is readable
+    /// from storage, and that enough disk space is left for the transcript.
+    ///
+    /// A failing check is not an error, but is reported as unhealthy in the report.
+    ///
+    pub fn health(&self, thresholds: &HealthThresholds) -> HealthReport {
+        // Coordinator contributors are never dropped for holding a lock, so their locks are not overdue.
+        let locks: Vec<_> = self
+            .state
+            .current_contributors()
+            .into_iter()
+            .chain(self.state.current_verifiers())
+            .filter(|(participant, _)| !self.state.is_coordinator_contributor(participant))
+            .flat_map(|(participant, info)| {
+                info.locked_chunks()
+                    .values()
+                    .map(|lock| (participant.clone(), lock.chunk_id(), *lock.lock_time()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        HealthReport {
+            checks: vec![
+                check_locks(
+                    &locks,
+                    self.time.now_utc(),
+                    self.environment.participant_lock_timeout(),
+                    thresholds.lock_grace,
+                ),
+                check_verification_backlog(
+                    self.state.get_pending_verifications().len(),
+                    thresholds.max_verification_backlog,
+                ),
+                check_storage(
+                    self.storage
+                        .get(&Locator::CoordinatorState)
+                        .err()
+                        .map(|error| error.to_string()),
+                ),
+                check_disk_headroom(
+                    available_disk_space(Path::new(self.environment.local_base_directory())),
+                    thresholds.min_free_bytes,
+                ),
+            ],
+        }
+    }
+
+    ///
+    /// Quarantines the response file of a contribution in the current round which
+    /// the given verifier rejected, along with a forensic bundle holding the reason
+    /// and the log lines the verifier reported, so that it is kept for analysis.
+    ///
+    /// On success, this function returns the forensic bundle of the response file.
+    ///
+    pub fn report_rejected_contribution(
+        &mut self,
+        participant: &Participant,
+        report: &RejectedContributionReport,
+    ) -> Result<ForensicBundle, CoordinatorError> {
+        // Check that the participant is a verifier.
+        if !participant.is_verifier() {
+            return Err(CoordinatorError::ExpectedVerifier);
+        }
+
+        // Check that the chunk ID is valid.
+        if report.chunk_id >= self.environment.number_of_chunks() {
+            return Err(CoordinatorError::ChunkIdInvalid);
+        }
+
+        let round = Self::load_current_round(&self.storage)?;
+        let contribution = round.chunk(report.chunk_id)?.get_contribution(report.contribution_id)?;
+        let rejection = Rejection {
+            round_height: round.round_height(),
+            chunk_id: report.chunk_id,
+            contribution_id: report.contribution_id,
+            contributor: contribution.get_contributor().clone(),
+            verifier: Some(participant.clone()),
+            reason: report.reason.clone(),
+            logs: report.logs.clone(),
+        };
+        let bundle = self.storage.quarantine(rejection, self.time.now_utc())?;
+
+        warn!(
+            "{} rejected chunk {} contribution {}, quarantined the response: {}",
+            participant, report.chunk_id, report.contribution_id, report.reason
+        );
+        Ok(bundle)
+    }
+
+    ///
+    /// Returns the forensic bundles of the response files which were rejected
+    /// by verification, in the order they were quarantined.
+    ///
+    pub fn quarantined_responses(&self) -> Result<Vec<ForensicBundle>, CoordinatorError> {
+        self.storage.quarantined()
+    }
+
+    ///
+    /// Quarantines a response file the coordinator rejected, logging rather than
+    /// returning any error, so that the rejection is handled either way.
+    ///
+    fn quarantine_response(&mut self, rejection: Rejection) {
+        let (chunk_id, contribution_id) = (rejection.chunk_id, rejection.contribution_id);
+        match self.storage.quarantine(rejection, self.time.now_utc()) {
+            Ok(_) => warn!(
+                "Quarantined the response of chunk {} contribution {}",
+                chunk_id, contribution_id
+            ),
+            Err(error) => error!(
+                "Failed to quarantine the response of chunk {} contribution {}: {}",
+                chunk_id, contribution_id, error
+            ),
+        }
+    }
+
+    ///
+    /// Checks an upload of the given participant for the given chunk ID before
+    /// it is accepted, based on the size the participant declared for it.
+    ///
+    /// Contributors may only upload responses for the chunks they hold the lock
+    /// of, and the declared size must be the size of the response file for the
+    /// chunk, or of the next challenge file for verifiers. This rejects uploads
+    /// before they are written to disk, rather than after.
+    ///
+    /// Returns [CoordinatorError::UploadRateLimited] if the participant made more
+    /// than `maximum_uploads_per_minute` uploads in the last minute.
+    ///
+    pub fn check_upload(
+        &mut self,
+        participant: &Participant,
+        chunk_id: u64,
+        declared_size: u64,
+    ) -> Result<(), CoordinatorError> {
+        // Check that the chunk ID is valid.
+        if chunk_id >= self.environment.number_of_chunks() {
+            return Err(CoordinatorError::ChunkIdInvalid);
+        }
+
+        // Check that the participant did not upload too often, counting rejected uploads too.
+        let maximum = self.environment.maximum_uploads_per_minute();
+        if maximum > 0 {
+            let now = self.time.now_utc();
+            let attempts = self.upload_attempts.entry(participant.clone()).or_default();
+            while matches!(attempts.front(), Some(attempt) if now - *attempt >= time::Duration::minutes(1)) {
+                attempts.pop_front();
+            }
+            if attempts.len() >= maximum {
+                warn!("{} made too many uploads in the last minute", participant);
+                return Err(CoordinatorError::UploadRateLimited);
+            }
+            attempts.push_back(now);
+        }
+
+        // Check that the contributor holds the lock of the chunk.
+        if participant.is_contributor() {
+            if !self.state.is_current_contributor(participant) {
+                return Err(CoordinatorError::ParticipantUnauthorized);
+            }
+            if !Self::load_current_round(&self.storage)?.is_chunk_locked_by(chunk_id, participant) {
+                return Err(CoordinatorError::ChunkNotLockedOrByWrongParticipant);
+            }
+        }
+
+        // Check that the declared size is the size of the file for the chunk.
+        let expected = Object::contribution_file_size(&self.environment, chunk_id, participant.is_verifier());
+        if declared_size != expected {
+            warn!(
+                "{} declared an upload of {} bytes for chunk {}, expected {} bytes",
+                participant, declared_size, chunk_id, expected
+            );
+            return Err(CoordinatorError::UploadSizeMismatch {
+                expected,
+                declared: declared_size,
+            });
+        }
+
+        self.record_event(EventKind::UploadStarted {
+            participant: participant.clone(),
+            chunk_id,
+            size: declared_size,
+        });
+        Ok(())
+    }
+
+    ///
+    /// Removes the upload attempts of contributors who finished the round, and of
+    /// participants who made no upload in the last minute, so that the attempts
+    /// do not accumulate over the ceremony.
+    ///
+    fn evict_upload_attempts(&mut self) {
+        let now = self.time.now_utc();
+        let state = &self.state;
+        self.upload_attempts.retain(|participant, attempts| {
+            !state.is_finished_contributor(participant)
+                && matches!(attempts.back(), Some(attempt) if now - *attempt < time::Duration::minutes(1))
+        });
+    }
+
+    ///
+    /// Returns the task of the upload processed under the given idempotency key
+    /// in the current round, if the given participant made it for the given chunk ID.
+    ///
+    /// Uploads retried after the coordinator processed them are acknowledged
+    /// based on this, without writing the uploaded file to storage again.
+    ///
+    /// Returns [CoordinatorError::IdempotencyKeyReused] if the key was used
+    /// by another participant, or for another chunk.
+    ///
+    pub fn processed_upload(
+        &self,
+        participant: &Participant,
+        chunk_id: u64,
+        idempotency_key: &str,
+    ) -> Result<Option<Task>, CoordinatorError> {
+        match self.state.lookup_processed_upload(idempotency_key) {
+            Some(upload) if upload.participant == *participant && upload.task.contains(chunk_id) => {
+                Ok(Some(upload.task))
+            }
+            Some(_) => Err(CoordinatorError::IdempotencyKeyReused),
+            None => Ok(None),
+        }
+    }
+
+    ///
+    /// Attempts to add a contribution like [Coordinator::try_contribute], for the
+    /// response file uploaded with the given idempotency key.
+    ///
+    /// If the contribution was already added under this key, this function
+    /// acknowledges the retry with the response file locator of the contribution,
+    /// without checking the response file again.
+    ///
+    pub fn try_contribute_idempotent(
+        &mut self,
+        participant: &Participant,
+        chunk_id: u64,
+        idempotency_key: &str,
+    ) -> Result<ContributionLocator, CoordinatorError> {
+        if let Some(task) = self.processed_upload(participant, chunk_id, idempotency_key)? {
+            debug!("Acknowledging a retried contribution to chunk {}", chunk_id);
+            let round_height = Self::load_current_round_height(&self.storage)?;
+            return Ok(ContributionLocator::new(round_height, chunk_id, task.contribution_id(), false));
+        }
+
+        let locator = self.try_contribute(participant, chunk_id)?;
 
-                    // Save the coordinator state in storage.
-                    self.save_state()?;
+        let task = Task::new(chunk_id, locator.contribution_id());
+        self.state.add_processed_upload(idempotency_key.to_string(), participant, task);
+        self.save_state()?;
 
-                    info!("Added contribution");
-                    return Ok(locator);
-                }
-                // Case 2 - Participant failed to add their contribution, remove the contribution file.
-                Err(error) => {
-                    info!("Failed to add a contribution and removing the contribution file");
-                    // Remove the invalid response file from storage.
-                    let response = Locator::ContributionFile(ContributionLocator::new(
-                        round_height,
-                        chunk_id,
-                        task.contribution_id(),
-                        false,
-                    ));
-                    self.storage.remove(&response)?;
+        Ok(locator)
+    }
 
-                    error!("{}", error);
-                    return Err(error);
-                }
+    ///
+    /// Attempts to add a verification like [Coordinator::try_verify], for the
+    /// next challenge file uploaded with the given idempotency key.
+    ///
+    /// If the verification was already added under this key, this function
+    /// acknowledges the retry without checking the next challenge file again.
+    ///
+    pub fn try_verify_idempotent(
+        &mut self,
+        participant: &Participant,
+        task: &Task,
+        idempotency_key: &str,
+    ) -> Result<(), CoordinatorError> {
+        if let Some(processed) = self.processed_upload(participant, task.chunk_id(), idempotency_key)? {
+            if processed != *task {
+                return Err(CoordinatorError::IdempotencyKeyReused);
             }
+            debug!("Acknowledging a retried verification of {}", task);
+            return Ok(());
         }
 
-        Err(CoordinatorError::ContributionFailed)
+        self.try_verify(participant, task)?;
+
+        self.state.add_processed_upload(idempotency_key.to_string(), participant, *task);
+        self.save_state()?;
+
+        Ok(())
     }
 
     ///
@@ -1120,7 +2420,7 @@ impl Coordinator {
         }
 
         // Check that the chunk ID is valid.
-        if task.chunk_id() > self.environment.number_of_chunks() {
+        if task.chunk_id() >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1146,16 +2446,54 @@ impl Coordinator {
             task.contribution_id()
         );
 
-        match self.verify_contribution(task, participant) {
+        match self.prepare_verification(task, participant) {
             // Case 1 - Participant verified contribution, return the response file locator.
-            Ok(contribution_id) => {
-                if contribution_id != task.contribution_id() {
+            Ok(prepared) => {
+                if prepared.contribution_id != task.contribution_id() {
                     return Err(CoordinatorError::ContributionIdMismatch);
                 }
                 self.state.completed_task(participant, task, self.time.as_ref())?;
 
-                // Save the coordinator state in storage.
-                self.save_state()?;
+                // Defer the full verification of contributions accepted by sampling.
+                if self.environment.verification_mode() != VerificationMode::Full {
+                    let contribution = prepared
+                        .round
+                        .chunk(task.chunk_id())?
+                        .get_contribution(task.contribution_id())?;
+                    if let Some(contributor) = contribution.get_contributor() {
+                        self.state.add_deferred_verification(task, contributor)?;
+                    }
+                }
+
+                // The verified contribution is the next challenge, unless it is the final contribution.
+                let round_height = prepared.round.round_height();
+                let is_final_contribution =
+                    task.contribution_id() == prepared.round.expected_number_of_contributions() - 1;
+
+                // Save the updated round and the coordinator state in storage.
+                let entry = WalEntry::new(format!(
+                    "verify chunk {} contribution {} by {}",
+                    task.chunk_id(),
+                    task.contribution_id(),
+                    participant
+                ))
+                .requires(prepared.files);
+                self.save_round_and_state(entry, prepared.round)?;
+
+                if !is_final_contribution {
+                    self.replicate_challenge(ContributionLocator::new(
+                        round_height,
+                        task.chunk_id(),
+                        task.contribution_id(),
+                        true,
+                    ));
+                }
+
+                self.record_event(EventKind::ContributionVerified {
+                    participant: participant.clone(),
+                    chunk_id: task.chunk_id(),
+                    contribution_id: task.contribution_id(),
+                });
 
                 info!("Added verification from {} for chunk {}", participant, task.chunk_id());
                 Ok(())
@@ -1195,6 +2533,147 @@ impl Coordinator {
         }
     }
 
+    ///
+    /// Returns the tasks of the current round which were accepted by a sampled verification
+    /// and are pending a full verification, with their contributors, in the order they are
+    /// handed to verifiers in. They are all urgent, as the round cannot finish before every
+    /// contribution in it is fully verified.
+    ///
+    pub fn prioritized_deferred_verifications(&self) -> Vec<(Task, Participant)> {
+        let mut tasks: Vec<(Task, Participant)> = self
+            .state
+            .get_deferred_verifications()
+            .iter()
+            .map(|(task, contributor)| (*task, contributor.clone()))
+            .collect();
+        tasks.sort_by_key(|(task, _)| task.to_tuple());
+        tasks
+    }
+
+    ///
+    /// Runs a full verification of the contribution of the given contributor for the given task,
+    /// which was accepted by a sampled verification.
+    ///
+    /// This only reads from storage, so that verifiers can run it outside of `update`, and the
+    /// outcome is recorded with `Coordinator::finish_deferred_verification`. Contributions which
+    /// were disposed of or replaced since they were accepted are not verified again.
+    ///
+    /// This is logged separately from the initial verifications,
+    /// under the `deferred-verification` span.
+    ///
+    pub fn run_deferred_verification(&self, task: &Task, contributor: &Participant) -> Result<(), CoordinatorError> {
+        let span = info_span!("deferred-verification");
+        let _enter = span.enter();
+
+        // Skip tasks which were disposed of since they were accepted.
+        if self.state.get_deferred_verifications().get(task) != Some(contributor) {
+            debug!("Skipping the full verification of the disposed task {}", task);
+            return Ok(());
+        }
+
+        // Skip contributions which are no longer in the round, or were since replaced.
+        let round = Self::load_current_round(&self.storage)?;
+        let is_current = round
+            .chunk(task.chunk_id())
+            .and_then(|chunk| chunk.get_contribution(task.contribution_id()))
+            .map(|contribution| contribution.get_contributor().as_ref() == Some(contributor))
+            .unwrap_or(false);
+        if !is_current {
+            debug!("Skipping the full verification of the disposed task {}", task);
+            return Ok(());
+        }
+
+        Verification::run_full(
+            &self.environment,
+            &self.storage,
+            round.round_height(),
+            task.chunk_id(),
+            task.contribution_id(),
+        )
+    }
+
+    ///
+    /// Records the outcome of the full verification of the contribution of the given
+    /// contributor for the given task, as returned by `Coordinator::run_deferred_verification`.
+    ///
+    /// Only a contribution which fails the full verification with
+    /// `CoordinatorError::VerificationFailed` is treated as though it had failed the initial
+    /// verification: its response is quarantined, and its contributor is dropped from the round,
+    /// which disposes of the contributions based on it. On any other error, e.g. when storage
+    /// could not be read, the task stays pending so that it is verified again.
+    ///
+    pub fn finish_deferred_verification(
+        &mut self,
+        task: &Task,
+        contributor: &Participant,
+        result: Result<(), CoordinatorError>,
+    ) -> Result<(), CoordinatorError> {
+        let span = info_span!("deferred-verification");
+        let _enter = span.enter();
+
+        // Skip tasks which were disposed of while they were verified.
+        if self.state.get_deferred_verifications().get(task) != Some(contributor) {
+            debug!("Skipping the full verification of the disposed task {}", task);
+            return Ok(());
+        }
+
+        match result {
+            Ok(()) => {
+                self.state.remove_deferred_verification(task)?;
+                info!("Fully verified the contribution of {} for {}", contributor, task);
+            }
+            Err(CoordinatorError::VerificationFailed) => {
+                self.state.remove_deferred_verification(task)?;
+                error!(
+                    "Full verification of the contribution of {} for {} failed",
+                    contributor, task
+                );
+
+                // Keep the response for analysis before dropping the contributor disposes of it.
+                self.quarantine_response(Rejection {
+                    round_height: self.state.current_round_height(),
+                    chunk_id: task.chunk_id(),
+                    contribution_id: task.contribution_id(),
+                    contributor: Some(contributor.clone()),
+                    verifier: None,
+                    reason: CoordinatorError::VerificationFailed.to_string(),
+                    logs: vec![],
+                });
+
+                // The contributor may already have been dropped for an earlier failed contribution.
+                if let Err(error) = self.drop_participant(contributor) {
+                    warn!(
+                        "Failed to drop {} after a failed full verification: {}",
+                        contributor, error
+                    );
+                }
+            }
+            Err(error) => {
+                warn!(
+                    "Full verification of the contribution of {} for {} could not run, and is retried: {}",
+                    contributor, task, error
+                );
+                return Ok(());
+            }
+        }
+
+        self.save_state()?;
+
+        Ok(())
+    }
+
+    ///
+    /// Runs a full verification of every contribution in the current round
+    /// which was accepted by a sampled verification, and records the outcomes.
+    ///
+    pub fn run_deferred_verifications(&mut self) -> Result<(), CoordinatorError> {
+        for (task, contributor) in self.prioritized_deferred_verifications() {
+            let result = self.run_deferred_verification(&task, &contributor);
+            self.finish_deferred_verification(&task, &contributor, result)?;
+        }
+        Ok(())
+    }
+
     ///
     /// Attempts to aggregate the contributions of the current round of the ceremony.
     ///
@@ -1229,6 +2708,9 @@ impl Coordinator {
             return Err(CoordinatorError::RoundAlreadyAggregated);
         }
 
+        // Check that the finalization lock and policies of the current round permit aggregation.
+        self.check_finalization_policies()?;
+
         // Update the coordinator state to set the start of aggregation for the current round.
         self.state.aggregating_current_round(self.time.as_ref())?;
 
@@ -1321,12 +2803,32 @@ impl Coordinator {
             // Case 1 - Precommit succeed, attempt to advance the round.
             Ok(contributors) => {
                 trace!("Trying to add advance to the next round");
-                match self.next_round(started_at, contributors) {
+                match self.prepare_next_round(started_at, contributors) {
                     // Case 1a - Coordinator advanced the round.
-                    Ok(next_round_height) => {
+                    Ok(next_round) => {
                         // If success, update coordinator state to next round.
-                        info!("Coordinator has advanced to round {}", next_round_height);
+                        let next_round_height = next_round.round_height();
                         self.state.commit_next_round();
+
+                        // Save the next round, the round height and the coordinator state in storage.
+                        let entry = WalEntry::new(format!("advance to round {}", next_round_height)).writes(
+                            &self.storage,
+                            Locator::RoundHeight,
+                            WalObject::RoundHeight(next_round_height),
+                        )?;
+                        self.save_round_and_state(entry, next_round)?;
+                        info!("Coordinator has advanced to round {}", next_round_height);
+
+                        // Replicate the challenges of the first contributions of the next round.
+                        for chunk_id in 0..self.environment.number_of_chunks() {
+                            self.replicate_challenge(ContributionLocator::new(next_round_height, chunk_id, 0, true));
+                        }
+
+                        // Archive the contributions of the finalized round to cold storage.
+                        if let Err(error) = self.storage.archive_round(current_round_height) {
+                            warn!("Failed to archive round {}: {}", current_round_height, error);
+                        }
+
                         Ok(next_round_height)
                     }
                     // Case 1b - Coordinator failed to advance the round.
@@ -1349,8 +2851,10 @@ impl Coordinator {
             }
         };
 
-        // Save the coordinator state in storage.
-        self.save_state()?;
+        // Save the rolled back coordinator state in storage.
+        if result.is_err() {
+            self.save_state()?;
+        }
 
         result
     }
@@ -1396,8 +2900,28 @@ impl Coordinator {
         chunk_id: u64,
         participant: &Participant,
     ) -> Result<LockedLocators, CoordinatorError> {
+        let (round, locked_locators) = self.prepare_lock_chunk(chunk_id, participant)?;
+
+        // Save the updated round and the coordinator state in storage.
+        let entry = WalEntry::new(format!("lock chunk {} for {}", chunk_id, participant));
+        self.save_round_and_state(entry, round)?;
+
+        debug!("{} acquired lock on chunk {}", participant, chunk_id);
+        Ok(locked_locators)
+    }
+
+    ///
+    /// Acquires the lock for the given chunk ID and participant like
+    /// `try_lock_chunk`, and returns the current round with the chunk
+    /// locked, without writing it to storage.
+    ///
+    fn prepare_lock_chunk(
+        &mut self,
+        chunk_id: u64,
+        participant: &Participant,
+    ) -> Result<(Round, LockedLocators), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1413,19 +2937,7 @@ impl Coordinator {
         let locked_locators = round.try_lock_chunk(&self.environment, &mut self.storage, chunk_id, &participant)?;
         trace!("Participant {} locked chunk {}", participant, chunk_id);
 
-        // Add the updated round to storage.
-        match self.storage.update(
-            &Locator::RoundState {
-                round_height: current_round_height,
-            },
-            Object::RoundState(round),
-        ) {
-            Ok(_) => {
-                debug!("{} acquired lock on chunk {}", participant, chunk_id);
-                Ok(locked_locators)
-            }
-            _ => Err(CoordinatorError::StorageUpdateFailed),
-        }
+        Ok((round, locked_locators))
     }
 
     ///
@@ -1446,6 +2958,33 @@ impl Coordinator {
         chunk_id: u64,
         participant: &Participant,
     ) -> Result<(ContributionLocator, u64), CoordinatorError> {
+        let prepared = self.prepare_contribution(chunk_id, participant)?;
+        let current_round_height = prepared.round.round_height();
+        let contribution_id = prepared.contribution_id;
+        let response_file_locator = ContributionLocator::new(current_round_height, chunk_id, contribution_id, false);
+
+        // Add the updated round to storage.
+        match self.storage.update(
+            &Locator::RoundState {
+                round_height: current_round_height,
+            },
+            Object::RoundState(prepared.round),
+        ) {
+            Ok(_) => {
+                debug!("Updated round {} in storage", current_round_height);
+                debug!("{} added a contribution to chunk {}", participant, chunk_id);
+                Ok((response_file_locator, contribution_id))
+            }
+            _ => Err(CoordinatorError::StorageUpdateFailed),
+        }
+    }
+
+    ///
+    /// Checks the contribution of the given participant to the given chunk ID like
+    /// `add_contribution`, and returns the current round with the contribution added,
+    /// without writing it to storage.
+    ///
+    fn prepare_contribution(&self, chunk_id: u64, participant: &Participant) -> Result<PreparedRound, CoordinatorError> {
         debug!("Adding contribution from {} to chunk {}", participant, chunk_id);
 
         // Fetch the current round height from storage.
@@ -1466,6 +3005,9 @@ impl Coordinator {
                 error!("{} should have lock on chunk {} but does not", participant, chunk_id);
                 return Err(CoordinatorError::ChunkNotLockedOrByWrongParticipant);
             }
+
+            // Check that the contributor has not used its key as often as the policy permits.
+            round.check_key_usage(participant, chunk_id, self.environment.key_usage_policy())?;
         }
 
         // Fetch the expected number of contributions for the current round.
@@ -1544,11 +3086,7 @@ impl Coordinator {
                 .next()
                 .expect("splitting a string should yield at least one item");
 
-            if !self.signature.verify(
-                &address,
-                &serde_json::to_string(&contribution_file_signature.get_state())?,
-                contribution_file_signature.get_signature(),
-            ) {
+            if !contribution_file_signature.verify(&*self.signature, address)? {
                 error!("Contribution file signature failed to verify for {}", participant);
                 return Err(CoordinatorError::ContributorSignatureInvalid);
             }
@@ -1581,20 +3119,14 @@ impl Coordinator {
             self.storage.to_path(&contribution_file_signature_locator)?,
         )?;
 
-        // Add the updated round to storage.
-        match self.storage.update(
-            &Locator::RoundState {
-                round_height: current_round_height,
-            },
-            Object::RoundState(round),
-        ) {
-            Ok(_) => {
-                debug!("Updated round {} in storage", current_round_height);
-                debug!("{} added a contribution to chunk {}", participant, chunk_id);
-                Ok((response_file_locator, contribution_id))
-            }
-            _ => Err(CoordinatorError::StorageUpdateFailed),
-        }
+        Ok(PreparedRound {
+            round,
+            contribution_id,
+            files: vec![
+                Locator::ContributionFile(response_file_locator),
+                contribution_file_signature_locator,
+            ],
+        })
     }
 
     ///
@@ -1616,15 +3148,45 @@ impl Coordinator {
     /// for the chunk for the round, its verification will be stored
     /// in the next round's directory as contribution 0.
     ///
-    #[tracing::instrument(
-        skip(self, participant),
-        fields(participant = %participant)
-    )]
     pub(crate) fn verify_contribution(
         &mut self,
         task: &Task,
         participant: &Participant,
     ) -> Result<u64, CoordinatorError> {
+        let prepared = self.prepare_verification(task, participant)?;
+        let current_round_height = prepared.round.round_height();
+
+        // Add the updated round to storage.
+        match self.storage.update(
+            &Locator::RoundState {
+                round_height: current_round_height,
+            },
+            Object::RoundState(prepared.round),
+        ) {
+            Ok(_) => {
+                debug!("Updated round {} in storage", current_round_height);
+                debug!(
+                    "{} verified chunk {} contribution {}",
+                    participant,
+                    task.chunk_id(),
+                    prepared.contribution_id
+                );
+                Ok(prepared.contribution_id)
+            }
+            _ => Err(CoordinatorError::StorageUpdateFailed),
+        }
+    }
+
+    ///
+    /// Checks the verification of the given task by the given participant like
+    /// `verify_contribution`, and returns the current round with the contribution
+    /// verified, without writing it to storage.
+    ///
+    #[tracing::instrument(
+        skip(self, participant),
+        fields(participant = %participant)
+    )]
+    fn prepare_verification(&self, task: &Task, participant: &Participant) -> Result<PreparedRound, CoordinatorError> {
         let chunk_id = task.chunk_id();
         debug!("Attempting to verify a contribution for chunk {}", chunk_id);
         if !participant.is_verifier() {
@@ -1769,11 +3331,7 @@ impl Coordinator {
                 .next()
                 .expect("splitting a string should yield at least one item");
 
-            if !self.signature.verify(
-                &address,
-                &serde_json::to_string(&contribution_file_signature.get_state())?,
-                contribution_file_signature.get_signature(),
-            ) {
+            if !contribution_file_signature.verify(&*self.signature, address)? {
                 error!("Contribution file signature failed to verify for {}", participant);
                 return Err(CoordinatorError::VerifierSignatureInvalid);
             }
@@ -1816,23 +3374,11 @@ impl Coordinator {
             self.storage.to_path(&contribution_file_signature_locator)?,
         )?;
 
-        // Add the updated round to storage.
-        match self.storage.update(
-            &Locator::RoundState {
-                round_height: current_round_height,
-            },
-            Object::RoundState(round),
-        ) {
-            Ok(_) => {
-                debug!("Updated round {} in storage", current_round_height);
-                debug!(
-                    "{} verified chunk {} contribution {}",
-                    participant, chunk_id, contribution_id
-                );
-                Ok(contribution_id)
-            }
-            _ => Err(CoordinatorError::StorageUpdateFailed),
-        }
+        Ok(PreparedRound {
+            round,
+            contribution_id,
+            files: vec![response_file_locator, next_challenge_locator, contribution_file_signature_locator],
+        })
     }
 
     ///
@@ -1957,6 +3503,36 @@ impl Coordinator {
         started_at: OffsetDateTime,
         contributors: Vec<Participant>,
     ) -> Result<u64, CoordinatorError> {
+        let new_round = self.prepare_next_round(started_at, contributors)?;
+        let new_height = new_round.round_height();
+
+        // Insert the new round into storage.
+        self.storage.insert(
+            Locator::RoundState {
+                round_height: new_height,
+            },
+            Object::RoundState(new_round),
+        )?;
+
+        // Next, update the round height to reflect the new round.
+        self.storage
+            .update(&Locator::RoundHeight, Object::RoundHeight(new_height))?;
+
+        debug!("Added round {} to storage", new_height);
+        info!("Transitioned from round {} to {}", new_height - 1, new_height);
+        Ok(new_height)
+    }
+
+    ///
+    /// Checks that the next round may be initiated like `next_round`, and
+    /// returns the next round, without writing it or the round height to
+    /// storage.
+    ///
+    fn prepare_next_round(
+        &mut self,
+        started_at: OffsetDateTime,
+        contributors: Vec<Participant>,
+    ) -> Result<Round, CoordinatorError> {
         // Check that the next round has at least one authorized contributor.
         if contributors.is_empty() {
             return Err(CoordinatorError::ContributorsMissing);
@@ -2011,31 +3587,17 @@ impl Coordinator {
 
         // Instantiate the new round and height.
         let new_round = Round::new(
-            &self.environment,
-            &mut self.storage,
-            new_height,
-            started_at,
-            contributors,
-        )?;
-
-        #[cfg(test)]
-        trace!("{:#?}", &new_round);
-
-        // Insert the new round into storage.
-        self.storage.insert(
-            Locator::RoundState {
-                round_height: new_height,
-            },
-            Object::RoundState(new_round),
+            &self.environment,
+            &mut self.storage,
+            new_height,
+            started_at,
+            contributors,
         )?;
 
-        // Next, update the round height to reflect the new round.
-        self.storage
-            .update(&Locator::RoundHeight, Object::RoundHeight(new_height))?;
+        #[cfg(test)]
+        trace!("{:#?}", &new_round);
 
-        debug!("Added round {} to storage", current_round_height);
-        info!("Transitioned from round {} to {}", current_round_height, new_height);
-        Ok(new_height)
+        Ok(new_round)
     }
 
     ///
@@ -2048,6 +3610,19 @@ impl Coordinator {
     ///
     #[inline]
     pub(super) fn run_initialization(&mut self, started_at: OffsetDateTime) -> Result<u64, CoordinatorError> {
+        self.run_initialization_with(started_at, Initialization::run)
+    }
+
+    ///
+    /// Initializes round 0 as in `run_initialization`, using the given
+    /// function to write contribution 0 of each chunk to storage.
+    ///
+    #[inline]
+    fn run_initialization_with(
+        &mut self,
+        started_at: OffsetDateTime,
+        mut initialize_chunk: impl FnMut(&Environment, &mut Disk, u64, u64) -> anyhow::Result<Vec<u8>>,
+    ) -> Result<u64, CoordinatorError> {
         // Check that the ceremony has not begun yet.
         if Self::load_current_round_height(&self.storage).is_ok() {
             return Err(CoordinatorError::RoundAlreadyInitialized);
@@ -2109,7 +3684,7 @@ impl Coordinator {
             }
 
             info!("Coordinator is starting initialization on chunk {}", chunk_id);
-            let _contribution_hash = Initialization::run(&self.environment, &mut self.storage, round_height, chunk_id)?;
+            let _contribution_hash = initialize_chunk(&self.environment, &mut self.storage, round_height, chunk_id)?;
             info!("Coordinator completed initialization on chunk {}", chunk_id);
 
             // 1 - Check that the contribution locator corresponding to this round's chunk now exists.
@@ -2143,7 +3718,8 @@ impl Coordinator {
         Ok(round_height)
     }
 
-    /// Update the round on disk after a drop has occured.
+    /// Update the round on disk after a drop has occured, and save the
+    /// coordinator state along with it through the write-ahead log.
     #[inline]
     fn drop_participant_from_storage(&mut self, drop: &DropParticipant) -> Result<(), CoordinatorError> {
         debug!(
@@ -2151,13 +3727,20 @@ impl Coordinator {
             drop
         );
 
+        // A dropped participant makes no further uploads.
+        let participant = match drop {
+            DropParticipant::DropCurrent(data) => &data.participant,
+            DropParticipant::DropQueue(data) => &data.participant,
+        };
+        self.upload_attempts.remove(participant);
+
         // Check the justification and extract the tasks.
         let drop_data = match drop {
             DropParticipant::DropCurrent(data) => data,
             DropParticipant::DropQueue(_) => {
                 // Participant is not part of the round, therefore
-                // there is nothing to do.
-                return Ok(());
+                // only the coordinator state changes.
+                return self.save_state();
             }
         };
 
@@ -2189,13 +3772,12 @@ impl Coordinator {
                     replace_action.replacement_contributor
                 );
 
-                // Save the updated round to storage.
-                self.storage.update(
-                    &Locator::RoundState {
-                        round_height: round.round_height(),
-                    },
-                    Object::RoundState(round),
-                )?;
+                // Save the updated round and the coordinator state in storage.
+                let entry = WalEntry::new(format!(
+                    "replace contributor {} with {}",
+                    replace_action.dropped_contributor, replace_action.replacement_contributor
+                ));
+                self.save_round_and_state(entry, round)?;
             }
         }
 
@@ -2282,14 +3864,14 @@ impl Coordinator {
     pub fn reset_round(&mut self) -> Result<(), CoordinatorError> {
         let reset_action = self.state.reset_current_round(true, &*self.time)?;
 
-        self.storage
-            .update(&Locator::CoordinatorState, Object::CoordinatorState(self.state.clone()))?;
+        // Reset the round, and save it and the coordinator state in storage.
         self.reset_round_storage(&reset_action)?;
 
         Ok(())
     }
 
-    /// Reset the current round in storage.
+    /// Reset the current round in storage, and save the coordinator
+    /// state along with it through the write-ahead log.
     ///
     /// + `remove_participants` is a list of participants that will
     ///   have their contributions removed from the round.
@@ -2306,13 +3888,6 @@ impl Coordinator {
 
         let mut round = Self::load_round(&mut self.storage, current_round_height)?;
 
-        tracing::debug!("Resetting round and applying storage changes");
-        self.storage.process(round.reset(&reset_action.remove_participants))?;
-
-        // Clear all files
-        self.storage
-            .process(StorageAction::ClearRoundFiles(current_round_height))?;
-
         if reset_action.rollback {
             if current_round_height == 0 {
                 return Err(CoordinatorError::RoundHeightIsZero);
@@ -2321,13 +3896,35 @@ impl Coordinator {
             let new_round_height = current_round_height - 1;
             tracing::debug!("Rolling back to round {} in storage.", new_round_height);
 
-            self.storage.remove(&Locator::RoundState {
-                round_height: current_round_height,
-            })?;
-            self.storage
-                .update(&Locator::RoundHeight, Object::RoundHeight(new_round_height))?;
+            // Remove the current round, and save the round height and the coordinator state in storage.
+            let entry = WalEntry::new(format!("roll back round {}", current_round_height))
+                .writes(
+                    &self.storage,
+                    Locator::RoundHeight,
+                    WalObject::RoundHeight(new_round_height),
+                )?
+                .writes(
+                    &self.storage,
+                    Locator::CoordinatorState,
+                    WalObject::CoordinatorState(self.state.clone()),
+                )?
+                .removes(Locator::RoundState {
+                    round_height: current_round_height,
+                });
+            self.storage.write_ahead(entry)?;
+        } else {
+            tracing::debug!("Resetting round and applying storage changes");
+            round.reset(&reset_action.remove_participants);
+
+            // Save the reset round and the coordinator state in storage.
+            let entry = WalEntry::new(format!("reset round {}", current_round_height));
+            self.save_round_and_state(entry, round)?;
         }
 
+        // Clear all files
+        self.storage
+            .process(StorageAction::ClearRoundFiles(current_round_height))?;
+
         warn!("Finished resetting round {} storage", current_round_height);
 
         Ok(())
@@ -2335,7 +3932,7 @@ impl Coordinator {
 }
 
 #[cfg(any(test, feature = "operator"))]
-use crate::commands::{Computation, Seed, SigningKey, Verification};
+use crate::commands::{Computation, Seed};
 
 #[cfg(any(test, feature = "operator"))]
 impl Coordinator {
@@ -2397,6 +3994,27 @@ impl Coordinator {
         self.state.get_pending_verifications()
     }
 
+    ///
+    /// Returns the tasks pending verification with their priority, in the order
+    /// they should be handed to verifiers in, the urgent ones first.
+    ///
+    pub fn prioritized_pending_verifications(&self) -> Vec<(Task, VerificationPriority)> {
+        self.state.prioritized_pending_verifications()
+    }
+
+    ///
+    /// Marks the verification of the given task as urgent, e.g. because it holds
+    /// up the end of the round, so that it is handed to verifiers first.
+    ///
+    pub fn mark_verification_urgent(&mut self, task: &Task) -> Result<(), CoordinatorError> {
+        self.state.mark_verification_urgent(task)?;
+
+        // Save the coordinator state in storage.
+        self.save_state()?;
+
+        Ok(())
+    }
+
     #[tracing::instrument(
         skip(self, verifier, verifier_signing_key),
         fields(verifier = %verifier),
@@ -2445,7 +4063,7 @@ impl Coordinator {
         );
 
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -2535,7 +4153,7 @@ impl Coordinator {
         );
 
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -2567,9 +4185,11 @@ impl Coordinator {
         let chunk = round.chunk(chunk_id)?;
 
         // Chat that the specified contribution ID has NOT been verified yet.
-        if chunk.get_contribution(contribution_id)?.is_verified() {
+        let contribution = chunk.get_contribution(contribution_id)?;
+        if contribution.is_verified() {
             return Err(CoordinatorError::ContributionAlreadyVerified);
         }
+        let contributor = contribution.get_contributor().clone();
 
         // Fetch whether this is the final contribution of the specified chunk.
         let is_final_contribution = chunk.only_contributions_complete(round.expected_number_of_contributions());
@@ -2584,7 +4204,7 @@ impl Coordinator {
             "Starting verification on round {} chunk {} contribution {} as {}",
             round_height, chunk_id, contribution_id, participant
         );
-        Verification::run(
+        if let Err(error) = Verification::run(
             &self.environment,
             &mut self.storage,
             self.signature.clone(),
@@ -2593,7 +4213,18 @@ impl Coordinator {
             chunk_id,
             contribution_id,
             is_final_contribution,
-        )?;
+        ) {
+            self.quarantine_response(Rejection {
+                round_height,
+                chunk_id,
+                contribution_id,
+                contributor,
+                verifier: Some(participant.clone()),
+                reason: error.to_string(),
+                logs: vec![],
+            });
+            return Err(error);
+        }
         info!(
             "Completed verification on round {} chunk {} contribution {} as {}",
             round_height, chunk_id, contribution_id, participant
@@ -2628,6 +4259,20 @@ impl Coordinator {
         &self.environment
     }
 
+    ///
+    /// Returns the artifact replicas to advertise to contributors in the public settings.
+    ///
+    pub fn artifact_replicas(&self) -> Vec<ArtifactReplica> {
+        self.environment
+            .artifact_replicas()
+            .iter()
+            .map(|replica| ArtifactReplica {
+                region: replica.region.clone(),
+                url: replica.url.clone(),
+            })
+            .collect()
+    }
+
     ///
     /// Rollback a task which was locked by a contributor. Should be used to unlock
     /// chunks which become stuck during the ceremony.
@@ -2651,19 +4296,42 @@ impl Coordinator {
 #[cfg(test)]
 mod tests {
     use crate::{
-        authentication::Dummy,
+        audit::{AdminOperation, AdminOutcome},
+        authentication::{AleoSignature, Dummy, Signature},
+        cohort::CohortPhase,
         commands::{Seed, SigningKey, SEED_LENGTH},
         environment::*,
+        health::HealthThresholds,
         objects::{Participant, Task},
+        storage::{
+            ContributionLocator,
+            ContributionSignatureLocator,
+            Locator,
+            Object,
+            StorageLocator,
+            StorageObject,
+            WalEntry,
+            WalObject,
+        },
         testing::prelude::*,
         Coordinator,
+        CoordinatorError,
+        MockTimeSource,
     };
 
     use once_cell::sync::Lazy;
     use rand::RngCore;
+    use setup1_shared::{
+        ids::{ChunkId, ContributionId},
+        protocol::v1::{CeremonyPhase, ErrorCode, RejectedContributionReport, SeedCommitment, SpotCheckRequest},
+    };
+    use setup_utils::{calculate_hash, seed_commitment, to_canonical_json};
+    use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey, ViewKey};
     use std::{
         collections::HashMap,
+        convert::TryFrom,
         net::{IpAddr, Ipv4Addr},
+        path::Path,
         sync::Arc,
     };
     use time::OffsetDateTime;
@@ -2697,7 +4365,7 @@ mod tests {
             }
 
             // Update the queue.
-            coordinator.state.update_queue()?;
+            coordinator.state.update_queue(coordinator.time.as_ref())?;
             coordinator.state.save(&mut coordinator.storage)?;
         }
 
@@ -2899,6 +4567,256 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn coordinator_administer() -> anyhow::Result<()> {
+        let environment: Environment = Testing::from(Parameters::Test8Chunks).into();
+        initialize_test_environment(&environment);
+
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID).clone();
+        let signing_key: SigningKey = "secret_key".to_string();
+
+        let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+        initialize_coordinator(&mut coordinator)?;
+
+        let ban = AdminOperation::BanParticipant {
+            participant: contributor.clone(),
+        };
+        let applied = coordinator.administer(&signing_key, "alice", "incident-7-ban", ban.clone())?;
+        assert!(matches!(applied, AdminOutcome::Applied(_)));
+        assert!(coordinator.state.is_banned_participant(&contributor));
+
+        // A retry is acknowledged with the original record, without banning the participant again.
+        let retried = coordinator.administer(&signing_key, "alice", "incident-7-ban", ban)?;
+        assert_eq!(AdminOutcome::AlreadyApplied(applied.record().clone()), retried);
+
+        // An operation ID is required, and can not be reused for another operation.
+        let lock = AdminOperation::EnableManualLock;
+        assert!(
+            coordinator
+                .administer(&signing_key, "alice", "incident-7-ban", lock.clone())
+                .is_err()
+        );
+        assert!(coordinator.administer(&signing_key, "alice", "", lock).is_err());
+        assert!(!coordinator.is_manual_lock_enabled());
+
+        // The audit log holds the operation once, signed by the coordinator.
+        let audit_log = coordinator.audit_log()?;
+        assert_eq!(vec![applied.record().clone()], audit_log);
+        let message = to_canonical_json(&audit_log[0].record)?;
+        assert!(Dummy.verify("", &message, &audit_log[0].signature));
+
+        // An operation is recorded before it is applied, so one which fails is in the audit log too.
+        let prioritize = AdminOperation::PrioritizeVerification {
+            chunk_id: 0,
+            contribution_id: 1,
+        };
+        assert!(
+            coordinator
+                .administer(&signing_key, "alice", "incident-8-prioritize", prioritize)
+                .is_err()
+        );
+        assert_eq!(2, coordinator.audit_log()?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn coordinator_commit_seed() -> anyhow::Result<()> {
+        let environment: Environment = Testing::from(Parameters::Test8Chunks).into();
+        initialize_test_environment(&environment);
+
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID).clone();
+        let contributor_2 = Lazy::force(&TEST_CONTRIBUTOR_ID_2).clone();
+        let signing_key: SigningKey = "secret_key".to_string();
+        let commitment = |seed: &[u8]| SeedCommitment {
+            commitment: hex::encode(seed_commitment(&[calculate_hash(seed)])),
+        };
+
+        let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+        initialize_coordinator(&mut coordinator)?;
+
+        let signed = coordinator.commit_seed(&signing_key, &contributor, &commitment(b"seed"))?;
+        assert_eq!(commitment(b"seed").commitment, signed.receipt.commitment);
+        assert_eq!(contributor.address(), signed.receipt.contributor);
+        assert_eq!(1, signed.receipt.round_height);
+        let message = to_canonical_json(&signed.receipt)?;
+        assert!(Dummy.verify("", &message, &signed.signature));
+
+        // A retry gets the receipt of the first commitment, and another seed is rejected.
+        let retried = coordinator.commit_seed(&signing_key, &contributor, &commitment(b"seed"))?;
+        assert_eq!(signed.receipt, retried.receipt);
+        assert!(
+            coordinator
+                .commit_seed(&signing_key, &contributor, &commitment(b"other seed"))
+                .is_err()
+        );
+        let invalid = SeedCommitment {
+            commitment: "seed".to_string(),
+        };
+        assert!(coordinator.commit_seed(&signing_key, &contributor_2, &invalid).is_err());
+
+        // A seed can not be committed to once a challenge of the round was handed out.
+        coordinator.try_lock(&contributor_2)?;
+        assert!(
+            coordinator
+                .commit_seed(&signing_key, &contributor_2, &commitment(b"seed 2"))
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn coordinator_cohort_phase() -> anyhow::Result<()> {
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID).clone();
+        let contributor_2 = Lazy::force(&TEST_CONTRIBUTOR_ID_2).clone();
+        let opens_at = *TEST_STARTED_AT + time::Duration::minutes(10);
+
+        let environment: Environment = Testing::from(Parameters::Test8Chunks)
+            .cohort_phase(CohortPhase {
+                roster: vec![contributor.address()].into_iter().collect(),
+                last_round: 2,
+                opens_at: Some(opens_at),
+            })
+            .into();
+        initialize_test_environment(&environment);
+
+        let time = Arc::new(MockTimeSource::new(*TEST_STARTED_AT));
+        let mut coordinator = Coordinator::new_with_time(environment, Arc::new(Dummy), time.clone())?;
+        initialize_coordinator_single_contributor(&mut coordinator)?;
+
+        // Only the contributors on the roster may join the queue during the rounds of the cohort.
+        assert_eq!(CeremonyPhase::TrustedCohort, coordinator.state.ceremony_phase());
+        assert!(coordinator.eligibility(&contributor).eligible);
+        let eligibility = coordinator.eligibility(&contributor_2);
+        assert!(!eligibility.eligible);
+        assert_eq!(Some(3), eligibility.public_round);
+        assert_eq!(Some(opens_at.unix_timestamp()), eligibility.public_at);
+        let error = coordinator.add_to_queue(contributor_2.clone(), None, 10).unwrap_err();
+        assert!(matches!(error, CoordinatorError::ParticipantNotInCohort));
+        assert_eq!(ErrorCode::NotEligible, error.error_code());
+
+        // The ceremony opens to the public on schedule.
+        time.set_time(opens_at);
+        coordinator.update()?;
+        let eligibility = coordinator.eligibility(&contributor_2);
+        assert_eq!(CeremonyPhase::Public, eligibility.phase);
+        assert!(eligibility.eligible);
+        assert_eq!(None, eligibility.public_round);
+        coordinator.add_to_queue(contributor_2, None, 10)?;
+
+        // Opening it again is a no-op.
+        coordinator.open_public_phase()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn coordinator_health() -> anyhow::Result<()> {
+        let environment: Environment = Testing::from(Parameters::Test8Chunks).into();
+        initialize_test_environment(&environment);
+
+        // There is no ceremony to inspect before the coordinator created its state.
+        assert!(matches!(
+            Coordinator::open_read_only(environment.clone(), Arc::new(Dummy)),
+            Err(CoordinatorError::CoordinatorStateNotInitialized)
+        ));
+        assert!(!Path::new(environment.local_base_directory()).exists());
+
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID);
+
+        let time = Arc::new(MockTimeSource::new(*TEST_STARTED_AT));
+        let mut coordinator = Coordinator::new_with_time(environment.clone(), Arc::new(Dummy), time.clone())?;
+        initialize_coordinator(&mut coordinator)?;
+        coordinator.try_lock_chunk(0, &contributor)?;
+
+        // Disk headroom depends on the machine running the tests, so it is not required.
+        let thresholds = HealthThresholds {
+            min_free_bytes: 0,
+            ..HealthThresholds::default()
+        };
+        let report = coordinator.health(&thresholds);
+        assert!(report.is_healthy(), "{}", report);
+
+        // Operator tools read the same state without modifying the storage.
+        let report = Coordinator::open_read_only(environment.clone(), Arc::new(Dummy))?.health(&thresholds);
+        assert!(report.is_healthy(), "{}", report);
+
+        // A lock held for longer than the lease and the grace period is overdue.
+        let overdue = environment.participant_lock_timeout() + thresholds.lock_grace + time::Duration::seconds(1);
+        time.set_time(*TEST_STARTED_AT + overdue);
+        let report = coordinator.health(&thresholds);
+        assert!(!report.is_healthy());
+        let failed: Vec<_> = report.checks.iter().filter(|check| !check.healthy).collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].check, "locks");
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn coordinator_check_upload() -> anyhow::Result<()> {
+        let environment: Environment = Testing::from(Parameters::Test8Chunks)
+            .maximum_uploads_per_minute(3)
+            .into();
+        initialize_test_environment(&environment);
+
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID);
+        let contributor_2 = Lazy::force(&TEST_CONTRIBUTOR_ID_2);
+        let verifier = Lazy::force(&TEST_VERIFIER_ID);
+
+        let time = Arc::new(MockTimeSource::new(*TEST_STARTED_AT));
+        let mut coordinator = Coordinator::new_with_time(environment.clone(), Arc::new(Dummy), time.clone())?;
+        initialize_coordinator(&mut coordinator)?;
+        coordinator.try_lock_chunk(0, &contributor)?;
+
+        let response_size = Object::contribution_file_size(&environment, 0, false);
+        let next_challenge_size = Object::contribution_file_size(&environment, 0, true);
+
+        // The chunk IDs start at zero.
+        assert!(matches!(
+            coordinator.check_upload(contributor, environment.number_of_chunks(), response_size),
+            Err(CoordinatorError::ChunkIdInvalid)
+        ));
+
+        // The declared size must be the size of the file for the chunk.
+        coordinator.check_upload(contributor, 0, response_size)?;
+        assert!(matches!(
+            coordinator.check_upload(contributor, 0, response_size + 1),
+            Err(CoordinatorError::UploadSizeMismatch { expected, declared })
+                if expected == response_size && declared == response_size + 1
+        ));
+        coordinator.check_upload(verifier, 0, next_challenge_size)?;
+
+        // Contributors may only upload responses for the chunks they locked.
+        assert!(matches!(
+            coordinator.check_upload(contributor_2, 0, response_size),
+            Err(CoordinatorError::ChunkNotLockedOrByWrongParticipant)
+        ));
+
+        // The rejected upload counts towards the limit of three uploads a minute.
+        coordinator.check_upload(contributor, 0, response_size)?;
+        assert!(matches!(
+            coordinator.check_upload(contributor, 0, response_size),
+            Err(CoordinatorError::UploadRateLimited)
+        ));
+
+        time.update(|prev| prev + time::Duration::minutes(1));
+        coordinator.check_upload(contributor, 0, response_size)?;
+
+        // Only the participants who uploaded in the last minute are remembered.
+        coordinator.evict_upload_attempts();
+        assert!(coordinator.upload_attempts.contains_key(contributor));
+        assert!(!coordinator.upload_attempts.contains_key(verifier));
+
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn coordinator_contributor_add_contribution() -> anyhow::Result<()> {
@@ -2965,6 +4883,69 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn coordinator_recovers_interrupted_contribution() -> anyhow::Result<()> {
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID).clone();
+        let contributor_signing_key: SigningKey = "secret_key".to_string();
+        let chunk_id = 0;
+
+        // Runs the contribution of contributor 1 to round 1 chunk 0, and writes the
+        // mutation adding it ahead to the log without applying it, as if the
+        // coordinator crashed right after.
+        let interrupted_contribution = || -> anyhow::Result<Coordinator> {
+            initialize_test_environment(&TEST_ENVIRONMENT_3);
+
+            let mut coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(Dummy))?;
+            initialize_coordinator(&mut coordinator)?;
+            coordinator.try_lock_chunk(chunk_id, &contributor)?;
+
+            let mut seed: Seed = [0; SEED_LENGTH];
+            rand::thread_rng().fill_bytes(&mut seed[..]);
+            coordinator.run_computation(1, chunk_id, 1, &contributor, &contributor_signing_key, &seed)?;
+
+            let prepared = coordinator.prepare_contribution(chunk_id, &contributor)?;
+            let mut state = coordinator.state.clone();
+            state.completed_task(&contributor, &Task::new(chunk_id, 1), coordinator.time.as_ref())?;
+            let entry = WalEntry::new("add a contribution")
+                .requires(prepared.files)
+                .writes(
+                    &coordinator.storage,
+                    Locator::RoundState { round_height: 1 },
+                    WalObject::RoundState(prepared.round),
+                )?
+                .writes(
+                    &coordinator.storage,
+                    Locator::CoordinatorState,
+                    WalObject::CoordinatorState(state),
+                )?;
+            coordinator.storage.append_wal_entry(&entry)?;
+            Ok(coordinator)
+        };
+
+        // The contribution is added to the round, and the task completed, on restart.
+        drop(interrupted_contribution()?);
+        let coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(Dummy))?;
+        let round = coordinator.current_round()?;
+        assert!(round.chunk(chunk_id)?.is_unlocked());
+        assert!(round.chunk(chunk_id)?.get_contribution(1).is_ok());
+        assert!(coordinator.state.lookup_pending_task(&contributor, chunk_id)?.is_none());
+
+        // The mutation is rolled back if its signature was lost.
+        let mut coordinator = interrupted_contribution()?;
+        coordinator.storage.remove(&Locator::ContributionFileSignature(
+            ContributionSignatureLocator::new(1, chunk_id, 1, false),
+        ))?;
+        drop(coordinator);
+        let coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(Dummy))?;
+        let round = coordinator.current_round()?;
+        assert!(round.chunk(chunk_id)?.is_locked_by(&contributor));
+        assert!(round.chunk(chunk_id)?.get_contribution(1).is_err());
+        assert!(coordinator.state.lookup_pending_task(&contributor, chunk_id)?.is_some());
+
+        Ok(())
+    }
+
     #[test]
     #[serial]
     fn coordinator_verifier_verify_contribution() -> anyhow::Result<()> {
@@ -3023,6 +5004,223 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn coordinator_accepts_contributions_signed_with_aleo_accounts() -> anyhow::Result<()> {
+        initialize_test_environment(&TEST_ENVIRONMENT_3);
+
+        // Sign as participants with Aleo accounts, as the contributor and verifier clients do.
+        let account = |new_participant: fn(&str) -> Participant| -> anyhow::Result<(Participant, SigningKey)> {
+            let private_key = PrivateKey::<Testnet2Parameters>::new(&mut rand::thread_rng());
+            let address = Address::try_from(&private_key)?.to_string();
+            Ok((new_participant(&address), ViewKey::try_from(&private_key)?.to_string()))
+        };
+        let (contributor, contributor_signing_key) = account(Participant::new_contributor)?;
+        let (verifier, verifier_signing_key) = account(Participant::new_verifier)?;
+
+        let mut coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(AleoSignature))?;
+        let contributors = [(contributor.clone(), IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1)))];
+        initialize_to_round_1(&mut coordinator, &contributors)?;
+        let round_height = coordinator.current_round_height()?;
+
+        let chunk_id = 0;
+        let contribution_id = 1;
+        coordinator.try_lock_chunk(chunk_id, &contributor)?;
+
+        // The contribution signature does not include a next challenge hash.
+        let mut seed: Seed = [0; SEED_LENGTH];
+        rand::thread_rng().fill_bytes(&mut seed[..]);
+        coordinator.run_computation(
+            round_height,
+            chunk_id,
+            contribution_id,
+            &contributor,
+            &contributor_signing_key,
+            &seed,
+        )?;
+        coordinator.add_contribution(chunk_id, &contributor)?;
+
+        // The verification signature includes a next challenge hash, which is not in
+        // the position of its canonical encoding when the state is serialized as is.
+        let task = Task::new(chunk_id, contribution_id);
+        coordinator.run_verification(round_height, &task, &verifier, &verifier_signing_key)?;
+        coordinator.verify_contribution(&task, &verifier)?;
+
+        // A contribution signed by another account is rejected.
+        let chunk_id = 1;
+        let (_, impostor_signing_key) = account(Participant::new_contributor)?;
+        coordinator.try_lock_chunk(chunk_id, &contributor)?;
+        coordinator.run_computation(
+            round_height,
+            chunk_id,
+            contribution_id,
+            &contributor,
+            &impostor_signing_key,
+            &seed,
+        )?;
+        assert!(matches!(
+            coordinator.add_contribution(chunk_id, &contributor),
+            Err(CoordinatorError::ContributorSignatureInvalid)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn coordinator_spot_check_contribution() -> anyhow::Result<()> {
+        initialize_test_environment(&TEST_ENVIRONMENT_3);
+
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID);
+        let signing_key: SigningKey = "secret_key".to_string();
+
+        let mut coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(Dummy))?;
+        initialize_coordinator(&mut coordinator)?;
+
+        // Run computation on round 1 chunk 0 contribution 1.
+        let round_height = coordinator.current_round_height()?;
+        let chunk_id = 0;
+        let contribution_id = 1;
+        coordinator.try_lock_chunk(chunk_id, &contributor)?;
+        let mut seed: Seed = [0; SEED_LENGTH];
+        rand::thread_rng().fill_bytes(&mut seed[..]);
+        coordinator.run_computation(
+            round_height,
+            chunk_id,
+            contribution_id,
+            contributor,
+            &signing_key,
+            &seed,
+        )?;
+        coordinator.add_contribution(chunk_id, &contributor)?;
+
+        let response_locator =
+            Locator::ContributionFile(ContributionLocator::new(round_height, chunk_id, contribution_id, false));
+        let mut response = coordinator.storage.reader(&response_locator)?.to_vec();
+        let request = SpotCheckRequest {
+            round_height,
+            chunk_id,
+            contribution_id,
+            challenge_hash: coordinator.challenge_hash(ChunkId(chunk_id), ContributionId(0))?,
+        };
+
+        // Verdicts are not signed with the dummy signature scheme.
+        assert!(matches!(
+            coordinator
+                .spot_check(&signing_key, &request, &response)
+                .unwrap_err()
+                .downcast::<CoordinatorError>()?,
+            CoordinatorError::SignatureSchemeIsInsecure
+        ));
+
+        // Spot check from the same storage as the coordinator with its Aleo account.
+        drop(coordinator);
+        let private_key = PrivateKey::<Testnet2Parameters>::new(&mut rand::thread_rng());
+        let address = Address::try_from(&private_key)?.to_string();
+        let signing_key: SigningKey = ViewKey::try_from(&private_key)?.to_string();
+        let coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(AleoSignature))?;
+
+        // The response verifies against the challenge it was computed on.
+        let signed = coordinator.spot_check(&signing_key, &request, &response)?;
+        assert!(AleoSignature.verify(&address, &to_canonical_json(&signed.verdict)?, &signed.signature));
+        let verdict = signed.verdict;
+        assert!(verdict.valid, "{:?}", verdict.reason);
+        assert_eq!(request.challenge_hash, verdict.challenge_hash);
+        assert_eq!(hex::encode(calculate_hash(&response)), verdict.response_hash);
+
+        // It does not verify against another challenge hash.
+        let other_challenge = SpotCheckRequest {
+            challenge_hash: hex::encode(calculate_hash(b"other challenge")),
+            ..request.clone()
+        };
+        let verdict = coordinator.spot_check(&signing_key, &other_challenge, &response)?.verdict;
+        assert!(!verdict.valid);
+
+        // A tampered response does not verify.
+        let last = response.len() - 1;
+        response[last] ^= 1;
+        let verdict = coordinator.spot_check(&signing_key, &request, &response)?.verdict;
+        assert!(!verdict.valid);
+        assert!(verdict.reason.is_some());
+
+        // A response of the wrong size is refused.
+        assert!(coordinator.spot_check(&signing_key, &request, &response[1..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn coordinator_quarantine_rejected_contribution() -> anyhow::Result<()> {
+        initialize_test_environment(&TEST_ENVIRONMENT_3);
+
+        let contributor = Lazy::force(&TEST_CONTRIBUTOR_ID);
+        let verifier = Lazy::force(&TEST_VERIFIER_ID);
+        let signing_key: SigningKey = "secret_key".to_string();
+
+        let mut coordinator = Coordinator::new(TEST_ENVIRONMENT_3.clone(), Arc::new(Dummy))?;
+        initialize_coordinator(&mut coordinator)?;
+
+        // Run computation on round 1 chunk 0 contribution 1.
+        let round_height = coordinator.current_round_height()?;
+        let chunk_id = 0;
+        let contribution_id = 1;
+        coordinator.try_lock_chunk(chunk_id, &contributor)?;
+        let mut seed: Seed = [0; SEED_LENGTH];
+        rand::thread_rng().fill_bytes(&mut seed[..]);
+        coordinator.run_computation(
+            round_height,
+            chunk_id,
+            contribution_id,
+            contributor,
+            &signing_key,
+            &seed,
+        )?;
+        coordinator.add_contribution(chunk_id, &contributor)?;
+
+        // Garble the first element of the response.
+        let response_locator =
+            Locator::ContributionFile(ContributionLocator::new(round_height, chunk_id, contribution_id, false));
+        let mut response = coordinator.storage.reader(&response_locator)?.to_vec();
+        response[64..160].iter_mut().for_each(|byte| *byte = 0xff);
+        std::fs::write(coordinator.storage.to_path(&response_locator)?, &response)?;
+
+        // The rejected response is quarantined along with its forensic bundle.
+        let task = Task::new(chunk_id, contribution_id);
+        assert!(
+            coordinator
+                .run_verification(round_height, &task, verifier, &signing_key)
+                .is_err()
+        );
+        let bundles = coordinator.quarantined_responses()?;
+        assert_eq!(1, bundles.len());
+        let bundle = &bundles[0];
+        assert_eq!(Some(contributor.clone()), bundle.rejection.contributor);
+        assert_eq!(Some(verifier.clone()), bundle.rejection.verifier);
+        assert_eq!(hex::encode(calculate_hash(&response)), bundle.response_hash);
+        assert_eq!(
+            Some(coordinator.challenge_hash(ChunkId(chunk_id), ContributionId(0))?),
+            bundle.challenge_hash
+        );
+        assert_eq!("tau_g1", bundle.offending_elements[0].section);
+        assert_eq!(0, bundle.offending_elements[0].index);
+
+        // A verifier reporting the same response does not quarantine it again.
+        let report = RejectedContributionReport {
+            chunk_id,
+            contribution_id,
+            reason: "the response failed verification".to_string(),
+            logs: vec!["Verification on chunk 0 contribution 1 failed".to_string()],
+        };
+        assert_eq!(*bundle, coordinator.report_rejected_contribution(verifier, &report)?);
+        assert_eq!(1, coordinator.quarantined_responses()?.len());
+
+        // Only verifiers report rejected contributions.
+        assert!(coordinator.report_rejected_contribution(contributor, &report).is_err());
+
+        Ok(())
+    }
+
     #[test]
     #[serial]
     // This test runs a round with a single coordinator and single verifier
@@ -4,6 +4,10 @@ use crate::{
         participant::*,
         task::{initialize_tasks, Task},
     },
+    policy::{ContributorOrigin, DiversityPolicy},
+    prioritization,
+    reservations::Reservation,
+    scheduling::QueuedContributor,
     storage::{Disk, Locator, Object},
     CoordinatorError,
     TimeSource,
@@ -12,6 +16,14 @@ use phase1::ProvingSystem;
 
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use setup1_shared::protocol::v1::{
+    CeremonyPhase,
+    ContributorEligibility,
+    MachineAttestation,
+    SeedCommitmentReceipt,
+    VerificationPriority,
+};
+use setup_utils::SEED_COMMITMENT_SCHEME;
 use std::{
     collections::{HashMap, HashSet, LinkedList},
     iter::FromIterator,
@@ -388,6 +400,28 @@ impl ParticipantInfo {
         Ok(())
     }
 
+    ///
+    /// Moves the assigned tasks on the given chunk IDs to the front of the assigned tasks,
+    /// in the given order, so the participant processes them first.
+    ///
+    /// Returns the chunk IDs which had an assigned task.
+    ///
+    #[inline]
+    fn pin_tasks(&mut self, chunk_ids: &[u64]) -> Vec<u64> {
+        let mut pinned = LinkedList::new();
+        for chunk_id in chunk_ids {
+            if let Some(task) = self.assigned_tasks.iter().find(|task| task.contains(*chunk_id)).cloned() {
+                self.assigned_tasks = self.assigned_tasks.iter().filter(|t| **t != task).cloned().collect();
+                pinned.push_back(task);
+            }
+        }
+
+        let pinned_chunk_ids = pinned.iter().map(|task| task.chunk_id()).collect();
+        pinned.append(&mut self.assigned_tasks);
+        self.assigned_tasks = pinned;
+        pinned_chunk_ids
+    }
+
     ///
     /// Pops the next (chunk ID, contribution ID) task the participant should process,
     /// in FIFO order when added to the linked list.
@@ -933,6 +967,25 @@ impl Default for RoundMetrics {
     }
 }
 
+/// An upload the coordinator has processed, recorded under
+/// the idempotency key the participant sent with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessedUpload {
+    /// The participant who uploaded the file.
+    pub participant: Participant,
+    /// The task the file was uploaded for.
+    pub task: Task,
+}
+
+/// The compute times of the contributions made on a class of hardware.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComputeTimes {
+    /// The total number of seconds spent computing the contributions.
+    pub total_seconds: u64,
+    /// The number of contributions computed.
+    pub contributions: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoordinatorState {
     /// The parameters and settings of this coordinator.
@@ -956,6 +1009,43 @@ pub struct CoordinatorState {
     current_verifiers: HashMap<Participant, ParticipantInfo>,
     /// The map of tasks pending verification in the current round.
     pending_verification: HashMap<Task, Participant>,
+    /// The map of tasks accepted by a sampled verification in the current round,
+    /// to the contributor of each task, which are pending a full verification.
+    #[serde(default)]
+    deferred_verification: HashMap<Task, Participant>,
+    /// The set of tasks pending verification which the coordinator marked urgent.
+    #[serde(default)]
+    urgent_verification: HashSet<Task>,
+    /// The map of idempotency keys to the uploads processed in the current round.
+    #[serde(default)]
+    processed_uploads: HashMap<String, ProcessedUpload>,
+    /// The map of contributors to the receipts of their seed commitments in the current round.
+    #[serde(default)]
+    seed_commitments: HashMap<Participant, SeedCommitmentReceipt>,
+    /// The set of contributors who locked a chunk in the current round, and so were handed a challenge.
+    #[serde(default)]
+    locking_contributors: HashSet<Participant>,
+    /// The map of contributors to the class of hardware they reported.
+    #[serde(default)]
+    hardware_classes: HashMap<Participant, String>,
+    /// The map of contributors run non-interactively to the machines they reported.
+    #[serde(default)]
+    machine_attestations: HashMap<Participant, MachineAttestation>,
+    /// The map of hardware classes to the compute times of the contributions made on them.
+    #[serde(default)]
+    compute_times: HashMap<String, ComputeTimes>,
+    /// The map of contributors to where they are located on the network.
+    #[serde(default)]
+    contributor_origins: HashMap<Participant, ContributorOrigin>,
+    /// The time windows contributors reserved to contribute in, which have not ended yet.
+    #[serde(default)]
+    reservations: Vec<Reservation>,
+    /// The ID of the next time window a contributor reserves.
+    #[serde(default)]
+    next_reservation_id: u64,
+    /// The next round height, and the time the queue was first scheduled for it at.
+    #[serde(default)]
+    queue_scheduled_at: Option<(u64, OffsetDateTime)>,
     /// The map of each round height to the corresponding contributors from that round.
     finished_contributors: HashMap<u64, HashMap<Participant, ParticipantInfo>>,
     /// The map of each round height to the corresponding verifiers from that round.
@@ -966,6 +1056,9 @@ pub struct CoordinatorState {
     banned: HashSet<Participant>,
     /// The manual lock to hold the coordinator from transitioning to the next round.
     manual_lock: bool,
+    /// The time the ceremony opened to the public, if its first rounds were restricted to a cohort.
+    #[serde(default)]
+    public_opened_at: Option<OffsetDateTime>,
 }
 
 impl CoordinatorState {
@@ -985,11 +1078,24 @@ impl CoordinatorState {
             contributor_ips: HashMap::default(),
             current_verifiers: HashMap::default(),
             pending_verification: HashMap::default(),
+            deferred_verification: HashMap::default(),
+            urgent_verification: HashSet::default(),
+            processed_uploads: HashMap::default(),
+            seed_commitments: HashMap::default(),
+            locking_contributors: HashSet::default(),
+            hardware_classes: HashMap::default(),
+            machine_attestations: HashMap::default(),
+            compute_times: HashMap::default(),
+            contributor_origins: HashMap::default(),
+            reservations: Vec::new(),
+            next_reservation_id: 0,
+            queue_scheduled_at: None,
             finished_contributors: HashMap::default(),
             finished_verifiers: HashMap::default(),
             dropped: Vec::new(),
             banned: HashSet::new(),
             manual_lock: false,
+            public_opened_at: None,
         }
     }
 
@@ -1097,6 +1203,10 @@ impl CoordinatorState {
                 current_round_height: Some(new_round_height),
                 queue,
                 banned: self.banned.clone(),
+                reservations: self.reservations.clone(),
+                next_reservation_id: self.next_reservation_id,
+                queue_scheduled_at: self.queue_scheduled_at,
+                public_opened_at: self.public_opened_at,
                 ..Self::new(self.environment.clone())
             };
 
@@ -1139,6 +1249,10 @@ impl CoordinatorState {
                 queue: self.queue.clone(),
                 banned: self.banned.clone(),
                 dropped: self.dropped.clone(),
+                reservations: self.reservations.clone(),
+                next_reservation_id: self.next_reservation_id,
+                queue_scheduled_at: self.queue_scheduled_at,
+                public_opened_at: self.public_opened_at,
                 ..Self::new(self.environment.clone())
             };
 
@@ -1262,6 +1376,106 @@ impl CoordinatorState {
         Ok(contributors)
     }
 
+    ///
+    /// Returns an error if the current round has a finalization lock which
+    /// does not yet permit the round to be finalized.
+    ///
+    #[inline]
+    pub(super) fn check_finalization_lock(&self, time: &dyn TimeSource) -> Result<(), CoordinatorError> {
+        let current_round_height = self.current_round_height();
+        let lock = match self.environment.finalization_lock(current_round_height) {
+            Some(lock) => lock,
+            None => return Ok(()),
+        };
+
+        // Check that the earliest finalization time has passed.
+        if let Some(not_before) = lock.not_before {
+            if time.now_utc() < not_before {
+                return Err(CoordinatorError::RoundFinalizationTooEarly { not_before });
+            }
+        }
+
+        // Check that enough independent contributors have finished the round.
+        let finished = self
+            .finished_contributors
+            .get(&current_round_height)
+            .map(|contributors| {
+                contributors
+                    .keys()
+                    .filter(|contributor| !self.is_coordinator_contributor(contributor))
+                    .count()
+            })
+            .unwrap_or_default();
+        if finished < lock.minimum_contributions {
+            return Err(CoordinatorError::RoundFinalizationContributionsInsufficient {
+                finished,
+                minimum: lock.minimum_contributions,
+            });
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Returns an error if the given round has a finalization lock which requires
+    /// more contributors, excluding the contributors managed by the coordinator,
+    /// than the given contributors of the round, as the round could never be finalized.
+    ///
+    #[inline]
+    fn check_finalization_lock_admission<'a>(
+        &self,
+        round_height: u64,
+        contributors: impl Iterator<Item = &'a Participant>,
+    ) -> Result<(), CoordinatorError> {
+        let lock = match self.environment.finalization_lock(round_height) {
+            Some(lock) => lock,
+            None => return Ok(()),
+        };
+
+        let queued = contributors
+            .filter(|contributor| !self.is_coordinator_contributor(contributor))
+            .count();
+        if queued < lock.minimum_contributions {
+            return Err(CoordinatorError::RoundAdmissionContributorsInsufficient {
+                queued,
+                minimum: lock.minimum_contributions,
+            });
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Returns an error if the contributors who finished the current round, excluding
+    /// the contributors managed by the coordinator, are not diverse enough for the
+    /// given policy to permit the round, which started at the given time, to be finalized.
+    ///
+    #[inline]
+    pub(super) fn check_diversity_policy(
+        &self,
+        policy: &DiversityPolicy,
+        started_at: OffsetDateTime,
+        time: &dyn TimeSource,
+    ) -> Result<(), CoordinatorError> {
+        let finished = self
+            .finished_contributors
+            .get(&self.current_round_height())
+            .into_iter()
+            .flat_map(|contributors| contributors.keys())
+            .filter(|contributor| !self.is_coordinator_contributor(contributor))
+            .map(|contributor| (contributor, self.contributor_origins.get(contributor)));
+
+        policy.check(finished, started_at, time.now_utc())
+    }
+
+    ///
+    /// Sets where the given contributor is located on the network.
+    ///
+    #[inline]
+    pub(super) fn set_contributor_origin(&mut self, participant: &Participant, origin: ContributorOrigin) {
+        self.contributor_origins.insert(participant.clone(), origin);
+    }
+
     ///
     /// Returns `true` if the given participant is a contributor managed
     /// by the coordinator.
@@ -1318,6 +1532,14 @@ impl CoordinatorState {
         self.current_contributors.clone().into_iter().collect()
     }
 
+    ///
+    /// Returns a list of the verifiers currently in the round.
+    ///
+    #[inline]
+    pub fn current_verifiers(&self) -> Vec<(Participant, ParticipantInfo)> {
+        self.current_verifiers.clone().into_iter().collect()
+    }
+
     /// Gets reference to the [ParticipantInfo] for a participant
     /// currently in the round.
     pub fn current_participant_info(&self, participant: &Participant) -> Option<&ParticipantInfo> {
@@ -1369,6 +1591,8 @@ impl CoordinatorState {
     pub fn is_current_round_finished(&self) -> bool {
         // Check that all contributions have undergone verification.
         self.pending_verification.is_empty()
+            // Check that all contributions accepted by sampling have been fully verified.
+            && self.deferred_verification.is_empty()
             // Check that all current contributors are finished.
             && self.current_contributors.is_empty()
     }
@@ -1478,6 +1702,17 @@ impl CoordinatorState {
             return false;
         }
 
+        // Check that the next round contains enough contributors to release its finalization lock.
+        let assigned_contributors = self
+            .queue
+            .iter()
+            .filter(|(p, (_, rh, _, _))| p.is_contributor() && rh.unwrap_or_default() == next_round_height)
+            .map(|(p, _)| p);
+        if let Err(error) = self.check_finalization_lock_admission(next_round_height, assigned_contributors) {
+            trace!("Waiting for more contributors to start the next round: {:?}", error);
+            return false;
+        }
+
         true
     }
 
@@ -1514,6 +1749,13 @@ impl CoordinatorState {
                     return Err(CoordinatorError::ParticipantUnauthorized);
                 }
 
+                // Check that the contributor is on the roster while the ceremony is restricted to a cohort.
+                if let Some(cohort_phase) = self.environment.cohort_phase() {
+                    if !cohort_phase.is_eligible(&participant, self.ceremony_phase()) {
+                        return Err(CoordinatorError::ParticipantNotInCohort);
+                    }
+                }
+
                 // Check that the contributor is not in the current round.
                 if !self.environment.allow_current_contributors_in_queue()
                     && self.current_contributors.contains_key(&participant)
@@ -1549,6 +1791,74 @@ impl CoordinatorState {
         Ok(())
     }
 
+    ///
+    /// Returns the phase of the ceremony, which is public unless its first
+    /// rounds are restricted to a cohort and it has not opened yet.
+    ///
+    #[inline]
+    pub fn ceremony_phase(&self) -> CeremonyPhase {
+        if self.environment.cohort_phase().is_some() && self.public_opened_at.is_none() {
+            CeremonyPhase::TrustedCohort
+        } else {
+            CeremonyPhase::Public
+        }
+    }
+
+    ///
+    /// Returns whether the given participant may join the queue in the
+    /// current phase of the ceremony, and when it opens to the public.
+    ///
+    pub fn eligibility(&self, participant: &Participant) -> ContributorEligibility {
+        let phase = self.ceremony_phase();
+        let cohort_phase = self.environment.cohort_phase();
+        let eligible = participant.is_contributor()
+            && !self.banned.contains(participant)
+            && cohort_phase.map_or(true, |cohort_phase| cohort_phase.is_eligible(participant, phase));
+        let (public_round, public_at) = match cohort_phase {
+            Some(cohort_phase) if phase == CeremonyPhase::TrustedCohort => (
+                Some(cohort_phase.last_round + 1),
+                cohort_phase.opens_at.map(|opens_at| opens_at.unix_timestamp()),
+            ),
+            _ => (None, None),
+        };
+        ContributorEligibility {
+            phase,
+            eligible,
+            public_round,
+            public_at,
+        }
+    }
+
+    ///
+    /// Returns `true` if the ceremony is restricted to a cohort, and is due to
+    /// open to the public by the schedule of the cohort phase.
+    ///
+    #[inline]
+    pub(super) fn is_public_phase_due(&self, time: &dyn TimeSource) -> bool {
+        match (self.environment.cohort_phase(), self.current_round_height) {
+            (Some(cohort_phase), Some(round_height)) if self.public_opened_at.is_none() => {
+                cohort_phase.is_public_due(round_height, time.now_utc())
+            }
+            _ => false,
+        }
+    }
+
+    ///
+    /// Opens the ceremony to the public, ending the phase which is restricted
+    /// to the cohort. Returns `false` if it was already open.
+    ///
+    #[inline]
+    pub(super) fn open_public_phase(&mut self, time: &dyn TimeSource) -> Result<bool, CoordinatorError> {
+        if self.environment.cohort_phase().is_none() {
+            return Err(CoordinatorError::CohortPhaseNotConfigured);
+        }
+        if self.public_opened_at.is_some() {
+            return Ok(false);
+        }
+        self.public_opened_at = Some(time.now_utc());
+        Ok(true)
+    }
+
     ///
     /// Removes the given participant from the queue.
     ///
@@ -1570,6 +1880,78 @@ impl CoordinatorState {
         Ok(())
     }
 
+    ///
+    /// Returns the time windows contributors reserved to contribute in, which have not ended yet.
+    ///
+    pub fn reservations(&self) -> &[Reservation] {
+        &self.reservations
+    }
+
+    ///
+    /// Reserves the time window from `starts_at` to `ends_at` for the given contributor,
+    /// in which it is scheduled for a round ahead of the contributors without a reservation.
+    ///
+    /// At most `maximum_contributors_per_round` reservations may overlap, so that
+    /// every contributor with a reservation fits in the round which starts in its window.
+    ///
+    #[inline]
+    pub(super) fn reserve_slot(
+        &mut self,
+        participant: &Participant,
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+        time: &dyn TimeSource,
+    ) -> Result<Reservation, CoordinatorError> {
+        if !participant.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor);
+        }
+        if self.banned.contains(participant) {
+            return Err(CoordinatorError::ParticipantBanned);
+        }
+
+        let now = time.now_utc();
+        Reservation::check_window(starts_at, ends_at, now)?;
+
+        let overlapping: Vec<_> = self
+            .reservations
+            .iter()
+            .filter(|reservation| reservation.overlaps(starts_at, ends_at))
+            .collect();
+        if overlapping.iter().any(|r| r.participant == *participant) {
+            return Err(CoordinatorError::ReservationOverlapping);
+        }
+        if overlapping.len() >= self.environment.maximum_contributors_per_round() {
+            return Err(CoordinatorError::ReservationSlotsFull);
+        }
+
+        let reservation = Reservation {
+            id: self.next_reservation_id,
+            participant: participant.clone(),
+            starts_at,
+            ends_at,
+            reserved_at: now,
+            assigned_round: None,
+        };
+        self.next_reservation_id += 1;
+        self.reservations.push(reservation.clone());
+
+        Ok(reservation)
+    }
+
+    ///
+    /// Cancels the time window with the given ID reserved by the given contributor.
+    ///
+    #[inline]
+    pub(super) fn cancel_reservation(&mut self, participant: &Participant, id: u64) -> Result<(), CoordinatorError> {
+        let index = self
+            .reservations
+            .iter()
+            .position(|reservation| reservation.id == id && reservation.participant == *participant)
+            .ok_or(CoordinatorError::ReservationMissing)?;
+        self.reservations.remove(index);
+        Ok(())
+    }
+
     ///
     /// Pops the next (chunk ID, contribution ID) task that the contributor should process.
     ///
@@ -1601,6 +1983,40 @@ impl CoordinatorState {
         }
     }
 
+    ///
+    /// Pins the given chunk IDs for the given contributor, who then locks them
+    /// before the rest of its assigned chunks, up to the number of chunks
+    /// the environment permits a contributor to pin.
+    ///
+    /// On success, this function returns the chunk IDs which were pinned.
+    ///
+    pub(super) fn pin_chunks(
+        &mut self,
+        participant: &Participant,
+        chunk_ids: &[u64],
+    ) -> Result<Vec<u64>, CoordinatorError> {
+        let maximum_pinned_chunks = self.environment.maximum_pinned_chunks();
+        if maximum_pinned_chunks == 0 {
+            return Err(CoordinatorError::ChunkPinningDisabled);
+        }
+
+        // Check that the chunk IDs are valid.
+        if chunk_ids.iter().any(|chunk_id| *chunk_id >= self.environment.number_of_chunks()) {
+            return Err(CoordinatorError::ChunkIdInvalid);
+        }
+
+        match participant {
+            Participant::Contributor(_) => match self.current_contributors.get_mut(participant) {
+                Some(participant_info) => {
+                    let chunk_ids = &chunk_ids[..chunk_ids.len().min(maximum_pinned_chunks)];
+                    Ok(participant_info.pin_tasks(chunk_ids))
+                }
+                None => Err(CoordinatorError::ParticipantNotFound(participant.clone())),
+            },
+            Participant::Verifier(_) => Err(CoordinatorError::ExpectedContributor),
+        }
+    }
+
     ///
     /// Adds the given chunk ID to the locks held by the given participant.
     ///
@@ -1612,14 +2028,18 @@ impl CoordinatorState {
         time: &dyn TimeSource,
     ) -> Result<(), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
         match participant {
             Participant::Contributor(_) => match self.current_contributors.get_mut(participant) {
                 // Acquire the chunk lock for the contributor.
-                Some(participant) => Ok(participant.acquired_lock(chunk_id, time)?),
+                Some(info) => {
+                    info.acquired_lock(chunk_id, time)?;
+                    self.locking_contributors.insert(participant.clone());
+                    Ok(())
+                }
                 None => Err(CoordinatorError::ParticipantNotFound(participant.clone())),
             },
             Participant::Verifier(_) => {
@@ -1640,7 +2060,7 @@ impl CoordinatorState {
         time: &dyn TimeSource,
     ) -> Result<(), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if task.chunk_id() > self.environment.number_of_chunks() {
+        if task.chunk_id() >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1657,7 +2077,7 @@ impl CoordinatorState {
         time: &dyn TimeSource,
     ) -> Result<(), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if task.chunk_id() > self.environment.number_of_chunks() {
+        if task.chunk_id() >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1677,7 +2097,7 @@ impl CoordinatorState {
         chunk_id: u64,
     ) -> Result<Option<&Task>, CoordinatorError> {
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1721,7 +2141,7 @@ impl CoordinatorState {
         chunk_id: u64,
     ) -> Result<Option<&Task>, CoordinatorError> {
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1771,7 +2191,7 @@ impl CoordinatorState {
         let contribution_id = task.contribution_id();
 
         // Check that the chunk ID is valid.
-        if chunk_id > self.environment.number_of_chunks() {
+        if chunk_id >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1801,7 +2221,7 @@ impl CoordinatorState {
     #[inline]
     pub(super) fn add_pending_verification(&mut self, task: &Task) -> Result<(), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if task.chunk_id() > self.environment.number_of_chunks() {
+        if task.chunk_id() >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1838,7 +2258,7 @@ impl CoordinatorState {
     #[inline]
     pub(super) fn remove_pending_verification(&mut self, task: &Task) -> Result<(), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if task.chunk_id() > self.environment.number_of_chunks() {
+        if task.chunk_id() >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1858,10 +2278,283 @@ impl CoordinatorState {
             .pending_verification
             .remove(task)
             .ok_or(CoordinatorError::VerifierMissing)?;
+        self.urgent_verification.remove(task);
+
+        Ok(())
+    }
+
+    ///
+    /// Marks the given (chunk ID, contribution ID) task pending verification as urgent,
+    /// so that it is handed to verifiers before the other pending verifications.
+    ///
+    #[inline]
+    pub(super) fn mark_verification_urgent(&mut self, task: &Task) -> Result<(), CoordinatorError> {
+        // Check that the task is pending verification.
+        if !self.pending_verification.contains_key(task) {
+            return Err(CoordinatorError::ChunkIdMissing);
+        }
+
+        info!(
+            "Marking the verification of (chunk {}, contribution {}) as urgent",
+            task.chunk_id(),
+            task.contribution_id()
+        );
+
+        self.urgent_verification.insert(*task);
+
+        Ok(())
+    }
+
+    ///
+    /// Returns how urgently the verification of the given (chunk ID, contribution ID) task
+    /// is needed, which is urgent if the coordinator marked it so, and decided by the
+    /// prioritization policy of the environment otherwise.
+    ///
+    #[inline]
+    pub fn verification_priority(&self, task: &Task) -> VerificationPriority {
+        if self.urgent_verification.contains(task) {
+            return VerificationPriority::Urgent;
+        }
+        self.environment
+            .prioritization_policy()
+            .policy()
+            .priority(task, self.number_of_outstanding_tasks())
+    }
+
+    ///
+    /// Returns the tasks pending verification with their priority, in the order
+    /// they are handed to verifiers in.
+    ///
+    pub fn prioritized_pending_verifications(&self) -> Vec<(Task, VerificationPriority)> {
+        let policy = self.environment.prioritization_policy().policy();
+        let outstanding_tasks = self.number_of_outstanding_tasks();
+
+        let mut pending: Vec<_> = self
+            .pending_verification
+            .keys()
+            .map(|task| {
+                let priority = if self.urgent_verification.contains(task) {
+                    VerificationPriority::Urgent
+                } else {
+                    policy.priority(task, outstanding_tasks)
+                };
+                (*task, priority)
+            })
+            .collect();
+        prioritization::order(&mut pending);
+        pending
+    }
+
+    ///
+    /// Returns the number of tasks of the current round which are not verified yet,
+    /// i.e. the tasks assigned to or computed by the current contributors, and the
+    /// tasks pending verification.
+    ///
+    #[inline]
+    fn number_of_outstanding_tasks(&self) -> u64 {
+        let contributing: usize = self
+            .current_contributors
+            .values()
+            .map(|info| info.assigned_tasks.len() + info.pending_tasks.len())
+            .sum();
+        (contributing + self.pending_verification.len()) as u64
+    }
+
+    ///
+    /// Adds the given (chunk ID, contribution ID) task of the given contributor to the
+    /// map of tasks which were accepted by a sampled verification, and are pending a full verification.
+    ///
+    #[inline]
+    pub(super) fn add_deferred_verification(
+        &mut self,
+        task: &Task,
+        contributor: &Participant,
+    ) -> Result<(), CoordinatorError> {
+        // Check that the chunk ID is valid.
+        if task.chunk_id() >= self.environment.number_of_chunks() {
+            return Err(CoordinatorError::ChunkIdInvalid);
+        }
+
+        // Check that the participant is a contributor.
+        if !contributor.is_contributor() {
+            return Err(CoordinatorError::ExpectedContributor);
+        }
+
+        debug!(
+            "Adding (chunk {}, contribution {}) to deferred verifications",
+            task.chunk_id(),
+            task.contribution_id(),
+        );
+
+        self.deferred_verification.insert(*task, contributor.clone());
 
         Ok(())
     }
 
+    pub fn get_deferred_verifications(&self) -> &HashMap<Task, Participant> {
+        &self.deferred_verification
+    }
+
+    ///
+    /// Returns the upload processed under the given idempotency key in the current round, if any.
+    ///
+    #[inline]
+    pub fn lookup_processed_upload(&self, idempotency_key: &str) -> Option<&ProcessedUpload> {
+        self.processed_uploads.get(idempotency_key)
+    }
+
+    ///
+    /// Records the upload of the given participant for the given task under the given idempotency key.
+    ///
+    #[inline]
+    pub(super) fn add_processed_upload(&mut self, idempotency_key: String, participant: &Participant, task: Task) {
+        self.processed_uploads.insert(
+            idempotency_key,
+            ProcessedUpload {
+                participant: participant.clone(),
+                task,
+            },
+        );
+    }
+
+    ///
+    /// Records the commitment of the given contributor to its seed, which is only accepted
+    /// before the contributor locked any chunk of the current round, so before it was handed
+    /// any challenge. Committing again to the same seed returns the receipt of the first commitment.
+    ///
+    pub(super) fn commit_seed(
+        &mut self,
+        participant: &Participant,
+        commitment: &str,
+        time: &dyn TimeSource,
+    ) -> Result<SeedCommitmentReceipt, CoordinatorError> {
+        // Check that the commitment is a hex encoded hash.
+        if commitment.len() != 64 || hex::decode(commitment).is_err() {
+            return Err(CoordinatorError::SeedCommitmentInvalid);
+        }
+        let commitment = commitment.to_lowercase();
+
+        if let Some(receipt) = self.seed_commitments.get(participant) {
+            if receipt.commitment != commitment {
+                return Err(CoordinatorError::SeedCommitmentMismatch);
+            }
+            return Ok(receipt.clone());
+        }
+
+        // Check that the participant is a contributor of the current round.
+        if !self.current_contributors.contains_key(participant) {
+            return Err(CoordinatorError::ParticipantNotFound(participant.clone()));
+        }
+
+        // Check that the contributor was not handed a challenge yet.
+        if self.locking_contributors.contains(participant) {
+            return Err(CoordinatorError::SeedCommittedAfterLock);
+        }
+
+        let receipt = SeedCommitmentReceipt {
+            round_height: self.current_round_height.ok_or(CoordinatorError::RoundHeightNotSet)?,
+            contributor: participant.address(),
+            scheme: SEED_COMMITMENT_SCHEME.to_string(),
+            commitment,
+            committed_at: time.now_utc().unix_timestamp(),
+        };
+        self.seed_commitments.insert(participant.clone(), receipt.clone());
+
+        Ok(receipt)
+    }
+
+    ///
+    /// Returns the receipt of the commitment of the given contributor to its seed in the
+    /// current round, if it made one.
+    ///
+    #[inline]
+    pub fn seed_commitment(&self, participant: &Participant) -> Option<&SeedCommitmentReceipt> {
+        self.seed_commitments.get(participant)
+    }
+
+    ///
+    /// Sets the class of hardware the given contributor computes on.
+    ///
+    #[inline]
+    pub(super) fn set_hardware_class(&mut self, participant: &Participant, hardware_class: &str) {
+        self.hardware_classes.insert(participant.clone(), hardware_class.to_string());
+    }
+
+    ///
+    /// Returns the class of hardware the given contributor reported, if any.
+    ///
+    #[inline]
+    pub fn hardware_class(&self, participant: &Participant) -> Option<&str> {
+        self.hardware_classes.get(participant).map(String::as_str)
+    }
+
+    ///
+    /// Sets the machine the given contributor runs on non-interactively.
+    ///
+    #[inline]
+    pub(super) fn set_machine_attestation(&mut self, participant: &Participant, machine: MachineAttestation) {
+        self.machine_attestations.insert(participant.clone(), machine);
+    }
+
+    ///
+    /// Returns the machine the given contributor reported, if any.
+    ///
+    #[inline]
+    pub fn machine_attestation(&self, participant: &Participant) -> Option<&MachineAttestation> {
+        self.machine_attestations.get(participant)
+    }
+
+    ///
+    /// Returns the average number of seconds a contribution takes on the given
+    /// class of hardware, if a contribution was computed on it yet.
+    ///
+    #[inline]
+    pub fn estimated_compute_seconds(&self, hardware_class: &str) -> Option<u64> {
+        match self.compute_times.get(hardware_class) {
+            Some(compute_times) if compute_times.contributions > 0 => {
+                Some(compute_times.total_seconds / compute_times.contributions)
+            }
+            _ => None,
+        }
+    }
+
+    ///
+    /// Adds the runtime of the given task, as measured by the task timer, to the
+    /// compute times of the class of hardware the given contributor reported.
+    ///
+    /// This function is a best effort tracker, like the task timer it reads from.
+    ///
+    #[inline]
+    fn add_compute_time(&mut self, participant: &Participant, task: &Task) {
+        let hardware_class = match self.hardware_classes.get(participant) {
+            Some(hardware_class) => hardware_class,
+            None => return,
+        };
+
+        let runtime = self
+            .current_metrics
+            .as_ref()
+            .and_then(|metrics| metrics.task_timer.get(participant))
+            .and_then(|tasks| tasks.get(task));
+
+        if let Some((start, Some(end))) = runtime {
+            let compute_times = self.compute_times.entry(hardware_class.clone()).or_default();
+            compute_times.total_seconds += end.saturating_sub(*start).max(0) as u64;
+            compute_times.contributions += 1;
+        }
+    }
+
+    ///
+    /// Removes the given (chunk ID, contribution ID) task from the map of tasks pending a full verification.
+    ///
+    #[inline]
+    pub(super) fn remove_deferred_verification(&mut self, task: &Task) -> Result<(), CoordinatorError> {
+        match self.deferred_verification.remove(task) {
+            Some(_) => Ok(()),
+            None => Err(CoordinatorError::ChunkIdMissing),
+        }
+    }
+
     ///
     /// Adds the given (chunk ID, contribution ID) task to the completed tasks of the given participant,
     /// and removes the chunk ID from the locks held by the given participant.
@@ -1881,7 +2574,7 @@ impl CoordinatorState {
         time: &dyn TimeSource,
     ) -> Result<(), CoordinatorError> {
         // Check that the chunk ID is valid.
-        if task.chunk_id() > self.environment.number_of_chunks() {
+        if task.chunk_id() >= self.environment.number_of_chunks() {
             return Err(CoordinatorError::ChunkIdInvalid);
         }
 
@@ -1892,6 +2585,7 @@ impl CoordinatorState {
                 Some(participant_info) => {
                     participant_info.completed_task(task, time)?;
                     self.stop_task_timer(participant, &task, time);
+                    self.add_compute_time(participant, task);
                     self.add_pending_verification(task)
                 }
                 None => Err(CoordinatorError::ParticipantNotFound(participant.clone())),
@@ -2287,6 +2981,10 @@ impl CoordinatorState {
                     verifier_info.disposed_tasks.extend(disposed_tasks);
                 }
 
+                // Disposed tasks will be contributed and verified again, without the marks of their verifications.
+                self.deferred_verification.retain(|task, _| !all_disposed_tasks.contains(task));
+                self.urgent_verification.retain(|task| !all_disposed_tasks.contains(task));
+
                 // Remove the current verifier from the coordinator state.
                 self.current_contributors.remove(&participant);
 
@@ -2464,51 +3162,104 @@ impl CoordinatorState {
     ///
     /// Updates the state of the queue for all waiting participants.
     ///
+    /// The contributors are assigned to rounds in the order decided by
+    /// the scheduling policy of the environment.
+    ///
     #[inline]
-    pub(super) fn update_queue(&mut self) -> Result<(), CoordinatorError> {
+    pub(super) fn update_queue(&mut self, time: &dyn TimeSource) -> Result<(), CoordinatorError> {
         // Fetch the next round height.
         let next_round = match self.current_round_height {
             Some(round_height) => round_height + 1,
             _ => return Err(CoordinatorError::RoundHeightNotSet),
         };
 
-        // Sort the participants in the queue by time joined.
-        let mut queue: Vec<_> = self
-            .queue
-            .clone()
-            .into_par_iter()
-            .map(|(p, (r, _, ls, j))| (p, r, ls, j))
-            .collect();
-        queue.par_sort_by(|a, b| (a.3).cmp(&b.3));
-
         // Parse the queue participants into contributors and verifiers,
         // and check that they are not banned participants.
-        let contributors: Vec<(_, _, _, _)> = queue
-            .clone()
-            .into_par_iter()
-            .filter(|(p, _, _, _)| p.is_contributor() && !self.banned.contains(&p))
+        let mut contributors: Vec<_> = self
+            .queue
+            .par_iter()
+            .filter(|(p, _)| p.is_contributor() && !self.banned.contains(*p))
+            .map(|(p, (r, _, _, j))| QueuedContributor {
+                participant: p.clone(),
+                reliability: *r,
+                joined: *j,
+            })
             .collect();
 
+        // Sort the contributors in the queue by time joined, and by ID if they joined
+        // at the same time, so that the order does not depend on the order of the map.
+        contributors.par_sort_by(|a, b| {
+            a.joined
+                .cmp(&b.joined)
+                .then_with(|| a.participant.to_string().cmp(&b.participant.to_string()))
+        });
+
+        // Order the contributors by the scheduling policy, as of the time the queue was first
+        // scheduled for the next round, so that the order is the same for every update.
+        let now = time.now_utc();
+        let scheduled_at = match self.queue_scheduled_at {
+            Some((round_height, scheduled_at)) if round_height == next_round => scheduled_at,
+            _ => {
+                self.queue_scheduled_at = Some((next_round, now));
+                now
+            }
+        };
+        self.environment
+            .scheduling_policy()
+            .policy()
+            .schedule(&mut contributors, next_round, scheduled_at);
+
+        // Schedule the contributors whose reserved time window is open first, as well as the
+        // contributors who were scheduled for the next round while their window was open,
+        // and forget the time windows which have ended once their round has started.
+        self.reservations
+            .retain(|reservation| reservation.ends_at > now || reservation.assigned_round == Some(next_round));
+        let reservations = &self.reservations;
+        contributors.sort_by_key(|contributor| {
+            !reservations.iter().any(|reservation| {
+                reservation.participant == contributor.participant && reservation.is_scheduled(next_round, now)
+            })
+        });
+
         // Fetch the permitted number of contributors
         let maximum_contributors = self.environment.maximum_contributors_per_round();
 
+        // Keep the contributors with an open time window in the next round until it starts,
+        // as at most a round of contributors reserve overlapping time windows.
+        let next_round_contributors = &contributors[..maximum_contributors.min(contributors.len())];
+        for reservation in self.reservations.iter_mut() {
+            if reservation.is_active(now)
+                && next_round_contributors
+                    .iter()
+                    .any(|contributor| contributor.participant == reservation.participant)
+            {
+                reservation.assigned_round = Some(next_round);
+            }
+        }
+
         // Initialize the updated queue.
         let mut updated_queue = HashMap::with_capacity(contributors.len());
 
         // Update assigned round height for each contributor.
         for (index, round) in contributors.chunks(maximum_contributors).enumerate() {
-            for (contributor, reliability, last_seen, joined) in round.into_iter() {
+            for contributor in round {
                 let assigned_round = next_round + index as u64;
                 trace!(
                     "Assigning contributor {} who joined at {} with reliability {} in queue to round {}",
-                    contributor,
-                    joined,
-                    reliability,
+                    contributor.participant,
+                    contributor.joined,
+                    contributor.reliability,
                     assigned_round
                 );
+                let (_, _, last_seen, _) = self.queue[&contributor.participant];
                 updated_queue.insert(
-                    contributor.clone(),
-                    (*reliability, Some(assigned_round), *last_seen, *joined),
+                    contributor.participant.clone(),
+                    (
+                        contributor.reliability,
+                        Some(assigned_round),
+                        last_seen,
+                        contributor.joined,
+                    ),
                 );
             }
         }
@@ -2544,6 +3295,11 @@ impl CoordinatorState {
                     return false;
                 }
 
+                // Keep the contributor in the round until its contributions are fully verified.
+                if self.deferred_verification.values().any(|c| c == contributor) {
+                    return true;
+                }
+
                 // Attempt to set the contributor as finished.
                 let mut finished_info = contributor_info.clone();
                 if let Err(_) = finished_info.finish(time) {
@@ -2696,8 +3452,11 @@ impl CoordinatorState {
     /// Note that as this function only checks dropped participants who have already
     /// been processed, we do not need to call `CoordinatorState::ban_participant`.
     ///
+    /// Returns the participants which were banned by this update.
+    ///
     #[inline]
-    pub(super) fn update_banned_participants(&mut self) -> Result<(), CoordinatorError> {
+    pub(super) fn update_banned_participants(&mut self) -> Result<Vec<Participant>, CoordinatorError> {
+        let mut banned = vec![];
         for participant_info in self.dropped.clone() {
             if !self.banned.contains(&participant_info.id) {
                 // Fetch the number of times this participant has been dropped.
@@ -2710,13 +3469,14 @@ impl CoordinatorState {
                 // Check if the participant meets the ban threshold.
                 if count > self.environment.participant_ban_threshold() as usize {
                     self.banned.insert(participant_info.id.clone());
+                    banned.push(participant_info.id.clone());
 
                     debug!("{} is being banned", participant_info.id);
                 }
             }
         }
 
-        Ok(())
+        Ok(banned)
     }
 
     ///
@@ -2926,6 +3686,9 @@ impl CoordinatorState {
             return Err(CoordinatorError::RoundNumberOfContributorsUnauthorized);
         }
 
+        // Check that the next round contains enough contributors to release its finalization lock.
+        self.check_finalization_lock_admission(next_round_height, contributors.iter().map(|(p, _)| p))?;
+
         // Initialize the precommit stage for the next round.
         let mut queue = self.queue.clone();
         let mut next = HashMap::default();
@@ -3090,6 +3853,13 @@ impl CoordinatorState {
 
         // Reset the next round map.
         self.next = HashMap::new();
+
+        // Uploads are only retried within the round they were made in.
+        self.processed_uploads = HashMap::new();
+
+        // Seeds are committed to before the first lock of each round.
+        self.seed_commitments = HashMap::new();
+        self.locking_contributors = HashSet::new();
     }
 
     ///
@@ -3469,7 +4239,7 @@ mod tests {
         assert_eq!(0, state.queue.get(&contributor_1).unwrap().0);
         assert_eq!(0, state.queue.get(&contributor_2).unwrap().0);
 
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
 
         // Drop one of the participants.
         state.drop_participant(&contributor_1, &time).unwrap();
@@ -3557,7 +4327,7 @@ mod tests {
         assert_eq!(None, participant.1);
 
         // Update the state of the queue.
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         assert_eq!(1, state.queue.len());
         assert_eq!(0, state.next.len());
         assert_eq!(Some(current_round_height), state.current_round_height);
@@ -3622,7 +4392,7 @@ mod tests {
             assert_eq!(None, participant.1);
 
             // Update the state of the queue.
-            state.update_queue().unwrap();
+            state.update_queue(&time).unwrap();
             assert_eq!(id, state.queue.len());
             assert_eq!(0, state.next.len());
             assert_eq!(Some(current_round_height), state.current_round_height);
@@ -3642,18 +4412,83 @@ mod tests {
         }
 
         // Update the state of the queue.
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         assert_eq!(number_of_contributors_in_queue, state.queue.len());
         assert_eq!(0, state.next.len());
         assert_eq!(Some(current_round_height), state.current_round_height);
 
         // Update the state of the queue.
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         assert_eq!(number_of_contributors_in_queue, state.queue.len());
         assert_eq!(0, state.next.len());
         assert_eq!(Some(current_round_height), state.current_round_height);
     }
 
+    #[test]
+    fn test_reserve_slot() {
+        let time = MockTimeSource::new(OffsetDateTime::now_utc());
+        let environment = TEST_ENVIRONMENT.clone();
+
+        // Initialize a new coordinator state.
+        let current_round_height = 5;
+        let mut state = CoordinatorState::new(environment.clone());
+        state.initialize(current_round_height);
+
+        // Add enough contributors to the queue to fill the next two rounds.
+        let maximum_contributors_per_round = environment.maximum_contributors_per_round();
+        for id in 0..2 * maximum_contributors_per_round {
+            let contributor = Participant::Contributor(id.to_string());
+            let contributor_ip = IpAddr::V4(format!("0.0.0.{}", id).parse().unwrap());
+            state
+                .add_to_queue(contributor, Some(contributor_ip), 10, &time)
+                .unwrap();
+        }
+        let contributor = Participant::Contributor((2 * maximum_contributors_per_round - 1).to_string());
+
+        // Reserve a time window for the contributor who joined last.
+        let starts_at = time.now_utc() + Duration::days(1);
+        let ends_at = starts_at + Duration::hours(4);
+        let reservation = state.reserve_slot(&contributor, starts_at, ends_at, &time).unwrap();
+        assert_eq!(1, state.reservations().len());
+
+        // Check that the contributor cannot reserve an overlapping time window.
+        let later = starts_at + Duration::hours(1);
+        let result = state.reserve_slot(&contributor, later, ends_at + Duration::hours(1), &time);
+        assert!(matches!(result, Err(CoordinatorError::ReservationOverlapping)));
+
+        // Check that no more contributors can reserve the time window than fit in a round.
+        for id in 1..maximum_contributors_per_round {
+            let other = Participant::Contributor(format!("reserved-{}", id));
+            state.reserve_slot(&other, starts_at, ends_at, &time).unwrap();
+        }
+        let other = Participant::Contributor("reserved-0".to_string());
+        let result = state.reserve_slot(&other, starts_at, ends_at, &time);
+        assert!(matches!(result, Err(CoordinatorError::ReservationSlotsFull)));
+
+        // Before the time window opens, the contributor is scheduled in the order it joined.
+        state.update_queue(&time).unwrap();
+        assert_eq!(Some(current_round_height + 2), state.queue[&contributor].1);
+
+        // While the time window is open, the contributor is scheduled for the next round.
+        time.set_time(starts_at);
+        state.update_queue(&time).unwrap();
+        assert_eq!(Some(current_round_height + 1), state.queue[&contributor].1);
+
+        // Once the time window ended, the contributor is still scheduled for the next round.
+        time.set_time(ends_at);
+        state.update_queue(&time).unwrap();
+        assert_eq!(Some(current_round_height + 1), state.queue[&contributor].1);
+        assert_eq!(1, state.reservations().len());
+
+        // Once the round started, the time window is forgotten.
+        state.current_round_height = Some(current_round_height + 1);
+        state.update_queue(&time).unwrap();
+        assert_eq!(Some(current_round_height + 3), state.queue[&contributor].1);
+        assert!(state.reservations().is_empty());
+        let result = state.cancel_reservation(&contributor, reservation.id);
+        assert!(matches!(result, Err(CoordinatorError::ReservationMissing)));
+    }
+
     #[test]
     fn test_remove_from_queue_contributor() {
         let time = SystemTimeSource::new();
@@ -3722,7 +4557,7 @@ mod tests {
         assert_eq!(1, state.queue.len());
 
         // Update the state of the queue.
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         assert_eq!(1, state.queue.len());
         assert_eq!(0, state.next.len());
         assert_eq!(Some(current_round_height), state.current_round_height);
@@ -3815,7 +4650,7 @@ mod tests {
         assert_eq!(1, state.queue.len());
 
         // Update the state of the queue.
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         assert_eq!(1, state.queue.len());
         assert_eq!(0, state.next.len());
         assert_eq!(Some(current_round_height), state.current_round_height);
@@ -3893,7 +4728,7 @@ mod tests {
         state
             .add_to_queue(contributor.clone(), Some(contributor_ip), 10, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
         assert!(state.is_current_round_finished());
@@ -3957,7 +4792,7 @@ mod tests {
         state
             .add_to_queue(contributor.clone(), Some(contributor_ip), 10, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
         assert!(state.is_current_round_finished());
@@ -4018,6 +4853,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prioritized_pending_verifications() {
+        let time = SystemTimeSource::new();
+        let environment = TEST_ENVIRONMENT.clone();
+
+        // Fetch the contributor and verifier of the coordinator.
+        let contributor = test_coordinator_contributor(&environment).unwrap();
+        let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+        let verifier = test_coordinator_verifier(&environment).unwrap();
+
+        // Initialize a new coordinator state, and advance it to the next round.
+        let current_round_height = 5;
+        let mut state = CoordinatorState::new(environment.clone());
+        state.initialize(current_round_height);
+        state
+            .add_to_queue(contributor.clone(), Some(contributor_ip), 10, &time)
+            .unwrap();
+        state.update_queue(&time).unwrap();
+        state.aggregating_current_round(&time).unwrap();
+        state.aggregated_current_round(&time).unwrap();
+        state.precommit_next_round(current_round_height + 1, &time).unwrap();
+        state.commit_next_round();
+
+        // Contribute to two chunks.
+        for _ in 0..2 {
+            let task = state.fetch_task(&contributor, &time).unwrap();
+            state.acquired_lock(&contributor, task.chunk_id(), &time).unwrap();
+            state.completed_task(&contributor, &task, &time).unwrap();
+        }
+        let (first, second) = (Task::new(0, 1), Task::new(1, 1));
+        assert_eq!(
+            vec![
+                (first, VerificationPriority::Normal),
+                (second, VerificationPriority::Normal)
+            ],
+            state.prioritized_pending_verifications()
+        );
+
+        // An urgent verification is handed out first.
+        state.mark_verification_urgent(&second).unwrap();
+        assert_eq!(VerificationPriority::Urgent, state.verification_priority(&second));
+        assert_eq!(
+            vec![
+                (second, VerificationPriority::Urgent),
+                (first, VerificationPriority::Normal)
+            ],
+            state.prioritized_pending_verifications()
+        );
+
+        // Only a pending verification can be marked urgent.
+        assert!(state.mark_verification_urgent(&Task::new(2, 1)).is_err());
+
+        // Verifying the task clears its mark.
+        state.completed_task(&verifier, &second, &time).unwrap();
+        assert!(state.urgent_verification.is_empty());
+        assert_eq!(
+            vec![(first, VerificationPriority::Normal)],
+            state.prioritized_pending_verifications()
+        );
+
+        // Under the round tail policy, the verifications are urgent once few tasks are outstanding.
+        let outstanding_tasks = state.number_of_outstanding_tasks();
+        let round_tail = |tail_tasks| {
+            Testing::from(Parameters::Test8Chunks)
+                .prioritization_policy(prioritization::PrioritizationPolicyKind::RoundTail { tail_tasks })
+                .into()
+        };
+        state.environment = round_tail(outstanding_tasks - 1);
+        assert_eq!(VerificationPriority::Normal, state.verification_priority(&first));
+        state.environment = round_tail(outstanding_tasks);
+        assert_eq!(VerificationPriority::Urgent, state.verification_priority(&first));
+
+        // Resetting the round clears the marks, as its tasks are contributed to again.
+        state.environment = environment;
+        state.mark_verification_urgent(&first).unwrap();
+        state.reset_current_round(false, &time).unwrap();
+        assert!(state.urgent_verification.is_empty());
+    }
+
     #[test]
     fn test_round_2x1() {
         test_logger();
@@ -4042,7 +4956,7 @@ mod tests {
         state
             .add_to_queue(contributor_2.clone(), Some(contributor_2_ip), 9, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
         assert!(state.is_current_round_finished());
@@ -4157,7 +5071,7 @@ mod tests {
         state
             .add_to_queue(contributor_2.clone(), Some(contributor_2_ip), 9, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
         assert!(state.is_current_round_finished());
@@ -4304,7 +5218,7 @@ mod tests {
         state
             .add_to_queue(contributor_2.clone(), Some(contributor_2_ip), 9, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
 
@@ -4464,7 +5378,7 @@ mod tests {
         state
             .add_to_queue(contributor_2.clone(), Some(contributor_2_ip), 9, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
 
@@ -4613,7 +5527,7 @@ mod tests {
         state
             .add_to_queue(contributor_1.clone(), Some(contributor_1_ip), 10, &time)
             .unwrap();
-        state.update_queue().unwrap();
+        state.update_queue(&time).unwrap();
         state.aggregating_current_round(&time).unwrap();
         state.aggregated_current_round(&time).unwrap();
 
@@ -1,9 +1,19 @@
-use crate::{objects::Participant, storage::Disk};
-use phase1::{chunk_size, helpers::CurveKind, total_size_in_g1, ContributionMode, ProvingSystem};
+use crate::{
+    cohort::CohortPhase,
+    objects::Participant,
+    policy::DiversityPolicy,
+    prioritization::PrioritizationPolicyKind,
+    scheduling::SchedulingPolicyKind,
+    storage::{Disk, StorageEncryption},
+};
+use phase1::{chunk_size, helpers::CurveKind, total_size_in_g1, ContributionMode, ProvingSystem, VerificationMode};
+use setup1_shared::structures::SetupKind;
 use setup_utils::{CheckForCorrectness, UseCompression};
 
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
 
 type BatchSize = usize;
 type ChunkSize = usize;
@@ -197,6 +207,49 @@ impl Parameters {
     }
 }
 
+/// The conditions which must hold before a round may be finalized (aggregated),
+/// so that it cannot be finalized early with too few independent contributions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalizationLock {
+    /// The earliest time at which the round may be finalized.
+    pub not_before: Option<OffsetDateTime>,
+    /// The minimum number of contributors, excluding the contributors
+    /// managed by the coordinator, who must have finished the round.
+    /// The round only starts with at least as many queued contributors,
+    /// and a round which finished with fewer must be reset.
+    pub minimum_contributions: usize,
+}
+
+/// A mirror which serves the challenges of the current round to contributors,
+/// so that the downloads do not all go through the coordinator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactReplicaStore {
+    /// The region the replica serves, advertised to contributors.
+    pub region: String,
+    /// The base URL contributors download the challenges from, which serves
+    /// the replica directory under the coordinator's download paths.
+    pub url: String,
+    /// The directory the coordinator replicates the challenges to,
+    /// e.g. a mount of the bucket behind the replica URL.
+    pub directory: String,
+}
+
+/// How often a contributor identity may contribute in a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyUsagePolicy {
+    /// A contributor contributes at most once to each chunk of a round.
+    OncePerChunk,
+    /// A contributor contributes at most once in a round, to a single chunk,
+    /// for ceremonies where each contribution covers the full parameters.
+    OncePerRound,
+}
+
+impl Default for KeyUsagePolicy {
+    fn default() -> Self {
+        KeyUsagePolicy::OncePerChunk
+    }
+}
+
 #[serde_with::serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Environment {
@@ -208,6 +261,38 @@ pub struct Environment {
     compressed_outputs: UseCompression,
     /// The input correctness check preference of the coordinator.
     check_input_for_correctness: CheckForCorrectness,
+    /// The verification mode used to accept contributions, which are fully
+    /// verified before the round is finalized if this is not `VerificationMode::Full`.
+    #[serde(default)]
+    verification_mode: VerificationMode,
+    /// The finalization locks of the rounds which have one, by round height.
+    #[serde(default)]
+    finalization_locks: BTreeMap<u64, FinalizationLock>,
+    /// The maximum number of chunks a contributor may pin to contribute to first,
+    /// where zero disables pinning.
+    #[serde(default)]
+    maximum_pinned_chunks: usize,
+    /// The maximum number of uploads a participant may make in a minute,
+    /// where zero disables the limit.
+    #[serde(default)]
+    maximum_uploads_per_minute: usize,
+    /// The number of contributions a contributor identity may make in a round.
+    #[serde(default)]
+    key_usage_policy: KeyUsagePolicy,
+    /// The minimum diversity of the contributors of each round before it may be finalized,
+    /// where `None` disables the policy.
+    #[serde(default)]
+    diversity_policy: Option<DiversityPolicy>,
+    /// The policy which decides the order contributors in the queue are scheduled in.
+    #[serde(default)]
+    scheduling_policy: SchedulingPolicyKind,
+    /// The policy which decides how urgently each pending verification is needed.
+    #[serde(default)]
+    prioritization_policy: PrioritizationPolicyKind,
+    /// The trusted cohort the first rounds are restricted to, where `None` opens
+    /// every round to the public.
+    #[serde(default)]
+    cohort_phase: Option<CohortPhase>,
 
     /// The minimum number of contributors permitted to participate in a round.
     minimum_contributors_per_round: usize,
@@ -255,6 +340,17 @@ pub struct Environment {
     deployment: Deployment,
     /// The base directory for disk storage of this coordinator.
     local_base_directory: String,
+    /// The base directory the contributions of finalized rounds are archived to,
+    /// where `None` disables archival.
+    #[serde(default)]
+    cold_storage_directory: Option<String>,
+    /// The keys the contributions archived to cold storage are encrypted with,
+    /// where `None` archives them as they are.
+    #[serde(default)]
+    storage_encryption: Option<StorageEncryption>,
+    /// The replicas the challenges of the current round are copied to.
+    #[serde(default)]
+    artifact_replicas: Vec<ArtifactReplicaStore>,
 
     disable_reliability_zeroing: bool,
 }
@@ -297,6 +393,98 @@ impl Environment {
         self.check_input_for_correctness
     }
 
+    ///
+    /// Returns the verification mode used to accept contributions.
+    ///
+    /// The default choice should be `VerificationMode::Full`. A sampled mode
+    /// accepts contributions sooner, and defers their full verification until
+    /// before the round is finalized.
+    ///
+    pub const fn verification_mode(&self) -> VerificationMode {
+        self.verification_mode
+    }
+
+    ///
+    /// Returns the finalization lock of the given round, if it has one.
+    ///
+    /// A round with a finalization lock is not aggregated before the lock
+    /// date has passed and enough contributors have finished the round.
+    ///
+    pub fn finalization_lock(&self, round_height: u64) -> Option<&FinalizationLock> {
+        self.finalization_locks.get(&round_height)
+    }
+
+    ///
+    /// Returns the maximum number of chunks a contributor may pin.
+    ///
+    /// The default choice should be zero, which disables pinning. Pinning lets
+    /// contributors with large hardware take the chunks they have resources
+    /// for first, for example the largest chunks.
+    ///
+    pub const fn maximum_pinned_chunks(&self) -> usize {
+        self.maximum_pinned_chunks
+    }
+
+    ///
+    /// Returns the maximum number of uploads a participant may make in a minute.
+    ///
+    /// The default choice should be zero, which disables the limit. Production
+    /// deployments should set it above the number of chunks a participant may
+    /// lock, so that retries of failed uploads are not rejected.
+    ///
+    pub const fn maximum_uploads_per_minute(&self) -> usize {
+        self.maximum_uploads_per_minute
+    }
+
+    ///
+    /// Returns the number of contributions a contributor identity may make in a round.
+    ///
+    /// The default choice should be `KeyUsagePolicy::OncePerChunk`. Ceremonies
+    /// in `ContributionMode::Full` should use `KeyUsagePolicy::OncePerRound`.
+    ///
+    pub const fn key_usage_policy(&self) -> KeyUsagePolicy {
+        self.key_usage_policy
+    }
+
+    ///
+    /// Returns the minimum diversity of the contributors of each round before it may be finalized.
+    ///
+    /// The default choice should be `None`. Unlike a finalization lock, which
+    /// is configured for a single round, the policy applies to every round.
+    ///
+    pub fn diversity_policy(&self) -> Option<&DiversityPolicy> {
+        self.diversity_policy.as_ref()
+    }
+
+    ///
+    /// Returns the policy which decides the order contributors in the queue are scheduled in.
+    ///
+    /// The default choice should be `SchedulingPolicyKind::FirstInFirstOut`. A lottery
+    /// gives every contributor the same chance of a slot, however late it joined.
+    ///
+    pub const fn scheduling_policy(&self) -> SchedulingPolicyKind {
+        self.scheduling_policy
+    }
+
+    ///
+    /// Returns the policy which decides how urgently each pending verification is needed.
+    ///
+    /// The default choice should be `PrioritizationPolicyKind::Manual`, under which only
+    /// the verifications marked urgent by the coordinator are handed out first.
+    ///
+    pub const fn prioritization_policy(&self) -> PrioritizationPolicyKind {
+        self.prioritization_policy
+    }
+
+    ///
+    /// Returns the trusted cohort the first rounds of the ceremony are restricted to.
+    ///
+    /// The default choice should be `None`, under which every round is open to the public.
+    ///
+    pub fn cohort_phase(&self) -> Option<&CohortPhase> {
+        self.cohort_phase.as_ref()
+    }
+
     ///
     /// Returns the minimum number of contributors permitted to
     /// participate in a round.
@@ -435,6 +623,40 @@ impl Environment {
         &self.local_base_directory
     }
 
+    ///
+    /// Returns the base directory the contributions of finalized rounds are archived to.
+    ///
+    /// The default choice should be `None`, which keeps all contributions in the
+    /// local base directory. Once a round is finalized, its contribution files are
+    /// only read again to re-verify the round, so they may be moved to cheaper storage.
+    ///
+    pub fn cold_storage_directory(&self) -> Option<&str> {
+        self.cold_storage_directory.as_deref()
+    }
+
+    ///
+    /// Returns the keys the contributions archived to cold storage are encrypted with.
+    ///
+    /// The default choice should be `None`, which archives the contributions as they are.
+    /// Archived contributions which are encrypted are decrypted when they are retrieved.
+    /// The files in the local base directory are never encrypted, so the keys require
+    /// a cold storage directory.
+    ///
+    pub fn storage_encryption(&self) -> Option<&StorageEncryption> {
+        self.storage_encryption.as_ref()
+    }
+
+    ///
+    /// Returns the replicas the challenges of the current round are copied to.
+    ///
+    /// The default choice should be no replicas, which serves all challenges from
+    /// the coordinator. Contributors verify a challenge they download from a replica
+    /// against the hash the coordinator advertises for it.
+    ///
+    pub fn artifact_replicas(&self) -> &[ArtifactReplicaStore] {
+        &self.artifact_replicas
+    }
+
     ///
     /// Returns the appropriate number of chunks for the coordinator
     /// to run given a proof system, power and chunk size.
@@ -474,6 +696,23 @@ impl From<Production> for Environment {
     }
 }
 
+/// The environment contributors run in for the kind of setup the coordinator announces in its public settings.
+impl From<&SetupKind> for Environment {
+    fn from(kind: &SetupKind) -> Environment {
+        match kind {
+            SetupKind::Development => Development::from(Parameters::TestCustom {
+                number_of_chunks: 64,
+                power: 16,
+                batch_size: 512,
+            })
+            .into(),
+            SetupKind::Inner => Production::from(Parameters::AleoInner).into(),
+            SetupKind::Outer => Production::from(Parameters::AleoOuter).into(),
+            SetupKind::Universal => Production::from(Parameters::AleoUniversal).into(),
+        }
+    }
+}
+
 // TODO (howardwu): Convert the implementation to a procedural macro.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Testing {
@@ -496,6 +735,71 @@ impl Testing {
         self
     }
 
+    pub fn finalization_lock(mut self, round_height: u64, lock: FinalizationLock) -> Self {
+        self.environment.finalization_locks.insert(round_height, lock);
+        self
+    }
+
+    pub fn maximum_pinned_chunks(mut self, maximum: usize) -> Self {
+        self.environment.maximum_pinned_chunks = maximum;
+        self
+    }
+
+    pub fn maximum_uploads_per_minute(mut self, maximum: usize) -> Self {
+        self.environment.maximum_uploads_per_minute = maximum;
+        self
+    }
+
+    pub fn key_usage_policy(mut self, key_usage_policy: KeyUsagePolicy) -> Self {
+        self.environment.key_usage_policy = key_usage_policy;
+        self
+    }
+
+    pub fn diversity_policy(mut self, diversity_policy: DiversityPolicy) -> Self {
+        self.environment.diversity_policy = Some(diversity_policy);
+        self
+    }
+
+    pub fn scheduling_policy(mut self, scheduling_policy: SchedulingPolicyKind) -> Self {
+        self.environment.scheduling_policy = scheduling_policy;
+        self
+    }
+
+    pub fn prioritization_policy(mut self, prioritization_policy: PrioritizationPolicyKind) -> Self {
+        self.environment.prioritization_policy = prioritization_policy;
+        self
+    }
+
+    pub fn cohort_phase(mut self, cohort_phase: CohortPhase) -> Self {
+        self.environment.cohort_phase = Some(cohort_phase);
+        self
+    }
+
+    pub fn cold_storage_directory(mut self, cold_storage_directory: &str) -> Self {
+        self.environment.cold_storage_directory = Some(cold_storage_directory.to_string());
+        self
+    }
+
+    pub fn storage_encryption(mut self, storage_encryption: StorageEncryption) -> Self {
+        self.environment.storage_encryption = Some(storage_encryption);
+        self
+    }
+
+    pub fn artifact_replica(mut self, artifact_replica: ArtifactReplicaStore) -> Self {
+        self.environment.artifact_replicas.push(artifact_replica);
+        self
+    }
+
+    pub fn local_base_directory(mut self, local_base_directory: &str) -> Self {
+        self.environment.local_base_directory = local_base_directory.to_string();
+        self
+    }
+
+    pub fn verification_mode(mut self, verification_mode: VerificationMode) -> Self {
+        self.environment.verification_mode = verification_mode;
+        self
+    }
+
     #[inline]
     pub fn coordinator_contributors(&self, contributors: &[Participant]) -> Self {
         // Check that all participants are contributors.
@@ -563,6 +867,15 @@ impl std::default::Default for Testing {
                 compressed_inputs: UseCompression::No,
                 compressed_outputs: UseCompression::Yes,
                 check_input_for_correctness: CheckForCorrectness::No,
+                verification_mode: VerificationMode::Full,
+                finalization_locks: BTreeMap::new(),
+                maximum_pinned_chunks: 0,
+                maximum_uploads_per_minute: 0,
+                key_usage_policy: KeyUsagePolicy::OncePerChunk,
+                diversity_policy: None,
+                scheduling_policy: SchedulingPolicyKind::FirstInFirstOut,
+                prioritization_policy: PrioritizationPolicyKind::Manual,
+                cohort_phase: None,
 
                 minimum_contributors_per_round: 1,
                 maximum_contributors_per_round: 5,
@@ -585,6 +898,9 @@ impl std::default::Default for Testing {
                 software_version: 1,
                 deployment: Deployment::Testing,
                 local_base_directory: "./transcript/testing".to_string(),
+                cold_storage_directory: None,
+                storage_encryption: None,
+                artifact_replicas: vec![],
 
                 disable_reliability_zeroing: false,
             },
@@ -624,6 +940,61 @@ impl Development {
         self
     }
 
+    pub fn finalization_lock(mut self, round_height: u64, lock: FinalizationLock) -> Self {
+        self.environment.finalization_locks.insert(round_height, lock);
+        self
+    }
+
+    pub fn maximum_pinned_chunks(mut self, maximum: usize) -> Self {
+        self.environment.maximum_pinned_chunks = maximum;
+        self
+    }
+
+    pub fn maximum_uploads_per_minute(mut self, maximum: usize) -> Self {
+        self.environment.maximum_uploads_per_minute = maximum;
+        self
+    }
+
+    pub fn key_usage_policy(mut self, key_usage_policy: KeyUsagePolicy) -> Self {
+        self.environment.key_usage_policy = key_usage_policy;
+        self
+    }
+
+    pub fn diversity_policy(mut self, diversity_policy: DiversityPolicy) -> Self {
+        self.environment.diversity_policy = Some(diversity_policy);
+        self
+    }
+
+    pub fn scheduling_policy(mut self, scheduling_policy: SchedulingPolicyKind) -> Self {
+        self.environment.scheduling_policy = scheduling_policy;
+        self
+    }
+
+    pub fn prioritization_policy(mut self, prioritization_policy: PrioritizationPolicyKind) -> Self {
+        self.environment.prioritization_policy = prioritization_policy;
+        self
+    }
+
+    pub fn cohort_phase(mut self, cohort_phase: CohortPhase) -> Self {
+        self.environment.cohort_phase = Some(cohort_phase);
+        self
+    }
+
+    pub fn cold_storage_directory(mut self, cold_storage_directory: &str) -> Self {
+        self.environment.cold_storage_directory = Some(cold_storage_directory.to_string());
+        self
+    }
+
+    pub fn storage_encryption(mut self, storage_encryption: StorageEncryption) -> Self {
+        self.environment.storage_encryption = Some(storage_encryption);
+        self
+    }
+
+    pub fn artifact_replica(mut self, artifact_replica: ArtifactReplicaStore) -> Self {
+        self.environment.artifact_replicas.push(artifact_replica);
+        self
+    }
+
     #[inline]
     pub fn coordinator_contributors(&self, contributors: &[Participant]) -> Self {
         // Check that all participants are contributors.
@@ -679,6 +1050,15 @@ impl std::default::Default for Development {
                 compressed_inputs: UseCompression::No,
                 compressed_outputs: UseCompression::Yes,
                 check_input_for_correctness: CheckForCorrectness::No,
+                verification_mode: VerificationMode::Full,
+                finalization_locks: BTreeMap::new(),
+                maximum_pinned_chunks: 0,
+                maximum_uploads_per_minute: 0,
+                key_usage_policy: KeyUsagePolicy::OncePerChunk,
+                diversity_policy: None,
+                scheduling_policy: SchedulingPolicyKind::FirstInFirstOut,
+                prioritization_policy: PrioritizationPolicyKind::Manual,
+                cohort_phase: None,
 
                 minimum_contributors_per_round: 1,
                 maximum_contributors_per_round: 5,
@@ -701,6 +1081,9 @@ impl std::default::Default for Development {
                 software_version: 1,
                 deployment: Deployment::Development,
                 local_base_directory: "./transcript/development".to_string(),
+                cold_storage_directory: None,
+                storage_encryption: None,
+                artifact_replicas: vec![],
 
                 disable_reliability_zeroing: false,
             },
@@ -745,6 +1128,61 @@ impl Production {
         self
     }
 
+    pub fn finalization_lock(mut self, round_height: u64, lock: FinalizationLock) -> Self {
+        self.environment.finalization_locks.insert(round_height, lock);
+        self
+    }
+
+    pub fn maximum_pinned_chunks(mut self, maximum: usize) -> Self {
+        self.environment.maximum_pinned_chunks = maximum;
+        self
+    }
+
+    pub fn maximum_uploads_per_minute(mut self, maximum: usize) -> Self {
+        self.environment.maximum_uploads_per_minute = maximum;
+        self
+    }
+
+    pub fn key_usage_policy(mut self, key_usage_policy: KeyUsagePolicy) -> Self {
+        self.environment.key_usage_policy = key_usage_policy;
+        self
+    }
+
+    pub fn diversity_policy(mut self, diversity_policy: DiversityPolicy) -> Self {
+        self.environment.diversity_policy = Some(diversity_policy);
+        self
+    }
+
+    pub fn scheduling_policy(mut self, scheduling_policy: SchedulingPolicyKind) -> Self {
+        self.environment.scheduling_policy = scheduling_policy;
+        self
+    }
+
+    pub fn prioritization_policy(mut self, prioritization_policy: PrioritizationPolicyKind) -> Self {
+        self.environment.prioritization_policy = prioritization_policy;
+        self
+    }
+
+    pub fn cohort_phase(mut self, cohort_phase: CohortPhase) -> Self {
+        self.environment.cohort_phase = Some(cohort_phase);
+        self
+    }
+
+    pub fn cold_storage_directory(mut self, cold_storage_directory: &str) -> Self {
+        self.environment.cold_storage_directory = Some(cold_storage_directory.to_string());
+        self
+    }
+
+    pub fn storage_encryption(mut self, storage_encryption: StorageEncryption) -> Self {
+        self.environment.storage_encryption = Some(storage_encryption);
+        self
+    }
+
+    pub fn artifact_replica(mut self, artifact_replica: ArtifactReplicaStore) -> Self {
+        self.environment.artifact_replicas.push(artifact_replica);
+        self
+    }
+
     #[inline]
     pub fn coordinator_contributors(&self, contributors: &[Participant]) -> Self {
         // Check that all participants are contributors.
@@ -794,6 +1232,15 @@ impl std::default::Default for Production {
                 compressed_inputs: UseCompression::No,
                 compressed_outputs: UseCompression::Yes,
                 check_input_for_correctness: CheckForCorrectness::No,
+                verification_mode: VerificationMode::Full,
+                finalization_locks: BTreeMap::new(),
+                maximum_pinned_chunks: 0,
+                maximum_uploads_per_minute: 0,
+                key_usage_policy: KeyUsagePolicy::OncePerChunk,
+                diversity_policy: None,
+                scheduling_policy: SchedulingPolicyKind::FirstInFirstOut,
+                prioritization_policy: PrioritizationPolicyKind::Manual,
+                cohort_phase: None,
 
                 minimum_contributors_per_round: 1,
                 maximum_contributors_per_round: 5,
@@ -816,6 +1263,9 @@ impl std::default::Default for Production {
                 software_version: 1,
                 deployment: Deployment::Production,
                 local_base_directory: "./transcript".to_string(),
+                cold_storage_directory: None,
+                storage_encryption: None,
+                artifact_replicas: vec![],
 
                 disable_reliability_zeroing: false,
             },
@@ -0,0 +1,258 @@
+//! The protocol events of a ceremony. The coordinator appends every
+//! [CeremonyEvent] to an event log in storage, one JSON object per
+//! line, so that the dynamics of a ceremony can be analyzed after
+//! the fact.
+
+use crate::{objects::Participant, CoordinatorError};
+use setup1_shared::protocol::v1::{ContributionPage, ContributionRecord, ContributionStatus, ContributionsQuery};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+
+/// The version of the schema of [CeremonyEvent]. It is incremented
+/// whenever an existing field or event type changes, so that
+/// exported events can be read with the schema they were written in.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// A protocol event, as written to the event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CeremonyEvent {
+    pub schema_version: u32,
+    pub timestamp: OffsetDateTime,
+    pub round_height: u64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+impl CeremonyEvent {
+    pub fn new(timestamp: OffsetDateTime, round_height: u64, kind: EventKind) -> Self {
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            timestamp,
+            round_height,
+            kind,
+        }
+    }
+}
+
+/// The type of a [CeremonyEvent], with the data specific to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// A contributor acquired the lock on a chunk to contribute to it.
+    ChunkAssigned {
+        participant: Participant,
+        chunk_id: u64,
+        contribution_id: u64,
+    },
+    /// A participant started to upload a file of the given size for a chunk, after the upload was checked.
+    UploadStarted {
+        participant: Participant,
+        chunk_id: u64,
+        size: u64,
+    },
+    /// A contributor uploaded a contribution which was added to the round.
+    ContributionUploaded {
+        participant: Participant,
+        chunk_id: u64,
+        contribution_id: u64,
+    },
+    /// A verifier uploaded a verification which was added to the round.
+    ContributionVerified {
+        participant: Participant,
+        chunk_id: u64,
+        contribution_id: u64,
+    },
+    /// A participant was dropped for exceeding a timeout.
+    ParticipantTimedOut { participant: Participant },
+    /// A participant was dropped by the coordinator operator.
+    ParticipantDropped { participant: Participant },
+    /// A participant was banned from the ceremony.
+    ParticipantBanned { participant: Participant },
+    /// A contributor committed to its seed before locking its first chunk of the round.
+    SeedCommitted { participant: Participant, commitment: String },
+    /// The ceremony opened to the public, on the schedule of the trusted cohort or by an administrator.
+    PublicPhaseOpened { scheduled: bool },
+    /// An operator advanced the ceremony to the round of the event, with the given contributors.
+    RoundAdvanced { contributors: Vec<Participant> },
+}
+
+///
+/// Returns the page of the contributions uploaded in the given events which matches
+/// the query, in the order they were uploaded, along with their verifications.
+///
+/// The cursor of a page is the position in the event log of the upload of its last
+/// contribution. As the event log is only ever appended to, a cursor stays valid,
+/// and the pages after it list the contributions uploaded since.
+///
+pub fn contribution_page(
+    events: &[CeremonyEvent],
+    query: &ContributionsQuery,
+) -> Result<ContributionPage, CoordinatorError> {
+    let start = match &query.cursor {
+        Some(cursor) => {
+            cursor
+                .parse::<usize>()
+                .map_err(|_| CoordinatorError::PageCursorInvalid)?
+                + 1
+        }
+        None => 0,
+    };
+    let limit = query.page_limit();
+
+    let mut verifications = HashMap::new();
+    for event in events {
+        if let EventKind::ContributionVerified {
+            participant,
+            chunk_id,
+            contribution_id,
+        } = &event.kind
+        {
+            verifications.insert(
+                (event.round_height, *chunk_id, *contribution_id),
+                (participant, event.timestamp),
+            );
+        }
+    }
+
+    let mut contributions = vec![];
+    let mut last_position = None;
+    for (position, event) in events.iter().enumerate().skip(start) {
+        let (participant, chunk_id, contribution_id) = match &event.kind {
+            EventKind::ContributionUploaded {
+                participant,
+                chunk_id,
+                contribution_id,
+            } => (participant, *chunk_id, *contribution_id),
+            _ => continue,
+        };
+        let verification = verifications.get(&(event.round_height, chunk_id, contribution_id));
+        let record = ContributionRecord {
+            round_height: event.round_height,
+            chunk_id,
+            contribution_id,
+            contributor: participant.to_string(),
+            contributed_at: event.timestamp.unix_timestamp(),
+            verifier: verification.map(|(verifier, _)| verifier.to_string()),
+            verified_at: verification.map(|(_, verified_at)| verified_at.unix_timestamp()),
+            status: match verification {
+                Some(_) => ContributionStatus::Verified,
+                None => ContributionStatus::Contributed,
+            },
+        };
+        if !query.matches(&record) {
+            continue;
+        }
+        // Another matching contribution means there is a next page.
+        if contributions.len() == limit {
+            return Ok(ContributionPage {
+                contributions,
+                next_cursor: last_position.map(|position| position.to_string()),
+            });
+        }
+        contributions.push(record);
+        last_position = Some(position);
+    }
+
+    Ok(ContributionPage {
+        contributions,
+        next_cursor: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    #[test]
+    fn test_event_schema() {
+        let event = CeremonyEvent::new(
+            datetime!(2021-11-01 12:00 UTC),
+            3,
+            EventKind::ChunkAssigned {
+                participant: Participant::new_contributor("test-contributor"),
+                chunk_id: 1,
+                contribution_id: 2,
+            },
+        );
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(1, json["schema_version"]);
+        assert_eq!(3, json["round_height"]);
+        assert_eq!("chunk_assigned", json["type"]);
+        assert_eq!("test-contributor.contributor", json["participant"]);
+        assert_eq!(1, json["chunk_id"]);
+        assert_eq!(2, json["contribution_id"]);
+
+        assert_eq!(event, serde_json::from_value(json).unwrap());
+    }
+
+    #[test]
+    fn test_contribution_page() {
+        let contributor = Participant::new_contributor("test-contributor");
+        let verifier = Participant::new_verifier("test-verifier");
+        let mut events = vec![];
+        for chunk_id in 0..5 {
+            let timestamp = datetime!(2021-11-01 12:00 UTC) + time::Duration::minutes(chunk_id as i64);
+            events.push(CeremonyEvent::new(
+                timestamp,
+                1,
+                EventKind::ContributionUploaded {
+                    participant: contributor.clone(),
+                    chunk_id,
+                    contribution_id: 1,
+                },
+            ));
+            if chunk_id % 2 == 0 {
+                events.push(CeremonyEvent::new(
+                    timestamp,
+                    1,
+                    EventKind::ContributionVerified {
+                        participant: verifier.clone(),
+                        chunk_id,
+                        contribution_id: 1,
+                    },
+                ));
+            }
+        }
+
+        // The pages list every contribution once, in the order they were uploaded.
+        let mut query = ContributionsQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut chunk_ids = vec![];
+        loop {
+            let page = contribution_page(&events, &query).unwrap();
+            chunk_ids.extend(page.contributions.iter().map(|record| record.chunk_id));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(vec![0, 1, 2, 3, 4], chunk_ids);
+
+        let verified = contribution_page(&events, &ContributionsQuery {
+            status: Some(ContributionStatus::Verified),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(3, verified.contributions.len());
+        assert_eq!(
+            Some("test-verifier.verifier"),
+            verified.contributions[0].verifier.as_deref()
+        );
+        assert_eq!(None, verified.next_cursor);
+
+        assert!(matches!(
+            contribution_page(&events, &ContributionsQuery {
+                cursor: Some("not-a-cursor".to_string()),
+                ..Default::default()
+            }),
+            Err(CoordinatorError::PageCursorInvalid)
+        ));
+    }
+}
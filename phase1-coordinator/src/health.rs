@@ -0,0 +1,211 @@
+//! Checks of the invariants of a running ceremony, summarized for operators so
+//! that a failing check can alert them, e.g. from a cron job which runs the
+//! `health` command of the coordinator and reports when it exits with an error.
+
+use crate::objects::Participant;
+
+use serde::Serialize;
+use std::{fmt, path::Path};
+use time::OffsetDateTime;
+
+/// The thresholds above which the ceremony is reported as unhealthy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthThresholds {
+    /// How long a chunk may stay locked beyond the lease of its participant, the
+    /// `participant_lock_timeout` of the environment, before the coordinator drops it.
+    pub lock_grace: time::Duration,
+    /// The maximum number of contributions waiting to be verified.
+    pub max_verification_backlog: usize,
+    /// The minimum number of bytes which must be free on the disk of the transcript.
+    pub min_free_bytes: u64,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            lock_grace: time::Duration::minutes(5),
+            max_verification_backlog: 64,
+            min_free_bytes: 10 << 30,
+        }
+    }
+}
+
+/// The outcome of one check of the ceremony.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthCheck {
+    pub check: &'static str,
+    pub healthy: bool,
+    pub detail: String,
+}
+
+impl HealthCheck {
+    fn new(check: &'static str, healthy: bool, detail: String) -> Self {
+        Self { check, healthy, detail }
+    }
+}
+
+/// The outcome of every check of the ceremony.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Returns `true` if every check passed.
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.healthy)
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = if check.healthy { "ok" } else { "FAILED" };
+            writeln!(f, "{:<6} {}: {}", status, check.check, check.detail)?;
+        }
+        let failed = self.checks.iter().filter(|check| !check.healthy).count();
+        match failed {
+            0 => writeln!(f, "The ceremony is healthy"),
+            _ => writeln!(f, "{} of {} checks failed", failed, self.checks.len()),
+        }
+    }
+}
+
+/// Checks that no chunk has been locked for longer than the lease and the grace period,
+/// given the participants holding each lock and the time they locked it at.
+pub fn check_locks(
+    locks: &[(Participant, u64, OffsetDateTime)],
+    now: OffsetDateTime,
+    lease: time::Duration,
+    grace: time::Duration,
+) -> HealthCheck {
+    let overdue: Vec<String> = locks
+        .iter()
+        .filter(|(_, _, lock_time)| now - *lock_time > lease + grace)
+        .map(|(participant, chunk_id, lock_time)| {
+            format!(
+                "chunk {} by {} for {} s",
+                chunk_id,
+                participant,
+                (now - *lock_time).whole_seconds()
+            )
+        })
+        .collect();
+    match overdue.is_empty() {
+        true => HealthCheck::new(
+            "locks",
+            true,
+            format!(
+                "{} chunks are locked, none for longer than {} s",
+                locks.len(),
+                (lease + grace).whole_seconds()
+            ),
+        ),
+        false => HealthCheck::new("locks", false, format!("overdue locks on {}", overdue.join(", "))),
+    }
+}
+
+/// Checks that the number of contributions waiting to be verified is at most `max_backlog`.
+pub fn check_verification_backlog(pending: usize, max_backlog: usize) -> HealthCheck {
+    HealthCheck::new(
+        "verification backlog",
+        pending <= max_backlog,
+        format!(
+            "{} contributions wait to be verified, at most {} may",
+            pending, max_backlog
+        ),
+    )
+}
+
+/// Checks that the state of the coordinator could be read from storage, given the error if it could not.
+pub fn check_storage(error: Option<String>) -> HealthCheck {
+    match error {
+        None => HealthCheck::new("storage", true, "the coordinator state is readable".to_string()),
+        Some(error) => HealthCheck::new(
+            "storage",
+            false,
+            format!("the coordinator state is unreadable: {}", error),
+        ),
+    }
+}
+
+/// Checks that at least `min_free_bytes` are free on the disk, given the bytes which are.
+pub fn check_disk_headroom(available: Option<u64>, min_free_bytes: u64) -> HealthCheck {
+    match available {
+        Some(available) => HealthCheck::new(
+            "disk headroom",
+            available >= min_free_bytes,
+            format!(
+                "{} free, at least {} must be",
+                format_gb(available),
+                format_gb(min_free_bytes)
+            ),
+        ),
+        None => HealthCheck::new("disk headroom", false, "the free disk space is unknown".to_string()),
+    }
+}
+
+fn format_gb(bytes: u64) -> String {
+    format!("{:.1} GB", bytes as f64 / (1u64 << 30) as f64)
+}
+
+/// Returns the number of bytes available to the coordinator on the disk of `dir`.
+#[cfg(unix)]
+pub(crate) fn available_disk_space(dir: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn available_disk_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    #[test]
+    fn test_check_locks() {
+        let now = datetime!(2021-06-01 12:00 UTC);
+        let lease = time::Duration::minutes(20);
+        let grace = time::Duration::minutes(5);
+        let contributor = Participant::new_contributor("aleo1contributor");
+        let locks = vec![
+            (contributor.clone(), 0, now - time::Duration::minutes(24)),
+            (contributor, 1, now - time::Duration::minutes(26)),
+        ];
+
+        assert!(check_locks(&locks[..1], now, lease, grace).healthy);
+        let check = check_locks(&locks, now, lease, grace);
+        assert!(!check.healthy);
+        assert!(check.detail.contains("chunk 1"));
+        assert!(!check.detail.contains("chunk 0"));
+    }
+
+    #[test]
+    fn test_health_report() {
+        let mut report = HealthReport {
+            checks: vec![
+                check_verification_backlog(3, 4),
+                check_storage(None),
+                check_disk_headroom(Some(20 << 30), 10 << 30),
+            ],
+        };
+        assert!(report.is_healthy());
+        assert!(report.to_string().ends_with("The ceremony is healthy\n"));
+
+        report.checks.push(check_disk_headroom(None, 10 << 30));
+        report.checks.push(check_verification_backlog(5, 4));
+        assert!(!report.is_healthy());
+        assert!(report.to_string().ends_with("2 of 5 checks failed\n"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Comparison of two consecutive rounds archived by the coordinator, which
+//! checks that the second round continues the first, and summarizes how the
+//! contributors and timing of the rounds differ.
+//!
+//! The comparison reads the round directories as they are laid out by the disk
+//! storage: the round state in `state.json`, and the contribution files and
+//! their signatures in `chunk_{chunk_id}/`. Contribution files which were moved
+//! to cold storage are not required, as the signatures hold their hashes, but
+//! they are checked against the signatures whenever they are present.
+
+use crate::{objects::Participant, ContributionFileSignature, CoordinatorError, Round};
+
+use serde::{Deserialize, Serialize};
+use setup_utils::calculate_hash;
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// A round read from the directory it was archived to.
+#[derive(Debug, Clone)]
+pub struct ArchivedRound {
+    directory: PathBuf,
+    round: Round,
+}
+
+impl ArchivedRound {
+    /// Reads the state of the round archived to the given directory.
+    pub fn load(directory: &Path) -> Result<Self, CoordinatorError> {
+        let state = directory.join("state.json");
+        if !state.exists() {
+            return Err(CoordinatorError::RoundStateMissing);
+        }
+        let round = serde_json::from_slice(&fs_err::read(state)?)?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            round,
+        })
+    }
+
+    /// Returns the state of the round.
+    pub fn round(&self) -> &Round {
+        &self.round
+    }
+
+    /// Returns the path of a contribution file of the round, with `suffix` appended to it.
+    fn contribution_path(&self, chunk_id: u64, contribution_id: u64, verified: bool, suffix: &str) -> PathBuf {
+        let state = if verified { "verified" } else { "unverified" };
+        self.directory
+            .join(format!("chunk_{}", chunk_id))
+            .join(format!("contribution_{}.{}{}", contribution_id, state, suffix))
+    }
+
+    /// Reads a contribution file signature of the round, if it was kept.
+    fn signature(
+        &self,
+        chunk_id: u64,
+        contribution_id: u64,
+        verified: bool,
+    ) -> Result<Option<ContributionFileSignature>, CoordinatorError> {
+        let path = self.contribution_path(chunk_id, contribution_id, verified, ".signature");
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs_err::read(path)?)?))
+    }
+
+    /// Reads a contribution file of the round, if it was not moved to cold storage.
+    fn contribution(
+        &self,
+        chunk_id: u64,
+        contribution_id: u64,
+        verified: bool,
+    ) -> Result<Option<Vec<u8>>, CoordinatorError> {
+        let path = self.contribution_path(chunk_id, contribution_id, verified, "");
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs_err::read(path)?))
+    }
+}
+
+/// The continuity of a chunk from the final contribution of a round to the initial challenge of the next.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkContinuity {
+    pub chunk_id: u64,
+    /// The hex-encoded hash of the final response of the chunk in the previous round.
+    pub final_response_hash: Option<String>,
+    /// The hex-encoded hash of the initial challenge of the chunk in the next round.
+    pub initial_challenge_hash: Option<String>,
+    /// The reasons the next round does not continue the chunk, if any.
+    pub problems: Vec<String>,
+}
+
+impl ChunkContinuity {
+    /// Returns `true` if the initial challenge of the chunk was computed from its final response.
+    pub fn is_continuous(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// The start, end and duration of a round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundTiming {
+    pub round_height: u64,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+impl RoundTiming {
+    fn new(round: &Round) -> Self {
+        Self {
+            round_height: round.round_height(),
+            started_at: round.started_at(),
+            finished_at: round.finished_at(),
+        }
+    }
+
+    /// Returns the number of seconds the round took, if it finished.
+    pub fn duration_secs(&self) -> Option<i64> {
+        Some((self.finished_at? - self.started_at?).whole_seconds())
+    }
+}
+
+/// The comparison of two consecutive archived rounds, see [compare_rounds].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundComparison {
+    pub previous: RoundTiming,
+    pub next: RoundTiming,
+    pub chunks: Vec<ChunkContinuity>,
+    /// The contributors authorized in both rounds.
+    pub returning_contributors: Vec<Participant>,
+    /// The contributors authorized only in the previous round.
+    pub departed_contributors: Vec<Participant>,
+    /// The contributors authorized only in the next round.
+    pub new_contributors: Vec<Participant>,
+}
+
+impl RoundComparison {
+    /// Returns `true` if the next round continues every chunk of the previous round.
+    pub fn is_continuous(&self) -> bool {
+        self.chunks.iter().all(ChunkContinuity::is_continuous)
+    }
+
+    /// Returns the number of seconds between the end of the previous round and the start of the next.
+    pub fn gap_secs(&self) -> Option<i64> {
+        Some((self.next.started_at? - self.previous.finished_at?).whole_seconds())
+    }
+}
+
+///
+/// Compares the round archived to `previous` with the round archived to `next`.
+///
+/// The next round continues the previous round if, for every chunk, the verifier
+/// of the final contribution of the previous round computed the initial challenge
+/// of the next round from the final response, and the first contribution of the
+/// next round was made to that challenge. The hashes in the signatures of the
+/// contributions are checked against each other, and against the contribution
+/// files whenever they are present.
+///
+/// Returns a `CoordinatorError` if the rounds are not consecutive, or if the
+/// previous round is not complete.
+///
+pub fn compare_rounds(previous: &ArchivedRound, next: &ArchivedRound) -> Result<RoundComparison, CoordinatorError> {
+    if next.round.round_height() != previous.round.round_height() + 1 {
+        return Err(CoordinatorError::RoundHeightMismatch);
+    }
+    if !previous.round.is_complete() {
+        return Err(CoordinatorError::RoundNotComplete);
+    }
+
+    let final_contribution_id = previous.round.expected_number_of_contributions() - 1;
+    let chunks = previous
+        .round
+        .chunks()
+        .iter()
+        .map(|chunk| chunk_continuity(previous, next, chunk.chunk_id(), final_contribution_id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let previous_contributors: HashSet<&Participant> = previous.round.contributors().iter().collect();
+    let next_contributors: HashSet<&Participant> = next.round.contributors().iter().collect();
+    let filter = |round: &Round, others: &HashSet<&Participant>, in_others: bool| -> Vec<Participant> {
+        round
+            .contributors()
+            .iter()
+            .filter(|contributor| others.contains(contributor) == in_others)
+            .cloned()
+            .collect()
+    };
+
+    Ok(RoundComparison {
+        previous: RoundTiming::new(&previous.round),
+        next: RoundTiming::new(&next.round),
+        chunks,
+        returning_contributors: filter(&previous.round, &next_contributors, true),
+        departed_contributors: filter(&previous.round, &next_contributors, false),
+        new_contributors: filter(&next.round, &previous_contributors, false),
+    })
+}
+
+fn chunk_continuity(
+    previous: &ArchivedRound,
+    next: &ArchivedRound,
+    chunk_id: u64,
+    final_contribution_id: u64,
+) -> Result<ChunkContinuity, CoordinatorError> {
+    let mut problems = vec![];
+
+    // The hash of the final response is signed by its contributor.
+    let final_response_hash = match previous.signature(chunk_id, final_contribution_id, false)? {
+        Some(signature) => Some(signature.get_response_hash().to_string()),
+        None => {
+            problems.push(format!(
+                "the signature of the final contribution {} is missing",
+                final_contribution_id
+            ));
+            None
+        }
+    };
+    if let (Some(expected), Some(response)) = (
+        &final_response_hash,
+        previous.contribution(chunk_id, final_contribution_id, false)?,
+    ) {
+        if hex::encode(calculate_hash(&response)) != *expected {
+            problems.push("the final response does not match its signature".to_string());
+        }
+    }
+
+    // The verifier of the final response signs the initial challenge of the next round,
+    // which is kept as the verified contribution 0 of the next round.
+    let initial_challenge_hash = match next.signature(chunk_id, 0, true)? {
+        Some(signature) => {
+            if Some(signature.get_response_hash()) != final_response_hash.as_deref() {
+                problems.push("the initial challenge was not computed from the final response".to_string());
+            }
+            if signature.get_next_challenge_hash().is_none() {
+                problems.push("the signature of the initial challenge does not hold its hash".to_string());
+            }
+            signature.get_next_challenge_hash().clone()
+        }
+        None => {
+            problems.push("the signature of the initial challenge is missing".to_string());
+            None
+        }
+    };
+    if let Some(challenge) = next.contribution(chunk_id, 0, true)? {
+        if let Some(expected) = &initial_challenge_hash {
+            if hex::encode(calculate_hash(&challenge)) != *expected {
+                problems.push("the initial challenge does not match its signature".to_string());
+            }
+        }
+        // The challenge starts with the hash of the response it was computed from.
+        if let Some(expected) = &final_response_hash {
+            if challenge.get(0..64).map(hex::encode).as_ref() != Some(expected) {
+                problems.push("the initial challenge does not start with the final response hash".to_string());
+            }
+        }
+    }
+
+    // The first contribution of the next round, if there is one, was made to the initial challenge.
+    if let (Some(expected), Some(signature)) = (&initial_challenge_hash, next.signature(chunk_id, 1, false)?) {
+        if signature.get_challenge_hash() != expected {
+            problems.push("the first contribution of the next round was not made to the initial challenge".to_string());
+        }
+    }
+
+    Ok(ChunkContinuity {
+        chunk_id,
+        final_response_hash,
+        initial_challenge_hash,
+        problems,
+    })
+}
+
+fn fmt_time(time: Option<OffsetDateTime>) -> String {
+    time.and_then(|time| time.format(&Rfc3339).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl fmt::Display for RoundComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let continuous = self.chunks.iter().filter(|chunk| chunk.is_continuous()).count();
+        writeln!(
+            f,
+            "Round {} continues round {}: {} ({} of {} chunks)",
+            self.next.round_height,
+            self.previous.round_height,
+            if self.is_continuous() { "yes" } else { "no" },
+            continuous,
+            self.chunks.len()
+        )?;
+        for chunk in self.chunks.iter().filter(|chunk| !chunk.is_continuous()) {
+            for problem in &chunk.problems {
+                writeln!(f, "  chunk {}: {}", chunk.chunk_id, problem)?;
+            }
+        }
+
+        writeln!(f, "Timing:")?;
+        for timing in &[&self.previous, &self.next] {
+            writeln!(
+                f,
+                "  round {}: started {}, finished {}, took {}",
+                timing.round_height,
+                fmt_time(timing.started_at),
+                fmt_time(timing.finished_at),
+                timing
+                    .duration_secs()
+                    .map_or_else(|| "-".to_string(), |secs| format!("{}s", secs))
+            )?;
+        }
+        if let Some(gap) = self.gap_secs() {
+            writeln!(
+                f,
+                "  round {} started {}s after round {} finished",
+                self.next.round_height, gap, self.previous.round_height
+            )?;
+        }
+
+        writeln!(f, "Contributors:")?;
+        for (label, contributors) in &[
+            ("in both rounds", &self.returning_contributors),
+            ("only in the previous round", &self.departed_contributors),
+            ("only in the next round", &self.new_contributors),
+        ] {
+            writeln!(f, "  {} ({}):", label, contributors.len())?;
+            for contributor in contributors.iter() {
+                writeln!(f, "    {}", contributor)?;
+            }
+        }
+        Ok(())
+    }
+}
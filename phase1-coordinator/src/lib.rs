@@ -28,9 +28,19 @@
 #[macro_use]
 mod macros;
 
+pub mod analytics;
+
+pub mod audit;
+
 pub mod authentication;
 
+pub mod bootstrap;
+
+pub mod cohort;
+
 pub(crate) mod commands;
+#[cfg(feature = "operator")]
+pub use commands::{Seed, SigningKey, SEED_LENGTH};
 
 pub mod coordinator;
 pub use coordinator::*;
@@ -47,9 +57,31 @@ pub use coordinator_state::CoordinatorState;
 
 pub mod environment;
 
+pub mod events;
+
+pub mod health;
+
+pub mod history;
+
 pub mod objects;
 pub use objects::{ContributionFileSignature, ContributionState, Participant, Round};
 
+pub mod outboard;
+
+pub mod policy;
+
+pub mod prioritization;
+
+pub mod quarantine;
+
+pub mod reservations;
+
+pub mod runbook;
+
+pub mod scheduling;
+
+pub mod spot_check;
+
 pub mod storage;
 
 #[cfg(any(test, feature = "testing"))]
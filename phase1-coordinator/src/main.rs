@@ -1,31 +1,564 @@
 use phase1_coordinator::{
-    authentication::{Dummy, Signature},
+    analytics::write_csv,
+    audit::{AdminOperation, AdminOutcome},
+    authentication::{AleoSignature, Dummy, Signature},
+    bootstrap::BootstrapManifest,
     environment::{Development, Environment, Parameters},
+    health::HealthThresholds,
+    history::{compare_rounds, ArchivedRound},
+    objects::Participant,
     Coordinator,
 };
+use setup1_shared::protocol::v1::{ContributionStatus, ContributionsQuery};
+use structopt::StructOpt;
 use tracing_subscriber;
 
-use std::{sync::Arc, time::Duration};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use time::OffsetDateTime;
 use tokio::{sync::RwLock, task, time::sleep};
 use tracing::*;
 
+#[derive(Debug, StructOpt)]
+#[structopt(name = "phase1-coordinator")]
+struct Options {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Bootstrap a new ceremony from pre-generated round 0 challenges,
+    /// and print the onboarding information for contributors.
+    Bootstrap {
+        /// Path to the manifest listing the challenge of each chunk.
+        #[structopt(long)]
+        manifest: PathBuf,
+
+        /// Directory the challenge paths in the manifest are relative
+        /// to. Defaults to the directory of the manifest.
+        #[structopt(long)]
+        challenges: Option<PathBuf>,
+
+        /// ID of a verifier to register with the coordinator.
+        #[structopt(long = "verifier", required = true, number_of_values = 1)]
+        verifiers: Vec<String>,
+
+        /// URL contributors reach the coordinator at.
+        #[structopt(long)]
+        api_url: Option<String>,
+    },
+    /// Inspect the protocol events of the ceremony.
+    Events(EventsCommand),
+    /// Analyze the ceremony, e.g. to plan the next one.
+    Analytics(AnalyticsCommand),
+    /// List the contributions of the ceremony as JSON lines, a page at a time.
+    Contributions {
+        /// Only list the contributions to the given round.
+        #[structopt(long)]
+        round: Option<u64>,
+
+        /// Only list the contributions to the given chunk.
+        #[structopt(long)]
+        chunk: Option<u64>,
+
+        /// Only list the contributions of the given contributor.
+        #[structopt(long)]
+        contributor: Option<String>,
+
+        /// Only list the contributions which are `contributed` or `verified`.
+        #[structopt(long, parse(try_from_str = parse_status))]
+        status: Option<ContributionStatus>,
+
+        /// Only list the contributions uploaded at or after the given
+        /// Unix timestamp, in seconds.
+        #[structopt(long)]
+        since: Option<i64>,
+
+        /// Only list the contributions uploaded before the given Unix
+        /// timestamp, in seconds.
+        #[structopt(long)]
+        until: Option<i64>,
+
+        /// The cursor printed after the previous page, to list the
+        /// contributions after it.
+        #[structopt(long)]
+        cursor: Option<String>,
+
+        /// The maximum number of contributions in the page.
+        #[structopt(long)]
+        limit: Option<usize>,
+
+        /// List every page instead of a single one.
+        #[structopt(long)]
+        all: bool,
+    },
+    /// Check that no contributor contributed more often in a round
+    /// of the transcript than the key usage policy permits.
+    VerifyTranscript,
+    /// Write the signed pointer to the latest round, which mirrors
+    /// serve and clients poll instead of the coordinator.
+    PublishIndex {
+        /// Path to the Aleo view key the coordinator signs with.
+        #[structopt(long)]
+        signing_key: PathBuf,
+
+        /// Base URL the artifacts of the round can be downloaded from.
+        #[structopt(long = "artifact-url", number_of_values = 1)]
+        artifact_urls: Vec<String>,
+
+        /// Path to write the signed index to.
+        #[structopt(long, default_value = "latest.json")]
+        output: PathBuf,
+    },
+    /// Check that an archived round continues the archived round before
+    /// it, and summarize the contributors and timing of both rounds.
+    CompareRounds {
+        /// Directory of the earlier round, e.g. `round_3`.
+        previous: PathBuf,
+
+        /// Directory of the round after it, e.g. `round_4`.
+        next: PathBuf,
+
+        /// Print the comparison as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Check the invariants of the running ceremony, and exit with an
+    /// error if any check fails, e.g. to alert from a cron job.
+    Health {
+        /// Seconds a chunk may stay locked beyond the lease of its
+        /// participant.
+        #[structopt(long, default_value = "300")]
+        lock_grace_seconds: i64,
+
+        /// The maximum number of contributions waiting to be verified.
+        #[structopt(long, default_value = "64")]
+        max_verification_backlog: usize,
+
+        /// The minimum free space on the disk of the transcript, in GB.
+        #[structopt(long, default_value = "10")]
+        min_free_gb: u64,
+
+        /// Print the report as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Apply an operation as an administrator, and record it in the
+    /// signed audit log. Retrying with the same operation ID does not
+    /// apply the operation again.
+    Admin {
+        /// Unique ID of the operation, which a retry must reuse.
+        #[structopt(long)]
+        operation_id: String,
+
+        /// Identity of the administrator, recorded in the audit log.
+        #[structopt(long)]
+        admin: String,
+
+        /// Path to the signing key of the coordinator.
+        #[structopt(long)]
+        signing_key: PathBuf,
+
+        #[structopt(subcommand)]
+        operation: AdminCommand,
+    },
+    /// Run an operator runbook, which chains the steps of an operation.
+    Ops(OpsCommand),
+    /// Print the signed audit log of administrator operations as JSON lines.
+    AuditLog,
+    /// Wrap the data keys of the contributions archived to cold storage
+    /// with the active storage encryption key, after a key rotation.
+    RewrapArchives,
+}
+
+#[derive(Debug, StructOpt)]
+enum AdminCommand {
+    /// Ban a contributor from the current and all future rounds.
+    Ban { contributor: String },
+    /// Allow a banned contributor to join the queue again.
+    Unban { contributor: String },
+    /// Drop a contributor from the current round.
+    Drop { contributor: String },
+    /// Reset the current round, with a rollback to the end of the
+    /// previous round.
+    ResetRound,
+    /// Enable the manual lock, which holds the coordinator from
+    /// transitioning to the next round.
+    Pause,
+    /// Disable the manual lock.
+    Resume,
+    /// Mark the verification of a contribution as urgent, so that it is
+    /// handed to verifiers before the other pending verifications.
+    Prioritize { chunk_id: u64, contribution_id: u64 },
+    /// Open the ceremony to the public before the rounds of the trusted
+    /// cohort are over.
+    OpenPublic,
+}
+
+impl AdminCommand {
+    fn operation(self) -> AdminOperation {
+        match self {
+            AdminCommand::Ban { contributor } => AdminOperation::BanParticipant {
+                participant: Participant::new_contributor(&contributor),
+            },
+            AdminCommand::Unban { contributor } => AdminOperation::UnbanParticipant {
+                participant: Participant::new_contributor(&contributor),
+            },
+            AdminCommand::Drop { contributor } => AdminOperation::DropParticipant {
+                participant: Participant::new_contributor(&contributor),
+            },
+            AdminCommand::ResetRound => AdminOperation::ResetRound,
+            AdminCommand::Pause => AdminOperation::EnableManualLock,
+            AdminCommand::Resume => AdminOperation::DisableManualLock,
+            AdminCommand::Prioritize {
+                chunk_id,
+                contribution_id,
+            } => AdminOperation::PrioritizeVerification {
+                chunk_id,
+                contribution_id,
+            },
+            AdminCommand::OpenPublic => AdminOperation::OpenPublicPhase,
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum OpsCommand {
+    /// Advance the ceremony to the next round: check that every chunk
+    /// is verified, aggregate the round, generate the challenges of the
+    /// next round, publish its index and notify the contributors. Steps
+    /// an earlier run already took are skipped, so it is safe to rerun.
+    AdvanceRound {
+        /// The round to advance from, which makes a rerun after the round
+        /// was advanced a no-op. Defaults to the current round.
+        #[structopt(long)]
+        round: Option<u64>,
+
+        /// Path to the signing key of the coordinator.
+        #[structopt(long)]
+        signing_key: PathBuf,
+
+        /// Base URL the artifacts of the round can be downloaded from.
+        #[structopt(long = "artifact-url", number_of_values = 1)]
+        artifact_urls: Vec<String>,
+
+        /// Path to write the signed index to.
+        #[structopt(long, default_value = "latest.json")]
+        output: PathBuf,
+
+        /// Print the report as JSON.
+        #[structopt(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum EventsCommand {
+    /// Print the events in the event log as JSON lines.
+    Export {
+        /// Only print the events which occurred at or after the given
+        /// Unix timestamp, in seconds.
+        #[structopt(long)]
+        since: Option<i64>,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+enum AnalyticsCommand {
+    /// Print the sizes of the files and the contribution, upload and
+    /// verification times of every contribution to a chunk as CSV.
+    ExportChunks {
+        /// Only print the contributions to the given round.
+        #[structopt(long)]
+        round: Option<u64>,
+    },
+}
+
+fn parse_status(src: &str) -> Result<ContributionStatus, serde_json::Error> {
+    serde_json::from_value(serde_json::Value::String(src.to_string()))
+}
+
 fn coordinator(environment: &Environment, signature: Arc<dyn Signature>) -> anyhow::Result<Coordinator> {
     Ok(Coordinator::new(environment.clone(), signature)?)
 }
 
-#[tokio::main]
-pub async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
-
-    // Set the environment.
-    let environment: Environment = Development::from(Parameters::TestCustom {
+fn environment() -> Development {
+    Development::from(Parameters::TestCustom {
         number_of_chunks: 8,
         power: 12,
         batch_size: 256,
     })
-    .into();
     // use phase1_coordinator::environment::Production;
-    // let environment: Environment = Production::from(Parameters::AleoInner).into();
+    // Production::from(Parameters::AleoInner)
+}
+
+fn bootstrap(
+    manifest: PathBuf,
+    challenges: Option<PathBuf>,
+    verifiers: Vec<String>,
+    api_url: Option<String>,
+) -> anyhow::Result<()> {
+    // Register the initial verifiers with the environment.
+    let verifiers: Vec<Participant> = verifiers.iter().map(|id| Participant::new_verifier(id)).collect();
+    let environment: Environment = environment().coordinator_verifiers(&verifiers).into();
+
+    let challenges = match challenges {
+        Some(challenges) => challenges,
+        None => manifest.parent().map(PathBuf::from).unwrap_or_default(),
+    };
+    let manifest = BootstrapManifest::load(&manifest)?;
+
+    let mut coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    let summary = coordinator.bootstrap(&manifest, &challenges)?;
+
+    println!("{}", summary);
+    println!("Contributors can join the ceremony with:");
+    println!(
+        "  setup1-contributor contribute --api-url {} --keys-path <keys.json>",
+        api_url.as_deref().unwrap_or("<coordinator api url>")
+    );
+
+    Ok(())
+}
+
+fn export_events(since: Option<i64>) -> anyhow::Result<()> {
+    let since = since.map(OffsetDateTime::from_unix_timestamp).transpose()?;
+
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    for event in coordinator.events(since)? {
+        println!("{}", serde_json::to_string(&event)?);
+    }
+
+    Ok(())
+}
+
+fn export_chunks(round: Option<u64>) -> anyhow::Result<()> {
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    write_csv(&coordinator.chunk_timings(round)?, std::io::stdout())?;
+
+    Ok(())
+}
+
+fn list_contributions(mut query: ContributionsQuery, all: bool) -> anyhow::Result<()> {
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    loop {
+        let page = coordinator.contributions(&query)?;
+        for contribution in &page.contributions {
+            println!("{}", serde_json::to_string(contribution)?);
+        }
+        match page.next_cursor {
+            Some(cursor) if all => query.cursor = Some(cursor),
+            // Print the cursor to stderr, so that the contributions can be piped from stdout.
+            Some(cursor) => {
+                eprintln!("More contributions match, list them with --cursor {}", cursor);
+                return Ok(());
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+fn verify_transcript() -> anyhow::Result<()> {
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    coordinator.verify_key_usage()?;
+
+    println!(
+        "The transcript up to round {} respects the key usage policy {:?}",
+        coordinator.current_round_height()?,
+        environment.key_usage_policy()
+    );
+
+    Ok(())
+}
+
+fn publish_index(signing_key: PathBuf, artifact_urls: Vec<String>, output: PathBuf) -> anyhow::Result<()> {
+    let signing_key = fs_err::read_to_string(signing_key)?.trim().to_string();
+
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(AleoSignature))?;
+    let latest = coordinator.latest_index(&signing_key, artifact_urls)?;
+    fs_err::write(&output, serde_json::to_vec_pretty(&latest)?)?;
+
+    println!(
+        "Published the index of round {} with {} challenges to {}",
+        latest.index.round_height,
+        latest.index.transcript_digest.challenges.len(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+fn compare(previous: PathBuf, next: PathBuf, json: bool) -> anyhow::Result<()> {
+    let comparison = compare_rounds(&ArchivedRound::load(&previous)?, &ArchivedRound::load(&next)?)?;
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(&comparison)?),
+        false => print!("{}", comparison),
+    }
+
+    if !comparison.is_continuous() {
+        anyhow::bail!("{} does not continue {}", next.display(), previous.display());
+    }
+    Ok(())
+}
+
+fn health(thresholds: HealthThresholds, json: bool) -> anyhow::Result<()> {
+    let environment: Environment = environment().into();
+    let coordinator = Coordinator::open_read_only(environment, Arc::new(Dummy))?;
+    let report = coordinator.health(&thresholds);
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(&report)?),
+        false => print!("{}", report),
+    }
+
+    if !report.is_healthy() {
+        anyhow::bail!("the ceremony is unhealthy");
+    }
+    Ok(())
+}
+
+fn admin(signing_key: PathBuf, admin: String, operation_id: String, operation: AdminOperation) -> anyhow::Result<()> {
+    let signing_key = fs_err::read_to_string(signing_key)?.trim().to_string();
+
+    let environment: Environment = environment().into();
+    let mut coordinator = coordinator(&environment, Arc::new(AleoSignature))?;
+    match coordinator.administer(&signing_key, &admin, &operation_id, operation)? {
+        AdminOutcome::Applied(_) => println!("Applied operation {} and recorded it in the audit log", operation_id),
+        AdminOutcome::AlreadyApplied(signed) => println!(
+            "Operation {} was already recorded at {}, so nothing changed",
+            operation_id, signed.record.timestamp
+        ),
+    }
+
+    Ok(())
+}
+
+fn advance_round(
+    round: Option<u64>,
+    signing_key: PathBuf,
+    artifact_urls: Vec<String>,
+    output: PathBuf,
+    json: bool,
+) -> anyhow::Result<()> {
+    let signing_key = fs_err::read_to_string(signing_key)?.trim().to_string();
+
+    let environment: Environment = environment().into();
+    let mut coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    let report = coordinator.advance_round(round, &signing_key, artifact_urls, &output)?;
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(&report)?),
+        false => print!("{}", report),
+    }
+
+    Ok(())
+}
+
+fn export_audit_log() -> anyhow::Result<()> {
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    for record in coordinator.audit_log()? {
+        println!("{}", serde_json::to_string(&record)?);
+    }
+
+    Ok(())
+}
+
+fn rewrap_archives() -> anyhow::Result<()> {
+    let environment: Environment = environment().into();
+    let coordinator = coordinator(&environment, Arc::new(Dummy))?;
+    match environment.storage_encryption() {
+        Some(encryption) => println!(
+            "Rewrapped {} archived contribution files with key {}",
+            coordinator.rewrap_archives()?,
+            encryption.active_key()
+        ),
+        None => println!("Storage encryption is not configured, so there is nothing to rewrap"),
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+pub async fn main() -> anyhow::Result<()> {
+    // Log to stderr, so that exported events can be piped from stdout.
+    tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+
+    match Options::from_args().command {
+        Some(Command::Bootstrap {
+            manifest,
+            challenges,
+            verifiers,
+            api_url,
+        }) => return bootstrap(manifest, challenges, verifiers, api_url),
+        Some(Command::Events(EventsCommand::Export { since })) => return export_events(since),
+        Some(Command::Analytics(AnalyticsCommand::ExportChunks { round })) => return export_chunks(round),
+        Some(Command::Contributions {
+            round,
+            chunk,
+            contributor,
+            status,
+            since,
+            until,
+            cursor,
+            limit,
+            all,
+        }) => {
+            let query = ContributionsQuery {
+                round_height: round,
+                chunk_id: chunk,
+                contributor,
+                status,
+                since,
+                until,
+                cursor,
+                limit,
+            };
+            return list_contributions(query, all);
+        }
+        Some(Command::VerifyTranscript) => return verify_transcript(),
+        Some(Command::PublishIndex {
+            signing_key,
+            artifact_urls,
+            output,
+        }) => return publish_index(signing_key, artifact_urls, output),
+        Some(Command::CompareRounds { previous, next, json }) => return compare(previous, next, json),
+        Some(Command::Health {
+            lock_grace_seconds,
+            max_verification_backlog,
+            min_free_gb,
+            json,
+        }) => {
+            let thresholds = HealthThresholds {
+                lock_grace: time::Duration::seconds(lock_grace_seconds),
+                max_verification_backlog,
+                min_free_bytes: min_free_gb << 30,
+            };
+            return health(thresholds, json);
+        }
+        Some(Command::Admin {
+            operation_id,
+            admin: admin_id,
+            signing_key,
+            operation,
+        }) => return admin(signing_key, admin_id, operation_id, operation.operation()),
+        Some(Command::Ops(OpsCommand::AdvanceRound {
+            round,
+            signing_key,
+            artifact_urls,
+            output,
+            json,
+        })) => return advance_round(round, signing_key, artifact_urls, output, json),
+        Some(Command::AuditLog) => return export_audit_log(),
+        Some(Command::RewrapArchives) => return rewrap_archives(),
+        None => {}
+    }
+
+    // Set the environment.
+    let environment: Environment = environment().into();
 
     // Instantiate the coordinator.
     let coordinator: Arc<RwLock<Coordinator>> = Arc::new(RwLock::new(coordinator(&environment, Arc::new(Dummy))?));
@@ -49,6 +582,45 @@ pub async fn main() -> anyhow::Result<()> {
         }
     });
 
+    // Fully verify the contributions accepted by a sampled verification outside of the update loop,
+    // holding the write lock only to record the outcome.
+    let verifier_coordinator = coordinator.clone();
+    task::spawn(async move {
+        loop {
+            let next = verifier_coordinator
+                .read()
+                .await
+                .prioritized_deferred_verifications()
+                .into_iter()
+                .next();
+            let (task, contributor) = match next {
+                Some(next) => next,
+                None => {
+                    sleep(Duration::from_secs(10)).await;
+                    continue;
+                }
+            };
+
+            let result = verifier_coordinator
+                .read()
+                .await
+                .run_deferred_verification(&task, &contributor);
+            let retry = result.is_err();
+            let recorded = verifier_coordinator
+                .write()
+                .await
+                .finish_deferred_verification(&task, &contributor, result);
+            if let Err(error) = recorded {
+                error!("{}", error);
+            }
+
+            // Back off before a task which could not be verified is retried.
+            if retry {
+                sleep(Duration::from_secs(10)).await;
+            }
+        }
+    });
+
     // Initialize the shutdown procedure.
     let shutdown_handler = {
         let shutdown_coordinator = coordinator.clone();
@@ -1,5 +1,5 @@
-#[cfg(any(test, feature = "operator"))]
-use crate::coordinator::CoordinatorError;
+use crate::{authentication::Signature, coordinator::CoordinatorError};
+use setup_utils::to_canonical_json;
 
 use serde::{Deserialize, Serialize};
 use serde_diff::SerdeDiff;
@@ -56,11 +56,14 @@ impl ContributionState {
             next_challenge_hash: next_challenge_hash.map(|h| hex::encode(h)),
         })
     }
+}
 
-    /// Returns the message that should be signed for the `ContributionFileSignature`.
+impl ContributionState {
+    /// Returns the message that should be signed for the `ContributionFileSignature`,
+    /// which is the canonical JSON encoding of the contribution state.
     #[inline]
     pub fn signature_message(&self) -> Result<String, CoordinatorError> {
-        Ok(serde_json::to_string(&self)?)
+        Ok(to_canonical_json(&self)?)
     }
 }
 
@@ -89,6 +92,30 @@ impl ContributionFileSignature {
         Ok(Self { signature, state })
     }
 
+    /// Signs the signature message of the given contribution state with the given
+    /// signature scheme and signing key, and returns the contribution file signature.
+    #[cfg(any(test, feature = "operator"))]
+    #[inline]
+    pub fn sign(
+        signature: &dyn Signature,
+        signing_key: &str,
+        state: ContributionState,
+    ) -> Result<Self, CoordinatorError> {
+        let signature = signature.sign(signing_key, &state.signature_message()?)?;
+        Self::new(signature, state)
+    }
+
+    ///
+    /// Returns `true` if the signature is valid for the given public key.
+    ///
+    /// The message is re-encoded from the contribution state, so the signature
+    /// does not depend on how the state was formatted when it was stored.
+    ///
+    #[inline]
+    pub fn verify(&self, signature: &dyn Signature, public_key: &str) -> Result<bool, CoordinatorError> {
+        Ok(signature.verify(public_key, &self.state.signature_message()?, &self.signature))
+    }
+
     /// Returns a reference to the signature.
     #[inline]
     pub fn get_signature(&self) -> &str {
@@ -125,6 +152,7 @@ impl ContributionFileSignature {
 mod tests {
     use super::*;
 
+    use crate::authentication::Dummy;
     use setup_utils::calculate_hash;
 
     #[test]
@@ -157,6 +185,31 @@ mod tests {
         assert!(contribution_signature.is_ok())
     }
 
+    #[test]
+    pub fn test_contribution_signature_is_verified_canonically() {
+        // Construct the contribution state.
+        let contribution_state = ContributionState::new(
+            calculate_hash(&[1u8; 128]).to_vec(),
+            calculate_hash(&[2u8; 128]).to_vec(),
+            None,
+        )
+        .unwrap();
+
+        // The message has no whitespace and its members are sorted.
+        let message = contribution_state.signature_message().unwrap();
+        assert!(message.starts_with("{\"challengeHash\":"));
+        assert!(message.contains("\",\"responseHash\":"));
+
+        // Sign the contribution state with the dummy signature scheme.
+        let signature = Dummy.sign("secret", &message).unwrap();
+        let contribution_signature = ContributionFileSignature::new(signature, contribution_state).unwrap();
+
+        // The signature is still valid once the contribution file signature is reformatted.
+        let reformatted = serde_json::to_vec_pretty(&contribution_signature).unwrap();
+        let contribution_signature: ContributionFileSignature = serde_json::from_slice(&reformatted).unwrap();
+        assert!(contribution_signature.verify(&Dummy, "public").unwrap());
+    }
+
     #[test]
     pub fn test_contribution_signature_invalid_signature_size() {
         // Construct the dummy challenge, response, and next_challenge files.
@@ -1,5 +1,5 @@
 use crate::{
-    environment::Environment,
+    environment::{Environment, KeyUsagePolicy},
     objects::{participant::*, Chunk},
     storage::{
         ContributionLocator,
@@ -19,7 +19,10 @@ use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_aux::prelude::*;
 use serde_diff::SerdeDiff;
-use std::{collections::HashSet, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
 use time::OffsetDateTime;
 use tracing::{debug, error, trace, warn};
 
@@ -181,6 +184,18 @@ impl Round {
         self.height
     }
 
+    /// Returns the time this round started, if it has.
+    #[inline]
+    pub fn started_at(&self) -> Option<OffsetDateTime> {
+        self.started_at
+    }
+
+    /// Returns the time this round finished, if it has.
+    #[inline]
+    pub fn finished_at(&self) -> Option<OffsetDateTime> {
+        self.finished_at
+    }
+
     /// Returns the number of contributors authorized for this round.
     #[inline]
     pub fn number_of_contributors(&self) -> u64 {
@@ -222,6 +237,73 @@ impl Round {
         }
     }
 
+    ///
+    /// Returns `Ok(())` if the given contributor may contribute to the given chunk
+    /// under the given key usage policy, based on its contributions in this round.
+    ///
+    /// If the contributor already used its key as often as the policy permits,
+    /// returns a `CoordinatorError`.
+    ///
+    #[inline]
+    pub fn check_key_usage(
+        &self,
+        participant: &Participant,
+        chunk_id: u64,
+        policy: KeyUsagePolicy,
+    ) -> Result<(), CoordinatorError> {
+        for chunk in &self.chunks {
+            // Check whether the contribution would be counted against a contribution in this chunk.
+            if policy == KeyUsagePolicy::OncePerChunk && chunk.chunk_id() != chunk_id {
+                continue;
+            }
+
+            let contributed = chunk
+                .get_contributions()
+                .values()
+                .any(|contribution| contribution.get_contributor().as_ref() == Some(participant));
+            if contributed {
+                return Err(CoordinatorError::KeyUsagePolicyViolated {
+                    participant: participant.clone(),
+                    chunk_id,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns `Ok(())` if every contribution in this round respects the given
+    /// key usage policy, which is used to check the transcript of a round.
+    ///
+    /// Otherwise, returns a `CoordinatorError` for the first contribution which
+    /// exceeds the usage the policy permits.
+    ///
+    pub fn verify_key_usage(&self, policy: KeyUsagePolicy) -> Result<(), CoordinatorError> {
+        let mut contributions: HashMap<&Participant, HashSet<u64>> = HashMap::new();
+        for chunk in &self.chunks {
+            for contribution in chunk.get_contributions().values() {
+                let participant = match contribution.get_contributor() {
+                    Some(participant) => participant,
+                    None => continue,
+                };
+
+                let chunk_ids = contributions.entry(participant).or_default();
+                let permitted = match policy {
+                    KeyUsagePolicy::OncePerChunk => !chunk_ids.contains(&chunk.chunk_id()),
+                    KeyUsagePolicy::OncePerRound => chunk_ids.is_empty(),
+                };
+                if !permitted {
+                    return Err(CoordinatorError::KeyUsagePolicyViolated {
+                        participant: participant.clone(),
+                        chunk_id: chunk.chunk_id(),
+                    });
+                }
+                chunk_ids.insert(chunk.chunk_id());
+            }
+        }
+        Ok(())
+    }
+
     ///
     /// Returns a reference to the chunk, if it exists.
     /// Otherwise returns `None`.
@@ -0,0 +1,96 @@
+//! The bao outboard trees of the challenges, which the coordinator serves at
+//! `/v1/download/challenge/{chunk_id}/{contribution_id}/outboard` since protocol
+//! version 1.15, along with the BLAKE3 root hash it advertises in lock responses.
+//! Contributors verify a challenge block by block against them while downloading it.
+//!
+//! The outboard of a challenge is encoded once, streaming the challenge, and kept
+//! under the BLAKE2b hash of the challenge, so it can not go stale when a round is
+//! reset and its challenges are computed again.
+
+use crate::CoordinatorError;
+
+use bao::encode::Encoder;
+use fs_err::{self as fs, OpenOptions};
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// The outboard tree of a challenge, and the root hash it was encoded for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeOutboard {
+    /// The hex encoded BLAKE3 root hash of the challenge.
+    pub root_hash: String,
+    /// The path of the bao outboard tree of the challenge.
+    pub path: PathBuf,
+}
+
+///
+/// Returns the outboard of the challenge with the given hex encoded BLAKE2b hash
+/// from the given directory, and encodes it from the given challenge if it was not
+/// encoded yet.
+///
+pub fn challenge_outboard(
+    directory: &Path,
+    challenge_hash: &str,
+    challenge: impl Read,
+) -> Result<ChallengeOutboard, CoordinatorError> {
+    let path = directory.join(format!("{}.outboard", challenge_hash));
+    let root_hash_path = directory.join(format!("{}.root", challenge_hash));
+    // The root hash is written last, so an outboard is complete once it is there.
+    if root_hash_path.exists() {
+        return Ok(ChallengeOutboard {
+            root_hash: fs::read_to_string(&root_hash_path)?,
+            path,
+        });
+    }
+
+    fs::create_dir_all(directory)?;
+    let partial = directory.join(format!("{}.outboard.partial", challenge_hash));
+    let root_hash = encode_outboard(challenge, &partial)?;
+    fs::rename(&partial, &path)?;
+    let partial = directory.join(format!("{}.root.partial", challenge_hash));
+    fs::write(&partial, &root_hash)?;
+    fs::rename(&partial, &root_hash_path)?;
+
+    Ok(ChallengeOutboard { root_hash, path })
+}
+
+/// Encodes the bao outboard tree of the given input to the given path, and returns its hex encoded root hash.
+fn encode_outboard(mut input: impl Read, path: &Path) -> Result<String, CoordinatorError> {
+    // The encoder reads the tree back to reorder it once it is finalized.
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let mut encoder = Encoder::new_outboard(file);
+    io::copy(&mut input, &mut encoder)?;
+    let root_hash = encoder.finalize()?;
+    encoder.into_inner().sync_all()?;
+    Ok(root_hash.to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_outboard() {
+        let directory = Path::new("./transcript/testing_outboards");
+        let _ = fs::remove_dir_all(directory);
+        let challenge: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let (expected_outboard, expected_root_hash) = bao::encode::outboard(&challenge);
+
+        let outboard = challenge_outboard(directory, "aa", &challenge[..]).unwrap();
+        assert_eq!(expected_root_hash.to_hex().to_string(), outboard.root_hash);
+        assert_eq!(expected_outboard, fs::read(&outboard.path).unwrap());
+
+        // The outboard is encoded once per challenge hash.
+        assert_eq!(outboard, challenge_outboard(directory, "aa", &[][..]).unwrap());
+        assert_ne!(outboard, challenge_outboard(directory, "bb", &[][..]).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+//! The policies organizers configure for the finalization of a round,
+//! so that a round is not finalized before its contributors are
+//! diverse enough that they are unlikely to all collude.
+
+use crate::{objects::Participant, CoordinatorError};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use time::OffsetDateTime;
+
+/// Where a contributor is located on the network, either reported by
+/// the contributor itself or derived from its IP address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributorOrigin {
+    /// The number of the autonomous system the contributor connects from.
+    pub asn: Option<u32>,
+    /// The ISO 3166-1 alpha-2 code of the country the contributor is in.
+    pub country: Option<String>,
+}
+
+/// A measure of the diversity of the contributors of a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiversityMeasure {
+    /// The number of distinct contributor identities.
+    Identities,
+    /// The number of distinct autonomous systems the contributors connect from.
+    Asns,
+    /// The number of distinct countries the contributors are in.
+    Countries,
+}
+
+/// The minimum diversity of the contributors who finished a round,
+/// which must be reached before the round may be finalized.
+///
+/// Contributors without a known origin only count towards the
+/// number of distinct identities.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiversityPolicy {
+    /// The minimum number of distinct contributor identities.
+    pub minimum_identities: usize,
+    /// The minimum number of distinct autonomous systems.
+    pub minimum_asns: usize,
+    /// The minimum number of distinct countries.
+    pub minimum_countries: usize,
+    /// The minimum number of days which must have elapsed since the round started.
+    pub minimum_days: u32,
+}
+
+impl DiversityPolicy {
+    ///
+    /// Checks that the given contributors, who finished a round which started at
+    /// the given time, are diverse enough for the round to be finalized now.
+    ///
+    /// Returns a `CoordinatorError` for the first requirement which is not met.
+    ///
+    pub fn check<'a>(
+        &self,
+        contributors: impl IntoIterator<Item = (&'a Participant, Option<&'a ContributorOrigin>)>,
+        started_at: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<(), CoordinatorError> {
+        // Check that the minimum number of days has elapsed.
+        let not_before = started_at + time::Duration::days(self.minimum_days as i64);
+        if now < not_before {
+            return Err(CoordinatorError::RoundFinalizationTooEarly { not_before });
+        }
+
+        let mut identities = HashSet::new();
+        let mut asns = HashSet::new();
+        let mut countries = HashSet::new();
+        for (participant, origin) in contributors {
+            identities.insert(participant);
+            if let Some(origin) = origin {
+                asns.extend(origin.asn);
+                countries.extend(origin.country.as_ref().map(|country| country.to_uppercase()));
+            }
+        }
+
+        let requirements = [
+            (DiversityMeasure::Identities, identities.len(), self.minimum_identities),
+            (DiversityMeasure::Asns, asns.len(), self.minimum_asns),
+            (DiversityMeasure::Countries, countries.len(), self.minimum_countries),
+        ];
+        for (measure, count, minimum) in requirements.iter().copied() {
+            if count < minimum {
+                return Err(CoordinatorError::RoundFinalizationDiversityInsufficient {
+                    measure,
+                    count,
+                    minimum,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    fn origin(asn: u32, country: &str) -> ContributorOrigin {
+        ContributorOrigin {
+            asn: Some(asn),
+            country: Some(country.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_diversity_policy() {
+        let policy = DiversityPolicy {
+            minimum_identities: 3,
+            minimum_asns: 2,
+            minimum_countries: 2,
+            minimum_days: 2,
+        };
+        let started_at = datetime!(2021-11-01 12:00 UTC);
+        let now = started_at + time::Duration::days(2);
+
+        let contributors = [
+            Participant::new_contributor("1"),
+            Participant::new_contributor("2"),
+            Participant::new_contributor("3"),
+        ];
+        let origins = [origin(64500, "de"), origin(64500, "DE"), origin(64501, "fr")];
+        let finished = || contributors.iter().zip(origins.iter().map(Some));
+        assert!(policy.check(finished(), started_at, now).is_ok());
+
+        // The round must have run for the minimum number of days.
+        assert!(matches!(
+            policy.check(finished(), started_at, now - time::Duration::seconds(1)),
+            Err(CoordinatorError::RoundFinalizationTooEarly { .. })
+        ));
+
+        // Contributors without an origin only count as distinct identities.
+        let unknown = [Some(&origins[0]), Some(&origins[1]), None];
+        assert!(matches!(
+            policy.check(contributors.iter().zip(unknown.iter().copied()), started_at, now),
+            Err(CoordinatorError::RoundFinalizationDiversityInsufficient {
+                measure: DiversityMeasure::Asns,
+                count: 1,
+                minimum: 2,
+            })
+        ));
+
+        assert!(matches!(
+            policy.check(finished().take(2), started_at, now),
+            Err(CoordinatorError::RoundFinalizationDiversityInsufficient {
+                measure: DiversityMeasure::Identities,
+                count: 2,
+                minimum: 3,
+            })
+        ));
+    }
+}
@@ -0,0 +1,114 @@
+//! The policies which decide how urgently the coordinator needs each pending
+//! verification, so that verifiers work on the verifications holding up the
+//! end of a round before the others.
+
+use crate::objects::Task;
+
+use serde::{Deserialize, Serialize};
+use setup1_shared::protocol::v1::VerificationPriority;
+use std::cmp::Reverse;
+
+/// Decides how urgently a pending verification is needed.
+///
+/// The verifications the coordinator marked urgent are urgent whatever the
+/// policy decides, so a policy only adds to them.
+pub trait PrioritizationPolicy {
+    ///
+    /// Returns the priority of the pending verification of the given task,
+    /// where `outstanding_tasks` is the number of tasks of the current round
+    /// which are not verified yet, including this one.
+    ///
+    fn priority(&self, task: &Task, outstanding_tasks: u64) -> VerificationPriority;
+}
+
+/// Only the verifications the coordinator marked urgent are urgent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manual;
+
+impl PrioritizationPolicy for Manual {
+    fn priority(&self, _task: &Task, _outstanding_tasks: u64) -> VerificationPriority {
+        VerificationPriority::Normal
+    }
+}
+
+/// Every verification is urgent once at most `tail_tasks` tasks of the round
+/// are outstanding, since each of them then delays the end of the round.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundTail {
+    pub tail_tasks: u64,
+}
+
+impl PrioritizationPolicy for RoundTail {
+    fn priority(&self, _task: &Task, outstanding_tasks: u64) -> VerificationPriority {
+        if outstanding_tasks <= self.tail_tasks {
+            VerificationPriority::Urgent
+        } else {
+            VerificationPriority::Normal
+        }
+    }
+}
+
+/// The prioritization policy the coordinator hands out verifications with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrioritizationPolicyKind {
+    /// See [Manual].
+    Manual,
+    /// See [RoundTail].
+    RoundTail { tail_tasks: u64 },
+}
+
+impl PrioritizationPolicyKind {
+    /// Returns the prioritization policy of this kind.
+    pub fn policy(&self) -> Box<dyn PrioritizationPolicy> {
+        match *self {
+            PrioritizationPolicyKind::Manual => Box::new(Manual),
+            PrioritizationPolicyKind::RoundTail { tail_tasks } => Box::new(RoundTail { tail_tasks }),
+        }
+    }
+}
+
+impl Default for PrioritizationPolicyKind {
+    fn default() -> Self {
+        PrioritizationPolicyKind::Manual
+    }
+}
+
+///
+/// Orders the given pending verifications in the order they are handed to
+/// verifiers in: the urgent ones first, then the ones of the chunks with the
+/// fewest contributions, which are furthest behind, and then by chunk ID.
+///
+pub fn order(pending: &mut [(Task, VerificationPriority)]) {
+    pending.sort_by_key(|(task, priority)| (Reverse(*priority), task.contribution_id(), task.chunk_id()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_tail() {
+        let policy = RoundTail { tail_tasks: 2 };
+        let task = Task::new(3, 1);
+        assert_eq!(VerificationPriority::Normal, policy.priority(&task, 10));
+        assert_eq!(VerificationPriority::Normal, policy.priority(&task, 3));
+        assert_eq!(VerificationPriority::Urgent, policy.priority(&task, 2));
+        assert_eq!(VerificationPriority::Urgent, policy.priority(&task, 1));
+
+        assert_eq!(VerificationPriority::Normal, Manual.priority(&task, 1));
+    }
+
+    #[test]
+    fn test_order() {
+        let mut pending = vec![
+            (Task::new(0, 2), VerificationPriority::Normal),
+            (Task::new(1, 1), VerificationPriority::Normal),
+            (Task::new(2, 2), VerificationPriority::Urgent),
+            (Task::new(0, 1), VerificationPriority::Normal),
+        ];
+        order(&mut pending);
+
+        let tasks: Vec<_> = pending.iter().map(|(task, _)| task.to_tuple()).collect();
+        assert_eq!(vec![(2, 2), (0, 1), (1, 1), (0, 2)], tasks);
+    }
+}
@@ -0,0 +1,192 @@
+//! The quarantine of response files which were rejected by verification,
+//! which keeps each response along with a forensic bundle of what is known
+//! about it, instead of deleting or overwriting it, so that incidents can
+//! be analyzed after the fact.
+
+use crate::{environment::Environment, objects::Participant, storage::Object};
+use phase1::helpers::{find_invalid_elements, CurveKind};
+use setup_utils::{calculate_hash, CheckForCorrectness};
+
+use serde::{Deserialize, Serialize};
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
+use time::OffsetDateTime;
+
+/// The maximum number of offending elements a forensic bundle lists.
+pub const MAXIMUM_OFFENDING_ELEMENTS: usize = 100;
+
+/// The size of the header of a response file, which holds the hash of the challenge it was computed on.
+const RESPONSE_HEADER_SIZE: usize = 64;
+
+/// A response file which was rejected by verification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejection {
+    pub round_height: u64,
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    pub contributor: Option<Participant>,
+    /// The verifier which rejected the response, or `None` if the coordinator rejected it.
+    pub verifier: Option<Participant>,
+    /// Why the response was rejected.
+    pub reason: String,
+    /// The log lines of the verification of the response.
+    pub logs: Vec<String>,
+}
+
+/// An element of a response file which fails to deserialize.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffendingElement {
+    /// The section of the accumulator the element is in, e.g. `tau_g1`.
+    pub section: String,
+    /// The index of the element in its section.
+    pub index: usize,
+}
+
+/// What is known about a rejected response file, captured when it is quarantined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForensicBundle {
+    #[serde(flatten)]
+    pub rejection: Rejection,
+    pub quarantined_at: OffsetDateTime,
+    pub response_size: u64,
+    /// The size of a valid response file for the chunk.
+    pub expected_response_size: u64,
+    /// The hex encoded hash of the response file.
+    pub response_hash: String,
+    /// The hex encoded hash of the challenge file the response should have
+    /// been computed on, if it is in storage.
+    pub challenge_hash: Option<String>,
+    /// The hex encoded hash in the header of the response file, which is the
+    /// hash of the challenge file it was computed on.
+    pub header_challenge_hash: Option<String>,
+    /// The elements of the response file which fail to deserialize, up to
+    /// [MAXIMUM_OFFENDING_ELEMENTS] of them.
+    pub offending_elements: Vec<OffendingElement>,
+}
+
+impl ForensicBundle {
+    ///
+    /// Captures the forensic bundle of a rejected response file, given the
+    /// challenge file it should have been computed on, if it is in storage.
+    ///
+    /// The elements of the response are only checked for offending elements
+    /// if the response is the size of a valid response for the chunk.
+    ///
+    pub fn capture(
+        environment: &Environment,
+        rejection: Rejection,
+        challenge: Option<&[u8]>,
+        response: &[u8],
+        quarantined_at: OffsetDateTime,
+    ) -> Self {
+        let chunk_id = rejection.chunk_id;
+        let expected_response_size = Object::contribution_file_size(environment, chunk_id, false);
+
+        let offending_elements = match response.len() as u64 == expected_response_size {
+            true => {
+                let settings = environment.parameters();
+                let compressed = environment.compressed_outputs();
+                let invalid_elements = match settings.curve() {
+                    CurveKind::Bls12_377 => find_invalid_elements(
+                        response,
+                        compressed,
+                        CheckForCorrectness::Full,
+                        &phase1_chunked_parameters!(Bls12_377, settings, chunk_id),
+                        MAXIMUM_OFFENDING_ELEMENTS,
+                    ),
+                    CurveKind::BW6 => find_invalid_elements(
+                        response,
+                        compressed,
+                        CheckForCorrectness::Full,
+                        &phase1_chunked_parameters!(BW6_761, settings, chunk_id),
+                        MAXIMUM_OFFENDING_ELEMENTS,
+                    ),
+                };
+                invalid_elements
+                    .into_iter()
+                    .map(|element| OffendingElement {
+                        section: element.section.to_string(),
+                        index: element.index,
+                    })
+                    .collect()
+            }
+            false => vec![],
+        };
+
+        Self {
+            rejection,
+            quarantined_at,
+            response_size: response.len() as u64,
+            expected_response_size,
+            response_hash: hex::encode(calculate_hash(response)),
+            challenge_hash: challenge.map(|challenge| hex::encode(calculate_hash(challenge))),
+            header_challenge_hash: response.get(..RESPONSE_HEADER_SIZE).map(hex::encode),
+            offending_elements,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::prelude::*;
+
+    use time::macros::datetime;
+
+    fn rejection() -> Rejection {
+        Rejection {
+            round_height: 1,
+            chunk_id: 0,
+            contribution_id: 1,
+            contributor: Some(TEST_CONTRIBUTOR_ID.clone()),
+            verifier: None,
+            reason: "the response failed verification".to_string(),
+            logs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_capture_garbage_response() {
+        let environment = &*TEST_ENVIRONMENT_3;
+        let expected_response_size = Object::contribution_file_size(environment, 0, false);
+        let challenge = vec![1u8; 128];
+        let mut response = vec![0xffu8; expected_response_size as usize];
+        response[..RESPONSE_HEADER_SIZE].copy_from_slice(&calculate_hash(&challenge));
+
+        let bundle = ForensicBundle::capture(
+            environment,
+            rejection(),
+            Some(&challenge),
+            &response,
+            datetime!(2021-11-01 12:00 UTC),
+        );
+        assert_eq!(expected_response_size, bundle.response_size);
+        assert_eq!(bundle.challenge_hash, bundle.header_challenge_hash);
+        assert_eq!(MAXIMUM_OFFENDING_ELEMENTS, bundle.offending_elements.len());
+        assert_eq!(
+            OffendingElement {
+                section: "tau_g1".to_string(),
+                index: 0
+            },
+            bundle.offending_elements[0]
+        );
+
+        let json = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(1, json["contribution_id"]);
+        assert_eq!(bundle, serde_json::from_value(json).unwrap());
+    }
+
+    #[test]
+    fn test_capture_truncated_response() {
+        let bundle = ForensicBundle::capture(
+            &TEST_ENVIRONMENT_3,
+            rejection(),
+            None,
+            &[0u8; 16],
+            datetime!(2021-11-01 12:00 UTC),
+        );
+        assert_eq!(16, bundle.response_size);
+        assert_eq!(None, bundle.challenge_hash);
+        assert_eq!(None, bundle.header_challenge_hash);
+        assert!(bundle.offending_elements.is_empty());
+    }
+}
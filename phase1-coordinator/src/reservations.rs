@@ -0,0 +1,178 @@
+//! The time windows contributors reserve ahead of time to contribute in,
+//! for organizations which have to schedule their contribution through
+//! a change control process before they can take part.
+
+use crate::{objects::Participant, CoordinatorError};
+use setup1_shared::protocol::v1::SlotReservation;
+
+use serde::{Deserialize, Serialize};
+use time::{macros::format_description, Duration, OffsetDateTime, UtcOffset};
+
+/// The shortest time window a contributor may reserve.
+pub const MINIMUM_RESERVATION: Duration = Duration::hours(1);
+
+/// The longest time window a contributor may reserve.
+pub const MAXIMUM_RESERVATION: Duration = Duration::hours(24);
+
+/// How far ahead of time a contributor may reserve a time window.
+pub const MAXIMUM_RESERVATION_LEAD: Duration = Duration::days(90);
+
+/// A time window reserved by a contributor, in which the coordinator
+/// schedules it for a round ahead of the contributors in the queue
+/// without a reservation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub id: u64,
+    pub participant: Participant,
+    pub starts_at: OffsetDateTime,
+    pub ends_at: OffsetDateTime,
+    /// The time the window was reserved at.
+    pub reserved_at: OffsetDateTime,
+    /// The round the contributor was scheduled for while the window was open,
+    /// which it keeps until the round starts, even once the window has ended.
+    #[serde(default)]
+    pub assigned_round: Option<u64>,
+}
+
+impl Reservation {
+    ///
+    /// Checks that a contributor may reserve the time window from `starts_at`
+    /// to `ends_at` at the time `now`.
+    ///
+    /// Returns `CoordinatorError::ReservationWindowInvalid` if the window
+    /// starts in the past, too far ahead, or is too short or too long.
+    ///
+    pub fn check_window(
+        starts_at: OffsetDateTime,
+        ends_at: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<(), CoordinatorError> {
+        let length = ends_at - starts_at;
+        if starts_at < now
+            || starts_at - now > MAXIMUM_RESERVATION_LEAD
+            || length < MINIMUM_RESERVATION
+            || length > MAXIMUM_RESERVATION
+        {
+            return Err(CoordinatorError::ReservationWindowInvalid);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the time window is open at the given time.
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// Returns `true` if the contributor is scheduled ahead of the contributors without
+    /// a reservation for the round `next_round_height` at the given time.
+    pub fn is_scheduled(&self, next_round_height: u64, now: OffsetDateTime) -> bool {
+        self.is_active(now) || self.assigned_round == Some(next_round_height)
+    }
+
+    /// Returns `true` if the time window overlaps the window from `starts_at` to `ends_at`.
+    pub fn overlaps(&self, starts_at: OffsetDateTime, ends_at: OffsetDateTime) -> bool {
+        self.starts_at < ends_at && starts_at < self.ends_at
+    }
+
+    /// Returns the reservation as an iCalendar (RFC 5545) event.
+    pub fn to_ics(&self) -> String {
+        let lines = [
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//Aleo//Aleo Setup Coordinator//EN".to_string(),
+            "METHOD:PUBLISH".to_string(),
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:reservation-{}@aleo-setup", self.id),
+            format!("DTSTAMP:{}", ics_time(self.reserved_at)),
+            format!("DTSTART:{}", ics_time(self.starts_at)),
+            format!("DTEND:{}", ics_time(self.ends_at)),
+            "SUMMARY:Aleo setup contribution".to_string(),
+            format!(
+                "DESCRIPTION:Contribution of {} to the Aleo setup ceremony. Start the contributor \
+                 before the window opens\\, it is scheduled for a round while the window is open.",
+                self.participant
+            ),
+            "END:VEVENT".to_string(),
+            "END:VCALENDAR".to_string(),
+        ];
+        lines.iter().map(|line| fold_ics_line(line)).collect()
+    }
+
+    /// Returns the reservation as it is sent to the contributor.
+    pub fn to_response(&self) -> SlotReservation {
+        SlotReservation {
+            id: self.id,
+            contributor: self.participant.to_string(),
+            starts_at: self.starts_at.unix_timestamp(),
+            ends_at: self.ends_at.unix_timestamp(),
+            ics: self.to_ics(),
+        }
+    }
+}
+
+/// Formats a time as an iCalendar UTC date-time, e.g. `20211101T120000Z`.
+fn ics_time(time: OffsetDateTime) -> String {
+    time.to_offset(UtcOffset::UTC)
+        .format(format_description!("[year][month][day]T[hour][minute][second]Z"))
+        .expect("a UTC date-time can always be formatted")
+}
+
+/// Terminates an iCalendar content line, folding it into lines of at most 75 octets.
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    #[test]
+    fn test_reservation_window() {
+        let now = datetime!(2021-11-01 12:00 UTC);
+        let starts_at = now + Duration::days(7);
+
+        assert!(Reservation::check_window(starts_at, starts_at + Duration::hours(4), now).is_ok());
+        for (starts_at, ends_at) in [
+            (now - Duration::hours(1), now + Duration::hours(3)),
+            (now + Duration::days(91), now + Duration::days(91) + Duration::hours(4)),
+            (starts_at, starts_at + Duration::minutes(30)),
+            (starts_at, starts_at + Duration::hours(25)),
+            (starts_at, starts_at - Duration::hours(4)),
+        ] {
+            assert!(Reservation::check_window(starts_at, ends_at, now).is_err());
+        }
+    }
+
+    #[test]
+    fn test_reservation_ics() {
+        let reservation = Reservation {
+            id: 3,
+            participant: Participant::new_contributor("aleo1abc"),
+            starts_at: datetime!(2021-11-08 09:00 +01:00),
+            ends_at: datetime!(2021-11-08 13:00 +01:00),
+            reserved_at: datetime!(2021-11-01 12:00 UTC),
+            assigned_round: None,
+        };
+        let ics = reservation.to_ics();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("\r\nUID:reservation-3@aleo-setup\r\n"));
+        assert!(ics.contains("\r\nDTSTART:20211108T080000Z\r\n"));
+        assert!(ics.contains("\r\nDTEND:20211108T120000Z\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    }
+}
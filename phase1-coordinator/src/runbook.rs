@@ -0,0 +1,137 @@
+//! Runbooks which chain the steps of an operation on the ceremony, so that an
+//! operator runs a single command instead of a sequence of commands which is
+//! easy to get wrong during an incident.
+//!
+//! Every step checks whether it was already taken before taking it, so a
+//! runbook which was interrupted halfway is resumed by running it again.
+
+use fs_err::{self as fs, File};
+use serde::Serialize;
+use std::{
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// A step of advancing the ceremony to the next round, in the order they are taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdvanceRoundStep {
+    /// Checks that every chunk of the round was contributed to and verified.
+    CheckVerified,
+    /// Aggregates the contributions of the chunks into the transcript of the round.
+    Aggregate,
+    /// Generates the challenges of the next round and starts it.
+    NextRound,
+    /// Publishes the signed index of the next round, which clients poll.
+    PublishIndex,
+    /// Notifies the contributors that the next round started.
+    NotifyContributors,
+}
+
+impl fmt::Display for AdvanceRoundStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let step = match self {
+            AdvanceRoundStep::CheckVerified => "check verified",
+            AdvanceRoundStep::Aggregate => "aggregate",
+            AdvanceRoundStep::NextRound => "next round",
+            AdvanceRoundStep::PublishIndex => "publish index",
+            AdvanceRoundStep::NotifyContributors => "notify contributors",
+        };
+        write!(f, "{}", step)
+    }
+}
+
+/// What a step of a runbook did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunbookStep {
+    pub step: AdvanceRoundStep,
+    /// `false` if the step was already taken by an earlier run, and was skipped.
+    pub taken: bool,
+    pub detail: String,
+}
+
+/// The steps taken to advance the ceremony from one round to the next.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AdvanceRoundReport {
+    pub from_round: u64,
+    pub to_round: u64,
+    pub steps: Vec<RunbookStep>,
+}
+
+impl AdvanceRoundReport {
+    pub fn new(from_round: u64) -> Self {
+        Self {
+            from_round,
+            to_round: from_round + 1,
+            steps: vec![],
+        }
+    }
+
+    /// Records that the step was taken.
+    pub fn taken(&mut self, step: AdvanceRoundStep, detail: String) {
+        self.steps.push(RunbookStep {
+            step,
+            taken: true,
+            detail,
+        });
+    }
+
+    /// Records that the step was skipped, as an earlier run already took it.
+    pub fn skipped(&mut self, step: AdvanceRoundStep, detail: String) {
+        self.steps.push(RunbookStep {
+            step,
+            taken: false,
+            detail,
+        });
+    }
+}
+
+impl fmt::Display for AdvanceRoundReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let status = if step.taken { "done" } else { "skip" };
+            writeln!(f, "{:<6} {}: {}", status, step.step, step.detail)?;
+        }
+        writeln!(f, "The ceremony advanced from round {} to round {}", self.from_round, self.to_round)
+    }
+}
+
+///
+/// Writes the given contents to a temporary file next to the given path, and moves
+/// it to the path once it is synced, so that a client polling the path never reads
+/// a file which was written halfway, and a rerun replaces what an earlier run wrote.
+///
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let mut file = File::create(&partial)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&partial, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_round_report() {
+        let mut report = AdvanceRoundReport::new(3);
+        report.skipped(AdvanceRoundStep::Aggregate, "round 3 was already aggregated".to_string());
+        report.taken(AdvanceRoundStep::NextRound, "generated 8 challenges".to_string());
+        assert_eq!(report.to_round, 4);
+        assert_eq!(
+            report.to_string(),
+            "skip   aggregate: round 3 was already aggregated\n\
+             done   next round: generated 8 challenges\n\
+             The ceremony advanced from round 3 to round 4\n"
+        );
+        assert_eq!(
+            serde_json::to_value(&report.steps[0]).unwrap()["step"],
+            serde_json::json!("aggregate")
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! The policies which decide the order contributors waiting in the queue
+//! are scheduled to rounds in, so that organizers can choose how the
+//! slots of a round are shared among the contributors who want them.
+
+use crate::objects::Participant;
+
+use serde::{Deserialize, Serialize};
+use setup_utils::calculate_hash;
+use std::cmp::Ordering;
+use time::OffsetDateTime;
+
+/// A contributor waiting in the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedContributor {
+    pub participant: Participant,
+    pub reliability: u8,
+    /// The time the contributor joined the queue.
+    pub joined: OffsetDateTime,
+}
+
+/// Decides the order contributors waiting in the queue are scheduled in.
+///
+/// The coordinator assigns the first `maximum_contributors_per_round` of the
+/// scheduled contributors to the next round, the following ones to the round
+/// after it, and so on. The queue is scheduled again whenever it is updated,
+/// so a policy should order the contributors the same way for the same next
+/// round, or the assigned rounds of the contributors change between updates.
+pub trait SchedulingPolicy {
+    ///
+    /// Orders the given contributors, which are sorted by the time they joined
+    /// the queue, in the order they are scheduled in for the rounds starting
+    /// from `next_round_height`. The queue was first scheduled for the next
+    /// round at `scheduled_at`, which is the same for every update.
+    ///
+    fn schedule(&self, contributors: &mut [QueuedContributor], next_round_height: u64, scheduled_at: OffsetDateTime);
+}
+
+/// Schedules contributors in the order they joined the queue.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstInFirstOut;
+
+impl SchedulingPolicy for FirstInFirstOut {
+    fn schedule(&self, _: &mut [QueuedContributor], _: u64, _: OffsetDateTime) {}
+}
+
+/// Schedules contributors in the order of a lottery drawn for each round,
+/// where every contributor has the same chance of each slot.
+#[derive(Debug, Clone, Copy)]
+pub struct Lottery {
+    pub seed: u64,
+}
+
+impl SchedulingPolicy for Lottery {
+    fn schedule(&self, contributors: &mut [QueuedContributor], next_round_height: u64, _scheduled_at: OffsetDateTime) {
+        contributors.sort_by_cached_key(|contributor| ticket(self.seed, next_round_height, &contributor.participant));
+    }
+}
+
+/// Schedules contributors in the order of a lottery drawn for each round,
+/// where the chance of a contributor to be drawn before another is
+/// proportional to the time it had waited in the queue when the queue was
+/// first scheduled for the round. Contributors who joined later have the
+/// smallest weight.
+#[derive(Debug, Clone, Copy)]
+pub struct WaitWeighted {
+    pub seed: u64,
+}
+
+impl SchedulingPolicy for WaitWeighted {
+    fn schedule(&self, contributors: &mut [QueuedContributor], next_round_height: u64, scheduled_at: OffsetDateTime) {
+        // Draws a weighted sample without replacement (Efraimidis and Spirakis), by sorting
+        // on `u^(1 / weight)` for a uniform `u` in (0, 1), compared as `ln(u) / weight`.
+        let key = |contributor: &QueuedContributor| {
+            let u = (ticket(self.seed, next_round_height, &contributor.participant) as f64 + 0.5) / 2f64.powi(64);
+            let weight = (scheduled_at - contributor.joined).whole_seconds().max(1) as f64;
+            u.ln() / weight
+        };
+        contributors.sort_by(|a, b| key(b).partial_cmp(&key(a)).unwrap_or(Ordering::Equal));
+    }
+}
+
+/// The scheduling policy the coordinator orders its queue with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SchedulingPolicyKind {
+    /// See [FirstInFirstOut].
+    FirstInFirstOut,
+    /// See [Lottery]. The seed should be kept secret, so that
+    /// contributors cannot predict their tickets.
+    Lottery { seed: u64 },
+    /// See [WaitWeighted]. The seed should be kept secret, so that
+    /// contributors cannot predict their tickets.
+    WaitWeighted { seed: u64 },
+}
+
+impl SchedulingPolicyKind {
+    /// Returns the scheduling policy of this kind.
+    pub fn policy(&self) -> Box<dyn SchedulingPolicy> {
+        match *self {
+            SchedulingPolicyKind::FirstInFirstOut => Box::new(FirstInFirstOut),
+            SchedulingPolicyKind::Lottery { seed } => Box::new(Lottery { seed }),
+            SchedulingPolicyKind::WaitWeighted { seed } => Box::new(WaitWeighted { seed }),
+        }
+    }
+}
+
+impl Default for SchedulingPolicyKind {
+    fn default() -> Self {
+        SchedulingPolicyKind::FirstInFirstOut
+    }
+}
+
+/// Returns the lottery ticket of a contributor for the given round, which is
+/// uniformly distributed, and the same for every update of the queue.
+fn ticket(seed: u64, round_height: u64, participant: &Participant) -> u64 {
+    let mut input = Vec::new();
+    input.extend_from_slice(&seed.to_le_bytes());
+    input.extend_from_slice(&round_height.to_le_bytes());
+    input.extend_from_slice(participant.to_string().as_bytes());
+    let hash = calculate_hash(&input);
+    let mut ticket = [0u8; 8];
+    ticket.copy_from_slice(&hash[..8]);
+    u64::from_le_bytes(ticket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use time::macros::datetime;
+
+    const NOW: OffsetDateTime = datetime!(2021-11-01 12:00 UTC);
+
+    fn queue(waits: &[i64]) -> Vec<QueuedContributor> {
+        waits
+            .iter()
+            .enumerate()
+            .map(|(id, wait)| QueuedContributor {
+                participant: Participant::new_contributor(&id.to_string()),
+                reliability: 10,
+                joined: NOW - time::Duration::minutes(*wait),
+            })
+            .collect()
+    }
+
+    /// Returns how often each contributor is scheduled first over many rounds.
+    fn first_slots(policy: &dyn SchedulingPolicy, waits: &[i64], rounds: u64) -> HashMap<Participant, u64> {
+        let mut first_slots = HashMap::new();
+        for round_height in 1..=rounds {
+            let mut contributors = queue(waits);
+            policy.schedule(&mut contributors, round_height, NOW);
+            *first_slots.entry(contributors[0].participant.clone()).or_default() += 1;
+        }
+        first_slots
+    }
+
+    #[test]
+    fn test_first_in_first_out() {
+        let mut contributors = queue(&[30, 20, 10]);
+        let expected = contributors.clone();
+        FirstInFirstOut.schedule(&mut contributors, 1, NOW);
+        assert_eq!(expected, contributors);
+    }
+
+    #[test]
+    fn test_lottery_is_uniform() {
+        let policy = Lottery { seed: 7 };
+        let rounds = 4000;
+        let first_slots = first_slots(&policy, &[40, 30, 20, 10], rounds);
+
+        // Each of the 4 contributors wins the first slot of about a quarter of the
+        // rounds, regardless of how long it has waited.
+        assert_eq!(4, first_slots.len());
+        for (participant, count) in first_slots {
+            assert!(
+                (850..1150).contains(&count),
+                "{} won {} of {} rounds",
+                participant,
+                count,
+                rounds
+            );
+        }
+    }
+
+    #[test]
+    fn test_lottery_is_stable_within_a_round() {
+        let policy = Lottery { seed: 7 };
+        let mut contributors = queue(&[40, 30, 20]);
+        policy.schedule(&mut contributors, 3, NOW);
+
+        // A contributor joining the queue does not change the order of the others.
+        let mut joined = queue(&[40, 30, 20, 0]);
+        policy.schedule(&mut joined, 3, NOW + time::Duration::minutes(5));
+        joined.retain(|contributor| contributors.contains(contributor));
+        assert_eq!(contributors, joined);
+    }
+
+    #[test]
+    fn test_wait_weighted_favors_longer_waits() {
+        let policy = WaitWeighted { seed: 7 };
+        let rounds = 4000;
+        let first_slots = first_slots(&policy, &[90, 30], rounds);
+
+        // The contributor who has waited three times longer is drawn first
+        // in about three quarters of the rounds, but not in every round.
+        let count = first_slots[&Participant::new_contributor("0")];
+        assert!((2800..3200).contains(&count), "won {} of {} rounds", count, rounds);
+    }
+
+    #[test]
+    fn test_wait_weighted_is_stable_within_a_round() {
+        let policy = WaitWeighted { seed: 7 };
+        let mut contributors = queue(&[40, 30, 20, 10]);
+        policy.schedule(&mut contributors, 3, NOW);
+
+        // Scheduled again as of the same time, the order is the same, even once a contributor joins.
+        let mut joined = queue(&[40, 30, 20, 10, 0]);
+        joined[4].joined = NOW + time::Duration::minutes(5);
+        policy.schedule(&mut joined, 3, NOW);
+        joined.retain(|contributor| contributors.contains(contributor));
+        assert_eq!(contributors, joined);
+    }
+
+    #[test]
+    fn test_wait_weighted_does_not_starve() {
+        let policy = WaitWeighted { seed: 7 };
+
+        // A contributor who just joined is still drawn first now and then,
+        // and is drawn first more often as it waits longer.
+        let newcomer = Participant::new_contributor("1");
+        let just_joined = first_slots(&policy, &[600, 10], 4000)
+            .get(&newcomer)
+            .copied()
+            .unwrap_or_default();
+        let waited = first_slots(&policy, &[600, 300], 4000)
+            .get(&newcomer)
+            .copied()
+            .unwrap_or_default();
+        assert!(just_joined > 0);
+        assert!(waited > just_joined);
+    }
+}
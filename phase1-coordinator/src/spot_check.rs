@@ -0,0 +1,63 @@
+//! Independent re-verification of a response file against the challenge it was
+//! computed on, so that anyone can spot check the contributions of the ceremony
+//! while it runs. The coordinator and verifiers running as a spot check service
+//! both verify with [spot_check], and sign the verdict it returns.
+
+use crate::{commands::Verification, environment::Environment, storage::Object, CoordinatorError};
+use setup1_shared::protocol::v1::{SpotCheckRequest, SpotCheckVerdict};
+
+use setup_utils::calculate_hash;
+
+///
+/// Verifies the response file of a spot check against the challenge file the caller
+/// looked up for the round, chunk and contribution of the request, as of `verified_at`.
+///
+/// A response which fails verification is not an error, but an invalid verdict which
+/// holds the reason it failed. The verdict holds the hash of the challenge the response
+/// was verified against, which is invalid if it is not the challenge of the request.
+///
+/// Returns a `CoordinatorError` if the request names a chunk which does not exist or
+/// the initial challenge of a chunk, or if the response file is not the size of a
+/// response to the chunk.
+///
+pub fn spot_check(
+    environment: &Environment,
+    request: &SpotCheckRequest,
+    challenge: &[u8],
+    response: &[u8],
+    verified_at: i64,
+) -> Result<SpotCheckVerdict, CoordinatorError> {
+    if request.chunk_id >= environment.number_of_chunks() {
+        return Err(CoordinatorError::ChunkIdInvalid);
+    }
+    if request.contribution_id == 0 {
+        return Err(CoordinatorError::VerificationOnContributionIdZero);
+    }
+    if response.len() as u64 != Object::contribution_file_size(environment, request.chunk_id, false) {
+        return Err(CoordinatorError::ContributionFileSizeMismatch);
+    }
+
+    let challenge_hash = hex::encode(calculate_hash(challenge));
+    let reason = if !challenge_hash.eq_ignore_ascii_case(&request.challenge_hash) {
+        Some("the challenge of the contribution does not have the given hash".to_string())
+    } else {
+        match Verification::run_spot_check(environment, request.chunk_id, challenge, response) {
+            Ok(_) => None,
+            Err(CoordinatorError::ContributionHashMismatch) => {
+                Some("the response was not computed on the challenge".to_string())
+            }
+            Err(error) => Some(format!("the response failed verification: {}", error)),
+        }
+    };
+
+    Ok(SpotCheckVerdict {
+        round_height: request.round_height,
+        chunk_id: request.chunk_id,
+        contribution_id: request.contribution_id,
+        challenge_hash,
+        response_hash: hex::encode(calculate_hash(response)),
+        valid: reason.is_none(),
+        reason,
+        verified_at,
+    })
+}
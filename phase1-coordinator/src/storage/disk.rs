@@ -1,15 +1,22 @@
 use crate::{
+    audit::SignedAuditRecord,
     environment::Environment,
+    events::CeremonyEvent,
     objects::{ContributionFileSignature, Round},
+    quarantine::{ForensicBundle, Rejection},
     storage::{
+        encryption::{is_partial, write_atomically},
         ContributionLocator,
         ContributionSignatureLocator,
         Locator,
         Object,
         ObjectReader,
         ObjectWriter,
+        StorageEncryption,
         StorageLocator,
         StorageObject,
+        WalEntry,
+        WalRecovery,
     },
     CoordinatorError,
     CoordinatorState,
@@ -19,15 +26,17 @@ use anyhow::Result;
 use fs_err::{self as fs, File, OpenOptions};
 use itertools::Itertools;
 use memmap::MmapOptions;
+use serde::{de::DeserializeOwned, Serialize};
 
 use std::{
     convert::TryFrom,
-    io::{Read, Write},
+    io::{BufRead, BufReader, Read, Write},
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
     str::FromStr,
 };
-use tracing::{debug, error, trace};
+use time::OffsetDateTime;
+use tracing::{debug, error, trace, warn};
 
 use super::{LocatorPath, StorageAction};
 
@@ -35,10 +44,13 @@ use super::{LocatorPath, StorageAction};
 pub struct Disk {
     environment: Environment,
     resolver: DiskResolver,
+    cold_resolver: Option<DiskResolver>,
+    encryption: Option<StorageEncryption>,
 }
 
 impl Disk {
-    /// Loads a new instance of `Disk`.
+    /// Loads a new instance of `Disk`. The mutations left in the write-ahead
+    /// log are not recovered here, but by the coordinator when it starts.
     pub fn load(environment: &Environment) -> Result<Self, CoordinatorError>
     where
         Self: Sized,
@@ -52,10 +64,7 @@ impl Disk {
         }
 
         // Create a new `Storage` instance, and set the `Environment`.
-        let mut storage = Self {
-            environment: environment.clone(),
-            resolver: DiskResolver::new(environment.local_base_directory()),
-        };
+        let mut storage = Self::new(environment)?;
 
         // Create the coordinator state locator if it does not exist yet.
         if !storage.exists(&Locator::CoordinatorState) {
@@ -69,6 +78,32 @@ impl Disk {
         Ok(storage)
     }
 
+    /// Opens the disk storage of an existing ceremony without modifying it,
+    /// for tools which inspect the ceremony. Unlike `load`, this creates
+    /// nothing, so it fails if the coordinator state does not exist.
+    pub fn open_read_only(environment: &Environment) -> Result<Self, CoordinatorError> {
+        let storage = Self::new(environment)?;
+        if !storage.exists(&Locator::CoordinatorState) {
+            return Err(CoordinatorError::CoordinatorStateNotInitialized);
+        }
+        Ok(storage)
+    }
+
+    fn new(environment: &Environment) -> Result<Self, CoordinatorError> {
+        // Only the archives in cold storage are encrypted, so encryption without
+        // cold storage would leave every file unencrypted.
+        if environment.storage_encryption().is_some() && environment.cold_storage_directory().is_none() {
+            return Err(CoordinatorError::StorageEncryptionWithoutColdStorage);
+        }
+
+        Ok(Self {
+            environment: environment.clone(),
+            resolver: DiskResolver::new(environment.local_base_directory()),
+            cold_resolver: environment.cold_storage_directory().map(DiskResolver::new),
+            encryption: environment.storage_encryption().cloned(),
+        })
+    }
+
     /// Initializes the location corresponding to the given locator.
     pub fn initialize(&mut self, locator: Locator, size: u64) -> Result<(), CoordinatorError> {
         let locator_path = self.to_path(&locator)?;
@@ -103,7 +138,8 @@ impl Disk {
         Ok(())
     }
 
-    /// Checks whether the given locator exists in the storage or not.
+    /// Checks whether the given locator exists in the storage or not,
+    /// including locators which were archived to cold storage.
     pub fn exists(&self, locator: &Locator) -> bool {
         let path = match self.to_path(locator) {
             Ok(path) => path,
@@ -113,11 +149,217 @@ impl Disk {
             }
         };
 
+        trace!("Checking that {} exists in storage", path);
+        if is_file(&path) {
+            return true;
+        }
+
+        match &self.cold_resolver {
+            Some(cold_resolver) => match cold_resolver.to_path(locator) {
+                Ok(cold_path) => is_file(&cold_path),
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Checks whether the given locator exists in the storage or not, and
+    /// if it was archived to cold storage, retrieves it back into storage,
+    /// so that it can be accessed as before.
+    fn exists_locally(&self, locator: &Locator) -> bool {
+        let path = match self.to_path(locator) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Could not convert locator to path - {}", e);
+                return false;
+            }
+        };
+
         trace!("Ensuring that {} exists in storage", path);
-        match fs::metadata(path) {
-            Ok(metadata) => metadata.is_file(),
-            Err(_) => false,
+        if is_file(&path) {
+            return true;
+        }
+
+        match self.retrieve(locator) {
+            Ok(retrieved) => retrieved,
+            Err(e) => {
+                error!("Could not retrieve {} from cold storage - {}", path, e);
+                false
+            }
+        }
+    }
+
+    ///
+    /// Moves the contribution files of the given round to cold storage,
+    /// if a cold storage directory is configured.
+    ///
+    /// The contribution file signatures, which hold the hashes of the
+    /// contribution files, and the round state are kept in storage.
+    /// If storage encryption is configured, the archived contribution
+    /// files are encrypted with the active key.
+    ///
+    /// Returns the number of contribution files which were archived.
+    ///
+    pub fn archive_round(&mut self, round_height: u64) -> Result<usize, CoordinatorError> {
+        let cold_resolver = match &self.cold_resolver {
+            Some(cold_resolver) => cold_resolver,
+            None => return Ok(0),
+        };
+
+        let round_directory = self.resolver.round_directory(round_height);
+        if !Path::new(&round_directory).exists() {
+            return Ok(0);
         }
+
+        let mut archived = 0;
+        for chunk_directory in fs::read_dir(&round_directory)? {
+            let chunk_directory = chunk_directory?.path();
+            if !chunk_directory.is_dir() {
+                continue;
+            }
+
+            for entry in fs::read_dir(&chunk_directory)? {
+                let path = entry?.path();
+                let path = path.to_str().ok_or(CoordinatorError::StorageLocatorFormatIncorrect)?;
+                let locator = match self.resolver.to_locator(&LocatorPath::new(path.to_string())) {
+                    Ok(locator @ Locator::ContributionFile(_)) => locator,
+                    _ => continue,
+                };
+
+                // Copy the contribution file before removing it, as cold storage
+                // may be mounted from a different file system.
+                let cold_path = cold_resolver.to_path(&locator)?;
+                if let Some(parent) = cold_path.as_path().parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                match &self.encryption {
+                    Some(encryption) => encryption.encrypt_file(Path::new(path), cold_path.as_path())?,
+                    None => {
+                        fs::copy(path, cold_path.as_path())?;
+                    }
+                }
+                fs::remove_file(path)?;
+
+                trace!("Archived {} to {}", path, cold_path);
+                archived += 1;
+            }
+        }
+
+        debug!("Archived {} contribution files of round {}", archived, round_height);
+        Ok(archived)
+    }
+
+    /// Copies the object at the given locator back from cold storage, if it was archived.
+    fn retrieve(&self, locator: &Locator) -> Result<bool, CoordinatorError> {
+        let cold_path = match &self.cold_resolver {
+            Some(cold_resolver) => cold_resolver.to_path(locator)?,
+            None => return Ok(false),
+        };
+        if !is_file(&cold_path) {
+            return Ok(false);
+        }
+
+        if let Locator::ContributionFile(contribution_locator) = locator {
+            self.resolver
+                .chunk_directory_init(contribution_locator.round_height(), contribution_locator.chunk_id());
+        }
+
+        let path = self.to_path(locator)?;
+        match &self.encryption {
+            Some(encryption) if StorageEncryption::is_encrypted_file(cold_path.as_path())? => {
+                encryption.decrypt_file(cold_path.as_path(), path.as_path())?
+            }
+            _ => {
+                fs::copy(cold_path.as_path(), path.as_path())?;
+            }
+        }
+
+        debug!("Retrieved {} from cold storage", path);
+        Ok(true)
+    }
+
+    ///
+    /// Wraps the data keys of the contribution files in cold storage with
+    /// the active key of the storage encryption, e.g. after a key rotation.
+    ///
+    /// Returns the number of contribution files which were rewrapped.
+    ///
+    pub fn rewrap_archives(&self) -> Result<usize, CoordinatorError> {
+        let (cold_resolver, encryption) = match (&self.cold_resolver, &self.encryption) {
+            (Some(cold_resolver), Some(encryption)) => (cold_resolver, encryption),
+            _ => return Ok(0),
+        };
+
+        let mut directories = vec![PathBuf::from(&cold_resolver.base)];
+        let mut rewrapped = 0;
+        while let Some(directory) = directories.pop() {
+            if !directory.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&directory)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    directories.push(path);
+                    continue;
+                }
+
+                // Skip the archives which are still being written, or were left behind by an interruption.
+                if is_partial(&path) || !StorageEncryption::is_encrypted_file(&path)? {
+                    continue;
+                }
+                if encryption.rewrap_file(&path)? {
+                    trace!("Rewrapped {}", path.display());
+                    rewrapped += 1;
+                }
+            }
+        }
+
+        debug!(
+            "Rewrapped {} contribution files with key {}",
+            rewrapped,
+            encryption.active_key()
+        );
+        Ok(rewrapped)
+    }
+
+    ///
+    /// Copies the verified contribution at the given locator to the directory of
+    /// each artifact replica, as the challenge of the next contribution, at the path
+    /// of the download endpoint which serves it from the coordinator.
+    ///
+    /// A challenge of an earlier round at the same path is replaced, and is never
+    /// left truncated, as replicas serve their directories while they are written.
+    ///
+    /// Returns the number of replicas the challenge was copied to.
+    ///
+    pub fn replicate_challenge(&self, locator: &ContributionLocator) -> Result<usize, CoordinatorError> {
+        let replicas = self.environment.artifact_replicas();
+        if replicas.is_empty() {
+            return Ok(0);
+        }
+        if !locator.is_verified() {
+            return Err(CoordinatorError::ContributionMissingVerifiedLocator);
+        }
+
+        let path = self.to_path(&Locator::ContributionFile(locator.clone()))?;
+        for replica in replicas {
+            let destination = Path::new(&replica.directory)
+                .join("v1/download/challenge")
+                .join(locator.chunk_id().to_string())
+                .join((locator.contribution_id() + 1).to_string());
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomically(&destination, |writer| {
+                std::io::copy(&mut File::open(path.as_path())?, writer)?;
+                Ok(())
+            })?;
+
+            trace!("Replicated {} to {}", path, destination.display());
+        }
+
+        debug!("Replicated {} to {} artifact replicas", path, replicas.len());
+        Ok(replicas.len())
     }
 
     /// Returns a copy of an object at the given locator in storage, if it exists.
@@ -126,7 +368,7 @@ impl Disk {
         trace!("Fetching {}", path);
 
         // Check that the given locator exists in storage.
-        if !self.exists(locator) {
+        if !self.exists_locally(locator) {
             error!("Locator missing in call to get() in storage - {:?}", locator);
             return Err(CoordinatorError::StorageLocatorMissing);
         }
@@ -239,7 +481,7 @@ impl Disk {
         trace!("Updating {}", path);
 
         // Check that the given locator exists in storage.
-        if !self.exists(locator) {
+        if !self.exists_locally(locator) {
             error!("Locator missing in call to update() in storage.");
             return Err(CoordinatorError::StorageLocatorMissing);
         }
@@ -263,7 +505,7 @@ impl Disk {
         );
 
         // Check that the given source locator exists in storage.
-        if !self.exists(source_locator) {
+        if !self.exists_locally(source_locator) {
             error!("Source locator missing in call to copy() in storage.");
             return Err(CoordinatorError::StorageLocatorMissing);
         }
@@ -301,7 +543,17 @@ impl Disk {
         // TODO: if any of the locators are directories, make this
         // detect whether the path is a directory of a file and call
         // the appropriate function.
-        fs::remove_file(path.clone())?;
+        if is_file(&path) {
+            fs::remove_file(path.clone())?;
+        }
+
+        // Remove the archived copy, so that it is not retrieved again.
+        if let Some(cold_resolver) = &self.cold_resolver {
+            let cold_path = cold_resolver.to_path(locator)?;
+            if is_file(&cold_path) {
+                fs::remove_file(cold_path)?;
+            }
+        }
 
         trace!("Removed {}", path);
         Ok(())
@@ -313,7 +565,7 @@ impl Disk {
         trace!("Fetching size of {}", path);
 
         // Check that the given locator exists in storage.
-        if !self.exists(locator) {
+        if !self.exists_locally(locator) {
             error!("Locator missing in call to size() in storage.");
             return Err(CoordinatorError::StorageLocatorMissing);
         }
@@ -325,6 +577,248 @@ impl Disk {
         Ok(file.metadata()?.len())
     }
 
+    /// Appends the given event to the event log.
+    pub fn append_event(&mut self, event: &CeremonyEvent) -> Result<(), CoordinatorError> {
+        let path = self.resolver.event_log();
+        trace!("Appending event to {}", path);
+        append_json_line(&path, event)
+    }
+
+    /// Returns the events in the event log, in the order they were appended.
+    pub fn events(&self) -> Result<Vec<CeremonyEvent>, CoordinatorError> {
+        read_json_lines(&self.resolver.event_log())
+    }
+
+    /// Appends the given record to the audit log.
+    pub fn append_audit_record(&mut self, record: &SignedAuditRecord) -> Result<(), CoordinatorError> {
+        let path = self.resolver.audit_log();
+        trace!("Appending audit record to {}", path);
+        append_json_line(&path, record)
+    }
+
+    /// Returns the records in the audit log, in the order they were appended.
+    pub fn audit_records(&self) -> Result<Vec<SignedAuditRecord>, CoordinatorError> {
+        read_json_lines(&self.resolver.audit_log())
+    }
+
+    ///
+    /// Quarantines a rejected response file, by copying it to the quarantine directory
+    /// along with its forensic bundle, so that it is kept for analysis after the round
+    /// disposes of or overwrites it.
+    ///
+    /// A response file which was already quarantined keeps the bundle it was first
+    /// quarantined with.
+    ///
+    /// Returns the forensic bundle of the response file.
+    ///
+    pub fn quarantine(
+        &mut self,
+        rejection: Rejection,
+        quarantined_at: OffsetDateTime,
+    ) -> Result<ForensicBundle, CoordinatorError> {
+        let response_locator = Locator::ContributionFile(ContributionLocator::new(
+            rejection.round_height,
+            rejection.chunk_id,
+            rejection.contribution_id,
+            false,
+        ));
+        if !self.exists_locally(&response_locator) {
+            return Err(CoordinatorError::StorageLocatorMissing);
+        }
+
+        // The files are read directly, as the reader refuses files of the wrong size.
+        let response_path = self.to_path(&response_locator)?;
+        let response = fs::read(response_path.clone())?;
+        let challenge = match rejection.contribution_id.checked_sub(1) {
+            Some(challenge_id) => {
+                let challenge_locator = Locator::ContributionFile(ContributionLocator::new(
+                    rejection.round_height,
+                    rejection.chunk_id,
+                    challenge_id,
+                    true,
+                ));
+                match self.exists_locally(&challenge_locator) {
+                    true => Some(fs::read(self.to_path(&challenge_locator)?)?),
+                    false => None,
+                }
+            }
+            None => None,
+        };
+        let bundle = ForensicBundle::capture(
+            &self.environment,
+            rejection,
+            challenge.as_deref(),
+            &response,
+            quarantined_at,
+        );
+
+        let directory = PathBuf::from(self.resolver.quarantine_directory(&bundle));
+        let bundle_path = directory.join("bundle.json");
+        if bundle_path.exists() {
+            debug!("{} is already quarantined in {}", response_path, directory.display());
+            return Ok(serde_json::from_slice(&fs::read(bundle_path)?)?);
+        }
+
+        fs::create_dir_all(&directory)?;
+        fs::copy(&response_path, directory.join("response"))?;
+        fs::write(bundle_path, serde_json::to_vec_pretty(&bundle)?)?;
+
+        debug!("Quarantined {} in {}", response_path, directory.display());
+        Ok(bundle)
+    }
+
+    /// Returns the forensic bundles of the quarantined response files, in the order they were quarantined.
+    pub fn quarantined(&self) -> Result<Vec<ForensicBundle>, CoordinatorError> {
+        let quarantine = self.resolver.quarantine_root();
+        if !Path::new(&quarantine).exists() {
+            return Ok(vec![]);
+        }
+
+        // The quarantine is laid out as `round_{h}/chunk_{c}/contribution_{id}_{hash}/bundle.json`.
+        let mut directories = vec![PathBuf::from(quarantine)];
+        for _ in 0..3 {
+            let mut children = vec![];
+            for directory in directories {
+                for entry in fs::read_dir(&directory)? {
+                    let path = entry?.path();
+                    if path.is_dir() {
+                        children.push(path);
+                    }
+                }
+            }
+            directories = children;
+        }
+
+        let mut bundles = vec![];
+        for directory in directories {
+            let bundle_path = directory.join("bundle.json");
+            if bundle_path.exists() {
+                bundles.push(serde_json::from_slice::<ForensicBundle>(&fs::read(bundle_path)?)?);
+            }
+        }
+        bundles.sort_by_key(|bundle| bundle.quarantined_at);
+        Ok(bundles)
+    }
+
+    ///
+    /// Applies the given mutation through the write-ahead log.
+    ///
+    /// The entry is synced to the log before any object is written, and the
+    /// log is truncated once every object is written and synced, so that a
+    /// mutation which is interrupted is recovered the next time the
+    /// coordinator starts.
+    ///
+    pub fn write_ahead(&mut self, entry: WalEntry) -> Result<(), CoordinatorError> {
+        trace!("Writing ahead the mutation to {}", entry.description);
+        self.append_wal_entry(&entry)?;
+        self.replay(&entry)?;
+        self.truncate_wal()?;
+        trace!("Applied the mutation to {}", entry.description);
+        Ok(())
+    }
+
+    /// Appends the given entry to the write-ahead log and syncs it to disk.
+    pub(crate) fn append_wal_entry(&mut self, entry: &WalEntry) -> Result<(), CoordinatorError> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.resolver.write_ahead_log())?;
+        file.write_all(&line)?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    ///
+    /// Recovers the mutations left in the write-ahead log, in the order they
+    /// were logged, and truncates the log.
+    ///
+    /// A mutation is replayed if every file it refers to is in storage, e.g.
+    /// the response file of a contribution the round records, and rolled back
+    /// otherwise. An entry which was not completely written is discarded, as
+    /// nothing was written for it yet.
+    ///
+    pub fn recover(&mut self) -> Result<Vec<WalRecovery>, CoordinatorError> {
+        let path = self.resolver.write_ahead_log();
+        if !Path::new(&path).exists() {
+            return Ok(vec![]);
+        }
+
+        let mut recoveries = vec![];
+        for line in fs::read_to_string(&path)?.lines().filter(|line| !line.is_empty()) {
+            let entry: WalEntry = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Discarding an incomplete entry of the write-ahead log - {}", e);
+                    break;
+                }
+            };
+
+            match entry.requires.iter().all(|locator| self.exists(locator)) {
+                true => {
+                    self.replay(&entry)?;
+                    recoveries.push(WalRecovery::Replayed(entry.description));
+                }
+                false => {
+                    self.roll_back(&entry)?;
+                    recoveries.push(WalRecovery::RolledBack(entry.description));
+                }
+            }
+        }
+
+        self.truncate_wal()?;
+        Ok(recoveries)
+    }
+
+    /// Writes the objects of the given mutation as they are after it, and removes its files.
+    fn replay(&mut self, entry: &WalEntry) -> Result<(), CoordinatorError> {
+        for write in &entry.writes {
+            self.write_synced(&write.locator, write.after.clone().into_object())?;
+        }
+        for locator in &entry.removes {
+            if self.exists(locator) {
+                self.remove(locator)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the objects of the given mutation as they were before it, in reverse order.
+    fn roll_back(&mut self, entry: &WalEntry) -> Result<(), CoordinatorError> {
+        for write in entry.writes.iter().rev() {
+            match &write.before {
+                Some(before) => self.write_synced(&write.locator, before.clone().into_object())?,
+                None if self.exists(&write.locator) => self.remove(&write.locator)?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts or updates the object at the given locator, and syncs it to disk.
+    fn write_synced(&mut self, locator: &Locator, object: Object) -> Result<(), CoordinatorError> {
+        match self.exists(locator) {
+            true => self.update(locator, object)?,
+            false => self.insert(locator.clone(), object)?,
+        }
+        File::open(self.to_path(locator)?)?.sync_all()?;
+        Ok(())
+    }
+
+    /// Truncates the write-ahead log, which commits the mutations in it.
+    fn truncate_wal(&mut self) -> Result<(), CoordinatorError> {
+        let path = self.resolver.write_ahead_log();
+        if Path::new(&path).exists() {
+            let file = OpenOptions::new().write(true).open(path)?;
+            file.set_len(0)?;
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
     /// Process a [StorageAction] which mutates the storage.
     pub fn process(&mut self, action: StorageAction) -> Result<()> {
         match action {
@@ -473,7 +967,7 @@ impl StorageObject for Disk {
         let path = self.to_path(&locator)?;
 
         // Check that the locator exists in storage.
-        if !self.exists(&locator) {
+        if !self.exists_locally(&locator) {
             error!("Locator {} missing in call to reader() in storage.", path);
             return Err(CoordinatorError::StorageLocatorMissing);
         }
@@ -534,7 +1028,7 @@ impl StorageObject for Disk {
         let path = self.to_path(&locator)?;
 
         // Check that the locator exists in storage.
-        if !self.exists(&locator) {
+        if !self.exists_locally(&locator) {
             error!("Locator {} missing in call to writer() in storage.", path);
             return Err(CoordinatorError::StorageLocatorMissing);
         }
@@ -582,6 +1076,43 @@ impl StorageObject for Disk {
     }
 }
 
+/// Returns `true` if there is a file at the given path.
+fn is_file(path: &LocatorPath) -> bool {
+    match fs::metadata(path) {
+        Ok(metadata) => metadata.is_file(),
+        Err(_) => false,
+    }
+}
+
+/// Appends the given value to the log at the given path, as a line of JSON.
+fn append_json_line<T: Serialize>(path: &str, value: &T) -> Result<(), CoordinatorError> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Returns the values in the log at the given path, one per line of JSON, in the order they were appended.
+fn read_json_lines<T: DeserializeOwned>(path: &str) -> Result<Vec<T>, CoordinatorError> {
+    if !Path::new(path).exists() {
+        return Ok(vec![]);
+    }
+
+    let file = File::open(path)?;
+    let mut values = vec![];
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.is_empty() {
+            values.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(values)
+}
+
 #[derive(Debug)]
 struct DiskResolver {
     base: String,
@@ -811,6 +1342,45 @@ impl DiskResolver {
         format!("{}/chunk_{}", path, chunk_id)
     }
 
+    /// Returns the path of the event log from the coordinator.
+    #[inline]
+    fn event_log(&self) -> String {
+        format!("{}/events.jsonl", self.base)
+    }
+
+    /// Returns the path of the audit log from the coordinator.
+    #[inline]
+    fn audit_log(&self) -> String {
+        format!("{}/audit.jsonl", self.base)
+    }
+
+    /// Returns the path of the write-ahead log from the coordinator.
+    #[inline]
+    fn write_ahead_log(&self) -> String {
+        format!("{}/wal.jsonl", self.base)
+    }
+
+    /// Returns the quarantine directory of rejected response files from the coordinator.
+    #[inline]
+    fn quarantine_root(&self) -> String {
+        format!("{}/quarantine", self.base)
+    }
+
+    /// Returns the quarantine directory of a rejected response file, which is
+    /// named after the hash of the response to keep each rejected response apart.
+    #[inline]
+    fn quarantine_directory(&self, bundle: &ForensicBundle) -> String {
+        let rejection = &bundle.rejection;
+        format!(
+            "{}/round_{}/chunk_{}/contribution_{}_{}",
+            self.quarantine_root(),
+            rejection.round_height,
+            rejection.chunk_id,
+            rejection.contribution_id,
+            &bundle.response_hash[..16]
+        )
+    }
+
     /// Initializes the chunk directory for a given  round height, and chunk ID.
     #[inline]
     fn chunk_directory_init(&self, round_height: u64, chunk_id: u64) {
@@ -0,0 +1,504 @@
+//! Envelope encryption of the contribution files archived to cold storage.
+//!
+//! Every archived file is encrypted with a fresh data key, and the data key
+//! is encrypted (wrapped) with a key encryption key, which is read from a
+//! local key file or obtained from a KMS. The ID of the key encryption key
+//! is stored in the header of the archived file, so that the files archived
+//! before a key rotation can still be decrypted, and rotating a key only
+//! rewraps the data keys instead of encrypting the files again.
+//!
+//! The files are encrypted and decrypted in segments of 64 KiB, so that
+//! archiving or retrieving a contribution file does not hold it in memory.
+//!
+//! Only the archives are encrypted. The files in the local base directory
+//! of the coordinator are stored as they are, as the contribution files of
+//! the current rounds are memory-mapped to compute and verify contributions,
+//! so they would be decrypted to disk anyway. The local base directory must
+//! be on an encrypted volume where encryption at rest is required, and the
+//! storage refuses to load with encryption configured but no cold storage,
+//! which would leave every file unencrypted.
+
+use crate::CoordinatorError;
+
+use chacha20poly1305::{
+    aead::{
+        generic_array::GenericArray,
+        stream::{DecryptorBE32, EncryptorBE32},
+        Aead,
+        NewAead,
+        Payload,
+    },
+    ChaCha20Poly1305,
+    Key,
+    Nonce,
+};
+use fs_err::{self as fs, File};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex, PoisonError},
+};
+use tracing::error;
+use zeroize::Zeroizing;
+
+/// The magic bytes an encrypted archive starts with.
+const MAGIC: &[u8; 8] = b"ALEOENC1";
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+const WRAPPED_KEY_SIZE: usize = NONCE_SIZE + KEY_SIZE + TAG_SIZE;
+/// The size of the nonce prefix of the segments, the remaining 5 bytes
+/// of the nonce hold the segment counter and the last segment flag.
+const STREAM_NONCE_SIZE: usize = NONCE_SIZE - 5;
+/// The size of the plaintext of every segment but the last one.
+const SEGMENT_SIZE: usize = 64 * 1024;
+
+/// A key which is overwritten with zeros when it is dropped.
+type SecretKey = Zeroizing<[u8; KEY_SIZE]>;
+
+/// The source of a key encryption key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum KeyEncryptionKey {
+    /// A hex-encoded 32-byte key, read from a local file.
+    File { path: String },
+    /// A hex-encoded 32-byte key, printed to stdout by the given command,
+    /// e.g. a KMS client which decrypts the key with a KMS key.
+    Kms { command: Vec<String> },
+}
+
+impl KeyEncryptionKey {
+    /// Returns the 32 bytes of the key.
+    fn load(&self) -> Result<SecretKey, CoordinatorError> {
+        let encoded = Zeroizing::new(match self {
+            KeyEncryptionKey::File { path } => fs::read_to_string(path)?,
+            KeyEncryptionKey::Kms { command } => {
+                let (program, args) = command.split_first().ok_or(CoordinatorError::StorageEncryptionKeyInvalid)?;
+                let output = Command::new(program).args(args).output()?;
+                if !output.status.success() {
+                    error!("The KMS command {} failed with {}", program, output.status);
+                    return Err(CoordinatorError::StorageEncryptionKeyInvalid);
+                }
+                String::from_utf8(output.stdout).map_err(|_| CoordinatorError::StorageEncryptionKeyInvalid)?
+            }
+        });
+
+        let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+        hex::decode_to_slice(encoded.trim(), &mut *key).map_err(|_| CoordinatorError::StorageEncryptionKeyInvalid)?;
+        Ok(key)
+    }
+}
+
+/// The key encryption keys which were loaded, by ID. The clones of the
+/// storage encryption share them, so that every key file is read and
+/// every KMS command is run once, until the keys are rotated or retired.
+#[derive(Clone, Default)]
+struct LoadedKeys(Arc<Mutex<BTreeMap<String, SecretKey>>>);
+
+impl fmt::Debug for LoadedKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LoadedKeys(..)")
+    }
+}
+
+/// The loaded keys only cache the key sources, so they are not compared.
+impl PartialEq for LoadedKeys {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LoadedKeys {}
+
+/// The keys the contributions archived to cold storage are encrypted with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageEncryption {
+    /// The ID of the key new archives are encrypted with.
+    active_key: String,
+    /// The key encryption keys by ID. Retired keys are kept until every
+    /// archive was rewrapped with the active key.
+    keys: BTreeMap<String, KeyEncryptionKey>,
+    #[serde(skip)]
+    loaded: LoadedKeys,
+}
+
+impl StorageEncryption {
+    /// Returns the keys with the given active key, which is also used to decrypt archives.
+    pub fn new(active_key: &str, key: KeyEncryptionKey) -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(active_key.to_string(), key);
+        Self {
+            active_key: active_key.to_string(),
+            keys,
+            loaded: LoadedKeys::default(),
+        }
+    }
+
+    /// Makes the given key the active key, and keeps the previous keys to decrypt older archives.
+    pub fn rotate(mut self, active_key: &str, key: KeyEncryptionKey) -> Self {
+        self.loaded = LoadedKeys::default();
+        self.keys.insert(active_key.to_string(), key);
+        self.active_key = active_key.to_string();
+        self
+    }
+
+    /// Removes a retired key, once no archive is encrypted with it anymore.
+    pub fn retire(mut self, key_id: &str) -> Self {
+        if key_id != self.active_key {
+            self.loaded = LoadedKeys::default();
+            self.keys.remove(key_id);
+        }
+        self
+    }
+
+    /// Returns the ID of the key new archives are encrypted with.
+    pub fn active_key(&self) -> &str {
+        &self.active_key
+    }
+
+    /// Returns `true` if the file at the given path is an encrypted archive.
+    pub fn is_encrypted_file(path: &Path) -> Result<bool, CoordinatorError> {
+        let mut magic = [0u8; MAGIC.len()];
+        match File::open(path)?.read_exact(&mut magic) {
+            Ok(()) => Ok(&magic == MAGIC),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Encrypts the given file to the given path with a fresh data key wrapped with the active key.
+    pub fn encrypt_file(&self, source: &Path, destination: &Path) -> Result<(), CoordinatorError> {
+        let mut data_key = Zeroizing::new([0u8; KEY_SIZE]);
+        rand::thread_rng().fill_bytes(&mut *data_key);
+        let mut nonce = [0u8; STREAM_NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let header = self.header(&self.active_key, &data_key)?;
+
+        let mut reader = File::open(source)?;
+        write_atomically(destination, |writer| {
+            writer.write_all(&header)?;
+            writer.write_all(&nonce)?;
+
+            let cipher = ChaCha20Poly1305::new(Key::from_slice(&*data_key));
+            let mut encryptor = EncryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce));
+            let mut segment = vec![0u8; SEGMENT_SIZE];
+            loop {
+                // The last segment is the first one which is shorter than a full segment.
+                let size = read_segment(&mut reader, &mut segment)?;
+                if size < SEGMENT_SIZE {
+                    let ciphertext = encryptor
+                        .encrypt_last(&segment[..size])
+                        .map_err(|_| CoordinatorError::StorageEncryptionFailed)?;
+                    writer.write_all(&ciphertext)?;
+                    return Ok(());
+                }
+                let ciphertext = encryptor
+                    .encrypt_next(&segment[..])
+                    .map_err(|_| CoordinatorError::StorageEncryptionFailed)?;
+                writer.write_all(&ciphertext)?;
+            }
+        })
+    }
+
+    /// Decrypts the given archive to the given path with the key its data key is wrapped with.
+    pub fn decrypt_file(&self, source: &Path, destination: &Path) -> Result<(), CoordinatorError> {
+        let mut reader = File::open(source)?;
+        let (_, data_key) = self.read_header(&mut reader)?;
+        let mut nonce = [0u8; STREAM_NONCE_SIZE];
+        read_field(&mut reader, &mut nonce)?;
+
+        write_atomically(destination, |writer| {
+            let cipher = ChaCha20Poly1305::new(Key::from_slice(&*data_key));
+            let mut decryptor = DecryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce));
+            let mut segment = vec![0u8; SEGMENT_SIZE + TAG_SIZE];
+            loop {
+                let size = read_segment(&mut reader, &mut segment)?;
+                if size < segment.len() {
+                    let plaintext = decryptor
+                        .decrypt_last(&segment[..size])
+                        .map_err(|_| CoordinatorError::StorageDecryptionFailed)?;
+                    writer.write_all(&plaintext)?;
+                    return Ok(());
+                }
+                let plaintext = decryptor
+                    .decrypt_next(&segment[..])
+                    .map_err(|_| CoordinatorError::StorageDecryptionFailed)?;
+                writer.write_all(&plaintext)?;
+            }
+        })
+    }
+
+    ///
+    /// Wraps the data key of the given archive with the active key.
+    ///
+    /// Returns `false` if the data key is already wrapped with the active key.
+    ///
+    pub fn rewrap_file(&self, path: &Path) -> Result<bool, CoordinatorError> {
+        let mut reader = File::open(path)?;
+        let (key_id, data_key) = self.read_header(&mut reader)?;
+        if key_id == self.active_key {
+            return Ok(false);
+        }
+
+        // The segments are encrypted with the data key, so they are copied as they are.
+        let header = self.header(&self.active_key, &data_key)?;
+        write_atomically(path, |writer| {
+            writer.write_all(&header)?;
+            io::copy(&mut reader, writer)?;
+            Ok(())
+        })?;
+        Ok(true)
+    }
+
+    /// Returns the header holding the key ID and the data key wrapped with that key.
+    fn header(&self, key_id: &str, data_key: &SecretKey) -> Result<Vec<u8>, CoordinatorError> {
+        let key = self.load_key(key_id)?;
+
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&(key_id.len() as u16).to_le_bytes());
+        header.extend_from_slice(key_id.as_bytes());
+        // Bind the wrapped data key to the key ID.
+        let wrapped_key = seal(&key, &**data_key, key_id.as_bytes())?;
+        header.extend_from_slice(&wrapped_key);
+        Ok(header)
+    }
+
+    /// Reads the header of an archive, and returns its key ID and the unwrapped data key.
+    fn read_header(&self, reader: &mut impl Read) -> Result<(String, SecretKey), CoordinatorError> {
+        let mut prefix = [0u8; MAGIC.len() + 2];
+        read_field(reader, &mut prefix)?;
+        if !prefix.starts_with(MAGIC) {
+            return Err(CoordinatorError::StorageDecryptionFailed);
+        }
+        let mut key_id = vec![0u8; u16::from_le_bytes([prefix[MAGIC.len()], prefix[MAGIC.len() + 1]]) as usize];
+        read_field(reader, &mut key_id)?;
+        let mut wrapped_key = [0u8; WRAPPED_KEY_SIZE];
+        read_field(reader, &mut wrapped_key)?;
+
+        let key_id = String::from_utf8(key_id).map_err(|_| CoordinatorError::StorageDecryptionFailed)?;
+        let key = self.load_key(&key_id)?;
+
+        let mut data_key = Zeroizing::new([0u8; KEY_SIZE]);
+        let unwrapped_key = open(&key, &wrapped_key, key_id.as_bytes())?;
+        if unwrapped_key.len() != KEY_SIZE {
+            return Err(CoordinatorError::StorageDecryptionFailed);
+        }
+        data_key.copy_from_slice(&unwrapped_key);
+        Ok((key_id, data_key))
+    }
+
+    /// Returns the key encryption key with the given ID, which is loaded from its source once.
+    fn load_key(&self, key_id: &str) -> Result<SecretKey, CoordinatorError> {
+        let mut loaded = self.loaded.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(key) = loaded.get(key_id) {
+            return Ok(Zeroizing::new(**key));
+        }
+
+        let key = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| CoordinatorError::StorageEncryptionKeyMissing {
+                key_id: key_id.to_string(),
+            })?
+            .load()?;
+        loaded.insert(key_id.to_string(), Zeroizing::new(*key));
+        Ok(key)
+    }
+}
+
+/// Encrypts the given message under a random nonce, which is prepended to the ciphertext.
+fn seal(key: &[u8; KEY_SIZE], msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, CoordinatorError> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg, aad })
+        .map_err(|_| CoordinatorError::StorageEncryptionFailed)?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypts a message sealed with [seal].
+fn open(key: &[u8; KEY_SIZE], sealed: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, CoordinatorError> {
+    if sealed.len() < NONCE_SIZE + TAG_SIZE {
+        return Err(CoordinatorError::StorageDecryptionFailed);
+    }
+    let (nonce, msg) = sealed.split_at(NONCE_SIZE);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), Payload { msg, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| CoordinatorError::StorageDecryptionFailed)
+}
+
+/// Fills the given buffer from the header of an archive, where a truncated header fails the decryption.
+fn read_field(reader: &mut impl Read, buffer: &mut [u8]) -> Result<(), CoordinatorError> {
+    match reader.read_exact(buffer) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Err(CoordinatorError::StorageDecryptionFailed),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Fills the given buffer as far as the reader allows, and returns the number of bytes read.
+fn read_segment(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut size = 0;
+    while size < buffer.len() {
+        match reader.read(&mut buffer[size..]) {
+            Ok(0) => break,
+            Ok(read) => size += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(size)
+}
+
+/// Returns the temporary path a file is written to before it is moved to the given path.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    PathBuf::from(partial)
+}
+
+///
+/// Writes a file to a temporary path with the given function, and moves it
+/// to the given path once it was written and synced.
+///
+/// A failed or interrupted write does not leave a truncated file at the path.
+///
+pub(crate) fn write_atomically(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), CoordinatorError>,
+) -> Result<(), CoordinatorError> {
+    let partial = partial_path(path);
+    let mut writer = BufWriter::new(File::create(&partial)?);
+    let written = write(&mut writer).and_then(|()| {
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        Ok(())
+    });
+    drop(writer);
+
+    match written {
+        Ok(()) => Ok(fs::rename(&partial, path)?),
+        Err(error) => {
+            let _ = fs::remove_file(&partial);
+            Err(error)
+        }
+    }
+}
+
+/// Returns `true` if the given path is a file which is still being written.
+pub(crate) fn is_partial(path: &Path) -> bool {
+    path.extension().map_or(false, |extension| extension == "partial")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a random key encryption key to the given directory.
+    fn key_file(directory: &Path, id: &str) -> KeyEncryptionKey {
+        let mut key = [0u8; KEY_SIZE];
+        rand::thread_rng().fill_bytes(&mut key);
+        let path = directory.join(format!("{}.key", id)).to_str().unwrap().to_string();
+        fs::write(&path, hex::encode(key)).unwrap();
+        KeyEncryptionKey::File { path }
+    }
+
+    #[test]
+    fn test_encrypt_file_round_trip() {
+        let directory = Path::new("./transcript/testing_encryption_round_trip");
+        let _ = fs::remove_dir_all(directory);
+        fs::create_dir_all(directory).unwrap();
+        let encryption = StorageEncryption::new("first", key_file(directory, "first"));
+
+        // Files which are empty, shorter than a segment, a multiple of the segment size and longer.
+        for size in [0, 1000, 2 * SEGMENT_SIZE, 2 * SEGMENT_SIZE + 1] {
+            let mut plaintext = vec![0u8; size];
+            rand::thread_rng().fill_bytes(&mut plaintext);
+            let source = directory.join("plaintext");
+            let encrypted = directory.join("encrypted");
+            let decrypted = directory.join("decrypted");
+            fs::write(&source, &plaintext).unwrap();
+
+            encryption.encrypt_file(&source, &encrypted).unwrap();
+            assert!(StorageEncryption::is_encrypted_file(&encrypted).unwrap());
+            assert!(!partial_path(&encrypted).exists());
+            encryption.decrypt_file(&encrypted, &decrypted).unwrap();
+            assert_eq!(plaintext, fs::read(&decrypted).unwrap());
+
+            // A truncated archive fails to decrypt, and leaves no file behind.
+            let ciphertext = fs::read(&encrypted).unwrap();
+            fs::remove_file(&decrypted).unwrap();
+            fs::write(&encrypted, &ciphertext[..ciphertext.len() - 1]).unwrap();
+            assert!(encryption.decrypt_file(&encrypted, &decrypted).is_err());
+            assert!(!decrypted.exists());
+            assert!(!partial_path(&decrypted).exists());
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_keys_are_loaded_once() {
+        let directory = Path::new("./transcript/testing_encryption_keys");
+        let _ = fs::remove_dir_all(directory);
+        fs::create_dir_all(directory).unwrap();
+        let first_key = key_file(directory, "first");
+        let encryption = StorageEncryption::new("first", first_key.clone());
+
+        let source = directory.join("plaintext");
+        let encrypted = directory.join("encrypted");
+        let decrypted = directory.join("decrypted");
+        fs::write(&source, b"contribution").unwrap();
+        encryption.encrypt_file(&source, &encrypted).unwrap();
+
+        // The key is not read again, by the storage encryption or its clones.
+        if let KeyEncryptionKey::File { path } = &first_key {
+            fs::remove_file(path).unwrap();
+        }
+        encryption.clone().decrypt_file(&encrypted, &decrypted).unwrap();
+        assert_eq!(b"contribution".to_vec(), fs::read(&decrypted).unwrap());
+
+        // After a rotation, the keys are loaded again.
+        let rotated = encryption.rotate("second", key_file(directory, "second"));
+        assert!(matches!(
+            rotated.rewrap_file(&encrypted),
+            Err(CoordinatorError::IOError(_))
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_rewrap_file() {
+        let directory = Path::new("./transcript/testing_encryption_rewrap");
+        let _ = fs::remove_dir_all(directory);
+        fs::create_dir_all(directory).unwrap();
+        let encryption = StorageEncryption::new("first", key_file(directory, "first"));
+
+        let source = directory.join("plaintext");
+        let encrypted = directory.join("encrypted");
+        let decrypted = directory.join("decrypted");
+        fs::write(&source, vec![7u8; SEGMENT_SIZE + 1]).unwrap();
+        encryption.encrypt_file(&source, &encrypted).unwrap();
+
+        let rotated = encryption.rotate("second", key_file(directory, "second"));
+        assert!(rotated.rewrap_file(&encrypted).unwrap());
+        assert!(!rotated.rewrap_file(&encrypted).unwrap());
+
+        let retired = rotated.retire("first");
+        retired.decrypt_file(&encrypted, &decrypted).unwrap();
+        assert_eq!(fs::read(&source).unwrap(), fs::read(&decrypted).unwrap());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
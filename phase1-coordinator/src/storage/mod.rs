@@ -1,5 +1,11 @@
 pub mod disk;
 pub use disk::*;
 
+pub mod encryption;
+pub use encryption::*;
+
 pub mod storage;
 pub use storage::*;
+
+pub mod wal;
+pub use wal::*;
@@ -0,0 +1,127 @@
+//! A write-ahead log of the mutations of the coordinator state and the round
+//! state, so that a crash in the middle of a mutation, e.g. after a response
+//! is added to the round but before the task of its contributor is completed
+//! in the coordinator state, cannot leave the two disagreeing.
+//!
+//! Before a mutation writes to storage, its entry is appended to the log and
+//! synced to disk, holding every object it writes as it was before and as it
+//! is after the mutation. The log is truncated once the mutation is applied.
+//! An entry which is still in the log on startup belongs to a mutation which
+//! was interrupted, and is replayed if the files it refers to are in storage,
+//! or rolled back otherwise.
+
+use crate::{
+    storage::{Disk, Locator, Object},
+    CoordinatorError,
+    CoordinatorState,
+    Round,
+};
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An object a mutation writes, which is one of the objects holding the state of the ceremony.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WalObject {
+    CoordinatorState(CoordinatorState),
+    RoundState(Round),
+    RoundHeight(u64),
+}
+
+impl WalObject {
+    fn from_object(object: Object) -> Result<Self, CoordinatorError> {
+        match object {
+            Object::CoordinatorState(state) => Ok(WalObject::CoordinatorState(state)),
+            Object::RoundState(round) => Ok(WalObject::RoundState(round)),
+            Object::RoundHeight(height) => Ok(WalObject::RoundHeight(height)),
+            _ => Err(CoordinatorError::StorageFailed),
+        }
+    }
+
+    pub fn into_object(self) -> Object {
+        match self {
+            WalObject::CoordinatorState(state) => Object::CoordinatorState(state),
+            WalObject::RoundState(round) => Object::RoundState(round),
+            WalObject::RoundHeight(height) => Object::RoundHeight(height),
+        }
+    }
+}
+
+/// An object a mutation writes, as it was before and as it is after the mutation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalWrite {
+    pub locator: Locator,
+    /// The object before the mutation, or `None` if it did not exist.
+    pub before: Option<WalObject>,
+    pub after: WalObject,
+}
+
+/// A mutation of storage, as it is written ahead to the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalEntry {
+    /// What the mutation does, for the logs.
+    pub description: String,
+    /// The files the mutation refers to, without which it is rolled back instead of replayed.
+    pub requires: Vec<Locator>,
+    /// The objects the mutation writes, in order.
+    pub writes: Vec<WalWrite>,
+    /// The files the mutation removes, which are not restored when it is rolled back.
+    pub removes: Vec<Locator>,
+}
+
+impl WalEntry {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            requires: vec![],
+            writes: vec![],
+            removes: vec![],
+        }
+    }
+
+    /// Adds the given files to the files the mutation refers to.
+    pub fn requires(mut self, locators: impl IntoIterator<Item = Locator>) -> Self {
+        self.requires.extend(locators);
+        self
+    }
+
+    /// Adds a write of the given object, reading the object it replaces from storage.
+    pub fn writes(mut self, storage: &Disk, locator: Locator, object: WalObject) -> Result<Self, CoordinatorError> {
+        let before = match storage.exists(&locator) {
+            true => Some(WalObject::from_object(storage.get(&locator)?)?),
+            false => None,
+        };
+        self.writes.push(WalWrite {
+            locator,
+            before,
+            after: object,
+        });
+        Ok(self)
+    }
+
+    /// Adds a removal of the given file.
+    pub fn removes(mut self, locator: Locator) -> Self {
+        self.removes.push(locator);
+        self
+    }
+}
+
+/// What the recovery did with a mutation which was interrupted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalRecovery {
+    /// The mutation was applied again in full.
+    Replayed(String),
+    /// The objects the mutation writes were restored, as a file it refers to is missing.
+    RolledBack(String),
+}
+
+impl fmt::Display for WalRecovery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WalRecovery::Replayed(description) => write!(f, "replayed the interrupted mutation to {}", description),
+            WalRecovery::RolledBack(description) => {
+                write!(f, "rolled back the interrupted mutation to {}", description)
+            }
+        }
+    }
+}
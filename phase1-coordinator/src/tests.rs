@@ -1,9 +1,21 @@
 use crate::{
     authentication::Dummy,
+    bootstrap::{BootstrapChallenge, BootstrapManifest},
     commands::{Seed, SigningKey, SEED_LENGTH},
-    environment::{Environment, Parameters, Settings, Testing},
+    environment::{ArtifactReplicaStore, Environment, FinalizationLock, KeyUsagePolicy, Parameters, Settings, Testing},
+    events::EventKind,
+    history::{compare_rounds, ArchivedRound},
     objects::Task,
-    storage::{Disk, StorageLocator},
+    runbook::AdvanceRoundStep,
+    storage::{
+        ContributionLocator,
+        ContributionSignatureLocator,
+        Disk,
+        KeyEncryptionKey,
+        Locator,
+        StorageEncryption,
+        StorageLocator,
+    },
     testing::prelude::*,
     Coordinator,
     CoordinatorError,
@@ -11,7 +23,12 @@ use crate::{
     Participant,
     Round,
 };
-use phase1::{helpers::CurveKind, ContributionMode, ProvingSystem};
+use phase1::{helpers::CurveKind, ContributionMode, ProvingSystem, VerificationMode};
+use setup1_shared::{
+    ids::{ChunkId, ContributionId},
+    structures::{ArtifactReplica, LockResponse},
+};
+use setup_utils::calculate_hash;
 use time::OffsetDateTime;
 
 use fs_err as fs;
@@ -21,6 +38,7 @@ use std::{
     collections::{HashSet, LinkedList},
     iter::FromIterator,
     net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
@@ -2389,3 +2407,1083 @@ fn round_on_groth16_bw6_761() {
 fn round_on_marlin_bls12_377() {
     execute_round(ProvingSystem::Marlin, CurveKind::Bls12_377).unwrap();
 }
+
+#[test]
+#[serial]
+fn sampled_verification_defers_full_verification() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let testing = Testing::from(parameters).verification_mode(VerificationMode::Sampled { samples: 4 });
+    let environment = initialize_test_environment(&testing.into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+
+    // Instantiate a coordinator.
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+
+    // Initialize the ceremony to round 0.
+    coordinator.initialize()?;
+    assert_eq!(0, coordinator.current_round_height()?);
+
+    // Add a contributor to the queue.
+    let contributor = create_contributor_test_details("1");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let verifier = create_verifier_test_details("1");
+    coordinator.add_to_queue(contributor.participant.clone(), Some(contributor_ip), 10)?;
+
+    // Advance the ceremony from round 0 to round 1.
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    // Run contribution and sampled verification for round 1.
+    for _ in 0..number_of_chunks {
+        contributor.contribute_to(&mut coordinator)?;
+        verifier.verify_if_available(&mut coordinator)?;
+    }
+
+    // Every contribution was accepted, but is pending a full verification.
+    assert!(coordinator.get_pending_verifications().is_empty());
+    assert_eq!(number_of_chunks, coordinator.state().get_deferred_verifications().len());
+    assert!(!coordinator.state().is_current_round_finished());
+
+    // The full verifications are not run by an update, and hold up the round.
+    coordinator.update()?;
+    assert_eq!(number_of_chunks, coordinator.state().get_deferred_verifications().len());
+    assert!(!coordinator.state().is_current_round_aggregated());
+
+    // A full verification which cannot run leaves the task to be verified again.
+    let (task, participant) = coordinator.prioritized_deferred_verifications()[0].clone();
+    coordinator.finish_deferred_verification(&task, &participant, Err(CoordinatorError::StorageFailed))?;
+    assert_eq!(number_of_chunks, coordinator.state().get_deferred_verifications().len());
+    assert!(coordinator.is_current_contributor(&contributor.participant));
+
+    // The full verifications run before the round is finalized.
+    coordinator.run_deferred_verifications()?;
+    assert!(coordinator.state().get_deferred_verifications().is_empty());
+    coordinator.update()?;
+    assert!(!coordinator.is_current_contributor(&contributor.participant));
+    assert!(coordinator.state().is_current_round_aggregated());
+
+    Ok(())
+}
+
+/// Returns the testing environment of a ceremony with the given finalization lock on round 1.
+fn locked_round_testing(lock: FinalizationLock) -> Testing {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        6,  /* power */
+        16, /* batch_size */
+        16, /* chunk_size */
+    ));
+    Testing::from(parameters).finalization_lock(1, lock)
+}
+
+/// Runs round 1 with a single contributor under the given finalization lock,
+/// returning the coordinator once every contribution was verified.
+fn run_locked_round(time: Arc<MockTimeSource>, lock: FinalizationLock) -> anyhow::Result<Coordinator> {
+    let environment = initialize_test_environment(&locked_round_testing(lock).into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+
+    // Instantiate a coordinator.
+    let mut coordinator = Coordinator::new_with_time(environment, Arc::new(Dummy), time)?;
+
+    // Initialize the ceremony to round 0.
+    coordinator.initialize()?;
+
+    // Add a contributor to the queue.
+    let contributor = create_contributor_test_details("1");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let verifier = create_verifier_test_details("1");
+    coordinator.add_to_queue(contributor.participant.clone(), Some(contributor_ip), 10)?;
+
+    // Advance the ceremony from round 0 to round 1.
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    // Run contribution and verification for round 1.
+    for _ in 0..number_of_chunks {
+        contributor.contribute_to(&mut coordinator)?;
+        verifier.verify_if_available(&mut coordinator)?;
+    }
+    assert!(coordinator.state().is_current_round_finished());
+
+    Ok(coordinator)
+}
+
+#[test]
+#[serial]
+fn finalization_lock_waits_for_date() -> anyhow::Result<()> {
+    let now = OffsetDateTime::now_utc();
+    let time = Arc::new(MockTimeSource::new(now));
+    let lock = FinalizationLock {
+        not_before: Some(now + time::Duration::hours(1)),
+        minimum_contributions: 1,
+    };
+    let mut coordinator = run_locked_round(time.clone(), lock)?;
+
+    // The round is finished, but is not finalized before the lock date.
+    coordinator.update()?;
+    assert!(!coordinator.state().is_current_round_aggregated());
+    assert!(matches!(
+        coordinator.try_aggregate(),
+        Err(CoordinatorError::RoundFinalizationTooEarly { .. })
+    ));
+
+    time.update(|prev| prev + time::Duration::hours(2));
+    coordinator.update()?;
+    assert!(coordinator.state().is_current_round_aggregated());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn finalization_lock_requires_minimum_contributions() -> anyhow::Result<()> {
+    let lock = FinalizationLock {
+        not_before: None,
+        minimum_contributions: 2,
+    };
+    let replacement_contributor = create_contributor_test_details("replacement-1");
+    let testing = locked_round_testing(lock).coordinator_contributors(&[replacement_contributor.participant.clone()]);
+    let environment = initialize_test_environment(&testing.into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+
+    // Instantiate a coordinator.
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+
+    // Initialize the ceremony to round 0.
+    coordinator.initialize()?;
+
+    // A single contributor is not enough to start a round which could be finalized.
+    let contributor_1 = create_contributor_test_details("1");
+    let contributor_1_ip = IpAddr::V4("0.0.0.1".parse().unwrap());
+    coordinator.add_to_queue(contributor_1.participant.clone(), Some(contributor_1_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(0, coordinator.current_round_height()?);
+    assert!(matches!(
+        coordinator.try_advance(OffsetDateTime::now_utc()),
+        Err(CoordinatorError::RoundAdmissionContributorsInsufficient { queued: 1, minimum: 2 })
+    ));
+
+    // The round starts once enough contributors joined the queue.
+    let contributor_2 = create_contributor_test_details("2");
+    let contributor_2_ip = IpAddr::V4("0.0.0.2".parse().unwrap());
+    coordinator.add_to_queue(contributor_2.participant.clone(), Some(contributor_2_ip), 9)?;
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    // Drop a contributor, whose tasks are taken over by a contributor managed by the coordinator.
+    coordinator.try_lock(&contributor_1.participant)?;
+    coordinator.drop_participant(&contributor_1.participant)?;
+
+    let verifier = create_verifier_test_details("1");
+    for _ in 0..number_of_chunks {
+        replacement_contributor.contribute_to(&mut coordinator)?;
+        contributor_2.contribute_to(&mut coordinator)?;
+        verifier.verify_if_available(&mut coordinator)?;
+        verifier.verify_if_available(&mut coordinator)?;
+    }
+    assert!(coordinator.state().is_current_round_finished());
+
+    // A single independent contributor is not enough to finalize the round, however long the coordinator waits.
+    coordinator.update()?;
+    coordinator.update()?;
+    assert!(!coordinator.state().is_current_round_aggregated());
+    assert!(matches!(
+        coordinator.try_aggregate(),
+        Err(CoordinatorError::RoundFinalizationContributionsInsufficient {
+            finished: 1,
+            minimum: 2
+        })
+    ));
+
+    // An administrator resets the round, which restarts once enough contributors joined the queue again.
+    coordinator.reset_round()?;
+    assert_eq!(0, coordinator.current_round_height()?);
+    coordinator.update()?;
+    assert_eq!(0, coordinator.current_round_height()?);
+
+    let contributor_3 = create_contributor_test_details("3");
+    let contributor_3_ip = IpAddr::V4("0.0.0.3".parse().unwrap());
+    coordinator.add_to_queue(contributor_3.participant.clone(), Some(contributor_3_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn retried_uploads_are_acknowledged() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+    coordinator.initialize()?;
+
+    let (contributor1, contributor_signing_key1, seed1) = create_contributor("1");
+    let (contributor2, _, _) = create_contributor("2");
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor1.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))), 10)?;
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    // Contribute to the first chunk, retrying the upload.
+    let (chunk_id, locked_locators) = coordinator.try_lock(&contributor1)?;
+    let response_locator = locked_locators.next_contribution();
+    coordinator.run_computation(
+        response_locator.round_height(),
+        chunk_id,
+        response_locator.contribution_id(),
+        &contributor1,
+        &contributor_signing_key1,
+        &seed1,
+    )?;
+    let locator = coordinator.try_contribute_idempotent(&contributor1, chunk_id, "response")?;
+    assert_eq!(response_locator, locator);
+    assert_eq!(
+        locator,
+        coordinator.try_contribute_idempotent(&contributor1, chunk_id, "response")?
+    );
+    assert_eq!(1, coordinator.get_pending_verifications().len());
+
+    // The key can't be reused by another participant.
+    assert!(matches!(
+        coordinator.try_contribute_idempotent(&contributor2, chunk_id, "response"),
+        Err(CoordinatorError::IdempotencyKeyReused)
+    ));
+
+    // Verify the contribution, retrying the upload.
+    let task = *coordinator.get_pending_verifications().keys().next().unwrap();
+    coordinator.run_verification(response_locator.round_height(), &task, &verifier, &verifier_signing_key)?;
+    coordinator.try_verify_idempotent(&verifier, &task, "next-challenge")?;
+    coordinator.try_verify_idempotent(&verifier, &task, "next-challenge")?;
+    assert!(coordinator.get_pending_verifications().is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn challenge_hash_is_advertised() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+    coordinator.initialize()?;
+
+    let (contributor, contributor_signing_key, seed) = create_contributor("1");
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))), 10)?;
+    coordinator.update()?;
+
+    // The initial challenge has no verifier signature, so its hash is computed from the file.
+    let (chunk_id, locked_locators) = coordinator.try_lock(&contributor)?;
+    let initial_hash = coordinator.challenge_hash(
+        ChunkId(chunk_id),
+        ContributionId(locked_locators.current_contribution().contribution_id()),
+    )?;
+    assert_eq!(128, initial_hash.len());
+
+    // The next challenge hash is taken from the verifier signature.
+    let response_locator = locked_locators.next_contribution();
+    coordinator.run_computation(
+        response_locator.round_height(),
+        chunk_id,
+        response_locator.contribution_id(),
+        &contributor,
+        &contributor_signing_key,
+        &seed,
+    )?;
+    coordinator.try_contribute(&contributor, chunk_id)?;
+    let task = *coordinator.get_pending_verifications().keys().next().unwrap();
+    coordinator.verify(&verifier, &verifier_signing_key, &task)?;
+
+    let next_hash =
+        coordinator.challenge_hash(ChunkId(chunk_id), ContributionId(response_locator.contribution_id()))?;
+    assert_eq!(128, next_hash.len());
+    assert_ne!(initial_hash, next_hash);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn pinned_chunks_are_locked_first() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let testing = Testing::from(parameters);
+    let (contributor, _, _) = create_contributor("1");
+
+    // Pinning is disabled by default.
+    let environment = initialize_test_environment(&testing.clone().into());
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    coordinator.add_to_queue(contributor.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))), 10)?;
+    coordinator.update()?;
+    assert!(matches!(
+        coordinator.pin_chunks(&contributor, &[3]),
+        Err(CoordinatorError::ChunkPinningDisabled)
+    ));
+
+    // At most the permitted number of chunks are pinned, in the requested order.
+    let environment = initialize_test_environment(&testing.maximum_pinned_chunks(2).into());
+    let number_of_chunks = environment.number_of_chunks();
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    coordinator.add_to_queue(contributor.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))), 10)?;
+    coordinator.update()?;
+    assert!(matches!(
+        coordinator.pin_chunks(&contributor, &[number_of_chunks]),
+        Err(CoordinatorError::ChunkIdInvalid)
+    ));
+    assert_eq!(vec![3, 1], coordinator.pin_chunks(&contributor, &[3, 1, 2])?);
+
+    assert_eq!(3, coordinator.try_lock(&contributor)?.0);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn bootstrap_imports_generated_challenges() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+
+    // Generate the round 0 challenges with a reference coordinator.
+    let challenges_directory = std::env::temp_dir().join("phase1-coordinator-bootstrap-test");
+    let _ = fs::remove_dir_all(&challenges_directory);
+    fs::create_dir_all(&challenges_directory)?;
+
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    let storage = test_storage(&environment);
+    let mut challenges = vec![];
+    for chunk_id in 0..environment.number_of_chunks() {
+        let locator = Locator::ContributionFile(ContributionLocator::new(0, chunk_id, 0, true));
+        let challenge = fs::read(storage.to_path(&locator)?)?;
+        let path = PathBuf::from(format!("challenge_{}", chunk_id));
+        fs::write(challenges_directory.join(&path), &challenge)?;
+        challenges.push(BootstrapChallenge {
+            chunk_id,
+            path,
+            hash: hex::encode(calculate_hash(&challenge)),
+        });
+    }
+    drop(coordinator);
+
+    // A manifest missing a chunk is rejected.
+    let environment = initialize_test_environment(&environment);
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    let incomplete = BootstrapManifest {
+        challenges: challenges[1..].to_vec(),
+    };
+    assert!(matches!(
+        coordinator.bootstrap(&incomplete, &challenges_directory),
+        Err(CoordinatorError::ChunkMissing)
+    ));
+
+    // A challenge which does not match its hash is rejected.
+    let mut tampered = BootstrapManifest {
+        challenges: challenges.clone(),
+    };
+    let mut challenge = fs::read(challenges_directory.join(&tampered.challenges[0].path))?;
+    *challenge.last_mut().unwrap() ^= 1;
+    fs::write(challenges_directory.join("tampered"), &challenge)?;
+    tampered.challenges[0].path = PathBuf::from("tampered");
+    assert!(coordinator.bootstrap(&tampered, &challenges_directory).is_err());
+
+    // The generated challenges are imported for round 0 and the next round.
+    let environment = initialize_test_environment(&environment);
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    let manifest = BootstrapManifest { challenges };
+    let summary = coordinator.bootstrap(&manifest, &challenges_directory)?;
+    assert_eq!(0, summary.round_height);
+    assert_eq!(environment.number_of_chunks(), summary.number_of_chunks);
+    assert_eq!(environment.coordinator_verifiers(), &summary.verifiers);
+    assert_eq!(0, coordinator.current_round_height()?);
+
+    let storage = test_storage(&environment);
+    for challenge in &manifest.challenges {
+        for round_height in 0..2 {
+            let locator =
+                Locator::ContributionFile(ContributionLocator::new(round_height, challenge.chunk_id, 0, true));
+            let imported = fs::read(storage.to_path(&locator)?)?;
+            assert_eq!(challenge.hash, hex::encode(calculate_hash(&imported)));
+        }
+    }
+
+    // A bootstrapped ceremony cannot be bootstrapped again.
+    assert!(matches!(
+        coordinator.bootstrap(&manifest, &challenges_directory),
+        Err(CoordinatorError::RoundAlreadyInitialized)
+    ));
+
+    fs::remove_dir_all(&challenges_directory)?;
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn protocol_events_are_logged() -> anyhow::Result<()> {
+    let time = Arc::new(MockTimeSource::new(OffsetDateTime::now_utc()));
+
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+
+    let mut coordinator = Coordinator::new_with_time(environment, Arc::new(Dummy), time.clone())?;
+    coordinator.initialize()?;
+
+    let (contributor, contributor_signing_key, seed) = create_contributor("1");
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))), 10)?;
+    coordinator.update()?;
+    assert!(coordinator.events(None)?.is_empty());
+
+    let (chunk_id, locked_locators) = coordinator.try_lock(&contributor)?;
+    let contribution_id = locked_locators.next_contribution().contribution_id();
+    coordinator.run_computation(
+        locked_locators.next_contribution().round_height(),
+        chunk_id,
+        contribution_id,
+        &contributor,
+        &contributor_signing_key,
+        &seed,
+    )?;
+
+    time.update(|now| now + time::Duration::minutes(1));
+    let uploaded_at = time.time();
+    coordinator.try_contribute(&contributor, chunk_id)?;
+    let task = *coordinator.get_pending_verifications().keys().next().unwrap();
+    coordinator.verify(&verifier, &verifier_signing_key, &task)?;
+    coordinator.drop_participant(&contributor)?;
+
+    let events: Vec<EventKind> = coordinator.events(None)?.into_iter().map(|event| event.kind).collect();
+    assert_eq!(
+        vec![
+            EventKind::ChunkAssigned {
+                participant: contributor.clone(),
+                chunk_id,
+                contribution_id,
+            },
+            EventKind::ContributionUploaded {
+                participant: contributor.clone(),
+                chunk_id,
+                contribution_id,
+            },
+            EventKind::ContributionVerified {
+                participant: verifier.clone(),
+                chunk_id,
+                contribution_id,
+            },
+            EventKind::ParticipantDropped {
+                participant: contributor.clone(),
+            },
+        ],
+        events
+    );
+
+    // Only the events at or after the given time are exported.
+    let events = coordinator.events(Some(uploaded_at))?;
+    assert_eq!(3, events.len());
+    assert!(
+        events
+            .iter()
+            .all(|event| event.round_height == 1 && event.timestamp >= uploaded_at)
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn finalized_rounds_are_archived() -> anyhow::Result<()> {
+    let cold_storage_directory = "./transcript/testing_cold";
+    let _ = fs::remove_dir_all(cold_storage_directory);
+
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let testing = Testing::from(parameters).cold_storage_directory(cold_storage_directory);
+    let environment = initialize_test_environment(&testing.into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+
+    let (contributor, contributor_signing_key, seed) = create_contributor("1");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    for _ in 0..number_of_chunks {
+        coordinator.contribute(&contributor, &contributor_signing_key, &seed)?;
+        verify_task_if_available(&mut coordinator, &verifier, &verifier_signing_key)?;
+    }
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(2, coordinator.current_round_height()?);
+
+    // The contribution files of round 1 are moved to cold storage,
+    // while the signatures holding their hashes are kept.
+    let storage = test_storage(&environment);
+    let contribution = Locator::ContributionFile(ContributionLocator::new(1, 0, 1, false));
+    let signature = Locator::ContributionFileSignature(ContributionSignatureLocator::new(1, 0, 1, false));
+    let path = storage.to_path(&contribution)?;
+    let cold_path = path
+        .to_string()
+        .replacen(environment.local_base_directory(), cold_storage_directory, 1);
+    assert!(!path.as_path().exists());
+    assert!(Path::new(&cold_path).exists());
+    assert!(storage.to_path(&signature)?.as_path().exists());
+
+    // The next round is not archived.
+    let next_challenge = Locator::ContributionFile(ContributionLocator::new(2, 0, 0, true));
+    assert!(storage.to_path(&next_challenge)?.as_path().exists());
+
+    // Archived contribution files exist, but are only retrieved when they are accessed.
+    assert!(storage.exists(&contribution));
+    assert!(!path.as_path().exists());
+    assert!(storage.size(&contribution)? > 0);
+    assert!(path.as_path().exists());
+    assert_eq!(fs::read(&cold_path)?, fs::read(path.as_path())?);
+
+    fs::remove_dir_all(cold_storage_directory)?;
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn challenges_are_replicated() -> anyhow::Result<()> {
+    let replica_directory = "./transcript/testing_replica";
+    let _ = fs::remove_dir_all(replica_directory);
+
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let testing = Testing::from(parameters).artifact_replica(ArtifactReplicaStore {
+        region: "ap-southeast-1".to_string(),
+        url: "https://replica.example.com".to_string(),
+        directory: replica_directory.to_string(),
+    });
+    let environment = initialize_test_environment(&testing.into());
+    let number_of_chunks = environment.number_of_chunks();
+
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    assert_eq!(
+        vec![ArtifactReplica {
+            region: "ap-southeast-1".to_string(),
+            url: "https://replica.example.com".to_string(),
+        }],
+        coordinator.artifact_replicas()
+    );
+
+    let (contributor_1, contributor_1_signing_key, seed_1) = create_contributor("1");
+    let (contributor_2, _, _) = create_contributor("2");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor_1.clone(), Some(contributor_ip), 10)?;
+    coordinator.add_to_queue(contributor_2.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    // The challenges of the first contributions of the round are replicated when it starts,
+    // at the paths the coordinator serves them at.
+    let storage = test_storage(&environment);
+    let replica_path = |chunk_id: u64, contribution_id: u64| {
+        format!(
+            "{}/v1/download/challenge/{}/{}",
+            replica_directory, chunk_id, contribution_id
+        )
+    };
+    for chunk_id in 0..number_of_chunks {
+        let challenge = Locator::ContributionFile(ContributionLocator::new(1, chunk_id, 0, true));
+        assert_eq!(
+            fs::read(storage.to_path(&challenge)?.as_path())?,
+            fs::read(replica_path(chunk_id, 1))?
+        );
+    }
+
+    // A verified contribution is replicated as the challenge of the next contribution.
+    coordinator.contribute(&contributor_1, &contributor_1_signing_key, &seed_1)?;
+    verify_task_if_available(&mut coordinator, &verifier, &verifier_signing_key)?;
+    let replicated = (0..number_of_chunks)
+        .filter(|chunk_id| Path::new(&replica_path(*chunk_id, 2)).exists())
+        .collect::<Vec<_>>();
+    assert_eq!(1, replicated.len());
+    let challenge = Locator::ContributionFile(ContributionLocator::new(1, replicated[0], 1, true));
+    assert_eq!(
+        fs::read(storage.to_path(&challenge)?.as_path())?,
+        fs::read(replica_path(replicated[0], 2))?
+    );
+
+    fs::remove_dir_all(replica_directory)?;
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn archived_rounds_are_encrypted() -> anyhow::Result<()> {
+    let cold_storage_directory = "./transcript/testing_cold_encrypted";
+    let keys_directory = "./transcript/testing_keys";
+    let _ = fs::remove_dir_all(cold_storage_directory);
+    let _ = fs::remove_dir_all(keys_directory);
+    fs::create_dir_all(keys_directory)?;
+
+    let key_encryption_key = |id: &str| -> anyhow::Result<KeyEncryptionKey> {
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let path = format!("{}/{}.key", keys_directory, id);
+        fs::write(&path, hex::encode(key))?;
+        Ok(KeyEncryptionKey::File { path })
+    };
+    let first_key = key_encryption_key("first")?;
+    let second_key = key_encryption_key("second")?;
+
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let testing = Testing::from(parameters.clone())
+        .cold_storage_directory(cold_storage_directory)
+        .storage_encryption(StorageEncryption::new("first", first_key.clone()));
+    let environment = initialize_test_environment(&testing.into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+
+    let (contributor, contributor_signing_key, seed) = create_contributor("1");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+
+    for _ in 0..number_of_chunks {
+        coordinator.contribute(&contributor, &contributor_signing_key, &seed)?;
+        verify_task_if_available(&mut coordinator, &verifier, &verifier_signing_key)?;
+    }
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(2, coordinator.current_round_height()?);
+
+    // The archived contribution files are encrypted, and decrypted when they are retrieved.
+    let storage = test_storage(&environment);
+    let contribution = Locator::ContributionFile(ContributionLocator::new(1, 0, 1, false));
+    let path = storage.to_path(&contribution)?;
+    let cold_path = path
+        .to_string()
+        .replacen(environment.local_base_directory(), cold_storage_directory, 1);
+    assert!(StorageEncryption::is_encrypted_file(Path::new(&cold_path))?);
+
+    assert!(storage.size(&contribution)? > 0);
+    assert!(!StorageEncryption::is_encrypted_file(path.as_path())?);
+    let contribution_bytes = fs::read(path.as_path())?;
+    assert_ne!(fs::read(&cold_path)?, contribution_bytes);
+    fs::remove_file(path.as_path())?;
+
+    // After a key rotation, the archives are rewrapped with the new key, so the old key can be retired.
+    let rotated = StorageEncryption::new("first", first_key).rotate("second", second_key);
+    let rotated_environment: Environment = Testing::from(parameters.clone())
+        .cold_storage_directory(cold_storage_directory)
+        .storage_encryption(rotated.clone())
+        .into();
+    let rotated_storage = test_storage(&rotated_environment);
+    assert!(rotated_storage.rewrap_archives()? > 0);
+    assert_eq!(0, rotated_storage.rewrap_archives()?);
+
+    let retired_environment: Environment = Testing::from(parameters)
+        .cold_storage_directory(cold_storage_directory)
+        .storage_encryption(rotated.retire("first"))
+        .into();
+    let retired_storage = test_storage(&retired_environment);
+    assert!(retired_storage.size(&contribution)? > 0);
+    assert_eq!(contribution_bytes, fs::read(path.as_path())?);
+
+    fs::remove_dir_all(cold_storage_directory)?;
+    fs::remove_dir_all(keys_directory)?;
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn storage_encryption_requires_cold_storage() {
+    let key = KeyEncryptionKey::File {
+        path: "./transcript/testing_keys/first.key".to_string(),
+    };
+    let testing = Testing::default().storage_encryption(StorageEncryption::new("first", key));
+    let environment = initialize_test_environment(&testing.into());
+
+    // Only archives are encrypted, so without cold storage nothing would be.
+    let error = environment.storage().unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<CoordinatorError>(),
+        Some(CoordinatorError::StorageEncryptionWithoutColdStorage)
+    ));
+}
+
+#[test]
+#[serial]
+fn advance_round_is_resumable() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+    let index_path = Path::new(environment.local_base_directory()).join("latest.json");
+    let signing_key: SigningKey = "secret_key".to_string();
+
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+
+    let (contributor, contributor_signing_key, seed) = create_contributor("1");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+
+    // A round which is not verified yet is not advanced, and its index is not published.
+    coordinator.contribute(&contributor, &contributor_signing_key, &seed)?;
+    let error = coordinator
+        .advance_round(None, &signing_key, vec![], &index_path)
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<CoordinatorError>(),
+        Some(CoordinatorError::RoundNotVerified { .. })
+    ));
+    assert!(!index_path.exists());
+
+    verify_task_if_available(&mut coordinator, &verifier, &verifier_signing_key)?;
+    for _ in 1..number_of_chunks {
+        coordinator.contribute(&contributor, &contributor_signing_key, &seed)?;
+        verify_task_if_available(&mut coordinator, &verifier, &verifier_signing_key)?;
+    }
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+
+    // The manual lock holds the round before it is aggregated.
+    coordinator.enable_manual_lock()?;
+    let error = coordinator
+        .advance_round(None, &signing_key, vec![], &index_path)
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<CoordinatorError>(),
+        Some(CoordinatorError::ManualLockEnabled)
+    ));
+    coordinator.disable_manual_lock()?;
+
+    let report = coordinator.advance_round(None, &signing_key, vec![], &index_path)?;
+    assert_eq!(2, coordinator.current_round_height()?);
+    assert_eq!((1, 2), (report.from_round, report.to_round));
+    assert_eq!(5, report.steps.len());
+    assert!(report.steps.iter().all(|step| step.taken));
+    assert!(index_path.exists());
+
+    // A rerun for the round which was advanced only publishes the index again.
+    fs::remove_file(&index_path)?;
+    let rerun = coordinator.advance_round(Some(1), &signing_key, vec![], &index_path)?;
+    assert_eq!(2, coordinator.current_round_height()?);
+    let taken: Vec<AdvanceRoundStep> = rerun
+        .steps
+        .iter()
+        .filter(|step| step.taken)
+        .map(|step| step.step)
+        .collect();
+    assert_eq!(vec![AdvanceRoundStep::PublishIndex], taken);
+    assert!(index_path.exists());
+
+    // Without the round, a rerun starts on the next round, which is not verified yet.
+    let error = coordinator
+        .advance_round(None, &signing_key, vec![], &index_path)
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<CoordinatorError>(),
+        Some(CoordinatorError::RoundNotVerified { .. })
+    ));
+    assert!(
+        coordinator
+            .advance_round(Some(0), &signing_key, vec![], &index_path)
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn compute_time_is_estimated_per_hardware_class() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+
+    let time = Arc::new(MockTimeSource::new(OffsetDateTime::now_utc()));
+    let mut coordinator = Coordinator::new_with_time(environment, Arc::new(Dummy), time.clone())?;
+    coordinator.initialize()?;
+
+    let (contributor1, contributor_signing_key1, seed1) = create_contributor("1");
+    let (contributor2, _, _) = create_contributor("2");
+    let (contributor3, _, _) = create_contributor("3");
+    coordinator.add_to_queue(contributor1.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))), 10)?;
+    coordinator.add_to_queue(contributor2.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 2))), 9)?;
+    coordinator.add_to_queue(contributor3.clone(), Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 3))), 8)?;
+    coordinator.update()?;
+    assert_eq!(1, coordinator.current_round_height()?);
+
+    coordinator.report_hardware_class(&contributor1, "small")?;
+    coordinator.report_hardware_class(&contributor2, "small")?;
+
+    // Nothing was computed on the hardware class yet.
+    assert_eq!(None, coordinator.estimated_compute_time(&contributor2));
+
+    // Contribute to a chunk in five minutes.
+    let (chunk_id, locked_locators) = coordinator.try_lock(&contributor1)?;
+    let response_locator = locked_locators.next_contribution();
+    time.update(|prev| prev + time::Duration::minutes(5));
+    coordinator.run_computation(
+        response_locator.round_height(),
+        chunk_id,
+        response_locator.contribution_id(),
+        &contributor1,
+        &contributor_signing_key1,
+        &seed1,
+    )?;
+    coordinator.try_contribute(&contributor1, chunk_id)?;
+
+    // The estimate applies to every contributor on the same hardware class.
+    assert_eq!(
+        Some(time::Duration::minutes(5)),
+        coordinator.estimated_compute_time(&contributor2)
+    );
+    assert_eq!(None, coordinator.estimated_compute_time(&contributor3));
+
+    // The lease of a lock is measured from the time it was acquired, on the coordinator clock.
+    let locked_at = time.time();
+    let (chunk_id, _) = coordinator.try_lock(&contributor2)?;
+    time.update(|prev| prev + time::Duration::minutes(1));
+    let mut lock_response: LockResponse = serde_json::from_value(serde_json::json!({
+        "chunk_id": chunk_id,
+        "contribution_id": 1,
+        "locked": true,
+        "participant_id": contributor2.to_string(),
+        "previous_response_locator": "",
+        "challenge_locator": "",
+        "response_locator": "",
+        "response_chunk_id": chunk_id,
+        "response_contribution_id": 1,
+    }))?;
+    coordinator.fill_lock_lease(&contributor2, &mut lock_response);
+    let lease = coordinator.environment().participant_lock_timeout();
+    assert_eq!(Some(5 * 60), lock_response.estimated_seconds);
+    assert_eq!(
+        Some((lease - time::Duration::minutes(1)).whole_seconds() as u64),
+        lock_response.lease_seconds
+    );
+    assert_eq!(
+        Some((locked_at + lease).unix_timestamp()),
+        lock_response.lease_expires_at
+    );
+
+    // A contributor without a lock on the chunk gets no lease.
+    let mut unlocked_response = lock_response.clone();
+    coordinator.fill_lock_lease(&contributor3, &mut unlocked_response);
+    assert_eq!(None, unlocked_response.estimated_seconds);
+    assert_eq!(None, unlocked_response.lease_seconds);
+    assert_eq!(None, unlocked_response.lease_expires_at);
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn key_usage_policy_is_enforced() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let testing = Testing::from(parameters);
+    let contributor = create_contributor_test_details("1");
+
+    // Under a policy of once per round, a contributor contributes to a single chunk.
+    let environment =
+        initialize_test_environment(&testing.clone().key_usage_policy(KeyUsagePolicy::OncePerRound).into());
+    let mut coordinator = Coordinator::new(environment, Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    coordinator.add_to_queue(
+        contributor.participant.clone(),
+        Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))),
+        10,
+    )?;
+    coordinator.update()?;
+    contributor.contribute_to(&mut coordinator)?;
+    assert!(matches!(
+        coordinator.try_lock(&contributor.participant),
+        Err(CoordinatorError::KeyUsagePolicyViolated { .. })
+    ));
+    coordinator.verify_key_usage()?;
+    drop(coordinator);
+
+    // Contributing to two chunks is permitted once per chunk, but fails the check of
+    // the transcript against a policy of once per round.
+    let environment: Environment = testing.clone().into();
+    let mut coordinator = Coordinator::new(initialize_test_environment(&environment), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+    coordinator.add_to_queue(
+        contributor.participant.clone(),
+        Some(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 1))),
+        10,
+    )?;
+    coordinator.update()?;
+    contributor.contribute_to(&mut coordinator)?;
+    contributor.contribute_to(&mut coordinator)?;
+    coordinator.verify_key_usage()?;
+    drop(coordinator);
+
+    let coordinator = Coordinator::new(
+        testing.key_usage_policy(KeyUsagePolicy::OncePerRound).into(),
+        Arc::new(Dummy),
+    )?;
+    assert!(matches!(
+        coordinator.verify_key_usage(),
+        Err(CoordinatorError::KeyUsagePolicyViolated { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn compare_rounds_checks_continuity() -> anyhow::Result<()> {
+    let parameters = Parameters::Custom(Settings::new(
+        ContributionMode::Chunked,
+        ProvingSystem::Groth16,
+        CurveKind::Bls12_377,
+        7,  /* power */
+        32, /* batch_size */
+        32, /* chunk_size */
+    ));
+    let environment = initialize_test_environment(&Testing::from(parameters).into());
+    let number_of_chunks = environment.number_of_chunks() as usize;
+
+    let mut coordinator = Coordinator::new(environment.clone(), Arc::new(Dummy))?;
+    coordinator.initialize()?;
+
+    let (contributor, contributor_signing_key, seed) = create_contributor("1");
+    let contributor_ip = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    let (verifier, verifier_signing_key) = create_verifier("1");
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+    for _ in 0..number_of_chunks {
+        coordinator.contribute(&contributor, &contributor_signing_key, &seed)?;
+        verify_task_if_available(&mut coordinator, &verifier, &verifier_signing_key)?;
+    }
+    coordinator.add_to_queue(contributor.clone(), Some(contributor_ip), 10)?;
+    coordinator.update()?;
+    assert_eq!(2, coordinator.current_round_height()?);
+
+    let base = Path::new(environment.local_base_directory());
+    let round_1 = ArchivedRound::load(&base.join("round_1"))?;
+    let round_2 = ArchivedRound::load(&base.join("round_2"))?;
+
+    let comparison = compare_rounds(&round_1, &round_2)?;
+    assert!(comparison.is_continuous(), "{}", comparison);
+    assert_eq!(number_of_chunks, comparison.chunks.len());
+    assert_eq!(vec![contributor], comparison.returning_contributors);
+    assert!(comparison.departed_contributors.is_empty());
+    assert!(comparison.new_contributors.is_empty());
+
+    // Rounds are only compared with the round after them.
+    assert!(matches!(
+        compare_rounds(&round_2, &round_1),
+        Err(CoordinatorError::RoundHeightMismatch)
+    ));
+
+    // A replaced initial challenge breaks the continuity of its chunk.
+    let challenge = base.join("round_2/chunk_0/contribution_0.verified");
+    let mut bytes = fs::read(&challenge)?;
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    fs::write(&challenge, bytes)?;
+
+    let comparison = compare_rounds(&round_1, &round_2)?;
+    assert!(!comparison.is_continuous());
+    assert!(!comparison.chunks[0].is_continuous());
+    assert!(comparison.chunks[1..].iter().all(|chunk| chunk.is_continuous()));
+
+    Ok(())
+}
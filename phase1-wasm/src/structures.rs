@@ -1,4 +1,6 @@
 use crate::errors::ContributeError;
+use setup_utils::to_canonical_json;
+
 use serde::{Deserialize, Serialize};
 use serde_diff::SerdeDiff;
 
@@ -53,9 +55,10 @@ impl ContributionState {
         })
     }
 
-    /// Returns the message that should be signed for the `ContributionFileSignature`.
+    /// Returns the message that should be signed for the `ContributionFileSignature`,
+    /// which is the canonical JSON encoding of the contribution state.
     pub fn signature_message(&self) -> anyhow::Result<String> {
-        Ok(serde_json::to_string(&self)?)
+        Ok(to_canonical_json(&self)?)
     }
 }
 
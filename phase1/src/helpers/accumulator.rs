@@ -12,6 +12,8 @@ use crate::ContributionMode;
 use snarkvm_fields::{FieldParameters, PrimeField, Zero};
 #[cfg(not(feature = "wasm"))]
 use snarkvm_utilities::BitIteratorBE;
+#[cfg(not(feature = "wasm"))]
+use std::sync::Mutex;
 
 #[allow(type_alias_bounds)]
 type AccumulatorElements<E: PairingEngine> = (
@@ -48,12 +50,13 @@ cfg_if! {
 
         /// Reads a list of G1 elements from the buffer to the provided `elements` slice
         /// and then checks that their powers pairs ratio matches the one from the
-        /// provided `check` pair
+        /// provided `check` pair, or adds the check to the `batch` if one is given
         pub(crate) fn check_power_ratios<E: PairingEngine>(
             (buffer, compression, check_for_correctness): (&[u8], UseCompression, CheckForCorrectness),
             (start, end): (usize, usize),
             elements: &mut [E::G1Affine],
             check: &(E::G2Affine, E::G2Affine),
+            batch: Option<&Mutex<RatioCheckBatch<E>>>,
         ) -> Result<()> {
             let size = buffer_size::<E::G1Affine>(compression);
             buffer[start * size..end * size].read_batch_preallocated(
@@ -61,18 +64,26 @@ cfg_if! {
                 compression,
                 check_for_correctness,
             )?;
-            check_same_ratio::<E>(&power_pairs(&elements[..end - start]), check, "Power pairs")?;
+            let pairs = power_pairs(&elements[..end - start]);
+            match batch {
+                Some(batch) => batch
+                    .lock()
+                    .expect("should have locked the ratio check batch")
+                    .push(&pairs, check, "Power pairs"),
+                None => check_same_ratio::<E>(&pairs, check, "Power pairs")?,
+            }
             Ok(())
         }
 
         /// Reads a list of G2 elements from the buffer to the provided `elements` slice
         /// and then checks that their powers pairs ratio matches the one from the
-        /// provided `check` pair
+        /// provided `check` pair, or adds the check to the `batch` if one is given
         pub(crate) fn check_power_ratios_g2<E: PairingEngine>(
             (buffer, compression, check_for_correctness): (&[u8], UseCompression, CheckForCorrectness),
             (start, end): (usize, usize),
             elements: &mut [E::G2Affine],
             check: &(E::G1Affine, E::G1Affine),
+            batch: Option<&Mutex<RatioCheckBatch<E>>>,
         ) -> Result<()> {
             let size = buffer_size::<E::G2Affine>(compression);
             buffer[start * size..end * size].read_batch_preallocated(
@@ -80,7 +91,14 @@ cfg_if! {
                 compression,
                 check_for_correctness,
             )?;
-            check_same_ratio::<E>(check, &power_pairs(&elements[..end - start]), "Power pairs")?;
+            let pairs = power_pairs(&elements[..end - start]);
+            match batch {
+                Some(batch) => batch
+                    .lock()
+                    .expect("should have locked the ratio check batch")
+                    .push(check, &pairs, "Power pairs"),
+                None => check_same_ratio::<E>(check, &pairs, "Power pairs")?,
+            }
             Ok(())
         }
 
@@ -307,6 +325,64 @@ pub fn deserialize<E: PairingEngine>(
     Ok((tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2))
 }
 
+/// An element of an accumulator which fails to deserialize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidElement {
+    /// The section of the accumulator the element is in, e.g. `tau_g1`.
+    pub section: &'static str,
+    /// The index of the element in its section.
+    pub index: usize,
+}
+
+/// Returns the first `limit` elements of the accumulator in the input which fail
+/// to deserialize, in the order they appear in the input, so that a rejected
+/// contribution can be analyzed. Returns no elements if the input is too short
+/// to hold the accumulator.
+pub fn find_invalid_elements<E: PairingEngine>(
+    input: &[u8],
+    compressed: UseCompression,
+    check_input_for_correctness: CheckForCorrectness,
+    parameters: &Phase1Parameters<E>,
+    limit: usize,
+) -> Vec<InvalidElement> {
+    if input.len() < parameters.get_length(compressed) {
+        return vec![];
+    }
+
+    // Get an immutable reference to the input chunks
+    let (in_tau_g1, in_tau_g2, in_alpha_g1, in_beta_g1, in_beta_g2) = split(&input, parameters, compressed);
+
+    let invalid_g1 =
+        |section, buffer| invalid_indices::<E::G1Affine>(section, buffer, compressed, check_input_for_correctness);
+    let invalid_g2 =
+        |section, buffer| invalid_indices::<E::G2Affine>(section, buffer, compressed, check_input_for_correctness);
+    invalid_g1("tau_g1", in_tau_g1)
+        .chain(invalid_g2("tau_g2", in_tau_g2))
+        .chain(invalid_g1("alpha_g1", in_alpha_g1))
+        .chain(invalid_g1("beta_g1", in_beta_g1))
+        .chain(invalid_g2("beta_g2", in_beta_g2))
+        .take(limit)
+        .collect()
+}
+
+/// Returns the elements of a section of an accumulator which fail to deserialize.
+fn invalid_indices<'a, C: AffineCurve>(
+    section: &'static str,
+    buffer: &'a [u8],
+    compressed: UseCompression,
+    check_input_for_correctness: CheckForCorrectness,
+) -> impl Iterator<Item = InvalidElement> + 'a {
+    buffer
+        .chunks_exact(buffer_size::<C>(compressed))
+        .enumerate()
+        .filter(move |(_, element)| {
+            (&**element)
+                .read_element::<C>(compressed, check_input_for_correctness)
+                .is_err()
+        })
+        .map(move |(index, _)| InvalidElement { section, index })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -338,6 +414,66 @@ mod tests {
         assert_eq!(deserialized, elements);
     }
 
+    #[test]
+    fn test_find_invalid_elements() {
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 2, 4);
+        let compressed = UseCompression::Yes;
+
+        // Serialize the accumulator of the generators.
+        let g1 = <Bls12_377 as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let g2 = <Bls12_377 as PairingEngine>::G2Affine::prime_subgroup_generator();
+        let mut input = vec![0; parameters.get_length(compressed)];
+        {
+            let (tau_g1, tau_g2, alpha_g1, beta_g1, beta_g2) = split_mut(&mut input, &parameters, compressed);
+            tau_g1
+                .write_batch(&vec![g1; parameters.g1_chunk_size], compressed)
+                .unwrap();
+            tau_g2
+                .write_batch(&vec![g2; parameters.other_chunk_size], compressed)
+                .unwrap();
+            alpha_g1
+                .write_batch(&vec![g1; parameters.other_chunk_size], compressed)
+                .unwrap();
+            beta_g1
+                .write_batch(&vec![g1; parameters.other_chunk_size], compressed)
+                .unwrap();
+            beta_g2.write_element(&g2, compressed).unwrap();
+        }
+        assert!(find_invalid_elements(&input, compressed, CheckForCorrectness::Full, &parameters, 10).is_empty());
+
+        // Corrupt the second tau_g2 element and the first alpha_g1 element.
+        let g1_size = buffer_size::<<Bls12_377 as PairingEngine>::G1Affine>(compressed);
+        let g2_size = buffer_size::<<Bls12_377 as PairingEngine>::G2Affine>(compressed);
+        let tau_g2_start = parameters.hash_size + g1_size * parameters.g1_chunk_size;
+        let alpha_g1_start = tau_g2_start + g2_size * parameters.other_chunk_size;
+        input[tau_g2_start + g2_size..tau_g2_start + 2 * g2_size]
+            .iter_mut()
+            .for_each(|byte| *byte = 0xff);
+        input[alpha_g1_start..alpha_g1_start + g1_size]
+            .iter_mut()
+            .for_each(|byte| *byte = 0xff);
+
+        let invalid = find_invalid_elements(&input, compressed, CheckForCorrectness::Full, &parameters, 10);
+        assert_eq!(
+            vec![
+                InvalidElement {
+                    section: "tau_g2",
+                    index: 1
+                },
+                InvalidElement {
+                    section: "alpha_g1",
+                    index: 0
+                },
+            ],
+            invalid
+        );
+        assert_eq!(
+            1,
+            find_invalid_elements(&input, compressed, CheckForCorrectness::Full, &parameters, 1).len()
+        );
+        assert!(find_invalid_elements(&input[..64], compressed, CheckForCorrectness::Full, &parameters, 10).is_empty());
+    }
+
     #[test]
     fn test_decompress_buffer() {
         decompress_buffer_curve_test::<<Bls12_377 as PairingEngine>::G1Affine>();
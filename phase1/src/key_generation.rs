@@ -10,31 +10,22 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
             });
         }
 
-        // tau is a contribution to the "powers of tau", in a set of points of the form "tau^i * G"
-        let tau = E::Fr::rand(rng);
-        // alpha and beta are a set of contributions in a form "alpha * tau^i * G" and that are required
-        // for construction of the polynomials
-        let alpha = E::Fr::rand(rng);
-        let beta = E::Fr::rand(rng);
+        let (private_key, g1) = Self::sample_key(rng);
 
-        let mut op = |x: E::Fr, personalization: u8| -> Result<_> {
-            // Sample random g^s
-            let g1_s = E::G1Projective::rand(rng).into_affine();
-            // Compute g^{s*x}
-            let g1_s_x = g1_s.mul(x);
+        let op = |g1: (E::G1Affine, E::G1Affine), x: E::Fr, personalization: u8| -> Result<_> {
             // Hash into G2 as g^{s'}
-            let g2_s: E::G2Affine = compute_g2_s::<E>(&digest, &g1_s, &g1_s_x, personalization)?;
+            let g2_s: E::G2Affine = compute_g2_s::<E>(&digest, &g1.0, &g1.1, personalization)?;
             // Compute g^{s'*x}
             let g2_s_x = g2_s.mul(x);
 
-            Ok(((g1_s, g1_s_x), g2_s_x))
+            Ok((g1, g2_s_x))
         };
 
         // These "public keys" are required for the next participants to check that points are in fact
         // sequential powers
-        let pk_tau = op(tau, 0)?;
-        let pk_alpha = op(alpha, 1)?;
-        let pk_beta = op(beta, 2)?;
+        let pk_tau = op(g1[0], private_key.tau, 0)?;
+        let pk_alpha = op(g1[1], private_key.alpha, 1)?;
+        let pk_beta = op(g1[2], private_key.beta, 2)?;
 
         Ok((
             PublicKey {
@@ -45,7 +36,55 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 alpha_g2: pk_alpha.1,
                 beta_g2: pk_beta.1,
             },
-            PrivateKey { tau, alpha, beta },
+            private_key,
         ))
     }
+
+    /// Returns the hash of the points in G1 of the public key `key_generation` constructs with the
+    /// given RNG, see `PublicKey::g1_hash`. They do not depend on the transcript, so they can be
+    /// committed to before the challenge is known.
+    pub fn public_key_g1_hash<R: Rng + CryptoRng>(rng: &mut R) -> Result<Vec<u8>> {
+        let (_, g1) = Self::sample_key(rng);
+        hash_g1_points::<E>(&g1)
+    }
+
+    /// Samples the private key, and the pairs (g^s, g^{s*x}) in G1 for x in τ, α and β.
+    fn sample_key<R: Rng + CryptoRng>(rng: &mut R) -> (PrivateKey<E>, [(E::G1Affine, E::G1Affine); 3]) {
+        // tau is a contribution to the "powers of tau", in a set of points of the form "tau^i * G"
+        let tau = E::Fr::rand(rng);
+        // alpha and beta are a set of contributions in a form "alpha * tau^i * G" and that are required
+        // for construction of the polynomials
+        let alpha = E::Fr::rand(rng);
+        let beta = E::Fr::rand(rng);
+
+        let mut g1 = |x: E::Fr| {
+            // Sample random g^s
+            let g1_s = E::G1Projective::rand(rng).into_affine();
+            // Compute g^{s*x}
+            let g1_s_x = g1_s.mul(x);
+            (g1_s, g1_s_x)
+        };
+        let points = [g1(tau), g1(alpha), g1(beta)];
+
+        (PrivateKey { tau, alpha, beta }, points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snarkvm_curves::bls12_377::Bls12_377;
+
+    #[test]
+    fn test_public_key_g1_hash_ignores_the_digest() {
+        let g1_hash = Phase1::<Bls12_377>::public_key_g1_hash(&mut derive_rng_from_seed(b"seed")).unwrap();
+        for digest in &[[0u8; 64], [1u8; 64]] {
+            let (public_key, _) =
+                Phase1::<Bls12_377>::key_generation(&mut derive_rng_from_seed(b"seed"), digest).unwrap();
+            assert_eq!(public_key.g1_hash().unwrap(), g1_hash);
+        }
+        let other = Phase1::<Bls12_377>::public_key_g1_hash(&mut derive_rng_from_seed(b"other seed")).unwrap();
+        assert_ne!(other, g1_hash);
+    }
 }
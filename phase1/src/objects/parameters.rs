@@ -18,6 +18,21 @@ pub enum ProvingSystem {
     Marlin,
 }
 
+/// How thoroughly the elements of a contribution are checked during verification.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum VerificationMode {
+    /// Checks that every element is non-zero and in the prime order subgroup.
+    Full,
+    /// Checks the ratio proofs, but only a random sample of `samples` elements per group.
+    Sampled { samples: usize },
+}
+
+impl Default for VerificationMode {
+    fn default() -> Self {
+        VerificationMode::Full
+    }
+}
+
 /// The sizes of the group elements of a curve
 #[derive(Clone, PartialEq, Eq, Default, Debug)]
 pub struct CurveParameters<E> {
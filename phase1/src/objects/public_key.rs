@@ -1,5 +1,5 @@
 use crate::Phase1Parameters;
-use setup_utils::{Error, UseCompression};
+use setup_utils::{calculate_hash, Error, UseCompression};
 
 use snarkvm_curves::PairingEngine;
 use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
@@ -68,4 +68,20 @@ impl<E: PairingEngine> PublicKey<E> {
         // The public key is written after the provided position
         Ok(PublicKey::deserialize(&mut &input_map[position..])?)
     }
+
+    /// Hashes the points of the key in G1, which only depend on the randomness of its creator
+    /// and not on the transcript, so that they can be committed to before the challenge is known.
+    pub fn g1_hash(&self) -> Result<Vec<u8>, Error> {
+        hash_g1_points::<E>(&[self.tau_g1, self.alpha_g1, self.beta_g1])
+    }
+}
+
+/// Hashes the serialized pairs of points in G1 for τ, α and β with `calculate_hash`.
+pub(crate) fn hash_g1_points<E: PairingEngine>(points: &[(E::G1Affine, E::G1Affine); 3]) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![];
+    for (g1_s, g1_s_x) in points {
+        g1_s.serialize(&mut bytes)?;
+        g1_s_x.serialize(&mut bytes)?;
+    }
+    Ok(calculate_hash(&bytes).to_vec())
 }
@@ -1,5 +1,7 @@
 use super::*;
 
+use std::sync::Mutex;
+
 impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
     /// Verifies that the accumulator was transformed correctly
     /// given the `PublicKey` and the so-far hash of the accumulator.
@@ -20,7 +22,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
     /// that they're in the prime order subgroup. In the first chunk, it also checks
     /// the proofs of knowledge and that the elements were correctly multiplied.
     ///
-    #[allow(clippy::too_many_arguments, clippy::cognitive_complexity)]
+    #[allow(clippy::too_many_arguments)]
     pub fn verification(
         input: &[u8],
         output: &[u8],
@@ -32,7 +34,80 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
         check_output_for_correctness: CheckForCorrectness,
         parameters: &'a Phase1Parameters<E>,
     ) -> Result<()> {
-        let span = info_span!("phase1-verification");
+        Self::verification_with_mode(
+            input,
+            output,
+            key,
+            digest,
+            compressed_input,
+            compressed_output,
+            check_input_for_correctness,
+            check_output_for_correctness,
+            VerificationMode::Full,
+            parameters,
+        )
+    }
+
+    ///
+    /// Phase 1 - Verification with a given `VerificationMode`
+    ///
+    /// Behaves as `verification` in `VerificationMode::Full`. In `VerificationMode::Sampled`,
+    /// the proofs of knowledge and the ratios of the initial elements are checked as usual,
+    /// but only a random sample of the remaining elements are checked to be non-zero and
+    /// in the prime order subgroup. This is intended for a fast preliminary acceptance of
+    /// a contribution, which must be followed by a full verification.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn verification_with_mode(
+        input: &[u8],
+        output: &[u8],
+        key: &PublicKey<E>,
+        digest: &[u8],
+        compressed_input: UseCompression,
+        compressed_output: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        check_output_for_correctness: CheckForCorrectness,
+        mode: VerificationMode,
+        parameters: &'a Phase1Parameters<E>,
+    ) -> Result<()> {
+        Self::verification_with_batch(
+            input,
+            output,
+            key,
+            digest,
+            compressed_input,
+            compressed_output,
+            check_input_for_correctness,
+            check_output_for_correctness,
+            mode,
+            None,
+            parameters,
+        )
+    }
+
+    ///
+    /// Phase 1 - Verification with batched ratio checks
+    ///
+    /// Behaves as `verification_with_mode`, except that if a `RatioCheckBatch` is given,
+    /// the pairing based ratio checks are added to it instead of being checked. This allows
+    /// the ratio checks of many chunks to be batched into a single multi-pairing, and the
+    /// contribution is only valid once the caller has successfully verified the batch.
+    ///
+    #[allow(clippy::too_many_arguments, clippy::cognitive_complexity)]
+    pub fn verification_with_batch(
+        input: &[u8],
+        output: &[u8],
+        key: &PublicKey<E>,
+        digest: &[u8],
+        compressed_input: UseCompression,
+        compressed_output: UseCompression,
+        check_input_for_correctness: CheckForCorrectness,
+        check_output_for_correctness: CheckForCorrectness,
+        mode: VerificationMode,
+        mut batch: Option<&mut RatioCheckBatch<E>>,
+        parameters: &'a Phase1Parameters<E>,
+    ) -> Result<()> {
+        let span = info_span!("phase1-verification", ?mode, batched = batch.is_some());
         let _ = span.enter();
 
         info!("starting...");
@@ -48,6 +123,18 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
 
             let [tau_g2_s, alpha_g2_s, beta_g2_s] = compute_g2_s_key(&key, &digest)?;
 
+            // Check the ratio immediately, or add it to the batch if one is given.
+            let mut check_ratio =
+                |g1: &(E::G1Affine, E::G1Affine), g2: &(E::G2Affine, E::G2Affine), err: &'static str| -> Result<()> {
+                    match batch.as_mut() {
+                        Some(batch) => {
+                            batch.push(g1, g2, err);
+                            Ok(())
+                        }
+                        None => Ok(check_same_ratio::<E>(g1, g2, err)?),
+                    }
+                };
+
             // Compose into tuple form for convenience.
             let tau_single_g1_check = &(key.tau_g1.0, key.tau_g1.1);
             let tau_single_g2_check = &(tau_g2_s, key.tau_g2);
@@ -74,7 +161,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 ];
 
                 for (a, b, err) in check_ratios {
-                    check_same_ratio::<E>(a, b, err)?;
+                    check_ratio(*a, *b, *err)?;
                 }
                 debug!("key ratios were correctly produced");
             }
@@ -98,7 +185,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 }
 
                 // Check that tau^1 was multiplied correctly.
-                check_same_ratio::<E>(
+                check_ratio(
                     &(before_g1[1], after_g1[1]),
                     tau_single_g2_check,
                     "Before-After: tau_g1",
@@ -122,7 +209,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 }
 
                 // Check that tau^1 was multiplied correctly.
-                check_same_ratio::<E>(
+                check_ratio(
                     tau_single_g1_check,
                     &(before_g2[1], after_g2[1]),
                     "Before-After: tau_g2",
@@ -144,9 +231,9 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                 for (before, after, check) in &checks {
                     before.read_batch_preallocated(&mut before_g1, compressed_input, check_input_for_correctness)?;
                     after.read_batch_preallocated(&mut after_g1, compressed_output, check_output_for_correctness)?;
-                    check_same_ratio::<E>(
+                    check_ratio(
                         &(before_g1[0], after_g1[0]),
-                        check,
+                        *check,
                         "Before-After: alpha_g1[0] / beta_g1[0]",
                     )?;
                 }
@@ -162,7 +249,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                         (&*beta_g2).read_element::<E::G2Affine>(compressed_output, check_output_for_correctness)?;
 
                     // Check that beta_g2[0] was multiplied correctly.
-                    check_same_ratio::<E>(
+                    check_ratio(
                         beta_single_g1_check,
                         &(before_beta_g2, after_beta_g2),
                         "Before-After: beta_g2[0]",
@@ -173,6 +260,18 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
 
         debug!("initial elements were computed correctly");
 
+        if let VerificationMode::Sampled { samples } = mode {
+            let rng = &mut rand::thread_rng();
+            check_sampled_elements::<E::G1Affine, _>((tau_g1, compressed_output), samples, rng)?;
+            check_sampled_elements::<E::G2Affine, _>((tau_g2, compressed_output), samples, rng)?;
+            check_sampled_elements::<E::G1Affine, _>((alpha_g1, compressed_output), samples, rng)?;
+            check_sampled_elements::<E::G1Affine, _>((beta_g1, compressed_output), samples, rng)?;
+
+            info!("phase1-verification of {} sampled elements per group complete", samples);
+
+            return Ok(());
+        }
+
         iter_chunk(&parameters, |start, end| {
             // Preallocate 2 vectors per batch.
             // Ensure that the pairs are created correctly (we do this in chunks!).
@@ -365,11 +464,47 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
         (output, compressed_output, check_output_for_correctness): (&[u8], UseCompression, CheckForCorrectness),
         parameters: &Phase1Parameters<E>,
     ) -> Result<()> {
-        let span = info_span!("phase1-aggregate-verification");
+        Self::aggregate_verification_with_batch(
+            (output, compressed_output, check_output_for_correctness),
+            None,
+            parameters,
+        )
+    }
+
+    ///
+    /// Behaves as `aggregate_verification`, except that if a `RatioCheckBatch` is given,
+    /// the ratio checks of each batch of elements are added to it instead of being checked,
+    /// and the accumulator is only valid once the caller has successfully verified the batch.
+    ///
+    pub fn aggregate_verification_with_batch(
+        (output, compressed_output, check_output_for_correctness): (&[u8], UseCompression, CheckForCorrectness),
+        batch: Option<&mut RatioCheckBatch<E>>,
+        parameters: &Phase1Parameters<E>,
+    ) -> Result<()> {
+        let span = info_span!("phase1-aggregate-verification", batched = batch.is_some());
         let _enter = span.enter();
 
         info!("starting...");
 
+        // The ratio checks of the batches, which are collected from the parallel tasks if a batch is given.
+        let pending_batch = batch.as_ref().map(|_| Mutex::new(RatioCheckBatch::<E>::new()));
+        let pending = pending_batch.as_ref();
+
+        // Check the ratio immediately, or add it to the batch if one is given.
+        let check_ratio =
+            |g1: &(E::G1Affine, E::G1Affine), g2: &(E::G2Affine, E::G2Affine), err: &'static str| -> Result<()> {
+                match pending {
+                    Some(pending) => {
+                        pending
+                            .lock()
+                            .expect("should have locked the ratio check batch")
+                            .push(g1, g2, err);
+                        Ok(())
+                    }
+                    None => Ok(check_same_ratio::<E>(g1, g2, err)?),
+                }
+            };
+
         let (tau_g1, tau_g2, alpha_g1, beta_g1, _) = split(output, parameters, compressed_output);
 
         let (g1_check, g2_check, g1_alpha_check) = {
@@ -422,6 +557,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                 (start, end),
                                 &mut g1,
                                 &g2_check,
+                                pending,
                             )
                             .expect("could not check ratios for tau_g1 elements");
 
@@ -451,6 +587,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                         (start, end),
                                         &mut g2,
                                         &g1_check,
+                                        pending,
                                     )
                                     .expect("could not check ratios for tau_g2 elements");
 
@@ -467,6 +604,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                         (start, end),
                                         &mut g1,
                                         &g2_check,
+                                        pending,
                                     )
                                     .expect("could not check ratios for alpha_g1 elements");
 
@@ -483,6 +621,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                         (start, end),
                                         &mut g1,
                                         &g2_check,
+                                        pending,
                                     )
                                     .expect("could not check ratios for beta_g1 elements");
 
@@ -517,6 +656,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                 (start, end),
                                 &mut g1,
                                 &g2_check,
+                                pending,
                             )
                             .expect("could not check ratios for tau_g1 elements");
 
@@ -540,7 +680,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                 let g2 = (&tau_g2[(2 + i) * g2_size..(2 + i + 1) * g2_size])
                                     .read_element(compressed_output, check_output_for_correctness)
                                     .expect("should have read g2 element");
-                                check_same_ratio::<E>(
+                                check_ratio(
                                     &(g1, E::G1Affine::prime_subgroup_generator()),
                                     &(E::G2Affine::prime_subgroup_generator(), g2),
                                     "G1<>G2",
@@ -555,19 +695,19 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                                         check_output_for_correctness,
                                     )
                                     .expect("should have read alpha g1 elements");
-                                check_same_ratio::<E>(
+                                check_ratio(
                                     &(alpha_g1_elements[0], alpha_g1_elements[1]),
                                     &g2_check,
                                     "alpha_g1 ratio 1",
                                 )
                                 .expect("should have checked same ratio");
-                                check_same_ratio::<E>(
+                                check_ratio(
                                     &(alpha_g1_elements[1], alpha_g1_elements[2]),
                                     &g2_check,
                                     "alpha_g1 ratio 2",
                                 )
                                 .expect("should have checked same ratio");
-                                check_same_ratio::<E>(
+                                check_ratio(
                                     &(alpha_g1_elements[0], g1_alpha_check.0),
                                     &(E::G2Affine::prime_subgroup_generator(), g2),
                                     "alpha consistent",
@@ -587,6 +727,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                             (0, num_alpha_powers),
                             &mut g1,
                             &g2_check,
+                            pending,
                         )
                         .expect("could not check ratios for alpha_g1");
 
@@ -599,6 +740,7 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
                             (0, 2),
                             &mut g2,
                             &g1_check,
+                            pending,
                         )
                         .expect("could not check ratios for tau_g2");
 
@@ -612,11 +754,44 @@ impl<'a, E: PairingEngine + Sync> Phase1<'a, E> {
             }
         }
 
+        // Hand the ratio checks of the batches to the caller.
+        if let (Some(batch), Some(pending)) = (batch, pending_batch) {
+            let mut pending = pending
+                .into_inner()
+                .expect("should have unlocked the ratio check batch");
+            batch.append(&mut pending);
+        }
+
         info!("aggregate verification complete");
         Ok(())
     }
 }
 
+/// Checks that `samples` randomly chosen elements of the buffer are non-zero
+/// and in the prime order subgroup. Elements may be sampled more than once.
+fn check_sampled_elements<C: AffineCurve, R: Rng>(
+    (buffer, compressed): (&[u8], UseCompression),
+    samples: usize,
+    rng: &mut R,
+) -> Result<()> {
+    let length = buffer.len() / buffer_size::<C>(compressed);
+    if length == 0 {
+        return Ok(());
+    }
+
+    let mut element = vec![C::zero(); 1];
+    for _ in 0..samples {
+        let index = rng.gen_range(0..length);
+        check_elements_are_nonzero_and_in_prime_order_subgroup::<C>(
+            (buffer, compressed),
+            (index, index + 1),
+            &mut element,
+        )?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -930,4 +1105,167 @@ mod tests {
         chunk_verification_test::<Bls12_377>(4, 3 + 3 * 4, UseCompression::No, UseCompression::No);
         chunk_verification_test::<Bls12_377>(4, 3 + 3 * 4, UseCompression::Yes, UseCompression::No);
     }
+
+    #[test]
+    fn test_batched_verification_bls12_377() {
+        let compressed = UseCompression::No;
+        let correctness = CheckForCorrectness::Full;
+        let parameters = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 4, 3 + 3 * 4);
+        let digest = blank_hash();
+        let (input, _) = generate_input(&parameters, compressed, correctness);
+
+        // Compute independent contributions on the same challenge.
+        let contributions: Vec<_> = [b"test_batched_verification 1", b"test_batched_verification 2"]
+            .iter()
+            .map(|seed| {
+                let mut rng = derive_rng_from_seed(*seed);
+                let (public_key, private_key) =
+                    Phase1::key_generation(&mut rng, digest.as_ref()).expect("could not generate keypair");
+                let mut output = generate_output(&parameters, compressed);
+                Phase1::computation(
+                    &input,
+                    &mut output,
+                    compressed,
+                    compressed,
+                    correctness,
+                    &private_key,
+                    &parameters,
+                )
+                .unwrap();
+                (public_key, output)
+            })
+            .collect();
+
+        let verify = |batch: &mut RatioCheckBatch<Bls12_377>,
+                      (public_key, output): &(PublicKey<Bls12_377>, Vec<u8>),
+                      digest: &[u8]| {
+            Phase1::verification_with_batch(
+                &input,
+                output,
+                public_key,
+                digest,
+                compressed,
+                compressed,
+                correctness,
+                correctness,
+                VerificationMode::Full,
+                Some(batch),
+                &parameters,
+            )
+        };
+
+        let rng = &mut rand::thread_rng();
+        let mut batch = RatioCheckBatch::new();
+        for contribution in &contributions {
+            assert!(verify(&mut batch, contribution, &digest).is_ok());
+        }
+        assert!(!batch.is_empty());
+        assert!(batch.verify(rng).is_ok());
+
+        // The ratio checks with a wrong digest are only rejected once the batch is verified.
+        assert!(verify(&mut batch, &contributions[0], &calculate_hash(&input)).is_ok());
+        assert!(batch.verify(rng).is_err());
+    }
+
+    #[test]
+    fn test_batched_aggregate_verification_bls12_377() {
+        let compressed = UseCompression::No;
+        let correctness = CheckForCorrectness::Full;
+
+        for proving_system in &[ProvingSystem::Groth16, ProvingSystem::Marlin] {
+            let parameters = Phase1Parameters::<Bls12_377>::new_full(*proving_system, 4, 3 + 3 * 4);
+            let (input, _) = generate_input(&parameters, compressed, correctness);
+
+            let mut rng = derive_rng_from_seed(b"test_batched_aggregate_verification");
+            let (_, private_key) =
+                Phase1::key_generation(&mut rng, blank_hash().as_ref()).expect("could not generate keypair");
+            let mut output = generate_output(&parameters, compressed);
+            Phase1::computation(
+                &input,
+                &mut output,
+                compressed,
+                compressed,
+                correctness,
+                &private_key,
+                &parameters,
+            )
+            .unwrap();
+
+            let verify = |output: &[u8], batch: &mut RatioCheckBatch<Bls12_377>| {
+                Phase1::aggregate_verification_with_batch((output, compressed, correctness), Some(batch), &parameters)
+            };
+
+            let rng = &mut rand::thread_rng();
+            let mut batch = RatioCheckBatch::new();
+            assert!(verify(&output, &mut batch).is_ok());
+            assert!(!batch.is_empty());
+            assert!(batch.verify(rng).is_ok());
+
+            // Replace tau^2 with tau in G1, which is only rejected once the batch is verified.
+            {
+                let (tau_g1, _, _, _, _) = split_mut(&mut output, &parameters, compressed);
+                let size = buffer_size::<<Bls12_377 as PairingEngine>::G1Affine>(compressed);
+                tau_g1.copy_within(size..2 * size, 2 * size);
+            }
+            let mut batch = RatioCheckBatch::new();
+            assert!(verify(&output, &mut batch).is_ok());
+            assert!(batch.verify(rng).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sampled_verification_bls12_377() {
+        let compressed = UseCompression::No;
+        let correctness = CheckForCorrectness::Full;
+        let mode = VerificationMode::Sampled { samples: 4 };
+
+        for proving_system in &[ProvingSystem::Groth16, ProvingSystem::Marlin] {
+            let parameters = Phase1Parameters::<Bls12_377>::new_chunk(
+                ContributionMode::Chunked,
+                0,
+                3 + 3 * 4,
+                *proving_system,
+                4,
+                3 + 3 * 4,
+            );
+
+            let digest = blank_hash();
+            let mut rng = derive_rng_from_seed(b"test_sampled_verification");
+            let (public_key, private_key) =
+                Phase1::key_generation(&mut rng, digest.as_ref()).expect("could not generate keypair");
+
+            let (input, _) = generate_input(&parameters, compressed, correctness);
+            let mut output = generate_output(&parameters, compressed);
+            Phase1::computation(
+                &input,
+                &mut output,
+                compressed,
+                compressed,
+                correctness,
+                &private_key,
+                &parameters,
+            )
+            .unwrap();
+            drop(private_key);
+
+            let verify = |digest: &[u8]| {
+                Phase1::verification_with_mode(
+                    &input,
+                    &output,
+                    &public_key,
+                    digest,
+                    compressed,
+                    compressed,
+                    correctness,
+                    correctness,
+                    mode,
+                    &parameters,
+                )
+            };
+
+            assert!(verify(&digest).is_ok());
+            // The ratio proofs are still checked in sampled mode.
+            assert!(verify(&calculate_hash(&output)).is_err());
+        }
+    }
 }
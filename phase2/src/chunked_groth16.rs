@@ -4,10 +4,23 @@
 //! utilities for operating directly on raw items which implement `Read`, `Write` and `Seek`
 //! such that contributing and verifying the MPC can be done in chunks which fit in memory.
 use crate::{
+    helpers::batch_mul,
     keypair::{Keypair, PublicKey},
     parameters::*,
 };
-use setup_utils::{batch_mul, check_same_ratio, merge_pairs, InvariantKind, Phase2Error, Result};
+use setup_utils::{
+    check_same_ratio,
+    merge_pairs,
+    BatchDeserializer,
+    BatchSerializer,
+    CancellationToken,
+    CheckForCorrectness,
+    Deserializer,
+    InvariantKind,
+    Phase2Error,
+    Result,
+    UseCompression,
+};
 use snarkvm_algorithms::snark::groth16::VerifyingKey;
 use snarkvm_curves::{AffineCurve, PairingEngine};
 use snarkvm_fields::Field;
@@ -16,16 +29,45 @@ use snarkvm_utilities::{CanonicalDeserialize, CanonicalSerialize, ConstantSerial
 use byteorder::{BigEndian, WriteBytesExt};
 use rand::{CryptoRng, Rng};
 use std::{
-    io::{Read, Seek, SeekFrom, Write},
-    ops::{Mul, Neg},
+    io::{Read, Seek, SeekFrom},
+    ops::{DerefMut, Mul},
 };
 use tracing::{debug, info, info_span, trace};
 
 /// Given two serialized contributions to the ceremony, this will check that `after`
 /// has been correctly calculated from `before`. Large vectors will be read in
 /// `batch_size` batches
-#[allow(clippy::cognitive_complexity)]
 pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size: usize) -> Result<Vec<[u8; 64]>> {
+    verify_cancellable::<E>(before, after, batch_size, CheckForCorrectness::No, &CancellationToken::new())
+}
+
+/// Same as `verify`, but checks the elements of the queries as they are read with
+/// `check`, and aborts with `Error::Cancelled` or `Error::DeadlineExceeded` between
+/// batches once `cancellation` fires. Neither buffer is modified.
+///
+/// The `a` and `b` queries may contain the point at infinity, so they are never
+/// checked to be non-zero.
+pub fn verify_cancellable<E: PairingEngine>(
+    before: &mut [u8],
+    after: &mut [u8],
+    batch_size: usize,
+    check: CheckForCorrectness,
+    cancellation: &CancellationToken,
+) -> Result<Vec<[u8; 64]>> {
+    verify_with_progress::<E>(before, after, batch_size, check, cancellation, &|_| ())
+}
+
+/// Same as `verify_cancellable`, but calls `progress` with the number of elements of
+/// the `h` and `l` queries checked by every batch, from the threads checking them.
+#[allow(clippy::cognitive_complexity)]
+pub fn verify_with_progress<E: PairingEngine>(
+    before: &mut [u8],
+    after: &mut [u8],
+    batch_size: usize,
+    check: CheckForCorrectness,
+    cancellation: &CancellationToken,
+    progress: &(dyn Fn(usize) + Sync),
+) -> Result<Vec<[u8; 64]>> {
     let span = info_span!("phase2-verify");
     let _enter = span.enter();
     info!("starting...");
@@ -55,6 +97,14 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
         &InvariantKind::GammaAbcG1,
     )?;
 
+    // The elements above pass the checks if they were degenerate before already
+    ensure_no_identity(&[vk_after.alpha_g1], 0, &InvariantKind::AlphaG1)?;
+    ensure_no_identity(&[beta_g1_after], 0, &InvariantKind::BetaG1)?;
+    ensure_no_identity(&[vk_after.beta_g2], 0, &InvariantKind::BetaG2)?;
+    ensure_no_identity(&[vk_after.gamma_g2], 0, &InvariantKind::GammaG2)?;
+    ensure_no_identity(&[delta_g1_after], 0, &InvariantKind::DeltaG1)?;
+    ensure_no_identity(&[vk_after.delta_g2], 0, &InvariantKind::DeltaG2)?;
+
     debug!("initial elements unchanged");
 
     // Split the before-after buffers in non-overlapping slices and spawn a thread for each group
@@ -88,7 +138,9 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
                 before_alpha_g1,
                 after_alpha_g1,
                 batch_size,
+                allow_zero(check),
                 &InvariantKind::AlphaG1Query,
+                cancellation,
             )
         }));
         threads.push(s.spawn(|_| {
@@ -99,7 +151,9 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
                 before_beta_g1,
                 after_beta_g1,
                 batch_size,
+                allow_zero(check),
                 &InvariantKind::BetaG1Query,
+                cancellation,
             )
         }));
         threads.push(s.spawn(|_| {
@@ -110,7 +164,9 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
                 before_beta_g2,
                 after_beta_g2,
                 batch_size,
+                allow_zero(check),
                 &InvariantKind::BetaG2Query,
+                cancellation,
             )
         }));
 
@@ -125,7 +181,11 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
                 after_h,
                 vk_after.delta_g2,
                 batch_size,
+                check,
+                &InvariantKind::HQuery,
                 "H_query ratio check failed",
+                cancellation,
+                progress,
             )
         }));
         threads.push(s.spawn(|_| {
@@ -138,7 +198,11 @@ pub fn verify<E: PairingEngine>(before: &mut [u8], after: &mut [u8], batch_size:
                 after_l,
                 vk_after.delta_g2,
                 batch_size,
+                check,
+                &InvariantKind::LQuery,
                 "L_query ratio check failed",
+                cancellation,
+                progress,
             )
         }));
 
@@ -209,30 +273,151 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
     rng: &mut R,
     batch_size: usize,
 ) -> Result<[u8; 64]> {
+    contribute_cancellable::<E, R>(buffer, rng, batch_size, &CancellationToken::new())
+}
+
+/// Same as `contribute`, but aborts with `Error::Cancelled` or `Error::DeadlineExceeded`
+/// between batches once `cancellation` fires.
+///
+/// If the operation is cancelled before the buffer is first written to, the buffer is
+/// left untouched. Otherwise the buffer holds a partially transformed contribution which
+/// MUST be discarded, since it no longer matches the previous contribution's transcript.
+pub fn contribute_cancellable<E: PairingEngine, R: Rng + CryptoRng>(
+    buffer: &mut [u8],
+    rng: &mut R,
+    batch_size: usize,
+    cancellation: &CancellationToken,
+) -> Result<[u8; 64]> {
+    let mut buffer = buffer;
+    contribute_resumable::<E, R, _, _>(
+        &mut buffer,
+        rng,
+        batch_size,
+        CheckForCorrectness::No,
+        cancellation,
+        None,
+        |_, _| Ok(()),
+    )
+}
+
+/// How far a contribution to serialized parameters got, which is all that is needed
+/// besides its randomness to resume it where it stopped. It keeps the parts of the
+/// parameters the contribution changes last as they were before it, so that they
+/// are computed from the same values however often the contribution is resumed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContributionCheckpoint {
+    /// The serialized `delta_g1` of the parameters before the contribution.
+    pub delta_g1: Vec<u8>,
+    /// The serialized `delta_g2` of the verifying key before the contribution.
+    pub delta_g2: Vec<u8>,
+    /// The number of contributions before this one.
+    pub contributions: usize,
+    /// The number of elements of the `h` query which were multiplied.
+    pub h_done: usize,
+    /// The number of elements of the `l` query which were multiplied.
+    pub l_done: usize,
+    /// The serialized elements of the batch of the `h` and `l` queries which is
+    /// multiplied next, as they were before it, so that a batch which was
+    /// interrupted halfway is undone when resuming.
+    pub pending: Vec<u8>,
+}
+
+/// Same as `contribute_cancellable`, but calls `checkpoint` with the progress of the
+/// contribution before every batch is multiplied and before the contribution is
+/// appended, so that an interrupted contribution can be resumed from its last
+/// checkpoint. `checkpoint` must make the buffer durable before it records the
+/// checkpoint.
+///
+/// A contribution is resumed by passing the checkpoint as `resume`, with the buffer it
+/// was taken of and an `rng` which produces the same randomness as the first time. The
+/// `h` and `l` queries are multiplied first, and the new delta and the public key are
+/// only written at the end, from the values in the checkpoint, so a contribution which
+/// was interrupted while writing them is completed by resuming it as well.
+///
+/// The elements of the `h` and `l` queries are checked with `check` as they are read,
+/// so a contribution to a corrupted buffer fails before its first batch is written.
+#[allow(clippy::cognitive_complexity)]
+pub fn contribute_resumable<E, R, B, F>(
+    buffer: &mut B,
+    rng: &mut R,
+    batch_size: usize,
+    check: CheckForCorrectness,
+    cancellation: &CancellationToken,
+    resume: Option<ContributionCheckpoint>,
+    mut checkpoint: F,
+) -> Result<[u8; 64]>
+where
+    E: PairingEngine,
+    R: Rng + CryptoRng,
+    B: DerefMut<Target = [u8]>,
+    F: FnMut(&B, &ContributionCheckpoint) -> Result<()>,
+{
     let span = info_span!("phase2-contribute");
     let _enter = span.enter();
 
     info!("starting...");
 
-    let buffer = &mut std::io::Cursor::new(buffer);
+    let reader = &mut std::io::Cursor::new(&buffer[..]);
     // The VK is small so we read it directly from the start
-    let mut vk = VerifyingKey::<E>::deserialize(buffer)?;
+    let mut vk = VerifyingKey::<E>::deserialize(reader)?;
     // leave beta_g1 unchanged
-    buffer.seek(SeekFrom::Current(E::G1Affine::SERIALIZED_SIZE as i64))?;
+    reader.seek(SeekFrom::Current(E::G1Affine::SERIALIZED_SIZE as i64))?;
     // read delta_g1
-    let mut delta_g1 = E::G1Affine::deserialize(buffer)?;
-
-    // Skip the vector elements for now so that we can read the contributions
-    skip_vec::<E::G1Affine, _>(buffer)?; // Alpha G1
-    skip_vec::<E::G1Affine, _>(buffer)?; // Beta G1
-    skip_vec::<E::G2Affine, _>(buffer)?; // Beta G2
-    skip_vec::<E::G1Affine, _>(buffer)?; // H
-    skip_vec::<E::G1Affine, _>(buffer)?; // L
+    let delta_g1_position = reader.position();
+    let mut delta_g1 = E::G1Affine::deserialize(reader)?;
+
+    // Skip the vector elements for now so that we can read the contributions,
+    // remembering where the `h` and `l` queries start
+    skip_vec::<E::G1Affine, _>(reader)?; // Alpha G1
+    skip_vec::<E::G1Affine, _>(reader)?; // Beta G1
+    skip_vec::<E::G2Affine, _>(reader)?; // Beta G2
+    let h_query_len = u64::deserialize(reader)? as usize;
+    let h_position = reader.position() as usize;
+    reader.seek(SeekFrom::Current((h_query_len * E::G1Affine::SERIALIZED_SIZE) as i64))?;
+    let l_query_len = u64::deserialize(reader)? as usize;
+    let l_position = reader.position() as usize;
+    reader.seek(SeekFrom::Current((l_query_len * E::G1Affine::SERIALIZED_SIZE) as i64))?;
 
     // Read the transcript hash and the contributions
     let mut cs_hash = [0u8; 64];
-    buffer.read_exact(&mut cs_hash)?;
-    let contributions = PublicKey::<E>::read_batch(buffer)?;
+    reader.read_exact(&mut cs_hash)?;
+    let contributions_position = reader.position();
+    let mut contributions = PublicKey::<E>::read_batch(reader)?;
+
+    // A resumed contribution starts from the parameters as they were before it,
+    // which no longer are in the buffer if it was interrupted at the very end
+    let mut progress = match resume {
+        Some(progress) => {
+            if progress.contributions > contributions.len()
+                || progress.h_done > h_query_len
+                || progress.l_done > l_query_len
+            {
+                return Err(Phase2Error::ContributionCheckpointMismatch.into());
+            }
+            delta_g1 = E::G1Affine::deserialize(&mut &progress.delta_g1[..])?;
+            vk.delta_g2 = E::G2Affine::deserialize(&mut &progress.delta_g2[..])?;
+            contributions.truncate(progress.contributions);
+            info!(
+                "resuming after {} of {} elements",
+                progress.h_done + progress.l_done,
+                h_query_len + l_query_len
+            );
+            progress
+        }
+        None => {
+            let (mut delta_g1_bytes, mut delta_g2_bytes) = (vec![], vec![]);
+            delta_g1.serialize(&mut delta_g1_bytes)?;
+            vk.delta_g2.serialize(&mut delta_g2_bytes)?;
+            ContributionCheckpoint {
+                delta_g1: delta_g1_bytes,
+                delta_g2: delta_g2_bytes,
+                contributions: contributions.len(),
+                h_done: 0,
+                l_done: 0,
+                pending: vec![],
+            }
+        }
+    };
 
     // Create the keypair
     let Keypair {
@@ -244,167 +429,161 @@ pub fn contribute<E: PairingEngine, R: Rng + CryptoRng>(
     let delta = private_key.delta;
     let delta_inv = private_key.delta.inverse().expect("nonzero");
 
-    // update the values
-    delta_g1 = delta_g1.mul(delta);
-    vk.delta_g2 = vk.delta_g2.mul(delta);
-
-    // go back to the start of the buffer to write the updated vk and delta_g1
-    buffer.seek(SeekFrom::Start(0))?;
-    // write the vk
-    vk.serialize(buffer)?;
-    // leave beta_g1 unchanged
-    buffer.seek(SeekFrom::Current(E::G1Affine::SERIALIZED_SIZE as i64))?;
-    // write delta_g1
-    delta_g1.serialize(buffer)?;
-
-    debug!("updated delta g1 and vk delta g2");
-
-    skip_vec::<E::G1Affine, _>(buffer)?; // Alpha G1
-    skip_vec::<E::G1Affine, _>(buffer)?; // Beta G1
-    skip_vec::<E::G2Affine, _>(buffer)?; // Beta G2
-
-    debug!("skipped unused elements...");
-
     // The previous operations are all on small size elements so do them serially
     // the `h` and `l` queries are relatively large, so we can get a nice speedup
-    // by performing the reads and writes in parallel
-    let h_query_len = u64::deserialize(buffer)? as usize;
-    let position = buffer.position() as usize;
-    let remaining = &mut buffer.get_mut()[position..];
-    let (h, l) = remaining.split_at_mut(h_query_len * E::G1Affine::SERIALIZED_SIZE);
-    let l_query_len = u64::deserialize(&mut &*l)? as usize;
-
-    // spawn 2 scoped threads to perform the contribution
-    crossbeam::scope(|s| -> Result<_> {
-        let mut threads = Vec::with_capacity(2);
+    // by performing the reads and writes in parallel, a batch of each at a time
+    let size = E::G1Affine::SERIALIZED_SIZE;
+    while progress.h_done < h_query_len || progress.l_done < l_query_len {
+        let span = info_span!("iter", h = progress.h_done, l = progress.l_done);
         let _enter = span.enter();
-        threads.push(s.spawn(|_| {
-            let _enter1 = span.enter();
-            let span = info_span!("h_query");
-            let _enter = span.enter();
-            chunked_mul_queries::<E::G1Affine>(h, h_query_len, &delta_inv, batch_size)
-        }));
 
-        threads.push(s.spawn(|_| {
-            let _enter1 = span.enter();
-            let span = info_span!("l_query");
-            let _enter = span.enter();
-            chunked_mul_queries::<E::G1Affine>(
-                // since we read the l_query length we will pass the buffer
-                // after it
-                &mut l[u64::SERIALIZED_SIZE..],
-                l_query_len,
-                &delta_inv,
-                batch_size,
-            )
-        }));
-
-        for t in threads {
-            t.join()??;
+        let h_count = std::cmp::min(batch_size, h_query_len - progress.h_done);
+        let l_count = std::cmp::min(batch_size, l_query_len - progress.l_done);
+        let (h, l) = buffer[h_position..].split_at_mut(l_position - h_position);
+        let h = &mut h[progress.h_done * size..(progress.h_done + h_count) * size];
+        let l = &mut l[progress.l_done * size..(progress.l_done + l_count) * size];
+
+        // undo the batch if it was interrupted halfway, or record it as it is
+        // before multiplying it, for the same reason
+        match progress.pending.len() {
+            0 => progress.pending = [&h[..], &l[..]].concat(),
+            len if len == h.len() + l.len() => {
+                let (pending_h, pending_l) = progress.pending.split_at(h.len());
+                h.copy_from_slice(pending_h);
+                l.copy_from_slice(pending_l);
+            }
+            _ => return Err(Phase2Error::ContributionCheckpointMismatch.into()),
         }
 
-        Ok(())
-    })??;
+        // the buffer is still unmodified the first time around
+        cancellation.check()?;
+        checkpoint(&*buffer, &progress)?;
+        let (h, l) = buffer[h_position..].split_at_mut(l_position - h_position);
+        let h = &mut h[progress.h_done * size..(progress.h_done + h_count) * size];
+        let l = &mut l[progress.l_done * size..(progress.l_done + l_count) * size];
+
+        crossbeam::scope(|s| -> Result<_> {
+            let threads = vec![(h, h_count), (l, l_count)]
+                .into_iter()
+                .filter(|(_, count)| *count > 0)
+                .map(|(query, _)| s.spawn(move |_| mul_query::<E::G1Affine>(query, &delta_inv, check)))
+                .collect::<Vec<_>>();
+            for t in threads {
+                t.join()??;
+            }
+            Ok(())
+        })??;
+
+        progress.h_done += h_count;
+        progress.l_done += l_count;
+        progress.pending.clear();
+        trace!("ok");
+    }
+    cancellation.check()?;
+    checkpoint(&*buffer, &progress)?;
 
     debug!("appending contribution...");
 
-    // we processed the 2 elements via the raw buffer, so we have to modify the cursor accordingly
-    let pos = position + (l_query_len + h_query_len) * E::G1Affine::SERIALIZED_SIZE + u64::SERIALIZED_SIZE;
-    buffer.seek(SeekFrom::Start(pos as u64))?;
-
-    // leave the cs_hash unchanged (64 bytes size)
-    buffer.seek(SeekFrom::Current(64))?;
-
+    // The new delta is computed from the one before the contribution, and the
+    // public key is appended after the contributions before it, so that doing
+    // it again when resuming gives the same parameters
+    let writer = &mut std::io::Cursor::new(&mut buffer[..]);
+    writer.seek(SeekFrom::Start(contributions_position))?;
     // update the pubkeys length
-    buffer.write_u32::<BigEndian>((contributions.len() + 1) as u32)?;
-
+    writer.write_u32::<BigEndian>((progress.contributions + 1) as u32)?;
     // advance to where the next pubkey would be in the buffer and append it
-    buffer.seek(SeekFrom::Current((PublicKey::<E>::size() * contributions.len()) as i64))?;
-    public_key.write(buffer)?;
+    writer.seek(SeekFrom::Current((PublicKey::<E>::size() * progress.contributions) as i64))?;
+    public_key.write(writer)?;
 
+    // go back to the start of the buffer to write the updated vk and delta_g1
+    writer.seek(SeekFrom::Start(0))?;
+    vk.delta_g2 = vk.delta_g2.mul(delta);
+    vk.serialize(writer)?;
+    writer.seek(SeekFrom::Start(delta_g1_position))?;
+    delta_g1.mul(delta).serialize(writer)?;
+
+    debug!("updated delta g1 and vk delta g2");
     info!("done.");
 
     Ok(hash)
 }
 
-/// Skips the vector ahead of the cursor.
-fn skip_vec<C: AffineCurve, B: Read + Seek>(buffer: &mut B) -> Result<()> {
-    let len = u64::deserialize(buffer)? as usize;
-    let skip_len = len * C::SERIALIZED_SIZE;
-    buffer.seek(SeekFrom::Current(skip_len as i64))?;
-    Ok(())
-}
-
-/// Multiplies a vector of affine elements by `element` in `batch_size` batches
-/// The first 8 bytes read from the buffer are the vector's length. The result
-/// is written back to the buffer in place
-#[allow(clippy::cognitive_complexity)]
-fn chunked_mul_queries<C: AffineCurve>(
-    buffer: &mut [u8],
-    query_len: usize,
-    element: &C::ScalarField,
-    batch_size: usize,
-) -> Result<()> {
-    let span = info_span!("multiply_query");
-    let _enter = span.enter();
-    debug!("starting...");
+/// Reads the `cs_hash` and the contributions from serialized parameters,
+/// skipping over the queries without deserializing them.
+pub fn read_contributions<E: PairingEngine>(buffer: &[u8]) -> Result<([u8; 64], Vec<PublicKey<E>>)> {
     let buffer = &mut std::io::Cursor::new(buffer);
+    VerifyingKey::<E>::deserialize(buffer)?;
+    // beta_g1 and delta_g1
+    buffer.seek(SeekFrom::Current(2 * E::G1Affine::SERIALIZED_SIZE as i64))?;
 
-    let iters = query_len / batch_size;
-    let leftovers = query_len % batch_size;
-    // naive chunking, probably room for parallelization
-    for i in 0..iters {
-        let span = info_span!("iter", i);
-        let _enter = span.enter();
+    skip_vec::<E::G1Affine, _>(buffer)?; // Alpha G1
+    skip_vec::<E::G1Affine, _>(buffer)?; // Beta G1
+    skip_vec::<E::G2Affine, _>(buffer)?; // Beta G2
+    skip_vec::<E::G1Affine, _>(buffer)?; // H
+    skip_vec::<E::G1Affine, _>(buffer)?; // L
 
-        mul_query::<C, _>(buffer, element, batch_size)?;
+    let mut cs_hash = [0u8; 64];
+    buffer.read_exact(&mut cs_hash)?;
+    let contributions = PublicKey::<E>::read_batch(buffer)?;
 
-        trace!("ok");
-    }
-    // in case the batch size did not evenly divide the number of queries
-    if leftovers > 0 {
-        let span = info_span!("iter", i = iters);
-        let _enter = span.enter();
+    Ok((cs_hash, contributions))
+}
 
-        mul_query::<C, _>(buffer, element, leftovers)?;
+/// Returns the number of elements of the H and L queries of serialized parameters,
+/// which are the ones a contribution multiplies, without deserializing them.
+pub fn query_points<E: PairingEngine>(buffer: &[u8]) -> Result<usize> {
+    let buffer = &mut std::io::Cursor::new(buffer);
+    VerifyingKey::<E>::deserialize(buffer)?;
+    // beta_g1 and delta_g1
+    buffer.seek(SeekFrom::Current(2 * E::G1Affine::SERIALIZED_SIZE as i64))?;
 
-        trace!("ok");
-    }
+    skip_vec::<E::G1Affine, _>(buffer)?; // Alpha G1
+    skip_vec::<E::G1Affine, _>(buffer)?; // Beta G1
+    skip_vec::<E::G2Affine, _>(buffer)?; // Beta G2
+    let h_query_len = u64::deserialize(buffer)? as usize;
+    buffer.seek(SeekFrom::Current((h_query_len * E::G1Affine::SERIALIZED_SIZE) as i64))?;
+    let l_query_len = u64::deserialize(buffer)? as usize;
 
-    debug!("done");
-    Ok(())
+    Ok(h_query_len + l_query_len)
 }
 
-/// Deserializes `num_els` elements, multiplies them by `element`
-/// and writes them back in place
-fn mul_query<C: AffineCurve, B: Read + Write + Seek>(
-    buffer: &mut B,
-    element: &C::ScalarField,
-    num_els: usize,
-) -> Result<()> {
-    let mut query = (0..num_els)
-        .map(|_| C::deserialize(buffer))
-        .collect::<std::result::Result<Vec<_>, _>>()?; // why can't we use the aliased error type here?
+/// Skips the vector ahead of the cursor.
+fn skip_vec<C: AffineCurve, B: Read + Seek>(buffer: &mut B) -> Result<()> {
+    let len = u64::deserialize(buffer)? as usize;
+    let skip_len = len * C::SERIALIZED_SIZE;
+    buffer.seek(SeekFrom::Current(skip_len as i64))?;
+    Ok(())
+}
 
+/// Deserializes the elements of the buffer, checking them with `check`,
+/// multiplies them by `element` and writes them back in place
+fn mul_query<C: AffineCurve>(buffer: &mut [u8], element: &C::ScalarField, check: CheckForCorrectness) -> Result<()> {
+    let mut query = buffer.read_batch::<C>(UseCompression::Yes, check)?;
     batch_mul(&mut query, element)?;
-
-    // seek back to update the elements
-    buffer.seek(SeekFrom::Current(((num_els * C::SERIALIZED_SIZE) as i64).neg()))?;
-    query
-        .iter()
-        .map(|el| el.serialize(buffer))
-        .collect::<std::result::Result<Vec<_>, _>>()?;
+    buffer.write_batch(&query, UseCompression::Yes)?;
 
     Ok(())
 }
 
+/// Relaxes `check` so that it accepts the point at infinity, for the queries
+/// which may contain it.
+pub(crate) fn allow_zero(check: CheckForCorrectness) -> CheckForCorrectness {
+    match check {
+        CheckForCorrectness::Full => CheckForCorrectness::OnlyInGroup,
+        CheckForCorrectness::OnlyNonZero => CheckForCorrectness::No,
+        check => check,
+    }
+}
+
 /// Checks that 2 vectors read from the 2 buffers are the same in chunks
 #[allow(clippy::cognitive_complexity)]
 fn chunked_ensure_unchanged_vec<C: AffineCurve>(
     before: &mut [u8],
     after: &mut [u8],
     batch_size: usize,
+    check: CheckForCorrectness,
     kind: &InvariantKind,
+    cancellation: &CancellationToken,
 ) -> Result<()> {
     let span = info_span!("unchanged_vec");
     let _enter = span.enter();
@@ -423,7 +602,8 @@ fn chunked_ensure_unchanged_vec<C: AffineCurve>(
         let span1 = info_span!("iter", i);
         let _enter = span1.enter();
 
-        let (els_before, els_after) = read_batch::<C, _>(before, after, batch_size)?;
+        cancellation.check()?;
+        let (els_before, els_after) = read_batch::<C, _>(before, after, batch_size, check)?;
         ensure_unchanged_vec(&els_before, &els_after, kind)?;
 
         trace!("ok");
@@ -434,7 +614,8 @@ fn chunked_ensure_unchanged_vec<C: AffineCurve>(
         let span1 = info_span!("iter", i = iters);
         let _enter = span1.enter();
 
-        let (els_before, els_after) = read_batch::<C, _>(before, after, leftovers)?;
+        cancellation.check()?;
+        let (els_before, els_after) = read_batch::<C, _>(before, after, leftovers, check)?;
         ensure_unchanged_vec(&els_before, &els_after, kind)?;
 
         trace!("ok");
@@ -445,14 +626,20 @@ fn chunked_ensure_unchanged_vec<C: AffineCurve>(
     Ok(())
 }
 
-/// Checks that 2 vectors read from the 2 buffers are the same in chunks
+/// Checks that the elements of the second buffer are those of the first one updated with
+/// delta^-1 in chunks, and that none of them is the point at infinity
+#[allow(clippy::too_many_arguments)]
 fn chunked_check_ratio<E: PairingEngine>(
     before: &mut [u8],
     before_delta_g2: E::G2Affine,
     after: &mut [u8],
     after_delta_g2: E::G2Affine,
     batch_size: usize,
+    check: CheckForCorrectness,
+    kind: &InvariantKind,
     err: &'static str,
+    cancellation: &CancellationToken,
+    progress: &(dyn Fn(usize) + Sync),
 ) -> Result<()> {
     let span = info_span!("check_ratio");
     let _enter = span.enter();
@@ -470,16 +657,22 @@ fn chunked_check_ratio<E: PairingEngine>(
 
     let iters = len_before / batch_size;
     let leftovers = len_before % batch_size;
-    for _ in 0..iters {
-        let (els_before, els_after) = read_batch::<E::G1Affine, _>(before, after, batch_size)?;
+    for i in 0..iters {
+        cancellation.check()?;
+        let (els_before, els_after) = read_batch::<E::G1Affine, _>(before, after, batch_size, check)?;
+        ensure_no_identity(&els_after, i * batch_size, kind)?;
         let pairs = merge_pairs(&els_before, &els_after);
         check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), err)?;
+        progress(batch_size);
     }
     // in case the batch size did not evenly divide the number of queries
     if leftovers > 0 {
-        let (els_before, els_after) = read_batch::<E::G1Affine, _>(before, after, leftovers)?;
+        cancellation.check()?;
+        let (els_before, els_after) = read_batch::<E::G1Affine, _>(before, after, leftovers, check)?;
+        ensure_no_identity(&els_after, iters * batch_size, kind)?;
         let pairs = merge_pairs(&els_before, &els_after);
         check_same_ratio::<E>(&pairs, &(after_delta_g2, before_delta_g2), err)?;
+        progress(leftovers);
     }
 
     debug!("done.");
@@ -487,17 +680,14 @@ fn chunked_check_ratio<E: PairingEngine>(
     Ok(())
 }
 
-fn read_batch<C: AffineCurve, B: Read + Write + Seek>(
+fn read_batch<C: AffineCurve, B: Read>(
     before: &mut B,
     after: &mut B,
     batch_size: usize,
+    check: CheckForCorrectness,
 ) -> Result<(Vec<C>, Vec<C>)> {
-    let els_before = (0..batch_size)
-        .map(|_| C::deserialize(before))
-        .collect::<std::result::Result<Vec<_>, _>>()?;
-    let els_after = (0..batch_size)
-        .map(|_| C::deserialize(after))
-        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let els_before = before.read_elements_exact(batch_size, UseCompression::Yes, check)?;
+    let els_after = after.read_elements_exact(batch_size, UseCompression::Yes, check)?;
     Ok((els_before, els_after))
 }
 
@@ -0,0 +1,311 @@
+//! Multiplication of the points of a query by a scalar on an OpenCL device, which
+//! dominates the time it takes to contribute to large chunks.
+//!
+//! Only the G1 groups of BLS12-377 and BW6-761 are supported, which hold the queries
+//! a contribution updates. [batch_mul] returns `false` for other groups, or when no
+//! OpenCL device is usable, in which case the caller multiplies on the CPU instead.
+
+use snarkvm_curves::{bls12_377, bw6_761, AffineCurve};
+use snarkvm_fields::{FieldParameters, One, PrimeField, Zero};
+use snarkvm_utilities::biginteger::BigInteger;
+
+use ocl::{Buffer, Context, Device, Kernel, Platform, Program, Queue};
+use once_cell::sync::Lazy;
+use std::{any::Any, collections::HashMap, sync::Mutex};
+use thiserror::Error;
+use tracing::{debug, warn};
+use zeroize::Zeroize;
+
+/// The kernel, built once for each number of limbs of a base field.
+const SCALAR_MUL_SOURCE: &str = include_str!("scalar_mul.cl");
+
+/// The OpenCL device the points are multiplied on, if there is one.
+static GPU: Lazy<Option<Gpu>> = Lazy::new(|| match Gpu::new() {
+    Ok(gpu) => Some(gpu),
+    Err(error) => {
+        warn!("No OpenCL device is usable, multiplying on the CPU: {}", error);
+        None
+    }
+});
+
+#[derive(Debug, Error)]
+pub enum GpuError {
+    #[error("OpenCL error: {0}")]
+    Ocl(#[from] ocl::Error),
+    #[error("The device returned a coordinate which is not a field element")]
+    InvalidCoordinate,
+}
+
+/// A group whose points the kernel can multiply, i.e. a short Weierstrass curve
+/// with `a = 0` over a prime field.
+trait GpuCurve: AffineCurve {
+    type BaseField: PrimeField;
+
+    /// Returns the affine coordinates of the point, or `None` at infinity.
+    fn coordinates(&self) -> Option<(Self::BaseField, Self::BaseField)>;
+
+    fn from_coordinates(x: Self::BaseField, y: Self::BaseField) -> Self;
+}
+
+impl GpuCurve for bls12_377::G1Affine {
+    type BaseField = bls12_377::Fq;
+
+    fn coordinates(&self) -> Option<(Self::BaseField, Self::BaseField)> {
+        (!self.infinity).then(|| (self.x, self.y))
+    }
+
+    fn from_coordinates(x: Self::BaseField, y: Self::BaseField) -> Self {
+        Self::new(x, y, false)
+    }
+}
+
+impl GpuCurve for bw6_761::G1Affine {
+    type BaseField = bw6_761::Fq;
+
+    fn coordinates(&self) -> Option<(Self::BaseField, Self::BaseField)> {
+        (!self.infinity).then(|| (self.x, self.y))
+    }
+
+    fn from_coordinates(x: Self::BaseField, y: Self::BaseField) -> Self {
+        Self::new(x, y, false)
+    }
+}
+
+/// Multiplies the points by `coeff` on the OpenCL device, in place. Returns `false`,
+/// leaving the points unchanged, if the group is not supported or the device failed.
+pub fn batch_mul<C: AffineCurve>(bases: &mut Vec<C>, coeff: &C::ScalarField) -> bool {
+    let gpu = match GPU.as_ref() {
+        Some(gpu) => gpu,
+        None => return false,
+    };
+
+    let result = if let Some(bases) = (bases as &mut dyn Any).downcast_mut::<Vec<bls12_377::G1Affine>>() {
+        gpu.batch_mul(bases, downcast_scalar(coeff))
+    } else if let Some(bases) = (bases as &mut dyn Any).downcast_mut::<Vec<bw6_761::G1Affine>>() {
+        gpu.batch_mul(bases, downcast_scalar(coeff))
+    } else {
+        return false;
+    };
+
+    match result {
+        Ok(()) => true,
+        Err(error) => {
+            warn!(
+                "Multiplying on the OpenCL device failed, multiplying on the CPU: {}",
+                error
+            );
+            false
+        }
+    }
+}
+
+/// Returns the scalar of a group the points were downcast to, which has the same type.
+fn downcast_scalar<S: 'static, T: 'static>(scalar: &S) -> &T {
+    (scalar as &dyn Any)
+        .downcast_ref()
+        .expect("the scalar field is determined by the group")
+}
+
+struct Gpu {
+    device: Device,
+    context: Context,
+    queue: Queue,
+    /// The kernel built for each number of limbs of a base field.
+    programs: Mutex<HashMap<usize, Program>>,
+}
+
+impl Gpu {
+    fn new() -> Result<Self, GpuError> {
+        let platform = Platform::default();
+        let device = Device::first(platform)?;
+        let context = Context::builder().platform(platform).devices(device).build()?;
+        let queue = Queue::new(&context, device, None)?;
+        debug!("Multiplying points on the OpenCL device {}", device.name()?);
+
+        Ok(Self {
+            device,
+            context,
+            queue,
+            programs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn program(&self, limbs: usize) -> Result<Program, GpuError> {
+        let mut programs = self.programs.lock().expect("the programs are not poisoned");
+        if let Some(program) = programs.get(&limbs) {
+            return Ok(program.clone());
+        }
+        let program = Program::builder()
+            .devices(self.device)
+            .src(SCALAR_MUL_SOURCE)
+            .cmplr_def("LIMBS", limbs as i32)
+            .build(&self.context)?;
+        programs.insert(limbs, program.clone());
+        Ok(program)
+    }
+
+    fn batch_mul<G: GpuCurve>(&self, bases: &mut [G], coeff: &G::ScalarField) -> Result<(), GpuError> {
+        if bases.is_empty() {
+            return Ok(());
+        }
+        let modulus = <<G::BaseField as PrimeField>::Parameters as FieldParameters>::MODULUS;
+        let r2 = <<G::BaseField as PrimeField>::Parameters as FieldParameters>::R2;
+        let inv = <<G::BaseField as PrimeField>::Parameters as FieldParameters>::INV;
+        let mut p_minus_2 = modulus;
+        p_minus_2.sub_noborrow(&2u64.into());
+        let limbs = modulus.as_ref().len();
+        // The scalar is secret, e.g. the inverse of delta, so it is multiplied by with a ladder over
+        // every bit of the scalar field, and the copies of it are zeroed once the kernel ran.
+        let mut scalar = coeff.to_repr();
+        let scalar_bits = <<G::ScalarField as PrimeField>::Parameters as FieldParameters>::MODULUS_BITS;
+
+        let mut xs = Vec::with_capacity(bases.len() * limbs);
+        let mut ys = Vec::with_capacity(bases.len() * limbs);
+        let mut infinity = Vec::with_capacity(bases.len());
+        for base in bases.iter() {
+            let (x, y) = base
+                .coordinates()
+                .unwrap_or_else(|| (G::BaseField::zero(), G::BaseField::zero()));
+            xs.extend_from_slice(x.to_repr().as_ref());
+            ys.extend_from_slice(y.to_repr().as_ref());
+            infinity.push(base.coordinates().is_none() as u8);
+        }
+
+        let buffer = |values: &[u64]| {
+            Buffer::<u64>::builder()
+                .queue(self.queue.clone())
+                .len(values.len())
+                .copy_host_slice(values)
+                .build()
+        };
+        let scalar_buffer = buffer(scalar.as_ref());
+        scalar.as_mut().zeroize();
+        let scalar_buffer = scalar_buffer?;
+
+        let multiplied = (|| -> Result<(), GpuError> {
+            let xs_buffer = buffer(&xs)?;
+            let ys_buffer = buffer(&ys)?;
+            let modulus_buffer = buffer(modulus.as_ref())?;
+            let r2_buffer = buffer(r2.as_ref())?;
+            let p_minus_2_buffer = buffer(p_minus_2.as_ref())?;
+            let infinity_buffer = Buffer::<u8>::builder()
+                .queue(self.queue.clone())
+                .len(infinity.len())
+                .copy_host_slice(&infinity)
+                .build()?;
+
+            let kernel = Kernel::builder()
+                .program(&self.program(limbs)?)
+                .name("scalar_mul")
+                .queue(self.queue.clone())
+                .global_work_size(bases.len())
+                .arg(&xs_buffer)
+                .arg(&ys_buffer)
+                .arg(&infinity_buffer)
+                .arg(&modulus_buffer)
+                .arg(&r2_buffer)
+                .arg(&p_minus_2_buffer)
+                .arg(inv)
+                .arg(&scalar_buffer)
+                .arg(scalar_bits)
+                .arg(bases.len() as u32)
+                .build()?;
+            unsafe {
+                kernel.enq()?;
+            }
+
+            xs_buffer.read(&mut xs).enq()?;
+            ys_buffer.read(&mut ys).enq()?;
+            infinity_buffer.read(&mut infinity).enq()?;
+            Ok(())
+        })();
+
+        // The device buffer is zeroed whether or not the kernel ran, before its memory is released.
+        let zeros = vec![0u64; scalar_buffer.len()];
+        scalar_buffer.write(&zeros).enq()?;
+        self.queue.finish()?;
+        multiplied?;
+
+        // The points are only replaced once every result is known to be valid.
+        let results = xs
+            .chunks(limbs)
+            .zip(ys.chunks(limbs))
+            .zip(&infinity)
+            .map(|((x, y), infinity)| match infinity {
+                0 => Ok(G::from_coordinates(from_limbs(x)?, from_limbs(y)?)),
+                _ => Ok(G::zero()),
+            })
+            .collect::<Result<Vec<G>, GpuError>>()?;
+        bases.copy_from_slice(&results);
+
+        Ok(())
+    }
+}
+
+/// Returns the field element with the given canonical little-endian limbs.
+fn from_limbs<F: PrimeField>(limbs: &[u64]) -> Result<F, GpuError> {
+    let mut repr = F::BigInteger::default();
+    repr.as_mut().copy_from_slice(limbs);
+    F::from_repr(repr).ok_or(GpuError::InvalidCoordinate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use setup_utils::batch_mul as cpu_batch_mul;
+    use snarkvm_curves::ProjectiveCurve;
+    use snarkvm_utilities::rand::UniformRand;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn random_points<C: AffineCurve>(n: usize, rng: &mut StdRng) -> Vec<C> {
+        let mut points: Vec<C> = (0..n).map(|_| C::Projective::rand(rng).into_affine()).collect();
+        points.push(C::zero());
+        points
+    }
+
+    fn test_batch_mul<C: AffineCurve>() {
+        // Machines without an OpenCL device multiply on the CPU, so there is nothing to compare.
+        if GPU.is_none() {
+            return;
+        }
+        let rng = &mut StdRng::seed_from_u64(1231275789u64);
+        let coeff = C::ScalarField::rand(rng);
+
+        let mut gpu_points = random_points::<C>(100, rng);
+        let mut cpu_points = gpu_points.clone();
+        assert!(batch_mul(&mut gpu_points, &coeff));
+        cpu_batch_mul(&mut cpu_points, &coeff).unwrap();
+        assert_eq!(gpu_points, cpu_points);
+
+        // Multiplying by one or by zero is handled like any other scalar
+        let mut points = cpu_points.clone();
+        assert!(batch_mul(&mut points, &C::ScalarField::one()));
+        assert_eq!(points, cpu_points);
+        assert!(batch_mul(&mut points, &C::ScalarField::zero()));
+        assert!(points.iter().all(|point| point.is_zero()));
+    }
+
+    #[test]
+    fn test_batch_mul_bls12_377() {
+        test_batch_mul::<bls12_377::G1Affine>();
+    }
+
+    #[test]
+    fn test_batch_mul_bw6_761() {
+        test_batch_mul::<bw6_761::G1Affine>();
+    }
+
+    #[test]
+    fn test_unsupported_group() {
+        let rng = &mut StdRng::seed_from_u64(1231275789u64);
+        let mut points = random_points::<bls12_377::G2Affine>(4, rng);
+        let original = points.clone();
+        assert!(!batch_mul(
+            &mut points,
+            &<bls12_377::G2Affine as AffineCurve>::ScalarField::rand(rng)
+        ));
+        assert_eq!(points, original);
+    }
+}
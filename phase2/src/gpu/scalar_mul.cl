@@ -0,0 +1,278 @@
+// Multiplies points of a short Weierstrass curve with `a = 0` by the same scalar,
+// one point per work item. The number of 64-bit limbs of the base field is
+// given by the `LIMBS` define when the program is built, e.g. 6 for the base
+// field of BLS12-377 and 12 for the base field of BW6-761.
+//
+// The coordinates are read and written in canonical form, little-endian limbs
+// first. The arithmetic is done in Montgomery form, and relies on the modulus
+// leaving the top bit of its top limb unset.
+
+typedef ulong limb;
+
+typedef struct {
+    limb v[LIMBS];
+} field;
+
+// Jacobian coordinates, with the point at infinity as `z = 0`.
+typedef struct {
+    field x;
+    field y;
+    field z;
+} point;
+
+// Returns the low limb of `a + b * c + carry`, and sets `carry` to the high limb.
+static inline limb mac(limb a, limb b, limb c, limb *carry) {
+    limb lo = b * c;
+    limb hi = mul_hi(b, c);
+    lo += a;
+    hi += (lo < a);
+    lo += *carry;
+    hi += (lo < *carry);
+    *carry = hi;
+    return lo;
+}
+
+// Returns `a + b + carry`, and sets `carry` to the carry out.
+static inline limb adc(limb a, limb b, limb *carry) {
+    limb sum = a + b;
+    limb out = sum < a;
+    limb result = sum + *carry;
+    out += result < sum;
+    *carry = out;
+    return result;
+}
+
+// Returns `a - b - borrow`, and sets `borrow` to the borrow out.
+static inline limb sbb(limb a, limb b, limb *borrow) {
+    limb diff = a - b;
+    limb out = a < b;
+    limb result = diff - *borrow;
+    out += diff < *borrow;
+    *borrow = out;
+    return result;
+}
+
+static inline bool f_is_zero(field a) {
+    limb acc = 0;
+    for (int i = 0; i < LIMBS; i++) {
+        acc |= a.v[i];
+    }
+    return acc == 0;
+}
+
+// Returns a mask with every bit set if `condition` holds, and no bit set otherwise.
+static inline limb mask_of(limb condition) {
+    return (limb)0 - (condition & 1);
+}
+
+// Returns `b` if every bit of `mask` is set and `a` if none is, without branching.
+static inline field f_select(field a, field b, limb mask) {
+    for (int i = 0; i < LIMBS; i++) {
+        a.v[i] ^= (a.v[i] ^ b.v[i]) & mask;
+    }
+    return a;
+}
+
+// Returns `a - p`, and sets `borrow` to the borrow out.
+static inline field f_sub_modulus(field a, __constant const field *p, limb *borrow) {
+    *borrow = 0;
+    for (int i = 0; i < LIMBS; i++) {
+        a.v[i] = sbb(a.v[i], p->v[i], borrow);
+    }
+    return a;
+}
+
+static inline field f_add(field a, field b, __constant const field *p) {
+    limb carry = 0;
+    for (int i = 0; i < LIMBS; i++) {
+        a.v[i] = adc(a.v[i], b.v[i], &carry);
+    }
+    // The sum is below twice the modulus, which fits in the limbs.
+    limb borrow;
+    field reduced = f_sub_modulus(a, p, &borrow);
+    return f_select(a, reduced, mask_of(borrow ^ 1));
+}
+
+static inline field f_sub(field a, field b, __constant const field *p) {
+    limb borrow = 0;
+    for (int i = 0; i < LIMBS; i++) {
+        a.v[i] = sbb(a.v[i], b.v[i], &borrow);
+    }
+    limb mask = mask_of(borrow);
+    limb carry = 0;
+    for (int i = 0; i < LIMBS; i++) {
+        a.v[i] = adc(a.v[i], p->v[i] & mask, &carry);
+    }
+    return a;
+}
+
+static inline field f_double(field a, __constant const field *p) {
+    return f_add(a, a, p);
+}
+
+// Montgomery multiplication with the CIOS method, where `inv` is `-p^-1 mod 2^64`.
+static field f_mul(field a, field b, __constant const field *p, limb inv) {
+    limb t[LIMBS + 2];
+    for (int i = 0; i < LIMBS + 2; i++) {
+        t[i] = 0;
+    }
+    for (int i = 0; i < LIMBS; i++) {
+        limb carry = 0;
+        for (int j = 0; j < LIMBS; j++) {
+            t[j] = mac(t[j], a.v[j], b.v[i], &carry);
+        }
+        limb carry2 = 0;
+        t[LIMBS] = adc(t[LIMBS], carry, &carry2);
+        t[LIMBS + 1] = carry2;
+
+        limb m = t[0] * inv;
+        carry = 0;
+        mac(t[0], m, p->v[0], &carry);
+        for (int j = 1; j < LIMBS; j++) {
+            t[j - 1] = mac(t[j], m, p->v[j], &carry);
+        }
+        carry2 = 0;
+        t[LIMBS - 1] = adc(t[LIMBS], carry, &carry2);
+        t[LIMBS] = t[LIMBS + 1] + carry2;
+    }
+
+    field result;
+    for (int i = 0; i < LIMBS; i++) {
+        result.v[i] = t[i];
+    }
+    limb borrow;
+    field reduced = f_sub_modulus(result, p, &borrow);
+    return f_select(result, reduced, mask_of((t[LIMBS] != 0) | (borrow ^ 1)));
+}
+
+static inline field f_square(field a, __constant const field *p, limb inv) {
+    return f_mul(a, a, p, inv);
+}
+
+// Returns `a^e` for an exponent `e` in canonical form.
+static field f_pow(field a, __constant const field *e, field one, __constant const field *p, limb inv) {
+    field result = one;
+    for (int i = LIMBS - 1; i >= 0; i--) {
+        for (int bit = 63; bit >= 0; bit--) {
+            result = f_square(result, p, inv);
+            if ((e->v[i] >> bit) & 1) {
+                result = f_mul(result, a, p, inv);
+            }
+        }
+    }
+    return result;
+}
+
+static inline point p_select(point a, point b, limb mask) {
+    a.x = f_select(a.x, b.x, mask);
+    a.y = f_select(a.y, b.y, mask);
+    a.z = f_select(a.z, b.z, mask);
+    return a;
+}
+
+// Swaps the points if every bit of `mask` is set, without branching.
+static inline void p_cswap(point *a, point *b, limb mask) {
+    point swapped_a = p_select(*a, *b, mask);
+    *b = p_select(*b, *a, mask);
+    *a = swapped_a;
+}
+
+// Doubling in Jacobian coordinates for `a = 0` (dbl-2009-l). The point at
+// infinity doubles to itself, as `z` stays zero.
+static point p_double(point a, __constant const field *p, limb inv) {
+    field xx = f_square(a.x, p, inv);
+    field yy = f_square(a.y, p, inv);
+    field yyyy = f_square(yy, p, inv);
+    field d = f_sub(f_sub(f_square(f_add(a.x, yy, p), p, inv), xx, p), yyyy, p);
+    d = f_double(d, p);
+    field e = f_add(f_double(xx, p), xx, p);
+    field f = f_square(e, p, inv);
+
+    point result;
+    result.x = f_sub(f, f_double(d, p), p);
+    field yyyy8 = f_double(f_double(f_double(yyyy, p), p), p);
+    result.y = f_sub(f_mul(e, f_sub(d, result.x, p), p, inv), yyyy8, p);
+    result.z = f_double(f_mul(a.y, a.z, p, inv), p);
+    return result;
+}
+
+// Addition in Jacobian coordinates (add-2007-bl), given `doubled = 2a` for the case
+// of equal points. The exceptional cases are selected without branching, and a sum
+// at infinity comes out with `z = 0` as `h` is then zero.
+static point p_add(point a, point b, point doubled, __constant const field *p, limb inv) {
+    field z1z1 = f_square(a.z, p, inv);
+    field z2z2 = f_square(b.z, p, inv);
+    field u1 = f_mul(a.x, z2z2, p, inv);
+    field u2 = f_mul(b.x, z1z1, p, inv);
+    field s1 = f_mul(a.y, f_mul(b.z, z2z2, p, inv), p, inv);
+    field s2 = f_mul(b.y, f_mul(a.z, z1z1, p, inv), p, inv);
+    field h = f_sub(u2, u1, p);
+    field i = f_square(f_double(h, p), p, inv);
+    field j = f_mul(h, i, p, inv);
+    field r = f_double(f_sub(s2, s1, p), p);
+    field v = f_mul(u1, i, p, inv);
+
+    point result;
+    result.x = f_sub(f_sub(f_square(r, p, inv), j, p), f_double(v, p), p);
+    result.y = f_sub(f_mul(r, f_sub(v, result.x, p), p, inv), f_double(f_mul(s1, j, p, inv), p), p);
+    result.z = f_mul(f_sub(f_sub(f_square(f_add(a.z, b.z, p), p, inv), z1z1, p), z2z2, p), h, p, inv);
+
+    result = p_select(result, doubled, mask_of(f_is_zero(h) & f_is_zero(r)));
+    result = p_select(result, b, mask_of(f_is_zero(a.z)));
+    result = p_select(result, a, mask_of(f_is_zero(b.z)));
+    return result;
+}
+
+// Multiplies every point by the scalar, given in canonical form with `scalar_bits`
+// bits, and writes the affine result back in place. `r2` is `R^2 mod p` for the
+// Montgomery radix `R`, and `p_minus_2` the exponent of the inverse.
+//
+// The scalar is secret, so the points are multiplied with a Montgomery ladder over
+// every bit of the scalar field, which does the same operations whatever the scalar.
+__kernel void scalar_mul(
+    __global field *xs,
+    __global field *ys,
+    __global uchar *infinity,
+    __constant const field *modulus,
+    __constant const field *r2,
+    __constant const field *p_minus_2,
+    limb inv,
+    __constant const limb *scalar,
+    uint scalar_bits,
+    uint n
+) {
+    uint index = get_global_id(0);
+    if (index >= n || infinity[index]) {
+        return;
+    }
+
+    field r2_value = *r2;
+    field canonical_one = {{1}};
+    field one = f_mul(canonical_one, r2_value, modulus, inv);
+    field x = f_mul(xs[index], r2_value, modulus, inv);
+    field y = f_mul(ys[index], r2_value, modulus, inv);
+
+    // The ladder keeps `r1 = r0 + base`, with `r0` the multiple of the bits so far.
+    point r0 = {one, one, {{0}}};
+    point r1 = {x, y, one};
+    for (int bit = (int)scalar_bits - 1; bit >= 0; bit--) {
+        limb swap = mask_of(scalar[bit / 64] >> (bit % 64));
+        p_cswap(&r0, &r1, swap);
+        point doubled = p_double(r0, modulus, inv);
+        r1 = p_add(r0, r1, doubled, modulus, inv);
+        r0 = doubled;
+        p_cswap(&r0, &r1, swap);
+    }
+    point acc = r0;
+
+    if (f_is_zero(acc.z)) {
+        infinity[index] = 1;
+        return;
+    }
+    field z_inv = f_pow(acc.z, p_minus_2, one, modulus, inv);
+    field z_inv2 = f_square(z_inv, modulus, inv);
+    field z_inv3 = f_mul(z_inv2, z_inv, modulus, inv);
+    // Multiplying by the canonical one leaves the Montgomery form.
+    xs[index] = f_mul(f_mul(acc.x, z_inv2, modulus, inv), canonical_one, modulus, inv);
+    ys[index] = f_mul(f_mul(acc.y, z_inv3, modulus, inv), canonical_one, modulus, inv);
+}
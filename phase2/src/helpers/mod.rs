@@ -1,2 +1,15 @@
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
+
+use setup_utils::Result;
+use snarkvm_curves::AffineCurve;
+
+/// Multiplies the points by `coeff` in place, on an OpenCL device if the `opencl`
+/// feature is enabled and one is usable, or on the CPU otherwise.
+pub fn batch_mul<C: AffineCurve>(bases: &mut Vec<C>, coeff: &C::ScalarField) -> Result<()> {
+    #[cfg(feature = "opencl")]
+    if crate::gpu::batch_mul(bases, coeff) {
+        return Ok(());
+    }
+    setup_utils::batch_mul(bases, coeff)
+}
@@ -10,6 +10,9 @@ mod polynomial;
 
 pub mod chunked_groth16;
 
+#[cfg(feature = "opencl")]
+pub mod gpu;
+
 cfg_if! {
     if #[cfg(feature = "wasm")] {
         use wasm_bindgen::prelude::*;
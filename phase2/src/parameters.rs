@@ -8,7 +8,11 @@ cfg_if! {
     }
 }
 
-use super::keypair::{hash_cs_pubkeys, Keypair, PublicKey};
+use super::{
+    chunked_groth16::allow_zero,
+    helpers::batch_mul,
+    keypair::{hash_cs_pubkeys, Keypair, PublicKey},
+};
 
 use setup_utils::*;
 
@@ -86,6 +90,19 @@ impl<E: PairingEngine> MPCParameters<E> {
     /// The resulting parameters are unsafe to use until there are contributions (see `contribute()`).
     #[cfg(not(feature = "wasm"))]
     pub fn new(assembly: KeypairAssembly<E>, params: Groth16Params<E>) -> Result<MPCParameters<E>> {
+        Self::new_cancellable(assembly, params, &CancellationToken::new())
+    }
+
+    /// Same as `new`, but returns `Error::Cancelled` or `Error::DeadlineExceeded`
+    /// if `cancellation` fires before or after the QAP evaluation.
+    #[cfg(not(feature = "wasm"))]
+    pub fn new_cancellable(
+        assembly: KeypairAssembly<E>,
+        params: Groth16Params<E>,
+        cancellation: &CancellationToken,
+    ) -> Result<MPCParameters<E>> {
+        cancellation.check()?;
+
         // Evaluate the QAP against the coefficients created from phase 1
         let (a_g1, b_g1, b_g2, gamma_abc_g1, l) = eval::<E>(
             // Lagrange coeffs for Tau, read in from Phase 1
@@ -101,6 +118,8 @@ impl<E: PairingEngine> MPCParameters<E> {
             assembly.num_public_variables,
         );
 
+        cancellation.check()?;
+
         // Reject unconstrained elements, so that
         // the L query is always fully dense.
         for e in l.iter() {
@@ -129,12 +148,37 @@ impl<E: PairingEngine> MPCParameters<E> {
             l_query: l,
         };
 
+        // A phase 2 size which is not a power of 2 gives Lagrange coefficients over a
+        // smaller domain than the one the QAP is evaluated on, and an incomplete H query
+        let phase2_size = params.h_query.len() + 1;
+        if !is_domain_size(phase2_size, params.a_query.len()) {
+            return Err(Phase2Error::InvalidPhase2Size(phase2_size, params.a_query.len()).into());
+        }
+
         let cs_hash = hash_params(&params)?;
-        Ok(MPCParameters {
+        let parameters = MPCParameters {
             params,
             cs_hash,
             contributions: vec![],
-        })
+        };
+        parameters.ensure_non_degenerate()?;
+        Ok(parameters)
+    }
+
+    /// Checks that none of the elements which must not be the point at infinity is.
+    /// Such parameters can still pass the pairing checks of `verify`, as a pairing
+    /// with the identity is always one, but they are degenerate: e.g. a zero delta
+    /// lets anyone forge proofs.
+    pub fn ensure_non_degenerate(&self) -> Result<()> {
+        let params = &self.params;
+        ensure_no_identity(&[params.vk.alpha_g1], 0, &InvariantKind::AlphaG1)?;
+        ensure_no_identity(&[params.beta_g1], 0, &InvariantKind::BetaG1)?;
+        ensure_no_identity(&[params.vk.beta_g2], 0, &InvariantKind::BetaG2)?;
+        ensure_no_identity(&[params.vk.gamma_g2], 0, &InvariantKind::GammaG2)?;
+        ensure_no_identity(&[params.delta_g1], 0, &InvariantKind::DeltaG1)?;
+        ensure_no_identity(&[params.vk.delta_g2], 0, &InvariantKind::DeltaG2)?;
+        ensure_no_identity(&params.h_query, 0, &InvariantKind::HQuery)?;
+        ensure_no_identity(&params.l_query, 0, &InvariantKind::LQuery)
     }
 
     /// Get the underlying Groth16 `ProvingKey`
@@ -188,6 +232,7 @@ impl<E: PairingEngine> MPCParameters<E> {
             // if there were no contributions then we should error
             return Err(Phase2Error::NoContributions.into());
         };
+        after.ensure_non_degenerate()?;
         // Current parameters should have consistent delta in G1
         ensure_unchanged(pubkey.delta_after, after.params.delta_g1, InvariantKind::DeltaG1)?;
         // Current parameters should have consistent delta in G2
@@ -274,6 +319,281 @@ impl<E: PairingEngine> MPCParameters<E> {
         verify_transcript(before.cs_hash, &after.contributions)
     }
 
+    /// Verify the final parameters on their own, without the
+    /// parameters they were derived from. This checks that the
+    /// verifying key is consistent with the proving key and the
+    /// last contribution, and returns the hashes of all
+    /// contributions, the last of which is the transcript digest.
+    ///
+    /// This is meant for users who only downloaded the published
+    /// parameters; the ceremony itself must use `verify`.
+    pub fn verify_final(&self) -> Result<Vec<[u8; 64]>> {
+        self.check_final_keys()?;
+
+        // generate the transcript from the contributions and the cs_hash
+        verify_transcript(self.cs_hash, &self.contributions)
+    }
+
+    /// Verify the final parameters like `verify_final`, but trust
+    /// the first `checkpoint` contributions, whose last contribution
+    /// must hash to `checkpoint_hash`. This lets verification resume
+    /// from a checkpoint published by those who verified the
+    /// transcript up to it, instead of starting over.
+    pub fn verify_final_from(&self, checkpoint: usize, checkpoint_hash: &[u8; 64]) -> Result<Vec<[u8; 64]>> {
+        self.check_final_keys()?;
+
+        verify_transcript_from(self.cs_hash, &self.contributions, checkpoint, checkpoint_hash)
+    }
+
+    /// Checks that the keys are consistent with each other and with the last contribution.
+    fn check_final_keys(&self) -> Result<()> {
+        let pubkey = self.contributions.last().ok_or(Phase2Error::NoContributions)?;
+        self.ensure_non_degenerate()?;
+
+        // The proving key's delta should be the one of the last contribution
+        ensure_unchanged(pubkey.delta_after, self.params.delta_g1, InvariantKind::DeltaG1)?;
+        // The verifying key's delta and beta should match the proving key's
+        check_same_ratio::<E>(
+            &(E::G1Affine::prime_subgroup_generator(), self.params.delta_g1),
+            &(E::G2Affine::prime_subgroup_generator(), self.params.vk.delta_g2),
+            "Inconsistent G2 Delta",
+        )?;
+        check_same_ratio::<E>(
+            &(E::G1Affine::prime_subgroup_generator(), self.params.beta_g1),
+            &(E::G2Affine::prime_subgroup_generator(), self.params.vk.beta_g2),
+            "Inconsistent G2 Beta",
+        )
+    }
+
+    /// Split the parameters into the query parameters and chunks of the
+    /// `h` and `l` queries, each holding at most `chunk_size` elements of
+    /// either query.
+    ///
+    /// The query parameters hold everything except the `h` and `l` queries,
+    /// which are the only ones contributions change. The chunks hold their
+    /// slices of the `h` and `l` queries along with the verifying key, delta
+    /// and the contributions, so that each of them can be contributed to on
+    /// its own, as long as every chunk gets the same delta. Chunked
+    /// contributions are checked with `verify_chunked`, and put back together
+    /// with `combine`.
+    pub fn split(&self, chunk_size: usize) -> Result<(MPCParameters<E>, Vec<MPCParameters<E>>)> {
+        let num_chunks = self.num_chunks(chunk_size)?;
+
+        let query = self.with_queries(
+            self.params.a_query.clone(),
+            self.params.b_g1_query.clone(),
+            self.params.b_g2_query.clone(),
+            vec![],
+            vec![],
+        );
+        let chunks = (0..num_chunks)
+            .map(|i| {
+                let h_query = chunk_slice(&self.params.h_query, chunk_size, i).to_vec();
+                let l_query = chunk_slice(&self.params.l_query, chunk_size, i).to_vec();
+                self.with_queries(vec![], vec![], vec![], h_query, l_query)
+            })
+            .collect();
+
+        Ok((query, chunks))
+    }
+
+    /// Returns the number of chunks `split` splits the parameters into.
+    pub fn num_chunks(&self, chunk_size: usize) -> Result<usize> {
+        if chunk_size == 0 {
+            return Err(Phase2Error::InvalidChunkSize.into());
+        }
+
+        let num_chunks = std::cmp::max(
+            (self.params.h_query.len() + chunk_size - 1) / chunk_size,
+            (self.params.l_query.len() + chunk_size - 1) / chunk_size,
+        );
+        Ok(std::cmp::max(num_chunks, 1))
+    }
+
+    /// The largest chunk size which does not produce an empty chunk, i.e.
+    /// the length of the longer of the `h` and `l` queries. Any larger chunk
+    /// size splits the parameters into the same single chunk.
+    pub fn max_chunk_size(&self) -> usize {
+        std::cmp::max(
+            std::cmp::max(self.params.h_query.len(), self.params.l_query.len()),
+            1,
+        )
+    }
+
+    /// The queries of the i-th chunk, which only holds its slices of the `h` and `l` queries.
+    fn chunk_queries(&self, chunk_size: usize, i: usize) -> Queries<'_, E> {
+        Queries {
+            a_query: &[],
+            b_g1_query: &[],
+            b_g2_query: &[],
+            h_query: chunk_slice(&self.params.h_query, chunk_size, i),
+            l_query: chunk_slice(&self.params.l_query, chunk_size, i),
+        }
+    }
+
+    /// Put chunks produced by `split` back together with the query parameters
+    /// into the full parameters. The chunks must all have the same contributions.
+    pub fn combine(query: &Self, chunks: &[Self]) -> Result<MPCParameters<E>> {
+        let first = chunks.first().ok_or(Phase2Error::NoChunks)?;
+
+        let mut h_query = vec![];
+        let mut l_query = vec![];
+        for chunk in chunks {
+            query.ensure_matches_query(chunk)?;
+            ensure_unchanged(
+                &first.contributions[..],
+                &chunk.contributions[..],
+                InvariantKind::Contributions,
+            )?;
+            ensure_unchanged(first.params.delta_g1, chunk.params.delta_g1, InvariantKind::DeltaG1)?;
+            h_query.extend_from_slice(&chunk.params.h_query);
+            l_query.extend_from_slice(&chunk.params.l_query);
+        }
+        query.ensure_complete_queries(h_query.len(), l_query.len())?;
+
+        Ok(first.with_queries(
+            query.params.a_query.clone(),
+            query.params.b_g1_query.clone(),
+            query.params.b_g2_query.clone(),
+            h_query,
+            l_query,
+        ))
+    }
+
+    /// Verify a chunked contribution against these query parameters, given
+    /// the pairs of chunks before and after it, in order. Each chunk is
+    /// checked like full parameters are by `verify`, and the chunks together
+    /// must cover the full structure described by the query parameters,
+    /// with the same contributions in each of them. Returns the contribution
+    /// hashes, like `verify` does.
+    ///
+    /// The pairs are consumed one at a time, so only a single pair of chunks
+    /// has to be held in memory if the iterator reads them lazily.
+    pub fn verify_chunked<I>(&self, chunks: I) -> Result<Vec<[u8; 64]>>
+    where
+        I: IntoIterator<Item = Result<(Self, Self)>>,
+    {
+        let mut verified: Option<(Vec<PublicKey<E>>, Vec<[u8; 64]>)> = None;
+        let mut h_query_len = 0;
+        let mut l_query_len = 0;
+        for chunk in chunks {
+            let (before, after) = chunk?;
+            let hashes = self.verify_chunk(&before, &after)?;
+
+            // every chunk must have received the same contributions, or
+            // the chunks do not combine into consistent parameters
+            match &verified {
+                Some((contributions, _)) => ensure_unchanged(
+                    &contributions[..],
+                    &after.contributions[..],
+                    InvariantKind::Contributions,
+                )?,
+                None => verified = Some((after.contributions.clone(), hashes)),
+            }
+            h_query_len += after.params.h_query.len();
+            l_query_len += after.params.l_query.len();
+        }
+
+        let (_, hashes) = verified.ok_or(Phase2Error::NoChunks)?;
+        self.ensure_complete_queries(h_query_len, l_query_len)?;
+
+        Ok(hashes)
+    }
+
+    /// Verify a single pair of chunks against these query parameters, like
+    /// `verify_chunked` does for each of them, so that the chunks of a
+    /// contribution can be verified independently, e.g. in parallel. The
+    /// caller must still check that every chunk received the same
+    /// contributions, and that the chunks cover the query parameters with
+    /// `ensure_complete_queries`.
+    pub fn verify_chunk(&self, before: &Self, after: &Self) -> Result<Vec<[u8; 64]>> {
+        self.ensure_matches_query(before)?;
+        self.ensure_matches_query(after)?;
+        before.verify(after)
+    }
+
+    /// Returns a copy of these parameters with the queries replaced.
+    fn with_queries(
+        &self,
+        a_query: Vec<E::G1Affine>,
+        b_g1_query: Vec<E::G1Affine>,
+        b_g2_query: Vec<E::G2Affine>,
+        h_query: Vec<E::G1Affine>,
+        l_query: Vec<E::G1Affine>,
+    ) -> MPCParameters<E> {
+        MPCParameters {
+            params: ProvingKey {
+                vk: self.params.vk.clone(),
+                beta_g1: self.params.beta_g1,
+                delta_g1: self.params.delta_g1,
+                a_query,
+                b_g1_query,
+                b_g2_query,
+                h_query,
+                l_query,
+            },
+            cs_hash: self.cs_hash,
+            contributions: self.contributions.clone(),
+        }
+    }
+
+    /// Checks that a chunk belongs to these query parameters, i.e. that
+    /// it only holds `h` and `l` elements and that nothing which
+    /// contributions leave unchanged differs.
+    fn ensure_matches_query(&self, chunk: &Self) -> Result<()> {
+        ensure_unchanged(&self.cs_hash[..], &chunk.cs_hash[..], InvariantKind::CsHash)?;
+        ensure_unchanged(
+            self.params.vk.alpha_g1,
+            chunk.params.vk.alpha_g1,
+            InvariantKind::AlphaG1,
+        )?;
+        ensure_unchanged(self.params.beta_g1, chunk.params.beta_g1, InvariantKind::BetaG1)?;
+        ensure_unchanged(self.params.vk.beta_g2, chunk.params.vk.beta_g2, InvariantKind::BetaG2)?;
+        ensure_unchanged(
+            self.params.vk.gamma_g2,
+            chunk.params.vk.gamma_g2,
+            InvariantKind::GammaG2,
+        )?;
+        ensure_unchanged_vec(
+            &self.params.vk.gamma_abc_g1,
+            &chunk.params.vk.gamma_abc_g1,
+            &InvariantKind::GammaAbcG1,
+        )?;
+        if !chunk.params.a_query.is_empty()
+            || !chunk.params.b_g1_query.is_empty()
+            || !chunk.params.b_g2_query.is_empty()
+        {
+            return Err(Phase2Error::InvalidLength.into());
+        }
+
+        // the query parameters are taken before any chunked contribution
+        ensure_unchanged(
+            &self.contributions[..],
+            &chunk.contributions[0..std::cmp::min(self.contributions.len(), chunk.contributions.len())],
+            InvariantKind::Contributions,
+        )
+    }
+
+    /// Checks that the chunks hold an `l` element for each of the private
+    /// variables, which are the ones in the `a` query without a `gamma_abc` element,
+    /// and an `h` element for each power of the evaluation domain but the last.
+    /// The domain has a power of 2 size, at least the number of variables, so
+    /// that chunks holding only `h` elements cannot be dropped from the end.
+    pub fn ensure_complete_queries(&self, h_query_len: usize, l_query_len: usize) -> Result<()> {
+        let num_private = self
+            .params
+            .a_query
+            .len()
+            .saturating_sub(self.params.vk.gamma_abc_g1.len());
+        if l_query_len != num_private {
+            return Err(Phase2Error::InvalidLength.into());
+        }
+        if !is_domain_size(h_query_len + 1, self.params.a_query.len()) {
+            return Err(Phase2Error::InvalidLength.into());
+        }
+        Ok(())
+    }
+
     /// Serialize these parameters. The serialized parameters
     /// can be read by snarkVM's Groth16 `ProvingKey`.
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
@@ -284,6 +604,106 @@ impl<E: PairingEngine> MPCParameters<E> {
         Ok(())
     }
 
+    /// Serialize these parameters like `write`, streaming them through a
+    /// buffer to the writer, e.g. a file or a memory map.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<()> {
+        let params = &self.params;
+        self.write_queries(writer, Queries {
+            a_query: &params.a_query,
+            b_g1_query: &params.b_g1_query,
+            b_g2_query: &params.b_g2_query,
+            h_query: &params.h_query,
+            l_query: &params.l_query,
+        })
+    }
+
+    /// Serialize the query parameters `split` returns, without copying the
+    /// queries out of these parameters.
+    pub fn write_query_to<W: Write>(&self, writer: W) -> Result<()> {
+        let params = &self.params;
+        self.write_queries(writer, Queries {
+            a_query: &params.a_query,
+            b_g1_query: &params.b_g1_query,
+            b_g2_query: &params.b_g2_query,
+            h_query: &[],
+            l_query: &[],
+        })
+    }
+
+    /// The number of elements of the H and L queries of the i-th chunk `split` returns.
+    pub fn chunk_points(&self, chunk_size: usize, i: usize) -> usize {
+        let queries = self.chunk_queries(chunk_size, i);
+        queries.h_query.len() + queries.l_query.len()
+    }
+
+    /// Serialize the i-th chunk `split` returns, without copying the queries
+    /// out of these parameters, so that the chunks can be written one at a
+    /// time (or in parallel) without holding all of them in memory.
+    pub fn write_chunk_to<W: Write>(&self, chunk_size: usize, i: usize, writer: W) -> Result<()> {
+        let num_chunks = self.num_chunks(chunk_size)?;
+        if i >= num_chunks {
+            return Err(Phase2Error::InvalidChunkIndex(i, num_chunks).into());
+        }
+        self.write_queries(writer, self.chunk_queries(chunk_size, i))
+    }
+
+    /// Serialize these parameters with the given queries instead of their own,
+    /// in the layout of `write`.
+    fn write_queries<W: Write>(&self, writer: W, queries: Queries<'_, E>) -> Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        self.params.vk.serialize(&mut writer)?;
+        self.params.beta_g1.serialize(&mut writer)?;
+        self.params.delta_g1.serialize(&mut writer)?;
+        write_slice(queries.a_query, &mut writer)?;
+        write_slice(queries.b_g1_query, &mut writer)?;
+        write_slice(queries.b_g2_query, &mut writer)?;
+        write_slice(queries.h_query, &mut writer)?;
+        write_slice(queries.l_query, &mut writer)?;
+        writer.write_all(&self.cs_hash)?;
+        PublicKey::write_batch(&mut writer, &self.contributions)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Deserialize these parameters from a buffer, e.g. a memory map of a file, checking
+    /// the elements of the queries with `check`. The queries are deserialized in parallel
+    /// straight from the buffer, so a file does not need to be read into memory first.
+    /// The `a` and `b` queries may contain the point at infinity, so they are never
+    /// checked to be non-zero.
+    pub fn read_from_buffer(buffer: &[u8], check: CheckForCorrectness) -> Result<MPCParameters<E>> {
+        let reader = &mut &buffer[..];
+        let vk = VerifyingKey::deserialize(reader)?;
+        let beta_g1 = reader.read_element(UseCompression::Yes, check)?;
+        let delta_g1 = reader.read_element(UseCompression::Yes, check)?;
+        let relaxed = allow_zero(check);
+        let a_query = read_query(reader, relaxed)?;
+        let b_g1_query = read_query(reader, relaxed)?;
+        let b_g2_query = read_query(reader, relaxed)?;
+        let h_query = read_query(reader, check)?;
+        let l_query = read_query(reader, check)?;
+
+        let mut cs_hash = [0u8; 64];
+        reader.read_exact(&mut cs_hash)?;
+
+        let contributions = PublicKey::read_batch(reader)?;
+
+        Ok(MPCParameters {
+            params: ProvingKey {
+                vk,
+                beta_g1,
+                delta_g1,
+                a_query,
+                b_g1_query,
+                b_g2_query,
+                h_query,
+                l_query,
+            },
+            cs_hash,
+            contributions,
+        })
+    }
+
     /// Deserialize these parameters.
     pub fn read<R: Read>(mut reader: R) -> Result<MPCParameters<E>> {
         let params = ProvingKey::deserialize(&mut reader)?;
@@ -301,6 +721,53 @@ impl<E: PairingEngine> MPCParameters<E> {
     }
 }
 
+/// The queries of serialized parameters, borrowed so that the query parameters
+/// and the chunks are written without copying them out of the full parameters.
+struct Queries<'a, E: PairingEngine> {
+    a_query: &'a [E::G1Affine],
+    b_g1_query: &'a [E::G1Affine],
+    b_g2_query: &'a [E::G2Affine],
+    h_query: &'a [E::G1Affine],
+    l_query: &'a [E::G1Affine],
+}
+
+/// Returns `true` if the given size is the size of an evaluation domain of a circuit with the given
+/// number of variables: a power of 2, at least the number of variables.
+fn is_domain_size(size: usize, num_variables: usize) -> bool {
+    size.is_power_of_two() && size >= num_variables
+}
+
+/// Returns the slice of a query in the i-th chunk, which is empty once the query runs out.
+fn chunk_slice<T>(query: &[T], chunk_size: usize, i: usize) -> &[T] {
+    let start = std::cmp::min(i * chunk_size, query.len());
+    let end = std::cmp::min(start + chunk_size, query.len());
+    &query[start..end]
+}
+
+/// Deserializes the elements written by `write_slice` from the start of the buffer,
+/// advancing it past them.
+fn read_query<C: AffineCurve>(buffer: &mut &[u8], check: CheckForCorrectness) -> Result<Vec<C>> {
+    let len = u64::deserialize(buffer)? as usize;
+    let size = buffer_size::<C>(UseCompression::Yes);
+    // a corrupted length must not overflow into one which fits in the buffer
+    let bytes = match len.checked_mul(size) {
+        Some(bytes) if bytes <= buffer.len() => bytes,
+        _ => return Err(Phase2Error::InvalidLength.into()),
+    };
+    let (query, rest) = buffer.split_at(bytes);
+    *buffer = rest;
+    query.read_batch(UseCompression::Yes, check)
+}
+
+/// Serializes the elements like a `Vec` of them, prefixed by their number.
+fn write_slice<T: CanonicalSerialize, W: Write>(elements: &[T], writer: &mut W) -> Result<()> {
+    (elements.len() as u64).serialize(writer)?;
+    for element in elements {
+        element.serialize(writer)?;
+    }
+    Ok(())
+}
+
 /// This is a cheap helper utility that exists purely
 /// because Rust still doesn't have type-level integers
 /// and so doesn't implement `PartialEq` for `[T; 64]`
@@ -340,10 +807,63 @@ pub fn ensure_unchanged<T: PartialEq>(before: T, after: T, kind: InvariantKind)
     Ok(())
 }
 
+/// Checks that none of the elements is the point at infinity, reporting the index of the first
+/// one which is. `offset` is the index of the first element, for elements checked in batches.
+pub fn ensure_no_identity<C: AffineCurve>(elements: &[C], offset: usize, kind: &InvariantKind) -> Result<()> {
+    match elements.iter().position(|element| element.is_zero()) {
+        Some(i) => Err(Phase2Error::IdentityElement(kind.clone(), offset + i).into()),
+        None => Ok(()),
+    }
+}
+
 pub fn verify_transcript<E: PairingEngine>(cs_hash: [u8; 64], contributions: &[PublicKey<E>]) -> Result<Vec<[u8; 64]>> {
+    verify_contributions(cs_hash, contributions, 0, E::G1Affine::prime_subgroup_generator())
+}
+
+/// Verifies the transcript like `verify_transcript`, but only checks that the
+/// first `checkpoint` contributions are the ones the checkpoint was taken of:
+/// the last of them must hash to `checkpoint_hash`, and its transcript hash,
+/// which covers all the contributions before it, must be consistent with them.
+pub fn verify_transcript_from<E: PairingEngine>(
+    cs_hash: [u8; 64],
+    contributions: &[PublicKey<E>],
+    checkpoint: usize,
+    checkpoint_hash: &[u8; 64],
+) -> Result<Vec<[u8; 64]>> {
+    if checkpoint == 0 || checkpoint > contributions.len() {
+        return Err(Phase2Error::InvalidCheckpoint(checkpoint, contributions.len()).into());
+    }
+    let pubkey = &contributions[checkpoint - 1];
+    ensure_unchanged(&pubkey.hash()[..], &checkpoint_hash[..], InvariantKind::Contributions)?;
+    let hash = hash_cs_pubkeys(cs_hash, &contributions[0..checkpoint - 1], pubkey.s, pubkey.s_delta);
+    ensure_unchanged(&pubkey.transcript[..], &hash.as_ref()[..], InvariantKind::Transcript)?;
+
+    let mut result: Vec<[u8; 64]> = contributions[0..checkpoint].iter().map(PublicKey::hash).collect();
+    result.extend(verify_contributions(
+        cs_hash,
+        contributions,
+        checkpoint,
+        pubkey.delta_after,
+    )?);
+    Ok(result)
+}
+
+/// Verifies the contributions from index `start` on, where `old_delta` is the delta
+/// after the contribution before it, and returns their hashes.
+fn verify_contributions<E: PairingEngine>(
+    cs_hash: [u8; 64],
+    contributions: &[PublicKey<E>],
+    start: usize,
+    mut old_delta: E::G1Affine,
+) -> Result<Vec<[u8; 64]>> {
     let mut result = vec![];
-    let mut old_delta = E::G1Affine::prime_subgroup_generator();
-    for (i, pubkey) in contributions.iter().enumerate() {
+    for (i, pubkey) in contributions.iter().enumerate().skip(start) {
+        // The pairing checks below hold trivially for the point at infinity
+        let g1 = [pubkey.delta_after, pubkey.s, pubkey.s_delta];
+        if g1.iter().any(|element| element.is_zero()) || pubkey.r_delta.is_zero() {
+            return Err(Phase2Error::IdentityElement(InvariantKind::Contributions, i).into());
+        }
+
         let hash = hash_cs_pubkeys(cs_hash, &contributions[0..i], pubkey.s, pubkey.s_delta);
         ensure_unchanged(&pubkey.transcript[..], &hash.as_ref()[..], InvariantKind::Transcript)?;
 
@@ -396,13 +916,9 @@ pub fn circuit_to_qap<E: PairingEngine, Zexe: PairingEngine, C: ConstraintSynthe
     };
 
     // Allocate the "one" input variable
-    assembly
-        .alloc_input(|| "", || Ok(E::Fr::one()))
-        .expect("One allocation should not fail");
+    assembly.alloc_input(|| "", || Ok(E::Fr::one()))?;
     // Synthesize the circuit.
-    circuit
-        .generate_constraints(&mut assembly)
-        .expect("constraint generation should not fail");
+    circuit.generate_constraints(&mut assembly)?;
     // Input constraints to ensure full density of IC query
     // x * 0 = 0
     for i in 0..assembly.num_public_variables {
@@ -419,26 +935,43 @@ pub fn circuit_to_qap<E: PairingEngine, Zexe: PairingEngine, C: ConstraintSynthe
     // (This could alternatively be done with unsafe memory swapping, but we
     // prefer to err on the side of caution)
     let mut serialized = Vec::new();
-    assembly
-        .serialize(&mut serialized)
-        .expect("serializing the KeypairAssembly should not fail");
+    assembly.serialize(&mut serialized)?;
     let assembly = KeypairAssembly::<Zexe>::deserialize(&mut &serialized[..])?;
 
     Ok(assembly)
 }
 
+/// Returns the digest of a circuit in QAP form. Unlike the `cs_hash`, it does not
+/// depend on the Phase 1 parameters, so it identifies the circuit on its own.
+pub fn qap_digest<E: PairingEngine>(assembly: &KeypairAssembly<E>) -> Result<[u8; 64]> {
+    let mut serialized = Vec::new();
+    assembly.serialize(&mut serialized)?;
+    let mut digest = [0; 64];
+    digest.copy_from_slice(calculate_hash(&serialized).as_ref());
+    Ok(digest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        chunked_groth16::{contribute, verify},
+        chunked_groth16::{
+            contribute,
+            contribute_cancellable,
+            contribute_resumable,
+            verify,
+            verify_cancellable,
+            verify_with_progress,
+            ContributionCheckpoint,
+        },
         helpers::testing::TestCircuit,
     };
     use phase1::{helpers::testing::setup_verify, Phase1, Phase1Parameters, ProvingSystem};
     use setup_utils::{Groth16Params, UseCompression};
     use snarkvm_curves::bls12_377::Bls12_377;
+    use snarkvm_utilities::ConstantSerializedSize;
 
-    use rand::thread_rng;
+    use rand::{rngs::StdRng, thread_rng, SeedableRng};
     use tracing_subscriber::{filter::EnvFilter, fmt::Subscriber};
 
     #[test]
@@ -457,6 +990,38 @@ mod tests {
         assert_eq!(deserialized, mpc)
     }
 
+    #[test]
+    fn read_from_buffer() {
+        let mut mpc = generate_ceremony::<Bls12_377, Bls12_377>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+
+        for check in &[CheckForCorrectness::Full, CheckForCorrectness::No] {
+            let read = MPCParameters::<Bls12_377>::read_from_buffer(&serialized, *check).unwrap();
+            assert_eq!(read, mpc);
+        }
+
+        // a point at infinity in the h query is only let through without checks
+        let mut zeroed = mpc.clone();
+        zeroed.params.h_query[0] = <Bls12_377 as PairingEngine>::G1Affine::zero();
+        let mut serialized = vec![];
+        zeroed.write(&mut serialized).unwrap();
+        assert!(MPCParameters::<Bls12_377>::read_from_buffer(&serialized, CheckForCorrectness::Full).is_err());
+        MPCParameters::<Bls12_377>::read_from_buffer(&serialized, CheckForCorrectness::No).unwrap();
+
+        // and a truncated buffer is rejected
+        assert!(MPCParameters::<Bls12_377>::read_from_buffer(&serialized[..100], CheckForCorrectness::No).is_err());
+
+        // as is a query whose length overflows the size of its elements
+        let mut corrupted = vec![];
+        u64::MAX.serialize(&mut corrupted).unwrap();
+        corrupted.extend_from_slice(&serialized[..100]);
+        type G1 = <Bls12_377 as PairingEngine>::G1Affine;
+        let err = read_query::<G1>(&mut &corrupted[..], CheckForCorrectness::No).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::InvalidLength)));
+    }
+
     #[test]
     fn verify_with_self_fails() {
         verify_with_self_fails_curve::<Bls12_377, Bls12_377>()
@@ -542,14 +1107,411 @@ mod tests {
         contribution2.verify(&contribution3).unwrap();
     }
 
+    #[test]
+    fn verify_final() {
+        verify_final_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // the final parameters verify on their own, and tampering with the verifying key is detected
+    fn verify_final_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        assert!(mpc.verify_final().is_err());
+
+        let before = mpc.clone();
+        mpc.contribute(rng).unwrap();
+        mpc.contribute(rng).unwrap();
+        let hashes = mpc.verify_final().unwrap();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(&hashes[..], &before.verify(&mpc).unwrap()[..]);
+
+        let mut tampered = mpc.clone();
+        tampered.params.vk.delta_g2 = before.params.vk.delta_g2;
+        assert!(tampered.verify_final().is_err());
+    }
+
+    #[test]
+    fn verify_final_from_checkpoint() {
+        verify_final_from_checkpoint_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // verification resumes after a checkpoint, but only of the contributions it was taken of
+    fn verify_final_from_checkpoint_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        for _ in 0..3 {
+            mpc.contribute(rng).unwrap();
+        }
+        let hashes = mpc.verify_final().unwrap();
+        assert_eq!(mpc.verify_final_from(2, &hashes[1]).unwrap(), hashes);
+        assert_eq!(mpc.verify_final_from(3, &hashes[2]).unwrap(), hashes);
+
+        assert!(mpc.verify_final_from(2, &hashes[0]).is_err());
+        assert!(mpc.verify_final_from(0, &hashes[0]).is_err());
+        assert!(mpc.verify_final_from(4, &hashes[2]).is_err());
+
+        // a contribution after the checkpoint is still checked
+        let mut tampered = mpc.clone();
+        tampered.contributions[2].transcript = [0; 64];
+        assert!(tampered.verify_final_from(2, &hashes[1]).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_identity_elements() {
+        verify_rejects_identity_elements_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // identity elements pass the ratio checks, so they are rejected explicitly
+    fn verify_rejects_identity_elements_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut contribution = mpc.clone();
+        contribution.contribute(&mut thread_rng()).unwrap();
+        mpc.verify(&contribution).unwrap();
+
+        let mut degenerate = contribution.clone();
+        degenerate.params.h_query[1] = E::G1Affine::zero();
+        let err = mpc.verify(&degenerate).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::IdentityElement(InvariantKind::HQuery, 1))
+        ));
+
+        // the chunked verifier reports the index across batches
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+        let mut after = vec![];
+        degenerate.write(&mut after).unwrap();
+        let err = verify::<E>(&mut before, &mut after, 1).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::IdentityElement(InvariantKind::HQuery, 1))
+        ));
+
+        // a contribution whose signature of knowledge is the identity
+        let mut degenerate = contribution;
+        degenerate.contributions[0].s = E::G1Affine::zero();
+        degenerate.contributions[0].s_delta = E::G1Affine::zero();
+        let err = mpc.verify(&degenerate).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::IdentityElement(InvariantKind::Contributions, 0))
+        ));
+    }
+
+    #[test]
+    fn verify_chunked() {
+        verify_chunked_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // contributing to every chunk with the same randomness matches contributing to the full parameters
+    fn verify_chunked_curve<Aleo: PairingEngine, E: PairingEngine + PartialEq>() {
+        let mut mpc = generate_ceremony::<Aleo, E>();
+        assert!(mpc.split(0).is_err());
+        let (query, before) = mpc.split(3).unwrap();
+        assert!(before.len() > 1);
+        assert!(query.params.h_query.is_empty() && query.params.l_query.is_empty());
+        assert_eq!(MPCParameters::combine(&query, &before).unwrap(), mpc);
+
+        let seed = thread_rng().gen::<[u8; 32]>();
+        let mut after = before.clone();
+        for chunk in after.iter_mut() {
+            chunk.contribute(&mut StdRng::from_seed(seed)).unwrap();
+        }
+        mpc.contribute(&mut StdRng::from_seed(seed)).unwrap();
+
+        let pairs = || before.iter().cloned().zip(after.iter().cloned()).map(Ok);
+        let hashes = query.verify_chunked(pairs()).unwrap();
+        assert_eq!(hashes.len(), 1);
+        assert_eq!(MPCParameters::combine(&query, &after).unwrap(), mpc);
+
+        // a missing chunk is detected, even if it only holds h elements
+        assert!(before.last().unwrap().params.l_query.is_empty());
+        assert!(query.verify_chunked(pairs().skip(1)).is_err());
+        assert!(query.verify_chunked(pairs().take(before.len() - 1)).is_err());
+        assert!(MPCParameters::combine(&query, &before[..before.len() - 1]).is_err());
+        assert!(query.verify_chunked(std::iter::empty()).is_err());
+
+        // chunks contributed to with different randomness do not verify together
+        let mut inconsistent = before.clone();
+        for chunk in inconsistent.iter_mut() {
+            chunk.contribute(&mut thread_rng()).unwrap();
+        }
+        let pairs = before.iter().cloned().zip(inconsistent.iter().cloned()).map(Ok);
+        assert!(query.verify_chunked(pairs).is_err());
+    }
+
+    #[test]
+    fn split_into_single_chunk() {
+        // a chunk size larger than the queries of a tiny circuit gives one chunk with all of them
+        let mpc = generate_ceremony::<Bls12_377, Bls12_377>();
+        let chunk_size = mpc.max_chunk_size();
+        assert_eq!(mpc.num_chunks(chunk_size).unwrap(), 1);
+        assert_eq!(mpc.num_chunks(chunk_size * 1000).unwrap(), 1);
+        assert_eq!(mpc.num_chunks(chunk_size - 1).unwrap(), 2);
+
+        let (query, before) = mpc.split(chunk_size * 1000).unwrap();
+        assert_eq!(before.len(), 1);
+        assert_eq!(before[0].params.h_query, mpc.params.h_query);
+        assert_eq!(before[0].params.l_query, mpc.params.l_query);
+
+        let seed = thread_rng().gen::<[u8; 32]>();
+        let mut after = before.clone();
+        after[0].contribute(&mut StdRng::from_seed(seed)).unwrap();
+        let mut full = mpc.clone();
+        full.contribute(&mut StdRng::from_seed(seed)).unwrap();
+
+        let pairs = before.into_iter().zip(after.iter().cloned()).map(Ok);
+        assert_eq!(query.verify_chunked(pairs).unwrap().len(), 1);
+        assert_eq!(MPCParameters::combine(&query, &after).unwrap(), full);
+    }
+
+    #[test]
+    fn read_contributions_skips_queries() {
+        let mut mpc = generate_ceremony::<Bls12_377, Bls12_377>();
+        mpc.contribute(&mut thread_rng()).unwrap();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+
+        let (cs_hash, contributions) = crate::chunked_groth16::read_contributions::<Bls12_377>(&serialized).unwrap();
+        assert_eq!(&cs_hash[..], &mpc.cs_hash[..]);
+        assert_eq!(contributions, mpc.contributions);
+    }
+
+    #[test]
+    fn query_points_counts_h_and_l() {
+        let mpc = generate_ceremony::<Bls12_377, Bls12_377>();
+        let mut serialized = vec![];
+        mpc.write(&mut serialized).unwrap();
+
+        let points = mpc.params.h_query.len() + mpc.params.l_query.len();
+        assert_eq!(
+            crate::chunked_groth16::query_points::<Bls12_377>(&serialized).unwrap(),
+            points
+        );
+
+        // the chunks hold every point once
+        let chunk_size = 2;
+        let num_chunks = mpc.num_chunks(chunk_size).unwrap();
+        let chunk_points: usize = (0..num_chunks).map(|i| mpc.chunk_points(chunk_size, i)).sum();
+        assert_eq!(chunk_points, points);
+    }
+
+    #[test]
+    fn verify_reports_progress_per_batch() {
+        let mpc = generate_ceremony::<Bls12_377, Bls12_377>();
+        let mut before = vec![];
+        mpc.write(&mut before).unwrap();
+        let mut after = before.clone();
+        after.resize(after.len() + PublicKey::<Bls12_377>::size(), 0);
+        contribute::<Bls12_377, _>(&mut after, &mut thread_rng(), 3).unwrap();
+
+        // every batch of the h and l queries is reported, including the leftovers
+        let batches = std::sync::Mutex::new(vec![]);
+        verify_with_progress::<Bls12_377>(
+            &mut before,
+            &mut after,
+            3,
+            CheckForCorrectness::No,
+            &CancellationToken::new(),
+            &|points| batches.lock().unwrap().push(points),
+        )
+        .unwrap();
+        let batches = batches.into_inner().unwrap();
+        let points = mpc.params.h_query.len() + mpc.params.l_query.len();
+        assert_eq!(batches.iter().sum::<usize>(), points);
+        assert!(batches.len() > 2);
+        assert!(batches.iter().all(|batch| *batch <= 3));
+    }
+
+    #[test]
+    fn write_to_streams_split() {
+        let mpc = generate_ceremony::<Bls12_377, Bls12_377>();
+        let serialize = |params: &MPCParameters<Bls12_377>| {
+            let mut serialized = vec![];
+            params.write(&mut serialized).unwrap();
+            serialized
+        };
+
+        let mut streamed = vec![];
+        mpc.write_to(&mut streamed).unwrap();
+        assert_eq!(streamed, serialize(&mpc));
+
+        let (query, chunks) = mpc.split(3).unwrap();
+        let mut streamed = vec![];
+        mpc.write_query_to(&mut streamed).unwrap();
+        assert_eq!(streamed, serialize(&query));
+
+        assert_eq!(mpc.num_chunks(3).unwrap(), chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut streamed = vec![];
+            mpc.write_chunk_to(3, i, &mut streamed).unwrap();
+            assert_eq!(streamed, serialize(chunk));
+        }
+        assert!(mpc.write_chunk_to(3, chunks.len(), &mut vec![]).is_err());
+    }
+
+    #[test]
+    fn qap_digest_is_deterministic() {
+        let digest = |c| qap_digest(&circuit_to_qap::<Bls12_377, Bls12_377, _>(c).unwrap()).unwrap();
+        assert_eq!(
+            &digest(TestCircuit::<Bls12_377>(None))[..],
+            &digest(TestCircuit::<Bls12_377>(None))[..]
+        );
+    }
+
+    #[test]
+    fn cancelled_contribution() {
+        cancelled_contribution_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // a cancelled contribution errors out and leaves the buffer untouched
+    fn cancelled_contribution_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let rng = &mut thread_rng();
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut mpc_serialized = vec![];
+        mpc.write(&mut mpc_serialized).unwrap();
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let mut buf = mpc_serialized.clone();
+        buf.resize(buf.len() + PublicKey::<E>::size(), 0);
+        let untouched = buf.clone();
+        let err = contribute_cancellable::<E, _>(&mut buf, rng, 4, &cancellation).unwrap_err();
+        assert!(matches!(err, Error::Cancelled));
+        assert_eq!(buf, untouched);
+
+        // a contribution with an expired deadline does not verify either
+        let mut c1_serialized = mpc_serialized.clone();
+        c1_serialized.resize(c1_serialized.len() + PublicKey::<E>::size(), 0);
+        contribute::<E, _>(&mut c1_serialized, rng, 4).unwrap();
+        let expired = CancellationToken::with_deadline(std::time::Instant::now());
+        let err = verify_cancellable::<E>(
+            &mut mpc_serialized,
+            &mut c1_serialized,
+            4,
+            CheckForCorrectness::No,
+            &expired,
+        ).unwrap_err();
+        assert!(matches!(err, Error::DeadlineExceeded));
+    }
+
+    #[test]
+    fn resumed_contribution() {
+        resumed_contribution_curve::<Bls12_377, Bls12_377>()
+    }
+
+    // a contribution interrupted after a checkpoint and resumed from it with the same
+    // randomness gives the same parameters as one which was not interrupted, even if
+    // the batch after the checkpoint was already multiplied
+    fn resumed_contribution_curve<Aleo: PairingEngine, E: PairingEngine>() {
+        let mpc = generate_ceremony::<Aleo, E>();
+        let mut mpc_serialized = vec![];
+        mpc.write(&mut mpc_serialized).unwrap();
+        mpc_serialized.resize(mpc_serialized.len() + PublicKey::<E>::size(), 0);
+        let seed = [7u8; 32];
+        let none = CancellationToken::new();
+
+        let mut expected = mpc_serialized.clone();
+        let hash = contribute::<E, _>(&mut expected, &mut StdRng::from_seed(seed), 1).unwrap();
+
+        // interrupt the contribution after its third batch, keeping the checkpoint before it
+        let mut interrupted = mpc_serialized.clone();
+        let mut buf = &mut interrupted[..];
+        let mut checkpoints = vec![];
+        let err = contribute_resumable::<E, _, _, _>(
+            &mut buf,
+            &mut StdRng::from_seed(seed),
+            1,
+            CheckForCorrectness::Full,
+            &none,
+            None,
+            |_, progress| {
+                checkpoints.push(progress.clone());
+                match checkpoints.len() {
+                    4 => Err(Error::Cancelled),
+                    _ => Ok(()),
+                }
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Cancelled));
+        let last = checkpoints[2].clone();
+        assert_eq!((last.h_done, last.l_done), (2, 2));
+        assert_eq!(last.pending.len(), 2 * E::G1Affine::SERIALIZED_SIZE);
+
+        // resuming it completes the same contribution
+        let mut last_checkpoint = None;
+        let resumed = contribute_resumable::<E, _, _, _>(
+            &mut buf,
+            &mut StdRng::from_seed(seed),
+            1,
+            CheckForCorrectness::Full,
+            &none,
+            Some(last.clone()),
+            |_, progress| {
+                last_checkpoint = Some(progress.clone());
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(&resumed[..], &hash[..]);
+        assert_eq!(&buf[..], &expected[..]);
+        verify::<E>(&mut mpc_serialized, &mut expected.clone(), 4).unwrap();
+
+        // so does resuming a contribution which was interrupted after its last checkpoint
+        contribute_resumable::<E, _, _, _>(
+            &mut buf,
+            &mut StdRng::from_seed(seed),
+            1,
+            CheckForCorrectness::Full,
+            &none,
+            last_checkpoint,
+            |_, _| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(&buf[..], &expected[..]);
+
+        // but not one resumed on other parameters
+        let mut other = mpc_serialized.clone();
+        let mismatched = ContributionCheckpoint { contributions: 5, ..last };
+        let err = contribute_resumable::<E, _, _, _>(
+            &mut &mut other[..],
+            &mut StdRng::from_seed(seed),
+            1,
+            CheckForCorrectness::Full,
+            &none,
+            Some(mismatched),
+            |_, _| Ok(()),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Phase2Error(Phase2Error::ContributionCheckpointMismatch)
+        ));
+    }
+
+    #[test]
+    fn rejects_phase2_size_not_power_of_two() {
+        // the circuit fits in 7 powers, but they are not an evaluation domain
+        let err = try_generate_ceremony::<Bls12_377, Bls12_377>(7).unwrap_err();
+        assert!(matches!(err, Error::Phase2Error(Phase2Error::InvalidPhase2Size(7, _))));
+        assert!(try_generate_ceremony::<Bls12_377, Bls12_377>(8).is_ok());
+    }
+
     // helper which generates the initial phase 2 params
     // for the TestCircuit
     fn generate_ceremony<Aleo: PairingEngine, E: PairingEngine>() -> MPCParameters<E> {
+        // this circuit requires 7 constraints, so a ceremony with size 8 is sufficient,
+        // as the size must be a power of 2
+        try_generate_ceremony::<Aleo, E>(8).unwrap()
+    }
+
+    fn try_generate_ceremony<Aleo: PairingEngine, E: PairingEngine>(phase2_size: usize) -> Result<MPCParameters<E>> {
         // the phase2 params are generated correctly,
         // even though the powers of tau are >> the circuit size
         let powers = 5;
         let batch = 16;
-        let phase2_size = 7;
         let params = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, powers, batch);
         let accumulator = {
             let compressed = UseCompression::No;
@@ -567,10 +1529,9 @@ mod tests {
         )
         .unwrap();
 
-        // this circuit requires 7 constraints, so a ceremony with size 8 is sufficient
         let c = TestCircuit::<Aleo>(None);
         let assembly = circuit_to_qap::<Aleo, E, _>(c).unwrap();
 
-        MPCParameters::new(assembly, groth_params).unwrap()
+        MPCParameters::new(assembly, groth_params)
     }
 }
@@ -0,0 +1,97 @@
+use crate::{Error, Result};
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A handle which allows an embedding application to abort a long-running
+/// operation (generation, contribution or verification), either explicitly
+/// via `cancel` or implicitly once an optional deadline has passed.
+///
+/// Clones share the same cancellation flag, so a clone may be handed to another
+/// thread which is then able to cancel the operation.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Returns a token which is only cancelled by calling `cancel`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a token which is additionally cancelled once `deadline` has passed.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Returns a token which is additionally cancelled once `timeout` has elapsed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Requests cancellation of every operation observing this token or one of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if `cancel` has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns the deadline of this token, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns `Error::Cancelled` if the token was cancelled, `Error::DeadlineExceeded`
+    /// if its deadline has passed, and `Ok(())` otherwise.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(Error::DeadlineExceeded),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        assert!(token.check().is_ok());
+
+        // Cancelling a clone cancels the original.
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_cancellation_token_deadline() {
+        let token = CancellationToken::with_timeout(Duration::from_secs(3600));
+        assert!(token.check().is_ok());
+
+        let token = CancellationToken::with_deadline(Instant::now());
+        assert!(matches!(token.check(), Err(Error::DeadlineExceeded)));
+
+        // Explicit cancellation takes precedence over the deadline.
+        token.cancel();
+        assert!(matches!(token.check(), Err(Error::Cancelled)));
+    }
+}
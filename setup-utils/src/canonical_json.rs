@@ -0,0 +1,192 @@
+//! Canonical JSON encoding in the style of RFC 8785 (the JSON Canonicalization Scheme).
+//!
+//! Every payload which is signed is encoded this way, so that a signature only depends on
+//! the content of the payload and not on the serializer which produced it: object members
+//! are sorted by the UTF-16 code units of their names, no whitespace is written, strings only
+//! escape what JSON requires, and floating point numbers are written like ECMAScript's
+//! `Number.prototype.toString`. Unlike RFC 8785, integers are written exactly, even beyond 2^53.
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// Returns the canonical JSON encoding of the given value.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    let mut json = String::new();
+    write_value(&mut json, &serde_json::to_value(value)?);
+    Ok(json)
+}
+
+/// Returns the canonical JSON encoding of the given JSON document, as received from a signer.
+///
+/// Signatures must be checked against this encoding rather than the received bytes, which
+/// may have been reformatted since they were signed.
+pub fn canonicalize_json(json: &[u8]) -> serde_json::Result<String> {
+    to_canonical_json(&serde_json::from_slice::<Value>(json)?)
+}
+
+fn write_value(json: &mut String, value: &Value) {
+    match value {
+        Value::Null => json.push_str("null"),
+        Value::Bool(value) => json.push_str(if *value { "true" } else { "false" }),
+        Value::Number(number) => write_number(json, number),
+        Value::String(string) => write_string(json, string),
+        Value::Array(values) => {
+            json.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_value(json, value);
+            }
+            json.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<_> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            json.push('{');
+            for (i, (name, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_string(json, name);
+                json.push(':');
+                write_value(json, value);
+            }
+            json.push('}');
+        }
+    }
+}
+
+fn write_string(json: &mut String, string: &str) {
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn write_number(json: &mut String, number: &Number) {
+    if let Some(number) = number.as_u64() {
+        json.push_str(&number.to_string());
+    } else if let Some(number) = number.as_i64() {
+        json.push_str(&number.to_string());
+    } else if let Some(number) = number.as_f64() {
+        write_float(json, number);
+    }
+}
+
+/// Writes a finite float as ECMAScript would, from the shortest digits which round trip.
+fn write_float(json: &mut String, number: f64) {
+    // This also covers negative zero, which is written as 0
+    if number == 0.0 {
+        json.push('0');
+        return;
+    }
+    if number < 0.0 {
+        json.push('-');
+    }
+
+    // The shortest digits `d` and the exponent `n` such that the number is 0.d * 10^n
+    let scientific = format!("{:e}", number.abs());
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').expect("exponent of a float"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent[1..].parse::<i32>().expect("exponent of a float") + 1;
+
+    if k <= n && n <= 21 {
+        json.push_str(&digits);
+        json.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        json.push_str(&digits[..n as usize]);
+        json.push('.');
+        json.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        json.push_str("0.");
+        json.extend(std::iter::repeat('0').take(-n as usize));
+        json.push_str(&digits);
+    } else {
+        json.push_str(&digits[..1]);
+        if k > 1 {
+            json.push('.');
+            json.push_str(&digits[1..]);
+        }
+        json.push('e');
+        json.push(if n > 0 { '+' } else { '-' });
+        json.push_str(&(n - 1).abs().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn test_members_are_sorted_by_utf16() {
+        // The example of RFC 8785, section 3.2.3, where the emoji sorts before U+FB33 in UTF-16 but not in UTF-8
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{80}": "Control",
+            "\u{f6}": "Latin Small Letter O With Diaeresis",
+        });
+        // Only the carriage return is escaped, the other names are written as they are
+        let expected = concat!(
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",",
+            "\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",",
+            "\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+        assert_eq!(to_canonical_json(&value).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        let value = json!({ "b": [1, -2, null, true], "a": { "d": "\"\\\n\u{1f}é", "c": false } });
+        assert_eq!(
+            to_canonical_json(&value).unwrap(),
+            r#"{"a":{"c":false,"d":"\"\\\n\u001fé"},"b":[1,-2,null,true]}"#
+        );
+
+        // Reformatting a signed document does not change its canonical encoding
+        let reformatted = serde_json::to_vec_pretty(&value).unwrap();
+        assert_eq!(
+            canonicalize_json(&reformatted).unwrap(),
+            to_canonical_json(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_numbers_are_written_like_ecmascript() {
+        // The examples of RFC 8785, appendix B
+        let numbers = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (4.5, "4.5"),
+            (0.002, "0.002"),
+            (1e-7, "1e-7"),
+            (1e21, "1e+21"),
+            (1e30, "1e+30"),
+            (1e20, "100000000000000000000"),
+            (-1.5e-10, "-1.5e-10"),
+            (333333333.33333329, "333333333.3333333"),
+            (9007199254740992.0, "9007199254740992"),
+        ];
+        for (number, expected) in numbers.iter() {
+            assert_eq!(to_canonical_json(number).unwrap(), *expected);
+        }
+        assert_eq!(to_canonical_json(&u64::MAX).unwrap(), u64::MAX.to_string());
+    }
+}
@@ -33,6 +33,10 @@ pub enum Error {
     IncorrectSubgroup,
     #[error("Got invalid decompression parameters")]
     InvalidDecompressionParametersError,
+    #[error("The operation was cancelled")]
+    Cancelled,
+    #[error("The operation did not complete before its deadline")]
+    DeadlineExceeded,
 }
 
 impl From<Box<dyn std::any::Any + Send>> for Error {
@@ -51,6 +55,20 @@ pub enum Phase2Error {
     NoContributions,
     #[error("The Transcript was not consistent")]
     InvalidTranscript,
+    #[error("The chunk size must be positive")]
+    InvalidChunkSize,
+    #[error("There were no chunks found")]
+    NoChunks,
+    #[error("Chunk {0} is out of range for {1} chunks")]
+    InvalidChunkIndex(usize, usize),
+    #[error("Element {1} of {0} is the point at infinity")]
+    IdentityElement(InvariantKind, usize),
+    #[error("A checkpoint after {0} contributions is invalid for a transcript of {1} contributions")]
+    InvalidCheckpoint(usize, usize),
+    #[error("The checkpoint of the contribution does not match the parameters it is resumed on")]
+    ContributionCheckpointMismatch,
+    #[error("The phase 2 size {0} is not a power of 2 of at least the {1} variables of the circuit")]
+    InvalidPhase2Size(usize, usize),
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -63,6 +81,9 @@ pub enum InvariantKind {
     GammaAbcG1,
     GammaG2,
     DeltaG1,
+    DeltaG2,
+    HQuery,
+    LQuery,
     Transcript,
     AlphaG1Query,
     BetaG1Query,
@@ -81,6 +102,9 @@ impl fmt::Display for InvariantKind {
             InvariantKind::GammaAbcG1 => write!(f, "GammaAbcG1"),
             InvariantKind::GammaG2 => write!(f, "GammaG2"),
             InvariantKind::DeltaG1 => write!(f, "DeltaG1"),
+            InvariantKind::DeltaG2 => write!(f, "DeltaG2"),
+            InvariantKind::HQuery => write!(f, "HQuery"),
+            InvariantKind::LQuery => write!(f, "LQuery"),
             InvariantKind::Transcript => write!(f, "Transcript"),
             InvariantKind::AlphaG1Query => write!(f, "AlphaG1Query"),
             InvariantKind::BetaG1Query => write!(f, "BetaG1Query"),
@@ -99,4 +123,10 @@ pub enum VerificationError {
     #[error("Invalid generator for {0} powers")]
     /// The first power of Tau was not the generator of that group
     InvalidGenerator(ElementType),
+    #[error("Invalid VDF proof")]
+    /// The proof of the delay function did not match its input and output
+    InvalidVdfProof,
+    #[error("The VDF proof takes {got} iterations instead of the required {expected}")]
+    /// The proof of the delay function was evaluated with another delay than the required one
+    VdfIterationsMismatch { expected: u64, got: u64 },
 }
@@ -161,6 +161,15 @@ impl<E: PairingEngine> Groth16Params<E> {
         Ok(())
     }
 
+    /// Returns the size of a processed Phase 1 transcript with size `phase1_size`, as written by `write`.
+    pub fn transcript_size(phase1_size: usize, compressed: UseCompression) -> usize {
+        let g1_size = buffer_size::<E::G1Affine>(compressed);
+        let g2_size = buffer_size::<E::G2Affine>(compressed);
+        // alpha, beta, the coefficients with tau, alpha and beta, and the H query bases in G1,
+        // and beta and the coefficients with tau in G2
+        (4 * phase1_size + 1) * g1_size + (phase1_size + 1) * g2_size
+    }
+
     /// Reads the first `num_constraints` coefficients from the provided processed
     /// Phase 1 transcript with size `phase1_size`.
     pub fn read(
@@ -323,6 +332,10 @@ mod tests {
 
         let mut writer = vec![];
         groth_params.write(&mut writer, compressed).unwrap();
+        assert_eq!(
+            writer.len(),
+            Groth16Params::<E>::transcript_size(prepared_phase1_size, compressed)
+        );
         let mut reader = std::io::Cursor::new(writer);
         let deserialized = Groth16Params::<E>::read(
             &mut reader.get_mut(),
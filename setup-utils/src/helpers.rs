@@ -4,7 +4,7 @@ use crate::{
 };
 
 use snarkvm_algorithms::{cfg_into_iter, cfg_iter, cfg_iter_mut};
-use snarkvm_curves::{AffineCurve, Group, PairingEngine, ProjectiveCurve};
+use snarkvm_curves::{AffineCurve, Group, PairingCurve, PairingEngine, ProjectiveCurve};
 use snarkvm_fields::{Field, One, PrimeField, Zero};
 use snarkvm_utilities::{biginteger::BigInteger, rand::UniformRand, CanonicalSerialize, ConstantSerializedSize};
 
@@ -297,6 +297,33 @@ mod tests {
         assert!(!same_ratio::<Bls12_377>(&(g1_s, g1), &(g2, g2_s)));
     }
 
+    #[test]
+    fn test_ratio_check_batch() {
+        let rng = &mut thread_rng();
+
+        let g1 = G1Affine::prime_subgroup_generator();
+        let g2 = G2Affine::prime_subgroup_generator();
+        let mut batch = RatioCheckBatch::<Bls12_377>::new();
+        assert!(batch.verify(rng).is_ok());
+
+        for _ in 0..4 {
+            let s = Fr::rand(rng);
+            batch.push(&(g1, g1.mul(s)), &(g2, g2.mul(s)), "valid");
+        }
+        assert_eq!(batch.len(), 4);
+        assert!(batch.verify(rng).is_ok());
+
+        let s = Fr::rand(rng);
+        let mut invalid = RatioCheckBatch::<Bls12_377>::new();
+        invalid.push(&(g1.mul(s), g1), &(g2, g2.mul(s)), "invalid");
+        batch.append(&mut invalid);
+        assert!(invalid.is_empty());
+        assert!(matches!(
+            batch.verify(rng),
+            Err(Error::VerificationError(VerificationError::InvalidRatio("invalid")))
+        ));
+    }
+
     #[test]
     fn test_power_pairs() {
         use std::ops::MulAssign;
@@ -374,6 +401,76 @@ pub fn check_same_ratio<E: PairingEngine>(
     Ok(())
 }
 
+/// A batch of ratio checks, which are verified together with a single multi-pairing.
+///
+/// Each check x1/x2 = y1/y2 is scaled by a random scalar r, and the batch is valid
+/// if the product of e(r * x1, y2) * e(-r * x2, y1) over all checks is one. This
+/// replaces two full pairings per check with two Miller loops per check and a
+/// single final exponentiation, and allows batching checks across chunks.
+#[derive(Debug, Clone)]
+pub struct RatioCheckBatch<E: PairingEngine> {
+    checks: Vec<((E::G1Affine, E::G1Affine), (E::G2Affine, E::G2Affine), &'static str)>,
+}
+
+impl<E: PairingEngine> RatioCheckBatch<E> {
+    pub fn new() -> Self {
+        Self { checks: vec![] }
+    }
+
+    /// Adds a ratio check to the batch, which is equivalent to `check_same_ratio`.
+    pub fn push(&mut self, g1: &(E::G1Affine, E::G1Affine), g2: &(E::G2Affine, E::G2Affine), err: &'static str) {
+        self.checks.push((*g1, *g2, err));
+    }
+
+    /// Moves all ratio checks of `other` into this batch.
+    pub fn append(&mut self, other: &mut Self) {
+        self.checks.append(&mut other.checks);
+    }
+
+    pub fn len(&self) -> usize {
+        self.checks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checks.is_empty()
+    }
+
+    /// Verifies all ratio checks in the batch. If the batch is invalid, the checks
+    /// are verified one by one, so that the error is the one of the first invalid check.
+    pub fn verify<R: Rng>(&self, rng: &mut R) -> Result<()> {
+        if self.checks.is_empty() {
+            return Ok(());
+        }
+
+        let pairs: Vec<_> = self
+            .checks
+            .iter()
+            .flat_map(|(g1, g2, _)| {
+                let r = E::Fr::rand(rng);
+                vec![
+                    (g1.0.mul(r).prepare(), g2.1.prepare()),
+                    ((-g1.1).mul(r).prepare(), g2.0.prepare()),
+                ]
+            })
+            .collect();
+
+        if E::product_of_pairings(&pairs).is_one() {
+            return Ok(());
+        }
+
+        for (g1, g2, err) in &self.checks {
+            check_same_ratio::<E>(g1, g2, err)?;
+        }
+        Err(VerificationError::InvalidRatio("batched ratio checks").into())
+    }
+}
+
+impl<E: PairingEngine> Default for RatioCheckBatch<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Compute BLAKE2b(personalization | transcript | g^s | g^{s*x})
 /// and then hash it to G2
 pub fn compute_g2_s<E: PairingEngine>(
@@ -8,6 +8,12 @@ pub use errors::{Error, InvariantKind, Phase2Error, VerificationError};
 /// A convenience result type for returning errors
 pub type Result<T> = std::result::Result<T, Error>;
 
+mod canonical_json;
+pub use canonical_json::{canonicalize_json, to_canonical_json};
+
+mod cancellation;
+pub use cancellation::CancellationToken;
+
 mod groth16_utils;
 pub use groth16_utils::Groth16Params;
 
@@ -23,7 +29,18 @@ pub use io::{buffer_size, BatchDeserializer, BatchSerializer, Deserializer, Seri
 pub mod rayon_cfg;
 
 mod seed;
-pub use seed::derive_rng_from_seed;
+pub use seed::{
+    derive_rng_for_chunk,
+    derive_rng_for_committed_chunk,
+    derive_rng_from_seed,
+    seed_commitment,
+    CHUNK_SEED_DERIVATION,
+    COMMITTED_CHUNK_SEED_DERIVATION,
+    SEED_COMMITMENT_SCHEME,
+};
+
+mod vdf;
+pub use vdf::{vdf_evaluate, vdf_verify, VdfProof};
 
 // Re-exports for handling hashes
 pub use blake2::digest::generic_array::GenericArray;
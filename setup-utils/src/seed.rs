@@ -3,12 +3,116 @@ use rand::{CryptoRng, Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 
 pub const SEED_PERSONALIZATION: &[u8] = b"ALEOSEED";
+pub const CHUNK_SEED_PERSONALIZATION: &[u8] = b"ALEOCHNK";
+pub const COMMITTED_CHUNK_SEED_PERSONALIZATION: &[u8] = b"ALEOCCHK";
+pub const SEED_COMMITMENT_PERSONALIZATION: &[u8] = b"ALEOCMIT";
+
+/// The name of the scheme `derive_rng_for_chunk` derives the randomness of a chunk with.
+pub const CHUNK_SEED_DERIVATION: &str = "blake2s-chacha20-chunk-v1";
+
+/// The name of the scheme `derive_rng_for_committed_chunk` derives the randomness of a chunk with.
+pub const COMMITTED_CHUNK_SEED_DERIVATION: &str = "blake2s-chacha20-committed-chunk-v1";
+
+/// The name of the scheme `seed_commitment` commits to the public keys of a round with.
+pub const SEED_COMMITMENT_SCHEME: &str = "blake2s-public-key-commitment-v1";
 
 pub fn derive_rng_from_seed(seed: &[u8]) -> impl Rng + CryptoRng {
-    let seed_hash = Params::new()
+    ChaChaRng::from_seed(seed_hash(seed))
+}
+
+/// Derives the randomness of a contribution to a chunk from the seed of the contributor, so that
+/// one seed can drive the contributions to many chunks, each with randomness of its own:
+///
+/// `ChaCha20(BLAKE2s(key = BLAKE2s(seed, personal = "ALEOSEED"), personal = "ALEOCHNK", chunk_id || challenge_hash))`
+///
+/// where `chunk_id` is encoded as 8 little endian bytes, and `challenge_hash` is the hash of the
+/// challenge file the contribution is computed on.
+pub fn derive_rng_for_chunk(seed: &[u8], chunk_id: u64, challenge_hash: &[u8]) -> ChaChaRng {
+    let chunk_hash = Params::new()
+        .key(&seed_hash(seed))
+        .personal(CHUNK_SEED_PERSONALIZATION)
+        .to_state()
+        .update(&chunk_id.to_le_bytes())
+        .update(challenge_hash)
+        .finalize();
+    ChaChaRng::from_seed(*chunk_hash.as_array())
+}
+
+/// Derives the randomness of a contribution to a chunk in a round the contributor committed to
+/// with `seed_commitment`:
+///
+/// `ChaCha20(BLAKE2s(key = BLAKE2s(seed, personal = "ALEOSEED"), personal = "ALEOCCHK", round_height || chunk_id))`
+///
+/// where both are encoded as 8 little endian bytes. Unlike `derive_rng_for_chunk`, it does not
+/// depend on the challenge, so the public key of the contribution is known before the challenge is.
+pub fn derive_rng_for_committed_chunk(seed: &[u8], round_height: u64, chunk_id: u64) -> ChaChaRng {
+    let chunk_hash = Params::new()
+        .key(&seed_hash(seed))
+        .personal(COMMITTED_CHUNK_SEED_PERSONALIZATION)
+        .to_state()
+        .update(&round_height.to_le_bytes())
+        .update(&chunk_id.to_le_bytes())
+        .finalize();
+    ChaChaRng::from_seed(*chunk_hash.as_array())
+}
+
+/// Commits to the contributions of a round before any of their challenges is known,
+/// `BLAKE2s(public_key_hash_0 || public_key_hash_1 || ..., personal = "ALEOCMIT")`, over the
+/// hashes of the points in G1 of the public key of every chunk, in the order of the chunks.
+/// These points only depend on the randomness `derive_rng_for_committed_chunk` yields, and the
+/// public key is published with the contribution, so anyone can check the commitment while the
+/// seed stays secret.
+pub fn seed_commitment<T: AsRef<[u8]>>(public_key_hashes: &[T]) -> [u8; 32] {
+    let mut state = Params::new().personal(SEED_COMMITMENT_PERSONALIZATION).to_state();
+    for public_key_hash in public_key_hashes {
+        state.update(public_key_hash.as_ref());
+    }
+    *state.finalize().as_array()
+}
+
+fn seed_hash(seed: &[u8]) -> [u8; 32] {
+    *Params::new()
         .personal(SEED_PERSONALIZATION)
         .to_state()
         .update(seed)
-        .finalize();
-    ChaChaRng::from_seed(*seed_hash.as_array())
+        .finalize()
+        .as_array()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_randomness_is_independent() {
+        let chunk =
+            |chunk_id, challenge_hash: &[u8]| derive_rng_for_chunk(b"seed", chunk_id, challenge_hash).gen::<[u8; 32]>();
+
+        assert_eq!(chunk(0, &[0; 64]), chunk(0, &[0; 64]));
+        assert_ne!(chunk(0, &[0; 64]), chunk(1, &[0; 64]));
+        assert_ne!(chunk(0, &[0; 64]), chunk(0, &[1; 64]));
+        assert_ne!(chunk(0, &[0; 64]), derive_rng_from_seed(b"seed").gen::<[u8; 32]>());
+    }
+
+    #[test]
+    fn test_committed_chunk_randomness_is_independent() {
+        let chunk =
+            |round_height, chunk_id| derive_rng_for_committed_chunk(b"seed", round_height, chunk_id).gen::<[u8; 32]>();
+
+        assert_eq!(chunk(1, 0), chunk(1, 0));
+        assert_ne!(chunk(1, 0), chunk(1, 1));
+        assert_ne!(chunk(1, 0), chunk(2, 0));
+        assert_ne!(
+            chunk(1, 0),
+            derive_rng_for_chunk(b"seed", 0, &[0; 64]).gen::<[u8; 32]>()
+        );
+    }
+
+    #[test]
+    fn test_seed_commitment() {
+        let hashes = [[0u8; 64], [1u8; 64]];
+        assert_eq!(seed_commitment(&hashes), seed_commitment(&hashes.to_vec()));
+        assert_ne!(seed_commitment(&hashes), seed_commitment(&[[1u8; 64], [0u8; 64]]));
+        assert_ne!(seed_commitment(&hashes), seed_commitment(&hashes[..1]));
+    }
 }
@@ -0,0 +1,300 @@
+//! A Wesolowski verifiable delay function over the RSA-2048 group.
+//!
+//! Evaluating the function takes a configurable number of sequential squarings, while
+//! verifying the accompanying proof only takes a couple of modular exponentiations. This
+//! lets the final beacon randomness be provably delayed, so that nobody can predict it
+//! before the beacon hash is published, yet anyone can check it quickly.
+//!
+//! The group has the element -1 of order 2, so `y` and `-y` cannot be told apart by the
+//! verification equation. The output and the proof are therefore taken modulo ±1, as the
+//! smaller of `v` and `N - v`, before they are hashed into the challenge or the randomness.
+use crate::{Error, VerificationError};
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The RSA-2048 challenge modulus, whose factorization (and hence group order) is unknown.
+const RSA_2048: &str = "25195908475657893494027183240048398571429282126204032027777137836043662020707595556264018525880784\
+                        40691829064124951508218929855914917618450280848912007284499268739280728777673597141834727026189637\
+                        50149718246911650776133798590957000973304597488084284017974291006424586918171951187461215151726546\
+                        32282216869987549182422433637259085141865462043576798423387184774447920739934236584823824281198163\
+                        81501067481045166037730605620161967625613384414360383390441495263443219011465754445417842402092461\
+                        6515723350778707749817125772467962926386356373289912154831438167899885040445364023527381951378636\
+                        564391212010397122822120720357";
+
+/// The number of bits of the Fiat-Shamir challenge prime.
+const CHALLENGE_BITS: usize = 128;
+
+/// The Miller-Rabin bases used to test challenge candidates for primality.
+const PRIMALITY_BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// The output of the delay function on a beacon hash, along with the proof of its correctness.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VdfProof {
+    /// The hex encoded beacon hash the function was evaluated on.
+    pub input: String,
+    /// The number of sequential squarings.
+    pub iterations: u64,
+    /// The hex encoded output `y = x^(2^iterations)`, modulo ±1.
+    pub output: String,
+    /// The hex encoded proof `π = x^floor(2^iterations / l)`, modulo ±1.
+    pub proof: String,
+}
+
+fn modulus() -> BigUint {
+    BigUint::parse_bytes(RSA_2048.as_bytes(), 10).expect("the RSA-2048 modulus should parse")
+}
+
+/// Hashes the input to an element of the group.
+fn hash_to_group(input: &[u8], modulus: &BigUint) -> BigUint {
+    // Two blocks more than the modulus needs make the bias of the reduction negligible
+    let blocks = (modulus.bits() as usize + 255) / 256 + 2;
+    let mut bytes = Vec::with_capacity(blocks * 32);
+    for i in 0..blocks as u32 {
+        let mut h = Sha256::new();
+        h.update(b"vdf input");
+        h.update(&i.to_le_bytes());
+        h.update(input);
+        bytes.extend_from_slice(&h.finalize());
+    }
+    BigUint::from_bytes_be(&bytes) % modulus
+}
+
+/// Returns the representative of `v` modulo ±1, the smaller of `v` and `N - v`.
+fn canonicalize(v: BigUint, modulus: &BigUint) -> BigUint {
+    let negated = modulus - &v;
+    if negated < v {
+        negated
+    } else {
+        v
+    }
+}
+
+/// Derives the Fiat-Shamir challenge prime from the number of iterations, and the input and
+/// canonical output of the function.
+fn hash_to_prime(iterations: u64, x: &BigUint, y: &BigUint) -> BigUint {
+    let mut counter = 0u64;
+    loop {
+        let mut h = Sha256::new();
+        h.update(b"vdf prime");
+        h.update(&counter.to_le_bytes());
+        h.update(&iterations.to_le_bytes());
+        h.update(&x.to_bytes_be());
+        h.update(&y.to_bytes_be());
+        let mut candidate = h.finalize()[..CHALLENGE_BITS / 8].to_vec();
+        // Set the top bit to fix the size of the prime, and the bottom bit to make it odd
+        candidate[0] |= 0x80;
+        candidate[CHALLENGE_BITS / 8 - 1] |= 1;
+
+        let candidate = BigUint::from_bytes_be(&candidate);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+/// Miller-Rabin test with fixed bases. The candidates are hash outputs rather than
+/// adversarially chosen numbers, so a fixed set of bases is sufficient.
+fn is_probable_prime(n: &BigUint) -> bool {
+    let one = BigUint::one();
+    let two = BigUint::from(2u32);
+    if n < &two {
+        return false;
+    }
+    for &p in PRIMALITY_BASES.iter() {
+        let p = BigUint::from(p);
+        if n == &p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+
+    // Write n - 1 = d * 2^s with d odd
+    let n_minus_one = n - &one;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+
+    'bases: for &a in PRIMALITY_BASES.iter() {
+        let mut x = BigUint::from(a).modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Hashes the canonical output of the function into 32 bytes of beacon randomness.
+fn output_randomness(y: &BigUint) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(b"vdf output");
+    h.update(&y.to_bytes_be());
+    let mut randomness = [0u8; 32];
+    randomness.copy_from_slice(&h.finalize());
+    randomness
+}
+
+/// Evaluates the delay function on the given beacon hash, which takes `iterations`
+/// sequential squarings, and computes the proof of the output.
+pub fn vdf_evaluate(beacon_hash: &[u8], iterations: u64) -> VdfProof {
+    let n = modulus();
+    let x = hash_to_group(beacon_hash, &n);
+
+    let mut y = x.clone();
+    for _ in 0..iterations {
+        y = &y * &y % &n;
+    }
+    let y = canonicalize(y, &n);
+
+    // Compute π = x^floor(2^T / l) by long division of 2^T by l, one bit at a time
+    let l = hash_to_prime(iterations, &x, &y);
+    let mut proof = BigUint::one();
+    let mut remainder = BigUint::one();
+    for _ in 0..iterations {
+        remainder <<= 1;
+        let bit = remainder >= l;
+        if bit {
+            remainder -= &l;
+        }
+        proof = &proof * &proof % &n;
+        if bit {
+            proof = proof * &x % &n;
+        }
+    }
+
+    VdfProof {
+        input: hex::encode(beacon_hash),
+        iterations,
+        output: hex::encode(y.to_bytes_be()),
+        proof: hex::encode(canonicalize(proof, &n).to_bytes_be()),
+    }
+}
+
+/// Verifies that the proof of the delay function takes the required number of iterations, which
+/// must come from somewhere the prover does not control, and returns the beacon randomness derived
+/// from its output.
+pub fn vdf_verify(proof: &VdfProof, iterations: u64) -> Result<[u8; 32], Error> {
+    if proof.iterations != iterations {
+        return Err(VerificationError::VdfIterationsMismatch {
+            expected: iterations,
+            got: proof.iterations,
+        }
+        .into());
+    }
+    let decode = |value: &str| {
+        hex::decode(value)
+            .map(|bytes| BigUint::from_bytes_be(&bytes))
+            .map_err(|_| VerificationError::InvalidVdfProof)
+    };
+    let input = hex::decode(&proof.input).map_err(|_| VerificationError::InvalidVdfProof)?;
+    let y = decode(&proof.output)?;
+    let pi = decode(&proof.proof)?;
+
+    let n = modulus();
+    if y.is_zero() || y >= n || pi.is_zero() || pi >= n {
+        return Err(VerificationError::InvalidVdfProof.into());
+    }
+    // Only the canonical representatives are accepted, so that every input has a single valid output
+    if canonicalize(y.clone(), &n) != y || canonicalize(pi.clone(), &n) != pi {
+        return Err(VerificationError::InvalidVdfProof.into());
+    }
+
+    // Check that π^l * x^r = ±y, where r = 2^T mod l
+    let x = hash_to_group(&input, &n);
+    let l = hash_to_prime(iterations, &x, &y);
+    let r = BigUint::from(2u32).modpow(&BigUint::from(iterations), &l);
+    if canonicalize(pi.modpow(&l, &n) * x.modpow(&r, &n) % &n, &n) != y {
+        return Err(VerificationError::InvalidVdfProof.into());
+    }
+
+    Ok(output_randomness(&y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_probable_prime() {
+        let primes = [2u64, 3, 37, 41, 7919, 2147483647, 2305843009213693951];
+        let composites = [0u64, 1, 4, 1369, 561, 3215031751, 2305843009213693953];
+        assert!(primes.iter().all(|&p| is_probable_prime(&BigUint::from(p))));
+        assert!(composites.iter().all(|&c| !is_probable_prime(&BigUint::from(c))));
+    }
+
+    #[test]
+    fn test_vdf_roundtrip() {
+        let beacon_hash = [7u8; 32];
+        let proof = vdf_evaluate(&beacon_hash, 100);
+        let randomness = vdf_verify(&proof, 100).unwrap();
+
+        // The evaluation is deterministic
+        assert_eq!(vdf_evaluate(&beacon_hash, 100), proof);
+        // Different beacon hashes yield different randomness
+        let other = vdf_verify(&vdf_evaluate(&[8u8; 32], 100), 100).unwrap();
+        assert_ne!(randomness, other);
+    }
+
+    #[test]
+    fn test_vdf_rejects_invalid_proofs() {
+        let proof = vdf_evaluate(&[7u8; 32], 100);
+
+        let mut wrong_iterations = proof.clone();
+        wrong_iterations.iterations = 99;
+        assert!(vdf_verify(&wrong_iterations, 100).is_err());
+        assert!(vdf_verify(&wrong_iterations, 99).is_err());
+
+        let mut wrong_input = proof.clone();
+        wrong_input.input = hex::encode([8u8; 32]);
+        assert!(vdf_verify(&wrong_input, 100).is_err());
+
+        let mut wrong_output = proof.clone();
+        wrong_output.output = hex::encode(BigUint::from(4u32).to_bytes_be());
+        assert!(vdf_verify(&wrong_output, 100).is_err());
+
+        let mut malformed = proof;
+        malformed.proof = "not hex".to_string();
+        assert!(vdf_verify(&malformed, 100).is_err());
+    }
+
+    #[test]
+    fn test_vdf_rejects_other_delays() {
+        // A proof without any delay is valid on its own, but not where a delay is required
+        let instant = vdf_evaluate(&[7u8; 32], 0);
+        vdf_verify(&instant, 0).unwrap();
+        assert!(matches!(
+            vdf_verify(&instant, 100),
+            Err(Error::VerificationError(VerificationError::VdfIterationsMismatch {
+                expected: 100,
+                got: 0
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_vdf_rejects_negated_elements() {
+        let proof = vdf_evaluate(&[7u8; 32], 100);
+        let n = modulus();
+        let negate =
+            |value: &str| hex::encode((&n - BigUint::from_bytes_be(&hex::decode(value).unwrap())).to_bytes_be());
+
+        let mut negated_output = proof.clone();
+        negated_output.output = negate(&proof.output);
+        assert!(vdf_verify(&negated_output, 100).is_err());
+
+        let mut negated_proof = proof.clone();
+        negated_proof.proof = negate(&proof.proof);
+        assert!(vdf_verify(&negated_proof, 100).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey, ViewKey};
+
+use anyhow::{anyhow, Result};
+use bip39::Mnemonic;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use serde::Serialize;
+use std::{convert::TryInto, env, io::Read, str::FromStr};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "Aleo key generator")]
+struct Options {
+    #[structopt(long, help = "Read the private key to derive the keys from stdin")]
+    private_key_stdin: bool,
+    #[structopt(long, help = "Read the private key to derive the keys from this environment variable")]
+    private_key_env: Option<String>,
+    #[structopt(long, help = "Read a BIP39 mnemonic to derive the keys from stdin")]
+    mnemonic_stdin: bool,
+    #[structopt(long, help = "Read a BIP39 mnemonic to derive the keys from this environment variable")]
+    mnemonic_env: Option<String>,
+    #[structopt(
+        long,
+        help = "Read the passphrase of the BIP39 mnemonic from this environment variable, if it has one"
+    )]
+    mnemonic_passphrase_env: Option<String>,
+    #[structopt(long, help = "Print the keys as a JSON object")]
+    json: bool,
+}
+
+/// The keys of a participant, all derived from the private key.
+#[derive(Debug, Serialize)]
+struct KeyBundle {
+    private_key: String,
+    view_key: String,
+    address: String,
+}
+
+fn read_stdin() -> Result<String> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+fn read_env(name: &str) -> Result<String> {
+    env::var(name)
+        .map(|value| value.trim().to_string())
+        .map_err(|_| anyhow!("The environment variable {} is not set", name))
+}
+
+/// Returns the BIP39 seed of a mnemonic and its passphrase.
+fn seed_from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<[u8; 64]> {
+    let mnemonic = Mnemonic::parse_normalized(mnemonic).map_err(|e| anyhow!("Invalid BIP39 mnemonic: {}", e))?;
+    Ok(mnemonic.to_seed(passphrase))
+}
+
+/// Derives the private key from a BIP39 mnemonic. The first half of the BIP39 seed seeds
+/// the generator the private key is sampled with, so the same mnemonic and passphrase
+/// always give the same keys. This is not a BIP32 derivation path.
+fn private_key_from_mnemonic(mnemonic: &str, passphrase: &str) -> Result<PrivateKey<Testnet2Parameters>> {
+    let seed = seed_from_mnemonic(mnemonic, passphrase)?;
+    let mut rng = ChaChaRng::from_seed(seed[..32].try_into()?);
+    Ok(PrivateKey::new(&mut rng))
+}
+
+fn private_key(options: &Options) -> Result<PrivateKey<Testnet2Parameters>> {
+    let sources = [
+        options.private_key_stdin,
+        options.private_key_env.is_some(),
+        options.mnemonic_stdin,
+        options.mnemonic_env.is_some(),
+    ];
+    if sources.iter().filter(|source| **source).count() > 1 {
+        return Err(anyhow!("Pass at most one private key or mnemonic to derive the keys from"));
+    }
+
+    let passphrase = match &options.mnemonic_passphrase_env {
+        Some(name) => read_env(name)?,
+        None => String::new(),
+    };
+    let private_key = if options.private_key_stdin {
+        PrivateKey::from_str(&read_stdin()?)?
+    } else if let Some(name) = &options.private_key_env {
+        PrivateKey::from_str(&read_env(name)?)?
+    } else if options.mnemonic_stdin {
+        private_key_from_mnemonic(&read_stdin()?, &passphrase)?
+    } else if let Some(name) = &options.mnemonic_env {
+        private_key_from_mnemonic(&read_env(name)?, &passphrase)?
+    } else {
+        PrivateKey::new(&mut rand::thread_rng())
+    };
+    Ok(private_key)
+}
+
+fn keygen(options: &Options) -> Result<KeyBundle> {
+    let private_key = private_key(options)?;
+    let view_key = ViewKey::from_private_key(&private_key)?;
+    let address = Address::from_private_key(&private_key)?;
+    Ok(KeyBundle {
+        private_key: private_key.to_string(),
+        view_key: view_key.to_string(),
+        address: address.to_string(),
+    })
+}
+
+fn main() -> Result<()> {
+    let options = Options::from_args();
+
+    let keys = keygen(&options)?;
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&keys)?);
+    } else {
+        println!("Private key  {}", keys.private_key);
+        println!("View key     {}", keys.view_key);
+        println!("Address      {}", keys.address);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_seed_from_mnemonic() {
+        // The test vector of the BIP39 specification for this mnemonic with the passphrase "TREZOR"
+        let seed = seed_from_mnemonic(MNEMONIC, "TREZOR").unwrap();
+        assert_eq!(
+            hex::encode(&seed[..]),
+            concat!(
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553",
+                "1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+            )
+        );
+    }
+
+    #[test]
+    fn test_private_key_from_mnemonic() {
+        // The same mnemonic and passphrase always give the same keys, and another passphrase other keys
+        let derive = |passphrase| private_key_from_mnemonic(MNEMONIC, passphrase).unwrap().to_string();
+        assert_eq!(derive(""), derive(""));
+        assert_ne!(derive(""), derive("passphrase"));
+
+        // The checksum of the mnemonic is checked
+        let invalid = MNEMONIC.replace("about", "abandon");
+        assert!(private_key_from_mnemonic(&invalid, "").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Result};
+use rcgen::{
+    date_time_ymd,
+    BasicConstraints,
+    Certificate,
+    CertificateParams,
+    DnType,
+    ExtendedKeyUsagePurpose,
+    IsCa,
+    KeyPair,
+};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "Worker certificate issuer")]
+enum Options {
+    /// Creates the certificate authority the coordinator trusts worker certificates from
+    NewCa {
+        #[structopt(long, default_value = "Aleo setup workers")]
+        name: String,
+        #[structopt(long, help = "The directory to write ca.pem and ca-key.pem to")]
+        out_dir: PathBuf,
+    },
+    /// Issues a client certificate to a verifier worker
+    Issue {
+        #[structopt(long)]
+        ca_cert: PathBuf,
+        #[structopt(long)]
+        ca_key: PathBuf,
+        #[structopt(long, help = "The name of the worker, used as the certificate common name")]
+        worker: String,
+        #[structopt(long, help = "The last day the certificate is valid on, as YYYY-MM-DD")]
+        not_after: Option<String>,
+        #[structopt(long, help = "The directory to write <worker>.pem and <worker>-key.pem to")]
+        out_dir: PathBuf,
+    },
+}
+
+/// Writes the private key to the file at `path`, replacing it if it exists.
+/// The file is only readable and writable by its owner.
+fn write_key(path: &Path, key: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    // A file which already exists keeps its mode, so it is set before the key is written
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(key.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn write_pair(out_dir: &Path, name: &str, cert: &str, key: &str) -> Result<()> {
+    fs::create_dir_all(out_dir)?;
+    fs::write(out_dir.join(format!("{}.pem", name)), cert)?;
+    write_key(&out_dir.join(format!("{}-key.pem", name)), key)
+}
+
+fn parse_date(date: &str) -> Result<(i32, u8, u8)> {
+    let parts = date.split('-').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [year, month, day] => Ok((year.parse()?, month.parse()?, day.parse()?)),
+        _ => Err(anyhow!("Expected a date as YYYY-MM-DD, got {}", date)),
+    }
+}
+
+fn new_ca(name: String, out_dir: PathBuf) -> Result<()> {
+    let mut params = CertificateParams::new(vec![]);
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.distinguished_name.push(DnType::CommonName, name);
+    let ca = Certificate::from_params(params)?;
+
+    write_pair(&out_dir, "ca", &ca.serialize_pem()?, &ca.serialize_private_key_pem())
+}
+
+fn issue(ca_cert: PathBuf, ca_key: PathBuf, worker: String, not_after: Option<String>, out_dir: PathBuf) -> Result<()> {
+    let ca_key = KeyPair::from_pem(&fs::read_to_string(ca_key)?)?;
+    let ca = Certificate::from_params(CertificateParams::from_ca_cert_pem(
+        &fs::read_to_string(ca_cert)?,
+        ca_key,
+    )?)?;
+
+    let mut params = CertificateParams::new(vec![]);
+    params.distinguished_name.push(DnType::CommonName, worker.clone());
+    // The certificate only authenticates the worker to the coordinator
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    if let Some(not_after) = not_after {
+        let (year, month, day) = parse_date(&not_after)?;
+        params.not_after = date_time_ymd(year, month, day);
+    }
+    let cert = Certificate::from_params(params)?;
+
+    write_pair(
+        &out_dir,
+        &worker,
+        &cert.serialize_pem_with_signer(&ca)?,
+        &cert.serialize_private_key_pem(),
+    )
+}
+
+fn main() -> Result<()> {
+    match Options::from_args() {
+        Options::NewCa { name, out_dir } => new_ca(name, out_dir),
+        Options::Issue {
+            ca_cert,
+            ca_key,
+            worker,
+            not_after,
+            out_dir,
+        } => issue(ca_cert, ca_key, worker, not_after, out_dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_pair_restricts_key() {
+        let out_dir = std::env::temp_dir().join(format!("worker-cert-{}", std::process::id()));
+        let key_path = out_dir.join("verifier-1-key.pem");
+        fs::create_dir_all(&out_dir).unwrap();
+        fs::write(&key_path, "").unwrap();
+        fs::set_permissions(&key_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_pair(&out_dir, "verifier-1", "certificate", "key").unwrap();
+        assert_eq!(fs::read_to_string(&key_path).unwrap(), "key");
+        assert_eq!(fs::metadata(&key_path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+}
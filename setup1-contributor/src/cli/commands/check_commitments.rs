@@ -0,0 +1,36 @@
+use clap::AppSettings;
+use structopt::StructOpt;
+use url::Url;
+
+use std::path::PathBuf;
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "Check commitments",
+    about = "Check the commitments to the seed in a randomness attestation against the contributions",
+    setting(AppSettings::ColoredHelp),
+    setting(AppSettings::DisableVersion)
+)]
+pub struct CheckCommitmentsOptions {
+    /// The randomness attestation holding the commitments to the seed.
+    #[structopt(long, default_value = "randomness-attestation.json")]
+    pub randomness_attestation: PathBuf,
+
+    /// Specify the URL of the ceremony coordinator, which tells
+    /// the parameters to read the response file with.
+    #[structopt(long, help = "Coordinator api url", requires = "response")]
+    pub api_url: Option<Url>,
+
+    /// Also check the public key in the given response file, as
+    /// published in the transcript, against the commitment.
+    #[structopt(long, requires_all = &["api_url", "round", "chunk"])]
+    pub response: Option<PathBuf>,
+
+    /// The round the response file contributes to.
+    #[structopt(long)]
+    pub round: Option<u64>,
+
+    /// The chunk the response file contributes to.
+    #[structopt(long)]
+    pub chunk: Option<u64>,
+}
@@ -16,7 +16,7 @@ pub struct ContributeOptions {
     /// The passphrase to use for decrypting the private key. If
     /// unspecified, the passphrase will be requested via tty or
     /// pinentry dialog.
-    #[structopt(long)]
+    #[structopt(long, env = "ALEO_SETUP_PASSPHRASE", hide_env_values = true)]
     pub passphrase: Option<SecretString>,
 
     /// Specify the URL of the ceremony coordinator.
@@ -26,4 +26,71 @@ pub struct ContributeOptions {
     /// Read seed and private key at the given path.
     #[structopt(long, help = "Path to a file containing seed and private key")]
     pub keys_path: PathBuf,
+
+    /// Check the hash of each downloaded challenge against the hash
+    /// the coordinator advertised, before contributing to it.
+    #[structopt(long)]
+    pub check_challenge_hash: bool,
+
+    /// Check the hash of each downloaded challenge against the public
+    /// transcript digest at the given URL, before contributing to it.
+    #[structopt(long)]
+    pub transcript_digest: Option<Url>,
+
+    /// Ask the coordinator to let this contributor contribute to the given
+    /// chunks first, e.g. the largest chunks on a big server. Whether the
+    /// chunks are pinned is up to the coordinator.
+    #[structopt(long, use_delimiter = true)]
+    pub pin_chunks: Vec<u64>,
+
+    /// Download challenges from the artifact replica in the given
+    /// region, instead of the one which responds the fastest.
+    #[structopt(long)]
+    pub region: Option<String>,
+
+    /// Contribute to up to this many chunks at once, each in a worker
+    /// thread of its own, if the coordinator lets this contributor lock
+    /// that many chunks.
+    #[structopt(long, default_value = "1")]
+    pub parallel_chunks: usize,
+
+    /// The memory (in GB) the chunks contributed to at once may use
+    /// together, which reduces the number of parallel chunks if needed.
+    #[structopt(long)]
+    pub max_memory_gb: Option<f64>,
+
+    /// Record how the randomness of every chunk was derived from the
+    /// seed in the given file, for auditors.
+    #[structopt(long, default_value = "randomness-attestation.json")]
+    pub randomness_attestation: PathBuf,
+
+    /// Commit to the seed before locking the first chunk of a round, and
+    /// record the receipt of the coordinator in the randomness attestation,
+    /// so that anyone can check with `check-commitments` that the seed
+    /// predates the challenges.
+    #[structopt(long)]
+    pub commit_seed: bool,
+
+    /// Run without prompting, e.g. in CI: fail instead of asking for
+    /// the passphrase, keep chunks estimated to take longer than their
+    /// lease, skip the prompts after contributing, and exit with an
+    /// error if the contribution failed.
+    #[structopt(long)]
+    pub non_interactive: bool,
+
+    /// Mix the contents of the given file, e.g. randomness exported
+    /// from an HSM, into the seed the contribution is derived from.
+    #[structopt(long)]
+    pub entropy_file: Option<PathBuf>,
+
+    /// The ID of the CI runner to record in the machine attestation,
+    /// read from the environment of common CI systems if unspecified.
+    #[structopt(long)]
+    pub runner_id: Option<String>,
+
+    /// The hex encoded hash of the environment to record in the machine
+    /// attestation, e.g. the digest of a container image. Defaults to
+    /// the hash of the contributor executable.
+    #[structopt(long)]
+    pub environment_hash: Option<String>,
 }
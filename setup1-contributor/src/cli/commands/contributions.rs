@@ -0,0 +1,58 @@
+use clap::AppSettings;
+use setup1_shared::protocol::v1::ContributionStatus;
+use structopt::StructOpt;
+use url::Url;
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "Contributions",
+    about = "List the contributions to the ceremony",
+    setting(AppSettings::ColoredHelp),
+    setting(AppSettings::DisableVersion)
+)]
+pub struct ContributionsOptions {
+    /// Specify the URL of the ceremony coordinator.
+    #[structopt(long, help = "Coordinator api url")]
+    pub api_url: Url,
+
+    /// Only list the contributions to the given round.
+    #[structopt(long)]
+    pub round: Option<u64>,
+
+    /// Only list the contributions to the given chunk.
+    #[structopt(long)]
+    pub chunk: Option<u64>,
+
+    /// Only list the contributions of the given contributor.
+    #[structopt(long)]
+    pub contributor: Option<String>,
+
+    /// Only list the contributions with the given status,
+    /// either `contributed` or `verified`.
+    #[structopt(long, parse(try_from_str = parse_status))]
+    pub status: Option<ContributionStatus>,
+
+    /// Only list the contributions uploaded at or after this Unix timestamp.
+    #[structopt(long)]
+    pub since: Option<i64>,
+
+    /// Only list the contributions uploaded before this Unix timestamp.
+    #[structopt(long)]
+    pub until: Option<i64>,
+
+    /// Resume listing after the page the cursor was returned with.
+    #[structopt(long)]
+    pub cursor: Option<String>,
+
+    /// The maximum number of contributions in each page.
+    #[structopt(long)]
+    pub limit: Option<usize>,
+
+    /// Keep requesting pages until every matching contribution is listed.
+    #[structopt(long)]
+    pub all: bool,
+}
+
+fn parse_status(src: &str) -> Result<ContributionStatus, serde_json::Error> {
+    serde_json::from_value(serde_json::Value::String(src.to_string()))
+}
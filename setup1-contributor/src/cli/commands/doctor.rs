@@ -0,0 +1,36 @@
+use clap::AppSettings;
+use structopt::StructOpt;
+use url::Url;
+
+use std::path::PathBuf;
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "Doctor",
+    about = "Check this machine for common problems before contributing",
+    setting(AppSettings::ColoredHelp),
+    setting(AppSettings::DisableVersion)
+)]
+pub struct DoctorOptions {
+    /// Specify the URL of the ceremony coordinator.
+    #[structopt(long, help = "Coordinator api url")]
+    pub api_url: Url,
+
+    /// The directory the contributor will be run in, which holds
+    /// the challenge and response files.
+    #[structopt(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// The number of chunks to contribute to at once, as passed
+    /// to `contribute --parallel-chunks`.
+    #[structopt(long, default_value = "1")]
+    pub parallel_chunks: usize,
+
+    /// Check that the phase 1 transcript is present at the given path.
+    #[structopt(long)]
+    pub transcript: Option<PathBuf>,
+
+    /// The expected hex encoded hash of the phase 1 transcript.
+    #[structopt(long, requires = "transcript")]
+    pub transcript_hash: Option<String>,
+}
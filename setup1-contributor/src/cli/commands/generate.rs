@@ -21,4 +21,12 @@ pub struct GenerateOptions {
     /// For example: --keys-path keys.json
     #[structopt(long)]
     pub keys_path: PathBuf,
+    /// Let several people feed entropy into the seed, one after
+    /// another, and record each of them in an attestation.
+    #[structopt(long)]
+    pub party: bool,
+    /// Store the party attestation at the given path.
+    /// Defaults to the keys path with an `.attestation.json` extension.
+    #[structopt(long, requires = "party")]
+    pub attestation_path: Option<PathBuf>,
 }
@@ -1,2 +1,6 @@
+pub mod check_commitments;
 pub mod contribute;
+pub mod contributions;
+pub mod doctor;
 pub mod generate;
+pub mod reserve;
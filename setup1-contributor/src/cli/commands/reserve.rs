@@ -0,0 +1,45 @@
+use clap::AppSettings;
+use secrecy::SecretString;
+use structopt::StructOpt;
+use url::Url;
+
+use std::path::PathBuf;
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "Reserve",
+    about = "Reserve a time window to contribute in",
+    setting(AppSettings::ColoredHelp),
+    setting(AppSettings::DisableVersion)
+)]
+pub struct ReserveOptions {
+    /// The passphrase to use for decrypting the private key. If
+    /// unspecified, the passphrase will be requested via tty or
+    /// pinentry dialog.
+    #[structopt(long)]
+    pub passphrase: Option<SecretString>,
+
+    /// Specify the URL of the ceremony coordinator.
+    #[structopt(long, help = "Coordinator api url")]
+    pub api_url: Url,
+
+    /// Read seed and private key at the given path.
+    #[structopt(long, help = "Path to a file containing seed and private key")]
+    pub keys_path: PathBuf,
+
+    /// The Unix timestamp the time window starts at.
+    #[structopt(long, required_unless = "cancel")]
+    pub start: Option<i64>,
+
+    /// The length of the time window in hours.
+    #[structopt(long, default_value = "4")]
+    pub hours: i64,
+
+    /// Write the calendar event of the reservation to the given path.
+    #[structopt(long, default_value = "reservation.ics")]
+    pub output: PathBuf,
+
+    /// Cancel the reservation with the given ID instead.
+    #[structopt(long, conflicts_with = "start")]
+    pub cancel: Option<u64>,
+}
@@ -7,6 +7,10 @@ use structopt::StructOpt;
 pub enum Command {
     Generate(commands::generate::GenerateOptions),
     Contribute(commands::contribute::ContributeOptions),
+    Doctor(commands::doctor::DoctorOptions),
+    Contributions(commands::contributions::ContributionsOptions),
+    Reserve(commands::reserve::ReserveOptions),
+    CheckCommitments(commands::check_commitments::CheckCommitmentsOptions),
 }
 
 #[derive(StructOpt, Debug)]
@@ -0,0 +1,63 @@
+//! Checks the commitments to the seed in a randomness attestation, which show that
+//! the randomness of the contributions was fixed before the contributor saw its challenges
+
+use crate::{
+    cli::commands::check_commitments::CheckCommitmentsOptions,
+    commands::contribute::request_coordinator_public_settings,
+    randomness::RandomnessAttestation,
+    utils::create_parameters_for_chunk,
+};
+
+use phase1::{helpers::converters::CurveKind, PublicKey};
+use phase1_coordinator::environment::Environment;
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+pub async fn check_commitments(opts: &CheckCommitmentsOptions) -> Result<()> {
+    let attestation: RandomnessAttestation = serde_json::from_slice(&fs_err::read(&opts.randomness_attestation)?)?;
+    if attestation.seed_commitments.is_empty() {
+        return Err(anyhow!(
+            "{} holds no commitment to the seed",
+            opts.randomness_attestation.display()
+        ));
+    }
+    attestation.check_seed_commitments()?;
+    println!(
+        "The public keys recorded in {} open the commitments of {} rounds",
+        opts.randomness_attestation.display(),
+        attestation.seed_commitments.len()
+    );
+
+    if let (Some(api_url), Some(response), Some(round_height), Some(chunk_id)) =
+        (&opts.api_url, &opts.response, opts.round, opts.chunk)
+    {
+        let (public_settings, _) = request_coordinator_public_settings(api_url).await?;
+        let environment = Environment::from(&public_settings.setup);
+        let public_key_hash = match environment.parameters().curve() {
+            CurveKind::Bls12_377 => response_public_key_hash::<Bls12_377>(&environment, chunk_id, response)?,
+            CurveKind::BW6 => response_public_key_hash::<BW6_761>(&environment, chunk_id, response)?,
+        };
+        attestation.check_public_key(round_height, chunk_id, &public_key_hash)?;
+        println!(
+            "The public key in {} is the one committed to for chunk {} of round {}",
+            response.display(),
+            chunk_id,
+            round_height
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the hash of the points in G1 of the public key in the given response file.
+fn response_public_key_hash<E: PairingEngine>(
+    environment: &Environment,
+    chunk_id: u64,
+    response: &Path,
+) -> Result<Vec<u8>> {
+    let parameters = create_parameters_for_chunk::<E>(environment, chunk_id as usize)?;
+    let public_key = PublicKey::<E>::read(&fs_err::read(response)?, environment.compressed_outputs(), &parameters)?;
+    Ok(public_key.g1_hash()?)
+}
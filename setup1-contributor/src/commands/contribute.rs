@@ -1,51 +1,81 @@
 use crate::{
     cli::commands::contribute::ContributeOptions,
     errors::ContributeError,
+    randomness::{attest_machine, ChunkRandomness, RandomnessAttestation, SeedCommitmentRecord},
     setup_keys::{
-        confirmation_key::{print_key_and_remove_the_file, ConfirmationKey},
+        confirmation_key::{print_key_and_remove_the_file, print_key_location, ConfirmationKey},
         AleoSetupKeys,
     },
     utils::{
         create_parameters_for_chunk,
         get_authorization_value,
+        measure_clock_skew,
         read_from_file,
         remove_file_if_exists,
         sign_contribution_state,
     },
+    verified_download::{BlockVerifier, MAX_REFETCHES},
 };
 
-use phase1::helpers::converters::CurveKind;
+use phase1::{helpers::converters::CurveKind, Phase1, PublicKey};
 use phase1_cli::contribute;
 use phase1_coordinator::{
     environment::Environment,
     objects::{Chunk, Round},
 };
-use setup1_shared::structures::{ContributorStatus, LockResponse, PublicSettings, TwitterInfo};
+use setup1_shared::{
+    clock::{ClockSkew, MAX_CLOCK_SKEW},
+    ids::{ChunkId, ContributionId},
+    protocol::{
+        self,
+        v1::{
+            CeremonyPhase,
+            ContributorEligibility,
+            ErrorCode,
+            ErrorResponse,
+            HardwareClass,
+            HardwareReport,
+            MachineAttestation,
+            PinChunksRequest,
+            PinChunksResponse,
+            ReleaseChunkRequest,
+            SeedCommitment,
+            SignedSeedCommitmentReceipt,
+            TranscriptDigest,
+        },
+        ProtocolVersion,
+        IDEMPOTENCY_KEY_HEADER,
+        PROTOCOL_VERSION_HEADER,
+    },
+    structures::{ArtifactReplica, ContributorStatus, LockResponse, PublicSettings, TwitterInfo},
+};
 use setup_utils::calculate_hash;
 use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
 use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey, ViewKey};
 
 use age::DecryptError;
 use anyhow::{Context, Result};
+use blake2::{Blake2b, Digest};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input};
-use fs_err::File;
+use fs_err::{File, OpenOptions};
+use futures::future::BoxFuture;
 use indicatif::{ProgressBar, ProgressStyle};
 use panic_control::{spawn_quiet, ThreadResultExt};
 use rand::{CryptoRng, Rng};
 use regex::Regex;
 use secrecy::{ExposeSecret, SecretString, SecretVec};
-use setup_utils::derive_rng_from_seed;
+use setup_utils::{derive_rng_for_chunk, derive_rng_for_committed_chunk, seed_commitment};
 use std::{
     collections::HashSet,
     convert::TryFrom,
-    io::{BufRead, Read, Write},
-    path::PathBuf,
+    io::{BufRead, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 use tokio::time::{sleep, Instant};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use url::Url;
 
 const CHALLENGE_FILENAME: &str = "challenge";
@@ -54,6 +84,10 @@ const RESPONSE_FILENAME: &str = "response";
 const DELAY_AFTER_ERROR: Duration = Duration::from_secs(60);
 const DELAY_POLL_CEREMONY: Duration = Duration::from_secs(5);
 const HEARTBEAT_POLL_DELAY: Duration = Duration::from_secs(30);
+const LATENCY_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The fewest bytes an entropy file mixed into the seed may hold.
+const MINIMUM_ENTROPY_BYTES: usize = 32;
 
 // Version constants
 const MAJOR: u8 = 0;
@@ -63,12 +97,50 @@ const PATCH: u8 = 0;
 #[derive(Clone)]
 pub struct Contribute {
     pub server_url: Url,
+    /// The base URL challenges are downloaded from, either the coordinator
+    /// or one of its artifact replicas
+    download_url: Url,
     /// Public key id for this contributor: e.g.
     /// `aleo1h7pwa3dh2egahqj7yvq7f7e533lr0ueysaxde2ktmtu2pxdjvqfqsj607a`
     pub participant_id: Address<Testnet2Parameters>,
     pub private_key: PrivateKey<Testnet2Parameters>,
     seed: Arc<SecretVec<u8>>,
     pub environment: Environment,
+    check_challenge_hash: bool,
+    transcript_digest: Option<Url>,
+    /// The chunks to ask the coordinator to pin, until it was asked
+    pin_chunks: Vec<u64>,
+    /// The class of the hardware the contribution is computed on
+    hardware_class: HardwareClass,
+    /// Whether the hardware class was reported to the coordinator
+    hardware_reported: bool,
+    /// Whether the machine attestation was reported to the coordinator
+    machine_reported: bool,
+    /// The protocol version negotiated with the coordinator
+    protocol_version: ProtocolVersion,
+    /// The offset of the clock of the coordinator, which leases are measured with
+    clock_skew: ClockSkew,
+    /// The maximum number of chunks to contribute to at once
+    parallel_chunks: usize,
+    /// The memory budget (in GB) shared by the chunks contributed to at once
+    max_memory_gb: Option<f64>,
+    /// The record of how the randomness of every chunk was derived, and where it is written
+    randomness_attestation: RandomnessAttestation,
+    randomness_attestation_path: PathBuf,
+    /// Whether to commit to the seed before locking the first chunk of a round
+    commit_seed: bool,
+    /// Whether to run without prompting, e.g. in CI
+    non_interactive: bool,
+    client: reqwest::Client,
+}
+
+/// A chunk locked by this contributor, with the files of its contribution.
+struct LockedChunk {
+    round_height: u64,
+    lock_response: LockResponse,
+    challenge: String,
+    challenge_hash: Vec<u8>,
+    response: String,
 }
 
 impl Contribute {
@@ -77,19 +149,45 @@ impl Contribute {
         environment: &Environment,
         private_key: PrivateKey<Testnet2Parameters>,
         seed: SecretVec<u8>,
+        protocol_version: ProtocolVersion,
+        clock_skew: ClockSkew,
     ) -> Self {
         // TODO (raychu86): Pass in pipelining options from the CLI.
+        let participant_id = Address::try_from(&private_key).expect("Should have derived an Aleo address");
+        let randomness_attestation =
+            RandomnessAttestation::load(&opts.randomness_attestation, &participant_id.to_string())
+                .expect("Failed to load the randomness attestation");
 
         Self {
             server_url: opts.api_url.clone(),
-            participant_id: Address::try_from(&private_key).expect("Should have derived an Aleo address"),
+            download_url: opts.api_url.clone(),
+            participant_id,
             private_key,
             seed: Arc::new(seed),
             environment: environment.clone(),
+            check_challenge_hash: opts.check_challenge_hash,
+            transcript_digest: opts.transcript_digest.clone(),
+            pin_chunks: opts.pin_chunks.clone(),
+            hardware_class: HardwareClass::from_cpus(num_cpus::get()),
+            hardware_reported: false,
+            machine_reported: false,
+            protocol_version,
+            clock_skew,
+            parallel_chunks: opts.parallel_chunks.max(1),
+            max_memory_gb: opts.max_memory_gb,
+            randomness_attestation,
+            randomness_attestation_path: opts.randomness_attestation.clone(),
+            commit_seed: opts.commit_seed,
+            non_interactive: opts.non_interactive,
+            client: http_client(protocol_version).expect("Failed to create the HTTP client"),
         }
     }
 
     async fn run_and_catch_errors<E: PairingEngine>(&mut self) -> Result<()> {
+        if self.protocol_version >= (ProtocolVersion { major: 1, minor: 14 }) {
+            self.wait_until_eligible(&mut rand::thread_rng()).await;
+        }
+
         println!("Attempting to join the queue...");
 
         loop {
@@ -119,7 +217,7 @@ impl Contribute {
         // XXX: This *needs* to be ran before the loop, so that heartbeats will
         // still come in while the contributor is queued or working and waiting for
         // an available chunk. Otherwise, the contributor will be dropped inadvertently.
-        initiate_heartbeat(self.server_url.clone(), self.private_key.clone());
+        initiate_heartbeat(self.server_url.clone(), self.private_key.clone(), self.protocol_version);
 
         let progress_bar = initialize_progress_bar();
         // Run contributor loop.
@@ -132,6 +230,20 @@ impl Contribute {
                 }
                 Err(err) => {
                     tracing::error!("Error from contribution run: {}", err);
+                    match err.downcast_ref::<ContributeError>() {
+                        Some(ContributeError::ChunkReleasedError(_)) => return Err(err),
+                        Some(ContributeError::RequestRejectedError(code, _)) => {
+                            progress_bar.println(code.guidance());
+                            if !code.is_retryable() {
+                                return Err(err);
+                            }
+                            // The next chunk can be locked right away.
+                            if *code == ErrorCode::ChunkReassigned {
+                                continue;
+                            }
+                        }
+                        _ => {}
+                    }
                     sleep(DELAY_AFTER_ERROR).await;
                 }
             }
@@ -139,7 +251,14 @@ impl Contribute {
 
         println!("You have completed your contribution! Thank you!");
 
-        print_key_and_remove_the_file().expect("Error finalizing the participation");
+        // Nobody is there to answer the prompts below in non-interactive mode,
+        // and the confirmation key is not printed to the logs of the run.
+        if self.non_interactive {
+            print_key_location()?;
+            return Ok(());
+        }
+
+        print_key_and_remove_the_file()?;
 
         // Here, we 'flush' everything that's been pushed into stdin while the
         // program was running.
@@ -174,7 +293,7 @@ impl Contribute {
 
     async fn run<E: PairingEngine>(&mut self, progress_bar: &ProgressBar) -> Result<()> {
         loop {
-            let status = get_contributor_status(&self.server_url, &self.private_key).await?;
+            let status = get_contributor_status(&self.client, &self.server_url, &self.private_key).await?;
             match status {
                 ContributorStatus::Queue(position, queue_size) => {
                     progress_bar.set_length(queue_size);
@@ -195,7 +314,7 @@ impl Contribute {
                     continue;
                 }
             }
-            let ceremony = get_ceremony(&self.server_url).await?;
+            let ceremony = get_ceremony(&self.client, &self.server_url).await?;
             let number_of_chunks = ceremony.chunks().len();
             progress_bar.set_length(number_of_chunks as u64);
             let non_contributed_chunks = get_non_contributed_chunks(&ceremony, &self.participant_id.to_string());
@@ -221,116 +340,319 @@ impl Contribute {
 
             let auth_rng = &mut rand::rngs::OsRng;
 
-            // Attempt to lock a chunk from the coordinator.
-            let lock_response = self.lock_chunk(auth_rng).await?;
+            // The chunks can only be pinned once the contributor is in the round.
+            if !self.pin_chunks.is_empty() {
+                let pinned = self.request_pin_chunks(auth_rng).await?;
+                info!("The coordinator pinned chunks {:?} of {:?}", pinned, self.pin_chunks);
+                self.pin_chunks.clear();
+            }
+
+            // The compute time is estimated from the hardware class since protocol version 1.5.
+            if !self.hardware_reported && self.protocol_version >= (ProtocolVersion { major: 1, minor: 5 }) {
+                self.report_hardware(auth_rng).await?;
+                self.hardware_reported = true;
+            }
+
+            // The machine a non-interactive contributor runs on is reported since protocol version 1.16.
+            if !self.machine_reported && self.protocol_version >= (ProtocolVersion { major: 1, minor: 16 }) {
+                if let Some(machine) = &self.randomness_attestation.machine {
+                    self.report_machine(machine, auth_rng).await?;
+                }
+                self.machine_reported = true;
+            }
+
+            // The seed is committed to before any challenge of the round is seen.
+            let round_height = ceremony.round_height();
+            if self.commit_seed && !self.randomness_attestation.has_seed_commitment(round_height) {
+                let number_of_chunks = ceremony.chunks().len() as u64;
+                self.commit_to_seed::<E, _>(round_height, number_of_chunks, progress_bar, auth_rng)
+                    .await?;
+            }
 
-            let chunk_id = lock_response.chunk_id;
+            // Lock as many chunks as can be contributed to at once within the memory budget.
+            let workers = self.parallel_chunks::<E>().min(incomplete_chunks.len());
+            let mut locked_chunks = Vec::with_capacity(workers);
+            for _ in 0..workers {
+                match self.lock_and_download(&ceremony, progress_bar, auth_rng).await {
+                    Ok(locked_chunk) => locked_chunks.push(locked_chunk),
+                    // The contributor chose to stop, so the chunks locked so far are released as well.
+                    Err(error)
+                        if matches!(
+                            error.downcast_ref::<ContributeError>(),
+                            Some(ContributeError::ChunkReleasedError(_))
+                        ) =>
+                    {
+                        for locked_chunk in &locked_chunks {
+                            let lock_response = &locked_chunk.lock_response;
+                            self.release_chunk(lock_response.chunk_id, lock_response.contribution_id, auth_rng)
+                                .await?;
+                            remove_file_if_exists(&locked_chunk.challenge)?;
+                        }
+                        return Err(error);
+                    }
+                    // The coordinator may limit the number of locks, so the chunks locked so far are contributed to.
+                    Err(error) if !locked_chunks.is_empty() => {
+                        warn!("Contributing to {} chunks at once: {}", locked_chunks.len(), error);
+                        break;
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
 
-            progress_bar.set_message(format!("Contributing to chunk {}...", chunk_id));
+            let chunk_ids: Vec<u64> = locked_chunks.iter().map(|chunk| chunk.lock_response.chunk_id).collect();
+            progress_bar.set_message(format!("Contributing to chunks {:?}...", chunk_ids));
+            self.contribute_chunks::<E>(&locked_chunks)?;
+
+            for locked_chunk in &locked_chunks {
+                self.upload_contribution(locked_chunk, auth_rng).await?;
+                let randomness = ChunkRandomness {
+                    round_height: locked_chunk.round_height,
+                    chunk_id: locked_chunk.lock_response.chunk_id,
+                    challenge_hash: hex::encode(&locked_chunk.challenge_hash),
+                    public_key_hash: Some(hex::encode(self.response_public_key_hash::<E>(locked_chunk)?)),
+                };
+                self.randomness_attestation
+                    .record(&self.randomness_attestation_path, randomness)?;
+                remove_file_if_exists(&locked_chunk.challenge)?;
+                remove_file_if_exists(&locked_chunk.response)?;
+            }
 
-            self.download_challenge(chunk_id, lock_response.contribution_id, CHALLENGE_FILENAME, auth_rng)
-                .await?;
+            progress_bar.set_message("Waiting for an available chunk...");
+        }
+    }
 
-            let exposed_seed = self.seed.expose_secret();
-            let seeded_rng = derive_rng_from_seed(&exposed_seed[..]);
-            let start = Instant::now();
-            remove_file_if_exists(RESPONSE_FILENAME)?;
+    /// Returns the number of chunks to contribute to at once: as many as were
+    /// asked for, as long as their contributions fit in the memory budget.
+    fn parallel_chunks<E: PairingEngine>(&self) -> usize {
+        let max_memory_gb = match self.max_memory_gb {
+            Some(max_memory_gb) => max_memory_gb,
+            None => return self.parallel_chunks,
+        };
+        // The first chunk is the largest one, and both of its files are memory mapped.
+        let parameters = create_parameters_for_chunk::<E>(&self.environment, 0).expect("Invalid chunk parameters");
+        let chunk_bytes = (parameters.accumulator_size + parameters.contribution_size) as f64;
+        let fitting = (max_memory_gb * 1e9 / chunk_bytes) as usize;
+        if fitting == 0 {
+            warn!(
+                "A contribution needs {:.2} GB, more than the budget of {} GB",
+                chunk_bytes / 1e9,
+                max_memory_gb
+            );
+        }
+        self.parallel_chunks.min(fitting).max(1)
+    }
 
-            // Fetch parameters required for contribution.
-            let parameters = create_parameters_for_chunk::<E>(&self.environment, chunk_id as usize)?;
-            let compressed_input = self.environment.compressed_inputs();
-            let compressed_output = self.environment.compressed_outputs();
-            let check_input_correctness = self.environment.check_input_for_correctness();
+    /// Locks the next chunk and downloads its challenge, to a file of its own so
+    /// that several chunks can be contributed to at once.
+    async fn lock_and_download<R: Rng + CryptoRng>(
+        &self,
+        ceremony: &Round,
+        progress_bar: &ProgressBar,
+        auth_rng: &mut R,
+    ) -> Result<LockedChunk> {
+        let lock_response = self.lock_chunk(auth_rng).await?;
+
+        let chunk_id = lock_response.chunk_id;
+
+        if let Some(estimated_seconds) = lock_response.estimated_seconds {
+            progress_bar.println(format!(
+                "Estimated time to contribute to chunk {} on {} hardware: {}",
+                chunk_id,
+                self.hardware_class.as_str(),
+                format_seconds(estimated_seconds),
+            ));
+
+            // The coordinator clock decides when the lease expires, not the local one.
+            let lease_seconds = match lock_response.lease_expires_at {
+                Some(expires_at) => Some(self.clock_skew.seconds_until(expires_at, SystemTime::now())),
+                None => lock_response.lease_seconds,
+            };
+            if let Some(lease_seconds) = lease_seconds {
+                if estimated_seconds > lease_seconds && !self.non_interactive && prompt_release_chunk(lease_seconds)? {
+                    self.release_chunk(chunk_id, lock_response.contribution_id, auth_rng)
+                        .await?;
+                    return Err(ContributeError::ChunkReleasedError(chunk_id).into());
+                }
+            }
+        }
+
+        let challenge = format!("{}.{}", CHALLENGE_FILENAME, chunk_id);
+        let response = format!("{}.{}", RESPONSE_FILENAME, chunk_id);
+        let downloaded = match self.download_challenge(&lock_response, &challenge, auth_rng).await {
+            Ok(()) => {
+                self.check_challenge(ceremony.round_height(), &lock_response, &challenge)
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+        if let Err(error) = downloaded {
+            remove_file_if_exists(&challenge)?;
+            // The chunk is released, so that it is not held until its lease expires.
+            if let Err(release_error) = self
+                .release_chunk(chunk_id, lock_response.contribution_id, auth_rng)
+                .await
+            {
+                warn!("Failed to release chunk {}: {}", chunk_id, release_error);
+            }
+            return Err(error);
+        }
+        remove_file_if_exists(&response)?;
+
+        Ok(LockedChunk {
+            round_height: ceremony.round_height(),
+            lock_response,
+            challenge_hash: calculate_hash(&read_from_file(&challenge)?).to_vec(),
+            challenge,
+            response,
+        })
+    }
 
-            // Run the contribution.
-            let h = spawn_quiet(move || {
+    /// Contributes to the locked chunks, each in a worker thread of its own.
+    fn contribute_chunks<E: PairingEngine>(&self, locked_chunks: &[LockedChunk]) -> Result<()> {
+        let start = Instant::now();
+        let compressed_input = self.environment.compressed_inputs();
+        let compressed_output = self.environment.compressed_outputs();
+        let check_input_correctness = self.environment.check_input_for_correctness();
+
+        let mut handles = Vec::with_capacity(locked_chunks.len());
+        for locked_chunk in locked_chunks {
+            let chunk_id = locked_chunk.lock_response.chunk_id;
+            let parameters = create_parameters_for_chunk::<E>(&self.environment, chunk_id as usize)?;
+            // Every chunk gets randomness of its own, derived from the seed. In a round the seed
+            // was committed to, it must not depend on the challenge, see `commit_to_seed`.
+            let seed = &self.seed.expose_secret()[..];
+            let seeded_rng = if self
+                .randomness_attestation
+                .has_seed_commitment(locked_chunk.round_height)
+            {
+                derive_rng_for_committed_chunk(seed, locked_chunk.round_height, chunk_id)
+            } else {
+                derive_rng_for_chunk(seed, chunk_id, &locked_chunk.challenge_hash)
+            };
+            let (challenge, response) = (locked_chunk.challenge.clone(), locked_chunk.response.clone());
+            let handle = spawn_quiet(move || {
                 contribute(
                     compressed_input,
-                    CHALLENGE_FILENAME,
+                    &challenge,
                     compressed_output,
-                    RESPONSE_FILENAME,
+                    &response,
                     check_input_correctness,
                     &parameters,
                     seeded_rng,
                 );
             });
-            let result = h.join();
+            handles.push((chunk_id, handle));
+        }
+
+        // Every worker is joined, even after one failed, so that none of them outlives the contribution.
+        let mut failed = false;
+        for (chunk_id, handle) in handles {
+            let result = handle.join();
             if result.is_err() {
                 if let Some(panic_value) = result.panic_value_as_str() {
-                    error!("Contribute failed: {}", panic_value);
+                    error!("Contribute failed for chunk {}: {}", chunk_id, panic_value);
                 }
-                return Err(ContributeError::FailedRunningContributeError.into());
+                failed = true;
             }
-            let duration = start.elapsed();
-
-            info!("Completed chunk {} in {} seconds", chunk_id, duration.as_secs());
-
-            // Read the challenge and response files.
-            let challenge_file = read_from_file(CHALLENGE_FILENAME)?;
-            let response_file = read_from_file(RESPONSE_FILENAME)?;
-
-            // Hash the challenge and response files.
-            let challenge_hash = calculate_hash(&challenge_file).to_vec();
-            let response_hash = calculate_hash(&response_file).to_vec();
-
-            // Sign the contribution state.
-            let view_key = ViewKey::try_from(&self.private_key)?;
-            let signed_contribution_state =
-                sign_contribution_state(&view_key.to_string(), &challenge_hash, &response_hash, None, auth_rng)?;
+        }
+        if failed {
+            return Err(ContributeError::FailedRunningContributeError.into());
+        }
 
-            // Construct the serialized response
-            let mut file = File::open(RESPONSE_FILENAME)?;
-            let mut response_file = Vec::new();
-            file.read_to_end(&mut response_file)?;
+        let chunk_ids: Vec<u64> = locked_chunks.iter().map(|chunk| chunk.lock_response.chunk_id).collect();
+        info!(
+            "Completed chunks {:?} in {} seconds",
+            chunk_ids,
+            start.elapsed().as_secs()
+        );
+        Ok(())
+    }
 
-            // Concatenate the signed contribution data and next challenge file.
-            let verifier_flag = vec![0];
-            let signature_bytes = hex::decode(signed_contribution_state.get_signature())?;
+    /// Signs the contribution to the locked chunk, then uploads it and notifies the coordinator.
+    async fn upload_contribution<R: Rng + CryptoRng>(
+        &self,
+        locked_chunk: &LockedChunk,
+        auth_rng: &mut R,
+    ) -> Result<()> {
+        let lock_response = &locked_chunk.lock_response;
+        let chunk_id = lock_response.chunk_id;
+
+        // Hash the response file, the challenge file was hashed when it was downloaded.
+        let response_file = read_from_file(&locked_chunk.response)?;
+        let challenge_hash = locked_chunk.challenge_hash.clone();
+        let response_hash = calculate_hash(&response_file).to_vec();
+
+        // Sign the contribution state.
+        let view_key = ViewKey::try_from(&self.private_key)?;
+        let signed_contribution_state =
+            sign_contribution_state(&view_key.to_string(), &challenge_hash, &response_hash, None)?;
+
+        // Construct the serialized response
+        let mut file = File::open(&locked_chunk.response)?;
+        let mut response_file = Vec::new();
+        file.read_to_end(&mut response_file)?;
+
+        // Concatenate the signed contribution data and next challenge file.
+        let verifier_flag = vec![0];
+        let signature_bytes = hex::decode(signed_contribution_state.get_signature())?;
+
+        // Retries of the upload and of the notification carry the same key.
+        let idempotency_key = protocol::idempotency_key(
+            ChunkId(lock_response.response_chunk_id),
+            ContributionId(lock_response.response_contribution_id),
+            &response_hash,
+        );
 
-            let signature_and_response_file_bytes = [
-                verifier_flag,
-                signature_bytes,
-                challenge_hash,
-                response_hash,
-                response_file,
-            ]
-            .concat();
+        let signature_and_response_file_bytes = [
+            verifier_flag,
+            signature_bytes,
+            challenge_hash,
+            response_hash,
+            response_file,
+        ]
+        .concat();
 
-            // Upload the response and contribution file signature to the coordinator.
-            loop {
-                match self
-                    .upload_response(
-                        lock_response.response_chunk_id,
-                        lock_response.response_contribution_id,
-                        signature_and_response_file_bytes.clone(),
-                        auth_rng,
-                    )
-                    .await
-                {
-                    Ok(_) => break,
-                    Err(e) => {
-                        tracing::error!("Could not upload response - {}", e);
-                        sleep(DELAY_POLL_CEREMONY).await;
+        // Upload the response and contribution file signature to the coordinator.
+        loop {
+            match self
+                .upload_response(
+                    lock_response.response_chunk_id,
+                    lock_response.response_contribution_id,
+                    signature_and_response_file_bytes.clone(),
+                    &idempotency_key,
+                    auth_rng,
+                )
+                .await
+            {
+                Ok(_) => break,
+                Err(e) => {
+                    tracing::error!("Could not upload response - {}", e);
+                    if !can_retry_upload(&e) {
+                        return Err(e);
                     }
-                };
-            }
+                    sleep(DELAY_POLL_CEREMONY).await;
+                }
+            };
+        }
 
-            // Attempt to perform the contribution with the uploaded response file at the `upload_url`.
-            loop {
-                match self
-                    .notify_contribution(chunk_id, serde_json::json!({}), auth_rng)
-                    .await
-                {
-                    Ok(_) => break,
-                    Err(e) => {
-                        tracing::error!("Could not notify the coordinator of contribution - {}", e);
-                        sleep(DELAY_POLL_CEREMONY).await;
+        // Attempt to perform the contribution with the uploaded response file at the `upload_url`.
+        loop {
+            match self
+                .notify_contribution(chunk_id, serde_json::json!({}), &idempotency_key, auth_rng)
+                .await
+            {
+                Ok(_) => break,
+                Err(e) => {
+                    tracing::error!("Could not notify the coordinator of contribution - {}", e);
+                    if !can_retry_upload(&e) {
+                        return Err(e);
                     }
-                };
-            }
-
-            progress_bar.set_message("Waiting for an available chunk...");
+                    sleep(DELAY_POLL_CEREMONY).await;
+                }
+            };
         }
+
+        Ok(())
     }
 
     /// Get references to the unlocked chunks which have been
@@ -346,7 +668,7 @@ impl Contribute {
     async fn join_queue<R: Rng + CryptoRng>(&self, auth_rng: &mut R) -> Result<bool> {
         let join_queue_path = format!("/v1/queue/contributor/join/{}/{}/{}", MAJOR, MINOR, PATCH);
         let join_queue_path_url = self.server_url.join(&join_queue_path)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let authorization = get_authorization_value(&self.private_key, "POST", &join_queue_path, auth_rng)?;
 
         let address = self.participant_id.to_string();
@@ -360,7 +682,8 @@ impl Contribute {
             .body(bytes)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
 
         let data = response.bytes().await?;
         let joined = serde_json::from_slice::<bool>(&*data)?;
@@ -368,10 +691,65 @@ impl Contribute {
         Ok(joined)
     }
 
+    /// Waits until the contributor may join the queue, while the first rounds
+    /// of the ceremony are restricted to a trusted cohort.
+    async fn wait_until_eligible<R: Rng + CryptoRng>(&self, auth_rng: &mut R) {
+        let mut told = false;
+        loop {
+            let eligibility = match self.get_eligibility(auth_rng).await {
+                Ok(eligibility) => eligibility,
+                Err(err) => {
+                    // Joining the queue checks the eligibility again, so this is only informative.
+                    warn!("Failed to get the eligibility of the contributor, error: {}", err);
+                    return;
+                }
+            };
+            if eligibility.eligible {
+                return;
+            }
+            if !told {
+                if eligibility.phase == CeremonyPhase::TrustedCohort {
+                    println!("The ceremony is restricted to a trusted cohort, which you are not part of.");
+                    if let Some(public_round) = eligibility.public_round {
+                        println!("It opens to the public in round {}.", public_round);
+                    }
+                    if let Some(public_at) = eligibility.public_at {
+                        println!("It opens to the public at the latest at UNIX time {}.", public_at);
+                    }
+                    println!("Waiting until it opens...");
+                } else {
+                    println!("You are not eligible to join the queue, waiting...");
+                }
+                told = true;
+            }
+            sleep(DELAY_POLL_CEREMONY).await;
+        }
+    }
+
+    async fn get_eligibility<R: Rng + CryptoRng>(&self, auth_rng: &mut R) -> Result<ContributorEligibility> {
+        let eligibility_path = "/v1/contributor/eligibility";
+        let eligibility_url = self.server_url.join(eligibility_path)?;
+        let client = &self.client;
+        let authorization = get_authorization_value(&self.private_key, "POST", eligibility_path, auth_rng)?;
+        let response = client
+            .post(eligibility_url.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_LENGTH, 0)
+            .send()
+            .await?
+            .error_for_code()
+            .await?;
+
+        let data = response.bytes().await?;
+        let eligibility = serde_json::from_slice::<ContributorEligibility>(&*data)?;
+
+        Ok(eligibility)
+    }
+
     async fn lock_chunk<R: Rng + CryptoRng>(&self, auth_rng: &mut R) -> Result<LockResponse> {
         let lock_path = "/v1/contributor/try_lock";
         let lock_chunk_url = self.server_url.join(lock_path)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let authorization = get_authorization_value(&self.private_key, "POST", lock_path, auth_rng)?;
         let response = client
             .post(lock_chunk_url.as_str())
@@ -379,7 +757,8 @@ impl Contribute {
             .header(http::header::CONTENT_LENGTH, 0)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
 
         let data = response.bytes().await?;
         let lock_response = serde_json::from_slice::<LockResponse>(&*data)?;
@@ -387,24 +766,216 @@ impl Contribute {
         Ok(lock_response)
     }
 
-    async fn download_challenge<R: Rng + CryptoRng>(
+    async fn request_pin_chunks<R: Rng + CryptoRng>(&self, auth_rng: &mut R) -> Result<Vec<u64>> {
+        let pin_path = "/v1/contributor/pin_chunks";
+        let pin_url = self.server_url.join(pin_path)?;
+        let client = &self.client;
+        let authorization = get_authorization_value(&self.private_key, "POST", pin_path, auth_rng)?;
+        let bytes = serde_json::to_vec(&PinChunksRequest {
+            chunk_ids: self.pin_chunks.clone(),
+        })?;
+        let response = client
+            .post(pin_url.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_code()
+            .await?;
+
+        let data = response.bytes().await?;
+        let pin_response = serde_json::from_slice::<PinChunksResponse>(&*data)?;
+
+        Ok(pin_response.pinned_chunk_ids)
+    }
+
+    async fn report_hardware<R: Rng + CryptoRng>(&self, auth_rng: &mut R) -> Result<()> {
+        let hardware_path = "/v1/contributor/hardware";
+        let hardware_url = self.server_url.join(hardware_path)?;
+        let client = &self.client;
+        let authorization = get_authorization_value(&self.private_key, "POST", hardware_path, auth_rng)?;
+        let bytes = serde_json::to_vec(&HardwareReport {
+            hardware_class: self.hardware_class,
+        })?;
+        client
+            .post(hardware_url.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_code()
+            .await?;
+
+        Ok(())
+    }
+
+    async fn report_machine<R: Rng + CryptoRng>(&self, machine: &MachineAttestation, auth_rng: &mut R) -> Result<()> {
+        let machine_path = "/v1/contributor/machine";
+        let machine_url = self.server_url.join(machine_path)?;
+        let client = &self.client;
+        let authorization = get_authorization_value(&self.private_key, "POST", machine_path, auth_rng)?;
+        let bytes = serde_json::to_vec(machine)?;
+        client
+            .post(machine_url.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_code()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the hash of the points in G1 of the public key in the response to the locked chunk.
+    fn response_public_key_hash<E: PairingEngine>(&self, locked_chunk: &LockedChunk) -> Result<Vec<u8>> {
+        let chunk_id = locked_chunk.lock_response.chunk_id as usize;
+        let parameters = create_parameters_for_chunk::<E>(&self.environment, chunk_id)?;
+        let response = read_from_file(&locked_chunk.response)?;
+        let public_key = PublicKey::<E>::read(&response, self.environment.compressed_outputs(), &parameters)?;
+        Ok(public_key.g1_hash()?)
+    }
+
+    /// Commits to the seed and records the receipt of the coordinator in the randomness
+    /// attestation. A coordinator which rejects the commitment, e.g. since a chunk of the
+    /// round was already locked, is not asked again.
+    ///
+    /// The commitment is computed over the points in G1 of the public keys the seed yields for
+    /// every chunk of the round, which are published with the contributions, so it reveals
+    /// nothing about the seed and anyone can check it.
+    async fn commit_to_seed<E: PairingEngine, R: Rng + CryptoRng>(
+        &mut self,
+        round_height: u64,
+        number_of_chunks: u64,
+        progress_bar: &ProgressBar,
+        auth_rng: &mut R,
+    ) -> Result<()> {
+        progress_bar.set_message("Committing to the seed...");
+        let seed = &self.seed.expose_secret()[..];
+        let public_key_hashes = (0..number_of_chunks)
+            .map(|chunk_id| {
+                let mut rng = derive_rng_for_committed_chunk(seed, round_height, chunk_id);
+                Ok(Phase1::<E>::public_key_g1_hash(&mut rng)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let commitment = hex::encode(seed_commitment(&public_key_hashes));
+        let signed = match self.request_seed_commitment(&commitment, auth_rng).await {
+            Ok(signed) => signed,
+            Err(err) => match err.downcast_ref::<ContributeError>() {
+                Some(ContributeError::RequestRejectedError(_, message)) => {
+                    progress_bar.println(format!(
+                        "The coordinator did not accept the commitment to the seed, contributing without it: {}",
+                        message
+                    ));
+                    warn!("The commitment to the seed was rejected: {}", message);
+                    self.commit_seed = false;
+                    return Ok(());
+                }
+                _ => return Err(err),
+            },
+        };
+        if signed.receipt.commitment != commitment {
+            return Err(anyhow::anyhow!(
+                "The coordinator acknowledged the commitment {} instead of {}",
+                signed.receipt.commitment,
+                commitment
+            ));
+        }
+
+        info!(
+            "Committed to the seed for round {} with {}",
+            signed.receipt.round_height, commitment
+        );
+        let record = SeedCommitmentRecord {
+            signed,
+            public_key_hashes: public_key_hashes.iter().map(hex::encode).collect(),
+        };
+        self.randomness_attestation
+            .record_seed_commitment(&self.randomness_attestation_path, record)
+    }
+
+    async fn request_seed_commitment<R: Rng + CryptoRng>(
+        &self,
+        commitment: &str,
+        auth_rng: &mut R,
+    ) -> Result<SignedSeedCommitmentReceipt> {
+        let commit_path = "/v1/contributor/commit_seed";
+        let commit_url = self.server_url.join(commit_path)?;
+        let client = &self.client;
+        let authorization = get_authorization_value(&self.private_key, "POST", commit_path, auth_rng)?;
+        let bytes = serde_json::to_vec(&SeedCommitment {
+            commitment: commitment.to_string(),
+        })?;
+        let response = client
+            .post(commit_url.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_code()
+            .await?;
+
+        let data = response.bytes().await?;
+        let signed = serde_json::from_slice::<SignedSeedCommitmentReceipt>(&*data)?;
+
+        Ok(signed)
+    }
+
+    async fn release_chunk<R: Rng + CryptoRng>(
         &self,
         chunk_id: u64,
         contribution_id: u64,
-        file_path: &str,
         auth_rng: &mut R,
     ) -> Result<()> {
-        let download_path = format!("/v1/download/challenge/{}/{}", chunk_id, contribution_id);
-        let download_path_url = self.server_url.join(&download_path)?;
-        let client = reqwest::Client::new();
-        let authorization = get_authorization_value(&self.private_key, "GET", &download_path, auth_rng)?;
-        let mut response = client
-            .get(download_path_url.as_str())
+        let release_path = "/v1/contributor/release_chunk";
+        let release_url = self.server_url.join(release_path)?;
+        let client = &self.client;
+        let authorization = get_authorization_value(&self.private_key, "POST", release_path, auth_rng)?;
+        let bytes = serde_json::to_vec(&ReleaseChunkRequest {
+            chunk_id,
+            contribution_id,
+        })?;
+        client
+            .post(release_url.as_str())
             .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
+
+        Ok(())
+    }
 
+    async fn download_challenge<R: Rng + CryptoRng>(
+        &self,
+        lock_response: &LockResponse,
+        file_path: &str,
+        auth_rng: &mut R,
+    ) -> Result<()> {
+        let download_path = format!(
+            "/v1/download/challenge/{}/{}",
+            lock_response.chunk_id, lock_response.contribution_id
+        );
+        if self.protocol_version >= (ProtocolVersion { major: 1, minor: 15 }) {
+            if let Some(bao_hash) = &lock_response.challenge_bao_hash {
+                return self
+                    .download_verified_challenge(lock_response.chunk_id, &download_path, bao_hash, file_path, auth_rng)
+                    .await;
+            }
+        }
+
+        let mut response = self.get_challenge(&download_path, 0, auth_rng).await?;
         remove_file_if_exists(file_path)?;
         let mut out = File::create(file_path)?;
         while let Some(chunk) = response.chunk().await? {
@@ -414,26 +985,275 @@ impl Contribute {
         Ok(())
     }
 
+    /// Downloads the challenge while verifying each block of it against the BLAKE3 hash the
+    /// coordinator advertised, and fetches the challenge again from the first damaged block on,
+    /// so corruption is caught early and only the damaged range is downloaded twice.
+    async fn download_verified_challenge<R: Rng + CryptoRng>(
+        &self,
+        chunk_id: u64,
+        download_path: &str,
+        bao_hash: &str,
+        file_path: &str,
+        auth_rng: &mut R,
+    ) -> Result<()> {
+        // The outboard is about 6% of the size of the challenge, which is still too large
+        // to hold in memory for the largest chunks, so it is streamed to a file.
+        let outboard_file = format!("{}.outboard", file_path);
+        remove_file_if_exists(&outboard_file)?;
+        let mut response = self
+            .get_challenge(&format!("{}/outboard", download_path), 0, auth_rng)
+            .await?;
+        let mut out = File::create(&outboard_file)?;
+        while let Some(chunk) = response.chunk().await? {
+            out.write_all(&chunk)?;
+        }
+        drop(out);
+
+        let result = self
+            .download_verified_blocks(chunk_id, download_path, bao_hash, file_path, &outboard_file, auth_rng)
+            .await;
+        remove_file_if_exists(&outboard_file)?;
+        result
+    }
+
+    /// Downloads the challenge while verifying it against the outboard in `outboard_file`,
+    /// fetching it again from the first damaged block on, up to `MAX_REFETCHES` times.
+    async fn download_verified_blocks<R: Rng + CryptoRng>(
+        &self,
+        chunk_id: u64,
+        download_path: &str,
+        bao_hash: &str,
+        file_path: &str,
+        outboard_file: &str,
+        auth_rng: &mut R,
+    ) -> Result<()> {
+        remove_file_if_exists(file_path)?;
+        File::create(file_path)?;
+        let mut verifier = BlockVerifier::new(file_path, outboard_file, bao_hash)?;
+        let mut offset = 0;
+        let mut refetches = 0;
+        loop {
+            let mut response = self.get_challenge(download_path, offset, auth_rng).await?;
+            if offset > 0 && response.status() != http::StatusCode::PARTIAL_CONTENT {
+                // The server does not support ranges, so the challenge is downloaded from the start.
+                offset = 0;
+                verifier.rewind(0)?;
+            }
+
+            let mut out = OpenOptions::new().write(true).open(file_path)?;
+            out.set_len(offset)?;
+            out.seek(SeekFrom::Start(offset))?;
+            let mut written = offset;
+            let damaged = loop {
+                match response.chunk().await? {
+                    Some(chunk) => {
+                        out.write_all(&chunk)?;
+                        written += chunk.len() as u64;
+                        if let Err(damaged) = verifier.verify(written) {
+                            break Some(damaged);
+                        }
+                    }
+                    None => break verifier.finish(written).err(),
+                }
+            };
+
+            match damaged {
+                None => {
+                    info!(
+                        "Verified the {} bytes of the challenge for chunk {} while downloading",
+                        verifier.verified(),
+                        chunk_id
+                    );
+                    return Ok(());
+                }
+                Some(damaged) if refetches < MAX_REFETCHES => {
+                    warn!(
+                        "The challenge for chunk {} is corrupted at byte {}, fetching it again from there",
+                        chunk_id, damaged
+                    );
+                    refetches += 1;
+                    offset = damaged;
+                    verifier.rewind(damaged)?;
+                }
+                Some(damaged) => {
+                    remove_file_if_exists(file_path)?;
+                    return Err(ContributeError::ChallengeCorruptedError(chunk_id, damaged, refetches + 1).into());
+                }
+            }
+        }
+    }
+
+    /// Requests a download from the artifact replica, starting at `offset`, and falls back
+    /// to the coordinator if the replica fails.
+    async fn get_challenge<R: Rng + CryptoRng>(
+        &self,
+        download_path: &str,
+        offset: u64,
+        auth_rng: &mut R,
+    ) -> Result<reqwest::Response> {
+        match self
+            .get_download(&self.download_url, download_path, offset, auth_rng)
+            .await
+        {
+            Ok(response) => Ok(response),
+            // Fall back to the coordinator if the artifact replica fails.
+            Err(error) if self.download_url != self.server_url => {
+                warn!(
+                    "Failed to download from {}, falling back to the coordinator: {}",
+                    self.download_url, error
+                );
+                self.get_download(&self.server_url, download_path, offset, auth_rng)
+                    .await
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Requests a download from `base_url`, starting at `offset`. Only the coordinator is sent
+    /// the authorization, as the signature is valid for any coordinator request of the same path
+    /// until it expires, and artifact replicas serve the challenges without it.
+    async fn get_download<R: Rng + CryptoRng>(
+        &self,
+        base_url: &Url,
+        download_path: &str,
+        offset: u64,
+        auth_rng: &mut R,
+    ) -> Result<reqwest::Response> {
+        let download_path_url = base_url.join(download_path)?;
+        let mut request = self.client.get(download_path_url.as_str());
+        if *base_url == self.server_url {
+            let authorization = get_authorization_value(&self.private_key, "GET", download_path, auth_rng)?;
+            request = request.header(http::header::AUTHORIZATION, authorization);
+        }
+        if offset > 0 {
+            request = request.header(http::header::RANGE, format!("bytes={}-", offset));
+        }
+        Ok(request.send().await?.error_for_code().await?)
+    }
+
+    /// Selects the artifact replica to download challenges from: the one in the
+    /// given region, or else whichever of the replicas and the coordinator
+    /// responds the fastest.
+    async fn select_artifact_replica(&mut self, replicas: &[ArtifactReplica], region: Option<&str>) {
+        let mut candidates = vec![self.server_url.clone()];
+        for replica in replicas {
+            if region.map_or(false, |region| region != replica.region) {
+                continue;
+            }
+            match Url::parse(&replica.url) {
+                Ok(url) => candidates.push(url),
+                Err(error) => warn!(
+                    "Ignoring the replica in {} with an invalid URL: {}",
+                    replica.region, error
+                ),
+            }
+        }
+
+        self.download_url = match region {
+            Some(region) => match candidates.get(1) {
+                Some(url) => url.clone(),
+                None => {
+                    warn!("The coordinator has no artifact replica in region {}", region);
+                    self.server_url.clone()
+                }
+            },
+            None => {
+                let latencies =
+                    futures::future::join_all(candidates.iter().map(|url| probe_latency(&self.client, url))).await;
+                candidates
+                    .into_iter()
+                    .zip(latencies)
+                    .filter_map(|(url, latency)| Some((url, latency?)))
+                    .min_by_key(|(_, latency)| *latency)
+                    .map(|(url, _)| url)
+                    .unwrap_or_else(|| self.server_url.clone())
+            }
+        };
+        info!("Downloading challenges from {}", self.download_url);
+    }
+
+    /// Checks the hash of the downloaded challenge against the hash the coordinator
+    /// advertised and against the public transcript digest, if enabled, so a stale
+    /// or corrupted challenge is caught before spending hours contributing to it.
+    /// The advertised hash is always checked when downloading from an artifact replica,
+    /// which the coordinator does not vouch for.
+    async fn check_challenge(&self, round_height: u64, lock_response: &LockResponse, file_path: &str) -> Result<()> {
+        let check_challenge_hash = self.check_challenge_hash || self.download_url != self.server_url;
+        if !check_challenge_hash && self.transcript_digest.is_none() {
+            return Ok(());
+        }
+
+        let chunk_id = lock_response.chunk_id;
+        let challenge_hash = hex::encode(calculate_hash(&read_from_file(file_path)?));
+        let check = |source: &'static str, expected: &str| {
+            if expected.eq_ignore_ascii_case(&challenge_hash) {
+                Ok(())
+            } else {
+                Err(ContributeError::ChallengeHashMismatchError(
+                    chunk_id,
+                    challenge_hash.clone(),
+                    source,
+                    expected.to_string(),
+                ))
+            }
+        };
+
+        if check_challenge_hash {
+            let advertised = lock_response
+                .challenge_hash
+                .as_ref()
+                .ok_or(ContributeError::ChallengeHashMissingError(chunk_id))?;
+            check("the coordinator", advertised)?;
+        }
+
+        if let Some(url) = &self.transcript_digest {
+            // The digest is published independently of the coordinator, so it is fetched without the protocol header.
+            let bytes = reqwest::get(url.as_str()).await?.error_for_status()?.bytes().await?;
+            let digest: TranscriptDigest = serde_json::from_slice(&bytes)?;
+            if digest.round_height != round_height {
+                return Err(
+                    ContributeError::TranscriptDigestRoundMismatchError(digest.round_height, round_height).into(),
+                );
+            }
+            match digest.challenge_hash(ChunkId(chunk_id), ContributionId(lock_response.contribution_id)) {
+                Some(expected) => check("the transcript digest", expected)?,
+                None => info!(
+                    "The transcript digest does not list the challenge for chunk {} yet",
+                    chunk_id
+                ),
+            }
+        }
+
+        info!(
+            "The challenge for chunk {} has the expected hash {}",
+            chunk_id, challenge_hash
+        );
+        Ok(())
+    }
+
     async fn upload_response<R: Rng + CryptoRng>(
         &self,
         chunk_id: u64,
         contribution_id: u64,
         contents: Vec<u8>,
+        idempotency_key: &str,
         auth_rng: &mut R,
     ) -> Result<()> {
         let upload_path = format!("/v1/upload/response/{}/{}", chunk_id, contribution_id);
         let upload_path_url = self.server_url.join(&upload_path)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let authorization = get_authorization_value(&self.private_key, "POST", &upload_path, auth_rng)?;
         client
             .post(upload_path_url.as_str())
             .header(http::header::AUTHORIZATION, authorization)
+            .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
             .header(http::header::CONTENT_TYPE, "application/octet-stream")
             .header(http::header::CONTENT_LENGTH, contents.len())
             .body(contents)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
 
         Ok(())
     }
@@ -442,21 +1262,24 @@ impl Contribute {
         &self,
         chunk_id: u64,
         body: serde_json::Value,
+        idempotency_key: &str,
         auth_rng: &mut R,
     ) -> Result<()> {
         let contribute_path = format!("/v1/contributor/try_contribute/{}", chunk_id);
         let contribute_chunk_url = self.server_url.join(&contribute_path)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let authorization = get_authorization_value(&self.private_key, "POST", &contribute_path, auth_rng)?;
         let bytes = serde_json::to_vec(&body)?;
         client
             .post(contribute_chunk_url.as_str())
             .header(http::header::AUTHORIZATION, authorization)
+            .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
             .header(http::header::CONTENT_LENGTH, bytes.len())
             .body(bytes)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
         Ok(())
     }
 
@@ -547,7 +1370,7 @@ impl Contribute {
         let upload_path = "/v1/contributor/add_eth_address";
         let upload_endpoint_url = self.server_url.join(upload_path)?;
         let authorization = get_authorization_value(&self.private_key, "POST", upload_path, auth_rng)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let bytes = serde_json::to_string(&address)?;
         client
             .post(upload_endpoint_url)
@@ -556,7 +1379,8 @@ impl Contribute {
             .body(bytes)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
         Ok(())
     }
 
@@ -564,13 +1388,14 @@ impl Contribute {
         let get_path = "/v1/contributor/get_twitter_access_token";
         let get_endpoint_url = self.server_url.join(get_path)?;
         let authorization = get_authorization_value(&self.private_key, "GET", get_path, auth_rng)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let response = client
             .get(get_endpoint_url)
             .header(http::header::AUTHORIZATION, authorization)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
 
         let data = response.bytes().await?;
         let request_token = serde_json::from_slice::<egg_mode::KeyPair>(&*data)?;
@@ -582,7 +1407,7 @@ impl Contribute {
         let post_path = "/v1/contributor/post_tweet";
         let post_endpoint_url = self.server_url.join(post_path)?;
         let authorization = get_authorization_value(&self.private_key, "POST", post_path, auth_rng)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let bytes = serde_json::to_vec(&info)?;
         let response = client
             .post(post_endpoint_url)
@@ -591,7 +1416,8 @@ impl Contribute {
             .body(bytes)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
 
         let data = response.bytes().await?;
         let request_token = serde_json::from_slice::<String>(&*data)?;
@@ -619,6 +1445,7 @@ fn initialize_progress_bar() -> ProgressBar {
 }
 
 async fn get_contributor_status(
+    client: &reqwest::Client,
     server_url: &Url,
     private_key: &PrivateKey<Testnet2Parameters>,
 ) -> Result<ContributorStatus> {
@@ -628,14 +1455,14 @@ async fn get_contributor_status(
     let auth_rng = &mut rand::rngs::OsRng;
     let authorization = get_authorization_value(private_key, "POST", endpoint, auth_rng)?;
 
-    let client = reqwest::Client::new();
     let response = client
         .post(ceremony_url)
         .header(http::header::AUTHORIZATION, authorization)
         .header(http::header::CONTENT_LENGTH, 0)
         .send()
         .await?
-        .error_for_status()?;
+        .error_for_code()
+        .await?;
 
     let data = response.bytes().await?;
     let status = serde_json::from_slice(&*data)?;
@@ -643,9 +1470,9 @@ async fn get_contributor_status(
     Ok(status)
 }
 
-async fn get_ceremony(server_url: &Url) -> Result<Round> {
+async fn get_ceremony(client: &reqwest::Client, server_url: &Url) -> Result<Round> {
     let ceremony_url = server_url.join("/v1/round/current")?;
-    let response = reqwest::get(ceremony_url.as_str()).await?.error_for_status()?;
+    let response = client.get(ceremony_url.as_str()).send().await?.error_for_code().await?;
 
     let data = response.bytes().await?;
     let ceremony = serde_json::from_slice(&*data)?;
@@ -673,13 +1500,14 @@ fn get_non_contributed_chunks<'r>(ceremony: &'r Round, participant_id: &str) ->
 struct HeartbeatData {
     server_url: Url,
     private_key: PrivateKey<Testnet2Parameters>,
+    client: reqwest::Client,
 }
 
 impl HeartbeatData {
     async fn heartbeat<R: Rng + CryptoRng>(&self, auth_rng: &mut R) -> Result<()> {
         let heartbeat_path = "/v1/contributor/heartbeat";
         let url = self.server_url.join(heartbeat_path)?;
-        let client = reqwest::Client::new();
+        let client = &self.client;
         let authorization = get_authorization_value(&self.private_key, "POST", heartbeat_path, auth_rng)?;
         let response = client
             .post(url.as_str())
@@ -687,7 +1515,8 @@ impl HeartbeatData {
             .header(http::header::CONTENT_LENGTH, 0)
             .send()
             .await?
-            .error_for_status()?;
+            .error_for_code()
+            .await?;
 
         response.error_for_status()?;
 
@@ -695,12 +1524,14 @@ impl HeartbeatData {
     }
 }
 
-fn initiate_heartbeat(server_url: Url, private_key: PrivateKey<Testnet2Parameters>) {
+fn initiate_heartbeat(server_url: Url, private_key: PrivateKey<Testnet2Parameters>, protocol_version: ProtocolVersion) {
     let private_key = private_key.to_string();
     std::thread::spawn(move || {
         let heartbeat_data = HeartbeatData {
             server_url,
             private_key: PrivateKey::from_str(&private_key).expect("Failed to create PrivateKey from String"),
+            // The heartbeat runs on its own runtime, which can't share the connection pool
+            client: http_client(protocol_version).expect("Failed to create the HTTP client"),
         };
 
         let auth_rng = &mut rand::rngs::OsRng;
@@ -739,7 +1570,7 @@ fn decrypt(passphrase: &SecretString, encrypted: &str) -> Result<Vec<u8>> {
 
 /// Decrypts and reads the private key from the specified `keys_path`,
 /// decrypting using the specified `passphrase`
-fn read_keys<P: Into<PathBuf>>(
+pub(crate) fn read_keys<P: Into<PathBuf>>(
     keys_path: P,
     passphrase: &SecretString,
 ) -> Result<(SecretVec<u8>, PrivateKey<Testnet2Parameters>)> {
@@ -754,22 +1585,115 @@ fn read_keys<P: Into<PathBuf>>(
     Ok((seed, private_key))
 }
 
-async fn request_coordinator_public_settings(coordinator_url: &Url) -> anyhow::Result<PublicSettings> {
+/// Mixes the contents of the entropy file, e.g. randomness exported from an HSM,
+/// into the seed, so that the randomness of the contribution depends on both
+pub(crate) fn mix_entropy(seed: &SecretVec<u8>, entropy_file: &Path) -> Result<SecretVec<u8>> {
+    let entropy = SecretVec::new(fs_err::read(entropy_file)?);
+    let length = entropy.expose_secret().len();
+    if length < MINIMUM_ENTROPY_BYTES {
+        return Err(ContributeError::EntropyFileTooShortError(length, MINIMUM_ENTROPY_BYTES).into());
+    }
+
+    let mut hasher = Blake2b::new();
+    hasher.update(seed.expose_secret());
+    hasher.update(entropy.expose_secret());
+    Ok(SecretVec::new(hasher.finalize().to_vec()))
+}
+
+/// Returns the public settings of the coordinator, with the skew of the local clock
+/// if the coordinator told its time
+pub(crate) async fn request_coordinator_public_settings(
+    coordinator_url: &Url,
+) -> anyhow::Result<(PublicSettings, Option<ClockSkew>)> {
     let settings_endpoint_url = coordinator_url.join("/v1/coordinator/settings")?;
     let client = reqwest::Client::new();
-    let bytes = client
+    let sent = SystemTime::now();
+    let response = client
         .post(settings_endpoint_url)
         .header(http::header::CONTENT_LENGTH, 0)
         .send()
-        .await?
-        .bytes()
         .await?;
-    PublicSettings::decode(&bytes.to_vec())
-        .map_err(|e| anyhow::anyhow!("Error decoding coordinator PublicSettings: {}", e))
+    let received = SystemTime::now();
+    let headers = response.headers().clone();
+    let bytes = response.bytes().await?;
+    let settings = PublicSettings::decode(&bytes.to_vec())
+        .map_err(|e| anyhow::anyhow!("Error decoding coordinator PublicSettings: {}", e))?;
+    let clock_skew = measure_clock_skew(sent, received, &headers, &settings);
+    Ok((settings, clock_skew))
+}
+
+/// Returns the round trip time of a request to the given URL,
+/// or `None` if it could not be reached
+async fn probe_latency(client: &reqwest::Client, url: &Url) -> Option<Duration> {
+    let start = Instant::now();
+    match tokio::time::timeout(LATENCY_PROBE_TIMEOUT, client.head(url.as_str()).send()).await {
+        Ok(Ok(_)) => Some(start.elapsed()),
+        _ => None,
+    }
+}
+
+/// Returns whether an upload which failed with the given error can succeed when retried
+fn can_retry_upload(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<ContributeError>() {
+        Some(ContributeError::RequestRejectedError(code, _)) => {
+            code.is_retryable() && *code != ErrorCode::ChunkReassigned
+        }
+        _ => true,
+    }
+}
+
+/// Turns the error responses of the coordinator into a [ContributeError::RequestRejectedError],
+/// which carries the category of the error for the contributor to act on
+trait ErrorForCode: Sized {
+    fn error_for_code(self) -> BoxFuture<'static, Result<Self>>;
+}
+
+impl ErrorForCode for reqwest::Response {
+    fn error_for_code(self) -> BoxFuture<'static, Result<Self>> {
+        Box::pin(async move {
+            let status = self.status();
+            if !status.is_client_error() && !status.is_server_error() {
+                return Ok(self);
+            }
+
+            // Coordinators which predate error codes only respond with a status code.
+            let body = self.bytes().await.unwrap_or_default();
+            let error = match serde_json::from_slice::<ErrorResponse>(&body) {
+                Ok(response) => ContributeError::RequestRejectedError(response.code, response.message),
+                Err(_) => {
+                    ContributeError::RequestRejectedError(ErrorCode::from_status(status.as_u16()), status.to_string())
+                }
+            };
+            Err(error.into())
+        })
+    }
+}
+
+/// Asks whether to give back a chunk which is estimated to take longer than its lease.
+/// Fails if there is no terminal to ask on.
+fn prompt_release_chunk(lease_seconds: u64) -> Result<bool> {
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Your hardware is likely too slow to contribute within the lease of {}. Release the chunk?",
+            format_seconds(lease_seconds)
+        ))
+        .interact()?)
+}
+
+fn format_seconds(seconds: u64) -> String {
+    format!("{}m {}s", seconds / 60, seconds % 60)
+}
+
+/// Returns the client for every request to the coordinator after the
+/// public settings, carrying the negotiated protocol version
+fn http_client(protocol_version: ProtocolVersion) -> Result<reqwest::Client> {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(PROTOCOL_VERSION_HEADER, protocol_version.to_string().parse()?);
+    Ok(reqwest::Client::builder().default_headers(headers).build()?)
 }
 
 pub async fn contribute_subcommand(opts: &ContributeOptions) -> anyhow::Result<()> {
-    let public_settings = request_coordinator_public_settings(&opts.api_url)
+    let (public_settings, clock_skew) = request_coordinator_public_settings(&opts.api_url)
         .await
         .map_err(|e| {
             tracing::error!("Failed to fetch the coordinator public settings");
@@ -777,26 +1701,79 @@ pub async fn contribute_subcommand(opts: &ContributeOptions) -> anyhow::Result<(
         })
         .with_context(|| "Failed to fetch the coordinator public settings".to_owned())?;
 
-    start_contributor(opts, &public_settings).await
+    start_contributor(opts, &public_settings, clock_skew).await
 }
 
-async fn start_contributor(opts: &ContributeOptions, public_settings: &PublicSettings) -> Result<()> {
-    let environment = crate::utils::environment_by_setup_kind(&public_settings.setup);
+async fn start_contributor(
+    opts: &ContributeOptions,
+    public_settings: &PublicSettings,
+    clock_skew: Option<ClockSkew>,
+) -> Result<()> {
+    let environment = Environment::from(&public_settings.setup);
 
     // Initialize tracing logger. Stored to `aleo-setup.log`.
     let appender = tracing_appender::rolling::never(".", "aleo-setup.log");
     let (non_blocking, _guard) = tracing_appender::non_blocking(appender);
     tracing_subscriber::fmt().with_writer(non_blocking).init();
 
+    if opts.non_interactive && opts.passphrase.is_none() {
+        return Err(ContributeError::PassphraseRequiredError.into());
+    }
     let passphrase = crate::setup_keys::read_passphrase(opts.passphrase.clone())?;
 
     // Read the stored contribution seed and Aleo private key.
-    let (seed, private_key) = read_keys(&opts.keys_path, &passphrase).expect("Unable to load Aleo setup keys");
+    let (seed, private_key) = read_keys(&opts.keys_path, &passphrase).context("Unable to load Aleo setup keys")?;
+    let seed = match &opts.entropy_file {
+        Some(entropy_file) => mix_entropy(&seed, entropy_file)?,
+        None => seed,
+    };
 
     let curve_kind = environment.parameters().curve();
 
+    let protocol_version = protocol::negotiate(&public_settings.protocol_versions)?;
+    info!("Speaking protocol version {} with the coordinator", protocol_version);
+
+    let clock_skew = match clock_skew {
+        Some(clock_skew) => clock_skew,
+        None => {
+            warn!("The coordinator did not tell its time, assuming the clocks agree");
+            ClockSkew::default()
+        }
+    };
+    if clock_skew.magnitude() > MAX_CLOCK_SKEW {
+        println!(
+            "Warning: your clock is {} s off the clock of the coordinator. Leases follow the clock of the \
+             coordinator, but consider synchronizing your clock, e.g. with `sudo timedatectl set-ntp true`",
+            clock_skew.magnitude().as_secs()
+        );
+        warn!(
+            "The clock is {} ms off the clock of the coordinator",
+            clock_skew.offset_ms()
+        );
+    }
+
     // Initialize the contributor.
-    let mut contribute = Contribute::new(opts, &environment, private_key, seed);
+    let mut contribute = Contribute::new(opts, &environment, private_key, seed, protocol_version, clock_skew);
+    // Coordinators accept commitments to the seed since protocol version 1.13.
+    if opts.commit_seed && protocol_version < (ProtocolVersion { major: 1, minor: 13 }) {
+        println!("Warning: the coordinator does not accept commitments to the seed, contributing without one");
+        warn!("Protocol version {} predates commitments to the seed", protocol_version);
+        contribute.commit_seed = false;
+    }
+    if opts.non_interactive || opts.runner_id.is_some() || opts.environment_hash.is_some() {
+        let machine = attest_machine(
+            opts.runner_id.clone(),
+            opts.environment_hash.clone(),
+            opts.entropy_file.is_some(),
+        )?;
+        info!("Attesting the machine {:?}", machine);
+        contribute.randomness_attestation.machine = Some(machine);
+    }
+    if !public_settings.artifact_replicas.is_empty() {
+        contribute
+            .select_artifact_replica(&public_settings.artifact_replicas, opts.region.as_deref())
+            .await;
+    }
 
     if public_settings.check_reliability {
         println!("Checking CPU performance, it may take a few minutes");
@@ -814,6 +1791,10 @@ async fn start_contributor(opts: &ContributeOptions, public_settings: &PublicSet
 
     if let Err(e) = contribution {
         info!("Error occurred during contribution: {}", e.to_string());
+        // Fail the CI job running the contributor, so that its operator notices.
+        if opts.non_interactive {
+            return Err(e);
+        }
     }
 
     Ok(())
@@ -841,8 +1822,10 @@ fn contributor_ids_in_chunk(chunk: &Chunk) -> HashSet<String> {
 
 #[cfg(test)]
 mod test {
-    use super::{chunk_all_verified, contributor_ids_in_chunk};
+    use super::{chunk_all_verified, contributor_ids_in_chunk, mix_entropy, MINIMUM_ENTROPY_BYTES};
+    use crate::utils::test_dir;
     use phase1_coordinator::objects::{Chunk, Participant};
+    use secrecy::{ExposeSecret, SecretVec};
 
     #[test]
     fn test_participant_ids_in_chunk() {
@@ -885,4 +1868,26 @@ mod test {
         assert!(ids.contains(&contributor1.to_string().replace(".contributor", "")));
         assert!(ids.contains(&contributor2.to_string().replace(".contributor", "")));
     }
+
+    #[test]
+    fn test_mix_entropy() {
+        let dir = test_dir("entropy");
+        let entropy_file = dir.join("entropy");
+        let seed = SecretVec::new(vec![7u8; 64]);
+
+        // The seed depends on the contents of the entropy file.
+        fs_err::write(&entropy_file, [1u8; MINIMUM_ENTROPY_BYTES]).unwrap();
+        let mixed = mix_entropy(&seed, &entropy_file).unwrap();
+        assert_eq!(64, mixed.expose_secret().len());
+        assert_ne!(seed.expose_secret(), mixed.expose_secret());
+        fs_err::write(&entropy_file, [2u8; MINIMUM_ENTROPY_BYTES]).unwrap();
+        let remixed = mix_entropy(&seed, &entropy_file).unwrap();
+        assert_ne!(mixed.expose_secret(), remixed.expose_secret());
+
+        // Too little entropy is rejected.
+        fs_err::write(&entropy_file, [1u8; MINIMUM_ENTROPY_BYTES - 1]).unwrap();
+        assert!(mix_entropy(&seed, &entropy_file).is_err());
+
+        fs_err::remove_dir_all(&dir).unwrap();
+    }
 }
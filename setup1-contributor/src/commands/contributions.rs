@@ -0,0 +1,52 @@
+//! Lists the contribution history of the ceremony, one page at a time
+
+use crate::cli::commands::contributions::ContributionsOptions;
+
+use setup1_shared::protocol::v1::{ContributionPage, ContributionsQuery};
+
+use anyhow::Result;
+use url::Url;
+
+pub async fn contributions(opts: &ContributionsOptions) -> Result<()> {
+    let client = reqwest::Client::new();
+    let mut query = ContributionsQuery {
+        round_height: opts.round,
+        chunk_id: opts.chunk,
+        contributor: opts.contributor.clone(),
+        status: opts.status,
+        since: opts.since,
+        until: opts.until,
+        cursor: opts.cursor.clone(),
+        limit: opts.limit,
+    };
+
+    loop {
+        let page = request_page(&client, &opts.api_url, &query).await?;
+        for contribution in &page.contributions {
+            println!("{}", serde_json::to_string(contribution)?);
+        }
+
+        match page.next_cursor {
+            Some(cursor) if opts.all => query.cursor = Some(cursor),
+            Some(cursor) => {
+                // Printed apart from the contributions, so the output can be piped as JSON lines.
+                eprintln!("Next cursor: {}", cursor);
+                return Ok(());
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+async fn request_page(
+    client: &reqwest::Client,
+    coordinator_url: &Url,
+    query: &ContributionsQuery,
+) -> Result<ContributionPage> {
+    let mut contributions_url = coordinator_url.join("/v1/contributions")?;
+    contributions_url.query_pairs_mut().extend_pairs(query.query_pairs());
+
+    let response = client.get(contributions_url).send().await?.error_for_status()?;
+    let data = response.bytes().await?;
+    Ok(serde_json::from_slice(&*data)?)
+}
@@ -0,0 +1,472 @@
+//! Diagnoses the common problems of the machines contributors run on, and how to fix them
+
+use crate::{
+    cli::commands::doctor::DoctorOptions,
+    errors::DoctorError,
+    utils::{create_parameters_for_chunk, measure_clock_skew},
+};
+
+use phase1::helpers::converters::CurveKind;
+use phase1_coordinator::environment::Environment;
+use setup1_shared::{
+    clock::{ClockSkew, MAX_CLOCK_SKEW},
+    protocol,
+    structures::PublicSettings,
+};
+use setup_utils::calculate_hash;
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use anyhow::Result;
+use memmap::MmapOptions;
+use std::{
+    fmt,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+use url::Url;
+
+/// The file descriptors the contributor keeps open besides those of the chunks,
+/// e.g. for its log and its connections to the coordinator.
+const BASE_FILE_DESCRIPTORS: u64 = 64;
+
+/// The file descriptors the contributor keeps open for each chunk it contributes to.
+const FILE_DESCRIPTORS_PER_CHUNK: u64 = 8;
+
+const BYTES_PER_GB: f64 = 1e9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "  OK  "),
+            Status::Warning => write!(f, " WARN "),
+            Status::Failed => write!(f, " FAIL "),
+        }
+    }
+}
+
+/// The outcome of a check, with the fix for the problem it found, if any
+struct Diagnosis {
+    check: &'static str,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Diagnosis {
+    fn ok(check: &'static str, detail: String) -> Self {
+        Self {
+            check,
+            status: Status::Ok,
+            detail,
+            fix: None,
+        }
+    }
+
+    fn warning(check: &'static str, detail: String, fix: &str) -> Self {
+        Self {
+            check,
+            status: Status::Warning,
+            detail,
+            fix: Some(fix.to_string()),
+        }
+    }
+
+    fn failed(check: &'static str, detail: String, fix: &str) -> Self {
+        Self {
+            check,
+            status: Status::Failed,
+            detail,
+            fix: Some(fix.to_string()),
+        }
+    }
+
+    fn print(&self) {
+        println!("[{}] {}: {}", self.status, self.check, self.detail);
+        if let Some(fix) = &self.fix {
+            println!("         fix: {}", fix);
+        }
+    }
+}
+
+/// The response of the coordinator to a request for its public settings
+struct CoordinatorResponse {
+    settings: PublicSettings,
+    round_trip: Duration,
+    clock_skew: Option<ClockSkew>,
+}
+
+pub async fn doctor(opts: &DoctorOptions) -> Result<()> {
+    let mut diagnoses = vec![];
+
+    // The public settings of the coordinator decide how large the chunks are.
+    let response = request_public_settings(&opts.api_url).await;
+    diagnoses.push(check_network(&opts.api_url, &response));
+    let response = response.ok();
+    let chunk_bytes = response
+        .as_ref()
+        .map(|response| chunk_bytes(&Environment::from(&response.settings.setup)));
+    let needed_bytes = chunk_bytes.map(|chunk_bytes| chunk_bytes * opts.parallel_chunks.max(1) as u64);
+
+    diagnoses.push(check_memory(needed_bytes));
+    diagnoses.push(check_disk(&opts.dir, needed_bytes));
+    diagnoses.push(check_file_descriptors(opts.parallel_chunks.max(1) as u64));
+    diagnoses.push(check_mmap(&opts.dir));
+    if let Some(response) = &response {
+        diagnoses.push(check_clock_skew(response));
+    }
+    if let Some(transcript) = &opts.transcript {
+        diagnoses.push(check_transcript(transcript, opts.transcript_hash.as_deref()));
+    }
+    diagnoses.push(check_gpu());
+
+    for diagnosis in &diagnoses {
+        diagnosis.print();
+    }
+
+    let failed = diagnoses
+        .iter()
+        .filter(|diagnosis| diagnosis.status == Status::Failed)
+        .count();
+    if failed > 0 {
+        return Err(DoctorError::ChecksFailedError(failed).into());
+    }
+    println!("This machine is ready to contribute.");
+    Ok(())
+}
+
+async fn request_public_settings(coordinator_url: &Url) -> Result<CoordinatorResponse> {
+    let settings_endpoint_url = coordinator_url.join("/v1/coordinator/settings")?;
+    let client = reqwest::Client::new();
+    let start = Instant::now();
+    let sent = SystemTime::now();
+    let response = client
+        .post(settings_endpoint_url)
+        .header(http::header::CONTENT_LENGTH, 0)
+        .send()
+        .await?
+        .error_for_status()?;
+    let round_trip = start.elapsed();
+    let received = SystemTime::now();
+    let headers = response.headers().clone();
+    let bytes = response.bytes().await?;
+    let settings = PublicSettings::decode(&bytes)
+        .map_err(|e| anyhow::anyhow!("Error decoding coordinator PublicSettings: {}", e))?;
+    let clock_skew = measure_clock_skew(sent, received, &headers, &settings);
+
+    Ok(CoordinatorResponse {
+        settings,
+        round_trip,
+        clock_skew,
+    })
+}
+
+/// Returns the size of the challenge and response files of the largest chunk,
+/// which are both memory mapped while contributing.
+fn chunk_bytes(environment: &Environment) -> u64 {
+    fn bytes<E: PairingEngine>(environment: &Environment) -> u64 {
+        let parameters = create_parameters_for_chunk::<E>(environment, 0).expect("Invalid chunk parameters");
+        (parameters.accumulator_size + parameters.contribution_size) as u64
+    }
+
+    match environment.parameters().curve() {
+        CurveKind::Bls12_377 => bytes::<Bls12_377>(environment),
+        CurveKind::BW6 => bytes::<BW6_761>(environment),
+    }
+}
+
+fn format_gb(bytes: u64) -> String {
+    format!("{:.2} GB", bytes as f64 / BYTES_PER_GB)
+}
+
+fn check_network(api_url: &Url, response: &Result<CoordinatorResponse>) -> Diagnosis {
+    const CHECK: &str = "network";
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            return Diagnosis::failed(
+                CHECK,
+                format!("could not reach the coordinator at {}: {}", api_url, error),
+                "check the coordinator URL, and that firewalls and proxies allow HTTPS connections to it",
+            );
+        }
+    };
+    if let Err(error) = protocol::negotiate(&response.settings.protocol_versions) {
+        return Diagnosis::failed(
+            CHECK,
+            format!(
+                "the coordinator speaks no protocol version of this contributor: {}",
+                error
+            ),
+            "install the latest release of setup1-contributor",
+        );
+    }
+    Diagnosis::ok(
+        CHECK,
+        format!(
+            "reached the coordinator at {} in {} ms",
+            api_url,
+            response.round_trip.as_millis()
+        ),
+    )
+}
+
+fn check_memory(needed_bytes: Option<u64>) -> Diagnosis {
+    const CHECK: &str = "memory";
+    let available = match available_memory() {
+        Some(available) => available,
+        None => {
+            return Diagnosis::warning(
+                CHECK,
+                "could not determine the available memory".to_string(),
+                "make sure the machine has enough free memory for the challenge and response of each chunk",
+            );
+        }
+    };
+    match needed_bytes {
+        Some(needed) if available < needed => Diagnosis::failed(
+            CHECK,
+            format!("{} available, but {} needed", format_gb(available), format_gb(needed)),
+            "close other programs, contribute to fewer chunks at once with --parallel-chunks, or use a larger machine",
+        ),
+        Some(needed) => Diagnosis::ok(
+            CHECK,
+            format!("{} available, {} needed", format_gb(available), format_gb(needed)),
+        ),
+        None => Diagnosis::ok(CHECK, format!("{} available", format_gb(available))),
+    }
+}
+
+fn check_disk(dir: &Path, needed_bytes: Option<u64>) -> Diagnosis {
+    const CHECK: &str = "disk";
+    let available = match available_disk_space(dir) {
+        Some(available) => available,
+        None => {
+            return Diagnosis::warning(
+                CHECK,
+                format!("could not determine the free space in {}", dir.display()),
+                "make sure the disk has enough free space for the challenge and response of each chunk",
+            );
+        }
+    };
+    match needed_bytes {
+        Some(needed) if available < needed => Diagnosis::failed(
+            CHECK,
+            format!(
+                "{} free in {}, but {} needed",
+                format_gb(available),
+                dir.display(),
+                format_gb(needed)
+            ),
+            "free up disk space, or run the contributor in a directory on a larger disk",
+        ),
+        Some(needed) => Diagnosis::ok(
+            CHECK,
+            format!(
+                "{} free in {}, {} needed",
+                format_gb(available),
+                dir.display(),
+                format_gb(needed)
+            ),
+        ),
+        None => Diagnosis::ok(CHECK, format!("{} free in {}", format_gb(available), dir.display())),
+    }
+}
+
+fn check_file_descriptors(parallel_chunks: u64) -> Diagnosis {
+    const CHECK: &str = "file descriptors";
+    let needed = BASE_FILE_DESCRIPTORS + FILE_DESCRIPTORS_PER_CHUNK * parallel_chunks;
+    match open_files_limit() {
+        Some(limit) if limit < needed => Diagnosis::failed(
+            CHECK,
+            format!("the limit of open files is {}, but {} are needed", limit, needed),
+            "raise the limit, e.g. with `ulimit -n 4096` in the shell the contributor is run from",
+        ),
+        Some(limit) => Diagnosis::ok(CHECK, format!("the limit of open files is {}", limit)),
+        None => Diagnosis::ok(CHECK, "this platform has no limit of open files".to_string()),
+    }
+}
+
+fn check_mmap(dir: &Path) -> Diagnosis {
+    const CHECK: &str = "mmap";
+    let path = dir.join(".doctor-mmap");
+    let result = (|| -> Result<()> {
+        let file = fs_err::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len(4096)?;
+        let mut map = unsafe { MmapOptions::new().map_mut(file.file())? };
+        map[4095] = 1;
+        map.flush()?;
+        Ok(())
+    })();
+    let _ = fs_err::remove_file(&path);
+
+    match result {
+        Ok(()) => Diagnosis::ok(CHECK, format!("files in {} can be memory mapped", dir.display())),
+        Err(error) => Diagnosis::failed(
+            CHECK,
+            format!("could not memory map a file in {}: {}", dir.display(), error),
+            "run the contributor in a writable directory on a local disk, not on a network or FUSE file system",
+        ),
+    }
+}
+
+fn check_clock_skew(response: &CoordinatorResponse) -> Diagnosis {
+    const CHECK: &str = "clock";
+    let skew = match response.clock_skew {
+        Some(clock_skew) => clock_skew.magnitude(),
+        None => {
+            return Diagnosis::warning(
+                CHECK,
+                "the coordinator did not send its time".to_string(),
+                "make sure the clock is synchronized, e.g. with NTP",
+            );
+        }
+    };
+    if skew > MAX_CLOCK_SKEW {
+        Diagnosis::warning(
+            CHECK,
+            format!("the clock is {} s off the clock of the coordinator", skew.as_secs()),
+            "synchronize the clock, e.g. with `sudo timedatectl set-ntp true`",
+        )
+    } else {
+        Diagnosis::ok(
+            CHECK,
+            format!("the clock is within {} s of the coordinator", MAX_CLOCK_SKEW.as_secs()),
+        )
+    }
+}
+
+fn check_transcript(transcript: &Path, expected_hash: Option<&str>) -> Diagnosis {
+    const CHECK: &str = "phase 1 transcript";
+    let hash = (|| -> Result<String> {
+        let file = fs_err::File::open(transcript)?;
+        let map = unsafe { MmapOptions::new().map(file.file())? };
+        Ok(hex::encode(calculate_hash(&map)))
+    })();
+
+    match (hash, expected_hash) {
+        (Err(error), _) => Diagnosis::failed(
+            CHECK,
+            format!("could not read {}: {}", transcript.display(), error),
+            "download the phase 1 transcript to the given path",
+        ),
+        (Ok(hash), Some(expected)) if !hash.eq_ignore_ascii_case(expected) => Diagnosis::failed(
+            CHECK,
+            format!(
+                "{} has hash {}, but {} was expected",
+                transcript.display(),
+                hash,
+                expected
+            ),
+            "download the phase 1 transcript again, from a source listed by the ceremony",
+        ),
+        (Ok(hash), Some(_)) => Diagnosis::ok(
+            CHECK,
+            format!("{} has the expected hash {}", transcript.display(), hash),
+        ),
+        (Ok(hash), None) => Diagnosis::ok(CHECK, format!("{} has hash {}", transcript.display(), hash)),
+    }
+}
+
+fn check_gpu() -> Diagnosis {
+    const CHECK: &str = "gpu";
+    // Contributions are computed on the CPU, so a GPU is reported for information only.
+    let cpus = num_cpus::get();
+    let devices = ["/dev/nvidia0", "/dev/kfd", "/dev/dri/renderD128"];
+    match devices.iter().find(|device| Path::new(device).exists()) {
+        Some(device) => Diagnosis::ok(
+            CHECK,
+            format!("found {}, but contributions are computed on the {} CPUs", device, cpus),
+        ),
+        None => Diagnosis::ok(
+            CHECK,
+            format!("none found, contributions are computed on the {} CPUs", cpus),
+        ),
+    }
+}
+
+/// Returns the memory available to new programs, in bytes.
+#[cfg(target_os = "linux")]
+fn available_memory() -> Option<u64> {
+    let meminfo = fs_err::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn available_memory() -> Option<u64> {
+    None
+}
+
+/// Returns the free space of the file system of the given directory, in bytes.
+#[cfg(unix)]
+fn available_disk_space(dir: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_disk_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+/// Returns the soft limit of open files of this process.
+#[cfg(unix)]
+fn open_files_limit() -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 || limit.rlim_cur == libc::RLIM_INFINITY {
+        return None;
+    }
+    Some(limit.rlim_cur as u64)
+}
+
+#[cfg(not(unix))]
+fn open_files_limit() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir;
+
+    #[test]
+    fn test_check_transcript() {
+        let dir = test_dir("doctor");
+        let transcript = dir.join("transcript");
+        fs_err::write(&transcript, b"powers of tau").unwrap();
+        let hash = hex::encode(calculate_hash(b"powers of tau"));
+
+        assert_eq!(Status::Ok, check_transcript(&transcript, None).status);
+        assert_eq!(
+            Status::Ok,
+            check_transcript(&transcript, Some(&hash.to_uppercase())).status
+        );
+        assert_eq!(Status::Failed, check_transcript(&transcript, Some("00")).status);
+        assert_eq!(Status::Failed, check_transcript(&dir.join("missing"), None).status);
+        assert_eq!(Status::Ok, check_mmap(&dir).status);
+
+        fs_err::remove_dir_all(&dir).unwrap();
+    }
+}
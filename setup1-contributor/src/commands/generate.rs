@@ -1,17 +1,43 @@
 use fs_err::File;
 use std::io::Write;
 
-use crate::cli::commands::generate::GenerateOptions;
+use crate::{cli::commands::generate::GenerateOptions, setup_keys::party::Party};
 
 pub fn generate_keys(opts: GenerateOptions) {
+    let party = if opts.party {
+        Some(Party::prompt().expect("Should have read the entropy of the party"))
+    } else {
+        None
+    };
+
     let mut file = File::create(&opts.keys_path).expect("Should have created keys file");
     let passphrase = crate::setup_keys::read_or_generate_passphrase(opts.passphrase);
 
     println!("\nDO NOT FORGET YOUR PASSPHRASE!\n\nYou will need your passphrase to access your keys.\n\n");
 
-    let aleo_setup_keys = crate::setup_keys::generate(passphrase);
+    let aleo_setup_keys = match &party {
+        Some(party) => crate::setup_keys::generate_for_party(passphrase, party),
+        None => crate::setup_keys::generate(passphrase),
+    };
 
     file.write_all(&serde_json::to_vec(&aleo_setup_keys).expect("Should have converted setup keys to vector"))
         .expect("Should have written setup keys successfully to file");
     println!("Done! Your keys are ready in {:?}.", &opts.keys_path);
+
+    if let Some(party) = party {
+        let attestation_path = opts
+            .attestation_path
+            .unwrap_or_else(|| opts.keys_path.with_extension("attestation.json"));
+        let attestation = party.attestation();
+        fs_err::write(
+            &attestation_path,
+            serde_json::to_vec_pretty(&attestation).expect("Should have converted attestation to vector"),
+        )
+        .expect("Should have written attestation successfully to file");
+        println!(
+            "The entropy of {} members was mixed in, see the attestation in {:?}.",
+            attestation.members.len(),
+            &attestation_path
+        );
+    }
 }
@@ -1,5 +1,17 @@
+pub mod check_commitments;
+pub use check_commitments::*;
+
 pub mod contribute;
 pub use contribute::*;
 
+pub mod contributions;
+pub use contributions::*;
+
+pub mod doctor;
+pub use doctor::*;
+
 pub mod generate;
 pub use generate::*;
+
+pub mod reserve;
+pub use reserve::*;
@@ -0,0 +1,95 @@
+//! Reserves a time window for the contributor to contribute in, for
+//! organizations which have to schedule their contribution in advance
+
+use crate::{cli::commands::reserve::ReserveOptions, commands::contribute::read_keys, utils::get_authorization_value};
+
+use setup1_shared::protocol::v1::{SlotReservation, SlotReservationRequest};
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, PrivateKey};
+
+use anyhow::{anyhow, Result};
+use std::time::{Duration, SystemTime};
+
+pub async fn reserve(opts: &ReserveOptions) -> Result<()> {
+    let passphrase = crate::setup_keys::read_passphrase(opts.passphrase.clone())?;
+    let (_, private_key) = read_keys(&opts.keys_path, &passphrase)?;
+
+    if let Some(id) = opts.cancel {
+        cancel_reservation(opts, &private_key, id).await?;
+        println!("Cancelled reservation {}", id);
+        return Ok(());
+    }
+
+    let starts_at = opts
+        .start
+        .ok_or_else(|| anyhow!("The start of the time window is missing"))?;
+    let ends_at = opts
+        .hours
+        .checked_mul(3600)
+        .and_then(|seconds| starts_at.checked_add(seconds))
+        .ok_or_else(|| anyhow!("The end of the time window is out of range"))?;
+    let request = SlotReservationRequest { starts_at, ends_at };
+    let reservation = request_reservation(opts, &private_key, &request).await?;
+    fs_err::write(&opts.output, &reservation.ics)?;
+
+    println!(
+        "Reserved the time window from {} to {} as reservation {}",
+        http_date(reservation.starts_at),
+        http_date(reservation.ends_at),
+        reservation.id
+    );
+    println!(
+        "Wrote the calendar event of the reservation to {}",
+        opts.output.display()
+    );
+    println!("Start the contributor before the time window opens, so that it is in the queue once it does.");
+
+    Ok(())
+}
+
+async fn request_reservation(
+    opts: &ReserveOptions,
+    private_key: &PrivateKey<Testnet2Parameters>,
+    request: &SlotReservationRequest,
+) -> Result<SlotReservation> {
+    let reservations_path = "/v1/contributor/reservations";
+    let reservations_url = opts.api_url.join(reservations_path)?;
+    let auth_rng = &mut rand::rngs::OsRng;
+    let authorization = get_authorization_value(private_key, "POST", reservations_path, auth_rng)?;
+    let bytes = serde_json::to_vec(request)?;
+
+    let response = reqwest::Client::new()
+        .post(reservations_url)
+        .header(http::header::AUTHORIZATION, authorization)
+        .header(http::header::CONTENT_LENGTH, bytes.len())
+        .body(bytes)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let data = response.bytes().await?;
+    Ok(serde_json::from_slice(&*data)?)
+}
+
+async fn cancel_reservation(
+    opts: &ReserveOptions,
+    private_key: &PrivateKey<Testnet2Parameters>,
+    id: u64,
+) -> Result<()> {
+    let reservation_path = format!("/v1/contributor/reservations/{}", id);
+    let reservation_url = opts.api_url.join(&reservation_path)?;
+    let auth_rng = &mut rand::rngs::OsRng;
+    let authorization = get_authorization_value(private_key, "DELETE", &reservation_path, auth_rng)?;
+
+    reqwest::Client::new()
+        .delete(reservation_url)
+        .header(http::header::AUTHORIZATION, authorization)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+fn http_date(timestamp: i64) -> String {
+    httpdate::fmt_http_date(SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp.max(0) as u64))
+}
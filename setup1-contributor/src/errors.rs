@@ -1,15 +1,39 @@
+use setup1_shared::protocol::v1::ErrorCode;
+
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ContributeError {
+    #[error("The downloaded challenge for chunk {} has hash {}, but {} lists {}", _0, _1, _2, _3)]
+    ChallengeHashMismatchError(u64, String, &'static str, String),
+    #[error("The downloaded challenge for chunk {} is corrupted at byte {} after {} attempts", _0, _1, _2)]
+    ChallengeCorruptedError(u64, u64, usize),
+    #[error("The coordinator did not advertise the challenge hash for chunk {}", _0)]
+    ChallengeHashMissingError(u64),
+    #[error("The transcript digest is for round {}, but the current round is {}", _0, _1)]
+    TranscriptDigestRoundMismatchError(u64, u64),
+    #[error("Released chunk {} which this contributor is too slow to contribute to in time", _0)]
+    ChunkReleasedError(u64),
+    #[error("The coordinator rejected the request with {:?}: {}", _0, _1)]
+    RequestRejectedError(ErrorCode, String),
     #[error("Could not read passphrase")]
     CouldNotReadPassphraseError,
+    #[error("The entropy file holds {} bytes, but at least {} are required", _0, _1)]
+    EntropyFileTooShortError(usize, usize),
+    #[error("A passphrase is required in non-interactive mode, pass --passphrase or set ALEO_SETUP_PASSPHRASE")]
+    PassphraseRequiredError,
     #[error("Failed running contribute")]
     FailedRunningContributeError,
     #[error("Unsupported decryptor")]
     UnsupportedDecryptorError,
 }
 
+#[derive(Debug, Error)]
+pub enum DoctorError {
+    #[error("{} of the checks failed", _0)]
+    ChecksFailedError(usize),
+}
+
 #[derive(Debug, Error)]
 pub enum GenerateError {}
 
@@ -18,6 +42,9 @@ pub enum CLIError {
     #[error("{}", _0)]
     ContributeError(ContributeError),
 
+    #[error("{}", _0)]
+    DoctorError(DoctorError),
+
     #[error("{}", _0)]
     GenerateError(GenerateError),
 }
@@ -28,6 +55,12 @@ impl From<ContributeError> for CLIError {
     }
 }
 
+impl From<DoctorError> for CLIError {
+    fn from(error: DoctorError) -> Self {
+        CLIError::DoctorError(error)
+    }
+}
+
 impl From<GenerateError> for CLIError {
     fn from(error: GenerateError) -> Self {
         CLIError::GenerateError(error)
@@ -4,13 +4,14 @@ use structopt::StructOpt;
 mod cli;
 mod commands;
 mod errors;
-mod objects;
+mod randomness;
 mod reliability;
 mod setup_keys;
 mod utils;
+mod verified_download;
 
 use cli::{Command, Options};
-use commands::{contribute_subcommand, generate_keys};
+use commands::{check_commitments, contribute_subcommand, contributions, doctor, generate_keys, reserve};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -26,6 +27,10 @@ async fn main() -> anyhow::Result<()> {
         Command::Contribute(contribute_opts) => {
             contribute_subcommand(&contribute_opts).await?;
         }
+        Command::Doctor(doctor_opts) => doctor(&doctor_opts).await?,
+        Command::Contributions(contributions_opts) => contributions(&contributions_opts).await?,
+        Command::Reserve(reserve_opts) => reserve(&reserve_opts).await?,
+        Command::CheckCommitments(check_commitments_opts) => check_commitments(&check_commitments_opts).await?,
     }
 
     Ok(())
@@ -0,0 +1,248 @@
+use anyhow::{anyhow, ensure};
+use serde::{Deserialize, Serialize};
+use setup1_shared::protocol::v1::{MachineAttestation, SignedSeedCommitmentReceipt};
+use setup_utils::{calculate_hash, seed_commitment, CHUNK_SEED_DERIVATION, SEED_COMMITMENT_SCHEME};
+use std::path::Path;
+
+/// The environment variables common CI systems set to the ID of the runner
+/// a job runs on: GitHub Actions, GitLab CI, Buildkite and Jenkins.
+const RUNNER_ID_VARIABLES: [&str; 4] = ["RUNNER_NAME", "CI_RUNNER_ID", "BUILDKITE_AGENT_ID", "NODE_NAME"];
+
+/// The public record of how the randomness of every chunk contributed to was derived
+/// from the seed of the contributor. It holds nothing secret: auditors can check the
+/// challenge hashes against the transcript, and that no two chunks share the inputs
+/// of the derivation. In the rounds with a commitment, the randomness is derived with
+/// `setup_utils::derive_rng_for_committed_chunk` instead.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RandomnessAttestation {
+    pub participant_id: String,
+    /// The name of the derivation scheme, as documented by `setup_utils::derive_rng_for_chunk`.
+    pub derivation: String,
+    pub chunks: Vec<ChunkRandomness>,
+    /// The machine the contributor ran on, if it was run non-interactively.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine: Option<MachineAttestation>,
+    /// The commitments to the seed, one per round, made before the contributor locked
+    /// its first chunk of the round.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seed_commitments: Vec<SeedCommitmentRecord>,
+}
+
+/// A commitment to the seed, with what it was computed from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedCommitmentRecord {
+    /// The receipt of the coordinator for the commitment.
+    #[serde(flatten)]
+    pub signed: SignedSeedCommitmentReceipt,
+    /// The hex encoded hashes of the points in G1 of the public key of every chunk of
+    /// the round, see `phase1::PublicKey::g1_hash`, which the commitment is computed over.
+    #[serde(default)]
+    pub public_key_hashes: Vec<String>,
+}
+
+/// Attests the machine this contributor runs on, reading the runner ID from the
+/// environment and hashing the contributor executable where they are not given.
+pub fn attest_machine(
+    runner_id: Option<String>,
+    environment_hash: Option<String>,
+    external_entropy: bool,
+) -> anyhow::Result<MachineAttestation> {
+    let runner_id = runner_id.or_else(|| {
+        RUNNER_ID_VARIABLES
+            .iter()
+            .find_map(|variable| std::env::var(variable).ok().filter(|id| !id.is_empty()))
+    });
+    let environment_hash = match environment_hash {
+        Some(environment_hash) => environment_hash,
+        None => hex::encode(calculate_hash(&fs_err::read(std::env::current_exe()?)?)),
+    };
+    Ok(MachineAttestation {
+        runner_id,
+        environment_hash,
+        external_entropy,
+    })
+}
+
+/// The public inputs the randomness of a chunk was derived from, besides the seed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkRandomness {
+    pub round_height: u64,
+    pub chunk_id: u64,
+    /// The hex encoded hash of the challenge file.
+    pub challenge_hash: String,
+    /// The hex encoded hash of the points in G1 of the public key of the contribution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_hash: Option<String>,
+}
+
+impl RandomnessAttestation {
+    /// Loads the attestation at the given path, so that a restarted contributor keeps
+    /// its record, or starts a new one if there is none for this contributor.
+    pub fn load(path: &Path, participant_id: &str) -> anyhow::Result<Self> {
+        if path.exists() {
+            let attestation: Self = serde_json::from_slice(&fs_err::read(path)?)?;
+            if attestation.participant_id == participant_id && attestation.derivation == CHUNK_SEED_DERIVATION {
+                return Ok(attestation);
+            }
+        }
+        Ok(Self {
+            participant_id: participant_id.to_string(),
+            derivation: CHUNK_SEED_DERIVATION.to_string(),
+            chunks: vec![],
+            machine: None,
+            seed_commitments: vec![],
+        })
+    }
+
+    /// Records the inputs of the randomness of a chunk, and writes the attestation.
+    pub fn record(&mut self, path: &Path, chunk: ChunkRandomness) -> anyhow::Result<()> {
+        self.chunks.push(chunk);
+        self.save(path)
+    }
+
+    /// Records a commitment to the seed, and writes the attestation.
+    pub fn record_seed_commitment(&mut self, path: &Path, commitment: SeedCommitmentRecord) -> anyhow::Result<()> {
+        self.seed_commitments.push(commitment);
+        self.save(path)
+    }
+
+    /// Returns whether the seed was committed to in the round with the given height.
+    pub fn has_seed_commitment(&self, round_height: u64) -> bool {
+        self.seed_commitments
+            .iter()
+            .any(|commitment| commitment.signed.receipt.round_height == round_height)
+    }
+
+    /// Checks that every commitment to the seed is computed over the hashes of the
+    /// public keys recorded with it, and that the public key of every chunk contributed
+    /// to in a round with a commitment is the committed one. Together with
+    /// `check_public_key`, this shows that the randomness of the contributions was fixed
+    /// before the contributor saw their challenges.
+    pub fn check_seed_commitments(&self) -> anyhow::Result<()> {
+        for commitment in &self.seed_commitments {
+            let receipt = &commitment.signed.receipt;
+            ensure!(
+                receipt.scheme == SEED_COMMITMENT_SCHEME,
+                "Unknown seed commitment scheme {}",
+                receipt.scheme
+            );
+            let public_key_hashes = commitment
+                .public_key_hashes
+                .iter()
+                .map(hex::decode)
+                .collect::<Result<Vec<_>, _>>()?;
+            ensure!(
+                receipt.commitment == hex::encode(seed_commitment(&public_key_hashes)),
+                "The public keys do not open the commitment of round {}",
+                receipt.round_height
+            );
+        }
+        for chunk in &self.chunks {
+            if self.has_seed_commitment(chunk.round_height) {
+                let public_key_hash = chunk.public_key_hash.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "The public key of chunk {} of round {} is not recorded",
+                        chunk.chunk_id,
+                        chunk.round_height
+                    )
+                })?;
+                self.check_public_key(chunk.round_height, chunk.chunk_id, &hex::decode(public_key_hash)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the hash of the points in G1 of a public key published with a
+    /// contribution to a chunk is the one committed to for the chunk.
+    pub fn check_public_key(&self, round_height: u64, chunk_id: u64, public_key_hash: &[u8]) -> anyhow::Result<()> {
+        let commitment = self
+            .seed_commitments
+            .iter()
+            .find(|commitment| commitment.signed.receipt.round_height == round_height)
+            .ok_or_else(|| anyhow!("The seed was not committed to in round {}", round_height))?;
+        let committed = commitment
+            .public_key_hashes
+            .get(chunk_id as usize)
+            .ok_or_else(|| anyhow!("Chunk {} is not committed to in round {}", chunk_id, round_height))?;
+        ensure!(
+            *committed == hex::encode(public_key_hash),
+            "The public key of chunk {} of round {} is not the committed one",
+            chunk_id,
+            round_height
+        );
+        Ok(())
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs_err::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use setup1_shared::protocol::v1::SeedCommitmentReceipt;
+
+    fn commitment_record(round_height: u64, public_key_hashes: &[[u8; 64]]) -> SeedCommitmentRecord {
+        SeedCommitmentRecord {
+            signed: SignedSeedCommitmentReceipt {
+                receipt: SeedCommitmentReceipt {
+                    round_height,
+                    contributor: "aleo1contributor".to_string(),
+                    scheme: SEED_COMMITMENT_SCHEME.to_string(),
+                    commitment: hex::encode(seed_commitment(public_key_hashes)),
+                    committed_at: 0,
+                },
+                signature_scheme: "test".to_string(),
+                signature: String::new(),
+            },
+            public_key_hashes: public_key_hashes.iter().map(hex::encode).collect(),
+        }
+    }
+
+    fn chunk_randomness(round_height: u64, chunk_id: u64, public_key_hash: &[u8; 64]) -> ChunkRandomness {
+        ChunkRandomness {
+            round_height,
+            chunk_id,
+            challenge_hash: hex::encode([0u8; 64]),
+            public_key_hash: Some(hex::encode(public_key_hash)),
+        }
+    }
+
+    #[test]
+    fn test_check_seed_commitments() {
+        let path = std::env::temp_dir().join(format!("test-seed-commitments-{}.json", std::process::id()));
+        let public_key_hashes = [[1u8; 64], [2u8; 64]];
+        let mut attestation = RandomnessAttestation::load(&path, "aleo1contributor").unwrap();
+        attestation
+            .record_seed_commitment(&path, commitment_record(1, &public_key_hashes))
+            .unwrap();
+        assert!(attestation.has_seed_commitment(1));
+        assert!(!attestation.has_seed_commitment(2));
+
+        attestation.record(&path, chunk_randomness(1, 1, &[2u8; 64])).unwrap();
+        attestation.check_seed_commitments().unwrap();
+        attestation.check_public_key(1, 0, &[1u8; 64]).unwrap();
+        assert!(attestation.check_public_key(1, 0, &[2u8; 64]).is_err());
+        assert!(attestation.check_public_key(1, 2, &[1u8; 64]).is_err());
+        assert!(attestation.check_public_key(2, 0, &[1u8; 64]).is_err());
+        assert_eq!(
+            attestation,
+            RandomnessAttestation::load(&path, "aleo1contributor").unwrap()
+        );
+
+        // A chunk contributed to with a key other than the committed one is caught.
+        attestation.chunks.push(chunk_randomness(1, 0, &[2u8; 64]));
+        assert!(attestation.check_seed_commitments().is_err());
+        attestation.chunks.pop();
+
+        // So are public keys which do not open the commitment.
+        attestation.seed_commitments[0].public_key_hashes.reverse();
+        assert!(attestation.check_seed_commitments().is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -73,6 +73,20 @@ impl ConfirmationKey {
     }
 }
 
+/// Prints the address of the confirmation key and the path of the file it is kept in,
+/// without the key itself, since the output of non-interactive runs is usually logged.
+pub fn print_key_location() -> Result<()> {
+    let key_file = ConfirmationKey::read_from_disk()?;
+    println!("Your address is {}", key_file.address);
+    println!(
+        "And confirmation key is kept in {}",
+        fs::canonicalize(CONFIRMATION_KEY_FILE)?.display()
+    );
+    println!("Move this file somewhere safe in order to prove your participation in the setup");
+    println!("Do not share the confirmation key with others!");
+    Ok(())
+}
+
 pub fn print_key_and_remove_the_file() -> Result<()> {
     let key_file = ConfirmationKey::read_from_disk()?;
     println!("|||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||");
@@ -14,6 +14,7 @@ use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey}
 use crate::errors::ContributeError;
 
 pub mod confirmation_key;
+pub mod party;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -68,6 +69,13 @@ pub fn generate(passphrase: SecretString) -> AleoSetupKeys {
     encrypt_keys(&unencrypted, passphrase)
 }
 
+/// Generate keys whose seed also mixes in the entropy of every member of the party.
+pub fn generate_for_party(passphrase: SecretString, party: &party::Party) -> AleoSetupKeys {
+    let mut unencrypted = generate_unencrypted();
+    unencrypted.seed = party.mix(&unencrypted.seed);
+    encrypt_keys(&unencrypted, passphrase)
+}
+
 /// If `cli_passphrase` is `None`, request passphrase via pinentry or tty
 pub fn read_passphrase(cli_passphrase: Option<SecretString>) -> Result<SecretString> {
     if let Some(passphrase) = cli_passphrase {
@@ -0,0 +1,179 @@
+use anyhow::Result;
+use blake2::{Blake2b, Blake2s, Digest};
+use dialoguer::{theme::ColorfulTheme, Input, Password};
+use rand::{rngs::OsRng, RngCore};
+use secrecy::{ExposeSecret, SecretString, SecretVec};
+use serde::{Deserialize, Serialize};
+
+/// The entropy typed in by a single member of the party.
+pub struct PartyInput {
+    name: String,
+    entropy: SecretString,
+    salt: [u8; 16],
+}
+
+impl PartyInput {
+    pub fn new(name: String, entropy: SecretString) -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self { name, entropy, salt }
+    }
+
+    /// Return the blake2s hash of the salt and the entropy as hex string.
+    ///
+    /// Only the member knows the salt, so the commitment reveals nothing
+    /// about their input, even if it was a short phrase.
+    fn commitment(&self) -> String {
+        let mut hasher = Blake2s::new();
+        hasher.update(&self.salt);
+        hasher.update(self.entropy.expose_secret().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Several people feeding entropy into the same contribution,
+/// for example at a public ceremony event.
+#[derive(Default)]
+pub struct Party {
+    inputs: Vec<PartyInput>,
+}
+
+/// The public record of who contributed entropy to a party contribution.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyAttestation {
+    pub members: Vec<PartyMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyMember {
+    pub name: String,
+    /// The blake2s hash of the member's salt and entropy
+    pub commitment: String,
+}
+
+impl Party {
+    pub fn push(&mut self, input: PartyInput) {
+        self.inputs.push(input);
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Mix the entropy of every member into the given seed.
+    ///
+    /// The seed stays the first input, so the result is never weaker
+    /// than the randomness of the machine, whatever the members type.
+    pub fn mix(&self, seed: &SecretVec<u8>) -> SecretVec<u8> {
+        let mut hasher = Blake2b::new();
+        hasher.update(seed.expose_secret());
+        for input in &self.inputs {
+            // Length prefixes keep the boundaries between inputs unambiguous
+            for bytes in [input.name.as_bytes(), input.entropy.expose_secret().as_bytes()] {
+                hasher.update(&(bytes.len() as u64).to_le_bytes());
+                hasher.update(bytes);
+            }
+        }
+        SecretVec::new(hasher.finalize().to_vec())
+    }
+
+    pub fn attestation(&self) -> PartyAttestation {
+        PartyAttestation {
+            members: self
+                .inputs
+                .iter()
+                .map(|input| PartyMember {
+                    name: input.name.clone(),
+                    commitment: input.commitment(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Walk the party through entering their entropy, one member at a time,
+    /// until an empty name is entered.
+    pub fn prompt() -> Result<Self> {
+        let theme = ColorfulTheme::default();
+        let mut party = Self::default();
+
+        println!("Party mode: every member enters a name and then types a phrase or mashes the keyboard.");
+        loop {
+            let name: String = Input::with_theme(&theme)
+                .with_prompt(format!("Name of member {} (leave empty to finish)", party.len() + 1))
+                .allow_empty(true)
+                .interact_text()?;
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                if party.is_empty() {
+                    println!("At least one member has to enter entropy.");
+                    continue;
+                }
+                break;
+            }
+
+            let entropy = Password::with_theme(&theme)
+                .with_prompt(format!("{}, type your phrase or mash the keyboard", name))
+                .interact()?;
+            let input = PartyInput::new(name, SecretString::new(entropy));
+            println!(
+                "{}, your salt is {}. Keep it to prove your input is the one committed to in the attestation.",
+                input.name,
+                hex::encode(&input.salt)
+            );
+            party.push(input);
+        }
+
+        Ok(party)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(name: &str, entropy: &str) -> PartyInput {
+        PartyInput {
+            name: name.to_string(),
+            entropy: SecretString::new(entropy.to_string()),
+            salt: [7u8; 16],
+        }
+    }
+
+    #[test]
+    fn test_mix_depends_on_every_input() {
+        let seed = SecretVec::new(vec![1u8; 64]);
+
+        let mut party = Party::default();
+        party.push(input("alice", "correct horse"));
+        let alone = party.mix(&seed);
+        assert_eq!(alone.expose_secret().len(), 64);
+        assert_ne!(alone.expose_secret(), seed.expose_secret());
+
+        party.push(input("bob", "battery staple"));
+        assert_ne!(party.mix(&seed).expose_secret(), alone.expose_secret());
+
+        // Moving characters between the name and the entropy changes the seed
+        let mut shifted = Party::default();
+        shifted.push(input("alic", "ecorrect horse"));
+        assert_ne!(shifted.mix(&seed).expose_secret(), alone.expose_secret());
+    }
+
+    #[test]
+    fn test_attestation_records_every_member() {
+        let mut party = Party::default();
+        party.push(input("alice", "correct horse"));
+        party.push(input("bob", "correct horse"));
+
+        let attestation = party.attestation();
+        let names: Vec<_> = attestation.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["alice", "bob"]);
+        assert!(!attestation.members[0].commitment.contains("correct"));
+        assert_eq!(attestation.members[0].commitment.len(), 64);
+    }
+}
@@ -1,23 +1,23 @@
 use phase1::{ContributionMode, Phase1Parameters};
 use phase1_coordinator::{
-    environment::{Development, Environment, Parameters, Production},
+    authentication::AleoSignature,
+    environment::Environment,
     objects::{ContributionFileSignature, ContributionState},
 };
-use setup1_shared::structures::SetupKind;
+pub use setup1_shared::authorization::get_authorization_value;
+use setup1_shared::{clock::ClockSkew, structures::PublicSettings};
 use snarkvm_curves::PairingEngine;
-use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey, ViewKey};
-use snarkvm_utilities::ToBytes;
 
 use anyhow::Result;
 #[cfg(test)]
-use fs_err::{create_dir_all, write};
-use rand::{CryptoRng, Rng};
+use fs_err::{create_dir_all, remove_dir_all, write};
+#[cfg(test)]
+use std::path::PathBuf;
 use std::{
-    convert::TryFrom,
     fs::{remove_file, File},
     io::Read,
     path::Path,
-    str::FromStr,
+    time::SystemTime,
 };
 #[cfg(test)]
 use tracing::error;
@@ -70,6 +70,18 @@ pub fn write_to_file(file_path: &str, file_bytes: Vec<u8>) -> Result<()> {
     Ok(())
 }
 
+///
+/// This function returns an empty directory for the test of the given name in the temporary
+/// directory, unique to this process, so that concurrent test runs do not share it.
+///
+#[cfg(test)]
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("setup1-contributor-{}-{}", name, std::process::id()));
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
 pub fn create_parameters_for_chunk<E: PairingEngine>(
     environment: &Environment,
     chunk_id: usize,
@@ -87,75 +99,36 @@ pub fn create_parameters_for_chunk<E: PairingEngine>(
     Ok(parameters)
 }
 
-pub fn get_authorization_value<R: Rng + CryptoRng>(
-    private_key: &PrivateKey<Testnet2Parameters>,
-    method: &str,
-    path: &str,
-    rng: &mut R,
-) -> Result<String> {
-    let view_key = ViewKey::try_from(private_key)?;
-    let address = Address::try_from(private_key)?.to_string();
-
-    let message = format!("{} {}", method.to_lowercase(), path.to_lowercase());
-    let signature = hex::encode(&view_key.sign(message.as_bytes(), rng)?.to_bytes_le()?);
-
-    let authorization = format!("Aleo {}:{}", address, signature);
-    Ok(authorization)
-}
-
 ///
 /// Signs and returns the contribution file signature.
 ///
-pub fn sign_contribution_state<R: Rng + CryptoRng>(
+pub fn sign_contribution_state(
     signing_key: &str,
     challenge_hash: &[u8],
     response_hash: &[u8],
     next_challenge_hash: Option<Vec<u8>>,
-    rng: &mut R,
 ) -> Result<ContributionFileSignature> {
     let contribution_state =
         ContributionState::new(challenge_hash.to_vec(), response_hash.to_vec(), next_challenge_hash)?;
-    let message = contribution_state.signature_message()?;
 
-    let view_key = ViewKey::<Testnet2Parameters>::from_str(signing_key)?;
-    let signature = hex::encode(&view_key.sign(message.as_bytes(), rng)?.to_bytes_le()?);
-
-    let contribution_file_signature = ContributionFileSignature::new(signature, contribution_state)?;
+    let contribution_file_signature = ContributionFileSignature::sign(&AleoSignature, signing_key, contribution_state)?;
 
     Ok(contribution_file_signature)
 }
 
-#[inline]
-fn development_environment() -> Environment {
-    let environment = Development::from(Parameters::TestCustom {
-        number_of_chunks: 64,
-        power: 16,
-        batch_size: 512,
-    });
-    environment.into()
-}
-
-#[inline]
-fn inner_environment() -> Environment {
-    Production::from(Parameters::AleoInner).into()
-}
-
-#[inline]
-fn outer_environment() -> Environment {
-    Production::from(Parameters::AleoOuter).into()
-}
-
-#[inline]
-fn universal_environment() -> Environment {
-    Production::from(Parameters::AleoUniversal).into()
-}
-
-/// Returns the [Environment] settings based on a setup kind
-pub fn environment_by_setup_kind(kind: &SetupKind) -> Environment {
-    match kind {
-        SetupKind::Development => development_environment(),
-        SetupKind::Inner => inner_environment(),
-        SetupKind::Outer => outer_environment(),
-        SetupKind::Universal => universal_environment(),
-    }
+/// Measures the skew of the local clock from the public settings of the coordinator,
+/// requested at `sent` and received at `received`. Coordinators which predate
+/// protocol version 1.11 only tell their time to the second, in the `Date` header.
+pub fn measure_clock_skew(
+    sent: SystemTime,
+    received: SystemTime,
+    headers: &http::HeaderMap,
+    settings: &PublicSettings,
+) -> Option<ClockSkew> {
+    let coordinator_time_ms = settings.server_time_ms.or_else(|| {
+        let date = headers.get(http::header::DATE)?.to_str().ok()?;
+        let date = httpdate::parse_http_date(date).ok()?;
+        Some(date.duration_since(SystemTime::UNIX_EPOCH).ok()?.as_millis() as i64)
+    })?;
+    Some(ClockSkew::measure(sent, received, coordinator_time_ms))
 }
@@ -0,0 +1,145 @@
+//! Verification of challenges while they are downloaded, with BLAKE3's verified streaming (bao).
+//!
+//! The coordinator advertises the BLAKE3 root hash of a challenge and serves its bao outboard
+//! tree, so each block of the challenge is checked as soon as it is written. The outboard is
+//! read from a file, as it grows with the challenge. A corrupted block is
+//! caught after at most a block of wasted download rather than after gigabytes, and only the
+//! challenge from that block on has to be fetched again.
+
+use anyhow::Result;
+use bao::{decode::Decoder, Hash};
+use fs_err::File;
+use std::{
+    convert::TryInto,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+/// The size of the blocks the challenge is verified in, and thus the granularity at which
+/// damaged ranges are fetched again.
+pub const BLOCK_SIZE: u64 = 16 * 1024;
+
+/// The number of times the damaged range of a challenge is fetched again before giving up.
+pub const MAX_REFETCHES: usize = 3;
+
+/// Verifies a file against its bao outboard tree while it is written, block by block.
+pub struct BlockVerifier {
+    path: String,
+    outboard_path: String,
+    hash: Hash,
+    decoder: Decoder<File, File>,
+    /// The number of bytes from the start of the file which were verified so far.
+    verified: u64,
+    buffer: Vec<u8>,
+}
+
+impl BlockVerifier {
+    /// Returns a verifier of the file at `path` against the outboard tree at `outboard_path`,
+    /// which must hash to the hex encoded BLAKE3 `root_hash`.
+    pub fn new(path: &str, outboard_path: &str, root_hash: &str) -> Result<Self> {
+        let bytes: [u8; 32] = hex::decode(root_hash)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("The BLAKE3 hash {} is not 32 bytes long", root_hash))?;
+        let hash = Hash::from(bytes);
+        let decoder = Decoder::new_outboard(File::open(path)?, File::open(outboard_path)?, &hash);
+        Ok(Self {
+            path: path.to_string(),
+            outboard_path: outboard_path.to_string(),
+            hash,
+            decoder,
+            verified: 0,
+            buffer: vec![0; BLOCK_SIZE as usize],
+        })
+    }
+
+    /// Returns the number of bytes from the start of the file which were verified so far.
+    pub fn verified(&self) -> u64 {
+        self.verified
+    }
+
+    ///
+    /// Verifies the blocks which were written completely, given that the first `written` bytes
+    /// of the file were written. Returns the offset of the first damaged block on a mismatch.
+    ///
+    pub fn verify(&mut self, written: u64) -> Result<(), u64> {
+        while self.verified + BLOCK_SIZE <= written {
+            if self.decoder.read_exact(&mut self.buffer).is_err() {
+                return Err(self.verified);
+            }
+            self.verified += BLOCK_SIZE;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Verifies the rest of the file once all of it was written, including its length.
+    /// Returns the offset of the first damaged block on a mismatch.
+    ///
+    pub fn finish(&mut self, written: u64) -> Result<(), u64> {
+        self.verify(written)?;
+        match io::copy(&mut self.decoder, &mut io::sink()) {
+            Ok(remaining) if self.verified + remaining == written => {
+                self.verified = written;
+                Ok(())
+            }
+            _ => Err(self.verified),
+        }
+    }
+
+    /// Restarts the verification at `offset`, which must be the start of a block, once the
+    /// file was written again from there on.
+    pub fn rewind(&mut self, offset: u64) -> Result<()> {
+        // The decoder can not be trusted to be in a consistent state after a mismatch.
+        self.decoder = Decoder::new_outboard(File::open(&self.path)?, File::open(&self.outboard_path)?, &self.hash);
+        self.decoder.seek(SeekFrom::Start(offset))?;
+        self.verified = offset;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use fs_err::OpenOptions;
+    use std::io::Write;
+
+    #[test]
+    fn test_block_verifier() {
+        let path = std::env::temp_dir().join(format!("verified-download-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let outboard_path = format!("{}.outboard", path);
+        let contents: Vec<u8> = (0..3 * BLOCK_SIZE + 100).map(|i| (i % 251) as u8).collect();
+        let (outboard, hash) = bao::encode::outboard(&contents);
+        fs_err::write(&outboard_path, outboard).unwrap();
+        let hash = hash.to_hex().to_string();
+
+        // The blocks are verified as soon as they are written completely
+        fs_err::write(path, &contents[..BLOCK_SIZE as usize + 10]).unwrap();
+        let mut verifier = BlockVerifier::new(path, &outboard_path, &hash).unwrap();
+        verifier.verify(BLOCK_SIZE + 10).unwrap();
+        assert_eq!(verifier.verified(), BLOCK_SIZE);
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&contents[BLOCK_SIZE as usize + 10..]).unwrap();
+        verifier.finish(contents.len() as u64).unwrap();
+
+        // A corrupted byte is reported at the start of its block
+        let mut corrupted = contents.clone();
+        corrupted[2 * BLOCK_SIZE as usize + 5] ^= 1;
+        fs_err::write(path, &corrupted).unwrap();
+        let mut verifier = BlockVerifier::new(path, &outboard_path, &hash).unwrap();
+        assert_eq!(verifier.verify(corrupted.len() as u64), Err(2 * BLOCK_SIZE));
+
+        // Only the damaged range has to be written again
+        fs_err::write(path, &contents).unwrap();
+        verifier.rewind(2 * BLOCK_SIZE).unwrap();
+        verifier.finish(contents.len() as u64).unwrap();
+
+        // A truncated file fails once it is finished
+        fs_err::write(path, &contents[..contents.len() - 1]).unwrap();
+        let mut verifier = BlockVerifier::new(path, &outboard_path, &hash).unwrap();
+        assert!(verifier.finish(contents.len() as u64 - 1).is_err());
+
+        fs_err::remove_file(path).unwrap();
+        fs_err::remove_file(outboard_path).unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+//! The authorization of the requests of participants to the coordinator
+//!
+//! Every request is authorized with the header `Aleo <address>:<signature>`,
+//! where the signature is over the lowercase method and path, by the view key
+//! of the participant.
+
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey, ViewKey};
+use snarkvm_utilities::ToBytes;
+
+use rand::{CryptoRng, Rng};
+use std::convert::TryFrom;
+
+/// Returns the value of the authorization header of a request with the given method and path
+pub fn get_authorization_value<R: Rng + CryptoRng>(
+    private_key: &PrivateKey<Testnet2Parameters>,
+    method: &str,
+    path: &str,
+    rng: &mut R,
+) -> anyhow::Result<String> {
+    let view_key = ViewKey::try_from(private_key)?;
+    let address = Address::try_from(private_key)?.to_string();
+
+    let message = format!("{} {}", method.to_lowercase(), path.to_lowercase());
+    let signature = hex::encode(&view_key.sign(message.as_bytes(), rng)?.to_bytes_le()?);
+
+    let authorization = format!("Aleo {}:{}", address, signature);
+    Ok(authorization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snarkvm_utilities::FromBytes;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_authorization_value() {
+        let rng = &mut ChaChaRng::from_seed([7; 32]);
+        let private_key = PrivateKey::<Testnet2Parameters>::new(rng);
+        let address = Address::try_from(&private_key).unwrap();
+
+        let value = get_authorization_value(&private_key, "POST", "/v1/contributor/try_lock", rng).unwrap();
+        let credentials = value.strip_prefix("Aleo ").unwrap();
+        let (keyid, signature) = credentials.split_once(':').unwrap();
+        assert_eq!(keyid, address.to_string());
+
+        // The coordinator checks the signature over the lowercase method and path
+        let signature = FromBytes::from_bytes_le(&hex::decode(signature).unwrap()).unwrap();
+        assert!(address
+            .verify_signature(b"post /v1/contributor/try_lock", &signature)
+            .unwrap());
+        assert!(!address
+            .verify_signature(b"post /v1/contributor/heartbeat", &signature)
+            .unwrap());
+    }
+}
@@ -0,0 +1,89 @@
+//! The skew between the clock of a participant and the clock of the
+//! coordinator, whose timestamps are the source of truth for leases and
+//! schedules
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The skew above which participants are warned to synchronize their clock
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// The offset of the clock of the coordinator from the local clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClockSkew {
+    /// The offset in milliseconds, positive if the coordinator clock is ahead
+    offset_ms: i64,
+}
+
+impl ClockSkew {
+    /// Measures the skew like NTP does, from the Unix time in milliseconds the
+    /// coordinator answered a request at, assuming it read its clock halfway
+    /// between the request being sent and the response being received
+    pub fn measure(sent: SystemTime, received: SystemTime, coordinator_time_ms: i64) -> Self {
+        let (sent, received) = (unix_ms(sent), unix_ms(received));
+        let midpoint = sent + (received - sent) / 2;
+        Self {
+            offset_ms: coordinator_time_ms - midpoint,
+        }
+    }
+
+    /// Returns the offset in milliseconds, positive if the coordinator clock is ahead
+    pub fn offset_ms(self) -> i64 {
+        self.offset_ms
+    }
+
+    /// Returns how far the local clock is off the coordinator clock, ahead or behind
+    pub fn magnitude(self) -> Duration {
+        Duration::from_millis(self.offset_ms.unsigned_abs())
+    }
+
+    /// Returns the Unix time in seconds on the coordinator clock at the given local time
+    pub fn to_coordinator_time(self, local: SystemTime) -> i64 {
+        (unix_ms(local) + self.offset_ms).div_euclid(1000)
+    }
+
+    /// Returns the number of seconds left at the given local time until the
+    /// coordinator clock shows the given Unix time in seconds, or zero if it passed
+    pub fn seconds_until(self, coordinator_time: i64, now: SystemTime) -> u64 {
+        (coordinator_time - self.to_coordinator_time(now)).max(0) as u64
+    }
+}
+
+fn unix_ms(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_millis() as i64,
+        Err(error) => -(error.duration().as_millis() as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_measure_skew() {
+        // The coordinator answered 2 s after the midpoint of a round trip of 400 ms
+        let skew = ClockSkew::measure(at(1_000_000), at(1_000_400), 1_002_200);
+        assert_eq!(skew.offset_ms(), 2_000);
+        assert_eq!(skew.magnitude(), Duration::from_secs(2));
+
+        // A clock ahead of the coordinator has a negative offset
+        let skew = ClockSkew::measure(at(1_000_000), at(1_000_000), 900_000);
+        assert_eq!(skew.offset_ms(), -100_000);
+        assert!(skew.magnitude() > MAX_CLOCK_SKEW);
+    }
+
+    #[test]
+    fn test_lease_uses_coordinator_time() {
+        // The local clock is an hour behind the coordinator
+        let skew = ClockSkew::measure(at(0), at(0), 3_600_000);
+        let now = at(1_000_000);
+        assert_eq!(skew.to_coordinator_time(now), 4_600);
+        assert_eq!(skew.seconds_until(4_900, now), 300);
+        assert_eq!(skew.seconds_until(4_000, now), 0);
+        assert_eq!(ClockSkew::default().seconds_until(1_300, now), 300);
+    }
+}
@@ -0,0 +1,56 @@
+//! Typed identifiers of chunks and contributions, so that the
+//! identifiers can not be mixed up where they are passed side by side
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+macro_rules! id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+id!(
+    /// The index of a chunk in a round, starting at 0
+    ChunkId
+);
+
+id!(
+    /// The index of a contribution to a chunk, where contribution 0 is
+    /// the initial challenge of the chunk
+    ContributionId
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_encode_as_numbers() {
+        assert_eq!(serde_json::to_string(&ChunkId(3)).unwrap(), "3");
+        assert_eq!(serde_json::from_str::<ContributionId>("7").unwrap(), ContributionId(7));
+        assert_eq!(u64::from(ChunkId::from(2)), 2);
+        assert_eq!(ChunkId(5).to_string(), "5");
+    }
+}
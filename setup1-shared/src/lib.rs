@@ -1,4 +1,9 @@
+#[cfg(feature = "authorization")]
+pub mod authorization;
 #[cfg(feature = "proof-of-work")]
 pub mod proof_of_work;
+pub mod clock;
+pub mod ids;
+pub mod protocol;
 pub mod reliability;
 pub mod structures;
@@ -0,0 +1,156 @@
+//! The versioned schema of the coordinator API
+//!
+//! Each major protocol version has its own module holding the request and
+//! response types the coordinator and its clients exchange. Minor versions
+//! only add to a schema, so any two peers agreeing on the major version can
+//! talk, using the lower of their minor versions.
+
+pub mod v1;
+
+use crate::ids::{ChunkId, ContributionId};
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// The header every request to the coordinator carries the negotiated protocol version in
+pub const PROTOCOL_VERSION_HEADER: &str = "x-aleo-setup-protocol-version";
+
+/// The header uploads carry their idempotency key in, since protocol version 1.1
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// The protocol version this build speaks
+pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion { major: 1, minor: 16 };
+
+/// The protocol version of a coordinator which predates version negotiation
+pub const LEGACY_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ProtocolVersion {
+    /// Returns the version both sides speak, if any
+    pub fn agree(&self, other: &ProtocolVersion) -> Option<ProtocolVersion> {
+        if self.major != other.major {
+            return None;
+        }
+        Some(ProtocolVersion {
+            major: self.major,
+            minor: self.minor.min(other.minor),
+        })
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for ProtocolVersion {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parts = input.splitn(2, '.');
+        match (parts.next(), parts.next()) {
+            (Some(major), Some(minor)) => Ok(ProtocolVersion {
+                major: major
+                    .parse()
+                    .map_err(|_| format!("Invalid protocol version {}", input))?,
+                minor: minor
+                    .parse()
+                    .map_err(|_| format!("Invalid protocol version {}", input))?,
+            }),
+            _ => Err(format!("Invalid protocol version {}", input)),
+        }
+    }
+}
+
+/// The coordinator and the client have no protocol version in common
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncompatibleProtocol {
+    pub client: ProtocolVersion,
+    pub coordinator: Vec<ProtocolVersion>,
+}
+
+impl fmt::Display for IncompatibleProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let coordinator = self
+            .coordinator
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let upgrade = if self.coordinator.iter().all(|version| version.major > self.client.major) {
+            "upgrade this client"
+        } else {
+            "the coordinator has to be upgraded first"
+        };
+        write!(
+            f,
+            "This client speaks protocol version {}, but the coordinator only speaks {}: {}",
+            self.client, coordinator, upgrade
+        )
+    }
+}
+
+impl std::error::Error for IncompatibleProtocol {}
+
+/// Picks the highest protocol version the client and the coordinator, which
+/// advertises `coordinator_versions` in its public settings, both speak
+pub fn negotiate(coordinator_versions: &[ProtocolVersion]) -> Result<ProtocolVersion, IncompatibleProtocol> {
+    let coordinator_versions = if coordinator_versions.is_empty() {
+        vec![LEGACY_PROTOCOL_VERSION]
+    } else {
+        coordinator_versions.to_vec()
+    };
+    coordinator_versions
+        .iter()
+        .filter_map(|version| PROTOCOL_VERSION.agree(version))
+        .max()
+        .ok_or(IncompatibleProtocol {
+            client: PROTOCOL_VERSION,
+            coordinator: coordinator_versions,
+        })
+}
+
+/// Returns the idempotency key of an upload for the given task, derived from the hash
+/// of the uploaded file, so every retry of the same upload carries the same key
+pub fn idempotency_key(chunk_id: ChunkId, contribution_id: ContributionId, file_hash: &[u8]) -> String {
+    let file_hash: String = file_hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}", chunk_id, contribution_id, file_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u16, minor: u16) -> ProtocolVersion {
+        ProtocolVersion { major, minor }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&[]), Ok(LEGACY_PROTOCOL_VERSION));
+        assert_eq!(negotiate(&[PROTOCOL_VERSION]), Ok(PROTOCOL_VERSION));
+
+        // A newer minor version on either side falls back to the older one
+        let newer = version(PROTOCOL_VERSION.major, PROTOCOL_VERSION.minor + 1);
+        assert_eq!(negotiate(&[newer]), Ok(PROTOCOL_VERSION));
+        assert_eq!(newer.agree(&PROTOCOL_VERSION), Some(PROTOCOL_VERSION));
+
+        let next_major = version(PROTOCOL_VERSION.major + 1, 0);
+        assert_eq!(negotiate(&[PROTOCOL_VERSION, next_major]), Ok(PROTOCOL_VERSION));
+        let error = negotiate(&[next_major]).unwrap_err();
+        assert!(error.to_string().contains("upgrade this client"));
+    }
+
+    #[test]
+    fn test_version_roundtrip() {
+        let version = version(1, 2);
+        assert_eq!(version.to_string().parse(), Ok(version));
+        assert!("1".parse::<ProtocolVersion>().is_err());
+    }
+}
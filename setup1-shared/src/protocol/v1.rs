@@ -0,0 +1,712 @@
+//! Version 1 of the coordinator API
+
+use crate::ids::{ChunkId, ContributionId};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[cfg(feature = "twitter")]
+pub use crate::structures::TwitterInfo;
+pub use crate::structures::{ArtifactReplica, ContributorStatus, LockResponse, PublicSettings, SetupKind};
+
+/// A verification task the coordinator assigned to a verifier
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AssignedTask {
+    pub round_id: u64,
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    /// How urgently the coordinator needs the verification, since protocol version 1.12
+    #[serde(default)]
+    pub priority: VerificationPriority,
+}
+
+/// How urgently the coordinator needs a verification, e.g. because it holds up the end of a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationPriority {
+    Normal,
+    Urgent,
+}
+
+impl Default for VerificationPriority {
+    fn default() -> Self {
+        VerificationPriority::Normal
+    }
+}
+
+/// `ContributedData` signed by the contributor
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedContributedData {
+    pub data: Value,
+    pub signature: String,
+}
+
+/// `VerifiedData` signed by the verifier
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedVerifiedData {
+    pub data: Value,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributedData {
+    pub challenge_hash: String,
+    pub response_hash: String,
+    pub contribution_duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedData {
+    pub challenge_hash: String,
+    pub response_hash: String,
+    pub new_challenge_hash: String,
+    pub verification_duration: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionUploadUrl {
+    pub chunk_id: String,
+    pub participant_id: String,
+    pub write_url: String,
+}
+
+/// The chunks a contributor asks to contribute to first, since protocol version 1.3
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PinChunksRequest {
+    pub chunk_ids: Vec<u64>,
+}
+
+/// The chunks the coordinator pinned, which may be fewer than requested, depending on its policy
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PinChunksResponse {
+    pub pinned_chunk_ids: Vec<u64>,
+}
+
+/// The category of an error the coordinator responds with, since protocol version 1.6
+///
+/// Each category tells the participant what to do about the error, see [ErrorCode::guidance].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The authorization of the request expired, or was signed with a skewed clock
+    AuthExpired,
+    /// The coordinator does not accept contributions at the moment, e.g. while aggregating a round
+    CeremonyPaused,
+    /// The chunk is no longer locked by the participant, e.g. after it was dropped
+    ChunkReassigned,
+    /// The upload exceeds the size the coordinator accepts
+    UploadTooLarge,
+    /// The declared size of the upload is not the size of the file for the chunk, since protocol version 1.10
+    UploadSizeMismatch,
+    /// The participant made too many uploads in a short time, since protocol version 1.10
+    TooManyRequests,
+    /// The participant may not contribute in the current phase of the ceremony, e.g. while it is
+    /// restricted to a trusted cohort, since protocol version 1.14
+    NotEligible,
+    /// An error without a more specific category, including categories added after this build
+    #[serde(other)]
+    Other,
+}
+
+impl ErrorCode {
+    /// Returns the category of an error from the HTTP status code alone, for
+    /// coordinators which predate error codes
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 => ErrorCode::AuthExpired,
+            409 => ErrorCode::ChunkReassigned,
+            413 => ErrorCode::UploadTooLarge,
+            429 => ErrorCode::TooManyRequests,
+            // Coordinators which predate error codes have no cohort, so they only
+            // respond with a 403 when they reject the authorization of the request.
+            403 => ErrorCode::AuthExpired,
+            503 => ErrorCode::CeremonyPaused,
+            _ => ErrorCode::Other,
+        }
+    }
+
+    /// Returns the HTTP status code the coordinator responds with for this category
+    pub fn status(&self) -> u16 {
+        match self {
+            ErrorCode::AuthExpired => 401,
+            ErrorCode::ChunkReassigned => 409,
+            ErrorCode::UploadTooLarge => 413,
+            ErrorCode::UploadSizeMismatch => 400,
+            ErrorCode::TooManyRequests => 429,
+            ErrorCode::NotEligible => 403,
+            ErrorCode::CeremonyPaused => 503,
+            ErrorCode::Other => 500,
+        }
+    }
+
+    /// Returns whether retrying the request can succeed
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, ErrorCode::UploadTooLarge | ErrorCode::UploadSizeMismatch)
+    }
+
+    /// Returns what the participant can do about the error
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            ErrorCode::AuthExpired => {
+                "The coordinator did not accept the authorization of the request. \
+                 Please check that the clock of this machine is set correctly."
+            }
+            ErrorCode::CeremonyPaused => {
+                "The ceremony is paused at the moment. Your contribution will resume once it continues."
+            }
+            ErrorCode::ChunkReassigned => {
+                "The chunk was reassigned to another contributor, most likely because this contributor \
+                 took too long or lost its connection. Moving on to the next chunk."
+            }
+            ErrorCode::UploadTooLarge => {
+                "The contribution is larger than the coordinator accepts, so it is likely corrupted. \
+                 Please restart the contributor to compute it again."
+            }
+            ErrorCode::UploadSizeMismatch => {
+                "The contribution does not have the size the coordinator expects for the chunk, so it is \
+                 likely corrupted. Please restart the contributor to compute it again."
+            }
+            ErrorCode::TooManyRequests => {
+                "The coordinator received too many uploads from this participant. \
+                 Retrying in a minute."
+            }
+            ErrorCode::NotEligible => {
+                "The ceremony is restricted to a trusted cohort at the moment. \
+                 You can join once it opens to the public."
+            }
+            ErrorCode::Other => "The coordinator failed to process the request. Retrying shortly.",
+        }
+    }
+}
+
+/// The body of an error response of the coordinator, since protocol version 1.6
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+/// The class of the hardware a contributor computes on, since protocol version 1.5
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum HardwareClass {
+    Small,
+    Medium,
+    Large,
+}
+
+impl HardwareClass {
+    /// Returns the hardware class of a machine with the given number of CPUs
+    pub fn from_cpus(cpus: usize) -> Self {
+        match cpus {
+            0..=4 => HardwareClass::Small,
+            5..=16 => HardwareClass::Medium,
+            _ => HardwareClass::Large,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HardwareClass::Small => "small",
+            HardwareClass::Medium => "medium",
+            HardwareClass::Large => "large",
+        }
+    }
+}
+
+/// The hardware a contributor reports before locking a chunk, which the
+/// coordinator estimates the compute time of its contributions from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareReport {
+    pub hardware_class: HardwareClass,
+}
+
+/// The machine a contributor runs on non-interactively, e.g. in CI, which it reports before
+/// locking a chunk since protocol version 1.16, so that auditors can tie its contributions
+/// to the run which made them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineAttestation {
+    /// The ID of the CI runner the contributor runs on, if known
+    pub runner_id: Option<String>,
+    /// The hex encoded hash of the environment, the contributor executable unless given
+    pub environment_hash: String,
+    /// Whether entropy from a file was mixed into the seed
+    pub external_entropy: bool,
+}
+
+/// A chunk a contributor gives up its lock on without contributing to it, since protocol version 1.5
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseChunkRequest {
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+}
+
+/// The hash of a challenge file in the public transcript digest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeDigest {
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    /// The hex encoded hash of the challenge file
+    pub hash: String,
+}
+
+/// The hashes of the challenge files of a round, published independently
+/// of the coordinator, so contributors can check the challenges they download
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptDigest {
+    pub round_height: u64,
+    pub challenges: Vec<ChallengeDigest>,
+}
+
+impl TranscriptDigest {
+    /// Returns the hex encoded hash of the challenge file for the given chunk and contribution, if listed
+    pub fn challenge_hash(&self, chunk_id: ChunkId, contribution_id: ContributionId) -> Option<&str> {
+        self.challenges
+            .iter()
+            .find(|challenge| challenge.chunk_id == chunk_id.0 && challenge.contribution_id == contribution_id.0)
+            .map(|challenge| challenge.hash.as_str())
+    }
+}
+
+/// The pointer to the latest state of the ceremony, which mirrors serve and clients
+/// poll, so that clients only reading the ceremony do not depend on the coordinator
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestIndex {
+    pub round_height: u64,
+    pub transcript_digest: TranscriptDigest,
+    /// The base URLs the artifacts of the round can be downloaded from
+    pub artifact_urls: Vec<String>,
+    /// The Unix timestamp the index was published at, in seconds
+    pub published_at: i64,
+}
+
+/// The latest index, signed by the coordinator over its canonical JSON encoding
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedLatestIndex {
+    pub index: LatestIndex,
+    /// The name of the signature scheme of the coordinator
+    pub signature_scheme: String,
+    pub signature: String,
+}
+
+/// The number of contributions in a page of the contribution history, unless the query sets a limit
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// The maximum number of contributions in a page of the contribution history
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+/// The state of a contribution in the contribution history
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContributionStatus {
+    /// The contribution was uploaded, but not verified yet
+    Contributed,
+    Verified,
+}
+
+/// A contribution in the contribution history of the ceremony
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionRecord {
+    pub round_height: u64,
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    pub contributor: String,
+    /// The Unix timestamp the contribution was uploaded at, in seconds
+    pub contributed_at: i64,
+    pub verifier: Option<String>,
+    /// The Unix timestamp the contribution was verified at, in seconds
+    pub verified_at: Option<i64>,
+    pub status: ContributionStatus,
+}
+
+/// The filters of a page of the contribution history and where the page starts,
+/// sent as the query parameters of the request
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionsQuery {
+    pub round_height: Option<u64>,
+    pub chunk_id: Option<u64>,
+    /// The ID of the contributor, with or without the `.contributor` suffix
+    pub contributor: Option<String>,
+    pub status: Option<ContributionStatus>,
+    /// Only list the contributions uploaded at or after this Unix timestamp, in seconds
+    pub since: Option<i64>,
+    /// Only list the contributions uploaded before this Unix timestamp, in seconds
+    pub until: Option<i64>,
+    /// The cursor returned with the previous page, to list the contributions after it
+    pub cursor: Option<String>,
+    /// The maximum number of contributions in the page, up to [MAX_PAGE_LIMIT]
+    pub limit: Option<usize>,
+}
+
+impl ContributionsQuery {
+    /// Returns the number of contributions the page holds at most
+    pub fn page_limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).max(1).min(MAX_PAGE_LIMIT)
+    }
+
+    /// Returns whether the contribution passes the filters of the query
+    pub fn matches(&self, record: &ContributionRecord) -> bool {
+        let contributor = self.contributor.as_deref().map_or(true, |contributor| {
+            record.contributor == contributor || record.contributor == format!("{}.contributor", contributor)
+        });
+        contributor
+            && self
+                .round_height
+                .map_or(true, |round_height| record.round_height == round_height)
+            && self.chunk_id.map_or(true, |chunk_id| record.chunk_id == chunk_id)
+            && self.status.map_or(true, |status| record.status == status)
+            && self.since.map_or(true, |since| record.contributed_at >= since)
+            && self.until.map_or(true, |until| record.contributed_at < until)
+    }
+
+    /// Returns the query parameters of the query, to append to the URL of the endpoint
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![];
+        if let Some(round_height) = self.round_height {
+            pairs.push(("roundHeight", round_height.to_string()));
+        }
+        if let Some(chunk_id) = self.chunk_id {
+            pairs.push(("chunkId", chunk_id.to_string()));
+        }
+        if let Some(contributor) = &self.contributor {
+            pairs.push(("contributor", contributor.clone()));
+        }
+        if let Some(status) = self.status {
+            let status = match status {
+                ContributionStatus::Contributed => "contributed",
+                ContributionStatus::Verified => "verified",
+            };
+            pairs.push(("status", status.to_string()));
+        }
+        if let Some(since) = self.since {
+            pairs.push(("since", since.to_string()));
+        }
+        if let Some(until) = self.until {
+            pairs.push(("until", until.to_string()));
+        }
+        if let Some(cursor) = &self.cursor {
+            pairs.push(("cursor", cursor.clone()));
+        }
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+        pairs
+    }
+}
+
+/// A page of the contribution history, ordered by the time the contributions were uploaded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionPage {
+    pub contributions: Vec<ContributionRecord>,
+    /// The cursor to request the next page with, if more contributions match the query.
+    /// Cursors stay valid as the history grows, so a client can resume listing later
+    pub next_cursor: Option<String>,
+}
+
+/// A time window a contributor asks to reserve to contribute in, since protocol version 1.7
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotReservationRequest {
+    /// The Unix timestamp the window starts at, in seconds
+    pub starts_at: i64,
+    /// The Unix timestamp the window ends at, in seconds
+    pub ends_at: i64,
+}
+
+/// A time window reserved by a contributor, in which the coordinator schedules it
+/// for a round ahead of the contributors without a reservation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotReservation {
+    pub id: u64,
+    pub contributor: String,
+    /// The Unix timestamp the window starts at, in seconds
+    pub starts_at: i64,
+    /// The Unix timestamp the window ends at, in seconds
+    pub ends_at: i64,
+    /// The reservation as an iCalendar (RFC 5545) event, to import into a calendar
+    pub ics: String,
+}
+
+/// A response file submitted for independent re-verification, since protocol version 1.8.
+/// It is sent as the query parameters of the request, with the response file as its body
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotCheckRequest {
+    pub round_height: u64,
+    pub chunk_id: u64,
+    /// The contribution ID of the response, which was computed on the verified contribution before it
+    pub contribution_id: u64,
+    /// The hex encoded hash of the challenge file the response was computed on
+    pub challenge_hash: String,
+}
+
+impl SpotCheckRequest {
+    /// Returns the query parameters of the request, to append to the URL of the endpoint
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("roundHeight", self.round_height.to_string()),
+            ("chunkId", self.chunk_id.to_string()),
+            ("contributionId", self.contribution_id.to_string()),
+            ("challengeHash", self.challenge_hash.clone()),
+        ]
+    }
+
+    /// Returns the request given by the query parameters of a URL, if they hold every field
+    pub fn from_query_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Option<Self> {
+        let (mut round_height, mut chunk_id, mut contribution_id, mut challenge_hash) = (None, None, None, None);
+        for (key, value) in pairs {
+            match key {
+                "roundHeight" => round_height = Some(value.parse().ok()?),
+                "chunkId" => chunk_id = Some(value.parse().ok()?),
+                "contributionId" => contribution_id = Some(value.parse().ok()?),
+                "challengeHash" => challenge_hash = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(Self {
+            round_height: round_height?,
+            chunk_id: chunk_id?,
+            contribution_id: contribution_id?,
+            challenge_hash: challenge_hash?,
+        })
+    }
+}
+
+/// The outcome of the re-verification of a response file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotCheckVerdict {
+    pub round_height: u64,
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    /// The hex encoded hash of the challenge file the response was verified against
+    pub challenge_hash: String,
+    /// The hex encoded hash of the submitted response file
+    pub response_hash: String,
+    pub valid: bool,
+    /// Why the response is invalid, if it is
+    pub reason: Option<String>,
+    /// The Unix timestamp the response was verified at, in seconds
+    pub verified_at: i64,
+}
+
+/// The verdict of a spot check, signed over its canonical JSON encoding
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedSpotCheckVerdict {
+    pub verdict: SpotCheckVerdict,
+    /// The address of the verifier which signed the verdict, or `None` if the coordinator signed it
+    pub verifier: Option<String>,
+    /// The name of the signature scheme of the signer
+    pub signature_scheme: String,
+    pub signature: String,
+}
+
+/// A response file a verifier rejected, reported to the coordinator so that it quarantines
+/// the response for analysis, since protocol version 1.9
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedContributionReport {
+    pub chunk_id: u64,
+    pub contribution_id: u64,
+    /// Why the verifier rejected the response
+    pub reason: String,
+    /// The log lines of the verifier about the verification of the response
+    pub logs: Vec<String>,
+}
+
+/// The phase of a ceremony which restricts its first rounds to a trusted cohort, since protocol version 1.14
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CeremonyPhase {
+    /// Only the contributors on the roster of the cohort may join the queue
+    TrustedCohort,
+    /// Every contributor may join the queue
+    Public,
+}
+
+/// Whether a contributor may join the queue in the current phase of the ceremony, since protocol version 1.14
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributorEligibility {
+    pub phase: CeremonyPhase,
+    pub eligible: bool,
+    /// The first round open to the public, if the ceremony is still restricted to the cohort
+    pub public_round: Option<u64>,
+    /// The Unix timestamp the ceremony opens to the public at the latest, if it is scheduled
+    pub public_at: Option<i64>,
+}
+
+/// A commitment to the seed of a contributor, made before it locks its first chunk of a
+/// round, since protocol version 1.13. It is computed over the public keys the seed yields,
+/// so it reveals nothing about the seed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedCommitment {
+    /// The hex encoded commitment, see `setup_utils::seed_commitment`
+    pub commitment: String,
+}
+
+/// The acknowledgement of a [SeedCommitment] by the coordinator, which shows that the seed
+/// was committed to before the contributor was handed any challenge of the round
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedCommitmentReceipt {
+    pub round_height: u64,
+    pub contributor: String,
+    /// The name of the commitment scheme, see `setup_utils::SEED_COMMITMENT_SCHEME`
+    pub scheme: String,
+    /// The hex encoded commitment
+    pub commitment: String,
+    /// The Unix timestamp the coordinator received the commitment at, in seconds
+    pub committed_at: i64,
+}
+
+/// A [SeedCommitmentReceipt] signed by the coordinator over its canonical JSON encoding
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedSeedCommitmentReceipt {
+    pub receipt: SeedCommitmentReceipt,
+    /// The name of the signature scheme of the coordinator
+    pub signature_scheme: String,
+    pub signature: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_roundtrip() {
+        let response = ErrorResponse {
+            code: ErrorCode::ChunkReassigned,
+            message: "ChunkNotLockedOrByWrongParticipant".to_string(),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!("chunk_reassigned", json["code"]);
+        assert_eq!(response, serde_json::from_value(json).unwrap());
+
+        for code in &[
+            ErrorCode::AuthExpired,
+            ErrorCode::CeremonyPaused,
+            ErrorCode::ChunkReassigned,
+            ErrorCode::UploadTooLarge,
+            ErrorCode::TooManyRequests,
+            ErrorCode::Other,
+        ] {
+            assert_eq!(*code, ErrorCode::from_status(code.status()));
+        }
+        // Any malformed request is a 400, so only the code in the body tells a size mismatch apart
+        assert_eq!(400, ErrorCode::UploadSizeMismatch.status());
+        assert_eq!(ErrorCode::Other, ErrorCode::from_status(400));
+        // A 403 without a code is a rejected authorization, only the code in the body tells ineligibility apart
+        assert_eq!(403, ErrorCode::NotEligible.status());
+        assert_eq!(ErrorCode::AuthExpired, ErrorCode::from_status(403));
+    }
+
+    #[test]
+    fn test_unknown_error_code() {
+        let response: ErrorResponse = serde_json::from_str(r#"{"code":"rate_limited","message":""}"#).unwrap();
+        assert_eq!(ErrorCode::Other, response.code);
+    }
+
+    #[test]
+    fn test_assigned_task_priority() {
+        // Coordinators before protocol version 1.12 send no priority
+        let task: AssignedTask = serde_json::from_str(r#"{"round_id":3,"chunk_id":7,"contribution_id":2}"#).unwrap();
+        assert_eq!(VerificationPriority::Normal, task.priority);
+
+        let task: AssignedTask =
+            serde_json::from_str(r#"{"round_id":3,"chunk_id":7,"contribution_id":2,"priority":"urgent"}"#).unwrap();
+        assert_eq!(VerificationPriority::Urgent, task.priority);
+        assert!(VerificationPriority::Urgent > VerificationPriority::Normal);
+    }
+
+    #[test]
+    fn test_contributions_query() {
+        let record = ContributionRecord {
+            round_height: 2,
+            chunk_id: 5,
+            contribution_id: 1,
+            contributor: "aleo1abc.contributor".to_string(),
+            contributed_at: 1_635_768_000,
+            verifier: None,
+            verified_at: None,
+            status: ContributionStatus::Contributed,
+        };
+        let query = ContributionsQuery {
+            round_height: Some(2),
+            contributor: Some("aleo1abc".to_string()),
+            since: Some(1_635_768_000),
+            ..Default::default()
+        };
+        assert!(query.matches(&record));
+        assert!(
+            !ContributionsQuery {
+                status: Some(ContributionStatus::Verified),
+                ..query.clone()
+            }
+            .matches(&record)
+        );
+        assert!(
+            !ContributionsQuery {
+                until: Some(1_635_768_000),
+                ..query.clone()
+            }
+            .matches(&record)
+        );
+
+        assert_eq!(query.query_pairs(), vec![
+            ("roundHeight", "2".to_string()),
+            ("contributor", "aleo1abc".to_string()),
+            ("since", "1635768000".to_string()),
+        ]);
+        assert_eq!(DEFAULT_PAGE_LIMIT, query.page_limit());
+        assert_eq!(
+            MAX_PAGE_LIMIT,
+            ContributionsQuery {
+                limit: Some(usize::MAX),
+                ..query
+            }
+            .page_limit()
+        );
+    }
+
+    #[test]
+    fn test_spot_check_request_query() {
+        let request = SpotCheckRequest {
+            round_height: 3,
+            chunk_id: 7,
+            contribution_id: 2,
+            challenge_hash: "ab01".to_string(),
+        };
+        let pairs = request.query_pairs();
+        let parsed = SpotCheckRequest::from_query_pairs(pairs.iter().map(|(key, value)| (*key, value.as_str())));
+        assert_eq!(Some(request), parsed);
+
+        assert_eq!(None, SpotCheckRequest::from_query_pairs(vec![("roundHeight", "3")]));
+        assert_eq!(
+            None,
+            SpotCheckRequest::from_query_pairs(pairs.iter().map(|(key, _)| (*key, "x")))
+        );
+    }
+}
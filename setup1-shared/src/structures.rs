@@ -1,5 +1,7 @@
 //! The data structures shared between coordinator, contributor and verifier
 
+use crate::protocol::ProtocolVersion;
+
 use serde::{Deserialize, Serialize};
 
 /// The kind of a setup
@@ -31,6 +33,19 @@ impl SetupKind {
 pub struct PublicSettings {
     pub setup: SetupKind,
     pub check_reliability: bool,
+    /// The protocol versions the coordinator speaks, empty for
+    /// a coordinator which predates version negotiation
+    #[serde(default)]
+    pub protocol_versions: Vec<ProtocolVersion>,
+    /// The replicas of the artifact store in other regions, since
+    /// protocol version 1.4
+    #[serde(default)]
+    pub artifact_replicas: Vec<ArtifactReplica>,
+    /// The Unix time in milliseconds the coordinator answered at, so
+    /// that clients can measure the skew of their clock, since protocol
+    /// version 1.11
+    #[serde(default)]
+    pub server_time_ms: Option<i64>,
 }
 
 impl PublicSettings {
@@ -45,6 +60,17 @@ impl PublicSettings {
     }
 }
 
+/// A replica of the coordinator artifact store in another region,
+/// serving the same download endpoints as the coordinator
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactReplica {
+    /// The name of the region, e.g. `ap-southeast-1`
+    pub region: String,
+    /// The base URL of the replica
+    pub url: String,
+}
+
 /// The combination of request token and PIN, used to tweet
 /// on a contributor's behalf by the coordinator.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -98,4 +124,30 @@ pub struct LockResponse {
 
     #[serde(alias = "responseContributionId")]
     pub response_contribution_id: u64,
+
+    /// The hex encoded hash of the challenge file, advertised since protocol version 1.2
+    #[serde(default, alias = "challengeHash")]
+    pub challenge_hash: Option<String>,
+
+    /// The hex encoded BLAKE3 root hash of the challenge file, advertised since protocol
+    /// version 1.15. The bao outboard tree of the challenge is served next to it, at
+    /// `/v1/download/challenge/{chunk_id}/{contribution_id}/outboard`, so the challenge
+    /// can be verified block by block while it is downloaded.
+    #[serde(default, alias = "challengeBaoHash")]
+    pub challenge_bao_hash: Option<String>,
+
+    /// The estimated number of seconds to compute the contribution on the hardware
+    /// class the contributor reported, advertised since protocol version 1.5
+    #[serde(default, alias = "estimatedSeconds")]
+    pub estimated_seconds: Option<u64>,
+
+    /// The number of seconds the contributor may hold the lock for, advertised
+    /// since protocol version 1.5
+    #[serde(default, alias = "leaseSeconds")]
+    pub lease_seconds: Option<u64>,
+
+    /// The Unix time in seconds on the clock of the coordinator at which the
+    /// lease expires, advertised since protocol version 1.11
+    #[serde(default, alias = "leaseExpiresAt")]
+    pub lease_expires_at: Option<i64>,
 }
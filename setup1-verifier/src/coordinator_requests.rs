@@ -1,6 +1,6 @@
 use crate::{errors::VerifierError, utils::AleoAuthentication, verifier::Verifier};
 
-use reqwest::Client;
+use setup1_shared::protocol::{v1::RejectedContributionReport, IDEMPOTENCY_KEY_HEADER};
 use tracing::{error, info};
 
 impl Verifier {
@@ -24,7 +24,8 @@ impl Verifier {
         info!("Verifier downloading a response file at {} ", path);
 
         let authentication = AleoAuthentication::authenticate(&self.view_key, &method, &path)?;
-        match Client::new()
+        match self
+            .http_client
             .get(coordinator_api_url.join(&path).expect("Should create a path"))
             .header("Authorization", authentication.to_string())
             .send()
@@ -70,7 +71,8 @@ impl Verifier {
         info!("Verifier downloading a challenge file at {} ", path);
 
         let authentication = AleoAuthentication::authenticate(&self.view_key, &method, &path)?;
-        match Client::new()
+        match self
+            .http_client
             .get(coordinator_api_url.join(&path).expect("Should create a path"))
             .header("Authorization", authentication.to_string())
             .send()
@@ -109,6 +111,7 @@ impl Verifier {
         chunk_id: u64,
         contribution_id: u64,
         signature_and_next_challenge_file_bytes: Vec<u8>,
+        idempotency_key: &str,
     ) -> Result<String, VerifierError> {
         let coordinator_api_url = &self.coordinator_api_url;
         let method = "post";
@@ -122,9 +125,11 @@ impl Verifier {
             path
         );
 
-        match Client::new()
+        match self
+            .http_client
             .post(coordinator_api_url.join(&path).expect("Should create a path"))
             .header(http::header::AUTHORIZATION, authentication.to_string())
+            .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
             .header(http::header::CONTENT_TYPE, "application/octet-stream")
             .header(
                 http::header::CONTENT_LENGTH,
@@ -153,4 +158,56 @@ impl Verifier {
             }
         }
     }
+
+    ///
+    /// Attempts to report a response file which failed verification to the
+    /// coordinator, which quarantines it for analysis
+    ///
+    /// On failure, this function returns a `VerifierError`.
+    ///
+    pub(crate) async fn report_rejected_contribution(
+        &self,
+        report: &RejectedContributionReport,
+    ) -> Result<(), VerifierError> {
+        let coordinator_api_url = &self.coordinator_api_url;
+        let method = "post";
+        let path = "/v1/verifier/reject";
+
+        let authentication = AleoAuthentication::authenticate(&self.view_key, &method, &path)?;
+        let body = serde_json::to_vec(report)?;
+
+        info!(
+            "Verifier reporting chunk {} contribution {} as rejected",
+            report.chunk_id, report.contribution_id
+        );
+
+        match self
+            .http_client
+            .post(coordinator_api_url.join(path).expect("Should create a path"))
+            .header(http::header::AUTHORIZATION, authentication.to_string())
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::CONTENT_LENGTH, body.len())
+            .body(body)
+            .send()
+            .await
+        {
+            Ok(response) => {
+                if !response.status().is_success() {
+                    error!("Failed to report a rejected contribution to {}", path);
+                    return Err(VerifierError::FailedRejectionReport(path.to_string()));
+                }
+
+                info!("Verifier reported a rejected contribution to {}", path);
+
+                Ok(())
+            }
+            Err(_) => {
+                error!("Request ({}) to report a rejected contribution failed.", path);
+                return Err(VerifierError::FailedRequest(
+                    path.to_string(),
+                    coordinator_api_url.to_string(),
+                ));
+            }
+        }
+    }
 }
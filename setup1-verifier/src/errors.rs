@@ -21,11 +21,23 @@ pub enum VerifierError {
     #[error("Failed to upload a new challenge file to {}", _0)]
     FailedChallengeUpload(String),
 
+    #[error("Failed to report a rejected contribution to {}", _0)]
+    FailedRejectionReport(String),
+
+    #[error("Invalid TLS configuration: {}", _0)]
+    InvalidTlsConfig(String),
+
+    #[error("Contribution {} of chunk {} failed verification", _1, _0)]
+    InvalidContribution(u64, u64),
+
     #[error("Mismatched response hashes")]
     MismatchedResponseHashes,
 
     #[error("Next challenge file missing stored response hash")]
     MissingStoredResponseHash,
+
+    #[error("Verification failed: {}", _0)]
+    VerificationFailed(String),
 }
 
 impl From<anyhow::Error> for VerifierError {
@@ -46,6 +58,12 @@ impl From<hex::FromHexError> for VerifierError {
     }
 }
 
+impl From<hyper::Error> for VerifierError {
+    fn from(error: hyper::Error) -> Self {
+        VerifierError::Crate("hyper", format!("{:?}", error))
+    }
+}
+
 impl From<reqwest::Error> for VerifierError {
     fn from(error: reqwest::Error) -> Self {
         VerifierError::Crate("reqwest", format!("{:?}", error))
@@ -1,7 +1,10 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{net::SocketAddr, path::PathBuf, str::FromStr};
 
 use phase1_coordinator::environment::{Development, Environment, Parameters, Production};
-use setup1_shared::structures::{PublicSettings, SetupKind};
+use setup1_shared::{
+    protocol,
+    structures::{PublicSettings, SetupKind},
+};
 use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, ViewKey};
 use structopt::StructOpt;
 use tracing::info;
@@ -9,10 +12,14 @@ use url::Url;
 
 mod coordinator_requests;
 mod errors;
+mod spot_check_service;
 mod utils;
 mod verifier;
 
-use crate::verifier::Verifier;
+use crate::{
+    utils::{http_client, TlsConfig},
+    verifier::Verifier,
+};
 
 fn development() -> Environment {
     Development::from(Parameters::TestCustom {
@@ -42,11 +49,65 @@ struct Options {
     view_key: PathBuf,
     #[structopt(long, help = "Coordinator api url, for example http://localhost:9000")]
     api_url: Url,
+
+    #[structopt(
+        long,
+        default_value = "1",
+        help = "The number of chunks to verify together, batching their pairing checks"
+    )]
+    pairing_batch_size: usize,
+
+    #[structopt(
+        long,
+        requires = "tls-key",
+        help = "Path to the PEM encoded certificate issued to this worker, enables mutual TLS with the coordinator"
+    )]
+    tls_cert: Option<PathBuf>,
+    #[structopt(
+        long,
+        requires = "tls-cert",
+        help = "Path to the PEM encoded private key of the worker certificate"
+    )]
+    tls_key: Option<PathBuf>,
+    #[structopt(
+        long,
+        requires = "tls-cert",
+        help = "Path to the PEM encoded certificate authority of the coordinator, if it is not publicly trusted"
+    )]
+    tls_ca: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        requires = "transcript",
+        help = "Address to serve spot checks on instead of verifying tasks of the coordinator, for example 0.0.0.0:9100"
+    )]
+    spot_check_address: Option<SocketAddr>,
+    #[structopt(
+        long,
+        requires = "spot-check-address",
+        help = "Path to a copy of the transcript, laid out as the coordinator storage, to spot check responses against"
+    )]
+    transcript: Option<PathBuf>,
+}
+
+impl Options {
+    fn tls_config(&self) -> Option<TlsConfig> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Some(TlsConfig {
+                cert: cert.clone(),
+                key: key.clone(),
+                ca: self.tls_ca.clone(),
+            }),
+            _ => None,
+        }
+    }
 }
 
-async fn request_coordinator_public_settings(coordinator_url: &Url) -> anyhow::Result<PublicSettings> {
+async fn request_coordinator_public_settings(
+    client: &reqwest::Client,
+    coordinator_url: &Url,
+) -> anyhow::Result<PublicSettings> {
     let settings_endpoint_url = coordinator_url.join("/v1/coordinator/settings")?;
-    let client = reqwest::Client::new();
     let bytes = client
         .post(settings_endpoint_url)
         .header(http::header::CONTENT_LENGTH, 0)
@@ -64,10 +125,18 @@ async fn main() {
 
     crate::utils::init_logger();
 
-    let public_settings = request_coordinator_public_settings(&options.api_url)
+    let tls_config = options.tls_config();
+    let settings_client = http_client(tls_config.as_ref(), None).expect("Failed to load the TLS configuration");
+
+    let public_settings = request_coordinator_public_settings(&settings_client, &options.api_url)
         .await
         .expect("Failed to fetch the coordinator public settings");
 
+    let protocol_version = protocol::negotiate(&public_settings.protocol_versions).unwrap_or_else(|e| panic!("{}", e));
+    info!("Speaking protocol version {} with the coordinator", protocol_version);
+    let http_client =
+        http_client(tls_config.as_ref(), Some(protocol_version)).expect("Failed to load the TLS configuration");
+
     let environment = match public_settings.setup {
         SetupKind::Development => development(),
         SetupKind::Inner => inner(),
@@ -81,8 +150,20 @@ async fn main() {
 
     // Initialize the verifier
     info!("Initializing verifier...");
-    let verifier =
-        Verifier::new(options.api_url.clone(), view_key, address, environment).expect("Failed to initialize verifier");
-
-    verifier.start_verifier().await;
+    let verifier = Verifier::new(
+        options.api_url.clone(),
+        view_key,
+        address,
+        environment,
+        options.pairing_batch_size,
+        http_client,
+    )
+    .expect("Failed to initialize verifier");
+
+    match (options.spot_check_address, options.transcript) {
+        (Some(address), Some(transcript)) => spot_check_service::serve(verifier, address, transcript)
+            .await
+            .expect("Failed to serve spot checks"),
+        _ => verifier.start_verifier().await,
+    }
 }
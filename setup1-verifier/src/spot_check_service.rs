@@ -0,0 +1,172 @@
+//! The spot check service, which verifies the response files anyone submits
+//! against the challenges of a local copy of the transcript, and responds with
+//! the verdict signed by the verifier, so that the community can check the
+//! contributions of the ceremony independently of the coordinator.
+
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use hyper::{
+    body::HttpBody,
+    service::{make_service_fn, service_fn},
+    Body,
+    Method,
+    Request,
+    Response,
+    Server,
+    StatusCode,
+};
+use phase1_coordinator::{storage::Object, CoordinatorError};
+use setup1_shared::protocol::v1::SpotCheckRequest;
+use tokio::sync::Semaphore;
+use tracing::{error, info};
+
+use crate::{errors::VerifierError, verifier::Verifier};
+
+/// The path of the endpoint response files are submitted to.
+pub const SPOT_CHECK_PATH: &str = "/v1/spot_check";
+
+/// The state shared by the requests to the service.
+struct SpotCheckService {
+    verifier: Verifier,
+    /// The directory of the transcript, laid out as the storage of the coordinator.
+    transcript: PathBuf,
+    /// Verifications are run one at a time. The response file is read before
+    /// a verification waits for its turn, so that a slow upload does not hold
+    /// up the verifications of the response files already read.
+    permits: Semaphore,
+}
+
+///
+/// Serves spot checks on the given address until the server fails, verifying the
+/// response files against the challenges in the given transcript directory.
+///
+pub async fn serve(verifier: Verifier, address: SocketAddr, transcript: PathBuf) -> Result<(), VerifierError> {
+    let service = Arc::new(SpotCheckService {
+        verifier,
+        transcript,
+        permits: Semaphore::new(1),
+    });
+    let make_service = make_service_fn(move |_connection| {
+        let service = service.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |request| handle(service.clone(), request))) }
+    });
+
+    info!("Serving spot checks on {}", address);
+    Server::try_bind(&address)?.serve(make_service).await?;
+    Ok(())
+}
+
+/// Returns the path of the challenge file a response was computed on in the transcript.
+fn challenge_path(transcript: &Path, request: &SpotCheckRequest) -> Option<PathBuf> {
+    let challenge_id = request.contribution_id.checked_sub(1)?;
+    Some(
+        transcript
+            .join(format!("round_{}", request.round_height))
+            .join(format!("chunk_{}", request.chunk_id))
+            .join(format!("contribution_{}.verified", challenge_id)),
+    )
+}
+
+fn respond(status: StatusCode, body: impl Into<Body>) -> Response<Body> {
+    let mut response = Response::new(body.into());
+    *response.status_mut() = status;
+    response
+}
+
+async fn handle(service: Arc<SpotCheckService>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::POST || request.uri().path() != SPOT_CHECK_PATH {
+        return Ok(respond(StatusCode::NOT_FOUND, "Not found"));
+    }
+
+    let pairs: Vec<(Cow<str>, Cow<str>)> =
+        url::form_urlencoded::parse(request.uri().query().unwrap_or_default().as_bytes()).collect();
+    let spot_check = match SpotCheckRequest::from_query_pairs(pairs.iter().map(|(key, value)| (&**key, &**value))) {
+        Some(spot_check) if spot_check.chunk_id < service.verifier.environment.number_of_chunks() => spot_check,
+        _ => return Ok(respond(StatusCode::BAD_REQUEST, "Invalid spot check request")),
+    };
+    let challenge_path = match challenge_path(&service.transcript, &spot_check) {
+        Some(path) if path.exists() => path,
+        _ => return Ok(respond(StatusCode::NOT_FOUND, "The challenge is not in the transcript")),
+    };
+
+    // Refuse response files which are too large before reading them.
+    let expected_size = Object::contribution_file_size(&service.verifier.environment, spot_check.chunk_id, false);
+    if request.body().size_hint().lower() > expected_size {
+        return Ok(respond(StatusCode::PAYLOAD_TOO_LARGE, "The response file is too large"));
+    }
+
+    let mut body = request.into_body();
+    let mut response = Vec::new();
+    while let Some(data) = body.data().await {
+        match data {
+            Ok(data) if response.len() + data.len() <= expected_size as usize => response.extend_from_slice(&data),
+            Ok(_) => return Ok(respond(StatusCode::PAYLOAD_TOO_LARGE, "The response file is too large")),
+            Err(error) => {
+                error!("Error reading a spot check request: {}", error);
+                return Ok(respond(StatusCode::BAD_REQUEST, "Failed to read the response file"));
+            }
+        }
+    }
+
+    let _permit = service.permits.acquire().await.expect("The semaphore is never closed");
+    let verifier_service = service.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let challenge = fs_err::read(&challenge_path)?;
+        verifier_service.verifier.spot_check(&spot_check, &challenge, &response)
+    })
+    .await;
+
+    Ok(match result {
+        Ok(Ok(verdict)) => match serde_json::to_vec(&verdict) {
+            Ok(verdict) => Response::builder()
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(verdict))
+                .expect("The response is well formed"),
+            Err(error) => respond(StatusCode::INTERNAL_SERVER_ERROR, error.to_string()),
+        },
+        Ok(Err(VerifierError::CoordinatorError(error @ CoordinatorError::ContributionFileSizeMismatch)))
+        | Ok(Err(VerifierError::CoordinatorError(error @ CoordinatorError::VerificationOnContributionIdZero))) => {
+            respond(StatusCode::BAD_REQUEST, error.to_string())
+        }
+        Ok(Err(error)) => {
+            error!("Error running a spot check: {}", error);
+            respond(StatusCode::INTERNAL_SERVER_ERROR, "Failed to run the spot check")
+        }
+        Err(error) => {
+            error!("Spot check panicked: {}", error);
+            respond(StatusCode::INTERNAL_SERVER_ERROR, "Failed to run the spot check")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_path() {
+        let request = SpotCheckRequest {
+            round_height: 3,
+            chunk_id: 7,
+            contribution_id: 2,
+            challenge_hash: String::new(),
+        };
+        assert_eq!(
+            Some(PathBuf::from("transcript/round_3/chunk_7/contribution_1.verified")),
+            challenge_path(Path::new("transcript"), &request)
+        );
+        assert_eq!(
+            None,
+            challenge_path(Path::new("transcript"), &SpotCheckRequest {
+                contribution_id: 0,
+                ..request
+            })
+        );
+    }
+}
@@ -4,6 +4,9 @@ pub use authentication::*;
 pub mod logger;
 pub use logger::*;
 
+pub mod tls;
+pub use tls::*;
+
 use std::{fs, path::Path};
 use tracing::{error, trace};
 
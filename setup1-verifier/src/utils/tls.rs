@@ -0,0 +1,60 @@
+use crate::errors::VerifierError;
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue},
+    Certificate,
+    Client,
+    Identity,
+};
+use setup1_shared::protocol::{ProtocolVersion, PROTOCOL_VERSION_HEADER};
+use std::{fs, path::PathBuf};
+
+///
+/// The certificate a verifier worker presents to the coordinator over mutual TLS.
+///
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// The PEM encoded certificate issued to this worker
+    pub cert: PathBuf,
+
+    /// The PEM encoded private key of the worker certificate
+    pub key: PathBuf,
+
+    /// The PEM encoded certificate authority of the coordinator, if it is not publicly trusted
+    pub ca: Option<PathBuf>,
+}
+
+///
+/// Returns the client used for every request to the coordinator.
+///
+/// With a `TlsConfig`, the client presents the worker certificate and
+/// refuses to talk to the coordinator over plain HTTP. With a `protocol_version`,
+/// every request carries the negotiated protocol version.
+///
+pub fn http_client(
+    tls: Option<&TlsConfig>,
+    protocol_version: Option<ProtocolVersion>,
+) -> Result<Client, VerifierError> {
+    let mut builder = Client::builder();
+    if let Some(version) = protocol_version {
+        let mut headers = HeaderMap::new();
+        let value = HeaderValue::from_str(&version.to_string()).expect("The protocol version should be a valid header");
+        headers.insert(PROTOCOL_VERSION_HEADER, value);
+        builder = builder.default_headers(headers);
+    }
+    if let Some(tls) = tls {
+        // The identity is parsed from a single PEM bundle holding both the key and the certificate
+        let mut pem = fs::read(&tls.key)?;
+        pem.push(b'\n');
+        pem.extend(fs::read(&tls.cert)?);
+        let identity = Identity::from_pem(&pem).map_err(|e| VerifierError::InvalidTlsConfig(e.to_string()))?;
+
+        builder = builder.use_rustls_tls().identity(identity).https_only(true);
+        if let Some(ca) = &tls.ca {
+            let ca =
+                Certificate::from_pem(&fs::read(ca)?).map_err(|e| VerifierError::InvalidTlsConfig(e.to_string()))?;
+            builder = builder.add_root_certificate(ca);
+        }
+    }
+    Ok(builder.build()?)
+}
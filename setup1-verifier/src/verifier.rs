@@ -1,22 +1,36 @@
 use std::{
     fs,
+    panic,
     str::FromStr,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use phase1::helpers::CurveKind;
-use phase1_cli::transform_pok_and_correctness;
+use phase1::{helpers::CurveKind, Phase1Parameters};
+use phase1_cli::{batch_transform_pok_and_correctness, transform_pok_and_correctness, BatchedContribution};
 use phase1_coordinator::{
     environment::Environment,
     objects::{ContributionFileSignature, ContributionState},
     phase1_chunked_parameters,
+    spot_check::spot_check,
     Participant,
 };
-use setup_utils::calculate_hash;
-use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
+use setup1_shared::{
+    ids::{ChunkId, ContributionId},
+    protocol::{
+        self,
+        v1::{
+            AssignedTask,
+            RejectedContributionReport,
+            SignedSpotCheckVerdict,
+            SpotCheckRequest,
+            VerificationPriority,
+        },
+    },
+};
+use setup_utils::{calculate_hash, to_canonical_json};
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
 use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, ViewKey};
 
-use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info};
 use url::Url;
 
@@ -28,6 +42,11 @@ use crate::{
 const NO_TASKS_DELAY: Duration = Duration::from_secs(5);
 const UPLOAD_TASK_ERROR_DELAY: Duration = Duration::from_secs(5);
 
+/// Returns `true` if the given tasks verify the same contribution, whatever their priority.
+fn is_same_task(a: &AssignedTask, b: &AssignedTask) -> bool {
+    (a.round_id, a.chunk_id, a.contribution_id) == (b.round_id, b.chunk_id, b.contribution_id)
+}
+
 /// Returns a pretty print of the given hash bytes for logging.
 fn pretty_hash(input: &[u8]) -> String {
     let mut output = format!("\n\n");
@@ -44,11 +63,38 @@ fn pretty_hash(input: &[u8]) -> String {
     output
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct AssignedTask {
-    pub round_id: u64,
-    pub chunk_id: u64,
-    pub contribution_id: u64,
+/// The local file locators of a task which is verified in a batch.
+#[derive(Debug, Clone)]
+pub struct TaskLocators {
+    pub challenge: String,
+    pub response: String,
+    pub next_challenge: String,
+}
+
+impl TaskLocators {
+    fn new(index: usize) -> Self {
+        Self {
+            challenge: format!("challenge_{}", index),
+            response: format!("response_{}", index),
+            next_challenge: format!("next_challenge_{}", index),
+        }
+    }
+}
+
+fn batched_contributions<'a, E: PairingEngine>(
+    parameters: &'a [Phase1Parameters<E>],
+    locators: &'a [TaskLocators],
+) -> Vec<BatchedContribution<'a, E>> {
+    parameters
+        .iter()
+        .zip(locators)
+        .map(|(parameters, locators)| BatchedContribution {
+            challenge_filename: &locators.challenge,
+            response_filename: &locators.response,
+            new_challenge_filename: &locators.next_challenge,
+            parameters,
+        })
+        .collect()
 }
 
 ///
@@ -68,6 +114,12 @@ pub struct Verifier {
 
     /// The coordinator environment
     pub(crate) environment: Environment,
+
+    /// The maximum number of tasks whose pairing checks are batched together
+    pub(crate) pairing_batch_size: usize,
+
+    /// The client used for every request to the coordinator
+    pub(crate) http_client: reqwest::Client,
 }
 
 // Manual implementation, since ViewKey doesn't implement Clone
@@ -79,6 +131,8 @@ impl Clone for Verifier {
             view_key,
             verifier: self.verifier.clone(),
             environment: self.environment.clone(),
+            pairing_batch_size: self.pairing_batch_size,
+            http_client: self.http_client.clone(),
         }
     }
 }
@@ -92,6 +146,8 @@ impl Verifier {
         view_key: ViewKey<Testnet2Parameters>,
         address: Address<Testnet2Parameters>,
         environment: Environment,
+        pairing_batch_size: usize,
+        http_client: reqwest::Client,
     ) -> Result<Self, VerifierError> {
         let verifier_id = address.to_string();
 
@@ -100,6 +156,8 @@ impl Verifier {
             view_key,
             verifier: Participant::Verifier(verifier_id),
             environment,
+            pairing_batch_size: pairing_batch_size.max(1),
+            http_client,
         })
     }
 
@@ -185,7 +243,8 @@ impl Verifier {
 
     ///
     /// Performs verification on a contribution with the given chunk id and file locators.
-    /// Returns the time (in milliseconds) it took for verification to execute.
+    /// Returns the time (in milliseconds) it took for verification to execute, or
+    /// `VerifierError::VerificationFailed` with the reason the contribution is invalid.
     ///
     pub fn run_verification(
         &self,
@@ -193,7 +252,7 @@ impl Verifier {
         challenge_file_locator: &str,
         response_locator: &str,
         next_challenge_locator: &str,
-    ) -> u128 {
+    ) -> Result<u128, VerifierError> {
         // Create the parent directory for the `next_challenge_locator` if it doesn't already exist.
         create_parent_directory(&next_challenge_locator);
         // Remove the `next_challenge_locator` if it already exists.
@@ -204,8 +263,9 @@ impl Verifier {
         let compressed_challenge = self.environment.compressed_inputs();
         let compressed_response = self.environment.compressed_outputs();
 
+        // Verification panics on an invalid contribution, with the reason it is invalid.
         let start = Instant::now();
-        match settings.curve() {
+        panic::catch_unwind(panic::AssertUnwindSafe(|| match settings.curve() {
             CurveKind::Bls12_377 => transform_pok_and_correctness(
                 compressed_challenge,
                 &challenge_file_locator,
@@ -224,9 +284,72 @@ impl Verifier {
                 &next_challenge_locator,
                 &phase1_chunked_parameters!(BW6_761, settings, chunk_id),
             ),
+        }))
+        .map_err(|payload| {
+            let reason = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+                (Some(reason), _) => reason.to_string(),
+                (_, Some(reason)) => reason.clone(),
+                _ => "verification panicked".to_string(),
+            };
+            VerifierError::VerificationFailed(reason)
+        })?;
+
+        Ok(start.elapsed().as_millis())
+    }
+
+    ///
+    /// Performs verification on the contributions of the given tasks, batching their pairing checks.
+    /// Returns whether each contribution is valid, and the time (in milliseconds) it took for
+    /// verification to execute.
+    ///
+    pub fn run_batch_verification(&self, tasks: &[AssignedTask], locators: &[TaskLocators]) -> (Vec<bool>, u128) {
+        for locators in locators {
+            // Create the parent directory for the `next_challenge_locator` if it doesn't already exist.
+            create_parent_directory(&locators.next_challenge);
+            // Remove the `next_challenge_locator` if it already exists.
+            remove_file_if_exists(&locators.next_challenge);
+        }
+
+        let settings = self.environment.parameters();
+
+        let compressed_challenge = self.environment.compressed_inputs();
+        let compressed_response = self.environment.compressed_outputs();
+
+        let start = Instant::now();
+        let valid = match settings.curve() {
+            CurveKind::Bls12_377 => {
+                let parameters: Vec<_> = tasks
+                    .iter()
+                    .map(|task| {
+                        let chunk_id = task.chunk_id;
+                        phase1_chunked_parameters!(Bls12_377, settings, chunk_id)
+                    })
+                    .collect();
+                batch_transform_pok_and_correctness(
+                    compressed_challenge,
+                    compressed_response,
+                    compressed_challenge,
+                    &batched_contributions(&parameters, locators),
+                )
+            }
+            CurveKind::BW6 => {
+                let parameters: Vec<_> = tasks
+                    .iter()
+                    .map(|task| {
+                        let chunk_id = task.chunk_id;
+                        phase1_chunked_parameters!(BW6_761, settings, chunk_id)
+                    })
+                    .collect();
+                batch_transform_pok_and_correctness(
+                    compressed_challenge,
+                    compressed_response,
+                    compressed_challenge,
+                    &batched_contributions(&parameters, locators),
+                )
+            }
         };
 
-        start.elapsed().as_millis()
+        (valid, start.elapsed().as_millis())
     }
 
     ///
@@ -314,6 +437,43 @@ impl Verifier {
         Ok(signature_and_next_challenge_bytes)
     }
 
+    ///
+    /// Verifies the response file of a spot check against the given challenge file,
+    /// and returns the verdict signed with the view key of the verifier.
+    ///
+    pub fn spot_check(
+        &self,
+        request: &SpotCheckRequest,
+        challenge: &[u8],
+        response: &[u8],
+    ) -> Result<SignedSpotCheckVerdict, VerifierError> {
+        info!(
+            "Spot checking round {} chunk {} contribution {}",
+            request.round_height, request.chunk_id, request.contribution_id
+        );
+
+        let verified_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        let verdict = spot_check(&self.environment, request, challenge, response, verified_at)?;
+        let signature = AleoAuthentication::sign(&self.view_key, to_canonical_json(&verdict)?)?;
+
+        info!(
+            "Spot check of round {} chunk {} contribution {} is {}",
+            request.round_height,
+            request.chunk_id,
+            request.contribution_id,
+            if verdict.valid { "valid" } else { "invalid" }
+        );
+
+        Ok(SignedSpotCheckVerdict {
+            verdict,
+            verifier: Some(self.verifier.address()),
+            signature_scheme: "Aleo".to_string(),
+            signature,
+        })
+    }
+
     ///
     /// Start the verifier loop. Polls the coordinator to lock and verify chunks.
     ///
@@ -332,11 +492,38 @@ impl Verifier {
 
             info!("Got a task: {:?}", task);
 
-            // Run the verification operations.
-            if let Err(error) = self.try_verify(&task).await {
-                error!("Error while verifying {}", error);
-                tokio::time::sleep(UPLOAD_TASK_ERROR_DELAY).await;
+            // Hold more tasks, if available, to batch their pairing checks. An urgent task is
+            // verified on its own as soon as it is handed out, ahead of the held tasks, rather
+            // than waiting for their files to be downloaded and checked along with it.
+            let mut tasks = vec![task];
+            while tasks.len() < self.pairing_batch_size && tasks[0].priority != VerificationPriority::Urgent {
+                match self.get_task().await {
+                    Some(task) if task.priority == VerificationPriority::Urgent => {
+                        info!("Got an urgent task, verifying it before the held tasks: {:?}", task);
+                        self.verify_tasks(&[task]).await;
+                    }
+                    Some(task) if !tasks.iter().any(|held| is_same_task(held, &task)) => {
+                        info!("Got a task: {:?}", task);
+                        tasks.push(task);
+                    }
+                    _ => break,
+                }
             }
+
+            self.verify_tasks(&tasks).await;
+        }
+    }
+
+    /// Runs the verification operations on the given tasks, batching their pairing checks
+    /// if there are several, and waits before going on if they failed.
+    async fn verify_tasks(&self, tasks: &[AssignedTask]) {
+        let result = match tasks {
+            [task] => self.try_verify(task).await,
+            tasks => self.try_verify_batch(tasks).await,
+        };
+        if let Err(error) = result {
+            error!("Error while verifying {}", error);
+            tokio::time::sleep(UPLOAD_TASK_ERROR_DELAY).await;
         }
     }
 
@@ -377,18 +564,144 @@ impl Verifier {
             chunk_id, contribution_id
         );
 
-        let duration = self.run_verification(chunk_id, &challenge_locator, &response_locator, &next_challenge_locator);
+        let duration =
+            match self.run_verification(chunk_id, &challenge_locator, &response_locator, &next_challenge_locator) {
+                Ok(duration) => duration,
+                Err(error) => {
+                    error!(
+                        "Verification on chunk {} contribution {} failed: {}",
+                        chunk_id, contribution_id, error
+                    );
+                    self.reject(task, &challenge_hash, &response_hash, error.to_string(), vec![])
+                        .await;
+                    return Err(VerifierError::InvalidContribution(chunk_id, contribution_id));
+                }
+            };
         info!(
             "Verification on chunk {} contribution {} completed in {} ms",
             chunk_id, contribution_id, duration,
         );
 
+        self.upload_verification(task, challenge_hash, response_hash, &next_challenge_locator)
+            .await
+    }
+
+    ///
+    /// Runs a set of operations to perform verification on several chunks at once,
+    /// batching the pairing checks of all of them.
+    ///
+    /// This is the same as `try_verify` for each task, except that all files are downloaded
+    /// and verified before any verification is uploaded. Invalid contributions are skipped,
+    /// and reported in the returned error once the valid ones have been uploaded.
+    ///
+    pub async fn try_verify_batch(&self, tasks: &[AssignedTask]) -> Result<(), VerifierError> {
+        let locators: Vec<TaskLocators> = (0..tasks.len()).map(TaskLocators::new).collect();
+
+        // Download and process the challenge and response files.
+        let mut hashes = Vec::with_capacity(tasks.len());
+        for (task, locators) in tasks.iter().zip(&locators) {
+            let challenge_hash = self
+                .process_challenge_file(task.chunk_id, task.contribution_id, &locators.challenge)
+                .await?;
+            let response_hash = self
+                .process_response_file(task.chunk_id, task.contribution_id, &locators.response)
+                .await?;
+            hashes.push((challenge_hash, response_hash));
+        }
+
+        // Run verification on all chunks with the given locators.
+        info!("Running batched verification on {} chunks", tasks.len());
+
+        let (valid, duration) = self.run_batch_verification(tasks, &locators);
+        info!(
+            "Batched verification on {} chunks completed in {} ms",
+            tasks.len(),
+            duration
+        );
+
+        let mut result = Ok(());
+        for (((task, locators), (challenge_hash, response_hash)), valid) in
+            tasks.iter().zip(&locators).zip(hashes).zip(valid)
+        {
+            if !valid {
+                error!(
+                    "Verification on chunk {} contribution {} failed",
+                    task.chunk_id, task.contribution_id
+                );
+
+                let logs = vec![format!(
+                    "Batched verification on {} chunks completed in {} ms",
+                    tasks.len(),
+                    duration
+                )];
+                self.reject(
+                    task,
+                    &challenge_hash,
+                    &response_hash,
+                    "the response failed the batched verification".to_string(),
+                    logs,
+                )
+                .await;
+                result = Err(VerifierError::InvalidContribution(task.chunk_id, task.contribution_id));
+                continue;
+            }
+
+            self.upload_verification(task, challenge_hash, response_hash, &locators.next_challenge)
+                .await?;
+        }
+
+        result
+    }
+
+    ///
+    /// Reports the response file of a task which failed verification to the coordinator,
+    /// so that it quarantines the response for analysis. The hashes of the challenge and
+    /// response files are added to the given log lines.
+    ///
+    async fn reject(
+        &self,
+        task: &AssignedTask,
+        challenge_hash: &[u8],
+        response_hash: &[u8],
+        reason: String,
+        mut logs: Vec<String>,
+    ) {
+        logs.insert(0, format!("Challenge hash {}", hex::encode(challenge_hash)));
+        logs.insert(1, format!("Response hash {}", hex::encode(response_hash)));
+        let report = RejectedContributionReport {
+            chunk_id: task.chunk_id,
+            contribution_id: task.contribution_id,
+            reason,
+            logs,
+        };
+        if let Err(error) = self.report_rejected_contribution(&report).await {
+            error!("Error reporting a rejected contribution {}", error);
+        }
+    }
+
+    ///
+    /// Signs and uploads the verification of a task, once the next challenge file was generated.
+    ///
+    async fn upload_verification(
+        &self,
+        task: &AssignedTask,
+        challenge_hash: Vec<u8>,
+        response_hash: Vec<u8>,
+        next_challenge_locator: &str,
+    ) -> Result<(), VerifierError> {
         // Fetch the next challenge file from the filesystem.
-        let (next_challenge_file, next_challenge_hash) = self.read_next_challenge_file(&next_challenge_locator).await?;
+        let (next_challenge_file, next_challenge_hash) = self.read_next_challenge_file(next_challenge_locator).await?;
 
         // Verify that the next challenge file stores the correct response hash.
         self.verify_response_hash(&next_challenge_file, &response_hash)?;
 
+        // Uploads of the same next challenge file carry the same key, even when signed anew.
+        let idempotency_key = protocol::idempotency_key(
+            ChunkId(task.chunk_id),
+            ContributionId(task.contribution_id),
+            &next_challenge_hash,
+        );
+
         // Construct a signature and serialize the contribution.
         let signature_and_next_challenge_bytes = self.serialize_contribution_and_signature(
             challenge_hash,
@@ -398,8 +711,13 @@ impl Verifier {
         )?;
 
         // Upload the signature and new challenge file
-        self.upload_next_challenge_locator_file(chunk_id, contribution_id, signature_and_next_challenge_bytes)
-            .await?;
+        self.upload_next_challenge_locator_file(
+            task.chunk_id,
+            task.contribution_id,
+            signature_and_next_challenge_bytes,
+            &idempotency_key,
+        )
+        .await?;
 
         Ok(())
     }
@@ -423,7 +741,8 @@ impl Verifier {
 
         info!("Asking for a new task");
 
-        match reqwest::Client::new()
+        match self
+            .http_client
             .post(coordinator_api_url.join(path).expect("Should create a path"))
             .header(http::header::AUTHORIZATION, authentication.to_string())
             .header(http::header::CONTENT_LENGTH, 0)
@@ -486,6 +805,8 @@ mod tests {
             view_key,
             address,
             environment.into(),
+            1,
+            reqwest::Client::new(),
         )
         .unwrap()
     }
@@ -0,0 +1,8 @@
+// Record how the binary was built, so verdict reports can tell independent builds apart
+use rustc_version::version_meta;
+
+fn main() {
+    let version = version_meta().unwrap();
+    println!("cargo:rustc-env=SETUP2_RUSTC_VERSION={}", version.short_version_string);
+    println!("cargo:rustc-env=SETUP2_TARGET={}", std::env::var("TARGET").unwrap());
+}
@@ -0,0 +1,91 @@
+//! Checks that downloaded ceremony parameters are the ones the ceremony published.
+//!
+//! Anyone using the network can run this against the parameter files they received,
+//! together with the transcript digest and verifying key fingerprint published by the
+//! ceremony, to confirm that they did not receive altered parameters.
+use phase2::parameters::MPCParameters;
+use setup_utils::calculate_hash;
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+use snarkvm_utilities::CanonicalSerialize;
+
+use fs_err::File;
+use gumdrop::Options;
+use memmap::MmapOptions;
+use std::process;
+
+#[derive(Debug, Options)]
+struct VerifyDownloadOpts {
+    help: bool,
+    #[options(required, help = "the downloaded parameters file")]
+    params: String,
+    #[options(help = "the parameters are for the inner circuit rather than the outer circuit")]
+    is_inner: bool,
+    #[options(help = "the published hash of the parameters file")]
+    params_hash: Option<String>,
+    #[options(help = "the published transcript digest, which is the hash of the last contribution")]
+    transcript_digest: Option<String>,
+    #[options(help = "the published fingerprint of the verifying key")]
+    vk_fingerprint: Option<String>,
+}
+
+/// Compares a computed hash against the published one, if any, and prints the outcome.
+fn check(name: &str, computed: &[u8], published: &Option<String>) -> anyhow::Result<bool> {
+    let computed = hex::encode(computed);
+    match published {
+        Some(published) => {
+            let matches = hex::decode(published.trim())? == hex::decode(&computed)?;
+            println!("{}: {}", name, if matches { "OK" } else { "MISMATCH" });
+            if !matches {
+                println!("  published: {}\n  computed:  {}", published.trim(), computed);
+            }
+            Ok(matches)
+        }
+        None => {
+            println!(
+                "{}: {} (not checked, nothing was published to compare against)",
+                name, computed
+            );
+            Ok(true)
+        }
+    }
+}
+
+fn verify_download<E: PairingEngine>(bytes: &[u8], opts: &VerifyDownloadOpts) -> anyhow::Result<bool> {
+    let mut valid = check("parameters hash", &calculate_hash(bytes), &opts.params_hash)?;
+
+    // Checks the consistency of the verifying key and the contributions, and recomputes the transcript
+    let params = MPCParameters::<E>::read(bytes)?;
+    let hashes = params.verify_final()?;
+    println!("the parameters contain {} valid contributions", hashes.len());
+    let digest = hashes.last().expect("verify_final fails without contributions");
+    valid &= check("transcript digest", digest, &opts.transcript_digest)?;
+
+    let mut vk = vec![];
+    params.get_params().vk.serialize(&mut vk)?;
+    valid &= check("verifying key fingerprint", &calculate_hash(&vk), &opts.vk_fingerprint)?;
+
+    Ok(valid)
+}
+
+fn main() -> anyhow::Result<()> {
+    let opts = VerifyDownloadOpts::parse_args_default_or_exit();
+
+    let file = File::open(&opts.params)?;
+    let bytes = unsafe { MmapOptions::new().map(file.file())? };
+    let valid = if opts.is_inner {
+        verify_download::<Bls12_377>(&bytes, &opts)?
+    } else {
+        verify_download::<BW6_761>(&bytes, &opts)?
+    };
+
+    if !valid {
+        eprintln!("The downloaded parameters do NOT match the published ceremony output.");
+        process::exit(1);
+    }
+    if opts.params_hash.is_none() || opts.transcript_digest.is_none() || opts.vk_fingerprint.is_none() {
+        println!("The parameters are internally consistent, but were not compared against every published value.");
+    } else {
+        println!("The downloaded parameters match the published ceremony output.");
+    }
+    Ok(())
+}
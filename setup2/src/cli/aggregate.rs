@@ -0,0 +1,334 @@
+//! The aggregation of the final responses of a chunked ceremony into the
+//! proving and verifying keys of the circuit.
+use super::permissions;
+use phase2::parameters::{verify_transcript, MPCParameters};
+use setup_utils::Error as SetupError;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+use snarkvm_utilities::CanonicalSerialize;
+
+use fs_err::File;
+use gumdrop::Options;
+use memmap::MmapOptions;
+use std::cmp::{max, min};
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Options, Clone)]
+pub struct AggregateOpts {
+    help: bool,
+    #[options(free, help = "the final responses of the chunks, in the order of the challenge list")]
+    pub responses: Vec<String>,
+    #[options(
+        required,
+        help = "a file listing the chunks `new` wrote, one path per line, in order"
+    )]
+    pub challenge_list: String,
+    #[options(
+        help = "the query parameters the chunks were split from",
+        default = "challenge.query"
+    )]
+    pub query: String,
+    #[options(help = "the file to write the proving key to", default = "proving_key")]
+    pub proving_key: String,
+    #[options(help = "the file to write the verifying key to", default = "verifying_key")]
+    pub verifying_key: String,
+    #[options(help = "also write the combined parameters, with their contributions, to this file")]
+    pub output: Option<String>,
+    #[options(help = "setup the inner or the outer circuit?")]
+    pub is_inner: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum AggregateError {
+    #[error("the challenge list {0} lists no chunks")]
+    EmptyChallengeList(String),
+    #[error("the challenge list has {challenges} chunks, but {responses} responses were given")]
+    ChunkCountMismatch { challenges: usize, responses: usize },
+    #[error("the queries of the challenges make up {expected} chunks, but the challenge list has {got}")]
+    InvalidChunkCount { expected: usize, got: usize },
+    #[error("the {query} query of {file} has {got} elements, but {expected} were expected")]
+    BoundaryMismatch {
+        file: String,
+        query: &'static str,
+        expected: usize,
+        got: usize,
+    },
+    #[error("{response} is not a valid contribution to {challenge}: {source}")]
+    InvalidChunk {
+        challenge: String,
+        response: String,
+        source: SetupError,
+    },
+}
+
+/// The number of elements of the `h` and `l` queries in a chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkBoundary {
+    pub file: String,
+    pub h: usize,
+    pub l: usize,
+}
+
+impl ChunkBoundary {
+    fn of<E: PairingEngine>(file: &str, chunk: &MPCParameters<E>) -> Self {
+        Self {
+            file: file.to_string(),
+            h: chunk.params.h_query.len(),
+            l: chunk.params.l_query.len(),
+        }
+    }
+}
+
+/// Returns the paths of the chunks in a challenge list, skipping blank lines.
+pub fn parse_challenge_list(list: &str) -> Vec<String> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+///
+/// Checks that the challenges are split like `new` splits the parameters,
+/// i.e. every chunk but the last of each query holds as many elements as
+/// the first chunk, and that each response holds as many elements of each
+/// query as its challenge.
+///
+pub fn ensure_boundaries(challenges: &[ChunkBoundary], responses: &[ChunkBoundary]) -> Result<(), AggregateError> {
+    if challenges.len() != responses.len() {
+        return Err(AggregateError::ChunkCountMismatch {
+            challenges: challenges.len(),
+            responses: responses.len(),
+        });
+    }
+    let first = match challenges.first() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    let chunk_size = max(first.h, first.l);
+    let total_h: usize = challenges.iter().map(|chunk| chunk.h).sum();
+    let total_l: usize = challenges.iter().map(|chunk| chunk.l).sum();
+    let ensure = |file: &str, query, expected, got| match expected == got {
+        true => Ok(()),
+        false => Err(AggregateError::BoundaryMismatch {
+            file: file.to_string(),
+            query,
+            expected,
+            got,
+        }),
+    };
+
+    for (i, (challenge, response)) in challenges.iter().zip(responses).enumerate() {
+        let expected_h = min(chunk_size, total_h.saturating_sub(i * chunk_size));
+        let expected_l = min(chunk_size, total_l.saturating_sub(i * chunk_size));
+        ensure(&challenge.file, "h", expected_h, challenge.h)?;
+        ensure(&challenge.file, "l", expected_l, challenge.l)?;
+        ensure(&response.file, "h", challenge.h, response.h)?;
+        ensure(&response.file, "l", challenge.l, response.l)?;
+    }
+    // Chunks past the end of both queries are not ones `new` wrote
+    let num_chunks = match chunk_size {
+        0 => 1,
+        _ => max(
+            (total_h + chunk_size - 1) / chunk_size,
+            (total_l + chunk_size - 1) / chunk_size,
+        ),
+    };
+    if challenges.len() != num_chunks {
+        return Err(AggregateError::InvalidChunkCount {
+            expected: num_chunks,
+            got: challenges.len(),
+        });
+    }
+    Ok(())
+}
+
+pub(super) fn read_params<E: PairingEngine>(path: &str) -> anyhow::Result<MPCParameters<E>> {
+    let file = File::open(path)?;
+    let mmap = unsafe { MmapOptions::new().map(file.file())? };
+    Ok(MPCParameters::read(&mmap[..])?)
+}
+
+fn aggregate_params<E: PairingEngine>(opts: &AggregateOpts, challenges: &[String]) -> anyhow::Result<()> {
+    let query = read_params::<E>(&opts.query)?;
+
+    // Every response is verified against its challenge before anything is combined, so that
+    // the keys are never written from a chunk which was not contributed to correctly. The
+    // challenges are not needed after that, so only one of them is held at a time
+    let mut challenge_boundaries = Vec::with_capacity(challenges.len());
+    let mut responses = Vec::with_capacity(opts.responses.len());
+    let mut response_boundaries = Vec::with_capacity(opts.responses.len());
+    for (challenge, response) in challenges.iter().zip(&opts.responses) {
+        let before = read_params::<E>(challenge)?;
+        let after = read_params::<E>(response)?;
+        query
+            .verify_chunk(&before, &after)
+            .map_err(|source| AggregateError::InvalidChunk {
+                challenge: challenge.clone(),
+                response: response.clone(),
+                source,
+            })?;
+        challenge_boundaries.push(ChunkBoundary::of(challenge, &before));
+        response_boundaries.push(ChunkBoundary::of(response, &after));
+        responses.push(after);
+    }
+    ensure_boundaries(&challenge_boundaries, &response_boundaries)?;
+
+    let combined = MPCParameters::combine(&query, &responses)?;
+    drop(responses);
+    let hashes = verify_transcript(combined.cs_hash, &combined.contributions)?;
+    info!(
+        "combined {} chunks with {} contributions",
+        challenges.len(),
+        hashes.len()
+    );
+
+    if let Some(output) = &opts.output {
+        combined.write_to(permissions::create(output)?)?;
+    }
+    combined
+        .params
+        .serialize(&mut permissions::create(&opts.proving_key)?)?;
+    combined
+        .params
+        .vk
+        .serialize(&mut permissions::create(&opts.verifying_key)?)?;
+    Ok(())
+}
+
+pub fn aggregate(opts: &AggregateOpts) -> anyhow::Result<()> {
+    let challenges = parse_challenge_list(&fs_err::read_to_string(&opts.challenge_list)?);
+    if challenges.is_empty() {
+        return Err(AggregateError::EmptyChallengeList(opts.challenge_list.clone()).into());
+    }
+    if challenges.len() != opts.responses.len() {
+        return Err(AggregateError::ChunkCountMismatch {
+            challenges: challenges.len(),
+            responses: opts.responses.len(),
+        }
+        .into());
+    }
+
+    if opts.is_inner {
+        aggregate_params::<Bls12_377>(opts, &challenges)
+    } else {
+        aggregate_params::<BW6_761>(opts, &challenges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cli::{new::CurveKind, test_vectors, TestVectorsOpts};
+    use std::path::Path;
+
+    fn boundaries(file: &str, chunks: &[(usize, usize)]) -> Vec<ChunkBoundary> {
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(i, (h, l))| ChunkBoundary {
+                file: format!("{}.{}", file, i),
+                h: *h,
+                l: *l,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_challenge_list() {
+        assert_eq!(parse_challenge_list("challenge.0\n\n  challenge.1 \n"), vec![
+            "challenge.0".to_string(),
+            "challenge.1".to_string()
+        ]);
+        assert!(parse_challenge_list("\n").is_empty());
+    }
+
+    #[test]
+    fn test_ensure_boundaries() {
+        // 7 h and 5 l elements in chunks of 3
+        let challenges = boundaries("challenge", &[(3, 3), (3, 2), (1, 0)]);
+        ensure_boundaries(&challenges, &boundaries("response", &[(3, 3), (3, 2), (1, 0)])).unwrap();
+
+        // a response which lost an element
+        let err = ensure_boundaries(&challenges, &boundaries("response", &[(3, 3), (3, 1), (1, 0)])).unwrap_err();
+        assert!(matches!(
+            err,
+            AggregateError::BoundaryMismatch { ref file, query: "l", expected: 2, got: 1 } if file == "response.1"
+        ));
+
+        // challenges which were not split like `new` splits them
+        let reordered = boundaries("challenge", &[(3, 3), (1, 0), (3, 2)]);
+        assert!(ensure_boundaries(&reordered, &reordered).is_err());
+        let padded = boundaries("challenge", &[(3, 3), (0, 0)]);
+        assert!(matches!(
+            ensure_boundaries(&padded, &padded),
+            Err(AggregateError::InvalidChunkCount { expected: 1, got: 2 })
+        ));
+
+        // the single chunk of a tiny circuit holds both queries whole
+        let single = boundaries("challenge", &[(7, 5)]);
+        ensure_boundaries(&single, &boundaries("response", &[(7, 5)])).unwrap();
+
+        assert!(matches!(
+            ensure_boundaries(&challenges, &challenges[..2]),
+            Err(AggregateError::ChunkCountMismatch {
+                challenges: 3,
+                responses: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_verifies_every_chunk() {
+        let dir = std::env::temp_dir().join(format!("setup2-aggregate-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+        test_vectors(&TestVectorsOpts {
+            help: false,
+            output: dir.display().to_string(),
+            seed: None,
+            curve_type: CurveKind::Bls12_377,
+            power: 4,
+            chunks: 3,
+            json: false,
+        })
+        .unwrap();
+        let path = |name: &str| dir.join(name).display().to_string();
+        let challenges: Vec<_> = (0..3).map(|i| path(&format!("challenge.{}", i))).collect();
+        fs_err::write(path("challenges"), challenges.join("\n")).unwrap();
+        let opts = AggregateOpts {
+            help: false,
+            responses: (0..3).map(|i| path(&format!("response.{}", i))).collect(),
+            challenge_list: path("challenges"),
+            query: path("challenge.query"),
+            proving_key: path("proving_key"),
+            verifying_key: path("verifying_key"),
+            output: None,
+            is_inner: true,
+            json: false,
+        };
+        aggregate(&opts).unwrap();
+        assert!(Path::new(&opts.proving_key).exists());
+
+        // A response with its elements swapped still combines, but is not a contribution to its challenge
+        fs_err::remove_file(&opts.proving_key).unwrap();
+        let mut tampered = read_params::<Bls12_377>(&opts.responses[1]).unwrap();
+        assert!(tampered.params.h_query.len() > 1);
+        tampered.params.h_query.swap(0, 1);
+        tampered
+            .write_to(permissions::create(&opts.responses[1]).unwrap())
+            .unwrap();
+        let error = aggregate(&opts).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<AggregateError>(),
+            Some(AggregateError::InvalidChunk { response, .. }) if response == &opts.responses[1]
+        ));
+        assert!(!Path::new(&opts.proving_key).exists());
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+}
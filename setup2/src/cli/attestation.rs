@@ -0,0 +1,204 @@
+//! Attestations by participants that they made a contribution, signed with their Aleo keys,
+//! so that each of them holds a verifiable record of which contribution they made to which
+//! file and when.
+use super::{organizers::OrganizerSignature, paths::attestation_path, permissions};
+use phase2::chunked_groth16::read_contributions;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyAttestationOpts {
+    help: bool,
+    #[options(required, help = "the attestation to verify")]
+    pub attestation: String,
+    #[options(help = "check that the attested contribution is part of this transcript or chunk")]
+    pub transcript: Option<String>,
+    #[options(help = "check that the attestation was signed by this Aleo address")]
+    pub address: Option<String>,
+    #[options(help = "setup the inner or the outer circuit?")]
+    pub is_inner: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum AttestationError {
+    #[error("the signature of {0} over the attestation is invalid")]
+    InvalidSignature(String),
+    #[error("the attestation was signed by {signer}, not {expected}")]
+    SignerMismatch { signer: String, expected: String },
+    #[error("the contribution {contribution_hash} is not part of {transcript}")]
+    ContributionMissing {
+        contribution_hash: String,
+        transcript: String,
+    },
+    #[error("{0} holds no contributions to attest to")]
+    NoContribution(String),
+}
+
+/// What a participant attests to: that they made a contribution to a file at a point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributionStatement {
+    /// The file name of the transcript or chunk which was contributed to.
+    pub file: String,
+    /// The index of the chunk, or `None` if the full parameters were contributed to.
+    pub chunk: Option<usize>,
+    /// The hex encoded hash of the public key of the contribution.
+    pub contribution_hash: String,
+    /// When the contribution was made, as a Unix timestamp in seconds.
+    pub timestamp: u64,
+}
+
+/// A contribution statement with the signature of the participant over it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContributionAttestation {
+    pub statement: ContributionStatement,
+    pub signature: OrganizerSignature,
+}
+
+impl ContributionAttestation {
+    /// Signs the statement with the given Aleo private key of the participant.
+    pub fn sign(statement: ContributionStatement, participant_key: &str) -> anyhow::Result<Self> {
+        let signature = OrganizerSignature::sign(&statement, participant_key)?;
+        Ok(Self { statement, signature })
+    }
+
+    /// Checks the signature of the participant over the statement.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        if !self.signature.verify(&self.statement)? {
+            return Err(AttestationError::InvalidSignature(self.signature.keyid.clone()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Returns the hex encoded hashes of the contributions to the parameters at `path`, in order.
+fn contribution_hashes<E: PairingEngine>(path: &Path) -> anyhow::Result<Vec<String>> {
+    let transcript = fs_err::File::open(path)?;
+    let transcript = unsafe { MmapOptions::new().map(transcript.file())? };
+    let (_, contributions) = read_contributions::<E>(&transcript)?;
+    Ok(contributions
+        .iter()
+        .map(|contribution| hex::encode(&contribution.hash()[..]))
+        .collect())
+}
+
+pub(super) fn read_contribution_hashes(path: &Path, is_inner: bool) -> anyhow::Result<Vec<String>> {
+    if is_inner {
+        contribution_hashes::<Bls12_377>(path)
+    } else {
+        contribution_hashes::<BW6_761>(path)
+    }
+}
+
+///
+/// Attests to the last contribution to the parameters at `path`, and writes the
+/// attestation signed with the participant's key next to them.
+///
+pub(super) fn attest(path: &Path, chunk: Option<usize>, is_inner: bool, participant_key: &str) -> anyhow::Result<()> {
+    let display = path.display().to_string();
+    let contribution_hash = read_contribution_hashes(path, is_inner)?
+        .pop()
+        .ok_or_else(|| AttestationError::NoContribution(display.clone()))?;
+    let statement = ContributionStatement {
+        file: path
+            .file_name()
+            .map_or(display, |name| name.to_string_lossy().to_string()),
+        chunk,
+        contribution_hash,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+
+    let attestation = ContributionAttestation::sign(statement, participant_key)?;
+    let attestation_path = attestation_path(path);
+    permissions::write(&attestation_path, serde_json::to_vec_pretty(&attestation)?)?;
+    info!(
+        "{} attested to contribution {}, wrote {}",
+        attestation.signature.keyid,
+        attestation.statement.contribution_hash,
+        attestation_path.display()
+    );
+    Ok(())
+}
+
+pub fn verify_attestation(opts: &VerifyAttestationOpts) -> anyhow::Result<()> {
+    let attestation: ContributionAttestation = serde_json::from_slice(&fs_err::read(&opts.attestation)?)?;
+    if let Some(expected) = &opts.address {
+        if expected != &attestation.signature.keyid {
+            return Err(AttestationError::SignerMismatch {
+                signer: attestation.signature.keyid,
+                expected: expected.clone(),
+            }
+            .into());
+        }
+    }
+    attestation.verify()?;
+
+    // Later contributions are appended to the earlier ones, so the attested one stays part of the transcript
+    if let Some(transcript) = &opts.transcript {
+        let hashes = read_contribution_hashes(Path::new(transcript), opts.is_inner)?;
+        if !hashes.contains(&attestation.statement.contribution_hash) {
+            return Err(AttestationError::ContributionMissing {
+                contribution_hash: attestation.statement.contribution_hash,
+                transcript: transcript.clone(),
+            }
+            .into());
+        }
+    }
+
+    info!(
+        "{} attested to contribution {} to {} at {}",
+        attestation.signature.keyid,
+        attestation.statement.contribution_hash,
+        attestation.statement.file,
+        attestation.statement.timestamp
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, PrivateKey};
+
+    fn statement() -> ContributionStatement {
+        ContributionStatement {
+            file: "challenge.3".to_string(),
+            chunk: Some(3),
+            contribution_hash: "ab".repeat(64),
+            timestamp: 1_600_000_000,
+        }
+    }
+
+    #[test]
+    fn test_attestation_signature() {
+        let key = PrivateKey::<Testnet2Parameters>::new(&mut ChaChaRng::from_seed([1; 32])).to_string();
+        let attestation = ContributionAttestation::sign(statement(), &key).unwrap();
+        attestation.verify().unwrap();
+
+        // The signature covers every field of the statement
+        let mut forged = attestation.clone();
+        forged.statement.chunk = Some(4);
+        assert!(matches!(
+            forged.verify().unwrap_err().downcast_ref::<AttestationError>(),
+            Some(AttestationError::InvalidSignature(_))
+        ));
+        let mut forged = attestation;
+        forged.statement.timestamp += 1;
+        assert!(forged.verify().is_err());
+    }
+}
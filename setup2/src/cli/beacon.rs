@@ -0,0 +1,52 @@
+//! The public random beacon applied as the final contribution of a ceremony, which is
+//! recorded in the manifest so that anyone can recompute the randomness it contributed.
+use super::{
+    beacon_vdf::evaluate_beacon,
+    ceremony::{CeremonyError, CeremonyManifest},
+    contribute::{contribute, ContributeOpts},
+    paths::artifact_path,
+};
+use setup_utils::get_rng;
+
+use std::path::Path;
+use tracing::{info, warn};
+
+pub fn beacon(opts: &ContributeOpts) -> anyhow::Result<()> {
+    // The manifest is stored next to the transcript, as `new` wrote it
+    let data = artifact_path(&opts.data)?;
+    let dir = data.parent().unwrap_or_else(|| Path::new(""));
+    let manifest = match CeremonyManifest::version_in(dir)? {
+        0 => None,
+        _ => Some(CeremonyManifest::load(dir)?),
+    };
+    if let Some(applied) = manifest.as_ref().and_then(|manifest| manifest.beacon.as_ref()) {
+        return Err(CeremonyError::BeaconApplied(applied.beacon_hash.clone()).into());
+    }
+
+    // Every chunk the parameters were split into receives the beacon, unless told otherwise
+    let mut opts = opts.clone();
+    if opts.chunks.is_none() {
+        opts.chunks = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.artifacts.as_ref())
+            .map(|artifacts| artifacts.num_chunks)
+            .filter(|num_chunks| *num_chunks > 0);
+    }
+
+    let vdf_iterations = manifest.as_ref().and_then(|manifest| manifest.config.vdf_iterations);
+    let beacon = evaluate_beacon(&opts, vdf_iterations)?;
+    contribute(&opts, &mut get_rng(&hex::decode(&beacon.randomness)?))?;
+
+    match manifest {
+        Some(_) => {
+            info!("recording the beacon {} in the ceremony manifest", beacon.beacon_hash);
+            CeremonyManifest::record_beacon(dir, beacon)?;
+        }
+        None => warn!(
+            "{} holds no ceremony manifest, the beacon {} was not recorded",
+            dir.display(),
+            beacon.beacon_hash
+        ),
+    }
+    Ok(())
+}
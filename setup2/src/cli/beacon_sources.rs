@@ -0,0 +1,250 @@
+//! The public sources a beacon contribution may take its randomness from, listed in order
+//! of preference. The beacon hash is chosen from them by a deterministic rule, so anyone can
+//! check the choice against the values the sources published.
+use super::permissions;
+use setup_utils::calculate_hash;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+use tracing::info;
+
+/// A public source of randomness, identified by the value it publishes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum BeaconSource {
+    /// The randomness of a drand round.
+    Drand { round: u64 },
+    /// The hash of the Bitcoin block at the given height, as displayed by block explorers.
+    Bitcoin { height: u64 },
+    /// The output value of a NIST randomness beacon pulse.
+    Nist { pulse: u64 },
+}
+
+impl BeaconSource {
+    /// Returns the number of bytes of the values published by the source.
+    fn value_len(&self) -> usize {
+        match self {
+            BeaconSource::Drand { .. } | BeaconSource::Bitcoin { .. } => 32,
+            BeaconSource::Nist { .. } => 64,
+        }
+    }
+}
+
+impl fmt::Display for BeaconSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BeaconSource::Drand { round } => write!(f, "drand round {}", round),
+            BeaconSource::Bitcoin { height } => write!(f, "bitcoin block {}", height),
+            BeaconSource::Nist { pulse } => write!(f, "NIST pulse {}", pulse),
+        }
+    }
+}
+
+/// What was observed of a beacon source: either the value it published, or why it was unavailable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BeaconObservation {
+    pub source: BeaconSource,
+    /// The hex encoded value the source published.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Why the source was unavailable, if it did not publish a value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unavailable: Option<String>,
+}
+
+/// The beacon hash chosen from an ordered list of sources, which is recorded so the choice can be verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconSelection {
+    /// Every source in order of preference.
+    pub sources: Vec<BeaconObservation>,
+    /// The index of the selected source, the first one which published a value.
+    pub selected: usize,
+    /// The hex encoded beacon hash derived from the value of the selected source.
+    pub beacon_hash: String,
+}
+
+#[derive(Debug, Error)]
+pub enum BeaconSourceError {
+    #[error("no beacon sources were listed")]
+    NoSources,
+    #[error("{0} must have either a value or the reason it was unavailable")]
+    UndecidedSource(BeaconSource),
+    #[error("the value of {beacon} must be {expected} hex encoded bytes")]
+    InvalidValue { beacon: BeaconSource, expected: usize },
+    #[error("none of the beacon sources published a value")]
+    NoSourceAvailable,
+    #[error("the recorded beacon hash {recorded} was not selected by the fallback rule, which selects {expected}")]
+    SelectionMismatch { recorded: String, expected: String },
+}
+
+impl BeaconSelection {
+    /// Selects the first source which published a value, after checking that every
+    /// source has either a well formed value or the reason it was unavailable.
+    ///
+    /// A source whose value is 32 bytes long gives the beacon hash directly, while a longer
+    /// value is reduced to the first 32 bytes of its BLAKE2b hash.
+    pub fn select(sources: Vec<BeaconObservation>) -> Result<Self, BeaconSourceError> {
+        if sources.is_empty() {
+            return Err(BeaconSourceError::NoSources);
+        }
+
+        let mut selected = None;
+        for (i, observation) in sources.iter().enumerate() {
+            let value = match (&observation.value, &observation.unavailable) {
+                (Some(value), None) => value,
+                (None, Some(_)) => continue,
+                _ => return Err(BeaconSourceError::UndecidedSource(observation.source.clone())),
+            };
+            let expected = observation.source.value_len();
+            let value = match hex::decode(value) {
+                Ok(value) if value.len() == expected => value,
+                _ => {
+                    return Err(BeaconSourceError::InvalidValue {
+                        beacon: observation.source.clone(),
+                        expected,
+                    });
+                }
+            };
+            if selected.is_none() {
+                let beacon_hash = match value.len() {
+                    32 => value,
+                    _ => calculate_hash(&value)[..32].to_vec(),
+                };
+                selected = Some((i, hex::encode(beacon_hash)));
+            }
+        }
+
+        let (selected, beacon_hash) = selected.ok_or(BeaconSourceError::NoSourceAvailable)?;
+        Ok(Self {
+            sources,
+            selected,
+            beacon_hash,
+        })
+    }
+
+    /// Checks that the recorded beacon hash is the one the fallback rule selects from the recorded sources.
+    pub fn verify(&self) -> Result<(), BeaconSourceError> {
+        let expected = Self::select(self.sources.clone())?;
+        if expected.selected != self.selected || expected.beacon_hash != self.beacon_hash {
+            return Err(BeaconSourceError::SelectionMismatch {
+                recorded: self.beacon_hash.clone(),
+                expected: expected.beacon_hash,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Selects the beacon hash from the sources listed in the given JSON file, writes the
+/// selection to the record file and returns the hex encoded beacon hash.
+pub fn select_beacon(sources: &str, record: &str) -> anyhow::Result<String> {
+    let sources: Vec<BeaconObservation> = serde_json::from_slice(&fs_err::read(sources)?)?;
+    let selection = BeaconSelection::select(sources)?;
+    for observation in &selection.sources[..selection.selected] {
+        info!(
+            "{} was unavailable: {}",
+            observation.source,
+            observation.unavailable.as_deref().unwrap_or_default()
+        );
+    }
+    info!(
+        "using the beacon hash {} of {}",
+        selection.beacon_hash, selection.sources[selection.selected].source
+    );
+    permissions::write(record, serde_json::to_vec_pretty(&selection)?)?;
+    Ok(selection.beacon_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available(source: BeaconSource, value: &str) -> BeaconObservation {
+        BeaconObservation {
+            source,
+            value: Some(value.to_string()),
+            unavailable: None,
+        }
+    }
+
+    fn unavailable(source: BeaconSource) -> BeaconObservation {
+        BeaconObservation {
+            source,
+            value: None,
+            unavailable: Some("not published before the deadline".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_first_available_source_is_selected() {
+        let bitcoin = "0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620";
+        let selection = BeaconSelection::select(vec![
+            unavailable(BeaconSource::Drand { round: 1000 }),
+            available(BeaconSource::Bitcoin { height: 700000 }, bitcoin),
+            available(BeaconSource::Nist { pulse: 42 }, &"ab".repeat(64)),
+        ])
+        .unwrap();
+        assert_eq!(selection.selected, 1);
+        assert_eq!(selection.beacon_hash, bitcoin);
+        assert!(selection.verify().is_ok());
+
+        // A value longer than 32 bytes is hashed
+        let nist =
+            BeaconSelection::select(vec![available(BeaconSource::Nist { pulse: 42 }, &"ab".repeat(64))]).unwrap();
+        assert_eq!(nist.beacon_hash.len(), 64);
+
+        // The recorded selection must follow the fallback rule
+        let mut tampered = selection;
+        tampered.selected = 2;
+        tampered.beacon_hash = nist.beacon_hash;
+        assert!(matches!(
+            tampered.verify(),
+            Err(BeaconSourceError::SelectionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_sources_are_rejected() {
+        assert!(matches!(
+            BeaconSelection::select(vec![]),
+            Err(BeaconSourceError::NoSources)
+        ));
+        assert!(matches!(
+            BeaconSelection::select(vec![unavailable(BeaconSource::Drand { round: 1 })]),
+            Err(BeaconSourceError::NoSourceAvailable)
+        ));
+        assert!(matches!(
+            BeaconSelection::select(vec![available(BeaconSource::Drand { round: 1 }, "00")]),
+            Err(BeaconSourceError::InvalidValue { expected: 32, .. })
+        ));
+
+        // Every source must be accounted for, even after the selected one
+        let undecided = BeaconObservation {
+            source: BeaconSource::Nist { pulse: 1 },
+            value: None,
+            unavailable: None,
+        };
+        assert!(matches!(
+            BeaconSelection::select(vec![
+                available(BeaconSource::Drand { round: 1 }, &"00".repeat(32)),
+                undecided,
+            ]),
+            Err(BeaconSourceError::UndecidedSource(_))
+        ));
+    }
+
+    #[test]
+    fn test_sources_file_format() {
+        let sources: Vec<BeaconObservation> = serde_json::from_str(
+            r#"[
+                { "source": { "drand": { "round": 1000 } }, "unavailable": "the drand network was halted" },
+                { "source": { "bitcoin": { "height": 700000 } }, "value": "00" }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(sources[0].source, BeaconSource::Drand { round: 1000 });
+        assert_eq!(sources[1].value.as_deref(), Some("00"));
+    }
+}
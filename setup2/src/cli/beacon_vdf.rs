@@ -0,0 +1,145 @@
+use super::{beacon_sources::select_beacon, ceremony::CeremonyBeacon, permissions, ContributeOpts};
+use setup_utils::{beacon_randomness, from_slice, vdf_evaluate, vdf_verify, VdfProof};
+
+use gumdrop::Options;
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Options, Clone)]
+pub struct BeaconVdfOpts {
+    help: bool,
+    #[options(command)]
+    pub command: Option<BeaconVdfCommand>,
+}
+
+#[derive(Debug, Options, Clone)]
+pub enum BeaconVdfCommand {
+    #[options(help = "evaluate the delay function on a beacon hash and write its proof")]
+    Evaluate(VdfEvaluateOpts),
+    #[options(help = "verify the proof of the delay function and print the beacon randomness")]
+    Verify(VdfVerifyOpts),
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VdfEvaluateOpts {
+    help: bool,
+    #[options(required, help = "the beacon hash to evaluate the delay function on")]
+    pub beacon_hash: String,
+    #[options(
+        help = "the number of sequential squarings, which sets the delay",
+        default = "1048576"
+    )]
+    pub iterations: u64,
+    #[options(help = "the file to write the proof to", default = "beacon_vdf.json")]
+    pub proof: String,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VdfVerifyOpts {
+    help: bool,
+    #[options(help = "the proof to verify", default = "beacon_vdf.json")]
+    pub proof: String,
+    #[options(
+        required,
+        help = "the number of sequential squarings the proof must take, as recorded in the ceremony manifest"
+    )]
+    pub iterations: u64,
+    #[options(help = "the beacon hash the proof must have been evaluated on")]
+    pub beacon_hash: Option<String>,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum BeaconVdfError {
+    #[error("no beacon-vdf subcommand was provided, expected `evaluate` or `verify`")]
+    MissingCommand,
+    #[error("the proof was evaluated on beacon hash {proof}, not {expected}")]
+    BeaconHashMismatch { proof: String, expected: String },
+    #[error("the beacon-vdf delay must be at least one squaring")]
+    ZeroIterations,
+    #[error("the ceremony manifest records no beacon-vdf delay, so the proof cannot be checked against one")]
+    IterationsNotRecorded,
+    #[error("the ceremony requires a beacon-vdf proof with {0} iterations, pass it with --vdf-proof")]
+    MissingProof(u64),
+}
+
+impl BeaconVdfOpts {
+    /// Returns `true` if the result should be printed as JSON.
+    pub fn json(&self) -> bool {
+        match &self.command {
+            Some(BeaconVdfCommand::Evaluate(opt)) => opt.json,
+            Some(BeaconVdfCommand::Verify(opt)) => opt.json,
+            None => false,
+        }
+    }
+}
+
+pub fn beacon_vdf(opts: &BeaconVdfOpts) -> anyhow::Result<()> {
+    match &opts.command {
+        Some(BeaconVdfCommand::Evaluate(opt)) => {
+            let beacon_hash = hex::decode(&opt.beacon_hash)?;
+            info!("evaluating the delay function with {} iterations", opt.iterations);
+            let proof = vdf_evaluate(&beacon_hash, opt.iterations);
+            permissions::write(&opt.proof, serde_json::to_vec_pretty(&proof)?)?;
+            info!("wrote the proof to {}", opt.proof);
+            Ok(())
+        }
+        Some(BeaconVdfCommand::Verify(opt)) => {
+            let randomness = verify_proof_file(&opt.proof, opt.iterations, opt.beacon_hash.as_deref())?;
+            info!(
+                "the proof is valid, the beacon randomness is {}",
+                hex::encode(randomness)
+            );
+            Ok(())
+        }
+        None => Err(BeaconVdfError::MissingCommand.into()),
+    }
+}
+
+/// Loads and verifies the proof in the given file, which must take the given number of iterations,
+/// optionally checking the beacon hash it was evaluated on.
+fn verify_proof_file(path: &str, iterations: u64, beacon_hash: Option<&str>) -> anyhow::Result<[u8; 32]> {
+    let proof: VdfProof = serde_json::from_slice(&fs_err::read(path)?)?;
+    if let Some(beacon_hash) = beacon_hash {
+        if hex::decode(beacon_hash)? != hex::decode(&proof.input)? {
+            return Err(BeaconVdfError::BeaconHashMismatch {
+                proof: proof.input,
+                expected: beacon_hash.to_string(),
+            }
+            .into());
+        }
+    }
+    Ok(vdf_verify(&proof, iterations)?)
+}
+
+/// Returns the beacon of a beacon contribution, whose randomness is taken from the verified
+/// VDF proof if one is given, and from iterated SHA256 of the beacon hash otherwise.
+///
+/// `vdf_iterations` is the delay the ceremony manifest requires, see `CeremonyConfig::vdf_iterations`:
+/// a ceremony which requires one is not finalized without a proof of it, and a proof is only
+/// accepted against a recorded delay.
+///
+/// If beacon sources are given, the beacon hash is selected from them instead.
+pub fn evaluate_beacon(opts: &ContributeOpts, vdf_iterations: Option<u64>) -> anyhow::Result<CeremonyBeacon> {
+    let beacon_hash = match &opts.beacon_sources {
+        Some(sources) => select_beacon(sources, &opts.beacon_record)?,
+        None => opts.beacon_hash.clone(),
+    };
+    let randomness = match (&opts.vdf_proof, vdf_iterations) {
+        (Some(path), Some(iterations)) => verify_proof_file(path, iterations, Some(&beacon_hash))?,
+        (Some(_), None) => return Err(BeaconVdfError::IterationsNotRecorded.into()),
+        (None, Some(iterations)) => return Err(BeaconVdfError::MissingProof(iterations).into()),
+        (None, None) => beacon_randomness(from_slice(&hex::decode(&beacon_hash)?)),
+    };
+    Ok(CeremonyBeacon {
+        beacon_hash,
+        randomness: hex::encode(randomness),
+        vdf: opts.vdf_proof.is_some(),
+        vdf_iterations,
+    })
+}
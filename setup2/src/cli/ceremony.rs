@@ -0,0 +1,588 @@
+use super::{
+    new::{CircuitKind, CurveKind},
+    paths::tmp_path,
+    permissions,
+};
+use phase2::chunked_groth16::read_contributions;
+use setup_utils::{calculate_hash, to_canonical_json, UseCompression};
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use fs_err::File;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// The name of the manifest file stored at the root of every ceremony directory.
+pub const MANIFEST_FILENAME: &str = "ceremony.json";
+
+/// The latest version of the ceremony directory layout and manifest schema.
+///
+/// Version 0 denotes a legacy ceremony directory which has no manifest.
+/// Whenever the schema changes, bump this and add a step to `migrate_manifest`.
+pub const CEREMONY_FORMAT_VERSION: u32 = 6;
+
+#[derive(Debug, Error)]
+pub enum CeremonyError {
+    #[error("Ceremony IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid ceremony manifest: {0}")]
+    InvalidManifest(#[from] serde_json::Error),
+    #[error("No ceremony manifest found at {0}, run `migrate` to create one")]
+    MissingManifest(PathBuf),
+    #[error("A ceremony manifest already exists at {0}")]
+    ManifestExists(PathBuf),
+    #[error("{0} already exists")]
+    ArtifactExists(PathBuf),
+    #[error("{0} records an interrupted `new` with other parameters, remove it and the artifacts to start over")]
+    ProgressMismatch(PathBuf),
+    #[error("The ceremony is at format version {0} but this binary expects version {1}, run `migrate` first")]
+    OutdatedVersion(u32, u32),
+    #[error(
+        "The ceremony is at format version {0} but this binary only supports up to version {1}, upgrade the binary"
+    )]
+    UnsupportedVersion(u32, u32),
+    #[error("Migrating a legacy ceremony requires the parameters it was created with")]
+    MissingLegacyParameters,
+    #[error("The beacon {0} was already applied as the final contribution of the ceremony")]
+    BeaconApplied(String),
+    #[error(
+        "{} was generated from another phase 1 transcript or circuit than the ceremony, its cs_hash is {cs_hash} \
+         instead of {expected}",
+        .file.display()
+    )]
+    ForeignArtifact {
+        file: PathBuf,
+        cs_hash: String,
+        expected: String,
+    },
+}
+
+/// The hash algorithm used for transcript and contribution hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Blake2b,
+}
+
+/// The hash algorithm used by this binary.
+pub const HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::Blake2b;
+
+/// The parameters a ceremony was created with, which must stay the same for its entire lifetime.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonyConfig {
+    /// The curve the phase 2 parameters are defined over.
+    pub curve: CurveKind,
+    /// Whether the ceremony is for the inner or the outer circuit.
+    pub is_inner: bool,
+    /// The number of powers (in powers of 2) of the phase 1 transcript.
+    pub phase1_size: u32,
+    /// Whether the elements of the phase 1 transcript are compressed.
+    pub compression: UseCompression,
+    /// The hash algorithm used for transcript and contribution hashes.
+    pub hash_algorithm: HashAlgorithm,
+    /// The file name of the initial transcript, relative to the ceremony directory.
+    pub transcript: String,
+    /// The hex encoded digest of the circuit (see `phase2::parameters::qap_digest`),
+    /// or `None` for ceremonies which were migrated from a layout without it.
+    pub circuit_digest: Option<String>,
+    /// The number of sequential squarings the beacon-vdf proof of the final beacon must take, or
+    /// `None` if the beacon is not delayed. It is fixed when the ceremony is created, so that whoever
+    /// applies the beacon cannot choose a shorter delay. Left out of the ceremony id if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vdf_iterations: Option<u64>,
+}
+
+impl CeremonyConfig {
+    /// Returns the id of the ceremony, the hex encoded hash of the canonical JSON encoding of
+    /// the parameters it was created with, which stays the same when the manifest is migrated.
+    pub fn ceremony_id(&self) -> Result<String, CeremonyError> {
+        Ok(hex::encode(&calculate_hash(to_canonical_json(self)?.as_bytes())[..]))
+    }
+}
+
+/// What `new` generated, so that coordinators and verifiers can check the artifacts of a ceremony
+/// without parsing them. Unlike the config, it is not part of the ceremony id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonyArtifacts {
+    /// The circuit the parameters were generated for, or `None` for a custom circuit loaded from an R1CS file.
+    pub circuit: Option<CircuitKind>,
+    /// The number of powers of the phase 2 parameters, the size of the circuit rounded up to a power of 2.
+    pub phase2_size: usize,
+    /// The hex encoded hash of the full parameters, the initial transcript.
+    pub transcript_hash: String,
+    /// The maximum number of elements of the H and L queries in a chunk, or `None` if the parameters were not split.
+    pub chunk_size: Option<usize>,
+    /// The number of chunks, 0 if the parameters were not split.
+    pub num_chunks: usize,
+    /// The hex encoded hash of the query parameters, if the parameters were split.
+    pub query_hash: Option<String>,
+    /// The hex encoded hashes of the chunks, by index.
+    pub chunk_hashes: Vec<String>,
+    /// The hex encoded hash of the phase 1 transcript the parameters were generated from,
+    /// or `None` for ceremonies which were migrated from a layout without it. The files of the
+    /// ceremony do not embed it, so it identifies the phase 1 transcript for auditors and for
+    /// `new` resuming a run, while `check_phase1` checks the files against the `cs_hash`.
+    pub phase1_hash: Option<String>,
+    /// The hex encoded `cs_hash` embedded in the initial transcript and its chunks, which commits to
+    /// the parameters generated from phase 1 and which every contribution to them keeps, or `None`
+    /// for ceremonies which were migrated from a layout without it.
+    pub cs_hash: Option<String>,
+}
+
+impl CeremonyArtifacts {
+    /// Checks that the `cs_hash` embedded in `file` is the one of the initial transcript, if it is recorded.
+    pub fn check_cs_hash(&self, file: &Path, cs_hash: &[u8]) -> Result<(), CeremonyError> {
+        match &self.cs_hash {
+            Some(expected) if *expected != hex::encode(cs_hash) => Err(CeremonyError::ForeignArtifact {
+                file: file.to_path_buf(),
+                cs_hash: hex::encode(cs_hash),
+                expected: expected.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The public random beacon applied as the final contribution of a ceremony, so that anyone
+/// can recompute the randomness of the last contribution from the beacon hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonyBeacon {
+    /// The hex encoded beacon hash, e.g. the hash of a Bitcoin block.
+    pub beacon_hash: String,
+    /// The hex encoded randomness the contribution was derived from.
+    pub randomness: String,
+    /// Whether the randomness is the output of a beacon-vdf proof rather than iterated SHA256 of the beacon hash.
+    pub vdf: bool,
+    /// The number of sequential squarings of the beacon-vdf proof, or `None` for beacons without
+    /// one and for beacons which were recorded before it was.
+    pub vdf_iterations: Option<u64>,
+}
+
+/// The versioned manifest stored as `ceremony.json` in a ceremony directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CeremonyManifest {
+    pub version: u32,
+    pub config: CeremonyConfig,
+    /// What `new` generated, or `None` for ceremonies which were migrated from a layout without it.
+    pub artifacts: Option<CeremonyArtifacts>,
+    /// The beacon applied as the final contribution, or `None` if the ceremony was not finalized with one.
+    pub beacon: Option<CeremonyBeacon>,
+}
+
+impl CeremonyManifest {
+    /// Returns a manifest at the latest format version.
+    pub fn new(config: CeremonyConfig, artifacts: Option<CeremonyArtifacts>) -> Self {
+        Self {
+            version: CEREMONY_FORMAT_VERSION,
+            config,
+            artifacts,
+            beacon: None,
+        }
+    }
+
+    /// Returns the path of the manifest in the given ceremony directory.
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(MANIFEST_FILENAME)
+    }
+
+    /// Returns the version of the manifest in the given directory, or 0 if there is none.
+    pub fn version_in(dir: &Path) -> Result<u32, CeremonyError> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(0);
+        }
+        Ok(read_version(&serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    /// Loads the manifest from the given ceremony directory, which must be at the latest version.
+    pub fn load(dir: &Path) -> Result<Self, CeremonyError> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Err(CeremonyError::MissingManifest(path));
+        }
+        let value: Value = serde_json::from_slice(&fs::read(path)?)?;
+        check_version(read_version(&value))?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Writes the manifest to the given ceremony directory, refusing to overwrite an existing one.
+    pub fn create(&self, dir: &Path) -> Result<(), CeremonyError> {
+        let path = Self::path(dir);
+        if path.exists() {
+            return Err(CeremonyError::ManifestExists(path));
+        }
+        self.save(dir)
+    }
+
+    /// Records the beacon applied as the final contribution in the manifest of the given ceremony directory.
+    pub fn record_beacon(dir: &Path, beacon: CeremonyBeacon) -> Result<(), CeremonyError> {
+        let mut manifest = Self::load(dir)?;
+        if let Some(applied) = manifest.beacon {
+            return Err(CeremonyError::BeaconApplied(applied.beacon_hash));
+        }
+        manifest.beacon = Some(beacon);
+        manifest.save(dir)
+    }
+
+    /// Writes the manifest to the given ceremony directory.
+    pub fn save(&self, dir: &Path) -> Result<(), CeremonyError> {
+        let path = Self::path(dir);
+        // Write to a temporary file first so an interrupted write never leaves a truncated manifest
+        let tmp = tmp_path(&path);
+        permissions::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+fn read_cs_hash<E: PairingEngine>(file: &Path) -> anyhow::Result<[u8; 64]> {
+    let mmap = unsafe { MmapOptions::new().map(File::open(file)?.file())? };
+    Ok(read_contributions::<E>(&mmap)?.0)
+}
+
+///
+/// Checks that the transcripts or chunks in `files` were generated from the same phase 1
+/// transcript and circuit as the ceremony in `dir`, by comparing the `cs_hash` embedded in
+/// each of them with the one its manifest records. Mixing them up would only surface once
+/// the chunks are combined, if at all. Directories without a manifest are not checked.
+///
+/// The `cs_hash` stands in for the `phase1_hash` of the manifest, which the files do not embed:
+/// it is the hash of the initial parameters, which are computed from the phase 1 transcript, so
+/// files generated from another phase 1 transcript have another `cs_hash`.
+///
+pub(super) fn check_phase1<P: AsRef<Path>>(dir: &Path, files: &[P], is_inner: bool) -> anyhow::Result<()> {
+    let artifacts = match CeremonyManifest::version_in(dir)? {
+        0 => return Ok(()),
+        _ => CeremonyManifest::load(dir)?.artifacts,
+    };
+    let artifacts = match artifacts {
+        Some(artifacts) => artifacts,
+        None => return Ok(()),
+    };
+    for file in files {
+        let file: &Path = file.as_ref();
+        let cs_hash = match is_inner {
+            true => read_cs_hash::<Bls12_377>(file)?,
+            false => read_cs_hash::<BW6_761>(file)?,
+        };
+        artifacts.check_cs_hash(file, &cs_hash)?;
+    }
+    Ok(())
+}
+
+fn read_version(value: &Value) -> u32 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+fn check_version(version: u32) -> Result<(), CeremonyError> {
+    if version < CEREMONY_FORMAT_VERSION {
+        Err(CeremonyError::OutdatedVersion(version, CEREMONY_FORMAT_VERSION))
+    } else if version > CEREMONY_FORMAT_VERSION {
+        Err(CeremonyError::UnsupportedVersion(version, CEREMONY_FORMAT_VERSION))
+    } else {
+        Ok(())
+    }
+}
+
+/// Upgrades the ceremony directory `dir` to the latest layout, one version at a time.
+///
+/// Legacy directories (version 0) have no manifest, so the parameters the ceremony
+/// was created with must be provided via `legacy`. Returns the version the directory
+/// was at before the migration.
+pub fn migrate(dir: &Path, legacy: Option<CeremonyConfig>) -> Result<u32, CeremonyError> {
+    let from = CeremonyManifest::version_in(dir)?;
+    if from > CEREMONY_FORMAT_VERSION {
+        return Err(CeremonyError::UnsupportedVersion(from, CEREMONY_FORMAT_VERSION));
+    }
+    if from == CEREMONY_FORMAT_VERSION {
+        return Ok(from);
+    }
+
+    // Legacy parameters are provided in the latest schema, so they need no further upgrades
+    let mut manifest = if from == 0 {
+        let config = legacy.ok_or(CeremonyError::MissingLegacyParameters)?;
+        serde_json::to_value(CeremonyManifest::new(config, None))?
+    } else {
+        serde_json::from_slice(&fs::read(CeremonyManifest::path(dir))?)?
+    };
+
+    let mut version = read_version(&manifest);
+    while version < CEREMONY_FORMAT_VERSION {
+        manifest = migrate_manifest(version, manifest)?;
+        version = read_version(&manifest);
+    }
+
+    let manifest: CeremonyManifest = serde_json::from_value(manifest)?;
+    manifest.save(dir)?;
+    Ok(from)
+}
+
+/// Upgrades a manifest by exactly one version.
+fn migrate_manifest(version: u32, mut manifest: Value) -> Result<Value, CeremonyError> {
+    match version {
+        // Version 2 records the circuit digest, which is unknown for older ceremonies
+        1 => {
+            manifest["config"]["circuit_digest"] = Value::Null;
+        }
+        // Version 3 records what `new` generated, which was not kept for older ceremonies
+        2 => {
+            manifest["artifacts"] = Value::Null;
+        }
+        // Version 4 records the beacon applied as the final contribution, which older ceremonies did not record
+        3 => {
+            manifest["beacon"] = Value::Null;
+        }
+        // Version 5 records the phase 1 transcript and the cs_hash of the initial transcript, which are unknown
+        // for older ceremonies
+        4 => {
+            if manifest["artifacts"].is_object() {
+                manifest["artifacts"]["phase1_hash"] = Value::Null;
+                manifest["artifacts"]["cs_hash"] = Value::Null;
+            }
+        }
+        // Version 6 records the delay of the beacon-vdf proof of the beacon, which is unknown for older ceremonies
+        5 => {
+            if manifest["beacon"].is_object() {
+                manifest["beacon"]["vdf_iterations"] = Value::Null;
+            }
+        }
+        _ => return Err(CeremonyError::UnsupportedVersion(version, CEREMONY_FORMAT_VERSION)),
+    }
+    manifest["version"] = (version + 1).into();
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CeremonyConfig {
+        CeremonyConfig {
+            curve: CurveKind::Bls12_377,
+            is_inner: true,
+            phase1_size: 19,
+            compression: UseCompression::No,
+            hash_algorithm: HashAlgorithm::Blake2b,
+            transcript: "initial_ceremony".to_string(),
+            circuit_digest: Some("00".repeat(64)),
+            vdf_iterations: None,
+        }
+    }
+
+    fn tempdir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("setup2-ceremony-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_ceremony_id() {
+        let id = config().ceremony_id().unwrap();
+        assert_eq!(id, config().ceremony_id().unwrap());
+        assert_eq!(id.len(), 128);
+
+        let mut other = config();
+        other.is_inner = false;
+        assert_ne!(id, other.ceremony_id().unwrap());
+
+        // The required delay of the beacon is part of the id
+        let mut delayed = config();
+        delayed.vdf_iterations = Some(1 << 20);
+        assert_ne!(id, delayed.ceremony_id().unwrap());
+    }
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let dir = tempdir("roundtrip");
+        let manifest = CeremonyManifest::new(config(), None);
+        manifest.create(&dir).unwrap();
+        assert!(matches!(manifest.create(&dir), Err(CeremonyError::ManifestExists(_))));
+        assert_eq!(CeremonyManifest::load(&dir).unwrap(), manifest);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let dir = tempdir("legacy");
+        assert!(matches!(
+            CeremonyManifest::load(&dir),
+            Err(CeremonyError::MissingManifest(_))
+        ));
+        assert!(matches!(
+            migrate(&dir, None),
+            Err(CeremonyError::MissingLegacyParameters)
+        ));
+
+        assert_eq!(migrate(&dir, Some(config())).unwrap(), 0);
+        assert_eq!(
+            CeremonyManifest::load(&dir).unwrap(),
+            CeremonyManifest::new(config(), None)
+        );
+
+        // migrating an up-to-date ceremony is a no-op
+        assert_eq!(migrate(&dir, None).unwrap(), CEREMONY_FORMAT_VERSION);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let dir = tempdir("v1");
+        let mut manifest = serde_json::to_value(CeremonyManifest::new(config(), None)).unwrap();
+        manifest["version"] = 1.into();
+        manifest["config"].as_object_mut().unwrap().remove("circuit_digest");
+        manifest.as_object_mut().unwrap().remove("artifacts");
+        manifest.as_object_mut().unwrap().remove("beacon");
+        fs::write(CeremonyManifest::path(&dir), serde_json::to_vec(&manifest).unwrap()).unwrap();
+        assert!(matches!(
+            CeremonyManifest::load(&dir),
+            Err(CeremonyError::OutdatedVersion(1, _))
+        ));
+
+        assert_eq!(migrate(&dir, None).unwrap(), 1);
+        let manifest = CeremonyManifest::load(&dir).unwrap();
+        assert_eq!(manifest.version, CEREMONY_FORMAT_VERSION);
+        assert_eq!(manifest.config.circuit_digest, None);
+        assert_eq!(manifest.artifacts, None);
+        assert_eq!(manifest.beacon, None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_beacon() {
+        let dir = tempdir("beacon");
+        CeremonyManifest::new(config(), None).create(&dir).unwrap();
+        let beacon = CeremonyBeacon {
+            beacon_hash: "00".repeat(32),
+            randomness: "01".repeat(32),
+            vdf: false,
+            vdf_iterations: None,
+        };
+        CeremonyManifest::record_beacon(&dir, beacon.clone()).unwrap();
+        assert_eq!(CeremonyManifest::load(&dir).unwrap().beacon, Some(beacon.clone()));
+
+        // The beacon is the final contribution, so a second one is refused
+        assert!(matches!(
+            CeremonyManifest::record_beacon(&dir, beacon),
+            Err(CeremonyError::BeaconApplied(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_artifacts_roundtrip() {
+        let dir = tempdir("artifacts");
+        let artifacts = CeremonyArtifacts {
+            circuit: Some(CircuitKind::Inner),
+            phase2_size: 1 << 19,
+            transcript_hash: "01".repeat(64),
+            chunk_size: Some(1 << 16),
+            num_chunks: 2,
+            query_hash: Some("02".repeat(64)),
+            chunk_hashes: vec!["03".repeat(64), "04".repeat(64)],
+            phase1_hash: Some("05".repeat(64)),
+            cs_hash: Some("06".repeat(64)),
+        };
+        let manifest = CeremonyManifest::new(config(), Some(artifacts));
+        manifest.create(&dir).unwrap();
+        assert_eq!(CeremonyManifest::load(&dir).unwrap(), manifest);
+
+        // The artifacts do not change the ceremony id
+        assert_eq!(
+            manifest.config.ceremony_id().unwrap(),
+            CeremonyManifest::new(config(), None).config.ceremony_id().unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v4() {
+        let dir = tempdir("v4");
+        let mut manifest = serde_json::to_value(CeremonyManifest::new(config(), None)).unwrap();
+        manifest["version"] = 4.into();
+        manifest["artifacts"] = serde_json::json!({
+            "circuit": null,
+            "phase2_size": 8,
+            "transcript_hash": "01".repeat(64),
+            "chunk_size": null,
+            "num_chunks": 0,
+            "query_hash": null,
+            "chunk_hashes": [],
+        });
+        fs::write(CeremonyManifest::path(&dir), serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        assert_eq!(migrate(&dir, None).unwrap(), 4);
+        let artifacts = CeremonyManifest::load(&dir).unwrap().artifacts.unwrap();
+        assert_eq!(artifacts.phase1_hash, None);
+        assert_eq!(artifacts.cs_hash, None);
+
+        // Without the cs_hash of the initial transcript, nothing can be checked against it
+        artifacts.check_cs_hash(Path::new("challenge"), &[1; 64]).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_v5() {
+        let dir = tempdir("v5");
+        let mut manifest = serde_json::to_value(CeremonyManifest::new(config(), None)).unwrap();
+        manifest["version"] = 5.into();
+        manifest["beacon"] = serde_json::json!({
+            "beacon_hash": "00".repeat(32),
+            "randomness": "01".repeat(32),
+            "vdf": true,
+        });
+        fs::write(CeremonyManifest::path(&dir), serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        assert_eq!(migrate(&dir, None).unwrap(), 5);
+        let manifest = CeremonyManifest::load(&dir).unwrap();
+        assert_eq!(manifest.config.vdf_iterations, None);
+        let beacon = manifest.beacon.unwrap();
+        assert!(beacon.vdf);
+        assert_eq!(beacon.vdf_iterations, None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_cs_hash() {
+        let artifacts = CeremonyArtifacts {
+            circuit: None,
+            phase2_size: 8,
+            transcript_hash: "01".repeat(64),
+            chunk_size: None,
+            num_chunks: 0,
+            query_hash: None,
+            chunk_hashes: vec![],
+            phase1_hash: Some("02".repeat(64)),
+            cs_hash: Some(hex::encode(&[3; 64][..])),
+        };
+        artifacts.check_cs_hash(Path::new("response.0"), &[3; 64]).unwrap();
+        assert!(matches!(
+            artifacts.check_cs_hash(Path::new("response.1"), &[4; 64]),
+            Err(CeremonyError::ForeignArtifact { file, .. }) if file == Path::new("response.1")
+        ));
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let dir = tempdir("newer");
+        let mut manifest = serde_json::to_value(CeremonyManifest::new(config(), None)).unwrap();
+        manifest["version"] = (CEREMONY_FORMAT_VERSION + 1).into();
+        fs::write(CeremonyManifest::path(&dir), serde_json::to_vec(&manifest).unwrap()).unwrap();
+        assert!(matches!(
+            CeremonyManifest::load(&dir),
+            Err(CeremonyError::UnsupportedVersion(..))
+        ));
+        assert!(matches!(
+            migrate(&dir, None),
+            Err(CeremonyError::UnsupportedVersion(..))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+//! Checks the chunks in a ceremony directory against the hashes the manifest records for them,
+//! so that participants can tell a corrupted or truncated download apart before spending hours
+//! contributing to it. The `.hash` file `new` writes next to each chunk is checked too, as a
+//! chunk is often downloaded along with it. `contribute` and `verify` rewrite it, so that a chunk
+//! which was contributed to is checked against the hash of the contribution instead.
+use super::{
+    ceremony::CeremonyManifest,
+    new::file_hash,
+    paths::{chunk_path, hash_path},
+};
+
+use gumdrop::Options;
+use serde::Serialize;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Options, Clone)]
+pub struct CheckHashesOpts {
+    help: bool,
+    #[options(help = "the ceremony directory holding the manifest and the chunks", default = ".")]
+    pub dir: String,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum CheckHashesError {
+    #[error("the manifest in {} records no chunks", .0.display())]
+    NoChunks(PathBuf),
+    #[error("the chunks do not match the manifest: {0}")]
+    Mismatch(String),
+}
+
+/// The state of a chunk compared to the hash the manifest records for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkHashState {
+    /// The chunk has the hash in the manifest.
+    Valid,
+    /// The chunk is not in the directory.
+    Missing,
+    /// The chunk has a different hash, which is the one in the hash file next to it, as it was contributed to.
+    Contributed,
+    /// The chunk has a different hash, e.g. as its download was truncated.
+    Corrupted,
+    /// The chunk is valid, but the hash file next to it differs from the manifest.
+    HashFileMismatch,
+}
+
+/// The outcome of checking a single chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChunkHashCheck {
+    pub chunk: usize,
+    pub file: String,
+    /// The hex encoded hash the manifest records for the chunk.
+    pub expected: String,
+    /// The hex encoded hash of the chunk, if it exists.
+    pub actual: Option<String>,
+    pub state: ChunkHashState,
+}
+
+/// The outcome of checking every chunk of a ceremony directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HashReport {
+    pub dir: String,
+    pub chunks: Vec<ChunkHashCheck>,
+}
+
+impl ChunkHashState {
+    /// Returns `true` if the chunk is intact, either as generated or as contributed to.
+    fn is_intact(self) -> bool {
+        matches!(self, ChunkHashState::Valid | ChunkHashState::Contributed)
+    }
+}
+
+impl HashReport {
+    /// Returns the error naming the chunks which do not match the manifest, if any.
+    fn failure(&self) -> Option<CheckHashesError> {
+        let failures: Vec<String> = self
+            .chunks
+            .iter()
+            .filter(|check| !check.state.is_intact())
+            .map(|check| format!("{} is {}", check.file, check.state))
+            .collect();
+        match failures.is_empty() {
+            true => None,
+            false => Some(CheckHashesError::Mismatch(failures.join("; "))),
+        }
+    }
+}
+
+impl fmt::Display for ChunkHashState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = match self {
+            ChunkHashState::Valid => "valid",
+            ChunkHashState::Contributed => "contributed to, and matches its hash file",
+            ChunkHashState::Missing => "missing",
+            ChunkHashState::Corrupted => "corrupted",
+            ChunkHashState::HashFileMismatch => "valid, but its hash file differs from the manifest",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+impl fmt::Display for HashReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "chunks of {}", self.dir)?;
+        for check in &self.chunks {
+            writeln!(f, "  {}: {}", check.file, check.state)?;
+        }
+        let valid = self
+            .chunks
+            .iter()
+            .filter(|check| check.state.is_intact())
+            .count();
+        write!(f, "{} of {} chunks are intact", valid, self.chunks.len())
+    }
+}
+
+fn check_chunk(transcript: &Path, chunk: usize, expected: &str) -> anyhow::Result<ChunkHashCheck> {
+    let path = chunk_path(transcript, chunk);
+    let actual = match path.exists() {
+        true => Some(file_hash(&path)?),
+        false => None,
+    };
+    let hash_file = hash_path(&path);
+    let recorded = match hash_file.exists() {
+        true => Some(fs_err::read_to_string(&hash_file)?.trim().to_string()),
+        false => None,
+    };
+    let state = match (&actual, &recorded) {
+        (None, _) => ChunkHashState::Missing,
+        (Some(actual), Some(recorded)) if actual != expected && actual == recorded => ChunkHashState::Contributed,
+        (Some(actual), _) if actual != expected => ChunkHashState::Corrupted,
+        (Some(_), Some(recorded)) if recorded != expected => ChunkHashState::HashFileMismatch,
+        (Some(_), _) => ChunkHashState::Valid,
+    };
+    Ok(ChunkHashCheck {
+        chunk,
+        file: path.display().to_string(),
+        expected: expected.to_string(),
+        actual,
+        state,
+    })
+}
+
+/// Checks every chunk the manifest in `dir` records against its hash.
+fn check_dir(dir: &Path) -> anyhow::Result<HashReport> {
+    let manifest = CeremonyManifest::load(dir)?;
+    let chunk_hashes = match manifest.artifacts {
+        Some(artifacts) if !artifacts.chunk_hashes.is_empty() => artifacts.chunk_hashes,
+        _ => return Err(CheckHashesError::NoChunks(dir.to_path_buf()).into()),
+    };
+    let transcript = dir.join(&manifest.config.transcript);
+    let chunks = chunk_hashes
+        .iter()
+        .enumerate()
+        .map(|(chunk, expected)| check_chunk(&transcript, chunk, expected))
+        .collect::<anyhow::Result<_>>()?;
+    Ok(HashReport {
+        dir: dir.display().to_string(),
+        chunks,
+    })
+}
+
+pub fn check_hashes(opts: &CheckHashesOpts) -> anyhow::Result<()> {
+    let report = check_dir(Path::new(&opts.dir))?;
+    if opts.json {
+        println!("{}", serde_json::to_string(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    if let Some(failure) = report.failure() {
+        return Err(failure.into());
+    }
+    info!("all {} chunks match the manifest", report.chunks.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cli::{
+        ceremony::{CeremonyArtifacts, CeremonyConfig, HASH_ALGORITHM},
+        new::CurveKind,
+    };
+    use setup_utils::{calculate_hash, UseCompression};
+
+    #[test]
+    fn test_check_hashes() {
+        let dir = std::env::temp_dir().join(format!("setup2-check-hashes-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+        fs_err::create_dir_all(&dir).unwrap();
+        let transcript = dir.join("challenge");
+        let hash = |contents: &[u8]| hex::encode(&calculate_hash(contents)[..]);
+        let contents: [&[u8]; 3] = [b"chunk 0", b"chunk 1", b"chunk 2"];
+        for (i, chunk) in contents.iter().enumerate() {
+            fs_err::write(chunk_path(&transcript, i), chunk).unwrap();
+            fs_err::write(hash_path(chunk_path(&transcript, i)), hash(chunk)).unwrap();
+        }
+        let config = CeremonyConfig {
+            curve: CurveKind::Bls12_377,
+            is_inner: true,
+            phase1_size: 10,
+            compression: UseCompression::No,
+            hash_algorithm: HASH_ALGORITHM,
+            transcript: "challenge".to_string(),
+            circuit_digest: None,
+            vdf_iterations: None,
+        };
+        CeremonyManifest::new(config, None).create(&dir).unwrap();
+        assert!(matches!(
+            check_dir(&dir).unwrap_err().downcast_ref::<CheckHashesError>(),
+            Some(CheckHashesError::NoChunks(_))
+        ));
+
+        let mut manifest = CeremonyManifest::load(&dir).unwrap();
+        manifest.artifacts = Some(CeremonyArtifacts {
+            circuit: None,
+            phase2_size: 8,
+            transcript_hash: hash(b"challenge"),
+            chunk_size: Some(3),
+            num_chunks: 3,
+            query_hash: Some(hash(b"query")),
+            chunk_hashes: contents.iter().map(|chunk| hash(chunk)).collect(),
+            phase1_hash: None,
+            cs_hash: None,
+        });
+        manifest.save(&dir).unwrap();
+        let report = check_dir(&dir).unwrap();
+        assert!(report.failure().is_none());
+        assert_eq!(report.chunks[1].actual, Some(hash(b"chunk 1")));
+
+        // A contributed chunk no longer has the hash in the manifest, but the one `contribute` wrote next to it
+        fs_err::write(chunk_path(&transcript, 1), b"chunk 1 contributed").unwrap();
+        fs_err::write(hash_path(chunk_path(&transcript, 1)), hash(b"chunk 1 contributed")).unwrap();
+        let report = check_dir(&dir).unwrap();
+        assert_eq!(report.chunks[1].state, ChunkHashState::Contributed);
+        assert!(report.failure().is_none());
+
+        // A truncated chunk, a missing one, and a hash file of another chunk are all reported
+        fs_err::write(chunk_path(&transcript, 0), b"chunk").unwrap();
+        fs_err::remove_file(chunk_path(&transcript, 1)).unwrap();
+        fs_err::write(hash_path(chunk_path(&transcript, 2)), hash(b"chunk 0")).unwrap();
+        let report = check_dir(&dir).unwrap();
+        let states: Vec<_> = report.chunks.iter().map(|check| check.state).collect();
+        assert_eq!(states, vec![
+            ChunkHashState::Corrupted,
+            ChunkHashState::Missing,
+            ChunkHashState::HashFileMismatch
+        ]);
+        let error = report.failure().unwrap().to_string();
+        assert!(error.contains("challenge.0 is corrupted"));
+        assert!(error.contains("challenge.1 is missing"));
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,391 @@
+//! Signed verification checkpoints, so that verifying a long transcript can resume from the
+//! contribution up to which the organizers and auditors already verified it, instead of starting over.
+//!
+//! A checkpoint records the hash of the last contribution it covers, which binds all the
+//! contributions before it, and the digest of the checkpoint before it, so that the published
+//! checkpoints form a chain which can be checked on its own. Each signer verifies the transcript
+//! from the latest published checkpoint before signing the next one.
+use super::{
+    ceremony::CeremonyManifest,
+    finalization::read_params,
+    organizers::{OrganizerError, OrganizerPolicy, OrganizerSignature},
+    permissions,
+};
+use setup_utils::{calculate_hash, to_canonical_json};
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use gumdrop::Options;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+
+/// The name of the directory holding the checkpoints and their signatures, stored in the ceremony directory.
+pub const CHECKPOINTS_DIRNAME: &str = "checkpoints";
+
+#[derive(Debug, Options, Clone)]
+pub struct SignCheckpointOpts {
+    help: bool,
+    #[options(help = "the ceremony directory to sign a checkpoint of", default = ".")]
+    pub dir: String,
+    #[options(required, help = "the file holding the Aleo private key of the organizer or auditor")]
+    pub key: String,
+    #[options(required, help = "the file listing the organizers and auditors who sign checkpoints")]
+    pub policy: String,
+    #[options(help = "the number of contributions the checkpoint covers, defaults to all of them")]
+    pub contributions: Option<usize>,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyTranscriptOpts {
+    help: bool,
+    #[options(help = "the ceremony directory whose transcript to verify", default = ".")]
+    pub dir: String,
+    #[options(required, help = "the file listing the organizers and auditors who sign checkpoints")]
+    pub policy: String,
+    #[options(help = "verify every contribution, ignoring the checkpoints")]
+    pub full: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("An address cannot be both an organizer and an auditor of the checkpoints")]
+    OverlappingPolicy,
+    #[error("The checkpoint after {0} contributions does not follow the checkpoint before it")]
+    BrokenChain(usize),
+    #[error("The checkpoint after {0} contributions was taken of another transcript")]
+    TranscriptMismatch(usize),
+    #[error("A checkpoint after {0} contributions must cover more than the latest one, after {1}")]
+    NotAfterLatest(usize, usize),
+}
+
+/// Who must sign a checkpoint before verification may resume from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckpointPolicy {
+    pub organizers: OrganizerPolicy,
+    pub auditors: OrganizerPolicy,
+}
+
+impl CheckpointPolicy {
+    /// Reads the policy at the given path, and checks that its thresholds can be met.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let policy: Self = serde_json::from_slice(&fs_err::read(path)?)?;
+        policy.organizers.check()?;
+        policy.auditors.check()?;
+        let auditors = &policy.auditors.addresses;
+        if policy
+            .organizers
+            .addresses
+            .iter()
+            .any(|address| auditors.contains(address))
+        {
+            return Err(CheckpointError::OverlappingPolicy.into());
+        }
+        Ok(policy)
+    }
+}
+
+/// The digest of a transcript after its first `contributions` contributions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationCheckpoint {
+    /// The number of contributions covered by the checkpoint.
+    pub contributions: usize,
+    /// The hex encoded hash of the last contribution covered.
+    pub contribution_hash: String,
+    /// The hex encoded digest of the checkpoint before this one, if there is one.
+    pub previous: Option<String>,
+}
+
+impl VerificationCheckpoint {
+    /// Returns the hex encoded hash of the canonical JSON encoding of the checkpoint.
+    pub fn digest(&self) -> anyhow::Result<String> {
+        Ok(hex::encode(&calculate_hash(to_canonical_json(self)?.as_bytes())[..]))
+    }
+}
+
+/// A checkpoint with the signatures of enough organizers and auditors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCheckpoint {
+    pub checkpoint: VerificationCheckpoint,
+    pub organizers: Vec<OrganizerSignature>,
+    pub auditors: Vec<OrganizerSignature>,
+}
+
+impl SignedCheckpoint {
+    /// Returns the checkpoint with the signatures of the organizers and auditors among
+    /// the given ones, if enough of each signed it.
+    pub fn approve(
+        checkpoint: VerificationCheckpoint,
+        signatures: &[OrganizerSignature],
+        policy: &CheckpointPolicy,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            organizers: policy.organizers.approvals(&checkpoint, signatures)?,
+            auditors: policy.auditors.approvals(&checkpoint, signatures)?,
+            checkpoint,
+        })
+    }
+
+    /// Checks that enough organizers and auditors signed the checkpoint.
+    fn verify(&self, policy: &CheckpointPolicy) -> anyhow::Result<()> {
+        policy.organizers.approvals(&self.checkpoint, &self.organizers)?;
+        policy.auditors.approvals(&self.checkpoint, &self.auditors)?;
+        Ok(())
+    }
+}
+
+fn checkpoints_dir(dir: &Path) -> PathBuf {
+    dir.join(CHECKPOINTS_DIRNAME)
+}
+
+/// Returns the directory the signatures of the checkpoint after `contributions` contributions are collected in.
+fn signatures_dir(dir: &Path, contributions: usize) -> PathBuf {
+    checkpoints_dir(dir).join(contributions.to_string())
+}
+
+/// Returns the path of the published checkpoint after `contributions` contributions.
+fn checkpoint_path(dir: &Path, contributions: usize) -> PathBuf {
+    checkpoints_dir(dir).join(format!("{}.json", contributions))
+}
+
+/// Reads the JSON files directly in the given directory, if it exists.
+fn read_json_files<T: serde::de::DeserializeOwned>(dir: &Path) -> anyhow::Result<Vec<T>> {
+    let mut values = vec![];
+    if dir.exists() {
+        for entry in fs_err::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().map_or(false, |extension| extension == "json") {
+                values.push(serde_json::from_slice(&fs_err::read(path)?)?);
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Reads the published checkpoints of the ceremony, and checks that they form a chain signed
+/// under the policy. Returns them in the order of the contributions they cover.
+pub fn load_chain(dir: &Path, policy: &CheckpointPolicy) -> anyhow::Result<Vec<SignedCheckpoint>> {
+    let mut chain: Vec<SignedCheckpoint> = read_json_files(&checkpoints_dir(dir))?;
+    chain.sort_by_key(|signed| signed.checkpoint.contributions);
+
+    let mut previous: Option<&SignedCheckpoint> = None;
+    for signed in &chain {
+        signed.verify(policy)?;
+        let checkpoint = &signed.checkpoint;
+        let follows = match previous {
+            Some(previous) => {
+                checkpoint.contributions > previous.checkpoint.contributions
+                    && checkpoint.previous == Some(previous.checkpoint.digest()?)
+            }
+            None => checkpoint.previous.is_none(),
+        };
+        if !follows {
+            return Err(CheckpointError::BrokenChain(checkpoint.contributions).into());
+        }
+        previous = Some(signed);
+    }
+    Ok(chain)
+}
+
+/// Verifies the transcript from the latest checkpoint of the chain, or entirely if there is none,
+/// and checks that every checkpoint was taken of it. Returns the hashes of all the contributions.
+fn verify_from_chain<E: PairingEngine>(transcript: &Path, chain: &[SignedCheckpoint]) -> anyhow::Result<Vec<[u8; 64]>> {
+    let params = read_params::<E>(transcript)?;
+    let hashes = match chain.last() {
+        Some(latest) => {
+            let mut hash = [0u8; 64];
+            hex::decode_to_slice(&latest.checkpoint.contribution_hash, &mut hash)?;
+            info!(
+                "resuming verification after contribution {}",
+                latest.checkpoint.contributions
+            );
+            params.verify_final_from(latest.checkpoint.contributions, &hash)?
+        }
+        None => params.verify_final()?,
+    };
+
+    for signed in chain {
+        let checkpoint = &signed.checkpoint;
+        let hash = checkpoint.contributions.checked_sub(1).and_then(|i| hashes.get(i));
+        if hash.map(|hash| hex::encode(&hash[..])).as_ref() != Some(&checkpoint.contribution_hash) {
+            return Err(CheckpointError::TranscriptMismatch(checkpoint.contributions).into());
+        }
+    }
+    Ok(hashes)
+}
+
+/// Verifies the transcript of the ceremony in `dir`, from the latest published checkpoint unless `full` is set.
+fn verify_ceremony(
+    dir: &Path,
+    policy: &CheckpointPolicy,
+    full: bool,
+) -> anyhow::Result<(Vec<SignedCheckpoint>, Vec<[u8; 64]>)> {
+    let config = CeremonyManifest::load(dir)?.config;
+    let chain = load_chain(dir, policy)?;
+    info!("the chain of {} checkpoints is signed under the policy", chain.len());
+
+    let trusted = if full { &[][..] } else { &chain[..] };
+    let transcript = dir.join(&config.transcript);
+    let hashes = if config.is_inner {
+        verify_from_chain::<Bls12_377>(&transcript, trusted)?
+    } else {
+        verify_from_chain::<BW6_761>(&transcript, trusted)?
+    };
+    Ok((chain, hashes))
+}
+
+pub fn sign_checkpoint(opts: &SignCheckpointOpts) -> anyhow::Result<()> {
+    let dir = Path::new(&opts.dir);
+    let policy = CheckpointPolicy::load(Path::new(&opts.policy))?;
+    let (chain, hashes) = verify_ceremony(dir, &policy, false)?;
+
+    let contributions = opts.contributions.unwrap_or(hashes.len());
+    let latest = chain.last().map(|signed| &signed.checkpoint);
+    let latest_contributions = latest.map_or(0, |latest| latest.contributions);
+    if contributions <= latest_contributions || contributions > hashes.len() {
+        return Err(CheckpointError::NotAfterLatest(contributions, latest_contributions).into());
+    }
+    let checkpoint = VerificationCheckpoint {
+        contributions,
+        contribution_hash: hex::encode(&hashes[contributions - 1][..]),
+        previous: latest.map(VerificationCheckpoint::digest).transpose()?,
+    };
+
+    let key = fs_err::read_to_string(&opts.key)?;
+    let signature = OrganizerSignature::sign(&checkpoint, key.trim())?;
+    let signatures_dir = signatures_dir(dir, contributions);
+    permissions::create_dir_all(&signatures_dir)?;
+    let path = signatures_dir.join(format!("{}.json", signature.keyid));
+    permissions::write(&path, serde_json::to_vec_pretty(&signature)?)?;
+    info!("{} signed the checkpoint, wrote {}", signature.keyid, path.display());
+
+    // The checkpoint is published by whoever adds the last signature it needs
+    let signatures: Vec<OrganizerSignature> = read_json_files(&signatures_dir)?;
+    match SignedCheckpoint::approve(checkpoint, &signatures, &policy) {
+        Ok(signed) => {
+            let path = checkpoint_path(dir, contributions);
+            permissions::write(&path, serde_json::to_vec_pretty(&signed)?)?;
+            info!(
+                "the checkpoint has enough signatures, published it to {}",
+                path.display()
+            );
+        }
+        Err(error) => match error.downcast_ref::<OrganizerError>() {
+            Some(OrganizerError::NotEnoughApprovals { .. }) => info!("the checkpoint needs more signatures: {}", error),
+            _ => return Err(error),
+        },
+    }
+    Ok(())
+}
+
+pub fn verify_transcript(opts: &VerifyTranscriptOpts) -> anyhow::Result<()> {
+    let policy = CheckpointPolicy::load(Path::new(&opts.policy))?;
+    let (_, hashes) = verify_ceremony(Path::new(&opts.dir), &policy, opts.full)?;
+    let digest = hashes.last().expect("verify_final fails without contributions");
+    info!(
+        "the transcript of {} contributions is valid, its digest is {}",
+        hashes.len(),
+        hex::encode(&digest[..])
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::paths::test_dir;
+
+    use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey};
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use std::{convert::TryFrom, str::FromStr};
+
+    fn key(seed: u8) -> String {
+        PrivateKey::<Testnet2Parameters>::new(&mut ChaChaRng::from_seed([seed; 32])).to_string()
+    }
+
+    fn address(key: &str) -> String {
+        let private_key = PrivateKey::<Testnet2Parameters>::from_str(key).unwrap();
+        Address::try_from(&private_key).unwrap().to_string()
+    }
+
+    fn publish(dir: &Path, checkpoint: VerificationCheckpoint, keys: &[String], policy: &CheckpointPolicy) {
+        let signatures: Vec<_> = keys
+            .iter()
+            .map(|key| OrganizerSignature::sign(&checkpoint, key).unwrap())
+            .collect();
+        let contributions = checkpoint.contributions;
+        let signed = SignedCheckpoint::approve(checkpoint, &signatures, policy).unwrap();
+        fs_err::write(
+            checkpoint_path(dir, contributions),
+            serde_json::to_vec(&signed).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_chain() {
+        let dir = test_dir("checkpoints");
+        fs_err::create_dir_all(checkpoints_dir(&dir)).unwrap();
+
+        let (organizer, auditor) = (key(1), key(2));
+        let policy = CheckpointPolicy {
+            organizers: OrganizerPolicy {
+                threshold: 1,
+                addresses: vec![address(&organizer)],
+            },
+            auditors: OrganizerPolicy {
+                threshold: 1,
+                addresses: vec![address(&auditor)],
+            },
+        };
+        let first = VerificationCheckpoint {
+            contributions: 2,
+            contribution_hash: "00".to_string(),
+            previous: None,
+        };
+        let second = VerificationCheckpoint {
+            contributions: 5,
+            contribution_hash: "01".to_string(),
+            previous: Some(first.digest().unwrap()),
+        };
+
+        // A checkpoint needs the signatures of both the organizers and the auditors
+        let organizer_only = [OrganizerSignature::sign(&first, &organizer).unwrap()];
+        assert!(SignedCheckpoint::approve(first.clone(), &organizer_only, &policy).is_err());
+
+        publish(&dir, first.clone(), &[organizer.clone(), auditor.clone()], &policy);
+        publish(&dir, second, &[organizer.clone(), auditor.clone()], &policy);
+        let chain = load_chain(&dir, &policy).unwrap();
+        assert_eq!(
+            chain
+                .iter()
+                .map(|signed| signed.checkpoint.contributions)
+                .collect::<Vec<_>>(),
+            vec![2, 5]
+        );
+
+        // A checkpoint which does not link to the one before it breaks the chain
+        let unlinked = VerificationCheckpoint {
+            contributions: 7,
+            contribution_hash: "02".to_string(),
+            previous: Some(first.digest().unwrap()),
+        };
+        publish(&dir, unlinked, &[organizer, auditor], &policy);
+        assert!(matches!(
+            load_chain(&dir, &policy).unwrap_err().downcast_ref::<CheckpointError>(),
+            Some(CheckpointError::BrokenChain(7))
+        ));
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ChunkError {
+    #[error("the chunk size must be positive")]
+    ZeroChunkSize,
+    #[error("the number of chunks must be positive")]
+    ZeroChunks,
+    #[error("verifying chunks requires the query parameters, pass them with --query")]
+    MissingQuery,
+    #[error("the query parameters are only used when verifying chunks, pass the number of chunks with --chunks")]
+    MissingChunks,
+}
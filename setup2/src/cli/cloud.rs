@@ -0,0 +1,658 @@
+//! Reading and writing the artifacts of a ceremony in S3 or GCS buckets, so that ceremonies run in
+//! the cloud do not copy hundreds of gigabytes of chunks around with scripts. The commands memory map
+//! the files they process and contributions transform them in place, so the objects a command reads
+//! are downloaded to a staging directory first, and the files it writes are uploaded from there with
+//! multipart uploads once it succeeds. The credentials are read from the environment, as with the
+//! `aws` and `gcloud` tools, and failed requests are retried with exponential backoff.
+//!
+//! A transcript is staged along with the objects next to it which are named after it, such as its
+//! chunks and query parameters, and the ceremony manifest. The staging directory defaults to the
+//! temporary directory of the system, and can be set with the `SETUP2_STAGING_DIR` variable. It is
+//! kept if the command fails, so that running the command again resumes it rather than downloading
+//! every object again. Each staged file is recorded with the modification time and size of the object
+//! it was downloaded from, and is only downloaded again once the object changed.
+use super::{
+    ceremony::MANIFEST_FILENAME,
+    contribute::{contribute, ContributeOpts},
+    new::{new, NewOpts},
+    paths::{contribution_state_path, tmp_path},
+    permissions,
+    verify::{verify, VerifyOpts},
+};
+
+use futures::StreamExt;
+use object_store::{
+    aws::AmazonS3Builder,
+    gcp::GoogleCloudStorageBuilder,
+    path::Path as ObjectPath,
+    ObjectMeta,
+    ObjectStore,
+    RetryConfig,
+};
+use rand::{CryptoRng, Rng};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+use tracing::info;
+
+/// The size of the parts of a multipart upload, and of the reads of the file uploaded.
+const PART_SIZE: usize = 64 << 20;
+
+/// The number of times a failed request is retried.
+const MAX_RETRIES: usize = 10;
+
+/// The time after which a failed request is no longer retried.
+const RETRY_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// The variable setting the directory objects are staged in.
+const STAGING_DIR_VAR: &str = "SETUP2_STAGING_DIR";
+
+/// The name of the object a contribution is uploaded to by default, next to the challenge.
+const DEFAULT_OUTPUT: &str = "response";
+
+/// The suffix of the record of the object a file was downloaded from, next to the staged file.
+const STAGED_SUFFIX: &str = ".staged";
+
+/// The suffix of the marker of a contribution which is complete but not yet uploaded, next to the staged challenge.
+const CONTRIBUTED_SUFFIX: &str = ".contributed";
+
+#[derive(Debug, Error)]
+pub enum CloudError {
+    #[error("{0} is not an object URL, expected s3://<bucket>/<key> or gs://<bucket>/<key>")]
+    InvalidUrl(String),
+    #[error("{0} and {1} are in different buckets, which a single command cannot read and write")]
+    DifferentBuckets(String, String),
+    #[error("--output requires --data to be an object URL")]
+    LocalData,
+    #[error("there is no object at {0}")]
+    NotFound(String),
+    #[error("--output must be given, as its default is {0} itself, which the contribution would replace")]
+    OutputOverData(String),
+}
+
+/// Returns true if `src` is the URL of an object rather than a path.
+pub fn is_object_url(src: &str) -> bool {
+    src.starts_with("s3://") || src.starts_with("gs://")
+}
+
+/// The storage service of a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    S3,
+    Gcs,
+}
+
+/// The URL of an object, e.g. `s3://ceremony/round-1/challenge`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ObjectUrl {
+    service: Service,
+    bucket: String,
+    key: String,
+}
+
+impl ObjectUrl {
+    fn parse(src: &str) -> Result<Self, CloudError> {
+        let (service, rest) = if let Some(rest) = src.strip_prefix("s3://") {
+            (Service::S3, rest)
+        } else if let Some(rest) = src.strip_prefix("gs://") {
+            (Service::Gcs, rest)
+        } else {
+            return Err(CloudError::InvalidUrl(src.to_string()));
+        };
+        match rest.split_once('/') {
+            Some((bucket, key)) if !bucket.is_empty() && !key.is_empty() && !key.ends_with('/') => Ok(Self {
+                service,
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+            }),
+            _ => Err(CloudError::InvalidUrl(src.to_string())),
+        }
+    }
+
+    /// Returns the name of the object, after the last `/` of its key.
+    fn name(&self) -> &str {
+        self.key.rsplit('/').next().unwrap_or(&self.key)
+    }
+
+    /// Returns the key of the "directory" the object is in, which is empty at the root of the bucket.
+    fn dir(&self) -> &str {
+        self.key.rsplit_once('/').map_or("", |(dir, _)| dir)
+    }
+
+    /// Returns the URL of the object named `name` next to this one.
+    fn sibling(&self, name: &str) -> Self {
+        let key = match self.dir() {
+            "" => name.to_string(),
+            dir => format!("{}/{}", dir, name),
+        };
+        Self { key, ..self.clone() }
+    }
+
+    /// Returns the path the object is staged at, under `staging`.
+    fn staged_path(&self, staging: &Path) -> PathBuf {
+        let service = match self.service {
+            Service::S3 => "s3",
+            Service::Gcs => "gs",
+        };
+        let mut path = staging.join(service).join(&self.bucket);
+        path.extend(self.key.split('/'));
+        path
+    }
+}
+
+impl fmt::Display for ObjectUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scheme = match self.service {
+            Service::S3 => "s3",
+            Service::Gcs => "gs",
+        };
+        write!(f, "{}://{}/{}", scheme, self.bucket, self.key)
+    }
+}
+
+/// Returns true if the file named `name` belongs to the transcript named `transcript`, i.e. is the
+/// transcript itself, one of the files named after it, or the ceremony manifest next to it.
+fn is_transcript_file(name: &str, transcript: &str) -> bool {
+    let is_named_after = name
+        .strip_prefix(transcript)
+        .map_or(false, |suffix| suffix.is_empty() || suffix.starts_with('.'));
+    // Temporary files, the progress of an interrupted contribution and the records of the staging
+    // directory never leave it
+    let is_local = [".tmp", ".contribution.json", ".contribution.seed", STAGED_SUFFIX, CONTRIBUTED_SUFFIX]
+        .iter()
+        .any(|suffix| name.ends_with(suffix));
+    (is_named_after && !is_local) || name == MANIFEST_FILENAME
+}
+
+/// Returns `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns the version of an object a staged file is recorded with, which changes whenever the object is written.
+fn object_version(meta: &ObjectMeta) -> String {
+    format!("{} {}", meta.last_modified.to_rfc3339(), meta.size)
+}
+
+/// A bucket, and the runtime its requests are made on.
+struct Bucket {
+    store: Arc<dyn ObjectStore>,
+    runtime: Runtime,
+    staging: PathBuf,
+}
+
+impl Bucket {
+    /// Returns the bucket of `store`, whose objects are staged under `staging`.
+    fn new(store: Arc<dyn ObjectStore>, staging: PathBuf) -> anyhow::Result<Self> {
+        Ok(Self {
+            store,
+            runtime: tokio::runtime::Builder::new_multi_thread().enable_all().build()?,
+            staging,
+        })
+    }
+
+    /// Connects to the bucket of `url` with the credentials in the environment.
+    fn connect(url: &ObjectUrl) -> anyhow::Result<Self> {
+        let retry = RetryConfig {
+            max_retries: MAX_RETRIES,
+            retry_timeout: RETRY_TIMEOUT,
+            ..Default::default()
+        };
+        let store: Arc<dyn ObjectStore> = match url.service {
+            Service::S3 => Arc::new(
+                AmazonS3Builder::from_env()
+                    .with_bucket_name(&url.bucket)
+                    .with_retry(retry)
+                    .build()?,
+            ),
+            Service::Gcs => Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_bucket_name(&url.bucket)
+                    .with_retry(retry)
+                    .build()?,
+            ),
+        };
+        let staging = std::env::var_os(STAGING_DIR_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join("setup2-staging"));
+        Self::new(store, staging)
+    }
+
+    /// Connects to the bucket all of `urls` are in, of which there must be at least one.
+    fn connect_all(urls: &[&ObjectUrl]) -> anyhow::Result<Self> {
+        let first = urls[0];
+        if let Some(other) = urls
+            .iter()
+            .find(|url| url.service != first.service || url.bucket != first.bucket)
+        {
+            return Err(CloudError::DifferentBuckets(first.to_string(), other.to_string()).into());
+        }
+        Self::connect(first)
+    }
+
+    fn staged_path(&self, url: &ObjectUrl) -> PathBuf {
+        url.staged_path(&self.staging)
+    }
+
+    /// Downloads the object at `url` to its staged path, unless it was already downloaded
+    /// there and has not changed since, and returns the path.
+    fn download(&self, url: &ObjectUrl) -> anyhow::Result<PathBuf> {
+        let path = self.staged_path(url);
+        let record = with_suffix(&path, STAGED_SUFFIX);
+        let location = ObjectPath::from(url.key.as_str());
+        self.runtime.block_on(async {
+            let meta = match self.store.head(&location).await {
+                Ok(meta) => meta,
+                Err(object_store::Error::NotFound { .. }) => return Err(CloudError::NotFound(url.to_string()).into()),
+                Err(error) => return Err(error.into()),
+            };
+            let version = object_version(&meta);
+            if path.exists() && record.exists() && fs_err::read_to_string(&record)? == version {
+                info!("{} is already staged at {}", url, path.display());
+                return Ok(());
+            }
+            info!("downloading {} ({} bytes) to {}", url, meta.size, path.display());
+            if let Some(dir) = path.parent() {
+                permissions::create_dir_all(dir)?;
+            }
+            let tmp = tmp_path(&path);
+            let mut file = tokio::fs::File::from_std(permissions::create(&tmp)?.file().try_clone()?);
+            let mut stream = self.store.get(&location).await?.into_stream();
+            while let Some(bytes) = stream.next().await {
+                file.write_all(&bytes?).await?;
+            }
+            file.sync_all().await?;
+            fs_err::rename(&tmp, &path)?;
+            permissions::write(&record, version)?;
+            Ok::<_, anyhow::Error>(())
+        })?;
+        Ok(path)
+    }
+
+    /// Forgets the objects the files of the transcript staged at `transcript` were downloaded from,
+    /// as they are about to be transformed in place, so that they are downloaded again unless the
+    /// transformation is resumed on them.
+    fn forget_downloads(&self, transcript: &Path) -> anyhow::Result<()> {
+        let local_name = transcript.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let dir = transcript.parent().unwrap_or_else(|| Path::new(""));
+        for entry in fs_err::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            if let Some(staged) = name.strip_suffix(STAGED_SUFFIX) {
+                if is_transcript_file(staged, &local_name) {
+                    fs_err::remove_file(dir.join(&name))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads the transcript at `url` along with the objects which belong to it, and returns
+    /// the staged path of the transcript.
+    fn download_transcript(&self, url: &ObjectUrl) -> anyhow::Result<PathBuf> {
+        let dir = match url.dir() {
+            "" => None,
+            dir => Some(ObjectPath::from(dir)),
+        };
+        let listing = self.runtime.block_on(self.store.list_with_delimiter(dir.as_ref()))?;
+        let mut found = false;
+        for object in listing.objects {
+            let name = object.location.filename().unwrap_or_default().to_string();
+            if is_transcript_file(&name, url.name()) {
+                found |= name == url.name();
+                self.download(&url.sibling(&name))?;
+            }
+        }
+        if !found {
+            return Err(CloudError::NotFound(url.to_string()).into());
+        }
+        Ok(self.staged_path(url))
+    }
+
+    /// Uploads the file at `path` to `url` in parts, aborting the upload if any part fails
+    /// for good, so that no partial object is left in the bucket.
+    fn upload(&self, path: &Path, url: &ObjectUrl) -> anyhow::Result<()> {
+        info!("uploading {} to {}", path.display(), url);
+        let location = ObjectPath::from(url.key.as_str());
+        self.runtime.block_on(async {
+            let (id, mut writer) = self.store.put_multipart(&location).await?;
+            let upload = async {
+                let mut file = tokio::fs::File::open(path).await?;
+                let mut part = vec![0u8; PART_SIZE];
+                loop {
+                    let read = file.read(&mut part).await?;
+                    if read == 0 {
+                        break;
+                    }
+                    writer.write_all(&part[..read]).await?;
+                }
+                writer.shutdown().await
+            };
+            if let Err(error) = upload.await {
+                self.store.abort_multipart(&location, &id).await?;
+                return Err(error.into());
+            }
+            Ok::<_, anyhow::Error>(())
+        })
+    }
+
+    /// Uploads the files of the transcript staged at `transcript` next to `url`, named after it.
+    fn upload_transcript(&self, transcript: &Path, url: &ObjectUrl, skip: impl Fn(&str) -> bool) -> anyhow::Result<()> {
+        let local_name = transcript.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let dir = transcript.parent().unwrap_or_else(|| Path::new(""));
+        for entry in fs_err::read_dir(dir)? {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            if !is_transcript_file(&name, &local_name) || skip(&name) {
+                continue;
+            }
+            let remote_name = match name.strip_prefix(local_name.as_str()) {
+                Some(suffix) => format!("{}{}", url.name(), suffix),
+                None => name.clone(),
+            };
+            self.upload(&dir.join(&name), &url.sibling(&remote_name))?;
+        }
+        Ok(())
+    }
+
+    /// Removes the staged files of a command which succeeded.
+    fn remove_staged(&self, paths: &[PathBuf]) -> anyhow::Result<()> {
+        for path in paths {
+            if path.exists() {
+                fs_err::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `new` with the phase 1 transcript and the challenge at object URLs, uploading
+/// the challenge, its chunks and query parameters, and the manifest next to `--output`.
+pub(super) fn new_in_bucket(opts: &NewOpts) -> anyhow::Result<()> {
+    let output = ObjectUrl::parse(&opts.output)?;
+    let phase1 = Some(&opts.phase1)
+        .filter(|phase1| is_object_url(phase1))
+        .map(|phase1| ObjectUrl::parse(phase1))
+        .transpose()?;
+    let bucket = Bucket::connect_all(&std::iter::once(&output).chain(&phase1).collect::<Vec<_>>())?;
+
+    let mut local = opts.clone();
+    if let Some(phase1) = &phase1 {
+        local.phase1 = bucket.download(phase1)?.display().to_string();
+    }
+    let challenge = bucket.staged_path(&output);
+    permissions::create_dir_all(challenge.parent().unwrap_or_else(|| Path::new("")))?;
+    local.output = challenge.display().to_string();
+    new(&local)?;
+    bucket.upload_transcript(&challenge, &output, |_| false)?;
+    info!("uploaded the challenge to {}", output);
+    Ok(())
+}
+
+/// Runs `contribute` on the challenge at the object URL `--data`, uploading the contribution
+/// next to `--output`, which defaults to `response` next to `--data`.
+pub(super) fn contribute_in_bucket<R: Rng + CryptoRng>(opts: &ContributeOpts, rng: &mut R) -> anyhow::Result<()> {
+    if !is_object_url(&opts.data) {
+        return Err(CloudError::LocalData.into());
+    }
+    let data = ObjectUrl::parse(&opts.data)?;
+    let output = match opts.output.as_deref() {
+        Some(output) => ObjectUrl::parse(output)?,
+        // The challenge is kept, so that a contribution which fails to upload can be made again
+        None if data.name() == DEFAULT_OUTPUT => return Err(CloudError::OutputOverData(data.to_string()).into()),
+        None => data.sibling(DEFAULT_OUTPUT),
+    };
+    let bucket = Bucket::connect_all(&[&data, &output])?;
+    contribute_staged(&bucket, &data, &output, opts, rng)
+}
+
+/// Contributes to the challenge at `data` staged in `bucket`, and uploads the contribution next to `output`.
+/// A contribution which is complete is marked as such until it is uploaded, so that running the command
+/// again after a failed upload only retries the upload.
+fn contribute_staged<R: Rng + CryptoRng>(
+    bucket: &Bucket,
+    data: &ObjectUrl,
+    output: &ObjectUrl,
+    opts: &ContributeOpts,
+    rng: &mut R,
+) -> anyhow::Result<()> {
+    let staged = bucket.staged_path(data);
+    let contributed = with_suffix(&staged, CONTRIBUTED_SUFFIX);
+    if !contributed.exists() {
+        // An interrupted contribution is resumed on the files it already transformed,
+        // which must not be replaced by the challenge again
+        if !contribution_state_path(&staged).exists() {
+            bucket.download_transcript(data)?;
+        }
+        bucket.forget_downloads(&staged)?;
+        let mut local = opts.clone();
+        local.data = staged.display().to_string();
+        local.output = None;
+        contribute(&local, rng)?;
+        permissions::write(&contributed, output.to_string())?;
+    }
+
+    // The receipts of the challenge do not hold for the contribution, unlike the hashes `contribute` rewrote
+    bucket.upload_transcript(&staged, output, |name| name.ends_with(".verified"))?;
+    info!("uploaded the contribution to {}", output);
+    bucket.remove_staged(&[staged, contributed])
+}
+
+/// Runs `verify` on the contributions at object URLs, uploading the receipt next to
+/// `--after` and the report to `--report` if they are object URLs too.
+pub(super) fn verify_in_bucket(opts: &VerifyOpts) -> anyhow::Result<()> {
+    let parse = |src: Option<&String>| {
+        src.filter(|src| is_object_url(src))
+            .map(|src| ObjectUrl::parse(src))
+            .transpose()
+    };
+    let before = parse(Some(&opts.before))?;
+    let after = parse(Some(&opts.after))?;
+    let query = parse(opts.query.as_ref())?;
+    let report = parse(opts.report.as_ref())?;
+    let urls: Vec<_> = before.iter().chain(&after).chain(&query).chain(&report).collect();
+    let bucket = Bucket::connect_all(&urls)?;
+
+    let mut local = opts.clone();
+    if let Some(before) = &before {
+        local.before = bucket.download_transcript(before)?.display().to_string();
+    }
+    if let Some(after) = &after {
+        local.after = bucket.download_transcript(after)?.display().to_string();
+    }
+    if let Some(query) = &query {
+        local.query = Some(bucket.download(query)?.display().to_string());
+    }
+    if let Some(report) = &report {
+        let path = bucket.staged_path(report);
+        permissions::create_dir_all(path.parent().unwrap_or_else(|| Path::new("")))?;
+        local.report = Some(path.display().to_string());
+    }
+    let result = verify(&local);
+
+    // The report is uploaded even if the contribution is invalid, as it is written then too
+    if let (Some(report), Some(path)) = (&report, &local.report) {
+        if Path::new(path).exists() {
+            bucket.upload(Path::new(path), report)?;
+        }
+    }
+    result?;
+    // so are the receipts of the contribution and of its chunks, and the hashes `verify` rewrote next to them
+    if let Some(after) = &after {
+        bucket.upload_transcript(Path::new(&local.after), after, |name| {
+            !name.ends_with(".verified") && !name.ends_with(".hash")
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cli::paths::chunk_path;
+    use gumdrop::Options;
+    use object_store::memory::InMemory;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_object_url() {
+        let url = ObjectUrl::parse("s3://ceremony/round-1/challenge").unwrap();
+        assert_eq!(url.service, Service::S3);
+        assert_eq!((url.bucket.as_str(), url.key.as_str()), ("ceremony", "round-1/challenge"));
+        assert_eq!((url.dir(), url.name()), ("round-1", "challenge"));
+        assert_eq!(url.sibling("challenge.3").to_string(), "s3://ceremony/round-1/challenge.3");
+        assert_eq!(
+            url.staged_path(Path::new("staging")),
+            Path::new("staging/s3/ceremony/round-1/challenge")
+        );
+
+        let root = ObjectUrl::parse("gs://ceremony/response").unwrap();
+        assert_eq!((root.dir(), root.name()), ("", "response"));
+        assert_eq!(root.sibling("ceremony.json").to_string(), "gs://ceremony/ceremony.json");
+
+        for src in &["challenge", "s3://ceremony", "s3:///challenge", "gs://ceremony/round-1/", "http://a/b"] {
+            assert!(matches!(ObjectUrl::parse(src), Err(CloudError::InvalidUrl(_))));
+        }
+        assert!(is_object_url("gs://ceremony/response"));
+        assert!(!is_object_url("./s3/challenge"));
+    }
+
+    #[test]
+    fn test_is_transcript_file() {
+        for name in &["challenge", "challenge.0", "challenge.query", "challenge.0.hash", "ceremony.json"] {
+            assert!(is_transcript_file(name, "challenge"), "{}", name);
+        }
+        let local = [
+            "challenges",
+            "response.0",
+            "challenge.0.tmp",
+            "challenge.contribution.json",
+            "challenge.contribution.seed",
+            "challenge.0.staged",
+            "challenge.contributed",
+        ];
+        for name in &local {
+            assert!(!is_transcript_file(name, "challenge"), "{}", name);
+        }
+    }
+
+    /// Returns a bucket kept in memory, staged in a fresh directory named after `test`.
+    fn in_memory(test: &str) -> Bucket {
+        let staging = std::env::temp_dir().join(format!("setup2-{}-{}", test, std::process::id()));
+        let _ = fs_err::remove_dir_all(&staging);
+        Bucket::new(Arc::new(InMemory::new()), staging).unwrap()
+    }
+
+    fn put(bucket: &Bucket, url: &ObjectUrl, contents: &[u8]) {
+        let location = ObjectPath::from(url.key.as_str());
+        bucket
+            .runtime
+            .block_on(bucket.store.put(&location, contents.to_vec().into()))
+            .unwrap();
+    }
+
+    fn get(bucket: &Bucket, url: &ObjectUrl) -> Vec<u8> {
+        let location = ObjectPath::from(url.key.as_str());
+        let result = bucket.runtime.block_on(bucket.store.get(&location)).unwrap();
+        bucket.runtime.block_on(result.bytes()).unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_download_changed_object() {
+        let bucket = in_memory("cloud-download");
+        let url = ObjectUrl::parse("s3://ceremony/round-1/challenge").unwrap();
+        assert!(matches!(
+            bucket.download(&url).unwrap_err().downcast_ref::<CloudError>(),
+            Some(CloudError::NotFound(_))
+        ));
+
+        put(&bucket, &url, b"challenge 1");
+        let path = bucket.download(&url).unwrap();
+        assert_eq!(fs_err::read(&path).unwrap(), b"challenge 1");
+
+        // A staged file is not downloaded again while the object is unchanged
+        fs_err::write(&path, b"challenge 2").unwrap();
+        bucket.download(&url).unwrap();
+        assert_eq!(fs_err::read(&path).unwrap(), b"challenge 2");
+
+        // but is once the object is replaced, even by one of the same size
+        put(&bucket, &url, b"challenge 3");
+        bucket.download(&url).unwrap();
+        assert_eq!(fs_err::read(&path).unwrap(), b"challenge 3");
+
+        // or once it was forgotten, as it is transformed in place
+        fs_err::write(&path, b"challenge 4").unwrap();
+        bucket.forget_downloads(&path).unwrap();
+        bucket.download(&url).unwrap();
+        assert_eq!(fs_err::read(&path).unwrap(), b"challenge 3");
+
+        fs_err::remove_dir_all(&bucket.staging).unwrap();
+    }
+
+    #[test]
+    fn test_transcript_round_trip() {
+        let bucket = in_memory("cloud-transcript");
+        let challenge = ObjectUrl::parse("gs://ceremony/round-1/challenge").unwrap();
+        for (name, contents) in &[
+            ("challenge", &b"challenge"[..]),
+            ("challenge.0", b"chunk 0"),
+            ("challenge.0.hash", b"hash 0"),
+            ("challenge.0.verified", b"receipt 0"),
+            ("ceremony.json", b"{}"),
+            ("other", b"other"),
+        ] {
+            put(&bucket, &challenge.sibling(name), contents);
+        }
+        let path = bucket.download_transcript(&challenge).unwrap();
+        let dir = path.parent().unwrap();
+        assert_eq!(fs_err::read(dir.join("challenge.0")).unwrap(), b"chunk 0");
+        assert!(dir.join("ceremony.json").exists());
+        assert!(!dir.join("other").exists());
+
+        // The files are uploaded under the name of the transcript they are uploaded as, but the records are not
+        let response = challenge.sibling("response");
+        bucket
+            .upload_transcript(&path, &response, |name| name.ends_with(".verified"))
+            .unwrap();
+        assert_eq!(get(&bucket, &response.sibling("response.0")), b"chunk 0");
+        assert_eq!(get(&bucket, &response.sibling("response.0.hash")), b"hash 0");
+        let location = ObjectPath::from("round-1/response.0.verified");
+        assert!(bucket.runtime.block_on(bucket.store.head(&location)).is_err());
+        let location = ObjectPath::from("round-1/response.0.staged");
+        assert!(bucket.runtime.block_on(bucket.store.head(&location)).is_err());
+
+        fs_err::remove_dir_all(&bucket.staging).unwrap();
+    }
+
+    #[test]
+    fn test_contributed_challenge_is_only_uploaded() {
+        let bucket = in_memory("cloud-contributed");
+        let challenge = ObjectUrl::parse("s3://ceremony/round-1/challenge").unwrap();
+        let response = challenge.sibling(DEFAULT_OUTPUT);
+        let opts = ContributeOpts::parse_args_default::<&str>(&[]).unwrap();
+
+        // A contribution whose upload failed is neither downloaded nor contributed to again,
+        // which would fail as there is no challenge in the bucket and the files are no parameters
+        let staged = bucket.staged_path(&challenge);
+        permissions::create_dir_all(staged.parent().unwrap()).unwrap();
+        fs_err::write(&staged, b"contribution").unwrap();
+        fs_err::write(chunk_path(&staged, 0), b"chunk 0").unwrap();
+        fs_err::write(with_suffix(&staged, CONTRIBUTED_SUFFIX), response.to_string()).unwrap();
+        contribute_staged(&bucket, &challenge, &response, &opts, &mut thread_rng()).unwrap();
+        assert_eq!(get(&bucket, &response), b"contribution");
+        assert_eq!(get(&bucket, &response.sibling("response.0")), b"chunk 0");
+        assert!(!staged.exists());
+        assert!(!with_suffix(&staged, CONTRIBUTED_SUFFIX).exists());
+
+        fs_err::remove_dir_all(&bucket.staging).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+use super::{
+    ceremony::{CeremonyConfig, CeremonyManifest, HashAlgorithm, CEREMONY_FORMAT_VERSION, HASH_ALGORITHM},
+    new::{circuit_digest, CurveKind, CURVES},
+};
+
+use gumdrop::Options;
+use std::{fmt, path::Path};
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Debug, Options, Clone)]
+pub struct CompatCheckOpts {
+    help: bool,
+    #[options(help = "the ceremony directory to check this binary against", default = ".")]
+    pub dir: String,
+    #[options(help = "skip recomputing the circuit digest, which is slow for the outer circuit")]
+    pub skip_circuit: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+/// A single setting in which the ceremony and the running binary disagree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub field: &'static str,
+    pub ceremony: String,
+    pub binary: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: ceremony has {}, binary has {}",
+            self.field, self.ceremony, self.binary
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("this binary cannot continue the ceremony: {}", join(.0))]
+pub struct IncompatibleError(pub Vec<Mismatch>);
+
+fn join(mismatches: &[Mismatch]) -> String {
+    mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>().join("; ")
+}
+
+/// The settings of the running binary which a ceremony depends on.
+#[derive(Debug, Clone)]
+pub struct BinarySettings {
+    pub format_version: u32,
+    pub hash_algorithm: HashAlgorithm,
+    pub curves: Vec<CurveKind>,
+    pub circuit_digest: Option<String>,
+}
+
+/// Returns every setting which would break continuation of the ceremony described by `config`,
+/// recorded at manifest version `version`, with the given binary.
+pub fn compare(version: u32, config: &CeremonyConfig, binary: &BinarySettings) -> Vec<Mismatch> {
+    let mut mismatches = vec![];
+    let mut check = |field, ceremony: String, binary: String| {
+        if ceremony != binary {
+            mismatches.push(Mismatch {
+                field,
+                ceremony,
+                binary,
+            });
+        }
+    };
+
+    check("format version", version.to_string(), binary.format_version.to_string());
+    check(
+        "hash algorithm",
+        format!("{:?}", config.hash_algorithm),
+        format!("{:?}", binary.hash_algorithm),
+    );
+    if !binary.curves.contains(&config.curve) {
+        check("curve", format!("{:?}", config.curve), format!("{:?}", binary.curves));
+    }
+    if let (Some(ceremony), Some(binary)) = (&config.circuit_digest, &binary.circuit_digest) {
+        check("circuit digest", ceremony.clone(), binary.clone());
+    }
+
+    mismatches
+}
+
+pub fn compat_check(opts: &CompatCheckOpts) -> anyhow::Result<()> {
+    let dir = Path::new(&opts.dir);
+
+    // Read the manifest leniently, so that outdated ceremonies are reported rather than rejected
+    let version = CeremonyManifest::version_in(dir)?;
+    let manifest: CeremonyManifest = match CeremonyManifest::load(dir) {
+        Ok(manifest) => manifest,
+        Err(_) if version != CEREMONY_FORMAT_VERSION => {
+            return Err(IncompatibleError(vec![Mismatch {
+                field: "format version",
+                ceremony: version.to_string(),
+                binary: CEREMONY_FORMAT_VERSION.to_string(),
+            }])
+            .into());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let config = &manifest.config;
+
+    let circuit_digest = match (&config.circuit_digest, opts.skip_circuit) {
+        (None, _) => {
+            warn!("the ceremony does not record a circuit digest, the circuit cannot be checked");
+            None
+        }
+        (Some(_), true) => None,
+        (Some(_), false) => Some(circuit_digest(config.is_inner)?),
+    };
+    let binary = BinarySettings {
+        format_version: CEREMONY_FORMAT_VERSION,
+        hash_algorithm: HASH_ALGORITHM,
+        curves: CURVES.to_vec(),
+        circuit_digest,
+    };
+
+    let mismatches = compare(manifest.version, config, &binary);
+    if !mismatches.is_empty() {
+        return Err(IncompatibleError(mismatches).into());
+    }
+
+    info!("this binary is compatible with the ceremony in {}", opts.dir);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use setup_utils::UseCompression;
+
+    #[test]
+    fn test_compare() {
+        let mut config = CeremonyConfig {
+            curve: CurveKind::Bls12_377,
+            is_inner: true,
+            phase1_size: 19,
+            compression: UseCompression::No,
+            hash_algorithm: HashAlgorithm::Blake2b,
+            transcript: "initial_ceremony".to_string(),
+            circuit_digest: Some("aa".to_string()),
+            vdf_iterations: None,
+        };
+        let mut binary = BinarySettings {
+            format_version: CEREMONY_FORMAT_VERSION,
+            hash_algorithm: HashAlgorithm::Blake2b,
+            curves: CURVES.to_vec(),
+            circuit_digest: Some("aa".to_string()),
+        };
+        assert!(compare(CEREMONY_FORMAT_VERSION, &config, &binary).is_empty());
+
+        // a ceremony over either curve can be continued by a binary with both
+        config.curve = CurveKind::BW6;
+        assert!(compare(CEREMONY_FORMAT_VERSION, &config, &binary).is_empty());
+
+        binary.curves = vec![CurveKind::Bls12_377];
+        binary.circuit_digest = Some("bb".to_string());
+        let mismatches = compare(CEREMONY_FORMAT_VERSION, &config, &binary);
+        let fields = mismatches.iter().map(|m| m.field).collect::<Vec<_>>();
+        assert_eq!(fields, vec!["curve", "circuit digest"]);
+
+        // an unknown digest cannot be compared
+        binary.circuit_digest = None;
+        assert_eq!(compare(CEREMONY_FORMAT_VERSION, &config, &binary).len(), 1);
+    }
+}
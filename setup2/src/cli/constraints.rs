@@ -0,0 +1,164 @@
+//! The size of the circuits of the ceremony, so that coordinators know the number of
+//! constraints and the powers the phase 1 transcript must have before running `new`.
+use super::{
+    new::{
+        circuit_from_str,
+        curve_from_str,
+        inner_circuit,
+        outer_circuit,
+        AleoInner,
+        AleoOuter,
+        CircuitKind,
+        CurveKind,
+    },
+    r1cs::R1csCircuit,
+};
+use setup_utils::log_2;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+use snarkvm_fields::Field;
+use snarkvm_r1cs::{ConstraintCounter, ConstraintSynthesizer, SynthesisError};
+
+use gumdrop::Options;
+use serde::Serialize;
+use std::{fmt, path::Path};
+
+#[derive(Debug, Options, Clone)]
+pub struct ConstraintsOpts {
+    help: bool,
+    #[options(
+        help = "the circuit to count the constraints of, `inner` or `outer`",
+        default = "outer",
+        parse(try_from_str = "circuit_from_str")
+    )]
+    pub circuit: CircuitKind,
+    #[options(help = "count the constraints of the R1CS circuit in this JSON file over the curve of --curve-type instead")]
+    pub circuit_file: Option<String>,
+    #[options(
+        help = "the elliptic curve of the circuit file",
+        default = "bls12_377",
+        parse(try_from_str = "curve_from_str")
+    )]
+    pub curve_type: CurveKind,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+/// The size of a circuit, and of the ceremony for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CircuitSize {
+    pub num_public_variables: usize,
+    pub num_private_variables: usize,
+    pub num_constraints: usize,
+    /// The number of powers `new` reads from the phase 1 transcript.
+    pub phase2_size: usize,
+    /// The minimum `--phase1-size` of the phase 1 transcript, in powers of 2.
+    pub min_phase1_size: u32,
+}
+
+impl CircuitSize {
+    /// Synthesizes the circuit and returns its size.
+    pub fn of<F: Field, C: Clone + ConstraintSynthesizer<F>>(circuit: &C) -> Result<Self, SynthesisError> {
+        let mut counter = ConstraintCounter {
+            num_public_variables: 0,
+            num_private_variables: 0,
+            num_constraints: 0,
+        };
+        circuit.clone().generate_constraints(&mut counter)?;
+        let phase2_size = std::cmp::max(
+            counter.num_constraints,
+            counter.num_private_variables + counter.num_public_variables + 1,
+        );
+        let power = log_2(phase2_size) as u32;
+
+        // get the nearest power of 2
+        let phase2_size = if phase2_size < 2usize.pow(power) {
+            2usize.pow(power + 1)
+        } else {
+            phase2_size
+        };
+        Ok(Self {
+            num_public_variables: counter.num_public_variables,
+            num_private_variables: counter.num_private_variables,
+            num_constraints: counter.num_constraints,
+            phase2_size,
+            min_phase1_size: phase2_size.next_power_of_two().trailing_zeros(),
+        })
+    }
+}
+
+impl fmt::Display for CircuitSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "public variables:  {}", self.num_public_variables)?;
+        writeln!(f, "private variables: {}", self.num_private_variables)?;
+        writeln!(f, "constraints:       {}", self.num_constraints)?;
+        writeln!(f, "phase 2 size:      {}", self.phase2_size)?;
+        write!(f, "min phase 1 size:  2^{}", self.min_phase1_size)
+    }
+}
+
+/// Returns the number of powers required for the Phase 2 ceremony
+/// = log2(aux + inputs + constraints)
+pub(super) fn ceremony_size<F: Field, C: Clone + ConstraintSynthesizer<F>>(
+    circuit: &C,
+) -> Result<usize, SynthesisError> {
+    Ok(CircuitSize::of(circuit)?.phase2_size)
+}
+
+fn circuit_file_size<E: PairingEngine>(path: &Path) -> anyhow::Result<CircuitSize> {
+    Ok(CircuitSize::of(&R1csCircuit::<E::Fr>::load(path)?)?)
+}
+
+pub fn constraints(opts: &ConstraintsOpts) -> anyhow::Result<()> {
+    let size = match (&opts.circuit_file, opts.curve_type, opts.circuit) {
+        (Some(path), CurveKind::Bls12_377, _) => circuit_file_size::<Bls12_377>(Path::new(path))?,
+        (Some(path), CurveKind::BW6, _) => circuit_file_size::<BW6_761>(Path::new(path))?,
+        (None, _, CircuitKind::Inner) => CircuitSize::of::<<AleoInner as PairingEngine>::Fr, _>(&inner_circuit())?,
+        (None, _, CircuitKind::Outer) => CircuitSize::of::<<AleoOuter as PairingEngine>::Fr, _>(&outer_circuit()?)?,
+    };
+    if opts.json {
+        println!("{}", serde_json::to_string(&size)?);
+    } else {
+        println!("{}", size);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cli::r1cs::{R1csConstraint, R1csFile, R1CS_FORMAT_VERSION};
+    use snarkvm_utilities::ToBytes;
+
+    type Fr = <Bls12_377 as PairingEngine>::Fr;
+
+    #[test]
+    fn test_circuit_size() {
+        // x * x = x^2 and x^2 * x = x^3, with x^3 public
+        let one = || hex::encode(Fr::from(1u64).to_bytes_le().unwrap());
+        let file = R1csFile {
+            version: R1CS_FORMAT_VERSION,
+            num_public: 1,
+            num_private: 2,
+            constraints: vec![
+                R1csConstraint {
+                    a: vec![(2, one())],
+                    b: vec![(2, one())],
+                    c: vec![(3, one())],
+                },
+                R1csConstraint {
+                    a: vec![(3, one())],
+                    b: vec![(2, one())],
+                    c: vec![(1, one())],
+                },
+            ],
+        };
+        let size = CircuitSize::of(&R1csCircuit::<Fr>::from_file(&file).unwrap()).unwrap();
+        assert_eq!(size.num_constraints, 2);
+        assert_eq!(size.num_private_variables, 2);
+        assert_eq!(size.phase2_size, std::cmp::max(2, size.num_public_variables + 3));
+        assert_eq!(1 << size.min_phase1_size, size.phase2_size.next_power_of_two());
+    }
+}
@@ -1,16 +1,40 @@
-use phase2::{chunked_groth16::contribute as chunked_contribute, keypair::PublicKey};
-use setup_utils::Result;
+use super::{
+    attestation::attest,
+    ceremony::check_phase1,
+    chunks::ChunkError,
+    input_format::{check_from_str, detect_chunks, input_format_from_str, InputFormat},
+    keystore::read_private_key,
+    memory::{cap_batch_size, contribute_bytes_per_element},
+    new::{file_hash, write_hash},
+    paths::{artifact_path, chunk_path, contribution_seed_path, contribution_state_path, tmp_path},
+    permissions,
+    progress::{file_points, Progress, ProgressMode},
+};
+use phase2::{
+    chunked_groth16::{contribute_resumable, ContributionCheckpoint},
+    keypair::PublicKey,
+};
+use setup_utils::{CancellationToken, CheckForCorrectness};
 
 use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
 
 use fs_err::OpenOptions;
 use gumdrop::Options;
-use memmap::MmapOptions;
-use rand::{CryptoRng, Rng};
+use memmap::{MmapMut, MmapOptions};
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::info;
 
 #[derive(Debug, Options, Clone)]
 pub struct ContributeOpts {
-    help: bool,
+    pub(super) help: bool,
     #[options(
         help = "the previous contribution - the action will happen in place",
         default = "challenge"
@@ -18,40 +42,430 @@ pub struct ContributeOpts {
     pub data: String,
     #[options(help = "the batches which can be loaded in memory", default = "50000")]
     pub batch: usize,
+    #[options(help = "the maximum memory (in GB) to use, which reduces the batch size if needed")]
+    pub max_memory_gb: Option<f64>,
     #[options(
         help = "the beacon hash to be used if running a beacon contribution",
         default = "0000000000000000000a558a61ddc8ee4e488d647a747fe4dcc362fe2026c620"
     )]
     pub beacon_hash: String,
+    #[options(help = "a beacon-vdf proof on the beacon hash, whose output is used instead of iterated SHA256")]
+    pub vdf_proof: Option<String>,
+    #[options(help = "a file listing beacon sources by preference, the first with a value gives the beacon hash")]
+    pub beacon_sources: Option<String>,
+    #[options(
+        help = "the file to record the beacon sources and the selected one to",
+        default = "beacon.json"
+    )]
+    pub beacon_record: String,
 
     #[options(help = "setup the inner or the outer circuit?")]
     pub is_inner: bool,
+
+    #[options(help = "contribute to this many chunks of the previous contribution instead of the full parameters")]
+    pub chunks: Option<usize>,
+    #[options(
+        help = "whether the previous contribution is `full` or `chunked`, detected from the chunks next to it if `auto`",
+        default = "auto",
+        parse(try_from_str = "input_format_from_str")
+    )]
+    pub input_format: InputFormat,
+    #[options(
+        help = "how the elements the contribution multiplies are checked as they are read, `full`, `non-zero`, `in-group` or `no`",
+        default = "no",
+        parse(try_from_str = "check_from_str")
+    )]
+    pub check_input: CheckForCorrectness,
+
+    #[cfg(feature = "cloud")]
+    #[options(
+        help = "upload the contribution to this object URL, by default `response` next to --data, which must be an object URL"
+    )]
+    pub output: Option<String>,
+
+    #[options(help = "sign an attestation of the contribution with the Aleo private key in this keystore")]
+    pub attestation_key: Option<String>,
+
+    #[options(help = "do not show the progress bar")]
+    pub quiet: bool,
+    #[options(help = "write the progress to stderr as a JSON object per line")]
+    pub progress_json: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum ResumeError {
+    #[error(
+        "{} holds an interrupted contribution to other files than {}, restore them and delete it to start over",
+        .0.display(),
+        .1.display()
+    )]
+    Mismatch(PathBuf, PathBuf),
+}
+
+/// The progress of a contribution, which `contribute` saves next to the previous contribution
+/// while it transforms it, so that a contribution which was interrupted, e.g. as the laptop it
+/// ran on was closed, resumes where it stopped when it is run again. The seed of the randomness
+/// of the contribution is saved once in a file of its own, readable by its owner only, rather
+/// than with every checkpoint, and both are deleted as soon as the contribution is complete.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ContributionState {
+    /// The seed of the RNG each file is contributed to with, which is saved apart from the progress.
+    #[serde(skip)]
+    seed: [u8; 32],
+    /// The batch size, which must not change when resuming.
+    batch: usize,
+    /// The number of files of the contribution.
+    files: usize,
+    /// The number of files which were contributed to, in order.
+    files_done: usize,
+    /// The length of the file being contributed to, before it was extended by 1 pubkey.
+    len: Option<u64>,
+    /// The last checkpoint of the file being contributed to.
+    checkpoint: Option<CheckpointState>,
+}
+
+/// A `ContributionCheckpoint`, with its serialized elements hex encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CheckpointState {
+    delta_g1: String,
+    delta_g2: String,
+    contributions: usize,
+    h_done: usize,
+    l_done: usize,
+    pending: String,
+}
+
+impl From<&ContributionCheckpoint> for CheckpointState {
+    fn from(checkpoint: &ContributionCheckpoint) -> Self {
+        Self {
+            delta_g1: hex::encode(&checkpoint.delta_g1),
+            delta_g2: hex::encode(&checkpoint.delta_g2),
+            contributions: checkpoint.contributions,
+            h_done: checkpoint.h_done,
+            l_done: checkpoint.l_done,
+            pending: hex::encode(&checkpoint.pending),
+        }
+    }
+}
+
+impl CheckpointState {
+    fn decode(&self) -> Result<ContributionCheckpoint, hex::FromHexError> {
+        Ok(ContributionCheckpoint {
+            delta_g1: hex::decode(&self.delta_g1)?,
+            delta_g2: hex::decode(&self.delta_g2)?,
+            contributions: self.contributions,
+            h_done: self.h_done,
+            l_done: self.l_done,
+            pending: hex::decode(&self.pending)?,
+        })
+    }
+}
+
+impl ContributionState {
+    /// Loads the state of the interrupted contribution to the `files` files of `data`,
+    /// or starts a new one with a seed sampled from `rng`, which is saved at once.
+    fn load_or_new<R: Rng + CryptoRng>(data: &Path, files: usize, batch: usize, rng: &mut R) -> anyhow::Result<Self> {
+        let path = contribution_state_path(data);
+        let seed_path = contribution_seed_path(data);
+        if !path.exists() {
+            // A seed left by a run which was interrupted before its first checkpoint is overwritten,
+            // as nothing was contributed with it
+            let seed = rng.gen::<[u8; 32]>();
+            permissions::write_secret(&seed_path, hex::encode(seed))?;
+            permissions::sync_parent(&seed_path)?;
+            return Ok(Self {
+                seed,
+                batch,
+                files,
+                files_done: 0,
+                len: None,
+                checkpoint: None,
+            });
+        }
+        let mut state: Self = serde_json::from_slice(&fs_err::read(&path)?)?;
+        if state.files != files {
+            return Err(ResumeError::Mismatch(path, data.to_path_buf()).into());
+        }
+        hex::decode_to_slice(fs_err::read_to_string(&seed_path)?.trim(), &mut state.seed)?;
+        info!("resuming the contribution saved in {}", path.display());
+        Ok(state)
+    }
+
+    fn rng(&self) -> ChaChaRng {
+        ChaChaRng::from_seed(self.seed)
+    }
+
+    /// Saves the state to `path`, replacing it at once so that it is never left truncated, and
+    /// only returns once it is on disk, as the batch it records is multiplied next.
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let tmp = tmp_path(path);
+        let mut file = permissions::create(&tmp)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.sync_all()?;
+        fs_err::rename(tmp, path)?;
+        permissions::sync_parent(path)
+    }
+
+    /// Deletes the state and the seed of the complete contribution to `data`. The seed is
+    /// overwritten before it is removed, so that it cannot be read back from the blocks it
+    /// was in on filesystems which write files in place, which copy-on-write filesystems
+    /// and the remapped blocks of SSDs do not guarantee.
+    fn delete(data: &Path) -> std::io::Result<()> {
+        let seed_path = contribution_seed_path(data);
+        let mut seed = OpenOptions::new().write(true).open(&seed_path)?;
+        let len = seed.metadata()?.len() as usize;
+        seed.write_all(&vec![0; len])?;
+        seed.sync_all()?;
+        fs_err::remove_file(&seed_path)?;
+        fs_err::remove_file(contribution_state_path(data))?;
+        permissions::sync_parent(&seed_path)
+    }
 }
 
-pub fn contribute<R: Rng + CryptoRng>(opts: &ContributeOpts, rng: &mut R) -> Result<()> {
-    let file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(&opts.data)
-        .expect("could not open file for writing the new MPC parameters ");
-    let metadata = file.metadata()?;
-    // extend the file by 1 pubkey
-    if opts.is_inner {
-        file.set_len(metadata.len() + PublicKey::<Bls12_377>::size() as u64)?;
+pub fn contribute<R: Rng + CryptoRng>(opts: &ContributeOpts, rng: &mut R) -> anyhow::Result<()> {
+    #[cfg(feature = "cloud")]
+    if super::cloud::is_object_url(&opts.data) || opts.output.is_some() {
+        return super::cloud::contribute_in_bucket(opts, rng);
+    }
+    // The transcript itself is memory mapped, so only the batches count towards the cap
+    let bytes_per_element = if opts.is_inner {
+        contribute_bytes_per_element::<Bls12_377>()
     } else {
-        file.set_len(metadata.len() + PublicKey::<BW6_761>::size() as u64)?;
+        contribute_bytes_per_element::<BW6_761>()
+    };
+    let batch = cap_batch_size(opts.batch, opts.max_memory_gb, bytes_per_element)?;
+    let data = artifact_path(&opts.data)?;
+    let mode = ProgressMode::new(opts.quiet, opts.progress_json);
+    let attestation_key = opts.attestation_key.as_ref().map(read_private_key).transpose()?;
+
+    let chunks = match opts.chunks {
+        Some(0) => return Err(ChunkError::ZeroChunks.into()),
+        Some(chunks) => Some(chunks),
+        None => detect_chunks(&data, opts.input_format)?,
+    };
+    let paths: Vec<_> = match chunks {
+        Some(chunks) => (0..chunks).map(|i| chunk_path(&data, i)).collect(),
+        None => vec![data.clone()],
+    };
+    // The files must have been generated for the ceremony next to them, and not from another phase 1 transcript
+    check_phase1(data.parent().unwrap_or_else(|| Path::new("")), &paths, opts.is_inner)?;
+
+    // Every chunk must be multiplied by the same delta, so the keypair of each
+    // of them is sampled from an identically seeded RNG. The seed is saved next
+    // to the progress, so that an interrupted contribution can be resumed
+    let state_path = contribution_state_path(&data);
+    let mut state = ContributionState::load_or_new(&data, paths.len(), batch, rng)?;
+    let points = paths
+        .iter()
+        .map(|path| file_points(path, opts.is_inner))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let progress = Progress::start("contribute", mode, paths.len(), points.iter().sum());
+    for (i, path) in paths.iter().enumerate() {
+        if i >= state.files_done {
+            // A checkpoint follows every batch, and the first one tells what an earlier run multiplied
+            let mut reported = None;
+            let report = |checkpoint: &ContributionCheckpoint| {
+                let done = (checkpoint.h_done + checkpoint.l_done) as u64;
+                match reported.replace(done) {
+                    Some(before) => progress.batch_done(done - before),
+                    None => progress.batch_skipped(done),
+                }
+                Ok(())
+            };
+            contribute_file(path, opts.is_inner, opts.check_input, &mut state, &state_path, report)?;
+            // The chunk changed in place, so the hash `new` wrote next to it is replaced by the one of the contribution
+            if chunks.is_some() {
+                write_hash(path, &file_hash(path)?)?;
+            }
+            if let Some(key) = &attestation_key {
+                attest(path, chunks.map(|_| i), opts.is_inner, key.expose_secret())?;
+            }
+            state.files_done += 1;
+            state.len = None;
+            state.checkpoint = None;
+            state.save(&state_path)?;
+        }
+        progress.chunk_done(i, points[i]);
     }
-    let mut file = unsafe {
-        MmapOptions::new()
-            .map_mut(file.file())
-            .expect("unable to create a memory map for input")
+    progress.finish();
+    ContributionState::delete(&data)?;
+    Ok(())
+}
+
+/// Contributes to the parameters in the file at `path`, in place, from the checkpoint
+/// in `state` if the contribution to it was interrupted, and saves a checkpoint to
+/// `state_path` before every batch, which is passed to `on_checkpoint` once it is on
+/// disk. An error from `on_checkpoint` stops the contribution, which can be resumed
+/// from that checkpoint. The elements are checked with `check` as they are read.
+fn contribute_file(
+    path: &Path,
+    is_inner: bool,
+    check: CheckForCorrectness,
+    state: &mut ContributionState,
+    state_path: &Path,
+    mut on_checkpoint: impl FnMut(&ContributionCheckpoint) -> setup_utils::Result<()>,
+) -> anyhow::Result<()> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+    let pubkey_size = if is_inner {
+        PublicKey::<Bls12_377>::size()
+    } else {
+        PublicKey::<BW6_761>::size()
+    } as u64;
+    // extend the file by 1 pubkey, only once however often the contribution is resumed
+    let len = match state.len {
+        Some(before) if len == before || len == before + pubkey_size => before,
+        Some(_) => return Err(ResumeError::Mismatch(state_path.to_path_buf(), path.to_path_buf()).into()),
+        None => {
+            state.len = Some(len);
+            state.save(state_path)?;
+            len
+        }
     };
+    file.set_len(len + pubkey_size)?;
+    let mut file = unsafe { MmapOptions::new().map_mut(file.file())? };
 
-    if opts.is_inner {
-        chunked_contribute::<Bls12_377, _>(&mut file, rng, opts.batch)?;
+    let rng = &mut state.rng();
+    let resume = state.checkpoint.as_ref().map(CheckpointState::decode).transpose()?;
+    let batch = state.batch;
+    // the batches must be on disk before the checkpoint which skips them
+    let save = |file: &MmapMut, checkpoint: &ContributionCheckpoint| -> setup_utils::Result<()> {
+        file.flush()?;
+        state.checkpoint = Some(checkpoint.into());
+        state.save(state_path)?;
+        on_checkpoint(checkpoint)
+    };
+    let cancellation = CancellationToken::new();
+    if is_inner {
+        contribute_resumable::<Bls12_377, _, _, _>(&mut file, rng, batch, check, &cancellation, resume, save)?;
     } else {
-        chunked_contribute::<BW6_761, _>(&mut file, rng, opts.batch)?;
+        contribute_resumable::<BW6_761, _, _, _>(&mut file, rng, batch, check, &cancellation, resume, save)?;
     }
+    file.flush()?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use phase1::{helpers::testing::setup_verify, Phase1, Phase1Parameters, ProvingSystem};
+    use phase2::{
+        helpers::testing::TestCircuit,
+        parameters::{circuit_to_qap, MPCParameters},
+    };
+    use setup_utils::{Groth16Params, UseCompression};
+
+    use rand::thread_rng;
+
+    #[test]
+    fn test_contribution_state() {
+        let dir = std::env::temp_dir().join(format!("setup2-contribution-state-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+        fs_err::create_dir_all(&dir).unwrap();
+        let data = dir.join("challenge");
+        let rng = &mut thread_rng();
+
+        let mut state = ContributionState::load_or_new(&data, 2, 10, rng).unwrap();
+        assert_eq!((state.files_done, state.checkpoint.as_ref()), (0, None));
+        let checkpoint = ContributionCheckpoint {
+            delta_g1: vec![1, 2],
+            delta_g2: vec![3],
+            contributions: 1,
+            h_done: 10,
+            l_done: 5,
+            pending: vec![4; 8],
+        };
+        state.checkpoint = Some((&checkpoint).into());
+        state.save(&contribution_state_path(&data)).unwrap();
+
+        // The saved state is resumed with its seed and batch size
+        let resumed = ContributionState::load_or_new(&data, 2, 20, rng).unwrap();
+        assert_eq!(resumed, state);
+        assert_eq!(resumed.checkpoint.as_ref().unwrap().decode().unwrap(), checkpoint);
+        assert_eq!(resumed.rng().gen::<[u8; 32]>(), state.rng().gen::<[u8; 32]>());
+
+        // but not by a contribution to another number of files
+        let err = ContributionState::load_or_new(&data, 1, 10, rng).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ResumeError>(), Some(ResumeError::Mismatch(..))));
+
+        // Once the contribution is complete, neither the progress nor the seed is left
+        ContributionState::delete(&data).unwrap();
+        assert!(!contribution_state_path(&data).exists());
+        assert!(!contribution_seed_path(&data).exists());
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+
+    /// Writes the parameters of a small test circuit to `path`, as `new` writes those of the inner circuit.
+    fn write_test_parameters(path: &Path) {
+        let params = Phase1Parameters::<Bls12_377>::new_full(ProvingSystem::Groth16, 5, 16);
+        let compressed = UseCompression::No;
+        let (_, output, _, _) = setup_verify(compressed, CheckForCorrectness::Full, compressed, &params);
+        let accumulator = Phase1::deserialize(&output, compressed, CheckForCorrectness::Full, &params).unwrap();
+        let groth_params = Groth16Params::<Bls12_377>::new(
+            8,
+            accumulator.tau_powers_g1,
+            accumulator.tau_powers_g2,
+            accumulator.alpha_tau_powers_g1,
+            accumulator.beta_tau_powers_g1,
+            accumulator.beta_g2,
+        )
+        .unwrap();
+        let assembly = circuit_to_qap::<Bls12_377, Bls12_377, _>(TestCircuit::<Bls12_377>(None)).unwrap();
+        let mut serialized = vec![];
+        MPCParameters::new(assembly, groth_params)
+            .unwrap()
+            .write(&mut serialized)
+            .unwrap();
+        fs_err::write(path, serialized).unwrap();
+    }
+
+    #[test]
+    fn test_interrupted_contribution() {
+        let dir = std::env::temp_dir().join(format!("setup2-interrupted-contribution-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+        fs_err::create_dir_all(&dir).unwrap();
+        let (expected, interrupted) = (dir.join("expected"), dir.join("interrupted"));
+        write_test_parameters(&expected);
+        fs_err::copy(&expected, &interrupted).unwrap();
+        let check = CheckForCorrectness::Full;
+        let seed = [7u8; 32];
+
+        let mut state = ContributionState::load_or_new(&expected, 1, 1, &mut ChaChaRng::from_seed(seed)).unwrap();
+        let state_path = contribution_state_path(&expected);
+        contribute_file(&expected, true, check, &mut state, &state_path, |_| Ok(())).unwrap();
+
+        // A contribution stopped after its third checkpoint, as if the process was killed then
+        let mut state = ContributionState::load_or_new(&interrupted, 1, 1, &mut ChaChaRng::from_seed(seed)).unwrap();
+        let state_path = contribution_state_path(&interrupted);
+        let mut checkpoints = 0;
+        let err = contribute_file(&interrupted, true, check, &mut state, &state_path, |_| {
+            checkpoints += 1;
+            match checkpoints {
+                3 => Err(setup_utils::Error::Cancelled),
+                _ => Ok(()),
+            }
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<setup_utils::Error>(),
+            Some(setup_utils::Error::Cancelled)
+        ));
+        assert_ne!(fs_err::read(&interrupted).unwrap(), fs_err::read(&expected).unwrap());
+
+        // is resumed by another run from the saved progress and seed, whatever its own randomness,
+        // and gives the same contribution as one which was not interrupted
+        let mut resumed = ContributionState::load_or_new(&interrupted, 1, 1, &mut thread_rng()).unwrap();
+        assert_eq!(resumed, state);
+        assert!(resumed.checkpoint.is_some());
+        contribute_file(&interrupted, true, check, &mut resumed, &state_path, |_| Ok(())).unwrap();
+        assert_eq!(fs_err::read(&interrupted).unwrap(), fs_err::read(&expected).unwrap());
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+//! Contributes to every chunk the coordinator hands out, one after another, until the
+//! participant has contributed to all the chunks of the current round.
+//!
+//! The coordinator runs a chunked phase 1 ceremony, so the chunks are contributed to and
+//! uploaded like the contributor does, rather than like the phase 2 chunks of `contribute`.
+use super::{keystore::read_private_key, permissions};
+use phase1::helpers::CurveKind;
+use phase1_coordinator::{
+    authentication::AleoSignature,
+    environment::Environment,
+    objects::{ContributionFileSignature, ContributionState},
+    phase1_chunked_parameters,
+};
+use setup1_shared::{
+    ids::{ChunkId, ContributionId},
+    protocol,
+    structures::{ContributorStatus, LockResponse},
+};
+use setup2::coordinator_client::{public_settings, CoordinatorClient, CoordinatorClientError};
+use setup_utils::calculate_hash;
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, PrivateKey, ViewKey};
+
+use gumdrop::Options;
+use rand::{CryptoRng, Rng};
+use secrecy::ExposeSecret;
+use std::{
+    convert::TryFrom,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use thiserror::Error;
+use url::Url;
+
+/// The delay between heartbeats, well within the lease of a lock.
+const HEARTBEAT_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum ContributeLoopError {
+    #[error("The participant key is not a valid Aleo private key")]
+    InvalidParticipantKey,
+    #[error("The participant is neither queued for nor contributing to the current round")]
+    NotParticipating,
+    #[error("The contribution to chunk {0} failed")]
+    ContributionFailed(u64),
+}
+
+#[derive(Debug, Options, Clone)]
+pub struct ContributeLoopOpts {
+    help: bool,
+    #[options(required, help = "the URL of the coordinator")]
+    pub api_url: String,
+    #[options(required, help = "the keystore holding the Aleo private key of the participant")]
+    pub participant_key: String,
+    #[options(
+        help = "the directory the challenges are downloaded to and contributed to in",
+        default = "."
+    )]
+    pub dir: String,
+    #[options(help = "the seconds to wait before asking the coordinator again", default = "10")]
+    pub poll_seconds: u64,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+pub fn contribute_loop<R: Rng + CryptoRng>(opts: &ContributeLoopOpts, rng: &mut R) -> anyhow::Result<()> {
+    let server_url = Url::parse(&opts.api_url)?;
+    let participant_key = read_private_key(&opts.participant_key)?;
+    let private_key = PrivateKey::<Testnet2Parameters>::from_str(participant_key.expose_secret())
+        .map_err(|_| ContributeLoopError::InvalidParticipantKey)?;
+    let view_key = ViewKey::try_from(&private_key)?.to_string();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    runtime.block_on(async {
+        // The setup the coordinator runs decides the parameters of its chunks
+        let settings = public_settings(&server_url).await?;
+        let environment = Environment::from(&settings.setup);
+        let protocol_version = protocol::negotiate(&settings.protocol_versions)?;
+        let client = Arc::new(CoordinatorClient::new(server_url, private_key, protocol_version)?);
+
+        let heartbeat = tokio::spawn(heartbeat(client.clone()));
+        let participant = Participant {
+            client: &client,
+            environment: &environment,
+            view_key: &view_key,
+        };
+        let result = contribute_until_finished(&participant, opts, rng).await;
+        heartbeat.abort();
+        result
+    })
+}
+
+/// The participant the chunks are contributed for, in the environment of the coordinator.
+struct Participant<'a> {
+    client: &'a CoordinatorClient,
+    environment: &'a Environment,
+    view_key: &'a str,
+}
+
+/// Keeps the locks of the participant, since contributing to a chunk can take longer than a lease.
+async fn heartbeat(client: Arc<CoordinatorClient>) {
+    let rng = &mut rand::rngs::OsRng;
+    loop {
+        if let Err(error) = client.heartbeat(rng).await {
+            tracing::warn!("Could not send a heartbeat to the coordinator - {}", error);
+        }
+        tokio::time::sleep(HEARTBEAT_DELAY).await;
+    }
+}
+
+async fn contribute_until_finished<R: Rng + CryptoRng>(
+    participant: &Participant<'_>,
+    opts: &ContributeLoopOpts,
+    rng: &mut R,
+) -> anyhow::Result<()> {
+    let client = participant.client;
+    let poll = Duration::from_secs(opts.poll_seconds);
+    let mut contributed = 0;
+    loop {
+        match client.contributor_status(rng).await {
+            Ok(ContributorStatus::Finished) => break,
+            Ok(ContributorStatus::Queue(position, size)) => {
+                tracing::info!(
+                    "Waiting for the round at position {} of {} in the queue",
+                    position,
+                    size
+                );
+                tokio::time::sleep(poll).await;
+            }
+            Ok(ContributorStatus::Round) => match client.lock_chunk(rng).await {
+                Ok(lock) => {
+                    contribute_chunk(participant, opts, &lock, rng).await?;
+                    contributed += 1;
+                }
+                // The coordinator also rejects the lock when every remaining chunk is locked by others
+                Err(error) if can_retry(&error) => {
+                    tracing::info!("Could not lock a chunk, trying again in {:?} - {}", poll, error);
+                    tokio::time::sleep(poll).await;
+                }
+                Err(error) => return Err(error),
+            },
+            Ok(ContributorStatus::Other) => return Err(ContributeLoopError::NotParticipating.into()),
+            Err(error) if can_retry(&error) => {
+                tracing::warn!("Could not get the status of the participant - {}", error);
+                tokio::time::sleep(poll).await;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    tracing::info!(
+        "Contributed to {} chunks, which completes the round for the participant",
+        contributed
+    );
+    Ok(())
+}
+
+/// Downloads the challenge of a locked chunk, contributes to it and uploads the response, signed with
+/// the Aleo account of the participant like the contributor signs it.
+async fn contribute_chunk<R: Rng + CryptoRng>(
+    participant: &Participant<'_>,
+    opts: &ContributeLoopOpts,
+    lock: &LockResponse,
+    rng: &mut R,
+) -> anyhow::Result<()> {
+    let client = participant.client;
+    let poll = Duration::from_secs(opts.poll_seconds);
+    let challenge = Path::new(&opts.dir).join(format!("challenge.{}", lock.chunk_id));
+    let response = Path::new(&opts.dir).join(format!("response.{}", lock.chunk_id));
+    tracing::info!("Contributing to chunk {}", lock.chunk_id);
+
+    loop {
+        let mut file = permissions::create(&challenge)?;
+        let (chunk_id, contribution_id) = (ChunkId(lock.chunk_id), ContributionId(lock.contribution_id));
+        match client
+            .download_challenge(chunk_id, contribution_id, &mut file, rng)
+            .await
+        {
+            Ok(()) => break,
+            Err(error) if can_retry(&error) => {
+                tracing::warn!("Could not download the challenge of chunk {} - {}", chunk_id, error);
+                tokio::time::sleep(poll).await;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    let challenge_hash = calculate_hash(&fs_err::read(&challenge)?).to_vec();
+
+    // The heartbeats run on the workers of the runtime, so they keep the lock while this
+    // thread computes the contribution. The response is created anew, so a stale one is removed.
+    if response.exists() {
+        fs_err::remove_file(&response)?;
+    }
+    let environment = participant.environment;
+    let contributed = catch_unwind(AssertUnwindSafe(|| match environment.parameters().curve() {
+        CurveKind::Bls12_377 => {
+            contribute_phase1::<Bls12_377, _>(environment, lock.chunk_id, &challenge, &response, rng)
+        }
+        CurveKind::BW6 => contribute_phase1::<BW6_761, _>(environment, lock.chunk_id, &challenge, &response, rng),
+    }));
+    if contributed.is_err() {
+        return Err(ContributeLoopError::ContributionFailed(lock.chunk_id).into());
+    }
+
+    // The coordinator takes the response along with the signature of its contribution state
+    let response_file = fs_err::read(&response)?;
+    let response_hash = calculate_hash(&response_file).to_vec();
+    let contribution_state = ContributionState::new(challenge_hash.clone(), response_hash.clone(), None)?;
+    let signature = ContributionFileSignature::sign(&AleoSignature, participant.view_key, contribution_state)?;
+    let verifier_flag = vec![0];
+    let contents = [
+        verifier_flag,
+        hex::decode(signature.get_signature())?,
+        challenge_hash,
+        response_hash,
+        response_file,
+    ]
+    .concat();
+
+    let idempotency_key = loop {
+        let (chunk_id, contribution_id) = (
+            ChunkId(lock.response_chunk_id),
+            ContributionId(lock.response_contribution_id),
+        );
+        match client
+            .upload_response(chunk_id, contribution_id, contents.clone(), rng)
+            .await
+        {
+            Ok(idempotency_key) => break idempotency_key,
+            Err(error) if can_retry(&error) => {
+                tracing::warn!("Could not upload the response of chunk {} - {}", chunk_id, error);
+                tokio::time::sleep(poll).await;
+            }
+            Err(error) => return Err(error),
+        }
+    };
+
+    loop {
+        match client
+            .try_contribute(ChunkId(lock.chunk_id), &idempotency_key, rng)
+            .await
+        {
+            Ok(()) => break,
+            Err(error) if can_retry(&error) => {
+                tracing::warn!("Could not contribute to chunk {} - {}", lock.chunk_id, error);
+                tokio::time::sleep(poll).await;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    fs_err::remove_file(&challenge)?;
+    fs_err::remove_file(&response)?;
+    Ok(())
+}
+
+/// Contributes to the challenge of a phase 1 chunk with the parameters of the environment,
+/// writing the response to `response`.
+fn contribute_phase1<E: PairingEngine + Sync, R: Rng + CryptoRng>(
+    environment: &Environment,
+    chunk_id: u64,
+    challenge: &Path,
+    response: &Path,
+    rng: &mut R,
+) {
+    let settings = environment.parameters();
+    let parameters = phase1_chunked_parameters!(E, settings, chunk_id);
+    phase1_cli::contribute(
+        environment.compressed_inputs(),
+        &challenge.display().to_string(),
+        environment.compressed_outputs(),
+        &response.display().to_string(),
+        environment.check_input_for_correctness(),
+        &parameters,
+        rng,
+    );
+}
+
+/// Returns `true` if the request can succeed when made again, i.e. if it failed to reach
+/// the coordinator or if the coordinator rejected it with a retryable error code.
+fn can_retry(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<CoordinatorClientError>() {
+        Some(CoordinatorClientError::Rejected(code, _)) => code.is_retryable(),
+        None => error.downcast_ref::<reqwest::Error>().is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use setup1_shared::protocol::v1::ErrorCode;
+
+    #[test]
+    fn test_can_retry() {
+        let rejected = |code| anyhow::Error::from(CoordinatorClientError::Rejected(code, String::new()));
+        assert!(can_retry(&rejected(ErrorCode::CeremonyPaused)));
+        assert!(can_retry(&rejected(ErrorCode::Other)));
+        assert!(!can_retry(&rejected(ErrorCode::UploadTooLarge)));
+        assert!(!can_retry(&anyhow::anyhow!("the challenge is corrupt")));
+    }
+}
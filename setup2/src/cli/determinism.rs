@@ -0,0 +1,255 @@
+//! Verdict reports, which record what a verifier build concluded about a transcript, and the
+//! comparison of the reports of independently built verifiers. Verification must not depend on
+//! the compiler or the platform, so any two builds given the same inputs must agree exactly on
+//! the verdict and on the digests of the contributions.
+use super::permissions;
+use setup_utils::calculate_hash;
+
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Debug, Options, Clone)]
+pub struct CompareVerdictsOpts {
+    help: bool,
+    #[options(free, help = "the verdict reports written by `verify --report`, at least two")]
+    pub reports: Vec<String>,
+    #[options(help = "the file to write the comparison to", default = "determinism.json")]
+    pub output: String,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum DeterminismError {
+    #[error("At least two verdict reports are required, but {0} were given")]
+    NotEnoughReports(usize),
+    #[error("The verdict reports disagree: {0}")]
+    Disagreement(String),
+}
+
+/// How a verifier binary was built.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierBuild {
+    /// The version of the package.
+    pub version: String,
+    /// The version of the compiler.
+    pub rustc: String,
+    /// The target triple.
+    pub target: String,
+    /// The hex encoded BLAKE2b hash of the executable.
+    pub executable_digest: String,
+}
+
+impl VerifierBuild {
+    /// Returns the build of this binary.
+    pub fn current() -> anyhow::Result<Self> {
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            rustc: env!("SETUP2_RUSTC_VERSION").to_string(),
+            target: env!("SETUP2_TARGET").to_string(),
+            executable_digest: file_digest(&std::env::current_exe()?)?,
+        })
+    }
+}
+
+/// A file the verifier read, identified by its role rather than its path, which may differ between machines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedInput {
+    /// `before` or `after`, or `query`, `before.<i>` and `after.<i>` for chunked contributions.
+    pub role: String,
+    /// The hex encoded BLAKE2b hash of the file.
+    pub digest: String,
+}
+
+impl VerifiedInput {
+    pub fn file(role: &str, path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            role: role.to_string(),
+            digest: file_digest(path)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Valid,
+    Invalid,
+}
+
+/// What one verifier build concluded about a transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerdictReport {
+    pub build: VerifierBuild,
+    pub is_inner: bool,
+    pub inputs: Vec<VerifiedInput>,
+    pub verdict: Verdict,
+    /// Why the transcript was invalid. The message may differ between builds, so it is not compared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The hex encoded digests of the verified contributions, empty if the transcript was invalid.
+    pub contribution_digests: Vec<String>,
+}
+
+impl VerdictReport {
+    /// Returns the report of this binary on the result of verifying the inputs.
+    pub fn new(
+        is_inner: bool,
+        inputs: Vec<VerifiedInput>,
+        result: &anyhow::Result<Vec<[u8; 64]>>,
+    ) -> anyhow::Result<Self> {
+        let (verdict, error, contribution_digests) = match result {
+            Ok(hashes) => (
+                Verdict::Valid,
+                None,
+                hashes.iter().map(|hash| hex::encode(&hash[..])).collect(),
+            ),
+            Err(error) => (Verdict::Invalid, Some(error.to_string()), vec![]),
+        };
+        Ok(Self {
+            build: VerifierBuild::current()?,
+            is_inner,
+            inputs,
+            verdict,
+            error,
+            contribution_digests,
+        })
+    }
+}
+
+/// The comparison of the verdict reports of several verifier builds, for auditors to cite.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeterminismReport {
+    /// `true` if every build reached the same verdict with the same digests on the same inputs.
+    pub agreed: bool,
+    /// The differences between the reports, if they did not agree.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disagreements: Vec<String>,
+    pub builds: Vec<VerifierBuild>,
+    pub is_inner: bool,
+    pub inputs: Vec<VerifiedInput>,
+    pub verdict: Verdict,
+    pub contribution_digests: Vec<String>,
+}
+
+impl DeterminismReport {
+    /// Compares every report against the first one.
+    pub fn compare(reports: &[VerdictReport]) -> Result<Self, DeterminismError> {
+        let first = match reports {
+            [first, _, ..] => first,
+            _ => return Err(DeterminismError::NotEnoughReports(reports.len())),
+        };
+
+        let mut disagreements = vec![];
+        for (i, report) in reports.iter().enumerate().skip(1) {
+            let build = &report.build;
+            if report.is_inner != first.is_inner || report.inputs != first.inputs {
+                disagreements.push(format!("report {} verified other inputs than report 0", i));
+            } else if report.verdict != first.verdict {
+                disagreements.push(format!(
+                    "report {} ({} on {}) found the transcript {:?} instead of {:?}",
+                    i, build.rustc, build.target, report.verdict, first.verdict
+                ));
+            } else if report.contribution_digests != first.contribution_digests {
+                disagreements.push(format!(
+                    "report {} ({} on {}) computed other contribution digests",
+                    i, build.rustc, build.target
+                ));
+            }
+        }
+
+        Ok(Self {
+            agreed: disagreements.is_empty(),
+            disagreements,
+            builds: reports.iter().map(|report| report.build.clone()).collect(),
+            is_inner: first.is_inner,
+            inputs: first.inputs.clone(),
+            verdict: first.verdict,
+            contribution_digests: first.contribution_digests.clone(),
+        })
+    }
+}
+
+fn file_digest(path: &Path) -> anyhow::Result<String> {
+    let file = fs_err::File::open(path)?;
+    let bytes = unsafe { MmapOptions::new().map(file.file())? };
+    Ok(hex::encode(&calculate_hash(&bytes)[..]))
+}
+
+/// Compares the verdict reports and writes the comparison, failing if the reports disagree.
+pub fn compare_verdicts(opts: &CompareVerdictsOpts) -> anyhow::Result<()> {
+    let reports = opts
+        .reports
+        .iter()
+        .map(|path| -> anyhow::Result<VerdictReport> { Ok(serde_json::from_slice(&fs_err::read(path)?)?) })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let comparison = DeterminismReport::compare(&reports)?;
+    for (i, build) in comparison.builds.iter().enumerate() {
+        if comparison.builds[..i].contains(build) {
+            warn!("report {} was written by the same build as an earlier report", i);
+        }
+    }
+    permissions::write(&opts.output, serde_json::to_vec_pretty(&comparison)?)?;
+    info!("compared {} verdict reports, wrote {}", reports.len(), opts.output);
+
+    if !comparison.agreed {
+        return Err(DeterminismError::Disagreement(comparison.disagreements.join("; ")).into());
+    }
+    info!(
+        "every build found the transcript {:?} with the same {} contribution digests",
+        comparison.verdict,
+        comparison.contribution_digests.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(rustc: &str, verdict: Verdict, contribution_digests: &[&str]) -> VerdictReport {
+        VerdictReport {
+            build: VerifierBuild {
+                version: "0.3.0".to_string(),
+                rustc: rustc.to_string(),
+                target: "x86_64-unknown-linux-gnu".to_string(),
+                executable_digest: rustc.to_string(),
+            },
+            is_inner: true,
+            inputs: vec![VerifiedInput {
+                role: "after".to_string(),
+                digest: "00".to_string(),
+            }],
+            verdict,
+            error: None,
+            contribution_digests: contribution_digests.iter().map(|digest| digest.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_builds_must_agree() {
+        let stable = report("rustc 1.56.0", Verdict::Valid, &["aa", "bb"]);
+        let nightly = report("rustc 1.57.0-nightly", Verdict::Valid, &["aa", "bb"]);
+        let comparison = DeterminismReport::compare(&[stable.clone(), nightly]).unwrap();
+        assert!(comparison.agreed);
+        assert_eq!(comparison.builds.len(), 2);
+
+        let digests = report("rustc 1.57.0-nightly", Verdict::Valid, &["aa", "cc"]);
+        let verdict = report("rustc 1.57.0-nightly", Verdict::Invalid, &[]);
+        let mut inputs = report("rustc 1.57.0-nightly", Verdict::Valid, &["aa", "bb"]);
+        inputs.inputs[0].digest = "01".to_string();
+        let comparison = DeterminismReport::compare(&[stable.clone(), digests, verdict, inputs]).unwrap();
+        assert!(!comparison.agreed);
+        assert_eq!(comparison.disagreements.len(), 3);
+
+        assert!(matches!(
+            DeterminismReport::compare(&[stable]),
+            Err(DeterminismError::NotEnoughReports(1))
+        ));
+    }
+}
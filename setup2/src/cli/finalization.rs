@@ -0,0 +1,127 @@
+//! The attestation of a finalized transcript, which only becomes the official record of the
+//! ceremony once enough organizers approved it, each after verifying the transcript themselves.
+use super::{
+    beacon_sources::BeaconSelection,
+    ceremony::CeremonyManifest,
+    organizers::{OrganizerPolicy, OrganizerSignature},
+    permissions,
+};
+use phase2::parameters::MPCParameters;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::info;
+
+/// The name of the file recording the beacon sources a beacon step selected from, stored in the ceremony directory.
+pub const BEACON_FILENAME: &str = "beacon.json";
+
+/// The name of the file attesting to the finalized transcript, stored in the ceremony directory.
+pub const FINALIZATION_FILENAME: &str = "finalization.json";
+
+/// The name of the directory holding the approvals of the organizers, stored in the ceremony directory.
+pub const APPROVALS_DIRNAME: &str = "approvals";
+
+#[derive(Debug, Options, Clone)]
+pub struct ApproveFinalizationOpts {
+    help: bool,
+    #[options(help = "the ceremony directory to approve the finalization of", default = ".")]
+    pub dir: String,
+    #[options(required, help = "the file holding the Aleo private key of the organizer")]
+    pub organizer_key: String,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+/// The record of a finalized transcript, which anyone can check against the transcript and the beacon sources.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinalizationAttestation {
+    /// The hex encoded digest of the final transcript.
+    pub transcript_digest: String,
+    /// The beacon sources the beacon hash was selected from, if it was not given directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beacon: Option<BeaconSelection>,
+    /// The signatures of the organizers who approved the attestation, over the attestation without them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<OrganizerSignature>,
+}
+
+impl FinalizationAttestation {
+    /// Verifies the final transcript in the ceremony directory, and returns its attestation without approvals.
+    pub fn compute(dir: &Path, transcript: &str, is_inner: bool) -> anyhow::Result<Self> {
+        let hashes = if is_inner {
+            read_params::<Bls12_377>(&dir.join(transcript))?.verify_final()?
+        } else {
+            read_params::<BW6_761>(&dir.join(transcript))?.verify_final()?
+        };
+        let digest = hashes.last().expect("verify_final fails without contributions");
+        let transcript_digest = hex::encode(&digest[..]);
+        info!("the transcript digest is {}", transcript_digest);
+
+        // The selection is checked again, as the record could have changed since the beacon step
+        let beacon_path = dir.join(BEACON_FILENAME);
+        let beacon = if beacon_path.exists() {
+            let selection: BeaconSelection = serde_json::from_slice(&fs_err::read(&beacon_path)?)?;
+            selection.verify()?;
+            Some(selection)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            transcript_digest,
+            beacon,
+            approvals: vec![],
+        })
+    }
+
+    /// Returns the attestation without its approvals, which is what the organizers sign.
+    fn approved_content(&self) -> Self {
+        Self {
+            approvals: vec![],
+            ..self.clone()
+        }
+    }
+
+    /// Collects the approvals in the ceremony directory, failing unless enough organizers approved.
+    pub fn approve(&mut self, dir: &Path, policy: &OrganizerPolicy) -> anyhow::Result<()> {
+        let mut signatures = vec![];
+        let approvals_dir = dir.join(APPROVALS_DIRNAME);
+        if approvals_dir.exists() {
+            for entry in fs_err::read_dir(approvals_dir)? {
+                let path = entry?.path();
+                if path.extension().map_or(false, |extension| extension == "json") {
+                    signatures.push(serde_json::from_slice(&fs_err::read(path)?)?);
+                }
+            }
+        }
+        self.approvals = policy.approvals(&self.approved_content(), &signatures)?;
+        info!("{} organizers approved the finalization", self.approvals.len());
+        Ok(())
+    }
+}
+
+pub(super) fn read_params<E: PairingEngine>(path: &Path) -> anyhow::Result<MPCParameters<E>> {
+    let transcript = fs_err::File::open(path)?;
+    let transcript = unsafe { MmapOptions::new().map(transcript.file())? };
+    Ok(MPCParameters::read(&transcript[..])?)
+}
+
+pub fn approve_finalization(opts: &ApproveFinalizationOpts) -> anyhow::Result<()> {
+    let dir = Path::new(&opts.dir);
+    let config = CeremonyManifest::load(dir)?.config;
+    let attestation = FinalizationAttestation::compute(dir, &config.transcript, config.is_inner)?;
+
+    let organizer_key = fs_err::read_to_string(&opts.organizer_key)?;
+    let approval = OrganizerSignature::sign(&attestation, organizer_key.trim())?;
+    let approvals_dir = dir.join(APPROVALS_DIRNAME);
+    permissions::create_dir_all(&approvals_dir)?;
+    let path = approvals_dir.join(format!("{}.json", approval.keyid));
+    permissions::write(&path, serde_json::to_vec_pretty(&approval)?)?;
+    info!("{} approved the finalization, wrote {}", approval.keyid, path.display());
+    Ok(())
+}
@@ -0,0 +1,505 @@
+//! Ceremonies run in a git repository, where participants contribute by opening a pull request
+//! which replaces the transcript in the ceremony directory with their contribution. A bot checks
+//! out the pull request, runs `git-mode` on it to verify the contribution against the transcript
+//! of the base branch, and with `--merge` merges it along with the verification receipts, so the
+//! next participant contributes on top of it. Pushing the merge is left to the bot.
+use super::{
+    attestation::{read_contribution_hashes, ContributionAttestation, ContributionStatement},
+    ceremony::{CeremonyManifest, MANIFEST_FILENAME},
+    input_format::InputFormat,
+    paths::{attestation_path, chunk_path, query_path, receipt_path},
+    permissions,
+    verify::{verify, VerifyOpts},
+};
+use setup_utils::CheckForCorrectness;
+
+use gumdrop::Options;
+use serde::Serialize;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Options, Clone)]
+pub struct GitModeOpts {
+    help: bool,
+    #[options(help = "the ceremony directory in the git repository", default = ".")]
+    pub dir: String,
+    #[options(help = "the branch holding the transcript contributions are made to", default = "main")]
+    pub base: String,
+    #[options(required, help = "the revision of the pull request, e.g. a branch fetched from refs/pull/<n>/head")]
+    pub head: String,
+    #[options(help = "merge the pull request into the base branch, which must be checked out, once it is verified")]
+    pub merge: bool,
+    #[options(help = "the directory the transcripts are extracted to for verification, a temporary one by default")]
+    pub work_dir: Option<String>,
+    #[options(help = "the batches which can be loaded in memory", default = "50000")]
+    pub batch: usize,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum GitModeError {
+    #[error("`git {command}` failed: {stderr}")]
+    Git { command: String, stderr: String },
+    #[error("the pull request changes {0}, but may only change the transcript, its chunks and their attestations")]
+    UnexpectedPath(String),
+    #[error("the pull request does not change the transcript")]
+    NoContribution,
+    #[error("the pull request was not made on the latest transcript of {0}, contribute again to it")]
+    Stale(String),
+    #[error("the pull request can only be merged into {base} while it is checked out, not {checked_out}")]
+    BaseNotCheckedOut { base: String, checked_out: String },
+    #[error("the attestation {0} is not next to a file the pull request contributes to")]
+    AttestationUnmatched(String),
+    #[error("the attestation {attestation} attests to {found}, not to {expected}")]
+    AttestationFileMismatch {
+        attestation: String,
+        expected: String,
+        found: String,
+    },
+    #[error("the attestation {attestation} attests to contribution {attested}, but the last one to {file} is {last}")]
+    AttestationHashMismatch {
+        attestation: String,
+        attested: String,
+        file: String,
+        last: String,
+    },
+}
+
+/// The outcome of checking, and possibly merging, a contribution pull request.
+#[derive(Debug, Clone, Serialize)]
+pub struct GitModeReport {
+    /// The commit of the base branch the contribution was verified against.
+    pub base: String,
+    /// The commit of the pull request.
+    pub head: String,
+    /// The files the pull request changes, relative to the ceremony directory.
+    pub files: Vec<String>,
+    /// The merge commit, if the pull request was merged.
+    pub merge: Option<String>,
+}
+
+impl fmt::Display for GitModeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "verified the contribution of {} on {}", self.head, self.base)?;
+        for file in &self.files {
+            writeln!(f, "  {}", file)?;
+        }
+        match &self.merge {
+            Some(merge) => write!(f, "merged as {}", merge),
+            None => write!(f, "not merged"),
+        }
+    }
+}
+
+/// Runs git in `dir`, and returns what it printed.
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(GitModeError::Git {
+            command: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// Returns `true` if the file `name` of the ceremony directory exists at `rev`.
+fn exists_at(dir: &Path, rev: &str, name: &str) -> anyhow::Result<bool> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(&["cat-file", "-e", &format!("{}:./{}", rev, name)])
+        .stderr(Stdio::null())
+        .status()?;
+    Ok(status.success())
+}
+
+/// Writes the file `name` of the ceremony directory at `rev` to `path`, without holding it in memory.
+fn extract(dir: &Path, rev: &str, name: &str, path: &Path) -> anyhow::Result<()> {
+    let spec = format!("{}:./{}", rev, name);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(&["cat-file", "blob", &spec])
+        .stdout(permissions::create(path)?.file().try_clone()?)
+        .stderr(Stdio::piped())
+        .output()?;
+    if !output.status.success() {
+        return Err(GitModeError::Git {
+            command: format!("cat-file blob {}", spec),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the name of a file in the ceremony directory.
+fn file_name(path: &Path) -> String {
+    path.display().to_string()
+}
+
+///
+/// Returns the files of the transcript a pull request may change: the transcript, or its chunks if it
+/// is chunked, and the attestations of the participant next to them. The query parameters are never
+/// contributed to, so they may not change.
+///
+fn contributed_files(transcript: &str, num_chunks: usize) -> Vec<String> {
+    let files: Vec<PathBuf> = match num_chunks {
+        0 => vec![PathBuf::from(transcript)],
+        _ => (0..num_chunks).map(|i| chunk_path(transcript, i)).collect(),
+    };
+    files
+        .iter()
+        .flat_map(|file| vec![file_name(file), file_name(&attestation_path(file))])
+        .collect()
+}
+
+/// Checks that the changed files, relative to the ceremony directory, are a contribution to the transcript.
+fn check_changes(changed: &[String], allowed: &[String]) -> Result<(), GitModeError> {
+    if let Some(unexpected) = changed.iter().find(|file| !allowed.contains(file)) {
+        return Err(GitModeError::UnexpectedPath(unexpected.clone()));
+    }
+    // Every other allowed file is an attestation
+    if !changed.iter().any(|file| !file.ends_with(".attestation.json")) {
+        return Err(GitModeError::NoContribution);
+    }
+    Ok(())
+}
+
+/// Describes the file and chunk a contribution statement names.
+fn describe_attested(file: &str, chunk: Option<usize>) -> String {
+    match chunk {
+        Some(chunk) => format!("{} (chunk {})", file, chunk),
+        None => file.to_string(),
+    }
+}
+
+///
+/// Returns the contributed file the attestation `attestation` is next to, after checking that the
+/// pull request changes that file, and that the statement names it and its chunk.
+///
+fn attested_file(
+    attestation: &str,
+    statement: &ContributionStatement,
+    contributed: &[String],
+    changed: &[String],
+    chunked: bool,
+) -> Result<String, GitModeError> {
+    let unmatched = || GitModeError::AttestationUnmatched(attestation.to_string());
+    let file = attestation.strip_suffix(".attestation.json").ok_or_else(unmatched)?;
+    let chunk = contributed.iter().position(|contributed| contributed == file).ok_or_else(unmatched)?;
+    if !changed.iter().any(|changed| changed == file) {
+        return Err(unmatched());
+    }
+
+    // The statement names the file without the directories it is in
+    let name = Path::new(file)
+        .file_name()
+        .map_or_else(|| file.to_string(), |name| name.to_string_lossy().to_string());
+    let chunk = if chunked { Some(chunk) } else { None };
+    if statement.file != name || statement.chunk != chunk {
+        return Err(GitModeError::AttestationFileMismatch {
+            attestation: attestation.to_string(),
+            expected: describe_attested(&name, chunk),
+            found: describe_attested(&statement.file, statement.chunk),
+        });
+    }
+    Ok(file.to_string())
+}
+
+/// Returns the files the pull request changes since it branched off the base, relative to the ceremony directory.
+fn changed_files(dir: &Path, base: &str, head: &str) -> anyhow::Result<Vec<String>> {
+    // The diff lists paths relative to the root of the repository
+    let prefix = git(dir, &["rev-parse", "--show-prefix"])?;
+    let diff = git(dir, &["diff", "--name-only", "--no-renames", &format!("{}...{}", base, head)])?;
+    diff.lines()
+        .map(|file| match file.strip_prefix(prefix.as_str()) {
+            Some(file) => Ok(file.to_string()),
+            None => Err(GitModeError::UnexpectedPath(file.to_string()).into()),
+        })
+        .collect()
+}
+
+pub fn git_mode(opts: &GitModeOpts) -> anyhow::Result<()> {
+    let work_dir = match &opts.work_dir {
+        Some(work_dir) => PathBuf::from(work_dir),
+        None => std::env::temp_dir().join(format!("setup2-git-mode-{}", std::process::id())),
+    };
+    let report = check_pull_request(opts, &work_dir);
+    // The transcripts extracted to a temporary directory are removed even if the contribution is invalid
+    if opts.work_dir.is_none() && work_dir.exists() {
+        fs_err::remove_dir_all(&work_dir)?;
+    }
+
+    let report = report?;
+    if opts.json {
+        println!("{}", serde_json::to_string(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    Ok(())
+}
+
+/// Verifies the contribution of the pull request, and merges it if requested.
+fn check_pull_request(opts: &GitModeOpts, work_dir: &Path) -> anyhow::Result<GitModeReport> {
+    let dir = Path::new(&opts.dir);
+    let base = git(dir, &["rev-parse", "--verify", &format!("{}^{{commit}}", opts.base)])?;
+    let head = git(dir, &["rev-parse", "--verify", &format!("{}^{{commit}}", opts.head)])?;
+
+    // A contribution is only valid on the latest transcript, so the pull request must contain the base
+    if !Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(&["merge-base", "--is-ancestor", &base, &head])
+        .status()?
+        .success()
+    {
+        return Err(GitModeError::Stale(opts.base.clone()).into());
+    }
+
+    let (before_dir, after_dir) = (work_dir.join("before"), work_dir.join("after"));
+    permissions::create_dir_all(&before_dir)?;
+    permissions::create_dir_all(&after_dir)?;
+
+    extract(dir, &base, MANIFEST_FILENAME, &CeremonyManifest::path(&before_dir))?;
+    let config = CeremonyManifest::load(&before_dir)?.config;
+    let transcript = config.transcript.as_str();
+    let num_chunks = (0..)
+        .map(|i| exists_at(dir, &base, &file_name(&chunk_path(transcript, i))))
+        .take_while(|exists| !matches!(exists, Ok(false)))
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .len();
+
+    let files = changed_files(dir, &base, &head)?;
+    check_changes(&files, &contributed_files(transcript, num_chunks))?;
+    info!("{} changes {}", head, files.join(", "));
+
+    // The contribution is verified against the transcript of the base branch, not the one in the pull request
+    let (before, after) = (before_dir.join(transcript), after_dir.join(transcript));
+    let contributed: Vec<String> = match num_chunks {
+        0 => vec![transcript.to_string()],
+        _ => {
+            let query = file_name(&query_path(transcript));
+            extract(dir, &base, &query, &before_dir.join(&query))?;
+            (0..num_chunks).map(|i| file_name(&chunk_path(transcript, i))).collect()
+        }
+    };
+    for file in &contributed {
+        extract(dir, &base, file, &before_dir.join(file))?;
+        extract(dir, &head, file, &after_dir.join(file))?;
+    }
+    let mut attestations = vec![];
+    for file in files.iter().filter(|file| file.ends_with(".attestation.json")) {
+        extract(dir, &head, file, &after_dir.join(file))?;
+        let attestation: ContributionAttestation = serde_json::from_slice(&fs_err::read(after_dir.join(file))?)?;
+        attestation.verify()?;
+        let attested = attested_file(file, &attestation.statement, &contributed, &files, num_chunks > 0)?;
+        attestations.push((file, attested, attestation.statement.contribution_hash));
+    }
+    verify(&VerifyOpts {
+        help: false,
+        before: file_name(&before),
+        after: file_name(&after),
+        batch: opts.batch,
+        max_memory_gb: None,
+        is_inner: config.is_inner,
+        chunks: Some(num_chunks).filter(|chunks| *chunks > 0),
+        query: Some(file_name(&query_path(&before))).filter(|_| num_chunks > 0),
+        input_format: if num_chunks > 0 {
+            InputFormat::Chunked
+        } else {
+            InputFormat::Full
+        },
+        check_input: CheckForCorrectness::Full,
+        report: None,
+        quiet: true,
+        progress_json: false,
+        json: false,
+    })?;
+    info!("verified the contribution of {}", head);
+
+    // An attestation is to the contribution the pull request makes, which is the last one to its file
+    for (attestation, file, attested) in attestations {
+        let last = read_contribution_hashes(&after_dir.join(&file), config.is_inner)?
+            .pop()
+            .unwrap_or_default();
+        if last != attested {
+            return Err(GitModeError::AttestationHashMismatch {
+                attestation: attestation.clone(),
+                attested,
+                file,
+                last,
+            }
+            .into());
+        }
+    }
+
+    let merge = if opts.merge {
+        Some(merge_contribution(dir, opts, &head, &after_dir, &contributed)?)
+    } else {
+        None
+    };
+    Ok(GitModeReport {
+        base,
+        head,
+        files,
+        merge,
+    })
+}
+
+/// Merges the verified pull request into the checked out base branch along with the receipts
+/// of the verification, and returns the merge commit.
+fn merge_contribution(
+    dir: &Path,
+    opts: &GitModeOpts,
+    head: &str,
+    after_dir: &Path,
+    contributed: &[String],
+) -> anyhow::Result<String> {
+    let checked_out = git(dir, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    if checked_out != opts.base {
+        return Err(GitModeError::BaseNotCheckedOut {
+            base: opts.base.clone(),
+            checked_out,
+        }
+        .into());
+    }
+
+    git(dir, &["merge", "--no-ff", "--no-commit", head])?;
+    let commit = || -> anyhow::Result<()> {
+        for file in contributed {
+            let receipt = file_name(&receipt_path(file));
+            fs_err::copy(after_dir.join(&receipt), dir.join(&receipt))?;
+            git(dir, &["add", "--", &receipt])?;
+        }
+        git(dir, &["commit", "-m", &format!("Merge the verified contribution {}", head)])?;
+        Ok(())
+    };
+    if let Err(error) = commit() {
+        // Leave the base branch as it was, so that the bot can try again
+        let _ = git(dir, &["merge", "--abort"]);
+        return Err(error);
+    }
+    git(dir, &["rev-parse", "HEAD"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_changes() {
+        let full = contributed_files("transcript", 0);
+        assert_eq!(full, vec!["transcript", "transcript.attestation.json"]);
+        check_changes(&["transcript".to_string()], &full).unwrap();
+
+        let chunked = contributed_files("transcript", 2);
+        let changes = vec![
+            "transcript.0".to_string(),
+            "transcript.1".to_string(),
+            "transcript.1.attestation.json".to_string(),
+        ];
+        check_changes(&changes, &chunked).unwrap();
+
+        // Neither the query parameters nor the receipts may be changed
+        for file in &["transcript.query", "transcript.0.verified", "../transcript", "transcript"] {
+            let changes = vec!["transcript.0".to_string(), file.to_string()];
+            assert!(matches!(
+                check_changes(&changes, &chunked),
+                Err(GitModeError::UnexpectedPath(path)) if path == *file
+            ));
+        }
+
+        // An attestation is no contribution
+        assert!(matches!(
+            check_changes(&["transcript.attestation.json".to_string()], &full),
+            Err(GitModeError::NoContribution)
+        ));
+    }
+
+    #[test]
+    fn test_attestations_match_the_changed_files() {
+        let repo = std::env::temp_dir().join(format!("setup2-git-mode-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&repo);
+        let dir = repo.join("ceremony");
+        fs_err::create_dir_all(&dir).unwrap();
+        let commit = |message: &str| {
+            let identity = ["-c", "user.name=setup2", "-c", "user.email=setup2@localhost"];
+            git(&dir, &["add", "--all"]).unwrap();
+            git(&dir, &[&identity[..], &["commit", "-qm", message]].concat()).unwrap();
+        };
+
+        git(&repo, &["init", "-q"]).unwrap();
+        git(&repo, &["checkout", "-q", "-b", "main"]).unwrap();
+        fs_err::write(dir.join("transcript.0"), "chunk 0").unwrap();
+        fs_err::write(dir.join("transcript.1"), "chunk 1").unwrap();
+        commit("the initial transcript");
+        git(&repo, &["checkout", "-q", "-b", "contribution"]).unwrap();
+        fs_err::write(dir.join("transcript.1"), "chunk 1 contributed to").unwrap();
+        fs_err::write(dir.join("transcript.1.attestation.json"), "{}").unwrap();
+        commit("a contribution to chunk 1");
+
+        // The changed files are relative to the ceremony directory, which holds two chunks
+        let changed = changed_files(&dir, "main", "contribution").unwrap();
+        assert_eq!(changed, vec!["transcript.1", "transcript.1.attestation.json"]);
+        assert!(exists_at(&dir, "main", "transcript.1").unwrap());
+        assert!(!exists_at(&dir, "main", "transcript.2").unwrap());
+        let contributed = vec!["transcript.0".to_string(), "transcript.1".to_string()];
+        check_changes(&changed, &contributed_files("transcript", 2)).unwrap();
+
+        let statement = |file: &str, chunk| ContributionStatement {
+            file: file.to_string(),
+            chunk,
+            contribution_hash: "ab".repeat(64),
+            timestamp: 1_600_000_000,
+        };
+        assert_eq!(
+            attested_file(
+                "transcript.1.attestation.json",
+                &statement("transcript.1", Some(1)),
+                &contributed,
+                &changed,
+                true
+            )
+            .unwrap(),
+            "transcript.1"
+        );
+
+        // An attestation to another chunk or file than the one it is next to is rejected
+        for (file, chunk) in &[("transcript.1", Some(0)), ("transcript.0", Some(1)), ("transcript.1", None)] {
+            assert!(matches!(
+                attested_file(
+                    "transcript.1.attestation.json",
+                    &statement(*file, *chunk),
+                    &contributed,
+                    &changed,
+                    true
+                ),
+                Err(GitModeError::AttestationFileMismatch { .. })
+            ));
+        }
+
+        // and so is one next to a chunk the pull request does not contribute to
+        assert!(matches!(
+            attested_file(
+                "transcript.0.attestation.json",
+                &statement("transcript.0", Some(0)),
+                &contributed,
+                &changed,
+                true
+            ),
+            Err(GitModeError::AttestationUnmatched(_))
+        ));
+
+        fs_err::remove_dir_all(repo).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+//! Golden files: the test vectors of a few small ceremonies, recorded once and checked in,
+//! which the current serialization must reproduce bit for bit. A change to the format of
+//! any artifact thus shows up as a difference to the recorded files, before it can corrupt
+//! a live ceremony.
+use super::{
+    new::CurveKind,
+    permissions,
+    test_vectors::{test_vectors, TestVectors, TestVectorsOpts, TEST_VECTORS_FILENAME},
+};
+
+use gumdrop::Options;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+
+/// The ceremonies the golden files are recorded for, by curve, power and number of chunks.
+const GOLDEN_CASES: &[(CurveKind, u32, usize)] = &[(CurveKind::Bls12_377, 4, 2), (CurveKind::BW6, 4, 3)];
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyGoldenOpts {
+    help: bool,
+    #[options(help = "the directory holding the golden files, by default the ones checked in with setup2")]
+    pub golden: Option<String>,
+    #[options(help = "record the golden files again instead of verifying them, after an intended format change")]
+    pub record: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum GoldenError {
+    #[error("no golden files were recorded at {}, run `verify-golden --record`", .0.display())]
+    MissingCase(PathBuf),
+    #[error("the serialization drifted from the golden files: {0}")]
+    Drift(String),
+}
+
+/// Returns the directory the golden files are checked in to, wherever setup2 is run from.
+fn checked_in_golden() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("golden")
+}
+
+/// Returns the name of the directory the golden files of a case are recorded to.
+fn case_name(curve: CurveKind, power: u32, chunks: usize) -> String {
+    let curve = match curve {
+        CurveKind::Bls12_377 => "bls12_377",
+        CurveKind::BW6 => "bw6",
+    };
+    format!("{}-{}-{}", curve, power, chunks)
+}
+
+/// Generates the test vectors of a case to a temporary directory, and returns it with their index.
+fn generate_case(name: &str, curve: CurveKind, power: u32, chunks: usize) -> anyhow::Result<(PathBuf, TestVectors)> {
+    let dir = std::env::temp_dir().join(format!("setup2-golden-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        fs_err::remove_dir_all(&dir)?;
+    }
+    test_vectors(&TestVectorsOpts {
+        help: false,
+        output: dir.display().to_string(),
+        seed: None,
+        curve_type: curve,
+        power,
+        chunks,
+        json: false,
+    })?;
+    let vectors = serde_json::from_slice(&fs_err::read(dir.join(TEST_VECTORS_FILENAME))?)?;
+    Ok((dir, vectors))
+}
+
+/// Returns the names of the files of the test vectors, starting with their index.
+fn file_names(vectors: &TestVectors) -> Vec<&str> {
+    let mut names = vec![TEST_VECTORS_FILENAME];
+    names.extend(vectors.files.iter().map(|file| file.name.as_str()));
+    names
+}
+
+/// Copies the generated files of a case to its golden directory, replacing what was recorded before.
+fn record_case(golden: &Path, generated: &Path, vectors: &TestVectors) -> anyhow::Result<()> {
+    if golden.exists() {
+        fs_err::remove_dir_all(golden)?;
+    }
+    permissions::create_dir_all(golden)?;
+    for name in file_names(vectors) {
+        permissions::write(golden.join(name), fs_err::read(generated.join(name))?)?;
+    }
+    Ok(())
+}
+
+/// Returns how the generated files of a case differ from its golden files.
+fn compare_case(golden: &Path, generated: &Path, vectors: &TestVectors) -> anyhow::Result<Vec<String>> {
+    let mut drift = vec![];
+    for name in file_names(vectors) {
+        let expected = golden.join(name);
+        if !expected.exists() {
+            drift.push(format!("{} was not recorded", expected.display()));
+        } else if fs_err::read(&expected)? != fs_err::read(generated.join(name))? {
+            drift.push(format!("{} differs", expected.display()));
+        }
+    }
+
+    // A file which is no longer generated is a format change too
+    let index = golden.join(TEST_VECTORS_FILENAME);
+    if index.exists() {
+        if let Ok(recorded) = serde_json::from_slice::<TestVectors>(&fs_err::read(&index)?) {
+            for file in &recorded.files {
+                if !vectors.files.iter().any(|generated| generated.name == file.name) {
+                    drift.push(format!("{} is no longer generated", golden.join(&file.name).display()));
+                }
+            }
+        }
+    }
+    Ok(drift)
+}
+
+/// Verifies the given cases against the golden files in `golden`, or records them if `record` is set.
+fn verify_cases(golden: &Path, cases: &[(CurveKind, u32, usize)], record: bool) -> anyhow::Result<()> {
+    let mut drift = vec![];
+    for &(curve, power, chunks) in cases {
+        let name = case_name(curve, power, chunks);
+        let case_dir = golden.join(&name);
+        if !record && !case_dir.exists() {
+            return Err(GoldenError::MissingCase(case_dir).into());
+        }
+
+        let (generated, vectors) = generate_case(&name, curve, power, chunks)?;
+        if record {
+            record_case(&case_dir, &generated, &vectors)?;
+            info!("recorded the golden files of {}", name);
+        } else {
+            let case_drift = compare_case(&case_dir, &generated, &vectors)?;
+            if case_drift.is_empty() {
+                info!("the golden files of {} are reproduced", name);
+            }
+            drift.extend(case_drift);
+        }
+        fs_err::remove_dir_all(generated)?;
+    }
+
+    if !drift.is_empty() {
+        return Err(GoldenError::Drift(drift.join("; ")).into());
+    }
+    Ok(())
+}
+
+pub fn verify_golden(opts: &VerifyGoldenOpts) -> anyhow::Result<()> {
+    let golden = opts.golden.as_ref().map_or_else(checked_in_golden, PathBuf::from);
+    verify_cases(&golden, GOLDEN_CASES, opts.record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialization_matches_golden_files() {
+        verify_cases(&checked_in_golden(), GOLDEN_CASES, false).unwrap();
+    }
+
+    #[test]
+    fn test_golden_files_catch_drift() {
+        // Records to a temporary directory, to check the comparison itself
+        let golden = std::env::temp_dir().join(format!("setup2-golden-files-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&golden);
+        let cases = &[(CurveKind::Bls12_377, 4, 2)];
+        assert!(matches!(
+            verify_cases(&golden, cases, false)
+                .unwrap_err()
+                .downcast_ref::<GoldenError>(),
+            Some(GoldenError::MissingCase(_))
+        ));
+
+        verify_cases(&golden, cases, true).unwrap();
+        verify_cases(&golden, cases, false).unwrap();
+
+        // A single flipped bit in any recorded file is reported
+        let response = golden.join("bls12_377-4-2").join("response.1");
+        let mut contents = fs_err::read(&response).unwrap();
+        contents[100] ^= 1;
+        fs_err::write(&response, contents).unwrap();
+        let error = verify_cases(&golden, cases, false).unwrap_err();
+        assert!(error.to_string().contains("response.1 differs"));
+
+        fs_err::remove_dir_all(golden).unwrap();
+    }
+}
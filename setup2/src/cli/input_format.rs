@@ -0,0 +1,295 @@
+//! The format of the files a command reads, which is detected from the files themselves
+//! unless it is given with `--input-format`, so that nobody needs to remember how each
+//! artifact was produced.
+use super::paths::chunk_path;
+use setup_utils::{CheckForCorrectness, Groth16Params, UseCompression};
+
+use snarkvm_curves::PairingEngine;
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::info;
+
+/// The format of an input file, as given with `--input-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Detect the format from the file.
+    Auto,
+    /// A prepared phase 1 transcript with compressed elements.
+    Compressed,
+    /// A prepared phase 1 transcript with uncompressed elements.
+    Uncompressed,
+    /// Full phase 2 parameters.
+    Full,
+    /// Phase 2 parameters split into query parameters and chunks.
+    Chunked,
+}
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = match self {
+            InputFormat::Auto => "auto",
+            InputFormat::Compressed => "compressed",
+            InputFormat::Uncompressed => "uncompressed",
+            InputFormat::Full => "full",
+            InputFormat::Chunked => "chunked",
+        };
+        write!(f, "{}", format)
+    }
+}
+
+pub fn input_format_from_str(src: &str) -> std::result::Result<InputFormat, String> {
+    let format = match src.to_lowercase().as_str() {
+        "auto" => InputFormat::Auto,
+        "compressed" => InputFormat::Compressed,
+        "uncompressed" => InputFormat::Uncompressed,
+        "full" => InputFormat::Full,
+        "chunked" => InputFormat::Chunked,
+        _ => {
+            return Err(format!(
+                "unsupported input format {}, expected `auto`, `compressed`, `uncompressed`, `full` or `chunked`.",
+                src
+            ));
+        }
+    };
+    Ok(format)
+}
+
+/// Parses the checks the elements of an input are read with, as given with `--check-input`.
+pub fn check_from_str(src: &str) -> std::result::Result<CheckForCorrectness, String> {
+    let check = match src.to_lowercase().as_str() {
+        "full" => CheckForCorrectness::Full,
+        "non-zero" => CheckForCorrectness::OnlyNonZero,
+        "in-group" => CheckForCorrectness::OnlyInGroup,
+        "no" => CheckForCorrectness::No,
+        _ => {
+            return Err(format!(
+                "unsupported check {}, expected `full`, `non-zero`, `in-group` or `no`.",
+                src
+            ));
+        }
+    };
+    Ok(check)
+}
+
+#[derive(Debug, Error)]
+pub enum InputFormatError {
+    #[error("the input format {format} does not apply to {input}")]
+    NotApplicable { format: InputFormat, input: &'static str },
+    #[error(
+        "{path} is {size} bytes, which is neither a compressed ({compressed} bytes) nor an uncompressed ({uncompressed} bytes) prepared phase 1 transcript of 2^{phase1_size} powers"
+    )]
+    UnknownCompression {
+        path: PathBuf,
+        size: u64,
+        phase1_size: u32,
+        compressed: u64,
+        uncompressed: u64,
+    },
+    #[error("no chunks of {0} were found next to it")]
+    MissingChunks(PathBuf),
+    #[error(
+        "{path} is {size} bytes, but a {compression} prepared phase 1 transcript of 2^{phase1_size} powers is {expected} bytes"
+    )]
+    TranscriptSizeMismatch {
+        path: PathBuf,
+        size: u64,
+        expected: u64,
+        phase1_size: u32,
+        compression: &'static str,
+    },
+}
+
+/// Returns the compression of the prepared phase 1 transcript at `path`, which is told
+/// apart by its size, as the elements of a transcript of a given size all have the same size.
+pub fn detect_compression<E: PairingEngine>(
+    path: &Path,
+    phase1_size: u32,
+    format: InputFormat,
+) -> anyhow::Result<UseCompression> {
+    match format {
+        InputFormat::Compressed => return Ok(UseCompression::Yes),
+        InputFormat::Uncompressed => return Ok(UseCompression::No),
+        InputFormat::Full | InputFormat::Chunked => {
+            return Err(InputFormatError::NotApplicable {
+                format,
+                input: "a prepared phase 1 transcript",
+            }
+            .into());
+        }
+        InputFormat::Auto => {}
+    }
+
+    let size = fs_err::metadata(path)?.len();
+    let compressed = Groth16Params::<E>::transcript_size(1 << phase1_size, UseCompression::Yes) as u64;
+    let uncompressed = Groth16Params::<E>::transcript_size(1 << phase1_size, UseCompression::No) as u64;
+    let compression = match size {
+        size if size == compressed => UseCompression::Yes,
+        size if size == uncompressed => UseCompression::No,
+        size => {
+            return Err(InputFormatError::UnknownCompression {
+                path: path.to_path_buf(),
+                size,
+                phase1_size,
+                compressed,
+                uncompressed,
+            }
+            .into());
+        }
+    };
+    info!("detected a prepared phase 1 transcript with {:?} compression", compression);
+    Ok(compression)
+}
+
+/// Checks that the prepared phase 1 transcript at `path` is as large as a transcript of `phase1_size`
+/// powers with the given compression over the curve, before it is memory mapped and read.
+pub fn check_transcript_size<E: PairingEngine>(
+    path: &Path,
+    phase1_size: u32,
+    compression: UseCompression,
+) -> anyhow::Result<()> {
+    let size = fs_err::metadata(path)?.len();
+    let expected = Groth16Params::<E>::transcript_size(1 << phase1_size, compression) as u64;
+    if size != expected {
+        return Err(InputFormatError::TranscriptSizeMismatch {
+            path: path.to_path_buf(),
+            size,
+            expected,
+            phase1_size,
+            compression: match compression {
+                UseCompression::Yes => "compressed",
+                UseCompression::No => "uncompressed",
+            },
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the number of chunks the phase 2 parameters at `path` were split into,
+/// or `None` if they are full parameters. The chunks are stored next to the parameters,
+/// so they are counted from the first one until one is missing.
+pub fn detect_chunks(path: &Path, format: InputFormat) -> Result<Option<usize>, InputFormatError> {
+    let num_chunks = (0..).take_while(|i| chunk_path(path, *i).exists()).count();
+    match format {
+        InputFormat::Full => Ok(None),
+        InputFormat::Chunked if num_chunks == 0 => Err(InputFormatError::MissingChunks(path.to_path_buf())),
+        InputFormat::Chunked => Ok(Some(num_chunks)),
+        InputFormat::Auto if num_chunks == 0 => Ok(None),
+        InputFormat::Auto => {
+            info!("detected {} chunks of {}", num_chunks, path.display());
+            Ok(Some(num_chunks))
+        }
+        InputFormat::Compressed | InputFormat::Uncompressed => Err(InputFormatError::NotApplicable {
+            format,
+            input: "phase 2 parameters",
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snarkvm_curves::bls12_377::Bls12_377;
+    use std::fs;
+
+    fn tempdir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("setup2-input-format-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_input_format_from_str() {
+        assert_eq!(input_format_from_str("auto").unwrap(), InputFormat::Auto);
+        assert_eq!(input_format_from_str("Chunked").unwrap(), InputFormat::Chunked);
+        assert!(input_format_from_str("v2").is_err());
+    }
+
+    #[test]
+    fn test_check_from_str() {
+        assert_eq!(check_from_str("full").unwrap(), CheckForCorrectness::Full);
+        assert_eq!(check_from_str("Non-Zero").unwrap(), CheckForCorrectness::OnlyNonZero);
+        assert_eq!(check_from_str("no").unwrap(), CheckForCorrectness::No);
+        assert!(check_from_str("none").is_err());
+    }
+
+    #[test]
+    fn test_detect_compression() {
+        let dir = tempdir("compression");
+        let path = dir.join("phase1");
+        let phase1_size = 2;
+        for compression in [UseCompression::Yes, UseCompression::No] {
+            let size = Groth16Params::<Bls12_377>::transcript_size(1 << phase1_size, compression);
+            fs::write(&path, vec![0u8; size]).unwrap();
+            assert_eq!(
+                detect_compression::<Bls12_377>(&path, phase1_size, InputFormat::Auto).unwrap(),
+                compression
+            );
+        }
+
+        // The size of a truncated transcript matches neither, unless the compression is given
+        fs::write(&path, vec![0u8; 7]).unwrap();
+        assert!(detect_compression::<Bls12_377>(&path, phase1_size, InputFormat::Auto).is_err());
+        assert_eq!(
+            detect_compression::<Bls12_377>(&path, phase1_size, InputFormat::Uncompressed).unwrap(),
+            UseCompression::No
+        );
+        assert!(detect_compression::<Bls12_377>(&path, phase1_size, InputFormat::Chunked).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_check_transcript_size() {
+        let dir = tempdir("transcript-size");
+        let path = dir.join("phase1");
+        let phase1_size = 2;
+        let size = Groth16Params::<Bls12_377>::transcript_size(1 << phase1_size, UseCompression::Yes);
+        fs::write(&path, vec![0u8; size]).unwrap();
+        check_transcript_size::<Bls12_377>(&path, phase1_size, UseCompression::Yes).unwrap();
+
+        // A transcript of fewer powers, or of uncompressed elements, is rejected with both sizes
+        for (phase1_size, compression) in [(3, UseCompression::Yes), (2, UseCompression::No)] {
+            let error = check_transcript_size::<Bls12_377>(&path, phase1_size, compression).unwrap_err();
+            match error.downcast_ref::<InputFormatError>() {
+                Some(InputFormatError::TranscriptSizeMismatch {
+                    size: actual, expected, ..
+                }) => {
+                    assert_eq!(*actual, size as u64);
+                    assert_eq!(
+                        *expected,
+                        Groth16Params::<Bls12_377>::transcript_size(1 << phase1_size, compression) as u64
+                    );
+                }
+                _ => panic!("unexpected error {}", error),
+            }
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_detect_chunks() {
+        let dir = tempdir("chunks");
+        let path = dir.join("challenge");
+        fs::write(&path, b"").unwrap();
+        assert_eq!(detect_chunks(&path, InputFormat::Auto).unwrap(), None);
+        assert!(matches!(
+            detect_chunks(&path, InputFormat::Chunked),
+            Err(InputFormatError::MissingChunks(_))
+        ));
+
+        for i in 0..3 {
+            fs::write(chunk_path(&path, i), b"").unwrap();
+        }
+        assert_eq!(detect_chunks(&path, InputFormat::Auto).unwrap(), Some(3));
+        assert_eq!(detect_chunks(&path, InputFormat::Chunked).unwrap(), Some(3));
+        assert_eq!(detect_chunks(&path, InputFormat::Full).unwrap(), None);
+        assert!(detect_chunks(&path, InputFormat::Compressed).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Keystores holding the Aleo private key of a participant encrypted under a passphrase with
+//! age, so that the key never has to be stored, passed or printed in plaintext. The address is
+//! stored in the clear, so a keystore can be told apart without the passphrase.
+use super::permissions;
+
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey};
+
+use age::{Decryptor, Encryptor};
+use gumdrop::Options;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+    path::Path,
+    str::FromStr,
+};
+use thiserror::Error;
+use tracing::info;
+use unic_langid::LanguageIdentifier;
+
+/// The version of the keystore format.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// The environment variable the passphrase of a keystore is read from, for unattended runs.
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "SETUP2_KEYSTORE_PASSPHRASE";
+
+#[derive(Debug, Options, Clone)]
+pub struct NewKeystoreOpts {
+    help: bool,
+    #[options(help = "the file to write the keystore to", default = "keystore.json")]
+    pub output: String,
+    #[options(help = "encrypt the Aleo private key in this file instead of generating a new one")]
+    pub import: Option<String>,
+    #[options(help = "replace the keystore if it exists")]
+    pub force: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("{0} is not a keystore, create one with `new-keystore --import <key file>`")]
+    NotAKeystore(String),
+    #[error("the keystore has version {0}, but this binary only reads version {}", KEYSTORE_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("the passphrase of the keystore is wrong")]
+    WrongPassphrase,
+    #[error("the keystore holds the key of {actual}, but claims to hold the key of {expected}")]
+    AddressMismatch { expected: String, actual: String },
+    #[error("the key is not a valid Aleo private key")]
+    InvalidPrivateKey,
+    #[error("the keystore {0} already exists, pass --force to replace it")]
+    Exists(String),
+    #[error("no passphrase was given, set {} or run in a terminal", KEYSTORE_PASSPHRASE_ENV)]
+    PassphraseRequired,
+}
+
+/// An Aleo private key encrypted under a passphrase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    /// The address of the private key.
+    pub address: String,
+    /// The hex encoded private key, encrypted with age under the passphrase.
+    pub encrypted_private_key: String,
+}
+
+fn address(private_key: &str) -> Result<String, KeystoreError> {
+    let private_key =
+        PrivateKey::<Testnet2Parameters>::from_str(private_key).map_err(|_| KeystoreError::InvalidPrivateKey)?;
+    Address::try_from(&private_key)
+        .map(|address| address.to_string())
+        .map_err(|_| KeystoreError::InvalidPrivateKey)
+}
+
+impl Keystore {
+    /// Returns the keystore holding the given private key, encrypted under the passphrase.
+    pub fn encrypt(private_key: &str, passphrase: SecretString) -> anyhow::Result<Self> {
+        let address = address(private_key)?;
+        let mut encrypted = vec![];
+        let mut writer = Encryptor::with_user_passphrase(passphrase).wrap_output(&mut encrypted)?;
+        writer.write_all(private_key.as_bytes())?;
+        writer.finish()?;
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address,
+            encrypted_private_key: hex::encode(encrypted),
+        })
+    }
+
+    /// Returns the private key in the keystore, decrypted with the passphrase.
+    pub fn decrypt(&self, passphrase: &SecretString) -> anyhow::Result<SecretString> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(self.version).into());
+        }
+        let encrypted = hex::decode(&self.encrypted_private_key)?;
+        let decryptor = match Decryptor::new(encrypted.as_slice())? {
+            Decryptor::Passphrase(decryptor) => decryptor,
+            Decryptor::Recipients(_) => return Err(KeystoreError::WrongPassphrase.into()),
+        };
+        let mut private_key = String::new();
+        decryptor
+            .decrypt(passphrase, None)
+            .map_err(|_| KeystoreError::WrongPassphrase)?
+            .read_to_string(&mut private_key)?;
+
+        let private_key = SecretString::new(private_key);
+        let actual = address(private_key.expose_secret())?;
+        if actual != self.address {
+            return Err(KeystoreError::AddressMismatch {
+                expected: self.address.clone(),
+                actual,
+            }
+            .into());
+        }
+        Ok(private_key)
+    }
+}
+
+/// Returns the passphrase from the environment, or else asks for it in the terminal.
+fn read_passphrase(description: &str, confirm: bool) -> anyhow::Result<SecretString> {
+    if let Ok(passphrase) = std::env::var(KEYSTORE_PASSPHRASE_ENV) {
+        return Ok(SecretString::new(passphrase));
+    }
+    let default_language: LanguageIdentifier = "en-US".parse()?;
+    age::localizer().select(&[default_language])?;
+    age::cli_common::read_secret(description, "Passphrase", confirm.then(|| "Confirm passphrase"))
+        .map_err(|_| KeystoreError::PassphraseRequired.into())
+}
+
+///
+/// Returns the private key in the keystore at `path`, asking for its passphrase
+/// unless it is given in the environment.
+///
+pub(super) fn read_private_key<P: AsRef<Path>>(path: P) -> anyhow::Result<SecretString> {
+    let display = path.as_ref().display().to_string();
+    let keystore: Keystore = serde_json::from_slice(&fs_err::read(path.as_ref())?)
+        .map_err(|_| KeystoreError::NotAKeystore(display.clone()))?;
+    let passphrase = read_passphrase(&format!("Enter the passphrase of the keystore {}", display), false)?;
+    let private_key = keystore.decrypt(&passphrase)?;
+    info!("unlocked the key of {}", keystore.address);
+    Ok(private_key)
+}
+
+pub fn new_keystore(opts: &NewKeystoreOpts) -> anyhow::Result<()> {
+    if Path::new(&opts.output).exists() && !opts.force {
+        return Err(KeystoreError::Exists(opts.output.clone()).into());
+    }
+    let private_key = match &opts.import {
+        Some(path) => SecretString::new(fs_err::read_to_string(path)?.trim().to_string()),
+        None => SecretString::new(PrivateKey::<Testnet2Parameters>::new(&mut rand::rngs::OsRng).to_string()),
+    };
+
+    let passphrase = read_passphrase("Choose the passphrase of the keystore", true)?;
+    let keystore = Keystore::encrypt(private_key.expose_secret(), passphrase)?;
+    permissions::write(&opts.output, serde_json::to_vec_pretty(&keystore)?)?;
+    info!("wrote the keystore of {} to {}", keystore.address, opts.output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_keystore() {
+        let private_key = PrivateKey::<Testnet2Parameters>::new(&mut ChaChaRng::from_seed([1; 32])).to_string();
+        let keystore = Keystore::encrypt(&private_key, SecretString::new("correct horse".to_string())).unwrap();
+        assert_eq!(keystore.address, address(&private_key).unwrap());
+        assert!(!keystore.encrypted_private_key.contains(&hex::encode(&private_key)));
+
+        let decrypted = keystore
+            .decrypt(&SecretString::new("correct horse".to_string()))
+            .unwrap();
+        assert_eq!(decrypted.expose_secret(), &private_key);
+
+        assert!(matches!(
+            keystore
+                .decrypt(&SecretString::new("battery staple".to_string()))
+                .unwrap_err()
+                .downcast_ref::<KeystoreError>(),
+            Some(KeystoreError::WrongPassphrase)
+        ));
+
+        // The address in the clear can not be swapped for another one
+        let mut swapped = keystore;
+        swapped.address = "aleo1".to_string();
+        assert!(matches!(
+            swapped
+                .decrypt(&SecretString::new("correct horse".to_string()))
+                .unwrap_err()
+                .downcast_ref::<KeystoreError>(),
+            Some(KeystoreError::AddressMismatch { .. })
+        ));
+    }
+}
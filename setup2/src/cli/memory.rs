@@ -0,0 +1,135 @@
+use super::constraints::CircuitSize;
+
+use snarkvm_curves::{AffineCurve, PairingEngine};
+
+use thiserror::Error;
+use tracing::warn;
+
+const BYTES_PER_GB: f64 = (1u64 << 30) as f64;
+
+/// The in-memory size of one element of `C`, in both its affine and projective form,
+/// since batch operations keep both representations alive at the same time.
+fn element_size<C: AffineCurve>() -> usize {
+    std::mem::size_of::<C>() + std::mem::size_of::<C::Projective>()
+}
+
+/// The number of bytes held in memory per batch element while contributing:
+/// the H and L queries are each processed by their own thread.
+pub fn contribute_bytes_per_element<E: PairingEngine>() -> usize {
+    2 * element_size::<E::G1Affine>()
+}
+
+/// The number of bytes held in memory per batch element while verifying: the
+/// before and after batches of five queries (one of them in G2) are processed
+/// concurrently, and the ratio checks additionally merge the G1 pairs.
+pub fn verify_bytes_per_element<E: PairingEngine>() -> usize {
+    2 * (4 * element_size::<E::G1Affine>() + element_size::<E::G2Affine>()) + 2 * element_size::<E::G1Affine>()
+}
+
+/// An estimate of the bytes held in memory by `new`: the Lagrange coefficients read from
+/// phase 1 (four vectors in G1 and one in G2), and the queries of the initial parameters,
+/// in both representations while they are computed. The constraint system of the circuit is
+/// not counted, so the estimate is a lower bound.
+pub fn new_bytes<E: PairingEngine>(size: &CircuitSize) -> usize {
+    let num_variables = size.num_public_variables + size.num_private_variables;
+    let phase1 = size.phase2_size * (4 * std::mem::size_of::<E::G1Affine>() + std::mem::size_of::<E::G2Affine>());
+    let queries = (3 * num_variables + size.phase2_size) * element_size::<E::G1Affine>()
+        + num_variables * element_size::<E::G2Affine>();
+    phase1 + queries
+}
+
+#[derive(Debug, Error)]
+pub enum MemoryCapError {
+    #[error("the memory cap must be positive (got {0} GB)")]
+    NotPositive(f64),
+    #[error("a memory cap of {0} GB is too small to process a single element ({1} bytes)")]
+    TooSmall(f64, usize),
+    #[error("the parameters are estimated to need {0:.1} GB of memory, more than the cap of {1} GB")]
+    Exceeded(f64, f64),
+}
+
+/// Checks that `bytes`, which cannot be reduced by batching, fit in `max_memory_gb`, if a cap was given.
+pub fn check_memory_cap(bytes: usize, max_memory_gb: Option<f64>) -> Result<(), MemoryCapError> {
+    let max_memory_gb = match max_memory_gb {
+        Some(max_memory_gb) => max_memory_gb,
+        None => return Ok(()),
+    };
+    if max_memory_gb.is_nan() || max_memory_gb <= 0.0 {
+        return Err(MemoryCapError::NotPositive(max_memory_gb));
+    }
+
+    let needed_gb = bytes as f64 / BYTES_PER_GB;
+    if needed_gb > max_memory_gb {
+        return Err(MemoryCapError::Exceeded(needed_gb, max_memory_gb));
+    }
+    Ok(())
+}
+
+/// Returns the largest batch size not exceeding `requested` whose projected working set
+/// fits in `max_memory_gb`, or `requested` itself if no cap was given.
+///
+/// Returns an error if the cap cannot accommodate even a single element per batch.
+pub fn cap_batch_size(
+    requested: usize,
+    max_memory_gb: Option<f64>,
+    bytes_per_element: usize,
+) -> Result<usize, MemoryCapError> {
+    let max_memory_gb = match max_memory_gb {
+        Some(max_memory_gb) => max_memory_gb,
+        None => return Ok(requested),
+    };
+    if max_memory_gb.is_nan() || max_memory_gb <= 0.0 {
+        return Err(MemoryCapError::NotPositive(max_memory_gb));
+    }
+
+    let budget = (max_memory_gb * BYTES_PER_GB) as usize;
+    let max_batch_size = budget / bytes_per_element.max(1);
+    if max_batch_size == 0 {
+        return Err(MemoryCapError::TooSmall(max_memory_gb, bytes_per_element));
+    }
+
+    if requested > max_batch_size {
+        warn!(
+            "reducing the batch size from {} to {} to stay within the {} GB memory cap",
+            requested, max_batch_size, max_memory_gb
+        );
+        Ok(max_batch_size)
+    } else {
+        Ok(requested)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm_curves::bls12_377::Bls12_377;
+
+    #[test]
+    fn test_cap_batch_size() {
+        // no cap
+        assert_eq!(cap_batch_size(50000, None, 1000).unwrap(), 50000);
+        // the cap is large enough
+        assert_eq!(cap_batch_size(1000, Some(1.0), 1000).unwrap(), 1000);
+        // the cap reduces the batch size
+        assert_eq!(cap_batch_size(1 << 30, Some(1.0), 1024).unwrap(), 1 << 20);
+        // the cap is too small
+        assert!(cap_batch_size(1000, Some(1e-9), 1 << 20).is_err());
+        assert!(cap_batch_size(1000, Some(0.0), 1000).is_err());
+    }
+
+    #[test]
+    fn test_check_memory_cap() {
+        assert!(check_memory_cap(1 << 40, None).is_ok());
+        assert!(check_memory_cap(1 << 30, Some(1.0)).is_ok());
+        assert!(matches!(
+            check_memory_cap((1 << 30) + 1, Some(1.0)),
+            Err(MemoryCapError::Exceeded(_, _))
+        ));
+        assert!(check_memory_cap(0, Some(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_bytes_per_element() {
+        assert!(verify_bytes_per_element::<Bls12_377>() > contribute_bytes_per_element::<Bls12_377>());
+    }
+}
@@ -0,0 +1,208 @@
+//! The metrics of a command in the Prometheus text format. Generating or verifying
+//! a transcript runs as a batch job rather than a service which can be scraped, so
+//! once the command finished its metrics are written to a snapshot file, e.g. for
+//! the textfile collector of the node exporter, or pushed to a Pushgateway.
+use super::{output::CommandOutput, paths::tmp_path, permissions};
+
+use once_cell::sync::Lazy;
+use std::{
+    fmt::Write as _,
+    io::{Read, Write},
+    net::TcpStream,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// The chunks and points processed by the command so far, over all of its progress reporters.
+static PROCESSED: Lazy<Mutex<Processed>> = Lazy::new(Default::default);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Processed {
+    chunks: usize,
+    points: u64,
+    elapsed: Duration,
+}
+
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    #[error("the Pushgateway URL {0} is invalid, expected http://<host>[:<port>]")]
+    InvalidPushgateway(String),
+    #[error("the Pushgateway rejected the metrics: {0}")]
+    PushRejected(String),
+}
+
+/// Records the chunks and points a progress reporter processed, once it finished.
+pub fn record_processed(chunks: usize, points: u64, elapsed: Duration) {
+    let mut processed = PROCESSED.lock().expect("metrics lock poisoned");
+    processed.chunks += chunks;
+    processed.points += points;
+    processed.elapsed += elapsed;
+}
+
+/// Returns the metrics of the finished command in the Prometheus text format.
+pub fn render(output: &CommandOutput) -> String {
+    let processed = *PROCESSED.lock().expect("metrics lock poisoned");
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    render_processed(output, processed, timestamp)
+}
+
+fn render_processed(output: &CommandOutput, processed: Processed, timestamp: u64) -> String {
+    let points_per_second = match processed.elapsed.as_secs_f64() {
+        seconds if seconds > 0.0 => processed.points as f64 / seconds,
+        _ => 0.0,
+    };
+
+    let mut metrics = String::new();
+    let mut metric = |name: &str, help: &str, value: String| {
+        let _ = writeln!(metrics, "# HELP setup2_{} {}", name, help);
+        let _ = writeln!(metrics, "# TYPE setup2_{} gauge", name);
+        let _ = writeln!(metrics, "setup2_{}{{command=\"{}\"}} {}", name, output.command, value);
+    };
+    metric(
+        "command_success",
+        "Whether the command succeeded.",
+        (output.success as u8).to_string(),
+    );
+    metric(
+        "command_exit_code",
+        "The exit code of the command.",
+        output.exit_code.to_string(),
+    );
+    metric(
+        "command_duration_seconds",
+        "How long the command ran.",
+        (output.elapsed_ms as f64 / 1000.0).to_string(),
+    );
+    metric(
+        "command_last_run_timestamp_seconds",
+        "When the command finished, as a Unix timestamp.",
+        timestamp.to_string(),
+    );
+    metric(
+        "chunks_processed",
+        "The number of chunks the command processed.",
+        processed.chunks.to_string(),
+    );
+    metric(
+        "points_processed",
+        "The number of points the command processed.",
+        processed.points.to_string(),
+    );
+    metric(
+        "points_per_second",
+        "The throughput of the command, in points per second.",
+        format!("{:.3}", points_per_second),
+    );
+    metrics
+}
+
+/// Writes the metrics to the file at `path`, replacing it at once so that collectors never read a partial file.
+fn write_snapshot(path: &str, metrics: &str) -> anyhow::Result<()> {
+    let tmp = tmp_path(path);
+    permissions::write(&tmp, metrics)?;
+    fs_err::rename(tmp, path)?;
+    Ok(())
+}
+
+/// Returns the address and the path of the group of the command at the Pushgateway with the given URL.
+fn push_target(url: &str, command: &str) -> Result<(String, String), MetricsError> {
+    let invalid = || MetricsError::InvalidPushgateway(url.to_string());
+    let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+    let (authority, base) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    if authority.is_empty() {
+        return Err(invalid());
+    }
+    let address = match authority.contains(':') {
+        true => authority.to_string(),
+        false => format!("{}:80", authority),
+    };
+    let path = format!("{}/metrics/job/setup2/command/{}", base.trim_end_matches('/'), command);
+    Ok((address, path))
+}
+
+/// Pushes the metrics of the command to the Pushgateway with the given URL, replacing
+/// the metrics the previous run of the same command pushed.
+fn push(url: &str, command: &str, metrics: &str) -> anyhow::Result<()> {
+    let (address, path) = push_target(url, command)?;
+    let mut stream = TcpStream::connect(&address)?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    write!(
+        stream,
+        "PUT {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        address,
+        metrics.len(),
+        metrics
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(MetricsError::PushRejected(status.to_string()).into()),
+    }
+}
+
+/// Writes the metrics of the finished command to the snapshot file and pushes them to the Pushgateway, if given.
+pub fn export_metrics(output: &CommandOutput, file: Option<&str>, pushgateway: Option<&str>) -> anyhow::Result<()> {
+    if file.is_none() && pushgateway.is_none() {
+        return Ok(());
+    }
+    let metrics = render(output);
+    if let Some(file) = file {
+        write_snapshot(file, &metrics)?;
+    }
+    if let Some(pushgateway) = pushgateway {
+        push(pushgateway, output.command, &metrics)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let processed = Processed {
+            chunks: 3,
+            points: 300,
+            elapsed: Duration::from_secs(2),
+        };
+        let output = CommandOutput::new("verify", &Ok(()), Duration::from_millis(2500));
+        let metrics = render_processed(&output, processed, 1_600_000_000);
+        assert!(metrics.contains("# TYPE setup2_command_success gauge\nsetup2_command_success{command=\"verify\"} 1\n"));
+        assert!(metrics.contains("setup2_command_duration_seconds{command=\"verify\"} 2.5\n"));
+        assert!(metrics.contains("setup2_chunks_processed{command=\"verify\"} 3\n"));
+        assert!(metrics.contains("setup2_points_per_second{command=\"verify\"} 150.000\n"));
+        assert!(metrics.contains("setup2_command_last_run_timestamp_seconds{command=\"verify\"} 1600000000\n"));
+    }
+
+    #[test]
+    fn test_push_target() {
+        assert_eq!(
+            push_target("http://pushgateway:9091", "new").unwrap(),
+            (
+                "pushgateway:9091".to_string(),
+                "/metrics/job/setup2/command/new".to_string()
+            )
+        );
+        assert_eq!(
+            push_target("http://monitoring/push/", "verify").unwrap(),
+            (
+                "monitoring:80".to_string(),
+                "/push/metrics/job/setup2/command/verify".to_string()
+            )
+        );
+        assert!(push_target("https://pushgateway:9091", "new").is_err());
+        assert!(push_target("http://", "new").is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use super::{
+    ceremony::{migrate as migrate_ceremony, CeremonyConfig, HashAlgorithm, CEREMONY_FORMAT_VERSION},
+    new::{curve_from_str, CurveKind},
+};
+use setup_utils::UseCompression;
+
+use gumdrop::Options;
+use std::path::Path;
+use tracing::info;
+
+#[derive(Debug, Options, Clone)]
+pub struct MigrateOpts {
+    help: bool,
+    #[options(help = "the ceremony directory to upgrade to the latest layout", default = ".")]
+    pub dir: String,
+
+    // The following are only required for legacy ceremony directories without a manifest
+    #[options(help = "the initial transcript file name (legacy ceremonies only)")]
+    pub transcript: Option<String>,
+    #[options(help = "the phase1 size the ceremony was created with (legacy ceremonies only)")]
+    pub phase1_size: Option<u32>,
+    #[options(
+        help = "the elliptic curve the ceremony was created with (legacy ceremonies only)",
+        default = "bls12_377",
+        parse(try_from_str = "curve_from_str")
+    )]
+    pub curve_type: CurveKind,
+    #[options(help = "whether the ceremony is for the inner circuit (legacy ceremonies only)")]
+    pub is_inner: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+pub fn migrate(opts: &MigrateOpts) -> anyhow::Result<()> {
+    // Legacy ceremonies were always uncompressed and hashed with Blake2b
+    let legacy = match (&opts.transcript, opts.phase1_size) {
+        (Some(transcript), Some(phase1_size)) => Some(CeremonyConfig {
+            curve: opts.curve_type,
+            is_inner: opts.is_inner,
+            phase1_size,
+            compression: UseCompression::No,
+            hash_algorithm: HashAlgorithm::Blake2b,
+            transcript: transcript.clone(),
+            circuit_digest: None,
+            vdf_iterations: None,
+        }),
+        _ => None,
+    };
+
+    let from = migrate_ceremony(Path::new(&opts.dir), legacy)?;
+    if from == CEREMONY_FORMAT_VERSION {
+        info!("{} is already at format version {}", opts.dir, from);
+    } else {
+        info!(
+            "migrated {} from format version {} to {}",
+            opts.dir, from, CEREMONY_FORMAT_VERSION
+        );
+    }
+
+    Ok(())
+}
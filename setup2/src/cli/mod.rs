@@ -1,12 +1,117 @@
-pub use new::{new, NewOpts};
+pub use new::{new, GenerationError, NewOpts};
 mod new;
 
 mod contribute;
-pub use contribute::{contribute, ContributeOpts};
+pub use contribute::{contribute, ContributeOpts, ResumeError};
+
+#[cfg(feature = "coordinator-client")]
+mod contribute_loop;
+#[cfg(feature = "coordinator-client")]
+pub use contribute_loop::{contribute_loop, ContributeLoopError, ContributeLoopOpts};
+
+#[cfg(feature = "cloud")]
+mod cloud;
+#[cfg(feature = "cloud")]
+pub use cloud::CloudError;
+
+mod constraints;
+pub use constraints::{constraints, ConstraintsOpts};
 
 mod verify;
 pub use verify::{verify, VerifyOpts};
 
+mod attestation;
+pub use attestation::{verify_attestation, AttestationError, VerifyAttestationOpts};
+
+mod keystore;
+pub use keystore::{new_keystore, KeystoreError, NewKeystoreOpts};
+
+mod verify_round;
+pub use verify_round::{verify_round, VerifyRoundError, VerifyRoundOpts};
+
+mod beacon;
+pub use beacon::beacon;
+
+mod beacon_vdf;
+pub use beacon_vdf::{beacon_vdf, BeaconVdfError, BeaconVdfOpts};
+
+mod beacon_sources;
+pub use beacon_sources::BeaconSourceError;
+
+mod chunks;
+pub use chunks::ChunkError;
+
+mod paths;
+pub use paths::PathError;
+
+mod input_format;
+pub use input_format::InputFormatError;
+
+mod ceremony;
+pub use ceremony::{CeremonyError, CeremonyManifest};
+
+mod determinism;
+pub use determinism::{compare_verdicts, CompareVerdictsOpts, DeterminismError};
+
+mod compat_check;
+pub use compat_check::{compat_check, CompatCheckOpts, IncompatibleError};
+
+mod finalization;
+pub use finalization::{approve_finalization, ApproveFinalizationOpts};
+
+mod checkpoint;
+pub use checkpoint::{sign_checkpoint, verify_transcript, CheckpointError, SignCheckpointOpts, VerifyTranscriptOpts};
+
+mod git_mode;
+pub use git_mode::{git_mode, GitModeError, GitModeOpts};
+
+mod pipeline;
+pub use pipeline::{run_pipeline, PipelineError, RunPipelineOpts};
+
+mod organizers;
+pub use organizers::OrganizerError;
+
+mod provenance;
+pub use provenance::{verify_provenance, ProvenanceError, VerifyProvenanceOpts};
+
+mod pok;
+pub use pok::{export_pok, ExportPokOpts};
+
+mod aggregate;
+pub use aggregate::{aggregate, AggregateError, AggregateOpts};
+
+mod test_vectors;
+pub use test_vectors::{test_vectors, TestVectorError, TestVectorsOpts};
+
+mod golden;
+pub use golden::{verify_golden, GoldenError, VerifyGoldenOpts};
+
+mod progress;
+
+mod metrics;
+pub use metrics::export_metrics;
+
+mod r1cs;
+pub use r1cs::R1csError;
+
+mod status;
+pub use status::{status, StatusOpts};
+
+mod check_hashes;
+pub use check_hashes::{check_hashes, CheckHashesError, CheckHashesOpts};
+
+mod migrate;
+pub use migrate::{migrate, MigrateOpts};
+
+mod memory;
+pub use memory::MemoryCapError;
+
+mod permissions;
+pub use permissions::ArtifactPermissions;
+
+mod output;
+pub use output::{CommandOutput, ExitCode};
+
 use gumdrop::Options;
 
 // The supported commands
@@ -14,12 +119,122 @@ use gumdrop::Options;
 pub enum Command {
     #[options(help = "creates new parameters for the ceremony which MUST be built upon")]
     New(NewOpts),
+    #[options(help = "count the constraints of the circuit and the powers its ceremony needs")]
+    Constraints(ConstraintsOpts),
     #[options(help = "contribute to ceremony by transforming the circuit parameters")]
     Contribute(ContributeOpts),
-    #[options(help = "contribute randomness via a random beacon (e.g. a bitcoin block header hash)")]
+    #[cfg(feature = "coordinator-client")]
+    #[options(help = "contribute to every chunk the coordinator hands out until the round is finished")]
+    ContributeLoop(ContributeLoopOpts),
+    #[options(
+        help = "apply a random beacon (e.g. a bitcoin block header hash) as the final contribution to every chunk"
+    )]
     Beacon(ContributeOpts),
+    #[options(help = "evaluate or verify a verifiable delay function on the beacon hash")]
+    BeaconVdf(BeaconVdfOpts),
     #[options(help = "verify the contributions so far")]
     Verify(VerifyOpts),
+    #[options(help = "verify the responses of every chunk of a round in parallel, reporting the verdict of each chunk")]
+    VerifyRound(VerifyRoundOpts),
+    #[options(help = "verify the signature of a contribution attestation, and that the contribution was made")]
+    VerifyAttestation(VerifyAttestationOpts),
+    #[options(help = "encrypt a new or existing Aleo private key under a passphrase in a keystore")]
+    NewKeystore(NewKeystoreOpts),
+    #[options(help = "check that the verdict reports of independently built verifiers agree")]
+    CompareVerdicts(CompareVerdictsOpts),
+    #[options(help = "upgrade a ceremony directory to the latest layout")]
+    Migrate(MigrateOpts),
+    #[options(help = "check that this binary can continue the ceremony in a directory")]
+    CompatCheck(CompatCheckOpts),
+    #[options(help = "show the state of a ceremony directory and the command to run next")]
+    Status(StatusOpts),
+    #[options(help = "check the downloaded chunks of a ceremony directory against the hashes in its manifest")]
+    CheckHashes(CheckHashesOpts),
+    #[options(help = "verify the final transcript and sign an approval of its finalization as an organizer")]
+    ApproveFinalization(ApproveFinalizationOpts),
+    #[options(help = "verify the transcript and sign a checkpoint verification can later resume from")]
+    SignCheckpoint(SignCheckpointOpts),
+    #[options(help = "verify the final transcript, resuming from the latest signed checkpoint")]
+    VerifyTranscript(VerifyTranscriptOpts),
+    #[options(help = "verify the signed provenance statement of exported keys against the files")]
+    VerifyProvenance(VerifyProvenanceOpts),
+    #[options(help = "combine the final responses of the chunks into the proving and verifying keys")]
+    Aggregate(AggregateOpts),
+    #[options(help = "export the proof of knowledge of every contribution for external audit tools")]
+    ExportPok(ExportPokOpts),
+    #[options(help = "run an entire ceremony as described by a pipeline file, resuming where a previous run stopped")]
+    RunPipeline(RunPipelineOpts),
+    #[options(help = "verify a contribution made as a pull request to a git repository, and merge it with --merge")]
+    GitMode(GitModeOpts),
+    #[options(help = "generate the artifacts of a tiny ceremony from a fixed seed, for testing other implementations")]
+    TestVectors(TestVectorsOpts),
+    #[options(help = "check that the serialization reproduces the recorded golden files bit for bit (development)")]
+    VerifyGolden(VerifyGoldenOpts),
+}
+
+impl Command {
+    /// Returns the name of the command, as used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::New(_) => "new",
+            Command::Constraints(_) => "constraints",
+            Command::Contribute(_) => "contribute",
+            #[cfg(feature = "coordinator-client")]
+            Command::ContributeLoop(_) => "contribute-loop",
+            Command::Beacon(_) => "beacon",
+            Command::BeaconVdf(_) => "beacon-vdf",
+            Command::Verify(_) => "verify",
+            Command::VerifyRound(_) => "verify-round",
+            Command::VerifyAttestation(_) => "verify-attestation",
+            Command::NewKeystore(_) => "new-keystore",
+            Command::CompareVerdicts(_) => "compare-verdicts",
+            Command::Migrate(_) => "migrate",
+            Command::CompatCheck(_) => "compat-check",
+            Command::Status(_) => "status",
+            Command::CheckHashes(_) => "check-hashes",
+            Command::ApproveFinalization(_) => "approve-finalization",
+            Command::SignCheckpoint(_) => "sign-checkpoint",
+            Command::VerifyTranscript(_) => "verify-transcript",
+            Command::VerifyProvenance(_) => "verify-provenance",
+            Command::Aggregate(_) => "aggregate",
+            Command::ExportPok(_) => "export-pok",
+            Command::RunPipeline(_) => "run-pipeline",
+            Command::GitMode(_) => "git-mode",
+            Command::TestVectors(_) => "test-vectors",
+            Command::VerifyGolden(_) => "verify-golden",
+        }
+    }
+
+    /// Returns `true` if the result should be printed as JSON.
+    pub fn json(&self) -> bool {
+        match self {
+            Command::New(opt) => opt.json,
+            Command::Constraints(opt) => opt.json,
+            Command::Contribute(opt) | Command::Beacon(opt) => opt.json,
+            #[cfg(feature = "coordinator-client")]
+            Command::ContributeLoop(opt) => opt.json,
+            Command::BeaconVdf(opt) => opt.json(),
+            Command::Verify(opt) => opt.json,
+            Command::VerifyRound(opt) => opt.json,
+            Command::VerifyAttestation(opt) => opt.json,
+            Command::NewKeystore(opt) => opt.json,
+            Command::CompareVerdicts(opt) => opt.json,
+            Command::Migrate(opt) => opt.json,
+            Command::CompatCheck(opt) => opt.json,
+            Command::Status(opt) => opt.json,
+            Command::CheckHashes(opt) => opt.json,
+            Command::ApproveFinalization(opt) => opt.json,
+            Command::SignCheckpoint(opt) => opt.json,
+            Command::VerifyTranscript(opt) => opt.json,
+            Command::VerifyProvenance(opt) => opt.json,
+            Command::Aggregate(opt) => opt.json,
+            Command::ExportPok(opt) => opt.json,
+            Command::RunPipeline(opt) => opt.json,
+            Command::GitMode(opt) => opt.json,
+            Command::TestVectors(opt) => opt.json,
+            Command::VerifyGolden(opt) => opt.json,
+        }
+    }
 }
 
 #[derive(Debug, Options, Clone)]
@@ -27,6 +242,23 @@ pub struct SNARKOpts {
     help: bool,
     // #[options(help = "the size of batches to process", default = "256")]
     // pub batch_size: usize,
+    #[options(
+        help = "the mode of the files written, in octal (e.g. 640), instead of the one of the umask",
+        parse(try_from_str = "permissions::parse_mode")
+    )]
+    pub file_mode: Option<u32>,
+    #[options(
+        help = "the mode of the directories created, in octal (e.g. 750), instead of the one of the umask",
+        parse(try_from_str = "permissions::parse_mode")
+    )]
+    pub dir_mode: Option<u32>,
+    #[options(help = "the group (name or ID) to give the files written and directories created")]
+    pub group: Option<String>,
+    #[options(help = "write the metrics of the command in the Prometheus text format to this file once it finished")]
+    pub metrics_file: Option<String>,
+    #[options(help = "push the metrics of the command to the Prometheus Pushgateway at this http URL once it finished")]
+    pub pushgateway: Option<String>,
+
     #[options(command)]
     pub command: Option<Command>,
 }
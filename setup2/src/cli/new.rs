@@ -1,54 +1,134 @@
-use phase2::parameters::{circuit_to_qap, MPCParameters};
-use setup_utils::{log_2, CheckForCorrectness, Groth16Params, UseCompression};
+use phase2::parameters::{circuit_to_qap, qap_digest, MPCParameters};
+use setup_utils::{calculate_hash, CheckForCorrectness, Error as SetupError, Groth16Params, UseCompression};
 use snarkvm_algorithms::{SNARK, SRS};
 use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
 use snarkvm_dpc::{
     parameters::testnet2::{Testnet2DPC, Testnet2Parameters},
     prelude::*,
 };
-use snarkvm_fields::Field;
-use snarkvm_r1cs::{ConstraintCounter, ConstraintSynthesizer};
+use snarkvm_r1cs::{ConstraintSynthesizer, SynthesisError};
 
+use super::{
+    beacon_vdf::BeaconVdfError,
+    ceremony::{CeremonyArtifacts, CeremonyConfig, CeremonyError, CeremonyManifest, HASH_ALGORITHM},
+    chunks::ChunkError,
+    constraints::CircuitSize,
+    input_format::{check_transcript_size, detect_compression, input_format_from_str, InputFormat},
+    memory::{check_memory_cap, new_bytes},
+    paths::{artifact_path, chunk_path, hash_path, query_path, tmp_path, PathError},
+    permissions,
+    progress::{Progress, ProgressMode},
+    r1cs::R1csCircuit,
+};
 use fs_err::OpenOptions;
 use gumdrop::Options;
-use memmap::MmapOptions;
+use memmap::{Mmap, MmapMut, MmapOptions};
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaChaRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use thiserror::Error;
+use tracing::info;
 
-type AleoInner = <Testnet2Parameters as Parameters>::InnerCurve;
-type AleoOuter = <Testnet2Parameters as Parameters>::OuterCurve;
-type ZexeInner = Bls12_377;
-type ZexeOuter = BW6_761;
+pub(super) type AleoInner = <Testnet2Parameters as Parameters>::InnerCurve;
+pub(super) type AleoOuter = <Testnet2Parameters as Parameters>::OuterCurve;
+pub(super) type ZexeInner = Bls12_377;
+pub(super) type ZexeOuter = BW6_761;
 
-const COMPRESSION: UseCompression = UseCompression::No;
+/// Returns the compression of the elements of a file, as given by a `--compressed-*` flag.
+pub(super) fn use_compression(compressed: bool) -> UseCompression {
+    match compressed {
+        true => UseCompression::Yes,
+        false => UseCompression::No,
+    }
+}
 
 pub const SEED_LENGTH: usize = 32;
 pub type Seed = [u8; SEED_LENGTH];
 
-#[derive(Debug, Clone)]
+/// The name of the file recording the progress of `new`, stored in the ceremony directory.
+pub const PROGRESS_FILENAME: &str = "new.progress.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CurveKind {
     Bls12_377,
     BW6,
 }
 
+/// The curves this binary has engines for, which ceremonies over other curves cannot be continued without.
+pub const CURVES: &[CurveKind] = &[CurveKind::Bls12_377, CurveKind::BW6];
+
 pub fn curve_from_str(src: &str) -> std::result::Result<CurveKind, String> {
     let curve = match src.to_lowercase().as_str() {
         "bls12_377" => CurveKind::Bls12_377,
         "bw6" => CurveKind::BW6,
+        // The pinned snarkVM has no BLS12-381 engine, so there are no parameters to generate over it
+        "bls12_381" => return Err("bls12_381 is not supported by this version of snarkVM.".to_string()),
         _ => return Err("unsupported curve.".to_string()),
     };
     Ok(curve)
 }
 
+/// The circuit whose parameters are generated.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CircuitKind {
+    Inner,
+    Outer,
+}
+
+impl CircuitKind {
+    pub fn is_inner(self) -> bool {
+        self == CircuitKind::Inner
+    }
+
+    /// Returns the curve the parameters of the circuit are generated over, whatever `--curve-type` is.
+    pub fn curve(self) -> CurveKind {
+        match self {
+            CircuitKind::Inner => CurveKind::Bls12_377,
+            CircuitKind::Outer => CurveKind::BW6,
+        }
+    }
+}
+
+pub fn circuit_from_str(src: &str) -> std::result::Result<CircuitKind, String> {
+    let circuit = match src.to_lowercase().as_str() {
+        "inner" => CircuitKind::Inner,
+        "outer" => CircuitKind::Outer,
+        _ => return Err(format!("unsupported circuit {}, expected `inner` or `outer`.", src)),
+    };
+    Ok(circuit)
+}
+
 #[derive(Debug, Options, Clone)]
 pub struct NewOpts {
-    help: bool,
+    pub(super) help: bool,
     #[options(help = "the path to the phase1 parameters", default = "phase1")]
     pub phase1: String,
     #[options(help = "the total number of coefficients (in powers of 2) which were created after processing phase 1")]
     pub phase1_size: u32,
-    #[options(help = "the challenge file name to be created", default = "challenge")]
+    #[options(
+        help = "the challenge file name to be created, or an s3:// or gs:// object URL with the `cloud` feature",
+        default = "challenge"
+    )]
     pub output: String,
+    #[options(help = "the phase1 parameters hold compressed elements, same as `--input-format compressed`")]
+    pub compressed_input: bool,
+    #[options(
+        help = "whether the phase1 parameters are `compressed` or `uncompressed`, detected from their size if `auto`",
+        default = "auto",
+        parse(try_from_str = "input_format_from_str")
+    )]
+    pub input_format: InputFormat,
 
     #[options(
         help = "the elliptic curve to use",
@@ -57,108 +137,523 @@ pub struct NewOpts {
     )]
     pub curve_type: CurveKind,
 
-    #[options(help = "setup the inner or the outer circuit?")]
-    pub is_inner: bool,
+    #[options(
+        help = "the circuit to setup, `inner` or `outer`",
+        default = "outer",
+        parse(try_from_str = "circuit_from_str")
+    )]
+    pub circuit: CircuitKind,
+
+    #[options(
+        help = "generate the parameters of the R1CS circuit in this JSON file over the curve of --curve-type instead"
+    )]
+    pub circuit_file: Option<String>,
+
+    #[options(
+        help = "also split the challenge into query parameters and chunks of the H and L queries with this many elements"
+    )]
+    pub chunk_size: Option<usize>,
+
+    #[options(
+        help = "require the final beacon to be delayed by a beacon-vdf proof with this many sequential squarings"
+    )]
+    pub vdf_iterations: Option<u64>,
+
+    #[options(
+        help = "the maximum memory (in GB) to use, which fails before reading phase 1 if the parameters need more"
+    )]
+    pub max_memory_gb: Option<f64>,
+
+    #[options(help = "do not show the progress bar")]
+    pub quiet: bool,
+    #[options(help = "write the progress to stderr as a JSON object per line")]
+    pub progress_json: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
 }
 
 pub fn new(opt: &NewOpts) -> anyhow::Result<()> {
-    if opt.is_inner {
-        let circuit = InnerCircuit::<Testnet2Parameters>::blank();
-        generate_params::<AleoInner, ZexeInner, _>(opt, circuit)
+    #[cfg(feature = "cloud")]
+    if super::cloud::is_object_url(&opt.output) || super::cloud::is_object_url(&opt.phase1) {
+        return super::cloud::new_in_bucket(opt);
+    }
+    if let Some(circuit_file) = &opt.circuit_file {
+        let circuit_file = Path::new(circuit_file);
+        return match opt.curve_type {
+            CurveKind::Bls12_377 => generate_params::<Bls12_377, Bls12_377, _>(opt, R1csCircuit::load(circuit_file)?),
+            CurveKind::BW6 => generate_params::<BW6_761, BW6_761, _>(opt, R1csCircuit::load(circuit_file)?),
+        };
+    }
+    match opt.circuit {
+        CircuitKind::Inner => generate_params::<AleoInner, ZexeInner, _>(opt, inner_circuit()),
+        CircuitKind::Outer => generate_params::<AleoOuter, ZexeOuter, _>(opt, outer_circuit()?),
+    }
+}
+
+/// Returns the hex encoded digest of the inner or the outer circuit built by this binary.
+pub fn circuit_digest(is_inner: bool) -> anyhow::Result<String> {
+    let digest = if is_inner {
+        qap_digest(&circuit_to_qap::<AleoInner, ZexeInner, _>(inner_circuit())?)?
     } else {
-        let mut seed: Seed = [0; SEED_LENGTH];
-        rand::thread_rng().fill_bytes(&mut seed[..]);
-        let rng = &mut ChaChaRng::from_seed(seed);
-        let dpc = Testnet2DPC::load(false)?;
-
-        let noop_circuit = dpc
-            .noop_program
-            .find_circuit_by_index(0)
-            .ok_or(DPCError::MissingNoopCircuit)?;
-        let private_program_input = dpc.noop_program.execute_blank(noop_circuit.circuit_id())?;
-
-        let inner_snark_parameters = <Testnet2Parameters as Parameters>::InnerSNARK::setup(
-            &InnerCircuit::<Testnet2Parameters>::blank(),
-            &mut SRS::CircuitSpecific(rng),
-        )?;
-
-        let inner_snark_vk: <<Testnet2Parameters as Parameters>::InnerSNARK as SNARK>::VerifyingKey =
-            inner_snark_parameters.1.clone().into();
-        let inner_snark_proof = <Testnet2Parameters as Parameters>::InnerSNARK::prove(
-            &inner_snark_parameters.0,
-            &InnerCircuit::<Testnet2Parameters>::blank(),
-            rng,
-        )?;
-
-        let circuit =
-            OuterCircuit::<Testnet2Parameters>::blank(inner_snark_vk, inner_snark_proof, private_program_input);
-        generate_params::<AleoOuter, ZexeOuter, _>(opt, circuit)
-    }
-}
-
-/// Returns the number of powers required for the Phase 2 ceremony
-/// = log2(aux + inputs + constraints)
-fn ceremony_size<F: Field, C: Clone + ConstraintSynthesizer<F>>(circuit: &C) -> usize {
-    let mut counter = ConstraintCounter {
-        num_public_variables: 0,
-        num_private_variables: 0,
-        num_constraints: 0,
+        qap_digest(&circuit_to_qap::<AleoOuter, ZexeOuter, _>(outer_circuit()?)?)?
     };
-    circuit
-        .clone()
-        .generate_constraints(&mut counter)
-        .expect("could not calculate number of required constraints");
-    let phase2_size = std::cmp::max(
-        counter.num_constraints,
-        counter.num_private_variables + counter.num_public_variables + 1,
-    );
-    let power = log_2(phase2_size) as u32;
-
-    // get the nearest power of 2
-    if phase2_size < 2usize.pow(power) {
-        2usize.pow(power + 1)
-    } else {
-        phase2_size
+    Ok(hex::encode(&digest[..]))
+}
+
+pub(super) fn inner_circuit() -> InnerCircuit<Testnet2Parameters> {
+    InnerCircuit::<Testnet2Parameters>::blank()
+}
+
+pub(super) fn outer_circuit() -> anyhow::Result<OuterCircuit<Testnet2Parameters>> {
+    let mut seed: Seed = [0; SEED_LENGTH];
+    rand::thread_rng().fill_bytes(&mut seed[..]);
+    let rng = &mut ChaChaRng::from_seed(seed);
+    let dpc = Testnet2DPC::load(false)?;
+
+    let noop_circuit = dpc
+        .noop_program
+        .find_circuit_by_index(0)
+        .ok_or(DPCError::MissingNoopCircuit)?;
+    let private_program_input = dpc.noop_program.execute_blank(noop_circuit.circuit_id())?;
+
+    let inner_snark_parameters = <Testnet2Parameters as Parameters>::InnerSNARK::setup(
+        &InnerCircuit::<Testnet2Parameters>::blank(),
+        &mut SRS::CircuitSpecific(rng),
+    )?;
+
+    let inner_snark_vk: <<Testnet2Parameters as Parameters>::InnerSNARK as SNARK>::VerifyingKey =
+        inner_snark_parameters.1.clone().into();
+    let inner_snark_proof = <Testnet2Parameters as Parameters>::InnerSNARK::prove(
+        &inner_snark_parameters.0,
+        &InnerCircuit::<Testnet2Parameters>::blank(),
+        rng,
+    )?;
+
+    Ok(OuterCircuit::<Testnet2Parameters>::blank(
+        inner_snark_vk,
+        inner_snark_proof,
+        private_program_input,
+    ))
+}
+
+/// The artifacts `new` has written, saved after each of them, so that an interrupted
+/// run resumes after the last one instead of generating the parameters again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct GenerationProgress {
+    /// The hex encoded digest of the circuit the parameters are generated for.
+    circuit_digest: String,
+    phase1_size: u32,
+    /// The hex encoded hash of the phase 1 transcript, which must not change between runs.
+    phase1_hash: Option<String>,
+    chunk_size: Option<usize>,
+    /// The file name of the initial transcript, relative to the ceremony directory.
+    transcript: String,
+    /// The hex encoded hash of the initial transcript, once it was written.
+    transcript_hash: Option<String>,
+    /// The hex encoded hash of the query parameters, once they were written.
+    query_hash: Option<String>,
+    /// The hex encoded hashes of the chunks, by index, once they were written.
+    chunk_hashes: Vec<Option<String>>,
+}
+
+impl GenerationProgress {
+    /// Returns `true` if the progress was saved by a run with the same parameters.
+    fn resumes(&self, other: &Self) -> bool {
+        self.circuit_digest == other.circuit_digest
+            && self.phase1_size == other.phase1_size
+            && self.phase1_hash == other.phase1_hash
+            && self.chunk_size == other.chunk_size
+            && self.transcript == other.transcript
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        // Write to a temporary file first so an interrupted write never leaves truncated progress
+        let tmp = tmp_path(path);
+        permissions::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs_err::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// The errors of generating the parameters, with the file they occurred on.
+#[derive(Debug, Error)]
+pub enum GenerationError {
+    #[error("the circuit could not be synthesized: {0}")]
+    Synthesis(#[from] SynthesisError),
+    #[error("the circuit needs a phase 1 transcript of at least 2^{min_phase1_size} powers, not 2^{phase1_size}")]
+    Phase1TooSmall { phase1_size: u32, min_phase1_size: u32 },
+    #[error("reading the phase 1 transcript {} failed: {source}", .path.display())]
+    Phase1 { path: PathBuf, source: SetupError },
+    #[error("generating the phase 2 parameters failed: {0}")]
+    Phase2(SetupError),
+    #[error("reading {} failed: {source}", .path.display())]
+    Read { path: PathBuf, source: SetupError },
+    #[error("mapping {} into memory failed: {source}", .path.display())]
+    Map { path: PathBuf, source: io::Error },
+    #[error("writing {} failed: {source}", .path.display())]
+    Write { path: PathBuf, source: SetupError },
+}
+
+fn map_error(path: &Path) -> impl FnOnce(io::Error) -> GenerationError + '_ {
+    move |source| GenerationError::Map {
+        path: path.to_path_buf(),
+        source,
     }
 }
 
+/// Memory maps the file at `path` to read it.
+fn map(path: &Path) -> Result<Mmap, GenerationError> {
+    let file = fs_err::File::open(path).map_err(map_error(path))?;
+    unsafe { MmapOptions::new().map(file.file()) }.map_err(map_error(path))
+}
+
+/// Memory maps the file at `path` to read and write it.
+fn map_mut(path: &Path) -> Result<MmapMut, GenerationError> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(map_error(path))?;
+    unsafe { MmapOptions::new().map_mut(file.file()) }.map_err(map_error(path))
+}
+
+/// Returns the hex encoded hash of the file at `path`.
+pub(super) fn file_hash(path: &Path) -> Result<String, GenerationError> {
+    Ok(hex::encode(&calculate_hash(&map(path)?)[..]))
+}
+
+/// Returns `true` if the artifact at `path` was completely written with the given hash.
+fn is_written(path: &Path, hash: Option<&String>) -> anyhow::Result<bool> {
+    match hash {
+        Some(hash) if path.exists() => Ok(file_hash(path)? == *hash),
+        _ => Ok(false),
+    }
+}
+
+/// Writes an artifact to a temporary file which replaces `path` once it is complete,
+/// and returns the hex encoded hash of the artifact.
+fn write_artifact(
+    path: &Path,
+    write: impl FnOnce(&mut fs_err::File) -> Result<(), SetupError>,
+) -> Result<String, GenerationError> {
+    let written = || -> Result<(), SetupError> {
+        let tmp = tmp_path(path);
+        write(&mut permissions::create(&tmp)?)?;
+        fs_err::rename(tmp, path)?;
+        Ok(())
+    };
+    written().map_err(|source| GenerationError::Write {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    file_hash(path)
+}
+
+/// Writes the hex encoded `hash` of the chunk at `chunk` next to it, for participants to check their download against.
+pub(super) fn write_hash(chunk: &Path, hash: &str) -> Result<(), GenerationError> {
+    write_artifact(&hash_path(chunk), |output| {
+        Ok(std::io::Write::write_all(output, hash.as_bytes())?)
+    })?;
+    Ok(())
+}
+
 pub fn generate_params<Aleo: PairingEngine, Zexe: PairingEngine, C: Clone + ConstraintSynthesizer<Aleo::Fr>>(
     opt: &NewOpts,
     circuit: C,
 ) -> anyhow::Result<()> {
-    let phase1_transcript = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(&opt.phase1)
-        .expect("could not read phase 1 transcript file");
-    let mut phase1_transcript = unsafe {
-        MmapOptions::new()
-            .map_mut(phase1_transcript.file())
-            .expect("unable to create a memory map for input")
+    // The ceremony directory is the one containing the initial transcript
+    let output_path = artifact_path(&opt.output)?;
+    let ceremony_dir = output_path.parent().unwrap_or_else(|| Path::new(""));
+    // The manifest records the name of the transcript, which must therefore be valid unicode
+    let transcript = output_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| PathError::NotUnicode(output_path.clone()))?
+        .to_string();
+    let manifest_path = CeremonyManifest::path(ceremony_dir);
+    if manifest_path.exists() {
+        return Err(CeremonyError::ManifestExists(manifest_path).into());
+    }
+    if opt.vdf_iterations == Some(0) {
+        return Err(BeaconVdfError::ZeroIterations.into());
+    }
+    if opt.chunk_size == Some(0) {
+        return Err(ChunkError::ZeroChunkSize.into());
+    }
+    let input_format = match opt.compressed_input {
+        true => InputFormat::Compressed,
+        false => opt.input_format,
     };
-    let mut output = OpenOptions::new()
-        .read(false)
-        .write(true)
-        .create_new(true)
-        .open(&opt.output)
-        .expect("could not open file for writing the MPC parameters ");
-
-    let phase2_size = ceremony_size(&circuit);
-    let keypair = circuit_to_qap::<Aleo, Zexe, _>(circuit)?;
-
-    // Read `num_constraints` Lagrange coefficients from the Phase1 Powers of Tau which were
-    // prepared for this step. This will fail if Phase 1 was too small.
-    let phase1 = Groth16Params::<Zexe>::read(
-        &mut phase1_transcript,
-        COMPRESSION,
-        CheckForCorrectness::No, // No need to check for correctness, since this has been processed by the coordinator.
-        2usize.pow(opt.phase1_size),
+    let phase1_path = artifact_path(&opt.phase1)?;
+    let compression = detect_compression::<Zexe>(&phase1_path, opt.phase1_size, input_format)?;
+    // A transcript of the wrong size would only fail once it is read, deep inside phase 2
+    check_transcript_size::<Zexe>(&phase1_path, opt.phase1_size, compression)?;
+    let phase1_hash = file_hash(&phase1_path)?;
+
+    let size = CircuitSize::of(&circuit).map_err(GenerationError::Synthesis)?;
+    if size.min_phase1_size > opt.phase1_size {
+        return Err(GenerationError::Phase1TooSmall {
+            phase1_size: opt.phase1_size,
+            min_phase1_size: size.min_phase1_size,
+        }
+        .into());
+    }
+    // The parameters are generated in memory as a whole, so unlike contributing and verifying,
+    // there is no batch size to reduce, and a cap they cannot fit in fails before the slow part
+    check_memory_cap(new_bytes::<Zexe>(&size), opt.max_memory_gb)?;
+    let phase2_size = size.phase2_size;
+    let keypair = circuit_to_qap::<Aleo, Zexe, _>(circuit).map_err(GenerationError::Phase2)?;
+    let circuit_digest = hex::encode(&qap_digest(&keypair)?[..]);
+
+    // Resume after the last artifact an interrupted run wrote, as long as the parameters are the same
+    let progress_path = ceremony_dir.join(PROGRESS_FILENAME);
+    let mut progress = GenerationProgress {
+        circuit_digest: circuit_digest.clone(),
+        phase1_size: opt.phase1_size,
+        phase1_hash: Some(phase1_hash.clone()),
+        chunk_size: opt.chunk_size,
+        transcript: transcript.clone(),
+        transcript_hash: None,
+        query_hash: None,
+        chunk_hashes: vec![],
+    };
+    if progress_path.exists() {
+        let saved: GenerationProgress = serde_json::from_slice(&fs_err::read(&progress_path)?)?;
+        if !saved.resumes(&progress) {
+            return Err(CeremonyError::ProgressMismatch(progress_path).into());
+        }
+        info!("resuming the generation recorded in {}", progress_path.display());
+        progress = saved;
+    } else {
+        for path in [output_path.clone(), query_path(&output_path)] {
+            if path.exists() {
+                return Err(CeremonyError::ArtifactExists(path).into());
+            }
+        }
+        progress.save(&progress_path)?;
+    }
+
+    let mpc = if is_written(&output_path, progress.transcript_hash.as_ref())? {
+        info!("the initial transcript was already written");
+        MPCParameters::read(&map(&output_path)?[..]).map_err(|source| GenerationError::Read {
+            path: output_path.clone(),
+            source,
+        })?
+    } else {
+        let mut phase1_transcript = map_mut(&phase1_path)?;
+
+        // Read `num_constraints` Lagrange coefficients from the Phase1 Powers of Tau which were
+        // prepared for this step. Phase 1 was checked above to hold enough of them.
+        let phase1 = Groth16Params::<Zexe>::read(
+            &mut phase1_transcript,
+            compression,
+            CheckForCorrectness::No, // No need to check for correctness, since this has been processed by the coordinator.
+            2usize.pow(opt.phase1_size),
+            phase2_size,
+        )
+        .map_err(|source| GenerationError::Phase1 {
+            path: phase1_path.clone(),
+            source,
+        })?;
+
+        // Generate the initial transcript
+        let mpc = MPCParameters::new(keypair, phase1).map_err(GenerationError::Phase2)?;
+        progress.transcript_hash = Some(write_artifact(&output_path, |output| mpc.write_to(output))?);
+        progress.save(&progress_path)?;
+        mpc
+    };
+
+    // The chunks are contributed to separately, and can only be verified
+    // against the query parameters, so both are written next to the challenge.
+    // They are streamed out of the full parameters rather than split off them,
+    // which would hold a second copy of the parameters in memory
+    let mut num_chunks = 0;
+    // A chunk size larger than the queries of a small circuit would be recorded in the manifest
+    // as if the chunks held that many elements, so it is clamped to the single chunk it gives
+    let chunk_size = opt.chunk_size.map(|chunk_size| {
+        let max_chunk_size = mpc.max_chunk_size();
+        if chunk_size > max_chunk_size {
+            info!(
+                "the chunk size {} is larger than the queries, writing a single chunk of {} elements",
+                chunk_size, max_chunk_size
+            );
+        }
+        std::cmp::min(chunk_size, max_chunk_size)
+    });
+    if let Some(chunk_size) = chunk_size {
+        num_chunks = mpc.num_chunks(chunk_size).map_err(GenerationError::Phase2)?;
+        let query_path = query_path(&output_path);
+        if !is_written(&query_path, progress.query_hash.as_ref())? {
+            progress.query_hash = Some(write_artifact(&query_path, |output| mpc.write_query_to(output))?);
+            progress.save(&progress_path)?;
+        }
+
+        // The chunks are serialized and written in parallel. Their hashes are recorded
+        // by index, so the progress lists them in order whichever is written first
+        progress.chunk_hashes.resize(num_chunks, None);
+        let shared = Mutex::new(progress);
+        let written = AtomicUsize::new(0);
+        let points: Vec<_> = (0..num_chunks)
+            .map(|i| mpc.chunk_points(chunk_size, i) as u64)
+            .collect();
+        let reporter = Progress::start(
+            "new",
+            ProgressMode::new(opt.quiet, opt.progress_json),
+            num_chunks,
+            points.iter().sum(),
+        );
+        (0..num_chunks)
+            .into_par_iter()
+            .try_for_each(|i| -> anyhow::Result<()> {
+                let chunk_path = chunk_path(&output_path, i);
+                let hash = shared.lock().expect("progress lock poisoned").chunk_hashes[i].clone();
+                let hash = match hash {
+                    Some(hash) if is_written(&chunk_path, Some(&hash))? => {
+                        reporter.chunk_skipped(i, points[i]);
+                        hash
+                    }
+                    _ => {
+                        let hash = write_artifact(&chunk_path, |output| mpc.write_chunk_to(chunk_size, i, output))?;
+                        let mut progress = shared.lock().expect("progress lock poisoned");
+                        progress.chunk_hashes[i] = Some(hash.clone());
+                        progress.save(&progress_path)?;
+                        reporter.chunk_done(i, points[i]);
+                        hash
+                    }
+                };
+                // The hash next to the chunk lets participants check their download before contributing
+                write_hash(&chunk_path, &hash)?;
+                let written = written.fetch_add(1, Ordering::SeqCst) + 1;
+                info!("chunk {} is written ({}/{})", i, written, num_chunks);
+                Ok(())
+            })?;
+        reporter.finish();
+        progress = shared.into_inner().expect("progress lock poisoned");
+    }
+
+    // Every artifact was written, so its hash is known
+    let artifacts = CeremonyArtifacts {
+        circuit: match opt.circuit_file {
+            Some(_) => None,
+            None => Some(opt.circuit),
+        },
         phase2_size,
-    )?;
+        transcript_hash: progress.transcript_hash.expect("the initial transcript was written"),
+        chunk_size,
+        num_chunks,
+        query_hash: progress.query_hash,
+        chunk_hashes: progress.chunk_hashes.into_iter().flatten().collect(),
+        phase1_hash: Some(phase1_hash),
+        cs_hash: Some(hex::encode(&mpc.cs_hash[..])),
+    };
 
-    // Generate the initial transcript
-    let mpc = MPCParameters::new(keypair, phase1)?;
-    mpc.write(&mut output)?;
+    // Record the ceremony parameters and artifacts next to the initial transcript
+    CeremonyManifest::new(
+        CeremonyConfig {
+            curve: match opt.circuit_file {
+                Some(_) => opt.curve_type,
+                None => opt.circuit.curve(),
+            },
+            // Later commands pick the curve of a custom circuit as they do for the inner and outer circuits
+            is_inner: match opt.circuit_file {
+                Some(_) => opt.curve_type == CurveKind::Bls12_377,
+                None => opt.circuit.is_inner(),
+            },
+            phase1_size: opt.phase1_size,
+            compression,
+            hash_algorithm: HASH_ALGORITHM,
+            transcript,
+            circuit_digest: Some(circuit_digest),
+            vdf_iterations: opt.vdf_iterations,
+        },
+        Some(artifacts),
+    )
+    .create(ceremony_dir)?;
+    fs_err::remove_file(progress_path)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_from_str() {
+        assert_eq!(circuit_from_str("inner"), Ok(CircuitKind::Inner));
+        assert_eq!(circuit_from_str("Outer"), Ok(CircuitKind::Outer));
+        // Anything else is an error, rather than the outer circuit
+        assert!(circuit_from_str("true").is_err());
+        assert!(circuit_from_str("").is_err());
+    }
+
+    #[test]
+    fn test_circuit_curve() {
+        // The standard circuits are generated over their own curve, whatever --curve-type is
+        assert_eq!(CircuitKind::Inner.curve(), CurveKind::Bls12_377);
+        assert_eq!(CircuitKind::Outer.curve(), CurveKind::BW6);
+        assert!(CURVES.contains(&CircuitKind::Inner.curve()));
+        assert!(CURVES.contains(&CircuitKind::Outer.curve()));
+    }
+
+    #[test]
+    fn test_curve_from_str() {
+        assert_eq!(curve_from_str("BLS12_377"), Ok(CurveKind::Bls12_377));
+        assert_eq!(curve_from_str("bw6"), Ok(CurveKind::BW6));
+        assert!(curve_from_str("bls12_381").unwrap_err().contains("not supported"));
+    }
+
+    #[test]
+    fn test_write_artifact() {
+        let dir = std::env::temp_dir().join(format!("setup2-new-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+        fs_err::create_dir_all(&dir).unwrap();
+        let path = dir.join("challenge.0");
+
+        let hash = write_artifact(&path, |output| Ok(std::io::Write::write_all(output, b"chunk")?)).unwrap();
+        assert_eq!(hash, hex::encode(&calculate_hash(b"chunk")[..]));
+        assert!(!tmp_path(&path).exists());
+        assert!(is_written(&path, Some(&hash)).unwrap());
+        assert!(!is_written(&path, None).unwrap());
+
+        // A chunk which was truncated by an interrupted run is written again.
+        fs_err::write(&path, b"chu").unwrap();
+        assert!(!is_written(&path, Some(&hash)).unwrap());
+
+        // A failed write names the artifact, rather than aborting
+        let missing = dir.join("missing").join("challenge.1");
+        match write_artifact(&missing, |output| Ok(std::io::Write::write_all(output, b"chunk")?)) {
+            Err(GenerationError::Write { path, .. }) => assert_eq!(path, missing),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_progress_chunk_hashes() {
+        // Progress saved before chunks were written in parallel lists the hashes of a prefix of the chunks.
+        let saved = r#"{
+            "circuit_digest": "00",
+            "phase1_size": 10,
+            "chunk_size": 2,
+            "transcript": "challenge",
+            "transcript_hash": "01",
+            "query_hash": "02",
+            "chunk_hashes": ["03", "04"]
+        }"#;
+        let mut progress: GenerationProgress = serde_json::from_str(saved).unwrap();
+        assert_eq!(progress.chunk_hashes, vec![
+            Some("03".to_string()),
+            Some("04".to_string())
+        ]);
+
+        // Chunks written out of order keep their index.
+        progress.chunk_hashes.resize(4, None);
+        progress.chunk_hashes[3] = Some("06".to_string());
+        let progress: GenerationProgress = serde_json::from_slice(&serde_json::to_vec(&progress).unwrap()).unwrap();
+        assert_eq!(progress.chunk_hashes[2], None);
+        assert_eq!(progress.chunk_hashes[3], Some("06".to_string()));
+    }
+}
@@ -0,0 +1,166 @@
+//! Signatures of the ceremony organizers, made with their Aleo keys over the canonical JSON
+//! encoding of a payload, and the K-of-N policy the official artifacts must be approved under.
+use setup_utils::to_canonical_json;
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, Address, PrivateKey, ViewKey};
+use snarkvm_utilities::{FromBytes, ToBytes};
+
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OrganizerError {
+    #[error("The organizer key is not a valid Aleo private key")]
+    InvalidOrganizerKey,
+    #[error("The organizer policy requires {threshold} of its {addresses} organizers, which is impossible")]
+    InvalidPolicy { threshold: usize, addresses: usize },
+    #[error("Only {approvals} organizers approved, but {threshold} approvals are required")]
+    NotEnoughApprovals { approvals: usize, threshold: usize },
+}
+
+/// The signature of an organizer over the canonical JSON encoding of a payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrganizerSignature {
+    /// The Aleo address of the organizer.
+    pub keyid: String,
+    /// The hex encoded signature.
+    pub sig: String,
+}
+
+impl OrganizerSignature {
+    /// Signs the payload with the given Aleo private key of an organizer.
+    pub fn sign<T: Serialize>(payload: &T, organizer_key: &str) -> anyhow::Result<Self> {
+        let private_key = PrivateKey::<Testnet2Parameters>::from_str(organizer_key)
+            .map_err(|_| OrganizerError::InvalidOrganizerKey)?;
+        let view_key = ViewKey::try_from(&private_key)?;
+        let message = to_canonical_json(payload)?;
+        let signature = view_key.sign(message.as_bytes(), &mut rand::thread_rng())?;
+        Ok(Self {
+            keyid: Address::try_from(&private_key)?.to_string(),
+            sig: hex::encode(signature.to_bytes_le()?),
+        })
+    }
+
+    /// Returns `true` if this is a valid signature over the payload.
+    ///
+    /// The payload is re-encoded canonically, so the signature does not depend
+    /// on how the payload was formatted when it was stored.
+    pub fn verify<T: Serialize>(&self, payload: &T) -> anyhow::Result<bool> {
+        let address = Address::<Testnet2Parameters>::from_str(&self.keyid)?;
+        let message = to_canonical_json(payload)?;
+        Ok(match FromBytes::from_bytes_le(&hex::decode(&self.sig)?) {
+            Ok(sig) => address.verify_signature(message.as_bytes(), &sig)?,
+            Err(_) => false,
+        })
+    }
+}
+
+/// The organizers of a ceremony, of whom at least `threshold` must approve its official artifacts,
+/// so that no single organizer machine can produce them on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OrganizerPolicy {
+    /// The number of organizers who must approve.
+    pub threshold: usize,
+    /// The Aleo addresses of the organizers.
+    pub addresses: Vec<String>,
+}
+
+impl OrganizerPolicy {
+    /// Checks that the threshold can be met by the organizers.
+    pub fn check(&self) -> Result<(), OrganizerError> {
+        if self.threshold == 0 || self.threshold > self.addresses.len() {
+            return Err(OrganizerError::InvalidPolicy {
+                threshold: self.threshold,
+                addresses: self.addresses.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the valid signatures over the payload, one per organizer, if there are enough of them.
+    ///
+    /// Signatures by anyone but the organizers, and invalid signatures, are ignored.
+    pub fn approvals<T: Serialize>(
+        &self,
+        payload: &T,
+        signatures: &[OrganizerSignature],
+    ) -> anyhow::Result<Vec<OrganizerSignature>> {
+        self.check()?;
+
+        let mut approvals: Vec<OrganizerSignature> = vec![];
+        for signature in signatures {
+            let approved = approvals.iter().any(|approval| approval.keyid == signature.keyid);
+            if !approved && self.addresses.contains(&signature.keyid) && signature.verify(payload)? {
+                approvals.push(signature.clone());
+            }
+        }
+        if approvals.len() < self.threshold {
+            return Err(OrganizerError::NotEnoughApprovals {
+                approvals: approvals.len(),
+                threshold: self.threshold,
+            }
+            .into());
+        }
+        Ok(approvals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    fn organizer_key(seed: u8) -> String {
+        PrivateKey::<Testnet2Parameters>::new(&mut ChaChaRng::from_seed([seed; 32])).to_string()
+    }
+
+    fn address(organizer_key: &str) -> String {
+        let private_key = PrivateKey::<Testnet2Parameters>::from_str(organizer_key).unwrap();
+        Address::try_from(&private_key).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_approvals_meet_threshold() {
+        let keys: Vec<String> = (1..=3).map(organizer_key).collect();
+        let policy = OrganizerPolicy {
+            threshold: 2,
+            addresses: keys.iter().map(String::as_str).map(address).collect(),
+        };
+        let payload = serde_json::json!({ "transcript_digest": "00" });
+        let first = OrganizerSignature::sign(&payload, &keys[0]).unwrap();
+        let second = OrganizerSignature::sign(&payload, &keys[1]).unwrap();
+        let outsider = OrganizerSignature::sign(&payload, &organizer_key(4)).unwrap();
+
+        // The same organizer only counts once, and outsiders do not count
+        let not_enough = [first.clone(), first.clone(), outsider];
+        assert!(matches!(
+            policy
+                .approvals(&payload, &not_enough)
+                .unwrap_err()
+                .downcast_ref::<OrganizerError>(),
+            Some(OrganizerError::NotEnoughApprovals {
+                approvals: 1,
+                threshold: 2
+            })
+        ));
+
+        let approvals = policy.approvals(&payload, &[first.clone(), second.clone()]).unwrap();
+        assert_eq!(approvals, vec![first.clone(), second]);
+
+        // A signature over another payload is not an approval
+        let other = OrganizerSignature::sign(&"other", &keys[2]).unwrap();
+        assert!(policy.approvals(&payload, &[first, other]).is_err());
+    }
+
+    #[test]
+    fn test_policy_threshold_must_be_reachable() {
+        let policy = OrganizerPolicy {
+            threshold: 2,
+            addresses: vec!["aleo1".to_string()],
+        };
+        assert!(matches!(policy.check(), Err(OrganizerError::InvalidPolicy { .. })));
+    }
+}
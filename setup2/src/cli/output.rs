@@ -0,0 +1,323 @@
+use super::{
+    AggregateError,
+    AttestationError,
+    BeaconSourceError,
+    BeaconVdfError,
+    CeremonyError,
+    CheckHashesError,
+    CheckpointError,
+    ChunkError,
+    DeterminismError,
+    GenerationError,
+    GitModeError,
+    GoldenError,
+    IncompatibleError,
+    InputFormatError,
+    KeystoreError,
+    MemoryCapError,
+    OrganizerError,
+    PathError,
+    PipelineError,
+    ProvenanceError,
+    R1csError,
+    ResumeError,
+    TestVectorError,
+    VerifyRoundError,
+};
+use setup_utils::{Error as SetupError, Phase2Error};
+
+use serde::Serialize;
+use std::{io, time::Duration};
+
+/// The stable set of exit codes returned by the `setup2` binary.
+///
+/// Orchestration scripts may rely on these values, so existing codes must never be renumbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCode {
+    /// The command completed successfully.
+    Success = 0,
+    /// An unexpected internal failure (e.g. a worker thread panicked).
+    Internal = 1,
+    /// The command line or configuration was invalid.
+    Config = 2,
+    /// An input file or argument could not be parsed or was malformed.
+    InputInvalid = 3,
+    /// A contribution failed verification.
+    VerificationFailed = 4,
+    /// Reading or writing a file failed.
+    Io = 5,
+    /// The command was cancelled or ran past its deadline.
+    Cancelled = 6,
+}
+
+impl ExitCode {
+    /// Returns the exit code which best describes the given error.
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        if let Some(error) = error.downcast_ref::<SetupError>() {
+            return match error {
+                SetupError::IoError(_) => ExitCode::Io,
+                SetupError::Phase2Error(Phase2Error::ContributionCheckpointMismatch) => ExitCode::InputInvalid,
+                SetupError::Phase2Error(Phase2Error::InvalidPhase2Size(..)) => ExitCode::InputInvalid,
+                SetupError::VerificationError(_) | SetupError::Phase2Error(_) => ExitCode::VerificationFailed,
+                SetupError::ZexeSerializationError(_)
+                | SetupError::PointAtInfinity
+                | SetupError::PositionError(..)
+                | SetupError::InvalidLength { .. }
+                | SetupError::InvalidChunk
+                | SetupError::IncorrectSubgroup
+                | SetupError::InvalidDecompressionParametersError => ExitCode::InputInvalid,
+                SetupError::SynthesisError(_) | SetupError::CrossBeamError => ExitCode::Internal,
+                SetupError::Cancelled | SetupError::DeadlineExceeded => ExitCode::Cancelled,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<CeremonyError>() {
+            return match error {
+                CeremonyError::IoError(_) => ExitCode::Io,
+                CeremonyError::InvalidManifest(_) | CeremonyError::ForeignArtifact { .. } => ExitCode::InputInvalid,
+                _ => ExitCode::Config,
+            };
+        }
+        if error.downcast_ref::<MemoryCapError>().is_some()
+            || error.downcast_ref::<IncompatibleError>().is_some()
+            || error.downcast_ref::<ChunkError>().is_some()
+            || error.downcast_ref::<PathError>().is_some()
+            || error.downcast_ref::<TestVectorError>().is_some()
+        {
+            return ExitCode::Config;
+        }
+        if let Some(error) = error.downcast_ref::<InputFormatError>() {
+            return match error {
+                InputFormatError::NotApplicable { .. } => ExitCode::Config,
+                InputFormatError::UnknownCompression { .. }
+                | InputFormatError::MissingChunks(_)
+                | InputFormatError::TranscriptSizeMismatch { .. } => ExitCode::InputInvalid,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<PipelineError>() {
+            return match error {
+                PipelineError::InvalidPipeline(_)
+                | PipelineError::InvalidOperatorKey(_)
+                | PipelineError::InvalidBeaconStep
+                | PipelineError::ExportBeforeFinalize => ExitCode::InputInvalid,
+                PipelineError::PipelineChanged(_) => ExitCode::Config,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<BeaconVdfError>() {
+            return match error {
+                BeaconVdfError::MissingCommand => ExitCode::Config,
+                BeaconVdfError::BeaconHashMismatch { .. } => ExitCode::VerificationFailed,
+                BeaconVdfError::ZeroIterations
+                | BeaconVdfError::IterationsNotRecorded
+                | BeaconVdfError::MissingProof(_) => ExitCode::Config,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<BeaconSourceError>() {
+            return match error {
+                BeaconSourceError::SelectionMismatch { .. } => ExitCode::VerificationFailed,
+                _ => ExitCode::InputInvalid,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<CheckpointError>() {
+            return match error {
+                CheckpointError::OverlappingPolicy | CheckpointError::NotAfterLatest(..) => ExitCode::Config,
+                CheckpointError::BrokenChain(_) | CheckpointError::TranscriptMismatch(_) => {
+                    ExitCode::VerificationFailed
+                }
+            };
+        }
+        if let Some(error) = error.downcast_ref::<AggregateError>() {
+            return match error {
+                AggregateError::ChunkCountMismatch { .. } => ExitCode::Config,
+                _ => ExitCode::InputInvalid,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<VerifyRoundError>() {
+            return match error {
+                VerifyRoundError::EmptyChallengeList(_) => ExitCode::InputInvalid,
+                VerifyRoundError::ChunkCountMismatch { .. } | VerifyRoundError::ZeroWorkers => ExitCode::Config,
+                VerifyRoundError::Failed(_) => ExitCode::VerificationFailed,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<AttestationError>() {
+            return match error {
+                AttestationError::NoContribution(_) => ExitCode::InputInvalid,
+                _ => ExitCode::VerificationFailed,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<KeystoreError>() {
+            return match error {
+                KeystoreError::Exists(_) | KeystoreError::PassphraseRequired => ExitCode::Config,
+                _ => ExitCode::InputInvalid,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<GenerationError>() {
+            return match error {
+                GenerationError::Phase1TooSmall { .. } => ExitCode::Config,
+                GenerationError::Phase1 { .. } | GenerationError::Read { .. } | GenerationError::Synthesis(_) => {
+                    ExitCode::InputInvalid
+                }
+                GenerationError::Map { .. } | GenerationError::Write { .. } => ExitCode::Io,
+                GenerationError::Phase2(_) => ExitCode::Internal,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<GitModeError>() {
+            return match error {
+                GitModeError::Git { .. } => ExitCode::Io,
+                GitModeError::BaseNotCheckedOut { .. } => ExitCode::Config,
+                _ => ExitCode::InputInvalid,
+            };
+        }
+        if error.downcast_ref::<ResumeError>().is_some() {
+            return ExitCode::InputInvalid;
+        }
+        #[cfg(feature = "cloud")]
+        if let Some(error) = error.downcast_ref::<super::CloudError>() {
+            return match error {
+                super::CloudError::NotFound(_) => ExitCode::InputInvalid,
+                _ => ExitCode::Config,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<CheckHashesError>() {
+            return match error {
+                CheckHashesError::NoChunks(_) => ExitCode::Config,
+                CheckHashesError::Mismatch(_) => ExitCode::VerificationFailed,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<GoldenError>() {
+            return match error {
+                GoldenError::MissingCase(_) => ExitCode::Config,
+                GoldenError::Drift(_) => ExitCode::VerificationFailed,
+            };
+        }
+        if error.downcast_ref::<ProvenanceError>().is_some() {
+            return ExitCode::VerificationFailed;
+        }
+        if error.downcast_ref::<R1csError>().is_some() {
+            return ExitCode::InputInvalid;
+        }
+        if let Some(error) = error.downcast_ref::<DeterminismError>() {
+            return match error {
+                DeterminismError::NotEnoughReports(_) => ExitCode::Config,
+                DeterminismError::Disagreement(_) => ExitCode::VerificationFailed,
+            };
+        }
+        if let Some(error) = error.downcast_ref::<OrganizerError>() {
+            return match error {
+                OrganizerError::InvalidOrganizerKey => ExitCode::InputInvalid,
+                OrganizerError::InvalidPolicy { .. } => ExitCode::Config,
+                OrganizerError::NotEnoughApprovals { .. } => ExitCode::VerificationFailed,
+            };
+        }
+        if error.downcast_ref::<io::Error>().is_some() {
+            return ExitCode::Io;
+        }
+        if error.downcast_ref::<hex::FromHexError>().is_some() {
+            return ExitCode::InputInvalid;
+        }
+        ExitCode::Internal
+    }
+
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// The structured result of a command, printed to stdout when `--json` is set.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandOutput {
+    pub command: &'static str,
+    pub success: bool,
+    pub exit_code: i32,
+    pub kind: ExitCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u128,
+}
+
+impl CommandOutput {
+    pub fn new(command: &'static str, result: &anyhow::Result<()>, elapsed: Duration) -> Self {
+        let (kind, error) = match result {
+            Ok(()) => (ExitCode::Success, None),
+            Err(error) => (ExitCode::from_error(error), Some(format!("{:#}", error))),
+        };
+        Self {
+            command,
+            success: result.is_ok(),
+            exit_code: kind.code(),
+            kind,
+            error,
+            elapsed_ms: elapsed.as_millis(),
+        }
+    }
+
+    /// Prints the result to stdout, as JSON if requested.
+    pub fn print(&self, json: bool) {
+        if json {
+            println!(
+                "{}",
+                serde_json::to_string(self).expect("command output should serialize to JSON")
+            );
+        } else {
+            match &self.error {
+                None => println!("Executing {} took: {}ms", self.command, self.elapsed_ms),
+                Some(error) => eprintln!("Executing {} failed ({:?}): {}", self.command, self.kind, error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_from_error() {
+        let io = anyhow::Error::from(io::Error::new(io::ErrorKind::NotFound, "missing"));
+        assert_eq!(ExitCode::from_error(&io), ExitCode::Io);
+
+        let wrapped_io = anyhow::Error::from(SetupError::IoError(io::Error::new(io::ErrorKind::Other, "disk")));
+        assert_eq!(ExitCode::from_error(&wrapped_io), ExitCode::Io);
+
+        let invalid = anyhow::Error::from(SetupError::InvalidLength { expected: 1, got: 2 });
+        assert_eq!(ExitCode::from_error(&invalid), ExitCode::InputInvalid);
+
+        let verification = anyhow::Error::from(SetupError::Phase2Error(setup_utils::Phase2Error::InvalidTranscript));
+        assert_eq!(ExitCode::from_error(&verification), ExitCode::VerificationFailed);
+
+        let hex = anyhow::Error::from(hex::decode("zz").unwrap_err());
+        assert_eq!(ExitCode::from_error(&hex), ExitCode::InputInvalid);
+
+        let config = anyhow::Error::from(MemoryCapError::NotPositive(0.0));
+        assert_eq!(ExitCode::from_error(&config), ExitCode::Config);
+        let chunks = anyhow::Error::from(ChunkError::MissingQuery);
+        assert_eq!(ExitCode::from_error(&chunks), ExitCode::Config);
+
+        let vdf = anyhow::Error::from(SetupError::VerificationError(
+            setup_utils::VerificationError::InvalidVdfProof,
+        ));
+        assert_eq!(ExitCode::from_error(&vdf), ExitCode::VerificationFailed);
+
+        let beacon = anyhow::Error::from(BeaconSourceError::NoSourceAvailable);
+        assert_eq!(ExitCode::from_error(&beacon), ExitCode::InputInvalid);
+
+        let round = anyhow::Error::from(VerifyRoundError::Failed("chunk 1: invalid".to_string()));
+        assert_eq!(ExitCode::from_error(&round), ExitCode::VerificationFailed);
+
+        let cancelled = anyhow::Error::from(SetupError::DeadlineExceeded);
+        assert_eq!(ExitCode::from_error(&cancelled), ExitCode::Cancelled);
+
+        assert_eq!(ExitCode::from_error(&anyhow::anyhow!("other")), ExitCode::Internal);
+    }
+
+    #[test]
+    fn test_command_output_json() {
+        let output = CommandOutput::new("verify", &Ok(()), Duration::from_millis(5));
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["command"], "verify");
+        assert_eq!(json["success"], true);
+        assert_eq!(json["exit_code"], 0);
+        assert_eq!(json["kind"], "success");
+        assert!(json.get("error").is_none());
+    }
+}
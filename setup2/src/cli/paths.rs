@@ -0,0 +1,219 @@
+//! The paths of the artifacts of a ceremony. The chunks, query parameters and receipts of a transcript
+//! are named by appending a suffix to its file name, which is done on the OS string of the path so that
+//! file names which are not valid UTF-8 are kept as they are. Paths given on the command line are
+//! validated and normalized before use, and made usable beyond the 260 character limit on Windows.
+use std::{
+    ffi::OsString,
+    path::{Component, Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PathError {
+    #[error("the path of an artifact cannot be empty")]
+    Empty,
+    #[error("{} does not name a file", .0.display())]
+    NotAFile(PathBuf),
+    #[error("{} contains a NUL character", .0.display())]
+    NulCharacter(PathBuf),
+    #[error("the file name of {} is not valid unicode", .0.display())]
+    NotUnicode(PathBuf),
+    #[error("{0} is an object URL, which only builds with the `cloud` feature read and write")]
+    ObjectUrl(String),
+}
+
+/// Returns the path of an artifact given on the command line, normalized and checked to name a file.
+pub fn artifact_path<P: AsRef<Path>>(path: P) -> Result<PathBuf, PathError> {
+    let path = path.as_ref();
+    if path.as_os_str().is_empty() {
+        return Err(PathError::Empty);
+    }
+    // With the `cloud` feature, the commands stage objects locally before they get here
+    let lossy = path.to_string_lossy();
+    if lossy.starts_with("s3://") || lossy.starts_with("gs://") {
+        return Err(PathError::ObjectUrl(lossy.to_string()));
+    }
+    if lossy.contains('\0') {
+        return Err(PathError::NulCharacter(path.to_path_buf()));
+    }
+    // Collecting the components drops `.` components, repeated separators and trailing separators.
+    let normalized: PathBuf = path.components().filter(|c| *c != Component::CurDir).collect();
+    match normalized.components().next_back() {
+        Some(Component::Normal(_)) => Ok(long_path(normalized)),
+        _ => Err(PathError::NotAFile(path.to_path_buf())),
+    }
+}
+
+/// Returns `path` with `suffix` appended to its file name, e.g. `challenge.query` for `challenge`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns the path of the query parameters split off the transcript at `base`,
+/// which hold everything but the chunked `h` and `l` queries.
+pub fn query_path<P: AsRef<Path>>(base: P) -> PathBuf {
+    with_suffix(base.as_ref(), "query")
+}
+
+/// Returns the path of the `index`-th chunk split off the transcript at `base`.
+pub fn chunk_path<P: AsRef<Path>>(base: P, index: usize) -> PathBuf {
+    with_suffix(base.as_ref(), &index.to_string())
+}
+
+/// Returns the path of the receipt `verify` leaves next to a successfully verified transcript.
+pub fn receipt_path<P: AsRef<Path>>(transcript: P) -> PathBuf {
+    with_suffix(transcript.as_ref(), "verified")
+}
+
+/// Returns the path of the hex encoded hash `new` writes next to a chunk, for checking downloads.
+pub fn hash_path<P: AsRef<Path>>(chunk: P) -> PathBuf {
+    with_suffix(chunk.as_ref(), "hash")
+}
+
+/// Returns the path of the progress `contribute` saves next to the transcript it transforms,
+/// from which an interrupted contribution is resumed.
+pub fn contribution_state_path<P: AsRef<Path>>(transcript: P) -> PathBuf {
+    with_suffix(transcript.as_ref(), "contribution.json")
+}
+
+/// Returns the path of the seed of the randomness of the contribution `contribute` saves next to
+/// the transcript it transforms, apart from its progress.
+pub fn contribution_seed_path<P: AsRef<Path>>(transcript: P) -> PathBuf {
+    with_suffix(transcript.as_ref(), "contribution.seed")
+}
+
+/// Returns the path of the attestation a participant signs for their contribution to a transcript.
+pub fn attestation_path<P: AsRef<Path>>(transcript: P) -> PathBuf {
+    with_suffix(transcript.as_ref(), "attestation.json")
+}
+
+/// Returns the path of the provenance record written next to an exported key.
+pub fn key_provenance_path<P: AsRef<Path>>(key: P) -> PathBuf {
+    with_suffix(key.as_ref(), "provenance.json")
+}
+
+/// Returns the path a file is written to before it is renamed to `path`, so that
+/// an interrupted write never leaves a truncated file at `path`.
+pub fn tmp_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    with_suffix(path.as_ref(), "tmp")
+}
+
+/// Returns the path of the copy of a transcript a contribution is applied to,
+/// which replaces the transcript once the contribution is verified.
+pub fn next_path<P: AsRef<Path>>(transcript: P) -> PathBuf {
+    with_suffix(transcript.as_ref(), "next")
+}
+
+/// Returns an empty directory for the test of the given name in the temporary directory,
+/// unique to this process, so that concurrent test runs do not share it.
+#[cfg(test)]
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("setup2-{}-{}", name, std::process::id()));
+    let _ = fs_err::remove_dir_all(&dir);
+    fs_err::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Paths longer than `MAX_PATH` can only be opened on Windows in their verbatim form.
+#[cfg(windows)]
+fn long_path(path: PathBuf) -> PathBuf {
+    const MAX_PATH: usize = 260;
+    let absolute = match std::env::current_dir() {
+        Ok(dir) if path.is_relative() => dir.join(&path),
+        _ => path.clone(),
+    };
+    if absolute.as_os_str().len() < MAX_PATH {
+        return path;
+    }
+    // Verbatim paths are not normalized by Windows, so `..` must be resolved first.
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                use std::path::Prefix;
+                match prefix.kind() {
+                    Prefix::Verbatim(_) | Prefix::VerbatimUNC(..) | Prefix::VerbatimDisk(_) => return absolute,
+                    Prefix::UNC(server, share) => {
+                        let mut verbatim = OsString::from(r"\\?\UNC\");
+                        verbatim.push(server);
+                        verbatim.push(r"\");
+                        verbatim.push(share);
+                        resolved.push(verbatim);
+                    }
+                    _ => {
+                        let mut verbatim = OsString::from(r"\\?\");
+                        verbatim.push(prefix.as_os_str());
+                        resolved.push(verbatim);
+                    }
+                }
+            }
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            component => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+#[cfg(not(windows))]
+fn long_path(path: PathBuf) -> PathBuf {
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suffixes() {
+        assert_eq!(query_path("ceremony/challenge"), Path::new("ceremony/challenge.query"));
+        assert_eq!(chunk_path("ceremony/challenge", 12), Path::new("ceremony/challenge.12"));
+        assert_eq!(receipt_path("response.1"), Path::new("response.1.verified"));
+        assert_eq!(hash_path("challenge.1"), Path::new("challenge.1.hash"));
+        assert_eq!(
+            contribution_state_path("challenge"),
+            Path::new("challenge.contribution.json")
+        );
+        assert_eq!(
+            contribution_seed_path("challenge"),
+            Path::new("challenge.contribution.seed")
+        );
+        // A suffix is appended, never substituted for an extension.
+        assert_eq!(tmp_path("params.v2"), Path::new("params.v2.tmp"));
+        assert_eq!(chunk_path("cérémonie/défi", 0), Path::new("cérémonie/défi.0"));
+    }
+
+    #[test]
+    fn test_artifact_path() {
+        assert_eq!(
+            artifact_path("./ceremony//challenge").unwrap(),
+            Path::new("ceremony/challenge")
+        );
+        assert_eq!(
+            artifact_path("данные/challenge").unwrap(),
+            Path::new("данные/challenge")
+        );
+        assert!(matches!(artifact_path(""), Err(PathError::Empty)));
+        assert!(matches!(artifact_path("ceremony/.."), Err(PathError::NotAFile(_))));
+        assert!(matches!(artifact_path("."), Err(PathError::NotAFile(_))));
+        assert!(matches!(artifact_path("chal\0lenge"), Err(PathError::NulCharacter(_))));
+        assert!(matches!(
+            artifact_path("s3://ceremony/challenge"),
+            Err(PathError::ObjectUrl(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_file_names() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let base = Path::new(OsStr::from_bytes(b"challenge\xff"));
+        assert_eq!(chunk_path(base, 3).as_os_str().as_bytes(), b"challenge\xff.3");
+    }
+}
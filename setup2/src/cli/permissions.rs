@@ -0,0 +1,213 @@
+//! The permissions and group of the artifacts the commands write. On a ceremony server shared by
+//! several users, the default umask leaves the artifacts readable or writable by the wrong people, so
+//! every file and directory is created through this module, which applies the permissions given on
+//! the command line to all of them.
+use once_cell::sync::OnceCell;
+use std::{io, path::Path};
+use thiserror::Error;
+
+static PERMISSIONS: OnceCell<ArtifactPermissions> = OnceCell::new();
+
+#[derive(Debug, Error)]
+pub enum PermissionsError {
+    #[error("unknown group {0}")]
+    UnknownGroup(String),
+    #[error("file modes and groups can only be set on Unix")]
+    Unsupported,
+}
+
+/// Parses a file mode given in octal, e.g. `640`.
+pub fn parse_mode(src: &str) -> Result<u32, String> {
+    match u32::from_str_radix(src, 8) {
+        Ok(mode) if mode <= 0o7777 => Ok(mode),
+        _ => Err(format!("invalid file mode {}, expected an octal mode such as 640", src)),
+    }
+}
+
+/// The permissions of the artifacts written by this process, which default to those
+/// of the umask and the primary group of the user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtifactPermissions {
+    pub file_mode: Option<u32>,
+    pub dir_mode: Option<u32>,
+    pub gid: Option<u32>,
+}
+
+impl ArtifactPermissions {
+    /// Returns the permissions with the given modes, and the group with the given name or ID.
+    pub fn new(file_mode: Option<u32>, dir_mode: Option<u32>, group: Option<&str>) -> Result<Self, PermissionsError> {
+        let gid = group.map(group_id).transpose()?;
+        let permissions = Self {
+            file_mode,
+            dir_mode,
+            gid,
+        };
+        if cfg!(not(unix)) && permissions != Self::default() {
+            return Err(PermissionsError::Unsupported);
+        }
+        Ok(permissions)
+    }
+
+    /// Applies the permissions to every artifact this process writes from now on.
+    pub fn install(self) {
+        PERMISSIONS
+            .set(self)
+            .expect("the artifact permissions should only be installed once");
+    }
+}
+
+fn installed() -> ArtifactPermissions {
+    PERMISSIONS.get().copied().unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn group_id(group: &str) -> Result<u32, PermissionsError> {
+    if let Ok(gid) = group.parse() {
+        return Ok(gid);
+    }
+    let name = std::ffi::CString::new(group).map_err(|_| PermissionsError::UnknownGroup(group.to_string()))?;
+    let entry = unsafe { libc::getgrnam(name.as_ptr()) };
+    if entry.is_null() {
+        return Err(PermissionsError::UnknownGroup(group.to_string()));
+    }
+    Ok(unsafe { (*entry).gr_gid })
+}
+
+#[cfg(not(unix))]
+fn group_id(_group: &str) -> Result<u32, PermissionsError> {
+    Err(PermissionsError::Unsupported)
+}
+
+#[cfg(unix)]
+fn apply(path: &Path, mode: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    use std::{
+        ffi::CString,
+        os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+    };
+
+    if let Some(gid) = gid {
+        // The group is changed first, since changing it may clear the setgid bit of the mode.
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        if unsafe { libc::chown(c_path.as_ptr(), u32::MAX, gid) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    if let Some(mode) = mode {
+        fs_err::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn apply(_path: &Path, _mode: Option<u32>, _gid: Option<u32>) -> io::Result<()> {
+    Ok(())
+}
+
+fn apply_to_file(path: &Path) -> io::Result<()> {
+    let permissions = installed();
+    apply(path, permissions.file_mode, permissions.gid)
+}
+
+/// Creates the file at `path`, failing if it already exists.
+pub fn create_new<P: AsRef<Path>>(path: P) -> io::Result<fs_err::File> {
+    let file = fs_err::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path.as_ref())?;
+    apply_to_file(path.as_ref())?;
+    Ok(file)
+}
+
+/// Creates the file at `path`, truncating it if it exists.
+pub fn create<P: AsRef<Path>>(path: P) -> io::Result<fs_err::File> {
+    let file = fs_err::File::create(path.as_ref())?;
+    apply_to_file(path.as_ref())?;
+    Ok(file)
+}
+
+/// Writes `contents` to the file at `path`, replacing it if it exists.
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    fs_err::write(path.as_ref(), contents)?;
+    apply_to_file(path.as_ref())
+}
+
+/// Writes the secret `contents` to the file at `path`, replacing it if it exists, and syncs it to disk.
+/// Unlike the artifacts, it is only readable and writable by its owner, whatever the permissions given
+/// on the command line.
+pub fn write_secret<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut file = fs_err::File::create(path)?;
+    // The mode is set before the secret is written, so that nobody else can ever read it
+    apply(path, Some(0o600), None)?;
+    io::Write::write_all(&mut file, contents.as_ref())?;
+    file.sync_all()
+}
+
+/// Syncs the directory holding `path`, so that the file created, renamed or removed there stays so after a crash.
+#[cfg(unix)]
+pub fn sync_parent<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let dir = match path.as_ref().parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs_err::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+pub fn sync_parent<P: AsRef<Path>>(_path: P) -> io::Result<()> {
+    Ok(())
+}
+
+/// Creates the directory at `path` and its missing parents.
+pub fn create_dir_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let missing: Vec<&Path> = path
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .collect();
+    fs_err::create_dir_all(path)?;
+
+    let permissions = installed();
+    for dir in missing.into_iter().rev() {
+        apply(dir, permissions.dir_mode, permissions.gid)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::paths::test_dir;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("640"), Ok(0o640));
+        assert_eq!(parse_mode("2770"), Ok(0o2770));
+        assert!(parse_mode("8").is_err());
+        assert!(parse_mode("17777").is_err());
+        assert!(parse_mode("rw-r-----").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = test_dir("permissions");
+        let path = dir.join("artifact");
+        fs_err::write(&path, b"").unwrap();
+
+        apply(&path, Some(0o640), None).unwrap();
+        assert_eq!(fs_err::metadata(&path).unwrap().permissions().mode() & 0o7777, 0o640);
+        apply(&dir, Some(0o750), None).unwrap();
+        assert_eq!(fs_err::metadata(&dir).unwrap().permissions().mode() & 0o7777, 0o750);
+
+        // A secret is only readable by its owner, even once it replaces a file readable by others
+        write_secret(&path, b"seed").unwrap();
+        assert_eq!(fs_err::metadata(&path).unwrap().permissions().mode() & 0o7777, 0o600);
+        assert_eq!(fs_err::read(&path).unwrap(), b"seed");
+        sync_parent(&path).unwrap();
+
+        fs_err::remove_dir_all(&dir).unwrap();
+    }
+}
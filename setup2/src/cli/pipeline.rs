@@ -0,0 +1,652 @@
+//! Runs an entire ceremony from a declarative pipeline file, for internal and test
+//! ceremonies which have to be reproducible.
+//!
+//! The contributions are derived from the seeds in the operator key files, so anyone
+//! holding the keys can recompute them: a pipeline must never be used for a ceremony
+//! whose security depends on the contributions being secret.
+use super::{
+    beacon_sources::select_beacon,
+    beacon_vdf::evaluate_beacon,
+    ceremony::CeremonyManifest,
+    contribute::{contribute, ContributeOpts},
+    finalization::{FinalizationAttestation, BEACON_FILENAME, FINALIZATION_FILENAME},
+    input_format::InputFormat,
+    new::{
+        generate_params,
+        inner_circuit,
+        outer_circuit,
+        use_compression,
+        AleoInner,
+        AleoOuter,
+        CircuitKind,
+        CurveKind,
+        NewOpts,
+        ZexeInner,
+        ZexeOuter,
+    },
+    organizers::OrganizerPolicy,
+    paths::{key_provenance_path, next_path, receipt_path, tmp_path},
+    permissions,
+    provenance::{KeyProvenance, Material, SignedStatement, Statement, Subject, PROVENANCE_FILENAME},
+    verify::{verify, VerifyOpts},
+};
+use phase1::{Phase1, Phase1Parameters, ProvingSystem};
+use phase2::{chunked_groth16::read_contributions, parameters::MPCParameters};
+use setup_utils::{calculate_hash, get_rng, CheckForCorrectness, Groth16Params, Phase2Error, UseCompression};
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+use snarkvm_utilities::CanonicalSerialize;
+
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+
+/// The name of the file recording the progress of a pipeline, stored in the ceremony directory.
+pub const STATE_FILENAME: &str = "pipeline.state.json";
+
+#[derive(Debug, Options, Clone)]
+pub struct RunPipelineOpts {
+    help: bool,
+    #[options(free, required, help = "the pipeline file describing the ceremony")]
+    pub pipeline: String,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("Invalid pipeline file: {0}")]
+    InvalidPipeline(#[from] serde_yaml::Error),
+    #[error("The pipeline file changed since the run recorded in {0}, remove it to start over")]
+    PipelineChanged(PathBuf),
+    #[error("The operator key {0} must hold at least 32 hex encoded bytes")]
+    InvalidOperatorKey(String),
+    #[error("A beacon step must have either a beacon_hash or sources, but not both")]
+    InvalidBeaconStep,
+    #[error("An export step with an organizer key must come after the finalize step")]
+    ExportBeforeFinalize,
+}
+
+/// A ceremony described as the ordered steps to run.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pipeline {
+    /// The ceremony directory, which all the other paths are relative to.
+    pub dir: String,
+    /// Whether the ceremony is for the inner or the outer circuit.
+    #[serde(default)]
+    pub is_inner: bool,
+    /// The prepared phase 1 transcript.
+    #[serde(default = "default_phase1")]
+    pub phase1: String,
+    /// The number of coefficients (in powers of 2) of the prepared phase 1 transcript.
+    pub phase1_size: u32,
+    /// Whether the prepared phase 1 transcript holds compressed elements, which halves its size.
+    #[serde(default)]
+    pub compressed: bool,
+    /// The file name of the transcript.
+    #[serde(default = "default_transcript")]
+    pub transcript: String,
+    /// The batches which can be loaded in memory.
+    #[serde(default = "default_batch")]
+    pub batch: usize,
+    /// The organizers who must approve the finalization, with `approve-finalization`, before it is written.
+    #[serde(default)]
+    pub organizers: Option<OrganizerPolicy>,
+    /// The number of sequential squarings the beacon-vdf proof of the beacon must take, if it is delayed.
+    #[serde(default)]
+    pub vdf_iterations: Option<u64>,
+    pub steps: Vec<Step>,
+}
+
+fn default_phase1() -> String {
+    "phase1".to_string()
+}
+
+fn default_transcript() -> String {
+    "challenge".to_string()
+}
+
+fn default_batch() -> usize {
+    50000
+}
+
+fn default_prepare_batch() -> usize {
+    256
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    /// Processes a phase 1 response into the prepared phase 1 transcript.
+    Prepare {
+        response: String,
+        /// The number of powers used for phase 1.
+        power: usize,
+        #[serde(default = "default_prepare_batch")]
+        batch_size: usize,
+    },
+    /// Creates the initial transcript and the ceremony manifest.
+    New,
+    /// Contributes once with the seed in each of the operator key files, in order.
+    Contribute { operators: Vec<String> },
+    /// Contributes the randomness of a random beacon, whose hash is either given or
+    /// selected from the file listing the beacon sources in order of preference.
+    Beacon {
+        #[serde(default)]
+        beacon_hash: Option<String>,
+        #[serde(default)]
+        sources: Option<String>,
+        vdf_proof: Option<String>,
+    },
+    /// Verifies the final parameters on their own.
+    Finalize,
+    /// Writes the proving and verifying keys, along with their provenance statement
+    /// signed by the organizer key if one is given.
+    Export {
+        proving_key: String,
+        verifying_key: String,
+        #[serde(default)]
+        organizer_key: Option<String>,
+    },
+}
+
+/// A single resumable unit of work, a step with several operators being split into one per operator.
+#[derive(Debug, Clone, Copy)]
+enum Action<'a> {
+    Prepare {
+        response: &'a str,
+        power: usize,
+        batch_size: usize,
+    },
+    New,
+    Contribute {
+        operator: &'a str,
+    },
+    Beacon {
+        beacon_hash: Option<&'a str>,
+        sources: Option<&'a str>,
+        vdf_proof: Option<&'a str>,
+    },
+    Finalize,
+    Export {
+        proving_key: &'a str,
+        verifying_key: &'a str,
+        organizer_key: Option<&'a str>,
+    },
+}
+
+impl Pipeline {
+    fn actions(&self) -> Vec<Action> {
+        let mut actions = vec![];
+        for step in &self.steps {
+            match step {
+                Step::Prepare {
+                    response,
+                    power,
+                    batch_size,
+                } => actions.push(Action::Prepare {
+                    response,
+                    power: *power,
+                    batch_size: *batch_size,
+                }),
+                Step::New => actions.push(Action::New),
+                Step::Contribute { operators } => {
+                    actions.extend(operators.iter().map(|operator| Action::Contribute { operator }))
+                }
+                Step::Beacon {
+                    beacon_hash,
+                    sources,
+                    vdf_proof,
+                } => actions.push(Action::Beacon {
+                    beacon_hash: beacon_hash.as_deref(),
+                    sources: sources.as_deref(),
+                    vdf_proof: vdf_proof.as_deref(),
+                }),
+                Step::Finalize => actions.push(Action::Finalize),
+                Step::Export {
+                    proving_key,
+                    verifying_key,
+                    organizer_key,
+                } => actions.push(Action::Export {
+                    proving_key,
+                    verifying_key,
+                    organizer_key: organizer_key.as_deref(),
+                }),
+            }
+        }
+        actions
+    }
+
+    /// Checks the steps which cannot be described by their type alone.
+    fn check(&self) -> Result<(), PipelineError> {
+        let mut finalized = false;
+        for step in &self.steps {
+            match step {
+                Step::Beacon {
+                    beacon_hash, sources, ..
+                } if beacon_hash.is_some() == sources.is_some() => return Err(PipelineError::InvalidBeaconStep),
+                // The provenance statement takes the transcript digest from the finalization attestation
+                Step::Export {
+                    organizer_key: Some(_), ..
+                } if !finalized => return Err(PipelineError::ExportBeforeFinalize),
+                Step::Finalize => finalized = true,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, file: &str) -> String {
+        Path::new(&self.dir).join(file).display().to_string()
+    }
+}
+
+/// The progress of a pipeline, saved after every completed action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PipelineState {
+    /// The hex encoded hash of the pipeline file the progress belongs to.
+    pipeline: String,
+    /// The number of completed actions.
+    completed: usize,
+}
+
+pub fn run_pipeline(opts: &RunPipelineOpts) -> anyhow::Result<()> {
+    let contents = fs_err::read(&opts.pipeline)?;
+    let pipeline: Pipeline = serde_yaml::from_slice(&contents).map_err(PipelineError::from)?;
+    pipeline.check()?;
+    if let Some(organizers) = &pipeline.organizers {
+        organizers.check()?;
+    }
+    permissions::create_dir_all(&pipeline.dir)?;
+
+    // Resume after the last completed action, as long as the pipeline is the same
+    let state_path = Path::new(&pipeline.dir).join(STATE_FILENAME);
+    let mut state = PipelineState {
+        pipeline: hex::encode(&calculate_hash(&contents)[..]),
+        completed: 0,
+    };
+    if state_path.exists() {
+        let saved: PipelineState = serde_json::from_slice(&fs_err::read(&state_path)?)?;
+        if saved.pipeline != state.pipeline {
+            return Err(PipelineError::PipelineChanged(state_path).into());
+        }
+        state.completed = saved.completed;
+    }
+
+    let actions = pipeline.actions();
+    let mut contributions = 0;
+    for (i, action) in actions.iter().enumerate() {
+        let is_contribution = matches!(action, Action::Contribute { .. } | Action::Beacon { .. });
+        if i < state.completed {
+            contributions += is_contribution as usize;
+            continue;
+        }
+
+        info!("running step {}/{}: {:?}", i + 1, actions.len(), action);
+        // A contribution which was applied before the state could be saved is not applied again
+        if is_contribution && transcript_contributions(&pipeline)? > contributions {
+            info!("the transcript already holds this contribution");
+        } else {
+            run_action(&pipeline, action)?;
+        }
+        contributions += is_contribution as usize;
+
+        state.completed = i + 1;
+        let tmp = tmp_path(&state_path);
+        permissions::write(&tmp, serde_json::to_vec_pretty(&state)?)?;
+        fs_err::rename(tmp, &state_path)?;
+    }
+
+    info!("the pipeline in {} is complete", opts.pipeline);
+    Ok(())
+}
+
+fn run_action(pipeline: &Pipeline, action: &Action) -> anyhow::Result<()> {
+    match *action {
+        Action::Prepare {
+            response,
+            power,
+            batch_size,
+        } => {
+            let response = pipeline.path(response);
+            let output = pipeline.path(&pipeline.phase1);
+            let compression = use_compression(pipeline.compressed);
+            if pipeline.is_inner {
+                prepare::<Bls12_377>(&response, &output, power, batch_size, pipeline.phase1_size, compression)
+            } else {
+                prepare::<BW6_761>(&response, &output, power, batch_size, pipeline.phase1_size, compression)
+            }
+        }
+        Action::New => {
+            let opts = NewOpts {
+                help: false,
+                phase1: pipeline.path(&pipeline.phase1),
+                phase1_size: pipeline.phase1_size,
+                output: pipeline.path(&pipeline.transcript),
+                compressed_input: false,
+                input_format: match pipeline.compressed {
+                    true => InputFormat::Compressed,
+                    false => InputFormat::Uncompressed,
+                },
+                curve_type: if pipeline.is_inner {
+                    CurveKind::Bls12_377
+                } else {
+                    CurveKind::BW6
+                },
+                circuit: if pipeline.is_inner {
+                    CircuitKind::Inner
+                } else {
+                    CircuitKind::Outer
+                },
+                circuit_file: None,
+                chunk_size: None,
+                vdf_iterations: pipeline.vdf_iterations,
+                max_memory_gb: None,
+                quiet: false,
+                progress_json: false,
+                json: false,
+            };
+            if pipeline.is_inner {
+                generate_params::<AleoInner, ZexeInner, _>(&opts, inner_circuit())
+            } else {
+                generate_params::<AleoOuter, ZexeOuter, _>(&opts, outer_circuit()?)
+            }
+        }
+        Action::Contribute { operator } => {
+            let key = fs_err::read_to_string(pipeline.path(operator))?;
+            let seed = hex::decode(key.trim()).map_err(|_| PipelineError::InvalidOperatorKey(operator.to_string()))?;
+            if seed.len() < 32 {
+                return Err(PipelineError::InvalidOperatorKey(operator.to_string()).into());
+            }
+            contribute_and_verify(pipeline, None, &mut get_rng(&seed))
+        }
+        Action::Beacon {
+            beacon_hash,
+            sources,
+            vdf_proof,
+        } => {
+            let beacon_hash = match (beacon_hash, sources) {
+                (Some(beacon_hash), None) => beacon_hash.to_string(),
+                (None, Some(sources)) => select_beacon(&pipeline.path(sources), &pipeline.path(BEACON_FILENAME))?,
+                _ => return Err(PipelineError::InvalidBeaconStep.into()),
+            };
+            let beacon = (beacon_hash, vdf_proof.map(|proof| pipeline.path(proof)));
+            let opts = contribute_opts(pipeline, String::new(), Some(beacon.clone()));
+            let evaluated = evaluate_beacon(&opts, pipeline.vdf_iterations)?;
+            contribute_and_verify(pipeline, Some(beacon), &mut get_rng(&hex::decode(&evaluated.randomness)?))?;
+            CeremonyManifest::record_beacon(Path::new(&pipeline.dir), evaluated)?;
+            Ok(())
+        }
+        Action::Finalize => {
+            let dir = Path::new(&pipeline.dir);
+            let mut attestation = FinalizationAttestation::compute(dir, &pipeline.transcript, pipeline.is_inner)?;
+            if let Some(organizers) = &pipeline.organizers {
+                attestation.approve(dir, organizers)?;
+            }
+            permissions::write(
+                pipeline.path(FINALIZATION_FILENAME),
+                serde_json::to_vec_pretty(&attestation)?,
+            )?;
+            Ok(())
+        }
+        Action::Export {
+            proving_key,
+            verifying_key,
+            organizer_key,
+        } => {
+            if pipeline.is_inner {
+                export::<Bls12_377>(pipeline, proving_key, verifying_key)?;
+            } else {
+                export::<BW6_761>(pipeline, proving_key, verifying_key)?;
+            }
+            match organizer_key {
+                Some(organizer_key) => write_provenance(pipeline, proving_key, verifying_key, organizer_key),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Processes the phase 1 response into the Lagrange coefficients phase 2 starts from,
+/// written with the given compression.
+pub(super) fn prepare<E: PairingEngine>(
+    response: &str,
+    output: &str,
+    power: usize,
+    batch_size: usize,
+    phase1_size: u32,
+    compression: UseCompression,
+) -> anyhow::Result<()> {
+    let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, power, batch_size);
+    let response = fs_err::File::open(response)?;
+    let response = unsafe { MmapOptions::new().map(response.file())? };
+    let accumulator = Phase1::deserialize(&response, UseCompression::Yes, CheckForCorrectness::Full, &parameters)?;
+
+    let groth16_params = Groth16Params::<E>::new(
+        2usize.pow(phase1_size),
+        accumulator.tau_powers_g1,
+        accumulator.tau_powers_g2,
+        accumulator.alpha_tau_powers_g1,
+        accumulator.beta_tau_powers_g1,
+        accumulator.beta_g2,
+    )?;
+
+    // Write to a temporary file first so an interrupted run never leaves a truncated transcript
+    let tmp = tmp_path(output);
+    let mut writer = permissions::create(&tmp)?;
+    groth16_params.write(&mut writer, compression)?;
+    fs_err::rename(tmp, output)?;
+    Ok(())
+}
+
+fn contribute_opts(pipeline: &Pipeline, data: String, beacon: Option<(String, Option<String>)>) -> ContributeOpts {
+    let (beacon_hash, vdf_proof) = beacon.unwrap_or_default();
+    ContributeOpts {
+        help: false,
+        data,
+        batch: pipeline.batch,
+        max_memory_gb: None,
+        beacon_hash,
+        vdf_proof,
+        beacon_sources: None,
+        beacon_record: String::new(),
+        attestation_key: None,
+        #[cfg(feature = "cloud")]
+        output: None,
+        is_inner: pipeline.is_inner,
+        chunks: None,
+        input_format: InputFormat::Full,
+        check_input: CheckForCorrectness::No,
+        quiet: false,
+        progress_json: false,
+        json: false,
+    }
+}
+
+/// Contributes to a copy of the transcript and verifies it against the transcript,
+/// which is only replaced once the contribution is verified.
+fn contribute_and_verify<R: rand::Rng + rand::CryptoRng>(
+    pipeline: &Pipeline,
+    beacon: Option<(String, Option<String>)>,
+    rng: &mut R,
+) -> anyhow::Result<()> {
+    let transcript = pipeline.path(&pipeline.transcript);
+    let next = next_path(&transcript).display().to_string();
+    fs_err::copy(&transcript, &next)?;
+
+    contribute(&contribute_opts(pipeline, next.clone(), beacon), rng)?;
+    verify(&VerifyOpts {
+        help: false,
+        before: transcript.clone(),
+        after: next.clone(),
+        batch: pipeline.batch,
+        max_memory_gb: None,
+        is_inner: pipeline.is_inner,
+        chunks: None,
+        input_format: InputFormat::Full,
+        query: None,
+        check_input: CheckForCorrectness::Full,
+        report: None,
+        quiet: false,
+        progress_json: false,
+        json: false,
+    })?;
+
+    fs_err::rename(&next, &transcript)?;
+    fs_err::rename(receipt_path(&next), receipt_path(&transcript))?;
+    Ok(())
+}
+
+/// Returns the number of contributions in the transcript, or 0 if it does not exist yet.
+fn transcript_contributions(pipeline: &Pipeline) -> anyhow::Result<usize> {
+    let transcript = pipeline.path(&pipeline.transcript);
+    if !Path::new(&transcript).exists() {
+        return Ok(0);
+    }
+    let transcript = fs_err::File::open(transcript)?;
+    let transcript = unsafe { MmapOptions::new().map(transcript.file())? };
+    let contributions = if pipeline.is_inner {
+        read_contributions::<Bls12_377>(&transcript)?.1.len()
+    } else {
+        read_contributions::<BW6_761>(&transcript)?.1.len()
+    };
+    Ok(contributions)
+}
+
+fn read_params<E: PairingEngine>(pipeline: &Pipeline) -> anyhow::Result<MPCParameters<E>> {
+    let transcript = fs_err::File::open(pipeline.path(&pipeline.transcript))?;
+    let transcript = unsafe { MmapOptions::new().map(transcript.file())? };
+    Ok(MPCParameters::read(&transcript[..])?)
+}
+
+fn export<E: PairingEngine>(pipeline: &Pipeline, proving_key: &str, verifying_key: &str) -> anyhow::Result<()> {
+    let mpc = read_params::<E>(pipeline)?;
+    let last = mpc.contributions.last().ok_or(Phase2Error::NoContributions)?;
+    let transcript_digest = hex::encode(&last.hash()[..]);
+
+    let params = mpc.params;
+    params.serialize(&mut permissions::create(pipeline.path(proving_key))?)?;
+    params
+        .vk
+        .serialize(&mut permissions::create(pipeline.path(verifying_key))?)?;
+
+    // The provenance records go next to the keys, which stay readable as they are
+    let ceremony_id = CeremonyManifest::load(Path::new(&pipeline.dir))?.config.ceremony_id()?;
+    for key in [proving_key, verifying_key] {
+        let path = pipeline.path(key);
+        let record = KeyProvenance::new(
+            key,
+            Path::new(&path),
+            &ceremony_id,
+            &pipeline.transcript,
+            &transcript_digest,
+        )?;
+        permissions::write(key_provenance_path(&path), serde_json::to_vec_pretty(&record)?)?;
+    }
+    Ok(())
+}
+
+/// Writes the provenance statement of the exported keys, signed by the organizer key.
+fn write_provenance(
+    pipeline: &Pipeline,
+    proving_key: &str,
+    verifying_key: &str,
+    organizer_key: &str,
+) -> anyhow::Result<()> {
+    let attestation: FinalizationAttestation =
+        serde_json::from_slice(&fs_err::read(pipeline.path(FINALIZATION_FILENAME))?)?;
+    let statement = Statement::new(
+        vec![
+            Subject::file(proving_key, Path::new(&pipeline.path(proving_key)))?,
+            Subject::file(verifying_key, Path::new(&pipeline.path(verifying_key)))?,
+        ],
+        vec![
+            Material::file(&pipeline.phase1, Path::new(&pipeline.path(&pipeline.phase1)))?,
+            Material::transcript(&pipeline.transcript, &attestation.transcript_digest),
+            Material::tool()?,
+        ],
+    );
+    let organizer_key = fs_err::read_to_string(pipeline.path(organizer_key))?;
+    let signed = SignedStatement::sign(statement, organizer_key.trim())?;
+    permissions::write(pipeline.path(PROVENANCE_FILENAME), serde_json::to_vec_pretty(&signed)?)?;
+    info!(
+        "wrote the provenance statement to {}",
+        pipeline.path(PROVENANCE_FILENAME)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_actions() {
+        let pipeline: Pipeline = serde_yaml::from_str(
+            r#"
+dir: ceremony
+is_inner: true
+phase1_size: 10
+compressed: true
+organizers: { threshold: 1, addresses: [aleo1organizer] }
+steps:
+  - prepare: { response: response, power: 10 }
+  - new
+  - contribute: { operators: [alice.key, bob.key] }
+  - beacon: { beacon_hash: "00" }
+  - beacon: { sources: beacon_sources.json }
+  - finalize
+  - export: { proving_key: pk, verifying_key: vk }
+"#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.transcript, "challenge");
+        assert!(pipeline.compressed);
+        assert_eq!(pipeline.organizers.as_ref().map(|organizers| organizers.threshold), Some(1));
+        assert_eq!(
+            pipeline.path("pk"),
+            Path::new("ceremony").join("pk").display().to_string()
+        );
+
+        let actions = pipeline.actions();
+        assert!(pipeline.check().is_ok());
+        assert_eq!(actions.len(), 8);
+        assert!(matches!(actions[0], Action::Prepare { batch_size: 256, .. }));
+        assert!(matches!(actions[3], Action::Contribute { operator: "bob.key" }));
+        assert!(matches!(actions[4], Action::Beacon { vdf_proof: None, .. }));
+        assert!(matches!(
+            actions[5],
+            Action::Beacon {
+                beacon_hash: None,
+                sources: Some("beacon_sources.json"),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_signed_export_needs_finalize() {
+        let pipeline: Pipeline = serde_yaml::from_str(
+            "dir: c\nphase1_size: 10\nsteps: [{ export: { proving_key: pk, verifying_key: vk, organizer_key: key } }]\n",
+        )
+        .unwrap();
+        assert!(matches!(pipeline.check(), Err(PipelineError::ExportBeforeFinalize)));
+    }
+
+    #[test]
+    fn test_beacon_step_needs_one_beacon() {
+        let pipeline: Pipeline =
+            serde_yaml::from_str("dir: c\nphase1_size: 10\nsteps: [{ beacon: { vdf_proof: proof.json } }]\n").unwrap();
+        assert!(matches!(pipeline.check(), Err(PipelineError::InvalidBeaconStep)));
+    }
+
+    #[test]
+    fn test_unknown_step_is_rejected() {
+        assert!(serde_yaml::from_str::<Pipeline>("dir: c\nphase1_size: 10\nsteps: [transmogrify]\n").is_err());
+    }
+}
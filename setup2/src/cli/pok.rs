@@ -0,0 +1,184 @@
+//! Standalone exports of the proofs of knowledge of the contributions, so that external audit
+//! tools can check the ratio proofs of a transcript without parsing the parameters.
+//!
+//! Every contribution is exported to its own JSON file, `contribution.<index>.json`. Its points
+//! are hex encoded uncompressed, as in the public keys stored in the parameters.
+use super::permissions;
+use phase2::{chunked_groth16::read_contributions, keypair::PublicKey};
+use setup_utils::Result;
+
+use snarkvm_algorithms::hash_to_curve::hash_to_curve;
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, AffineCurve, PairingEngine};
+use snarkvm_utilities::CanonicalSerialize;
+
+use fs_err::File;
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::info;
+
+/// The version of the format of the exported proofs of knowledge, bumped on every breaking change.
+pub const POK_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Options, Clone)]
+pub struct ExportPokOpts {
+    help: bool,
+    #[options(
+        help = "the parameters whose contributions are exported, the transcript or one of its chunks",
+        default = "challenge"
+    )]
+    pub data: String,
+    #[options(help = "the directory to write the proofs of knowledge to", default = "pok")]
+    pub output: String,
+    #[options(help = "setup the inner or the outer circuit?")]
+    pub is_inner: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+/// The proof of knowledge of the delta of a contribution, with everything needed to check it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofOfKnowledge {
+    pub version: u32,
+    /// The curve of the points, `bls12_377` or `bw6_761`.
+    pub curve: String,
+    /// The index of the contribution in the transcript, starting at 0.
+    pub index: usize,
+    /// The hex encoded hash of the circuit, which the transcript hashes start from.
+    pub cs_hash: String,
+    /// The hex encoded hash of the public key of the contribution.
+    pub hash: String,
+    /// The hex encoded public key, as it is hashed into the transcript hashes of later contributions.
+    pub public_key: String,
+    /// The delta in G1 before the contribution: the generator for the first contribution, and
+    /// the `delta_after` of the previous contribution otherwise.
+    pub previous_delta_after: String,
+    pub delta_after: String,
+    pub s: String,
+    pub s_delta: String,
+    pub r_delta: String,
+    /// The hex encoded transcript hash, `H(cs_hash | <previous public keys> | s | s_delta)`.
+    pub transcript: String,
+    /// The point in G2 the hex encoded transcript hash maps to.
+    pub r: String,
+}
+
+impl ProofOfKnowledge {
+    ///
+    /// Returns the proofs of knowledge of the contributions, in order.
+    ///
+    /// The proofs are exported as they are, so that the audit tools find out
+    /// on their own whether they hold.
+    ///
+    pub fn export<E: PairingEngine>(
+        curve: &str,
+        cs_hash: [u8; 64],
+        contributions: &[PublicKey<E>],
+    ) -> Result<Vec<ProofOfKnowledge>> {
+        let mut previous_delta_after = E::G1Affine::prime_subgroup_generator();
+        let mut proofs = Vec::with_capacity(contributions.len());
+        for (index, pubkey) in contributions.iter().enumerate() {
+            let r = hash_to_curve::<E::G2Affine>(&hex::encode(&pubkey.transcript[..])).0;
+            let mut public_key = vec![];
+            pubkey.write(&mut public_key)?;
+            proofs.push(ProofOfKnowledge {
+                version: POK_FORMAT_VERSION,
+                curve: curve.to_string(),
+                index,
+                cs_hash: hex::encode(&cs_hash[..]),
+                hash: hex::encode(&pubkey.hash()[..]),
+                public_key: hex::encode(&public_key),
+                previous_delta_after: encode(&previous_delta_after)?,
+                delta_after: encode(&pubkey.delta_after)?,
+                s: encode(&pubkey.s)?,
+                s_delta: encode(&pubkey.s_delta)?,
+                r_delta: encode(&pubkey.r_delta)?,
+                transcript: hex::encode(&pubkey.transcript[..]),
+                r: encode(&r)?,
+            });
+            previous_delta_after = pubkey.delta_after;
+        }
+        Ok(proofs)
+    }
+
+    /// Returns the name of the file the proof is exported to.
+    pub fn filename(&self) -> String {
+        format!("contribution.{}.json", self.index)
+    }
+}
+
+/// Returns the hex encoded uncompressed point.
+fn encode<C: CanonicalSerialize>(point: &C) -> Result<String> {
+    let mut bytes = vec![];
+    point.serialize_uncompressed(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+fn read_proofs<E: PairingEngine>(curve: &str, data: &Path) -> anyhow::Result<Vec<ProofOfKnowledge>> {
+    let mmap = unsafe { MmapOptions::new().map(File::open(data)?.file())? };
+    let (cs_hash, contributions) = read_contributions::<E>(&mmap)?;
+    Ok(ProofOfKnowledge::export(curve, cs_hash, &contributions)?)
+}
+
+pub fn export_pok(opts: &ExportPokOpts) -> anyhow::Result<()> {
+    let data = Path::new(&opts.data);
+    let proofs = if opts.is_inner {
+        read_proofs::<Bls12_377>("bls12_377", data)?
+    } else {
+        read_proofs::<BW6_761>("bw6_761", data)?
+    };
+
+    let output = Path::new(&opts.output);
+    permissions::create_dir_all(output)?;
+    for proof in &proofs {
+        permissions::write(output.join(proof.filename()), serde_json::to_vec_pretty(proof)?)?;
+    }
+    info!(
+        "exported the proofs of knowledge of {} contributions to {}",
+        proofs.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phase2::{keypair::Keypair, parameters::verify_transcript};
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use std::ops::Mul;
+
+    #[test]
+    fn test_export_chains_contributions() {
+        let rng = &mut ChaChaRng::seed_from_u64(0);
+        let cs_hash = [7u8; 64];
+        let generator = <Bls12_377 as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let mut delta = generator;
+        let mut contributions = vec![];
+        for _ in 0..2 {
+            let keypair = Keypair::<Bls12_377>::new(delta, cs_hash, &contributions, rng);
+            delta = delta.mul(keypair.private_key.delta);
+            contributions.push(keypair.public_key);
+        }
+        let hashes = verify_transcript(cs_hash, &contributions).unwrap();
+
+        let proofs = ProofOfKnowledge::export("bls12_377", cs_hash, &contributions).unwrap();
+        assert_eq!(2, proofs.len());
+        assert_eq!(proofs[0].previous_delta_after, encode(&generator).unwrap());
+        assert_eq!(proofs[1].previous_delta_after, proofs[0].delta_after);
+        for (proof, hash) in proofs.iter().zip(&hashes) {
+            assert_eq!(proof.hash, hex::encode(&hash[..]));
+            let r = hash_to_curve::<<Bls12_377 as PairingEngine>::G2Affine>(&proof.transcript).0;
+            assert_eq!(proof.r, encode(&r).unwrap());
+        }
+        assert_eq!("contribution.1.json", proofs[1].filename());
+
+        let json = serde_json::to_value(&proofs[1]).unwrap();
+        assert_eq!(POK_FORMAT_VERSION, json["version"]);
+        assert_eq!(proofs[1], serde_json::from_value(json).unwrap());
+    }
+}
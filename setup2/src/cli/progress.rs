@@ -0,0 +1,280 @@
+use super::metrics::record_processed;
+use phase2::chunked_groth16::query_points;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
+
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
+use memmap::MmapOptions;
+use serde::Serialize;
+use std::{
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// How the progress of a command is reported. It is written to stderr, leaving stdout to the result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// A progress bar, drawn if stderr is a terminal.
+    Bar,
+    /// A JSON object per line, for wrappers.
+    Json,
+    /// Nothing.
+    Quiet,
+}
+
+impl ProgressMode {
+    /// Returns the mode selected by `--quiet` and `--progress-json`. The JSON stream
+    /// is meant for wrappers rather than people, so `--quiet` does not silence it.
+    pub fn new(quiet: bool, progress_json: bool) -> Self {
+        match (quiet, progress_json) {
+            (_, true) => ProgressMode::Json,
+            (true, false) => ProgressMode::Quiet,
+            (false, false) => ProgressMode::Bar,
+        }
+    }
+}
+
+/// The progress of a command, as written by `--progress-json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    pub command: &'static str,
+    /// The chunk which was just processed, none for the event starting the command and for batches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<usize>,
+    pub chunks_done: usize,
+    pub total_chunks: usize,
+    pub points_done: u64,
+    pub total_points: u64,
+    pub points_per_sec: f64,
+    /// The estimated time left, once the throughput is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta_secs: Option<u64>,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    chunks_done: usize,
+    points_done: u64,
+    /// The points of the chunks an earlier run processed, which do not count towards the throughput.
+    skipped_points: u64,
+    /// The points of the batches of the chunk being processed, which count towards it once it is done.
+    batch_points: u64,
+}
+
+/// Reports the progress of a command over the chunks it processes, which may
+/// be processed in parallel.
+pub struct Progress {
+    command: &'static str,
+    mode: ProgressMode,
+    bar: ProgressBar,
+    started: Instant,
+    total_chunks: usize,
+    total_points: u64,
+    counts: Mutex<Counts>,
+}
+
+impl Progress {
+    /// Starts reporting the progress of `command` over `total_chunks` chunks holding `total_points` points.
+    pub fn start(command: &'static str, mode: ProgressMode, total_chunks: usize, total_points: u64) -> Self {
+        let bar = match mode {
+            ProgressMode::Bar => {
+                let bar = ProgressBar::new(total_points);
+                bar.set_style(
+                    ProgressStyle::default_bar()
+                        .template("{prefix} [{elapsed_precise}] {wide_bar} {pos}/{len} points {msg}"),
+                );
+                bar.set_prefix(command);
+                bar
+            }
+            ProgressMode::Json | ProgressMode::Quiet => ProgressBar::hidden(),
+        };
+        let progress = Self {
+            command,
+            mode,
+            bar,
+            started: Instant::now(),
+            total_chunks,
+            total_points,
+            counts: Mutex::new(Counts::default()),
+        };
+        progress.report(progress.event(None, &Counts::default(), Duration::default()));
+        progress
+    }
+
+    /// Returns the number of points of all the chunks.
+    pub fn total_points(&self) -> u64 {
+        self.total_points
+    }
+
+    /// Records that the given chunk holding `points` points was processed.
+    pub fn chunk_done(&self, chunk: usize, points: u64) {
+        self.advance(chunk, points, false)
+    }
+
+    /// Records that the given chunk holding `points` points was already processed by an earlier run.
+    pub fn chunk_skipped(&self, chunk: usize, points: u64) {
+        self.advance(chunk, points, true)
+    }
+
+    /// Records that a batch of `points` points of the chunk being processed was processed, for
+    /// commands which process one chunk at a time. The chunk is still recorded with `chunk_done`.
+    pub fn batch_done(&self, points: u64) {
+        self.advance_batch(points, false)
+    }
+
+    /// Records that a batch of `points` points of the chunk being processed was already processed
+    /// by an earlier run, e.g. before the checkpoint a contribution is resumed from.
+    pub fn batch_skipped(&self, points: u64) {
+        self.advance_batch(points, true)
+    }
+
+    /// Finishes the progress bar, leaving it on the terminal, and records what was processed in the metrics.
+    pub fn finish(&self) {
+        self.bar.finish();
+        let counts = self.counts.lock().expect("progress lock poisoned");
+        record_processed(
+            counts.chunks_done,
+            counts.points_done - counts.skipped_points,
+            self.started.elapsed(),
+        );
+    }
+
+    fn advance(&self, chunk: usize, points: u64, skipped: bool) {
+        let event = {
+            let mut counts = self.counts.lock().expect("progress lock poisoned");
+            // The batches of the chunk were counted already
+            let points = points.saturating_sub(std::mem::take(&mut counts.batch_points));
+            counts.chunks_done += 1;
+            counts.points_done += points;
+            if skipped {
+                counts.skipped_points += points;
+            }
+            self.event(Some(chunk), &counts, self.started.elapsed())
+        };
+        self.report(event);
+    }
+
+    fn advance_batch(&self, points: u64, skipped: bool) {
+        let event = {
+            let mut counts = self.counts.lock().expect("progress lock poisoned");
+            counts.batch_points += points;
+            counts.points_done += points;
+            if skipped {
+                counts.skipped_points += points;
+            }
+            self.event(None, &counts, self.started.elapsed())
+        };
+        self.report(event);
+    }
+
+    fn event(&self, chunk: Option<usize>, counts: &Counts, elapsed: Duration) -> ProgressEvent {
+        let processed = counts.points_done - counts.skipped_points;
+        let seconds = elapsed.as_secs_f64();
+        let points_per_sec = if seconds > 0.0 { processed as f64 / seconds } else { 0.0 };
+        let eta_secs = if points_per_sec > 0.0 {
+            let remaining = self.total_points.saturating_sub(counts.points_done);
+            Some((remaining as f64 / points_per_sec).ceil() as u64)
+        } else {
+            None
+        };
+        ProgressEvent {
+            command: self.command,
+            chunk,
+            chunks_done: counts.chunks_done,
+            total_chunks: self.total_chunks,
+            points_done: counts.points_done,
+            total_points: self.total_points,
+            points_per_sec,
+            eta_secs,
+            elapsed_ms: elapsed.as_millis(),
+        }
+    }
+
+    fn report(&self, event: ProgressEvent) {
+        match self.mode {
+            ProgressMode::Bar => {
+                // The throughput and ETA are our own, as the ones of the bar count the skipped chunks
+                let eta = match event.eta_secs {
+                    Some(eta_secs) => HumanDuration(Duration::from_secs(eta_secs)).to_string(),
+                    None => "unknown".to_string(),
+                };
+                self.bar.set_position(event.points_done);
+                self.bar.set_message(format!(
+                    "chunk {}/{}, {:.0} points/s, ETA {}",
+                    event.chunks_done, event.total_chunks, event.points_per_sec, eta
+                ));
+            }
+            ProgressMode::Json => eprintln!(
+                "{}",
+                serde_json::to_string(&event).expect("progress events should serialize to JSON")
+            ),
+            ProgressMode::Quiet => {}
+        }
+    }
+}
+
+/// Returns the number of points of the H and L queries of the parameters in the file at
+/// `path`, which are the ones contributions multiply, without reading the queries.
+pub fn file_points(path: &Path, is_inner: bool) -> anyhow::Result<u64> {
+    let file = fs_err::File::open(path)?;
+    let file = unsafe { MmapOptions::new().map(file.file())? };
+    let points = if is_inner {
+        query_points::<Bls12_377>(&file)?
+    } else {
+        query_points::<BW6_761>(&file)?
+    };
+    Ok(points as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_mode() {
+        assert_eq!(ProgressMode::new(false, false), ProgressMode::Bar);
+        assert_eq!(ProgressMode::new(true, false), ProgressMode::Quiet);
+        assert_eq!(ProgressMode::new(false, true), ProgressMode::Json);
+        assert_eq!(ProgressMode::new(true, true), ProgressMode::Json);
+    }
+
+    #[test]
+    fn test_progress_event() {
+        let progress = Progress::start("contribute", ProgressMode::Quiet, 4, 100);
+        progress.chunk_skipped(0, 20);
+        progress.chunk_done(1, 20);
+        assert_eq!(progress.counts.lock().unwrap().chunks_done, 2);
+
+        // The batches of a chunk count towards it, and a resumed one skips those done before
+        progress.batch_skipped(5);
+        progress.batch_done(10);
+        assert_eq!(progress.counts.lock().unwrap().points_done, 55);
+        progress.chunk_done(2, 20);
+        let counts = progress.counts.lock().unwrap();
+        assert_eq!((counts.chunks_done, counts.points_done), (3, 60));
+        assert_eq!((counts.skipped_points, counts.batch_points), (25, 0));
+        drop(counts);
+
+        // Only the chunk processed by this run counts towards the throughput
+        let counts = Counts {
+            chunks_done: 2,
+            points_done: 40,
+            skipped_points: 20,
+            batch_points: 0,
+        };
+        let event = progress.event(Some(1), &counts, Duration::from_secs(10));
+        assert_eq!(event.points_per_sec, 2.0);
+        assert_eq!(event.eta_secs, Some(30));
+        assert_eq!(event.elapsed_ms, 10_000);
+
+        // The ETA is unknown until a chunk was processed
+        let event = progress.event(None, &Counts::default(), Duration::default());
+        assert_eq!(event.points_per_sec, 0.0);
+        assert_eq!(event.eta_secs, None);
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("chunk").is_none());
+        assert!(json.get("eta_secs").is_none());
+    }
+}
@@ -0,0 +1,332 @@
+//! In-toto provenance statements for the exported parameters, with a SLSA provenance predicate,
+//! so that anyone can check mechanically which phase 1 transcript, ceremony transcript and tool
+//! the proving and verifying keys were produced from.
+//!
+//! Rather than a DSSE envelope, the statement is signed over its canonical JSON encoding by the
+//! Aleo keys of the organizers, like every other signed payload of the ceremony.
+//!
+//! Every exported key also gets an unsigned provenance record next to it, which stays with the key
+//! wherever it is deployed and names the ceremony and the transcript digest it was produced from.
+use super::{organizers::OrganizerSignature, paths::key_provenance_path};
+use setup_utils::calculate_hash;
+
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use thiserror::Error;
+use tracing::info;
+
+/// The name of the file holding the signed provenance statement, stored next to the exported files.
+pub const PROVENANCE_FILENAME: &str = "provenance.json";
+
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v0.1";
+const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v0.2";
+const BUILD_TYPE: &str = "https://github.com/AleoHQ/aleo-setup/setup2/export@v1";
+
+/// The version of the format of the provenance records of the exported keys.
+pub const KEY_PROVENANCE_VERSION: u32 = 1;
+
+/// The name of the digest algorithm, as used in the digest sets of the statement.
+const DIGEST_ALGORITHM: &str = "blake2b";
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyProvenanceOpts {
+    help: bool,
+    #[options(help = "the signed provenance statement", default = "provenance.json")]
+    pub provenance: String,
+    #[options(
+        required,
+        help = "the Aleo address of the organizer who must have signed the statement"
+    )]
+    pub organizer: String,
+    #[options(help = "the directory holding the exported files, defaults to the directory of the statement")]
+    pub dir: Option<String>,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum ProvenanceError {
+    #[error("The statement is not signed by the organizer {0}")]
+    MissingSignature(String),
+    #[error("The signature of {0} over the statement is invalid")]
+    InvalidSignature(String),
+    #[error("The digest of {0} does not match the statement")]
+    SubjectMismatch(String),
+    #[error("The provenance record {0} does not match the statement")]
+    RecordMismatch(String),
+}
+
+/// A file the statement is about, one of the exported keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subject {
+    pub name: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+/// An input the exported keys were produced from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Material {
+    pub uri: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Builder {
+    pub id: String,
+}
+
+/// The SLSA provenance predicate, limited to the fields the ceremony can vouch for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub builder: Builder,
+    pub build_type: String,
+    pub materials: Vec<Material>,
+}
+
+/// An in-toto statement about the exported keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<Subject>,
+    pub predicate_type: String,
+    pub predicate: Provenance,
+}
+
+/// The provenance record of an exported key, written next to it, which traces the key back
+/// to the ceremony transcript it was produced from without changing the key itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyProvenance {
+    pub version: u32,
+    /// The name of the key, as in the subjects of the provenance statement.
+    pub key: String,
+    pub digest: BTreeMap<String, String>,
+    /// The id of the ceremony, see `CeremonyConfig::ceremony_id`.
+    pub ceremony_id: String,
+    /// The file name of the transcript the key was exported from.
+    pub transcript: String,
+    /// The hex encoded transcript digest, which is the hash of the last contribution.
+    pub transcript_digest: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedStatement {
+    pub statement: Statement,
+    pub signatures: Vec<OrganizerSignature>,
+}
+
+/// Returns the digest set of the given bytes.
+fn digest(bytes: &[u8]) -> BTreeMap<String, String> {
+    let mut digest = BTreeMap::new();
+    digest.insert(DIGEST_ALGORITHM.to_string(), hex::encode(&calculate_hash(bytes)[..]));
+    digest
+}
+
+/// Returns the digest set of the file at the given path.
+fn file_digest(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let file = fs_err::File::open(path)?;
+    let bytes = unsafe { MmapOptions::new().map(file.file())? };
+    Ok(digest(&bytes))
+}
+
+impl Subject {
+    /// Returns the subject for the file at the given path, named after its path relative to the ceremony.
+    pub fn file(name: &str, path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            digest: file_digest(path)?,
+        })
+    }
+}
+
+impl Material {
+    /// Returns the material for the file at the given path, named after its path relative to the ceremony.
+    pub fn file(name: &str, path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            uri: name.to_string(),
+            digest: file_digest(path)?,
+        })
+    }
+
+    /// Returns the material for the ceremony transcript, whose digest is the hash of its last contribution.
+    pub fn transcript(name: &str, transcript_digest: &str) -> Self {
+        let mut digest = BTreeMap::new();
+        digest.insert(DIGEST_ALGORITHM.to_string(), transcript_digest.to_string());
+        Self {
+            uri: name.to_string(),
+            digest,
+        }
+    }
+
+    /// Returns the material for this binary, identified by its version and the hash of its executable.
+    pub fn tool() -> anyhow::Result<Self> {
+        Ok(Self {
+            uri: format!("pkg:cargo/{}@{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            digest: file_digest(&std::env::current_exe()?)?,
+        })
+    }
+}
+
+impl KeyProvenance {
+    /// Returns the provenance record of the key at the given path, named after its path relative to the ceremony.
+    pub fn new(
+        key: &str,
+        path: &Path,
+        ceremony_id: &str,
+        transcript: &str,
+        transcript_digest: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            version: KEY_PROVENANCE_VERSION,
+            key: key.to_string(),
+            digest: file_digest(path)?,
+            ceremony_id: ceremony_id.to_string(),
+            transcript: transcript.to_string(),
+            transcript_digest: transcript_digest.to_string(),
+        })
+    }
+
+    /// Checks that the record is about the given subject, and that it names a transcript
+    /// among the materials of the statement.
+    fn matches(&self, subject: &Subject, statement: &Statement) -> bool {
+        let transcript = Material::transcript(&self.transcript, &self.transcript_digest);
+        self.key == subject.name && self.digest == subject.digest && statement.predicate.materials.contains(&transcript)
+    }
+}
+
+impl Statement {
+    pub fn new(subject: Vec<Subject>, materials: Vec<Material>) -> Self {
+        Self {
+            statement_type: STATEMENT_TYPE.to_string(),
+            subject,
+            predicate_type: PREDICATE_TYPE.to_string(),
+            predicate: Provenance {
+                builder: Builder {
+                    id: format!("pkg:cargo/{}", env!("CARGO_PKG_NAME")),
+                },
+                build_type: BUILD_TYPE.to_string(),
+                materials,
+            },
+        }
+    }
+}
+
+impl SignedStatement {
+    /// Returns the statement signed by the organizer with the given Aleo private key.
+    pub fn sign(statement: Statement, organizer_key: &str) -> anyhow::Result<Self> {
+        let signature = OrganizerSignature::sign(&statement, organizer_key)?;
+        Ok(Self {
+            statement,
+            signatures: vec![signature],
+        })
+    }
+
+    /// Checks that the organizer with the given Aleo address signed the statement.
+    pub fn verify(&self, organizer: &str) -> anyhow::Result<()> {
+        let signature = self
+            .signatures
+            .iter()
+            .find(|signature| signature.keyid == organizer)
+            .ok_or_else(|| ProvenanceError::MissingSignature(organizer.to_string()))?;
+        if !signature.verify(&self.statement)? {
+            return Err(ProvenanceError::InvalidSignature(organizer.to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+pub fn verify_provenance(opts: &VerifyProvenanceOpts) -> anyhow::Result<()> {
+    let signed: SignedStatement = serde_json::from_slice(&fs_err::read(&opts.provenance)?)?;
+    signed.verify(&opts.organizer)?;
+    info!("the statement is signed by {}", opts.organizer);
+
+    let dir = match &opts.dir {
+        Some(dir) => Path::new(dir).to_path_buf(),
+        None => Path::new(&opts.provenance)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf(),
+    };
+    for subject in &signed.statement.subject {
+        if file_digest(&dir.join(&subject.name))? != subject.digest {
+            return Err(ProvenanceError::SubjectMismatch(subject.name.clone()).into());
+        }
+        info!("{} matches the statement", subject.name);
+
+        let record_path = key_provenance_path(dir.join(&subject.name));
+        if record_path.exists() {
+            let record: KeyProvenance = serde_json::from_slice(&fs_err::read(&record_path)?)?;
+            if !record.matches(subject, &signed.statement) {
+                return Err(ProvenanceError::RecordMismatch(record_path.display().to_string()).into());
+            }
+            info!(
+                "{} traces back to the transcript {} of the ceremony {}",
+                subject.name, record.transcript_digest, record.ceremony_id
+            );
+        }
+    }
+    for material in &signed.statement.predicate.materials {
+        let digest = material.digest.get(DIGEST_ALGORITHM).map(String::as_str);
+        info!("produced from {} ({})", material.uri, digest.unwrap_or("no digest"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_format() {
+        let statement = Statement::new(
+            vec![Subject {
+                name: "inner.vk".to_string(),
+                digest: digest(b"vk"),
+            }],
+            vec![Material::transcript("challenge", "00")],
+        );
+        let json = serde_json::to_value(&statement).unwrap();
+        assert_eq!(json["_type"], STATEMENT_TYPE);
+        assert_eq!(json["predicateType"], PREDICATE_TYPE);
+        assert_eq!(json["predicate"]["buildType"], BUILD_TYPE);
+        assert_eq!(
+            json["subject"][0]["digest"]["blake2b"],
+            hex::encode(&calculate_hash(b"vk")[..])
+        );
+        assert_eq!(json["predicate"]["materials"][0]["uri"], "challenge");
+    }
+
+    #[test]
+    fn test_key_provenance_matches_statement() {
+        let subject = Subject {
+            name: "inner.vk".to_string(),
+            digest: digest(b"vk"),
+        };
+        let statement = Statement::new(vec![subject.clone()], vec![Material::transcript("challenge", "00")]);
+        let record = KeyProvenance {
+            version: KEY_PROVENANCE_VERSION,
+            key: "inner.vk".to_string(),
+            digest: digest(b"vk"),
+            ceremony_id: "01".to_string(),
+            transcript: "challenge".to_string(),
+            transcript_digest: "00".to_string(),
+        };
+        assert!(record.matches(&subject, &statement));
+
+        let other_transcript = KeyProvenance {
+            transcript_digest: "02".to_string(),
+            ..record.clone()
+        };
+        assert!(!other_transcript.matches(&subject, &statement));
+        let other_key = KeyProvenance {
+            digest: digest(b"pk"),
+            ..record
+        };
+        assert!(!other_key.matches(&subject, &statement));
+    }
+}
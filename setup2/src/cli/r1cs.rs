@@ -0,0 +1,222 @@
+//! Custom circuits in R1CS form, loaded from a JSON file so that the parameters of circuits
+//! other than the inner and outer circuits of Testnet2 can be generated.
+//!
+//! Variable 0 is the constant one, followed by the public and then the private variables.
+//! Coefficients are hex encoded little-endian field elements, as in `to_bytes_le`.
+use snarkvm_fields::Field;
+use snarkvm_r1cs::{ConstraintSynthesizer, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use snarkvm_utilities::FromBytes;
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// The version of the format of R1CS files, bumped on every breaking change.
+pub const R1CS_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum R1csError {
+    #[error("unsupported R1CS format version {0}, expected {}", R1CS_FORMAT_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("constraint {constraint} refers to variable {variable}, but the circuit has {num_variables} variables")]
+    VariableOutOfRange {
+        constraint: usize,
+        variable: usize,
+        num_variables: usize,
+    },
+    #[error("constraint {constraint} has the invalid coefficient {coefficient}")]
+    InvalidCoefficient { constraint: usize, coefficient: String },
+}
+
+/// A constraint `a * b = c`, whose linear combinations are pairs of a variable and its coefficient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct R1csConstraint {
+    pub a: Vec<(usize, String)>,
+    pub b: Vec<(usize, String)>,
+    pub c: Vec<(usize, String)>,
+}
+
+/// The contents of an R1CS file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct R1csFile {
+    pub version: u32,
+    /// The number of public variables, not counting the constant one.
+    pub num_public: usize,
+    pub num_private: usize,
+    pub constraints: Vec<R1csConstraint>,
+}
+
+type Terms<F> = Vec<(usize, F)>;
+
+/// A circuit built from an R1CS file, which synthesizes its constraints without a witness.
+#[derive(Debug, Clone)]
+pub struct R1csCircuit<F: Field> {
+    num_public: usize,
+    num_private: usize,
+    constraints: Vec<(Terms<F>, Terms<F>, Terms<F>)>,
+}
+
+impl<F: Field> R1csCircuit<F> {
+    /// Returns the circuit of the given R1CS file, checking its variables and coefficients.
+    pub fn from_file(file: &R1csFile) -> Result<Self, R1csError> {
+        if file.version != R1CS_FORMAT_VERSION {
+            return Err(R1csError::UnsupportedVersion(file.version));
+        }
+        let num_variables = 1 + file.num_public + file.num_private;
+        let parse = |constraint: usize, terms: &[(usize, String)]| -> Result<Terms<F>, R1csError> {
+            terms
+                .iter()
+                .map(|(variable, coefficient)| {
+                    if *variable >= num_variables {
+                        return Err(R1csError::VariableOutOfRange {
+                            constraint,
+                            variable: *variable,
+                            num_variables,
+                        });
+                    }
+                    let invalid = || R1csError::InvalidCoefficient {
+                        constraint,
+                        coefficient: coefficient.clone(),
+                    };
+                    let bytes = hex::decode(coefficient).map_err(|_| invalid())?;
+                    Ok((*variable, F::from_bytes_le(&bytes).map_err(|_| invalid())?))
+                })
+                .collect()
+        };
+
+        let constraints = file
+            .constraints
+            .iter()
+            .enumerate()
+            .map(|(i, constraint)| {
+                Ok((
+                    parse(i, &constraint.a)?,
+                    parse(i, &constraint.b)?,
+                    parse(i, &constraint.c)?,
+                ))
+            })
+            .collect::<Result<_, R1csError>>()?;
+        Ok(Self {
+            num_public: file.num_public,
+            num_private: file.num_private,
+            constraints,
+        })
+    }
+
+    /// Reads the circuit from the R1CS file at the given path.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file: R1csFile = serde_json::from_slice(&fs_err::read(path)?)?;
+        Ok(Self::from_file(&file)?)
+    }
+}
+
+fn combination<F: Field>(
+    mut lc: LinearCombination<F>,
+    terms: &[(usize, F)],
+    variables: &[Variable],
+) -> LinearCombination<F> {
+    for (variable, coefficient) in terms {
+        lc = lc + (*coefficient, variables[*variable]);
+    }
+    lc
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for R1csCircuit<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(&self, cs: &mut CS) -> std::result::Result<(), SynthesisError> {
+        let mut variables = Vec::with_capacity(1 + self.num_public + self.num_private);
+        variables.push(CS::one());
+        for i in 0..self.num_public {
+            variables.push(cs.alloc_input(
+                || format!("public {}", i),
+                || Err::<F, _>(SynthesisError::AssignmentMissing),
+            )?);
+        }
+        for i in 0..self.num_private {
+            variables.push(cs.alloc(
+                || format!("private {}", i),
+                || Err::<F, _>(SynthesisError::AssignmentMissing),
+            )?);
+        }
+
+        for (i, (a, b, c)) in self.constraints.iter().enumerate() {
+            cs.enforce(
+                || format!("constraint {}", i),
+                |lc| combination(lc, a, &variables),
+                |lc| combination(lc, b, &variables),
+                |lc| combination(lc, c, &variables),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use snarkvm_curves::bls12_377::Fr;
+    use snarkvm_r1cs::ConstraintCounter;
+    use snarkvm_utilities::ToBytes;
+
+    fn coefficient(value: u8) -> String {
+        hex::encode(Fr::from(value).to_bytes_le().unwrap())
+    }
+
+    // x * x = y, with the square y public
+    fn square() -> R1csFile {
+        R1csFile {
+            version: R1CS_FORMAT_VERSION,
+            num_public: 1,
+            num_private: 1,
+            constraints: vec![R1csConstraint {
+                a: vec![(2, coefficient(1))],
+                b: vec![(2, coefficient(1))],
+                c: vec![(1, coefficient(1))],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_r1cs_circuit_constraints() {
+        let file: R1csFile = serde_json::from_slice(&serde_json::to_vec(&square()).unwrap()).unwrap();
+        let circuit = R1csCircuit::<Fr>::from_file(&file).unwrap();
+
+        let mut counter = ConstraintCounter {
+            num_public_variables: 0,
+            num_private_variables: 0,
+            num_constraints: 0,
+        };
+        circuit.generate_constraints(&mut counter).unwrap();
+        assert_eq!(1, counter.num_public_variables);
+        assert_eq!(1, counter.num_private_variables);
+        assert_eq!(1, counter.num_constraints);
+    }
+
+    #[test]
+    fn test_r1cs_file_is_checked() {
+        let mut file = square();
+        file.constraints[0].c = vec![(3, coefficient(1))];
+        assert!(matches!(
+            R1csCircuit::<Fr>::from_file(&file),
+            Err(R1csError::VariableOutOfRange {
+                constraint: 0,
+                variable: 3,
+                num_variables: 3
+            })
+        ));
+
+        let mut file = square();
+        file.constraints[0].a = vec![(2, "zz".to_string())];
+        assert!(matches!(
+            R1csCircuit::<Fr>::from_file(&file),
+            Err(R1csError::InvalidCoefficient { constraint: 0, .. })
+        ));
+
+        let mut file = square();
+        file.version = R1CS_FORMAT_VERSION + 1;
+        assert!(matches!(
+            R1csCircuit::<Fr>::from_file(&file),
+            Err(R1csError::UnsupportedVersion(_))
+        ));
+    }
+}
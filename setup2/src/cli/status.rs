@@ -0,0 +1,327 @@
+use super::{
+    ceremony::{CeremonyManifest, CEREMONY_FORMAT_VERSION},
+    paths::{chunk_path, query_path, receipt_path},
+};
+use phase2::chunked_groth16::read_contributions;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use fs_err::File;
+use gumdrop::Options;
+use memmap::MmapOptions;
+use serde::Serialize;
+use std::{fmt, path::Path};
+
+#[derive(Debug, Options, Clone)]
+pub struct StatusOpts {
+    help: bool,
+    #[options(help = "the ceremony directory to inspect", default = ".")]
+    pub dir: String,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+/// The state of a transcript, or of a chunk of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileState {
+    /// Nobody has contributed yet.
+    Generated,
+    /// The last contribution has not been verified.
+    Contributed,
+    /// The last contribution has been verified.
+    Verified,
+    /// The chunk has fewer contributions than the other chunks.
+    Stale,
+}
+
+/// What was read from a transcript, or from a chunk of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRecord {
+    pub file: String,
+    /// The hex encoded `cs_hash`.
+    pub cs_hash: String,
+    /// The hex encoded hashes of the contributions, in order.
+    pub contributions: Vec<String>,
+    /// The hex encoded hash recorded in the verification receipt, if any.
+    pub receipt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileStatus {
+    pub file: String,
+    pub contributions: usize,
+    /// The hex encoded hash of the last contribution.
+    pub last_contribution: Option<String>,
+    pub state: FileState,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CeremonyStatus {
+    pub dir: String,
+    pub version: u32,
+    pub files: Vec<FileStatus>,
+    pub inconsistencies: Vec<String>,
+    pub next: String,
+}
+
+impl fmt::Display for CeremonyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ceremony {} (format version {})", self.dir, self.version)?;
+        for status in &self.files {
+            write!(f, "  {}: {:?}", status.file, status.state)?;
+            if let Some(hash) = &status.last_contribution {
+                write!(
+                    f,
+                    ", {} contributions, last {}",
+                    status.contributions,
+                    &hash[..std::cmp::min(16, hash.len())]
+                )?;
+            }
+            writeln!(f)?;
+        }
+        for inconsistency in &self.inconsistencies {
+            writeln!(f, "  inconsistent: {}", inconsistency)?;
+        }
+        write!(f, "next: {}", self.next)
+    }
+}
+
+pub fn status(opts: &StatusOpts) -> anyhow::Result<()> {
+    let status = ceremony_status(Path::new(&opts.dir))?;
+    if opts.json {
+        println!("{}", serde_json::to_string(&status)?);
+    } else {
+        println!("{}", status);
+    }
+    Ok(())
+}
+
+/// Inspects the ceremony directory `dir`.
+pub fn ceremony_status(dir: &Path) -> anyhow::Result<CeremonyStatus> {
+    let mut status = CeremonyStatus {
+        dir: dir.display().to_string(),
+        version: CeremonyManifest::version_in(dir)?,
+        files: vec![],
+        inconsistencies: vec![],
+        next: String::new(),
+    };
+    if status.version == 0 {
+        status.next = "run `new` to start a ceremony, or `migrate` to adopt a legacy ceremony directory".to_string();
+        return Ok(status);
+    }
+    if status.version != CEREMONY_FORMAT_VERSION {
+        status.next = format!(
+            "run `migrate`, or use a binary which supports format version {}",
+            status.version
+        );
+        return Ok(status);
+    }
+
+    let config = CeremonyManifest::load(dir)?.config;
+    let transcript = dir.join(&config.transcript);
+    if !transcript.exists() {
+        status
+            .inconsistencies
+            .push(format!("the transcript {} is missing", transcript.display()));
+        status.next = "restore the transcript, or run `new` in an empty directory".to_string();
+        return Ok(status);
+    }
+
+    // A chunked ceremony is contributed to chunk by chunk, leaving the transcript as generated
+    let num_chunks = (0..).take_while(|i| chunk_path(&transcript, *i).exists()).count();
+    let query = query_path(&transcript);
+    let files = if num_chunks > 0 {
+        (0..num_chunks).map(|i| chunk_path(&transcript, i)).collect()
+    } else {
+        vec![transcript.clone()]
+    };
+
+    let mut records = Vec::with_capacity(files.len());
+    for file in files {
+        records.push(if config.is_inner {
+            read_record::<Bls12_377>(&file)?
+        } else {
+            read_record::<BW6_761>(&file)?
+        });
+    }
+    let query_cs_hash = match (num_chunks, query.exists()) {
+        (0, true) => {
+            status
+                .inconsistencies
+                .push(format!("the query parameters {} have no chunks", query.display()));
+            None
+        }
+        (0, false) => None,
+        (_, false) => {
+            status.inconsistencies.push(format!(
+                "the chunks cannot be verified without the query parameters {}",
+                query.display()
+            ));
+            None
+        }
+        (_, true) => Some(if config.is_inner {
+            read_record::<Bls12_377>(&query)?.cs_hash
+        } else {
+            read_record::<BW6_761>(&query)?.cs_hash
+        }),
+    };
+
+    let (files, inconsistencies) = assess(&records, query_cs_hash.as_deref());
+    status.files = files;
+    status.inconsistencies.extend(inconsistencies);
+    status.next = next_step(&status, &transcript, num_chunks);
+    Ok(status)
+}
+
+fn read_record<E: PairingEngine>(file: &Path) -> anyhow::Result<FileRecord> {
+    let mmap = unsafe { MmapOptions::new().map(File::open(file)?.file())? };
+    let (cs_hash, contributions) = read_contributions::<E>(&mmap)?;
+    let receipt = match fs_err::read_to_string(receipt_path(file)) {
+        Ok(receipt) => Some(receipt.trim().to_string()),
+        Err(_) => None,
+    };
+    Ok(FileRecord {
+        cs_hash: hex::encode(&cs_hash[..]),
+        contributions: contributions.iter().map(|c| hex::encode(&c.hash()[..])).collect(),
+        receipt,
+        file: file.display().to_string(),
+    })
+}
+
+/// Returns the state of every file, and the inconsistencies between them.
+///
+/// Chunks are compared against the chunk with the most contributions: a chunk with
+/// fewer is stale, and a chunk whose contributions differ cannot be combined at all.
+pub fn assess(records: &[FileRecord], query_cs_hash: Option<&str>) -> (Vec<FileStatus>, Vec<String>) {
+    let mut inconsistencies = vec![];
+    let latest = match records.iter().max_by_key(|record| record.contributions.len()) {
+        Some(latest) => latest,
+        None => return (vec![], inconsistencies),
+    };
+
+    let files = records
+        .iter()
+        .map(|record| {
+            if let Some(cs_hash) = query_cs_hash {
+                if record.cs_hash != cs_hash {
+                    inconsistencies.push(format!("{} belongs to different query parameters", record.file));
+                }
+            }
+            if !latest.contributions.starts_with(&record.contributions) {
+                inconsistencies.push(format!(
+                    "{} has different contributions than {}",
+                    record.file, latest.file
+                ));
+            }
+
+            let last_contribution = record.contributions.last().cloned();
+            let state = if record.contributions.len() < latest.contributions.len() {
+                FileState::Stale
+            } else if last_contribution.is_none() {
+                FileState::Generated
+            } else if record.receipt.is_some() && record.receipt == last_contribution {
+                FileState::Verified
+            } else {
+                FileState::Contributed
+            };
+            FileStatus {
+                file: record.file.clone(),
+                contributions: record.contributions.len(),
+                last_contribution,
+                state,
+            }
+        })
+        .collect();
+
+    (files, inconsistencies)
+}
+
+/// Returns the command which should run next.
+fn next_step(status: &CeremonyStatus, transcript: &Path, num_chunks: usize) -> String {
+    let chunked = if num_chunks > 0 {
+        format!(" --chunks {}", num_chunks)
+    } else {
+        String::new()
+    };
+    let has = |state| status.files.iter().any(|file| file.state == state);
+
+    if !status.inconsistencies.is_empty() {
+        "resolve the inconsistencies before continuing".to_string()
+    } else if has(FileState::Stale) {
+        format!(
+            "restore the chunks from the last verified round and run `contribute --data {}{}` again",
+            transcript.display(),
+            chunked
+        )
+    } else if has(FileState::Contributed) {
+        let query = if num_chunks > 0 {
+            format!(" --query {}", query_path(transcript).display())
+        } else {
+            String::new()
+        };
+        format!(
+            "run `verify --before <previous round> --after {}{}{}`",
+            transcript.display(),
+            chunked,
+            query
+        )
+    } else if has(FileState::Verified) {
+        format!(
+            "run `contribute --data {}{}` for the next participant, or `beacon` to finalize",
+            transcript.display(),
+            chunked
+        )
+    } else {
+        format!("run `contribute --data {}{}`", transcript.display(), chunked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(file: &str, contributions: &[&str], receipt: Option<&str>) -> FileRecord {
+        FileRecord {
+            file: file.to_string(),
+            cs_hash: "aa".to_string(),
+            contributions: contributions.iter().map(|c| c.to_string()).collect(),
+            receipt: receipt.map(str::to_string),
+        }
+    }
+
+    fn states(records: &[FileRecord]) -> Vec<FileState> {
+        assess(records, Some("aa")).0.iter().map(|file| file.state).collect()
+    }
+
+    #[test]
+    fn test_assess_states() {
+        assert_eq!(states(&[record("c.0", &[], None)]), vec![FileState::Generated]);
+        assert_eq!(
+            states(&[record("c.0", &["01"], Some("01")), record("c.1", &["01"], None)]),
+            vec![FileState::Verified, FileState::Contributed]
+        );
+        // a receipt for an earlier contribution does not count
+        assert_eq!(
+            states(&[record("c.0", &["01", "02"], Some("01"))]),
+            vec![FileState::Contributed]
+        );
+        assert_eq!(
+            states(&[record("c.0", &["01"], Some("01")), record("c.1", &["01", "02"], None)]),
+            vec![FileState::Stale, FileState::Contributed]
+        );
+    }
+
+    #[test]
+    fn test_assess_inconsistencies() {
+        let (_, inconsistencies) = assess(&[record("c.0", &["01"], None), record("c.1", &["02"], None)], None);
+        assert_eq!(inconsistencies.len(), 1);
+
+        let (_, inconsistencies) = assess(&[record("c.0", &["01"], None)], Some("bb"));
+        assert_eq!(
+            inconsistencies,
+            vec!["c.0 belongs to different query parameters".to_string()]
+        );
+    }
+}
@@ -0,0 +1,417 @@
+//! Deterministic test vectors: a tiny ceremony, from a fresh phase 1 accumulator to a chunked
+//! phase 2 contribution, whose randomness is all derived from a fixed seed. The same seed always
+//! gives the same files, so that downstream verifiers and implementations in other languages can
+//! check their results against these canonical artifacts.
+//!
+//! The randomness is derived from the seed, so the parameters are insecure by construction and
+//! must never be used outside of tests.
+use super::{
+    ceremony::{HashAlgorithm, HASH_ALGORITHM},
+    constraints::ceremony_size,
+    contribute::{contribute, ContributeOpts},
+    input_format::InputFormat,
+    new::{curve_from_str, new, CircuitKind, CurveKind, NewOpts},
+    paths::{chunk_path, query_path},
+    permissions,
+    pipeline::prepare,
+    r1cs::{R1csCircuit, R1csConstraint, R1csFile, R1CS_FORMAT_VERSION},
+    verify::{verify, VerifyOpts},
+};
+use phase1::{Phase1, Phase1Parameters, ProvingSystem};
+use setup_utils::{blank_hash, calculate_hash, get_rng, CheckForCorrectness, UseCompression};
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+use snarkvm_fields::Field;
+use snarkvm_utilities::ToBytes;
+
+use gumdrop::Options;
+use rand::{CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::info;
+
+/// The name of the file listing the test vectors, stored in the output directory.
+pub const TEST_VECTORS_FILENAME: &str = "test-vectors.json";
+
+/// The seed the test vectors are generated with unless another one is given.
+pub const DEFAULT_SEED: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// The numbers of chunks the test vectors can be split into.
+const CHUNKS: RangeInclusive<usize> = 1..=4;
+
+/// The batch size of the phase 1 accumulator, which is larger than any of its queries.
+const PHASE1_BATCH_SIZE: usize = 256;
+
+#[derive(Debug, Options, Clone)]
+pub struct TestVectorsOpts {
+    pub(super) help: bool,
+    #[options(help = "the directory to write the test vectors to", default = "test-vectors")]
+    pub output: String,
+    #[options(help = "the hex encoded 32 byte seed all the randomness is derived from")]
+    pub seed: Option<String>,
+    #[options(
+        help = "the elliptic curve to use",
+        default = "bls12_377",
+        parse(try_from_str = "curve_from_str")
+    )]
+    pub curve_type: CurveKind,
+    #[options(
+        help = "the number of powers (in powers of 2) of the phase 1 accumulator",
+        default = "4"
+    )]
+    pub power: u32,
+    #[options(help = "the number of chunks to split the parameters into, 1 to 4", default = "2")]
+    pub chunks: usize,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum TestVectorError {
+    #[error("The seed must be 32 hex encoded bytes")]
+    InvalidSeed,
+    #[error("The test vectors can be split into {} to {} chunks, not {0}", CHUNKS.start(), CHUNKS.end())]
+    InvalidChunks(usize),
+    #[error("The test circuit needs at least 2^{0} powers of phase 1")]
+    PowerTooSmall(u32),
+    #[error("{} already exists", .0.display())]
+    OutputExists(PathBuf),
+}
+
+/// A file of the test vectors with its hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectorFile {
+    /// The file name, relative to the output directory.
+    pub name: String,
+    /// What the file holds, e.g. the chunk of a response.
+    pub description: String,
+    /// The hex encoded hash of the file.
+    pub hash: String,
+}
+
+/// The parameters the test vectors were generated with, and the files they consist of in the
+/// order they were written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectors {
+    pub seed: String,
+    pub curve: CurveKind,
+    pub power: u32,
+    pub phase2_size: usize,
+    pub chunk_size: usize,
+    pub num_chunks: usize,
+    pub hash_algorithm: HashAlgorithm,
+    pub files: Vec<TestVectorFile>,
+}
+
+impl TestVectors {
+    fn add(&mut self, dir: &Path, name: &str, description: &str) -> anyhow::Result<()> {
+        let hash = hex::encode(&calculate_hash(&fs_err::read(dir.join(name))?)[..]);
+        self.files.push(TestVectorFile {
+            name: name.to_string(),
+            description: description.to_string(),
+            hash,
+        });
+        Ok(())
+    }
+}
+
+pub fn test_vectors(opts: &TestVectorsOpts) -> anyhow::Result<()> {
+    let seed = opts.seed.as_deref().unwrap_or(DEFAULT_SEED);
+    let seed_bytes = hex::decode(seed).map_err(|_| TestVectorError::InvalidSeed)?;
+    if seed_bytes.len() != 32 {
+        return Err(TestVectorError::InvalidSeed.into());
+    }
+    if !CHUNKS.contains(&opts.chunks) {
+        return Err(TestVectorError::InvalidChunks(opts.chunks).into());
+    }
+    let dir = Path::new(&opts.output);
+    if dir.exists() {
+        return Err(TestVectorError::OutputExists(dir.to_path_buf()).into());
+    }
+    permissions::create_dir_all(dir)?;
+
+    let rng = &mut get_rng(&seed_bytes);
+    let vectors = match opts.curve_type {
+        CurveKind::Bls12_377 => generate::<Bls12_377, _>(opts, dir, seed, rng)?,
+        CurveKind::BW6 => generate::<BW6_761, _>(opts, dir, seed, rng)?,
+    };
+    permissions::write(dir.join(TEST_VECTORS_FILENAME), serde_json::to_vec_pretty(&vectors)?)?;
+    info!("wrote {} test vectors to {}", vectors.files.len(), dir.display());
+    Ok(())
+}
+
+/// Returns the coefficient `value` as hex encoded little-endian bytes, as in R1CS files.
+fn coefficient<F: Field>(value: u64) -> anyhow::Result<String> {
+    Ok(hex::encode(F::from(value).to_bytes_le()?))
+}
+
+/// Returns the circuit the test vectors are generated for, `x^3 + x + 5 = y` with `y` public,
+/// using the private variables `x`, `x^2` and `x^3`.
+fn test_circuit<F: Field>() -> anyhow::Result<R1csFile> {
+    let one = || coefficient::<F>(1);
+    let (y, x, x_squared, x_cubed) = (1, 2, 3, 4);
+    Ok(R1csFile {
+        version: R1CS_FORMAT_VERSION,
+        num_public: 1,
+        num_private: 3,
+        constraints: vec![
+            R1csConstraint {
+                a: vec![(x, one()?)],
+                b: vec![(x, one()?)],
+                c: vec![(x_squared, one()?)],
+            },
+            R1csConstraint {
+                a: vec![(x_squared, one()?)],
+                b: vec![(x, one()?)],
+                c: vec![(x_cubed, one()?)],
+            },
+            R1csConstraint {
+                a: vec![(x_cubed, one()?), (x, one()?), (0, coefficient::<F>(5)?)],
+                b: vec![(0, one()?)],
+                c: vec![(y, one()?)],
+            },
+        ],
+    })
+}
+
+fn generate<E: PairingEngine, R: Rng + CryptoRng>(
+    opts: &TestVectorsOpts,
+    dir: &Path,
+    seed: &str,
+    rng: &mut R,
+) -> anyhow::Result<TestVectors> {
+    let path = |name: &str| dir.join(name).display().to_string();
+
+    // The circuit is written out, so that other implementations can synthesize it too
+    let circuit_file = test_circuit::<E::Fr>()?;
+    permissions::write(path("circuit.json"), serde_json::to_vec_pretty(&circuit_file)?)?;
+    let circuit = R1csCircuit::<E::Fr>::from_file(&circuit_file)?;
+
+    // The H query has an element less than the domain and the L query one per private variable,
+    // so the chunk size which splits the longer of them into the requested number of chunks is known
+    let phase2_size = ceremony_size(&circuit)?;
+    if phase2_size > 2usize.pow(opts.power) {
+        return Err(TestVectorError::PowerTooSmall(phase2_size.trailing_zeros()).into());
+    }
+    let longest_query = std::cmp::max(phase2_size - 1, circuit_file.num_private);
+    let chunk_size = (longest_query + opts.chunks - 1) / opts.chunks;
+    let num_chunks = (longest_query + chunk_size - 1) / chunk_size;
+
+    let mut vectors = TestVectors {
+        seed: seed.to_string(),
+        curve: opts.curve_type,
+        power: opts.power,
+        phase2_size,
+        chunk_size,
+        num_chunks,
+        hash_algorithm: HASH_ALGORITHM,
+        files: vec![],
+    };
+    vectors.add(dir, "circuit.json", "the R1CS circuit of x^3 + x + 5 = y")?;
+
+    // Phase 1: a fresh accumulator, a single contribution to it, and its preparation for phase 2
+    let power = opts.power as usize;
+    let parameters = Phase1Parameters::<E>::new_full(ProvingSystem::Groth16, power, PHASE1_BATCH_SIZE);
+    let mut challenge = vec![0; parameters.accumulator_size];
+    challenge[..64].copy_from_slice(blank_hash().as_slice());
+    Phase1::initialization(&mut challenge, UseCompression::No, &parameters)?;
+    permissions::write(path("phase1.challenge"), &challenge)?;
+    vectors.add(dir, "phase1.challenge", "the initial phase 1 accumulator, uncompressed")?;
+
+    let challenge_hash = calculate_hash(&challenge);
+    let (public_key, private_key) = Phase1::key_generation(rng, challenge_hash.as_ref())?;
+    let mut response = vec![0; parameters.contribution_size];
+    response[..64].copy_from_slice(challenge_hash.as_slice());
+    Phase1::computation(
+        &challenge,
+        &mut response,
+        UseCompression::No,
+        UseCompression::Yes,
+        CheckForCorrectness::No,
+        &private_key,
+        &parameters,
+    )?;
+    public_key.write(&mut response, UseCompression::Yes, &parameters)?;
+    permissions::write(path("phase1.response"), &response)?;
+    vectors.add(
+        dir,
+        "phase1.response",
+        "the contribution to the phase 1 accumulator, compressed",
+    )?;
+
+    prepare::<E>(
+        &path("phase1.response"),
+        &path("phase1"),
+        power,
+        PHASE1_BATCH_SIZE,
+        opts.power,
+        UseCompression::No,
+    )?;
+    vectors.add(dir, "phase1", "the Lagrange coefficients phase 2 starts from")?;
+
+    // Phase 2: the initial transcript with its query parameters and chunks
+    new(&NewOpts {
+        help: false,
+        phase1: path("phase1"),
+        phase1_size: opts.power,
+        output: path("challenge"),
+        compressed_input: false,
+        input_format: InputFormat::Uncompressed,
+        curve_type: opts.curve_type,
+        circuit: CircuitKind::Outer,
+        circuit_file: Some(path("circuit.json")),
+        chunk_size: Some(chunk_size),
+        vdf_iterations: None,
+        max_memory_gb: None,
+        quiet: true,
+        progress_json: false,
+        json: false,
+    })?;
+    vectors.add(dir, "challenge", "the initial phase 2 transcript")?;
+    vectors.add(
+        dir,
+        "challenge.query",
+        "the query parameters of the initial phase 2 transcript",
+    )?;
+    for i in 0..num_chunks {
+        vectors.add(
+            dir,
+            &format!("challenge.{}", i),
+            &format!("chunk {} of the initial phase 2 transcript", i),
+        )?;
+    }
+
+    // A contribution to the full transcript, and one to its chunks, each verified
+    let is_inner = opts.curve_type == CurveKind::Bls12_377;
+    fs_err::copy(path("challenge"), path("response"))?;
+    for i in 0..num_chunks {
+        fs_err::copy(chunk_path(path("challenge"), i), chunk_path(path("response"), i))?;
+    }
+    for chunks in [None, Some(num_chunks)] {
+        let contribute_opts = ContributeOpts {
+            help: false,
+            data: path("response"),
+            batch: PHASE1_BATCH_SIZE,
+            max_memory_gb: None,
+            beacon_hash: String::new(),
+            vdf_proof: None,
+            beacon_sources: None,
+            beacon_record: String::new(),
+            attestation_key: None,
+            #[cfg(feature = "cloud")]
+            output: None,
+            is_inner,
+            chunks,
+            input_format: InputFormat::Full,
+            check_input: CheckForCorrectness::No,
+            quiet: true,
+            progress_json: false,
+            json: false,
+        };
+        contribute(&contribute_opts, rng)?;
+        verify(&VerifyOpts {
+            help: false,
+            before: path("challenge"),
+            after: path("response"),
+            batch: PHASE1_BATCH_SIZE,
+            max_memory_gb: None,
+            is_inner,
+            chunks,
+            input_format: InputFormat::Full,
+            query: chunks.map(|_| query_path(path("challenge")).display().to_string()),
+            check_input: CheckForCorrectness::Full,
+            report: None,
+            quiet: true,
+            progress_json: false,
+            json: false,
+        })?;
+    }
+    vectors.add(dir, "response", "the contribution to the full phase 2 transcript")?;
+    for i in 0..num_chunks {
+        vectors.add(
+            dir,
+            &format!("response.{}", i),
+            &format!("the contribution to chunk {}", i),
+        )?;
+    }
+
+    // The receipts of the verifications hold the time they were written at, so they are not test vectors
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(output: &Path, chunks: usize) -> TestVectorsOpts {
+        TestVectorsOpts {
+            help: false,
+            output: output.display().to_string(),
+            seed: None,
+            curve_type: CurveKind::Bls12_377,
+            power: 4,
+            chunks,
+            json: false,
+        }
+    }
+
+    fn read_vectors(dir: &Path) -> TestVectors {
+        serde_json::from_slice(&fs_err::read(dir.join(TEST_VECTORS_FILENAME)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_vectors_are_deterministic() {
+        let dir = std::env::temp_dir().join(format!("setup2-test-vectors-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+        let (first, second) = (dir.join("first"), dir.join("second"));
+        fs_err::create_dir_all(&dir).unwrap();
+
+        test_vectors(&opts(&first, 3)).unwrap();
+        test_vectors(&opts(&second, 3)).unwrap();
+        let vectors = read_vectors(&first);
+        assert_eq!(vectors, read_vectors(&second));
+        assert_eq!(3, vectors.num_chunks);
+        for file in &vectors.files {
+            let contents = fs_err::read(first.join(&file.name)).unwrap();
+            assert_eq!(file.hash, hex::encode(&calculate_hash(&contents)[..]));
+        }
+
+        // The output is never overwritten
+        assert!(test_vectors(&opts(&first, 3)).is_err());
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_vectors_single_chunk() {
+        // The whole pipeline runs on a single chunk holding all of both queries
+        let dir = std::env::temp_dir().join(format!("setup2-test-vectors-single-{}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&dir);
+
+        test_vectors(&opts(&dir, 1)).unwrap();
+        let vectors = read_vectors(&dir);
+        assert_eq!(1, vectors.num_chunks);
+        assert_eq!(std::cmp::max(vectors.phase2_size - 1, 3), vectors.chunk_size);
+        assert!(dir.join("response.0").exists());
+        assert!(!dir.join("response.1").exists());
+
+        fs_err::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_vectors_options_are_checked() {
+        let dir = std::env::temp_dir().join(format!("setup2-test-vectors-opts-{}", std::process::id()));
+        assert!(test_vectors(&opts(&dir, 0)).is_err());
+        assert!(test_vectors(&opts(&dir, 5)).is_err());
+
+        let mut seeded = opts(&dir, 2);
+        seeded.seed = Some("00".to_string());
+        assert!(test_vectors(&seeded).is_err());
+        assert!(!dir.exists());
+    }
+}
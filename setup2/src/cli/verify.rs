@@ -1,51 +1,257 @@
-use phase2::chunked_groth16::verify as chunked_verify;
-use setup_utils::Result;
+use super::{
+    ceremony::check_phase1,
+    chunks::ChunkError,
+    determinism::{VerdictReport, VerifiedInput},
+    input_format::{check_from_str, detect_chunks, input_format_from_str, InputFormat},
+    memory::{cap_batch_size, verify_bytes_per_element},
+    new::{file_hash, write_hash},
+    paths::{artifact_path, chunk_path, query_path, receipt_path},
+    permissions,
+    progress::{file_points, Progress, ProgressMode},
+};
+use phase2::{chunked_groth16::verify_with_progress as chunked_verify, parameters::MPCParameters};
+use setup_utils::{CancellationToken, CheckForCorrectness};
 
-use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761};
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
 
-use fs_err::OpenOptions;
+use fs_err::File;
 use gumdrop::Options;
-use memmap::MmapOptions;
+use memmap::{MmapMut, MmapOptions};
+use std::path::Path;
 
 // Options for the Contribute command
 #[derive(Debug, Options, Clone)]
 pub struct VerifyOpts {
-    help: bool,
+    pub(super) help: bool,
     #[options(help = "a previous contribution", default = "challenge")]
     pub before: String,
     #[options(help = "the current contribution", default = "challenge")]
     pub after: String,
     #[options(help = "the batches which can be loaded in memory", default = "50000")]
     pub batch: usize,
+    #[options(help = "the maximum memory (in GB) to use, which reduces the batch size if needed")]
+    pub max_memory_gb: Option<f64>,
     #[options(help = "setup the inner or the outer circuit?")]
     pub is_inner: bool,
+    #[options(help = "verify this many chunks of the contributions instead of the full parameters")]
+    pub chunks: Option<usize>,
+    #[options(help = "the query parameters the chunks are verified against, required with --chunks")]
+    pub query: Option<String>,
+    #[options(
+        help = "whether the contributions are `full` or `chunked`, detected from the chunks next to them if `auto`",
+        default = "auto",
+        parse(try_from_str = "input_format_from_str")
+    )]
+    pub input_format: InputFormat,
+    #[options(
+        help = "how the elements of the contributions are checked as they are read, `full`, `non-zero`, `in-group` or `no`",
+        default = "full",
+        parse(try_from_str = "check_from_str")
+    )]
+    pub check_input: CheckForCorrectness,
+    #[options(help = "write the verdict, the contribution digests and the build of this binary to this file")]
+    pub report: Option<String>,
+    #[options(help = "do not show the progress bar")]
+    pub quiet: bool,
+    #[options(help = "write the progress to stderr as a JSON object per line")]
+    pub progress_json: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+/// The paths of the contributions to verify.
+struct Contributions<'a> {
+    opts: &'a VerifyOpts,
+    before: &'a Path,
+    after: &'a Path,
 }
 
-pub fn verify(opts: &VerifyOpts) -> Result<()> {
-    let before = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(&opts.before)
-        .expect("could not read the previous participant's MPC transcript file");
-    let mut before = unsafe {
-        MmapOptions::new()
-            .map_mut(before.file())
-            .expect("unable to create a memory map for input")
+pub fn verify(opts: &VerifyOpts) -> anyhow::Result<()> {
+    #[cfg(feature = "cloud")]
+    {
+        use super::cloud::{is_object_url, verify_in_bucket};
+        let urls = [Some(&opts.before), Some(&opts.after), opts.query.as_ref(), opts.report.as_ref()];
+        if urls.iter().flatten().any(|src| is_object_url(src)) {
+            return verify_in_bucket(opts);
+        }
+    }
+    let (before, after) = (artifact_path(&opts.before)?, artifact_path(&opts.after)?);
+    let mut query = opts.query.as_ref().map(artifact_path).transpose()?;
+    // Detected chunks are verified against the query parameters next to the previous contribution,
+    // while chunks given with --chunks still need the query parameters given with --query
+    let chunks = match opts.chunks {
+        Some(0) => return Err(ChunkError::ZeroChunks.into()),
+        Some(chunks) => Some(chunks),
+        None => {
+            let chunks = detect_chunks(&after, opts.input_format)?;
+            if chunks.is_some() && query.is_none() {
+                query = Some(query_path(&before)).filter(|query| query.exists());
+            }
+            chunks
+        }
     };
-    let after = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .open(&opts.after)
-        .expect("could not read the previous participant's MPC transcript file");
-    let mut after = unsafe {
-        MmapOptions::new()
-            .map_mut(after.file())
-            .expect("unable to create a memory map for input")
+    let contributions = Contributions {
+        opts,
+        before: &before,
+        after: &after,
     };
-    if opts.is_inner {
-        chunked_verify::<Bls12_377>(&mut before, &mut after, opts.batch)?;
+
+    // Contributions to artifacts of another phase 1 transcript may well be valid on their own
+    let files = match (chunks, &query) {
+        (Some(chunks), Some(query)) => std::iter::once(query.clone())
+            .chain((0..chunks).flat_map(|i| vec![chunk_path(&before, i), chunk_path(&after, i)]))
+            .collect(),
+        (None, None) => vec![before.clone(), after.clone()],
+        // Passing only one of --chunks and --query is reported below
+        _ => vec![],
+    };
+    check_phase1(before.parent().unwrap_or_else(|| Path::new("")), &files, opts.is_inner)?;
+
+    // The inputs are hashed before they are verified, so that the report names what was verified
+    let (inputs, result) = match (chunks, &query) {
+        (Some(chunks), Some(query)) => {
+            let inputs = report_inputs(opts, || chunk_inputs(&contributions, query, chunks))?;
+            let result = if opts.is_inner {
+                verify_chunks::<Bls12_377>(&contributions, query, chunks)
+            } else {
+                verify_chunks::<BW6_761>(&contributions, query, chunks)
+            };
+            (inputs, result)
+        }
+        (Some(_), None) => return Err(ChunkError::MissingQuery.into()),
+        (None, Some(_)) => return Err(ChunkError::MissingChunks.into()),
+        (None, None) => (
+            report_inputs(opts, || full_inputs(&contributions))?,
+            verify_full(&contributions),
+        ),
+    };
+
+    // The report is written even if the transcript is invalid, as the verdicts of other builds must agree
+    if let (Some(report), Some(inputs)) = (&opts.report, inputs) {
+        let report_json = serde_json::to_vec_pretty(&VerdictReport::new(opts.is_inner, inputs, &result)?)?;
+        permissions::write(report, report_json)?;
+    }
+    result.map(|_| ())
+}
+
+/// Returns the inputs to record in the verdict report, if one was requested.
+fn report_inputs(
+    opts: &VerifyOpts,
+    inputs: impl FnOnce() -> anyhow::Result<Vec<VerifiedInput>>,
+) -> anyhow::Result<Option<Vec<VerifiedInput>>> {
+    opts.report.as_ref().map(|_| inputs()).transpose()
+}
+
+fn full_inputs(contributions: &Contributions) -> anyhow::Result<Vec<VerifiedInput>> {
+    Ok(vec![
+        VerifiedInput::file("before", contributions.before)?,
+        VerifiedInput::file("after", contributions.after)?,
+    ])
+}
+
+fn chunk_inputs(contributions: &Contributions, query: &Path, chunks: usize) -> anyhow::Result<Vec<VerifiedInput>> {
+    let mut inputs = vec![VerifiedInput::file("query", query)?];
+    for i in 0..chunks {
+        let (before, after) = (chunk_path(contributions.before, i), chunk_path(contributions.after, i));
+        inputs.push(VerifiedInput::file(&format!("before.{}", i), &before)?);
+        inputs.push(VerifiedInput::file(&format!("after.{}", i), &after)?);
+    }
+    Ok(inputs)
+}
+
+fn verify_full(contributions: &Contributions) -> anyhow::Result<Vec<[u8; 64]>> {
+    let opts = contributions.opts;
+    // The transcripts themselves are memory mapped, so only the batches count towards the cap
+    let bytes_per_element = if opts.is_inner {
+        verify_bytes_per_element::<Bls12_377>()
+    } else {
+        verify_bytes_per_element::<BW6_761>()
+    };
+    let batch = cap_batch_size(opts.batch, opts.max_memory_gb, bytes_per_element)?;
+    let progress = Progress::start(
+        "verify",
+        ProgressMode::new(opts.quiet, opts.progress_json),
+        1,
+        file_points(contributions.before, opts.is_inner)?,
+    );
+
+    // The verifier does not modify the contributions, so they are mapped copy-on-write from
+    // files opened read-only, which also lets it verify files it may not write to
+    let mut before = map_copy(contributions.before)?;
+    let mut after = map_copy(contributions.after)?;
+    let (check, cancellation) = (opts.check_input, CancellationToken::new());
+    let batch_done = |points: usize| progress.batch_done(points as u64);
+    let hashes = if opts.is_inner {
+        chunked_verify::<Bls12_377>(&mut before, &mut after, batch, check, &cancellation, &batch_done)?
     } else {
-        chunked_verify::<BW6_761>(&mut before, &mut after, opts.batch)?;
+        chunked_verify::<BW6_761>(&mut before, &mut after, batch, check, &cancellation, &batch_done)?
+    };
+    progress.chunk_done(0, progress.total_points());
+    progress.finish();
+    write_receipt(contributions.after, &hashes)?;
+    Ok(hashes)
+}
+
+/// Verifies the chunks of the contribution against the query parameters. The chunks
+/// are memory mapped and deserialized one pair at a time, so only a single pair of
+/// them is held in memory at once, but that pair is held whole.
+fn verify_chunks<E: PairingEngine>(
+    contributions: &Contributions,
+    query: &Path,
+    chunks: usize,
+) -> anyhow::Result<Vec<[u8; 64]>> {
+    let opts = contributions.opts;
+    let points = (0..chunks)
+        .map(|i| file_points(&chunk_path(contributions.before, i), opts.is_inner))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let progress = Progress::start(
+        "verify",
+        ProgressMode::new(opts.quiet, opts.progress_json),
+        chunks,
+        points.iter().sum(),
+    );
+
+    let read = |path: &Path| -> setup_utils::Result<MPCParameters<E>> {
+        let file = File::open(path)?;
+        let mmap = unsafe { MmapOptions::new().map(file.file())? };
+        MPCParameters::read_from_buffer(&mmap, opts.check_input)
+    };
+    let query = read(query)?;
+    let pairs = (0..chunks).map(|i| -> setup_utils::Result<_> {
+        // The pairs are verified in order, so the previous pair was verified once the next one is read
+        if i > 0 {
+            progress.chunk_done(i - 1, points[i - 1]);
+        }
+        Ok((
+            read(&chunk_path(contributions.before, i))?,
+            read(&chunk_path(contributions.after, i))?,
+        ))
+    });
+    let hashes = query.verify_chunked(pairs)?;
+    if let Some(last) = chunks.checked_sub(1) {
+        progress.chunk_done(last, points[last]);
+    }
+    progress.finish();
+    for i in 0..chunks {
+        // The hash next to the chunk is the one of the verified contribution, whatever it was downloaded with
+        let after = chunk_path(contributions.after, i);
+        write_hash(&after, &file_hash(&after)?)?;
+        write_receipt(&after, &hashes)?;
+    }
+    Ok(hashes)
+}
+
+/// Maps the file at `path` copy-on-write, so that it is read lazily and never written to.
+fn map_copy(path: &Path) -> std::io::Result<MmapMut> {
+    let file = File::open(path)?;
+    unsafe { MmapOptions::new().map_copy(file.file()) }
+}
+
+/// Records that the last of the verified contributions to `transcript` is valid, for `status`.
+fn write_receipt(transcript: &Path, hashes: &[[u8; 64]]) -> anyhow::Result<()> {
+    if let Some(hash) = hashes.last() {
+        permissions::write(receipt_path(transcript), hex::encode(&hash[..]))?;
     }
     Ok(())
 }
@@ -0,0 +1,317 @@
+//! The verification of every chunk of a round at once, with the chunks
+//! verified in parallel by a pool of workers.
+use super::{
+    aggregate::{parse_challenge_list, read_params},
+    ceremony::check_phase1,
+    paths::receipt_path,
+    permissions,
+    progress::{file_points, Progress, ProgressMode},
+};
+use phase2::parameters::MPCParameters;
+
+use snarkvm_curves::{bls12_377::Bls12_377, bw6_761::BW6_761, PairingEngine};
+
+use gumdrop::Options;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+use tracing::{info, warn};
+
+#[derive(Debug, Options, Clone)]
+pub struct VerifyRoundOpts {
+    help: bool,
+    #[options(free, help = "the responses of the chunks, in the order of the challenge list")]
+    pub responses: Vec<String>,
+    #[options(
+        required,
+        help = "a file listing the challenges of the chunks, one path per line, in order"
+    )]
+    pub challenge_list: String,
+    #[options(
+        help = "the query parameters the chunks are verified against",
+        default = "challenge.query"
+    )]
+    pub query: String,
+    #[options(help = "the number of chunks verified at once, each holding a pair of chunks in memory")]
+    pub workers: Option<usize>,
+    #[options(help = "write the verdict of every chunk to this file")]
+    pub report: Option<String>,
+    #[options(help = "setup the inner or the outer circuit?")]
+    pub is_inner: bool,
+    #[options(help = "do not show the progress bar")]
+    pub quiet: bool,
+    #[options(help = "write the progress to stderr as a JSON object per line")]
+    pub progress_json: bool,
+
+    #[options(help = "print the result as a JSON object")]
+    pub json: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum VerifyRoundError {
+    #[error("the challenge list {0} lists no chunks")]
+    EmptyChallengeList(String),
+    #[error("the challenge list has {challenges} chunks, but {responses} responses were given")]
+    ChunkCountMismatch { challenges: usize, responses: usize },
+    #[error("the number of workers must be positive")]
+    ZeroWorkers,
+    #[error("the round failed verification: {0}")]
+    Failed(String),
+}
+
+/// The outcome of the verification of a single chunk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkVerdict {
+    pub chunk: usize,
+    pub challenge: String,
+    pub response: String,
+    /// The hex encoded hash of the last contribution to the chunk, if the chunk is valid.
+    pub contribution_hash: Option<String>,
+    /// The hex encoded hashes of all the contributions to the chunk, in order, if the chunk is valid.
+    #[serde(default)]
+    pub contribution_hashes: Vec<String>,
+    /// Why the chunk is invalid, if it is.
+    pub error: Option<String>,
+}
+
+/// The verdicts of all the chunks of a round, and of the round as a whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundReport {
+    pub passed: bool,
+    pub chunks: Vec<ChunkVerdict>,
+    /// Why the chunks, each valid on their own, do not make up a valid round.
+    pub errors: Vec<String>,
+}
+
+impl RoundReport {
+    ///
+    /// Returns the report of the given verdicts, which is only passed if every
+    /// chunk is valid and received the same contributions as the others, in
+    /// the same order.
+    ///
+    pub fn new(chunks: Vec<ChunkVerdict>, mut errors: Vec<String>) -> Self {
+        let mut hashes = chunks
+            .iter()
+            .filter(|chunk| chunk.error.is_none())
+            .map(|chunk| &chunk.contribution_hashes);
+        if let Some(first) = hashes.next() {
+            if hashes.any(|hash| hash != first) {
+                errors.push("the chunks did not all receive the same contributions".to_string());
+            }
+        }
+        let passed = errors.is_empty() && chunks.iter().all(|chunk| chunk.error.is_none());
+        Self { passed, chunks, errors }
+    }
+
+    /// Returns the error describing why the round failed, if it did.
+    fn failure(&self) -> Option<VerifyRoundError> {
+        if self.passed {
+            return None;
+        }
+        let mut failures: Vec<String> = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| chunk.error.as_ref().map(|error| format!("chunk {}: {}", chunk.chunk, error)))
+            .collect();
+        failures.extend(self.errors.iter().cloned());
+        Some(VerifyRoundError::Failed(failures.join("; ")))
+    }
+}
+
+/// A chunk which was verified, with what the round checks need of it.
+struct VerifiedChunk {
+    verdict: ChunkVerdict,
+    h_query_len: usize,
+    l_query_len: usize,
+}
+
+fn verify_chunk<E: PairingEngine>(
+    query: &MPCParameters<E>,
+    chunk: usize,
+    challenge: &str,
+    response: &str,
+) -> VerifiedChunk {
+    let mut verdict = ChunkVerdict {
+        chunk,
+        challenge: challenge.to_string(),
+        response: response.to_string(),
+        contribution_hash: None,
+        contribution_hashes: vec![],
+        error: None,
+    };
+    let result = (|| -> anyhow::Result<_> {
+        let before = read_params::<E>(challenge)?;
+        let after = read_params::<E>(response)?;
+        let hashes = query.verify_chunk(&before, &after)?;
+        Ok((hashes, after.params.h_query.len(), after.params.l_query.len()))
+    })();
+
+    match result {
+        Ok((hashes, h_query_len, l_query_len)) => {
+            verdict.contribution_hashes = hashes.iter().map(|hash| hex::encode(&hash[..])).collect();
+            verdict.contribution_hash = verdict.contribution_hashes.last().cloned();
+            VerifiedChunk {
+                verdict,
+                h_query_len,
+                l_query_len,
+            }
+        }
+        Err(error) => {
+            warn!("chunk {} ({}) is invalid: {:#}", chunk, response, error);
+            verdict.error = Some(format!("{:#}", error));
+            VerifiedChunk {
+                verdict,
+                h_query_len: 0,
+                l_query_len: 0,
+            }
+        }
+    }
+}
+
+fn verify_round_params<E: PairingEngine>(opts: &VerifyRoundOpts, challenges: &[String]) -> anyhow::Result<RoundReport> {
+    let query = read_params::<E>(&opts.query)?;
+    let points = opts
+        .responses
+        .iter()
+        .map(|response| file_points(Path::new(response), opts.is_inner))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let progress = Progress::start(
+        "verify-round",
+        ProgressMode::new(opts.quiet, opts.progress_json),
+        challenges.len(),
+        points.iter().sum(),
+    );
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(workers) = opts.workers {
+        pool = pool.num_threads(workers);
+    }
+    let verified: Vec<VerifiedChunk> = pool.build()?.install(|| {
+        challenges
+            .par_iter()
+            .zip(opts.responses.par_iter())
+            .enumerate()
+            .map(|(i, (challenge, response))| {
+                let verified = verify_chunk(&query, i, challenge, response);
+                progress.chunk_done(i, points[i]);
+                verified
+            })
+            .collect()
+    });
+    progress.finish();
+
+    // The chunks together must make up the full structure of the circuit, which
+    // can only be told once each of them is valid
+    let mut errors = vec![];
+    if verified.iter().all(|chunk| chunk.verdict.error.is_none()) {
+        let h_query_len = verified.iter().map(|chunk| chunk.h_query_len).sum();
+        let l_query_len = verified.iter().map(|chunk| chunk.l_query_len).sum();
+        if let Err(error) = query.ensure_complete_queries(h_query_len, l_query_len) {
+            errors.push(format!("the chunks do not cover the query parameters: {}", error));
+        }
+    }
+    Ok(RoundReport::new(
+        verified.into_iter().map(|chunk| chunk.verdict).collect(),
+        errors,
+    ))
+}
+
+pub fn verify_round(opts: &VerifyRoundOpts) -> anyhow::Result<()> {
+    let challenges = parse_challenge_list(&fs_err::read_to_string(&opts.challenge_list)?);
+    if challenges.is_empty() {
+        return Err(VerifyRoundError::EmptyChallengeList(opts.challenge_list.clone()).into());
+    }
+    if challenges.len() != opts.responses.len() {
+        return Err(VerifyRoundError::ChunkCountMismatch {
+            challenges: challenges.len(),
+            responses: opts.responses.len(),
+        }
+        .into());
+    }
+    if opts.workers == Some(0) {
+        return Err(VerifyRoundError::ZeroWorkers.into());
+    }
+    // The chunks must all have been generated for the ceremony of the query parameters
+    let query = Path::new(&opts.query);
+    let files: Vec<&str> = std::iter::once(opts.query.as_str())
+        .chain(challenges.iter().map(String::as_str))
+        .chain(opts.responses.iter().map(String::as_str))
+        .collect();
+    check_phase1(query.parent().unwrap_or_else(|| Path::new("")), &files, opts.is_inner)?;
+
+    let report = if opts.is_inner {
+        verify_round_params::<Bls12_377>(opts, &challenges)?
+    } else {
+        verify_round_params::<BW6_761>(opts, &challenges)?
+    };
+
+    // The report is written even if the round is invalid, as it tells which chunks to redo
+    if let Some(path) = &opts.report {
+        permissions::write(path, serde_json::to_vec_pretty(&report)?)?;
+    }
+    if let Some(failure) = report.failure() {
+        return Err(failure.into());
+    }
+
+    // Record that the responses are valid, for `status`
+    for (response, verdict) in opts.responses.iter().zip(&report.chunks) {
+        if let Some(hash) = &verdict.contribution_hash {
+            permissions::write(receipt_path(Path::new(response)), hash)?;
+        }
+    }
+    info!("all {} chunks of the round are valid", report.chunks.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdict(chunk: usize, contribution_hashes: &[&str], error: Option<&str>) -> ChunkVerdict {
+        ChunkVerdict {
+            chunk,
+            challenge: format!("challenge.{}", chunk),
+            response: format!("response.{}", chunk),
+            contribution_hash: contribution_hashes.last().map(|hash| hash.to_string()),
+            contribution_hashes: contribution_hashes.iter().map(|hash| hash.to_string()).collect(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_round_report() {
+        let report = RoundReport::new(vec![verdict(0, &["aa"], None), verdict(1, &["aa"], None)], vec![]);
+        assert!(report.passed);
+        assert!(report.failure().is_none());
+
+        // a single invalid chunk fails the round, and is named in the error
+        let report = RoundReport::new(
+            vec![verdict(0, &["aa"], None), verdict(1, &[], Some("H_query ratio check failed"))],
+            vec![],
+        );
+        assert!(!report.passed);
+        let failure = report.failure().unwrap().to_string();
+        assert!(failure.contains("chunk 1: H_query ratio check failed"));
+
+        // chunks which are valid on their own, but received different contributions
+        let report = RoundReport::new(vec![verdict(0, &["aa"], None), verdict(1, &["bb"], None)], vec![]);
+        assert!(!report.passed);
+        assert_eq!(report.errors.len(), 1);
+
+        // chunks whose last contributions match, but which received different contributions before
+        let report = RoundReport::new(
+            vec![verdict(0, &["aa", "cc"], None), verdict(1, &["bb", "cc"], None)],
+            vec![],
+        );
+        assert!(!report.passed);
+        assert_eq!(report.errors.len(), 1);
+
+        // or which received an extra contribution
+        let report = RoundReport::new(vec![verdict(0, &["cc"], None), verdict(1, &["aa", "cc"], None)], vec![]);
+        assert!(!report.passed);
+
+        let report = RoundReport::new(vec![verdict(0, &["aa"], None)], vec!["incomplete".to_string()]);
+        assert!(!report.passed);
+    }
+}
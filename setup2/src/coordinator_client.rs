@@ -0,0 +1,251 @@
+//! A client of the ceremony coordinator, for programs which take part in a ceremony
+//! without scripting the requests themselves.
+//!
+//! Every request is authorized like the contributor authorizes its requests, with
+//! `setup1_shared::authorization`.
+use setup1_shared::{
+    authorization::get_authorization_value,
+    ids::{ChunkId, ContributionId},
+    protocol::{
+        self,
+        v1::{ErrorCode, ErrorResponse},
+        ProtocolVersion,
+        IDEMPOTENCY_KEY_HEADER,
+        PROTOCOL_VERSION_HEADER,
+    },
+    structures::{ContributorStatus, LockResponse, PublicSettings},
+};
+use setup_utils::calculate_hash;
+use snarkvm_dpc::{parameters::testnet2::Testnet2Parameters, PrivateKey};
+
+use rand::{CryptoRng, Rng};
+use serde::Serialize;
+use std::io::Write;
+use thiserror::Error;
+use url::Url;
+
+/// The version of the contributor announced when joining the queue.
+const CLIENT_VERSION: (u8, u8, u8) = (0, 1, 0);
+
+#[derive(Debug, Error)]
+pub enum CoordinatorClientError {
+    #[error("the coordinator rejected the request with {0:?}: {1}")]
+    Rejected(ErrorCode, String),
+}
+
+/// A client of the coordinator at `server_url`, authenticated as the owner of a private key.
+pub struct CoordinatorClient {
+    server_url: Url,
+    private_key: PrivateKey<Testnet2Parameters>,
+    protocol_version: ProtocolVersion,
+    client: reqwest::Client,
+}
+
+impl CoordinatorClient {
+    /// Returns a client of the coordinator speaking the given protocol version.
+    pub fn new(
+        server_url: Url,
+        private_key: PrivateKey<Testnet2Parameters>,
+        protocol_version: ProtocolVersion,
+    ) -> anyhow::Result<Self> {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(PROTOCOL_VERSION_HEADER, protocol_version.to_string().parse()?);
+        Ok(Self {
+            server_url,
+            private_key,
+            protocol_version,
+            client: reqwest::Client::builder().default_headers(headers).build()?,
+        })
+    }
+
+    /// Returns a client of the coordinator speaking the highest protocol version both support,
+    /// as advertised in its public settings.
+    pub async fn connect(server_url: Url, private_key: PrivateKey<Testnet2Parameters>) -> anyhow::Result<Self> {
+        let settings = public_settings(&server_url).await?;
+        let protocol_version = protocol::negotiate(&settings.protocol_versions)?;
+        Self::new(server_url, private_key, protocol_version)
+    }
+
+    /// Returns the protocol version the client speaks.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// Asks to join the queue of contributors, with the confirmation key of the participant.
+    /// Returns `true` if the participant joined.
+    pub async fn join_queue<T: Serialize, R: Rng + CryptoRng>(
+        &self,
+        confirmation_key: &T,
+        rng: &mut R,
+    ) -> anyhow::Result<bool> {
+        let (major, minor, patch) = CLIENT_VERSION;
+        let join_queue_path = format!("/v1/queue/contributor/join/{}/{}/{}", major, minor, patch);
+        let bytes = serde_json::to_vec(confirmation_key)?;
+        let authorization = self.authorization("POST", &join_queue_path, rng)?;
+        let response = self
+            .client
+            .post(self.server_url.join(&join_queue_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
+            .send()
+            .await?;
+
+        Ok(serde_json::from_slice(&error_for_code(response).await?.bytes().await?)?)
+    }
+
+    /// Locks the next chunk to contribute to.
+    pub async fn lock_chunk<R: Rng + CryptoRng>(&self, rng: &mut R) -> anyhow::Result<LockResponse> {
+        let lock_path = "/v1/contributor/try_lock";
+        let authorization = self.authorization("POST", lock_path, rng)?;
+        let response = self
+            .client
+            .post(self.server_url.join(lock_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_LENGTH, 0)
+            .send()
+            .await?;
+
+        Ok(serde_json::from_slice(&error_for_code(response).await?.bytes().await?)?)
+    }
+
+    /// Downloads the challenge of a locked chunk to `out`, streamed rather than held in memory.
+    pub async fn download_challenge<W: Write, R: Rng + CryptoRng>(
+        &self,
+        chunk_id: ChunkId,
+        contribution_id: ContributionId,
+        out: &mut W,
+        rng: &mut R,
+    ) -> anyhow::Result<()> {
+        let download_path = format!("/v1/download/challenge/{}/{}", chunk_id, contribution_id);
+        let authorization = self.authorization("GET", &download_path, rng)?;
+        let response = self
+            .client
+            .get(self.server_url.join(&download_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .send()
+            .await?;
+
+        let mut response = error_for_code(response).await?;
+        while let Some(chunk) = response.chunk().await? {
+            out.write_all(&chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Uploads the response to the challenge of a locked chunk. Retries of the same upload
+    /// carry the same idempotency key, so the coordinator accepts the response only once.
+    /// Returns the idempotency key, which the contribution is then tried with.
+    pub async fn upload_response<R: Rng + CryptoRng>(
+        &self,
+        chunk_id: ChunkId,
+        contribution_id: ContributionId,
+        contents: Vec<u8>,
+        rng: &mut R,
+    ) -> anyhow::Result<String> {
+        let upload_path = format!("/v1/upload/response/{}/{}", chunk_id, contribution_id);
+        let idempotency_key = protocol::idempotency_key(chunk_id, contribution_id, &calculate_hash(&contents));
+        let authorization = self.authorization("POST", &upload_path, rng)?;
+        let response = self
+            .client
+            .post(self.server_url.join(&upload_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(IDEMPOTENCY_KEY_HEADER, &idempotency_key)
+            .header(http::header::CONTENT_TYPE, "application/octet-stream")
+            .header(http::header::CONTENT_LENGTH, contents.len())
+            .body(contents)
+            .send()
+            .await?;
+
+        error_for_code(response).await?;
+        Ok(idempotency_key)
+    }
+
+    /// Asks the coordinator to take the uploaded response as the contribution to a chunk,
+    /// which releases the lock on it, with the idempotency key of the upload.
+    pub async fn try_contribute<R: Rng + CryptoRng>(
+        &self,
+        chunk_id: ChunkId,
+        idempotency_key: &str,
+        rng: &mut R,
+    ) -> anyhow::Result<()> {
+        let contribute_path = format!("/v1/contributor/try_contribute/{}", chunk_id);
+        let bytes = serde_json::to_vec(&serde_json::json!({}))?;
+        let authorization = self.authorization("POST", &contribute_path, rng)?;
+        let response = self
+            .client
+            .post(self.server_url.join(&contribute_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
+            .header(http::header::CONTENT_LENGTH, bytes.len())
+            .body(bytes)
+            .send()
+            .await?;
+
+        error_for_code(response).await?;
+        Ok(())
+    }
+
+    /// Returns the status of the participant in the current round.
+    pub async fn contributor_status<R: Rng + CryptoRng>(&self, rng: &mut R) -> anyhow::Result<ContributorStatus> {
+        let status_path = "/v1/contributor/status";
+        let authorization = self.authorization("POST", status_path, rng)?;
+        let response = self
+            .client
+            .post(self.server_url.join(status_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_LENGTH, 0)
+            .send()
+            .await?;
+
+        Ok(serde_json::from_slice(&error_for_code(response).await?.bytes().await?)?)
+    }
+
+    /// Signals that the participant is still online, which keeps its locks.
+    pub async fn heartbeat<R: Rng + CryptoRng>(&self, rng: &mut R) -> anyhow::Result<()> {
+        let heartbeat_path = "/v1/contributor/heartbeat";
+        let authorization = self.authorization("POST", heartbeat_path, rng)?;
+        let response = self
+            .client
+            .post(self.server_url.join(heartbeat_path)?.as_str())
+            .header(http::header::AUTHORIZATION, authorization)
+            .header(http::header::CONTENT_LENGTH, 0)
+            .send()
+            .await?;
+
+        error_for_code(response).await?;
+        Ok(())
+    }
+
+    fn authorization<R: Rng + CryptoRng>(&self, method: &str, path: &str, rng: &mut R) -> anyhow::Result<String> {
+        get_authorization_value(&self.private_key, method, path, rng)
+    }
+}
+
+/// Returns the public settings of the coordinator at `server_url`, which need no authorization.
+pub async fn public_settings(server_url: &Url) -> anyhow::Result<PublicSettings> {
+    let response = reqwest::Client::new()
+        .post(server_url.join("/v1/coordinator/settings")?.as_str())
+        .header(http::header::CONTENT_LENGTH, 0)
+        .send()
+        .await?;
+    let bytes = error_for_code(response).await?.bytes().await?;
+    PublicSettings::decode(&bytes).map_err(|e| anyhow::anyhow!("Error decoding coordinator PublicSettings: {}", e))
+}
+
+/// Turns the error responses of the coordinator into a [CoordinatorClientError::Rejected],
+/// which carries the category of the error for the caller to act on.
+async fn error_for_code(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(response);
+    }
+
+    // Coordinators which predate error codes only respond with a status code.
+    let body = response.bytes().await.unwrap_or_default();
+    let error = match serde_json::from_slice::<ErrorResponse>(&body) {
+        Ok(response) => CoordinatorClientError::Rejected(response.code, response.message),
+        Err(_) => CoordinatorClientError::Rejected(ErrorCode::from_status(status.as_u16()), status.to_string()),
+    };
+    Err(error.into())
+}
@@ -0,0 +1,4 @@
+//! The parts of Aleo Setup II which programs other than the `setup2` binary can use.
+
+#[cfg(feature = "coordinator-client")]
+pub mod coordinator_client;
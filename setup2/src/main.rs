@@ -5,7 +5,7 @@ cfg_if! {
         mod cli;
         use cli::*;
 
-        use setup_utils::{beacon_randomness, from_slice, get_rng, user_system_randomness};
+        use setup_utils::{get_rng, user_system_randomness};
 
         use gumdrop::Options;
         use std::{process, time::Instant};
@@ -21,36 +21,64 @@ cfg_if! {
                 .init();
             let opts = SNARKOpts::parse_args_default_or_exit();
 
-            let command = opts.clone().command.unwrap_or_else(|| {
+            match ArtifactPermissions::new(opts.file_mode, opts.dir_mode, opts.group.as_deref()) {
+                Ok(permissions) => permissions.install(),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    process::exit(ExitCode::Config.code())
+                }
+            }
+
+            let command = opts.command.unwrap_or_else(|| {
                 eprintln!("No command was provided.");
                 eprintln!("{}", SNARKOpts::usage());
-                process::exit(2)
+                process::exit(ExitCode::Config.code())
             });
 
             let now = Instant::now();
-            let res = match command {
-                Command::New(ref opt) => new(&opt).unwrap(),
+            let res: anyhow::Result<()> = match command {
+                Command::New(ref opt) => new(&opt),
+                Command::Constraints(ref opt) => constraints(&opt),
                 Command::Contribute(ref opt) => {
                     // contribute to the randomness
                     let mut rng = get_rng(&user_system_randomness());
-                    contribute(&opt, &mut rng).unwrap()
+                    contribute(&opt, &mut rng)
                 }
-                Command::Beacon(ref opt) => {
-                    // use the beacon's randomness
-                    let beacon_hash = hex::decode(&opt.beacon_hash).expect("could not hex decode beacon hash");
-                    let mut rng = get_rng(&beacon_randomness(from_slice(&beacon_hash)));
-                    contribute(&opt, &mut rng).unwrap()
+                #[cfg(feature = "coordinator-client")]
+                Command::ContributeLoop(ref opt) => {
+                    let mut rng = get_rng(&user_system_randomness());
+                    contribute_loop(&opt, &mut rng)
                 }
-                Command::Verify(ref opt) => verify(&opt).unwrap(),
+                Command::Beacon(ref opt) => beacon(&opt),
+                Command::BeaconVdf(ref opt) => beacon_vdf(&opt),
+                Command::Verify(ref opt) => verify(&opt),
+                Command::VerifyRound(ref opt) => verify_round(&opt),
+                Command::VerifyAttestation(ref opt) => verify_attestation(&opt),
+                Command::NewKeystore(ref opt) => new_keystore(&opt),
+                Command::CompareVerdicts(ref opt) => compare_verdicts(&opt),
+                Command::Migrate(ref opt) => migrate(&opt),
+                Command::CompatCheck(ref opt) => compat_check(&opt),
+                Command::Status(ref opt) => status(&opt),
+                Command::CheckHashes(ref opt) => check_hashes(&opt),
+                Command::ApproveFinalization(ref opt) => approve_finalization(&opt),
+                Command::SignCheckpoint(ref opt) => sign_checkpoint(&opt),
+                Command::VerifyTranscript(ref opt) => verify_transcript(&opt),
+                Command::VerifyProvenance(ref opt) => verify_provenance(&opt),
+                Command::Aggregate(ref opt) => aggregate(&opt),
+                Command::ExportPok(ref opt) => export_pok(&opt),
+                Command::RunPipeline(ref opt) => run_pipeline(&opt),
+                Command::GitMode(ref opt) => git_mode(&opt),
+                Command::TestVectors(ref opt) => test_vectors(&opt),
+                Command::VerifyGolden(ref opt) => verify_golden(&opt),
             };
 
-            let new_now = Instant::now();
-            println!(
-                "Executing {:?} took: {:?}. Result {:?}",
-                opts,
-                new_now.duration_since(now),
-                res,
-            );
+            let output = CommandOutput::new(command.name(), &res, now.elapsed());
+            output.print(command.json());
+            // The metrics are a side channel, so failing to export them does not fail the command
+            if let Err(error) = export_metrics(&output, opts.metrics_file.as_deref(), opts.pushgateway.as_deref()) {
+                eprintln!("Exporting the metrics of {} failed: {:#}", command.name(), error);
+            }
+            process::exit(output.exit_code);
         }
     }
 }